@@ -0,0 +1,87 @@
+//!
+//! Programmatic generators for synthetic EBML documents used by the benchmarks.
+//!
+//! Every generator writes through [`TagWriter`] using the `TestSpec` from the integration tests, so the output is always readable by a `TagIterator<_, TestSpec>`.
+//!
+
+use std::io::Cursor;
+
+use ebml_iterable::specs::Master;
+use ebml_iterable::TagWriter;
+
+use super::test_spec::TestSpec;
+
+///
+/// A document with `count` small root level tags, each holding a few children.
+///
+pub fn flat_document(count: usize) -> Vec<u8> {
+    let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+    for i in 0..count {
+        writer.write(&TestSpec::Root(Master::Full(vec![
+            TestSpec::Int(i as u64),
+            TestSpec::String(format!("tag {i}")),
+        ]))).expect("fixture should be writable");
+    }
+    writer.into_inner().expect("fixture should flush").into_inner()
+}
+
+///
+/// A segment with `clusters` clusters, each holding `blocks` simple blocks of `block_size` bytes.
+///
+pub fn cluster_document(clusters: usize, blocks: usize, block_size: usize) -> Vec<u8> {
+    let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+    writer.write(&TestSpec::Segment(Master::Start)).expect("fixture should be writable");
+    for cluster in 0..clusters {
+        writer.write(&TestSpec::Cluster(Master::Start)).expect("fixture should be writable");
+        writer.write(&TestSpec::Count(cluster as u64)).expect("fixture should be writable");
+        for block in 0..blocks {
+            writer.write(&TestSpec::SimpleBlock(vec![block as u8; block_size])).expect("fixture should be writable");
+        }
+        writer.write(&TestSpec::Cluster(Master::End)).expect("fixture should be writable");
+    }
+    writer.write(&TestSpec::Segment(Master::End)).expect("fixture should be writable");
+    writer.into_inner().expect("fixture should flush").into_inner()
+}
+
+///
+/// A [`cluster_document`] with `junk_len` bytes of pseudo-random data inserted right after the segment header.
+///
+pub fn corrupted_cluster_document(junk_len: usize) -> Vec<u8> {
+    let mut data = cluster_document(10, 10, 256);
+    let (_, size_len) = ebml_iterable::tools::read_vint(&data[4..]).expect("fixture should be valid").expect("fixture should be valid");
+
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let junk = (0..junk_len).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u8
+    });
+    data.splice((4 + size_len)..(4 + size_len), junk);
+    data
+}
+
+///
+/// The tags of a single buffered root tag holding `parents` parent tags, each holding `children` children.
+///
+pub fn nested_tags(parents: usize, children: usize) -> Vec<TestSpec> {
+    let mut tags = vec![TestSpec::Root(Master::Start)];
+    for _ in 0..parents {
+        tags.push(TestSpec::Parent(Master::Start));
+        tags.extend((0..children).map(|i| TestSpec::Child(i as u64)));
+        tags.push(TestSpec::Parent(Master::End));
+    }
+    tags.push(TestSpec::Root(Master::End));
+    tags
+}
+
+///
+/// A buffer of `count` vints of increasing size.
+///
+pub fn vints(count: usize) -> Vec<u8> {
+    use ebml_iterable::tools::Vint;
+
+    (0..count as u64)
+        .flat_map(|i| (i * 0x1_0001).as_vint().expect("fixture vint should be encodable"))
+        .collect()
+}
@@ -0,0 +1,135 @@
+//!
+//! Benchmarks for the reader and writer hot paths.
+//!
+//! Run with `cargo bench`.  When run as part of `cargo test --benches`, Criterion only runs each benchmark once as a smoke test.
+//!
+
+#[path = "../tests/test_spec.rs"]
+mod test_spec;
+mod fixtures;
+
+use std::hint::black_box;
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+#[cfg(feature = "mmap")]
+use ebml_iterable::iterator::TagSliceIterator;
+use ebml_iterable::specs::Master;
+use ebml_iterable::tools::read_vint;
+use ebml_iterable::{TagIterator, TagWriter};
+
+use test_spec::TestSpec;
+
+fn vint_decode(c: &mut Criterion) {
+    let vints = fixtures::vints(10_000);
+    let mut group = c.benchmark_group("vint");
+    group.throughput(Throughput::Bytes(vints.len() as u64));
+    group.bench_function("decode", |b| b.iter(|| {
+        let mut position = 0;
+        while let Ok(Some((value, length))) = read_vint(&vints[position..]) {
+            black_box(value);
+            position += length;
+        }
+    }));
+    group.finish();
+}
+
+fn flat_scan(c: &mut Criterion) {
+    let flat = fixtures::flat_document(10_000);
+    let mut group = c.benchmark_group("flat");
+    group.throughput(Throughput::Bytes(flat.len() as u64));
+    group.bench_function("scan", |b| b.iter(|| {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&flat), &[]);
+        for tag in iter {
+            black_box(tag.expect("fixture should be valid"));
+        }
+    }));
+    group.finish();
+}
+
+fn nested_write(c: &mut Criterion) {
+    let nested = fixtures::nested_tags(1_000, 32);
+    let write_nested = || {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        for tag in nested.iter() {
+            writer.write(tag).expect("fixture should be writable");
+        }
+        writer.into_inner().expect("fixture should flush").into_inner()
+    };
+    let mut group = c.benchmark_group("nested");
+    group.throughput(Throughput::Bytes(write_nested().len() as u64));
+    group.bench_function("write", |b| b.iter(&write_nested));
+    group.finish();
+}
+
+fn cluster_parse(c: &mut Criterion) {
+    let clusters = fixtures::cluster_document(100, 50, 1024);
+    let mut group = c.benchmark_group("cluster");
+    group.throughput(Throughput::Bytes(clusters.len() as u64));
+    group.bench_function("parse", |b| b.iter(|| {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&clusters), &[]);
+        for tag in iter {
+            black_box(tag.expect("fixture should be valid"));
+        }
+    }));
+    group.bench_function("buffered parse", |b| b.iter(|| {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&clusters), &[TestSpec::Cluster(Master::Start)]);
+        for tag in iter {
+            black_box(tag.expect("fixture should be valid"));
+        }
+    }));
+
+    #[cfg(feature = "mmap")]
+    {
+        // The same document read from a file, through read calls and through a memory map
+        let path = std::env::temp_dir().join(format!("ebml-iterable-bench-{}.ebml", std::process::id()));
+        std::fs::write(&path, &clusters).expect("fixture should be writable to a temporary file");
+        group.bench_function("file parse", |b| b.iter(|| {
+            let file = std::fs::File::open(&path).expect("fixture file should open");
+            let iter: TagIterator<_, TestSpec> = TagIterator::new(file, &[]);
+            for tag in iter {
+                black_box(tag.expect("fixture should be valid"));
+            }
+        }));
+        group.bench_function("mmap parse", |b| b.iter(|| {
+            let iter: TagIterator<_, TestSpec> = unsafe { TagIterator::from_mmap(&path, &[]) }.expect("fixture file should map");
+            for tag in iter {
+                black_box(tag.expect("fixture should be valid"));
+            }
+        }));
+        let mapped = unsafe { TagIterator::<_, TestSpec>::from_mmap(&path, &[]) }.expect("fixture file should map");
+        group.bench_function("mmap slice parse", |b| b.iter(|| {
+            let iter: TagSliceIterator<TestSpec> = TagSliceIterator::new(mapped.get_ref().get_ref(), &[]);
+            for tag in iter {
+                black_box(tag.expect("fixture should be valid"));
+            }
+        }));
+        drop(mapped);
+        let _ = std::fs::remove_file(&path);
+    }
+    group.finish();
+}
+
+fn recover_at_cluster(c: &mut Criterion) {
+    let corrupted = fixtures::corrupted_cluster_document(4 * 1024 * 1024);
+    let mut group = c.benchmark_group("corrupted");
+    group.throughput(Throughput::Bytes(corrupted.len() as u64));
+    group.bench_function("recover at cluster", |b| b.iter(|| {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&corrupted), &[]);
+        iter.recover_at_tags(&[TestSpec::Cluster(Master::Start)]);
+        black_box(iter.next());
+        while let Some(Err(_)) = iter.next() {
+            if iter.try_recover().is_err() {
+                break;
+            }
+        }
+        for tag in iter {
+            black_box(tag.ok());
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, vint_decode, flat_scan, nested_write, cluster_parse, recover_at_cluster);
+criterion_main!(benches);
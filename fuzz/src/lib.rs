@@ -0,0 +1,176 @@
+//! Shared logic for the `cargo fuzz` targets in `fuzz_targets/`.
+//!
+//! Each target is a thin wrapper around one of the public functions here, so the same checks can be replayed from a regular test or debugger when a crash is found.
+//!
+
+use std::io::Cursor;
+
+use ebml_iterable::error::TagIteratorError;
+use ebml_iterable::iterator::AllowableErrors;
+use ebml_iterable::specs::{ebml_specification, Master, TagDataType};
+use ebml_iterable::{TagIterator, TagWriter};
+
+///
+/// A small specification covering every data type and a few levels of nesting.
+///
+#[ebml_specification]
+#[derive(Clone, Debug, PartialEq)]
+pub enum FuzzSpec {
+    #[id(0x18538067)]
+    #[data_type(TagDataType::Master)]
+    Root,
+
+    #[id(0x4102)]
+    #[data_type(TagDataType::Utf8)]
+    #[doc_path(Root)]
+    Title,
+
+    #[id(0x1f43b675)]
+    #[data_type(TagDataType::Master)]
+    #[doc_path(Root)]
+    Group,
+
+    #[id(0x4100)]
+    #[data_type(TagDataType::UnsignedInt)]
+    #[doc_path(Root/Group)]
+    Count,
+
+    #[id(0x4101)]
+    #[data_type(TagDataType::Integer)]
+    #[doc_path(Root/Group)]
+    Offset,
+
+    #[id(0x4103)]
+    #[data_type(TagDataType::Float)]
+    #[doc_path(Root/Group)]
+    Ratio,
+
+    #[id(0xa1)]
+    #[data_type(TagDataType::Binary)]
+    #[doc_path(Root/Group)]
+    Payload,
+}
+
+const BUFFER_LEN: usize = 64;
+
+///
+/// Parses arbitrary bytes, both as a stream of start/end tags and with every master buffered and errors recovered from.  Only panics (or hangs) are failures.
+///
+pub fn parse_bytes(data: &[u8]) {
+    let mut iter: TagIterator<_, FuzzSpec> = TagIterator::with_capacity(Cursor::new(data), &[], BUFFER_LEN);
+    for tag in iter.by_ref() {
+        if tag.is_err() {
+            break;
+        }
+    }
+
+    let mut iter: TagIterator<_, FuzzSpec> = TagIterator::with_capacity(Cursor::new(data), &[FuzzSpec::Root(Master::Start), FuzzSpec::Group(Master::Start)], BUFFER_LEN);
+    iter.allow_errors(&[AllowableErrors::InvalidTagIds, AllowableErrors::HierarchyProblems]);
+    while let Some(tag) = iter.next() {
+        match tag {
+            Ok(_) => {},
+            Err(TagIteratorError::CorruptedFileData(_)) => {
+                if iter.try_recover().is_err() {
+                    break;
+                }
+            },
+            Err(_) => break,
+        }
+    }
+}
+
+///
+/// Builds a valid document from arbitrary bytes, then asserts that writing and reading it back gives the same tags.
+///
+pub fn round_trip(data: &[u8]) {
+    let document = document_from_bytes(data);
+
+    let mut writer = TagWriter::new(Vec::new());
+    writer.write(&document).expect("valid documents should always be writable");
+    let written = writer.into_inner().expect("writing to a Vec should not fail");
+
+    let iter: TagIterator<_, FuzzSpec> = TagIterator::new(Cursor::new(written.as_slice()), &[FuzzSpec::Root(Master::Start)]);
+    let read: Vec<FuzzSpec> = iter.map(|tag| tag.expect("written documents should always be readable")).collect();
+    assert_eq!(vec![document], read);
+}
+
+///
+/// Parses arbitrary bytes, re-writes whatever was read successfully, and asserts that parsing and writing the result again is stable.
+///
+/// Byte output is compared instead of tags so that floats holding NaN do not cause false failures.
+///
+pub fn parse_write_parse(data: &[u8]) {
+    let first = parse_prefix(data);
+    let first_bytes = match write_tags(&first) {
+        Some(bytes) => bytes,
+        None => return,
+    };
+
+    let second = parse_prefix(&first_bytes);
+    assert_eq!(first.len(), second.len(), "re-parsing written data should produce the same number of tags");
+    let second_bytes = write_tags(&second).expect("tags read from written data should be writable");
+    assert_eq!(first_bytes, second_bytes);
+}
+
+///
+/// Deterministically maps arbitrary bytes to a document that conforms to [`FuzzSpec`].
+///
+pub fn document_from_bytes(data: &[u8]) -> FuzzSpec {
+    let mut bytes = data.iter().copied();
+    let mut children = Vec::new();
+    while let Some(op) = bytes.next() {
+        match op % 3 {
+            0 => children.push(FuzzSpec::Title(take_string(&mut bytes))),
+            _ => {
+                let mut group = Vec::new();
+                for _ in 0..(op >> 5) {
+                    let op = match bytes.next() {
+                        Some(op) => op,
+                        None => break,
+                    };
+                    group.push(match op % 4 {
+                        0 => FuzzSpec::Count(u64::from_le_bytes(take_array(&mut bytes))),
+                        1 => FuzzSpec::Offset(i64::from_le_bytes(take_array(&mut bytes))),
+                        2 => {
+                            let ratio = f64::from_le_bytes(take_array(&mut bytes));
+                            FuzzSpec::Ratio(if ratio.is_nan() { 0.0 } else { ratio })
+                        },
+                        _ => FuzzSpec::Payload(take_bytes(&mut bytes)),
+                    });
+                }
+                children.push(FuzzSpec::Group(Master::Full(group)));
+            },
+        }
+    }
+    FuzzSpec::Root(Master::Full(children))
+}
+
+fn take_array(bytes: &mut impl Iterator<Item = u8>) -> [u8; 8] {
+    let mut array = [0; 8];
+    for (slot, byte) in array.iter_mut().zip(bytes) {
+        *slot = byte;
+    }
+    array
+}
+
+fn take_bytes(bytes: &mut impl Iterator<Item = u8>) -> Vec<u8> {
+    let len = bytes.next().unwrap_or(0);
+    bytes.take(len as usize).collect()
+}
+
+fn take_string(bytes: &mut impl Iterator<Item = u8>) -> String {
+    String::from_utf8_lossy(&take_bytes(bytes)).into_owned()
+}
+
+fn parse_prefix(data: &[u8]) -> Vec<FuzzSpec> {
+    let iter: TagIterator<_, FuzzSpec> = TagIterator::with_capacity(Cursor::new(data), &[], BUFFER_LEN);
+    iter.take_while(|tag| tag.is_ok()).map(|tag| tag.unwrap()).collect()
+}
+
+fn write_tags(tags: &[FuzzSpec]) -> Option<Vec<u8>> {
+    let mut writer = TagWriter::new(Vec::new());
+    let written = tags.iter().try_for_each(|tag| writer.write(tag));
+    // Always finishing lets the writer be dropped without open tags, and rejects prefixes that leave tags open
+    let data = writer.finish().ok();
+    written.ok().and(data)
+}
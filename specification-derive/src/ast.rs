@@ -1,5 +1,4 @@
 use std::collections::HashSet;
-use proc_macro2::TokenStream;
 use syn::{ItemEnum, Error, Generics, Ident, Result, LitInt, Path, spanned::Spanned};
 
 use ebml_iterable_specification::TagDataType;
@@ -20,11 +19,14 @@ pub struct Variant<'a> {
     pub id_attr: (u64, Attribute<'a>),
     pub data_type_attr: (TagDataType, Path, Attribute<'a>),
     pub path_attr: Option<(EBMLPath, Attribute<'a>)>,
+    pub min_version_attr: Option<(u64, Attribute<'a>)>,
+    pub min_read_version_attr: Option<(u64, Attribute<'a>)>,
+    pub mandatory_attr: Option<Attribute<'a>>,
+    pub ascii_attr: Option<Attribute<'a>>,
 }
 
 pub struct Attribute<'a> {
     pub original: &'a syn::Attribute,
-    pub tokens: &'a TokenStream,
 }
 
 impl<'a> Enum<'a> {
@@ -50,6 +52,10 @@ impl<'a> Variant<'a> {
         let mut id_attr: Option<(u64, Attribute<'a>)> = None;
         let mut data_type_attr: Option<(TagDataType, Path, Attribute<'a>)> = None;
         let mut path_attr: Option<(EBMLPath, Attribute<'a>)> = None;
+        let mut min_version_attr: Option<(u64, Attribute<'a>)> = None;
+        let mut min_read_version_attr: Option<(u64, Attribute<'a>)> = None;
+        let mut mandatory_attr: Option<Attribute<'a>> = None;
+        let mut ascii_attr: Option<Attribute<'a>> = None;
 
         for attr in &node.attrs {
             if attr.path.is_ident("id") {
@@ -59,7 +65,6 @@ impl<'a> Variant<'a> {
                 let val = attr.parse_args::<LitInt>()?.base10_parse::<u64>()?;
                 id_attr = Some((val, Attribute {
                     original: attr,
-                    tokens: &attr.tokens,
                 }));
             } else if attr.path.is_ident("data_type") {
                 if data_type_attr.is_some() {
@@ -82,6 +87,8 @@ impl<'a> Variant<'a> {
                     TagDataType::Binary
                 } else if data_type_name == "Float" {
                     TagDataType::Float
+                } else if data_type_name == "Date" {
+                    TagDataType::Date
                 } else if data_type_name == "Master" {
                     TagDataType::Master
                 } else {
@@ -89,7 +96,6 @@ impl<'a> Variant<'a> {
                 };
                 data_type_attr = Some((data_type_val, val, Attribute {
                     original: attr,
-                    tokens: &attr.tokens,
                 }));
             } else if attr.path.is_ident("doc_path") {
                 if path_attr.is_some() {
@@ -116,8 +122,36 @@ impl<'a> Variant<'a> {
                 }
                 path_attr = Some((path, Attribute {
                     original: attr,
-                    tokens: &attr.tokens,
                 }))
+            } else if attr.path.is_ident("min_version") || attr.path.is_ident("min_read_version") {
+                let target = if attr.path.is_ident("min_version") { &mut min_version_attr } else { &mut min_read_version_attr };
+                if target.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let val = attr.parse_args::<LitInt>()?.base10_parse::<u64>()?;
+                *target = Some((val, Attribute {
+                    original: attr,
+                }));
+            } else if attr.path.is_ident("mandatory") {
+                if mandatory_attr.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                if !attr.tokens.is_empty() {
+                    return Err(Error::new_spanned(attr, "#[mandatory] does not take arguments"));
+                }
+                mandatory_attr = Some(Attribute {
+                    original: attr,
+                });
+            } else if attr.path.is_ident("ascii") {
+                if ascii_attr.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                if !attr.tokens.is_empty() {
+                    return Err(Error::new_spanned(attr, "#[ascii] does not take arguments"));
+                }
+                ascii_attr = Some(Attribute {
+                    original: attr,
+                });
             }
         }
 
@@ -129,12 +163,29 @@ impl<'a> Variant<'a> {
             return Err(Error::new_spanned(node, "#[data_type] attribute is required when using #[ebml_specification] attribute"));
         };
 
+        if let Some(mandatory) = &mandatory_attr {
+            let has_parent = path_attr.as_ref().map(|(path, _)| matches!(path.parts.last(), Some(PathPart::Ident(_)))).unwrap_or(false);
+            if !has_parent {
+                return Err(Error::new_spanned(mandatory.original, "#[mandatory] requires a #[doc_path] ending in a parent element"));
+            }
+        }
+
+        if let Some(ascii) = &ascii_attr {
+            if !matches!(data_type_attr.0, TagDataType::Utf8) {
+                return Err(Error::new_spanned(ascii.original, "#[ascii] can only be used on elements with a data type of TagDataType::Utf8"));
+            }
+        }
+
         Ok(Variant {
             original: node,
             ident: node.ident.clone(),
             id_attr,
             data_type_attr,
-            path_attr
+            path_attr,
+            min_version_attr,
+            min_read_version_attr,
+            mandatory_attr,
+            ascii_attr,
         })
     }
 }
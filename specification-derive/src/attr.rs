@@ -109,11 +109,13 @@ fn modify_orig(original: &mut ItemEnum) -> Result<TokenStream> {
             quote!( (::std::vec::Vec<u8>) )
         } else if data_type == "Float" {
             quote!( (f64) )
+        } else if data_type == "Date" {
+            quote!( (i64) )
         } else {
             return Err(Error::new_spanned(data_type_attribute.clone(), format!("unknown data_type \"{data_type}\"")));
         };
 
-        var.attrs.retain(|a| !(a.path.is_ident("id") || a.path.is_ident("data_type") || a.path.is_ident("doc_path")));
+        var.attrs.retain(|a| !(a.path.is_ident("id") || a.path.is_ident("data_type") || a.path.is_ident("doc_path") || a.path.is_ident("min_version") || a.path.is_ident("min_read_version") || a.path.is_ident("mandatory") || a.path.is_ident("ascii")));
         var.fields = Fields::Unnamed(syn::parse2::<FieldsUnnamed>(data_type)?);
     }
     original.variants.push(syn::parse_str::<Variant>("RawTag(u64, ::std::vec::Vec<u8>)")?);
@@ -191,6 +193,45 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         }
     });
 
+    let get_version = |var: &crate::ast::Variant, attr: &Option<(u64, crate::ast::Attribute)>| {
+        attr.as_ref().map(|(version, attr)| {
+            let id = &var.id_attr.0;
+            quote_spanned! { attr.original.span() =>
+                #id => Some(#version),
+            }
+        })
+    };
+    let get_min_version = input.variants.iter().filter_map(|v| get_version(v, &v.min_version_attr));
+    let get_tag_name = input.variants.iter().map(|v| {
+        let id = &v.id_attr.0;
+        let name = v.ident.to_string();
+        quote_spanned! { v.original.span() =>
+            #id => Some(#name),
+        }
+    });
+    let get_min_read_version = input.variants.iter().filter_map(|v| get_version(v, &v.min_read_version_attr));
+
+    let get_mandatory_children = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Master))
+        .filter_map(|parent| {
+            let children: Vec<u64> = input.variants.iter()
+                .filter(|v| v.mandatory_attr.is_some())
+                .filter(|v| matches!(v.path_attr.as_ref().and_then(|path| path.0.parts.last()), Some(PathPart::Ident(ident)) if ident == &parent.ident))
+                .map(|v| v.id_attr.0)
+                .collect();
+            if children.is_empty() {
+                return None;
+            }
+            let id = &parent.id_attr.0;
+            Some(quote_spanned! { parent.original.span() =>
+                #id => &[#(#children),*],
+            })
+        });
+
+    let ascii_strings = input.variants.iter()
+        .filter(|v| v.ascii_attr.is_some())
+        .map(|v| v.id_attr.0);
+
     let get_unsigned_int_tag = input.variants.iter()
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::UnsignedInt))
         .map(get_tag(String::from("data")));
@@ -211,6 +252,10 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Float))
         .map(get_tag(String::from("data")));
 
+    let get_date_tag = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Date))
+        .map(get_tag(String::from("data")));
+
     let get_master_tag = input.variants.iter()
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Master))
         .map(get_tag(String::from("data")));
@@ -243,6 +288,10 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Float))
         .map(as_data);
 
+    let as_date = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Date))
+        .map(as_data);
+
     let as_master = input.variants.iter()
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Master))
         .map(as_data);
@@ -268,6 +317,39 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn get_min_version(id: u64) -> Option<u64> {
+                match id {
+                    #(#get_min_version)*
+                    _ => None
+                }
+            }
+
+            fn get_min_read_version(id: u64) -> Option<u64> {
+                match id {
+                    #(#get_min_read_version)*
+                    _ => None
+                }
+            }
+
+            fn get_mandatory_children(id: u64) -> &'static [u64] {
+                match id {
+                    #(#get_mandatory_children)*
+                    _ => &[]
+                }
+            }
+
+            fn get_tag_name(id: u64) -> Option<&'static str> {
+                match id {
+                    #(#get_tag_name)*
+                    _ => None
+                }
+            }
+
+            fn is_ascii_string(id: u64) -> bool {
+                let ids: &[u64] = &[#(#ascii_strings),*];
+                ids.contains(&id)
+            }
+
             fn get_unsigned_int_tag(id: u64, data: u64) -> Option<#ty> {
                 match id {
                     #(#get_unsigned_int_tag)*
@@ -303,6 +385,13 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn get_date_tag(id: u64, data: i64) -> Option<#ty> {
+                match id {
+                    #(#get_date_tag)*
+                    _ => None
+                }
+            }
+
             fn get_master_tag(id: u64, data: #spanned_master_enum<#ty>) -> Option<#ty> {
                 match id {
                     #(#get_master_tag)*
@@ -360,6 +449,13 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn as_date(&self) -> Option<&i64> {
+                match self {
+                    #(#as_date)*
+                    _ => None,
+                }
+            }
+
             fn as_master(&self) -> Option<&#spanned_master_enum<#ty>> {
                 match self {
                     #(#as_master)*
@@ -73,6 +73,10 @@ impl EasyEBMLVariant {
             path: Ident::new("id", proc_macro2::Span::call_site()).into(),
             tokens: quote!((#id))
         });
+
+        // `Ascii` is shorthand for an EBML `string` element: Utf8 data limited to printable ASCII
+        let ascii = ty == "Ascii";
+        let ty = if ascii { Ident::new("Utf8", ty.span()) } else { ty };
         attrs.push(Attribute {
             pound_token: Default::default(),
             style: AttrStyle::Outer,
@@ -80,6 +84,15 @@ impl EasyEBMLVariant {
             path: Ident::new("data_type", proc_macro2::Span::call_site()).into(),
             tokens: quote!((TagDataType::#ty))
         });
+        if ascii {
+            attrs.push(Attribute {
+                pound_token: Default::default(),
+                style: AttrStyle::Outer,
+                bracket_token: Default::default(),
+                path: Ident::new("ascii", proc_macro2::Span::call_site()).into(),
+                tokens: quote!()
+            });
+        }
 
         if !path.is_empty() {
             let mut tokens: Punctuated<PathPart, Token![/]> = Punctuated::new();
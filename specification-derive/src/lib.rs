@@ -18,8 +18,12 @@ use crate::easy_ebml::EasyEBML;
 ///   * __#[id(`u64`)]__ - This attribute specifies the "id" of the tag. e.g. `0x1a45dfa3`
 ///   * __#[data_type(`TagDataType`)]__ - This attribute specifies the type of data contained in the tag. e.g. `TagDataType::UnsignedInt`
 ///
-/// The following attribute is optional for each variant:
+/// The following attributes are optional for each variant:
 ///   * __#[doc_path(Path/To/Element)]__ - This attribute specifies the document path of the current element.  If this attribute is not present, the variant is treated as a Root element.  Global elements can be defined with wildcard paths, e.g. #[doc_path(Segment/(1-)/)].
+///   * __#[min_version(`u64`)]__ - This attribute specifies the earliest `DocTypeVersion` that defines the element (the `minver` of an EBML Schema).
+///   * __#[min_read_version(`u64`)]__ - This attribute specifies the minimum `DocTypeReadVersion` needed to interpret the element, if readers cannot simply skip it.
+///   * __#[mandatory]__ - This attribute marks the element as a required child of the last element in its `doc_path` (a `minOccurs` of at least 1 in an EBML Schema).
+///   * __#[ascii]__ - This attribute marks a `TagDataType::Utf8` element as an EBML `string` element, which may only contain printable ASCII characters.
 /// 
 /// # Note
 ///
@@ -28,9 +32,10 @@ use crate::easy_ebml::EasyEBML;
 /// - `Void(Vec<u8>)` - global tag defined in the EBML spec
 /// - `RawTag(u64, Vec<u8>)` - used to support reading "unknown" tags that aren't in the spec
 ///
+/// This is why the macro is an attribute rather than a `#[derive(EbmlSpecification)]`: derive macros can only add new items next to an enum, not change the enum itself.
+///
 /// [spec]: ebml_iterable_specification::EbmlSpecification
 /// [tag]: ebml_iterable_specification::EbmlTag
-
 #[proc_macro_attribute]
 pub fn ebml_specification(_args: TokenStream, input: TokenStream) -> TokenStream {
     let mut input = match syn::parse::<ItemEnum>(input) {
@@ -104,11 +109,12 @@ pub fn ebml_specification(_args: TokenStream, input: TokenStream) -> TokenStream
 /// ```
 /// 
 /// Behind the scenes `easy_ebml!` still uses the existing [`[#ebml_specification]`][macro] attribute macro, so the final output of this macro will remain identical.
+///
+/// EBML `string` elements, which may only contain printable ASCII, can be declared with the `Ascii` type (e.g. `Ebml/DocType: Ascii = 0x4282`).  This is the same as a `Utf8` element with the `#[ascii]` attribute.
 /// 
 /// [spec]: ebml_iterable_specification::EbmlSpecification
 /// [tag]: ebml_iterable_specification::EbmlTag
 /// [macro]: macro@crate::ebml_specification
-
 #[proc_macro]
 pub fn easy_ebml(input: TokenStream) -> TokenStream {
     let input = match syn::parse::<EasyEBML>(input) {
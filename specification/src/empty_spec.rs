@@ -1,3 +1,6 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use super::{EbmlSpecification, EbmlTag, Master, TagDataType, PathPart};
 
 ///
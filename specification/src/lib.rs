@@ -2,6 +2,15 @@
 //!
 //! The related ebml-iterable-specification-derive crate can be used to simplify implementation of this spec.
 //!
+//! This crate only needs `alloc`, so it can be used in `no_std` environments.
+//!
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
 
 ///
 /// Contains an empty specification for use with examples or very basic testing.
@@ -13,10 +22,8 @@ pub mod empty_spec;
 ///
 /// # Notes
 ///
-/// This library made a concious decision to not work with "Date" elements from EBML due to lack of built-in support for dates in Rust. Specification implementations should treat Date elements as Binary so that consumers have the option of parsing the unaltered data using their library of choice, if needed.
+/// "Date" elements are represented as the signed number of nanoseconds since 2001-01-01T00:00:00 UTC, the way they are stored in EBML.  Libraries like `ebml-iterable` provide helpers to convert these values to and from system times.
 ///
-
-// Possible future feature flag to enable Date functionality by having `chrono` as an optional dependency?
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum TagDataType {
     Master,
@@ -25,6 +32,7 @@ pub enum TagDataType {
     Utf8,
     Binary,
     Float,
+    Date,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -40,7 +48,6 @@ pub enum PathPart {
 ///
 /// This trait and [`EbmlTag`] are typically implemented simultaneously.  They are separate traits as they have primarily different uses - [`EbmlSpecification`] should be brought into scope when dealing with the specification as a whole, whereas [`EbmlTag`] should be brought into scope when dealing with specific tags.
 ///
-
 pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
     ///
     /// Pulls the data type for a tag from the spec, based on the tag id.
@@ -74,6 +81,51 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
         Self::get_path_by_id(item.get_id())
     }
 
+    ///
+    /// Gets the earliest `DocTypeVersion` that defines a tag.
+    ///
+    /// Documents containing this tag must declare at least this `DocTypeVersion` in their EBML header.  Default implementation returns `None`, meaning the specification does not track element versions.
+    ///
+    fn get_min_version(_id: u64) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the minimum `DocTypeReadVersion` a reader must support to interpret a tag.
+    ///
+    /// This is typically lower than [`Self::get_min_version`] (often not set at all), since readers can safely skip most elements they don't understand.  Default implementation returns `None`.
+    ///
+    fn get_min_read_version(_id: u64) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the ids of the children that must be present in a "Master" tag.
+    ///
+    /// These are the elements with a `minOccurs` of at least 1 in an EBML Schema.  Default implementation returns an empty slice, meaning no children are required.
+    ///
+    fn get_mandatory_children(_id: u64) -> &'static [u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the human readable name of a tag, such as `"Segment"`.
+    ///
+    /// Tools use this to describe tags without hardcoding names for every id.  Default implementation returns `None`, meaning the specification does not provide names.
+    ///
+    fn get_tag_name(_id: u64) -> Option<&'static str> {
+        None
+    }
+
+    ///
+    /// Returns whether a [`TagDataType::Utf8`] tag is an EBML `string` element, which may only contain printable ASCII characters (0x20 to 0x7E) followed by optional 0x00 padding.
+    ///
+    /// EBML distinguishes these from `utf-8` elements, and specifications like Matroska use both.  Readers and writers validate the data of these tags against the stricter constraint.  Default implementation returns `false`, meaning every string tag holds UTF-8 data.
+    ///
+    fn is_ascii_string(_id: u64) -> bool {
+        false
+    }
+
     ///
     /// Creates an unsigned integer type tag from the spec.
     ///
@@ -116,6 +168,15 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
     ///
     fn get_master_tag(id: u64, data: Master<T>) -> Option<T>;
 
+    ///
+    /// Creates a date type tag from the spec, where `data` is the number of nanoseconds since 2001-01-01T00:00:00 UTC.
+    ///
+    /// This function *must* return `None` if the input id is not in the specification or if the input id data type is not [`TagDataType::Date`].  Default implementation returns `None`, for specifications without any date elements.
+    ///
+    fn get_date_tag(_id: u64, _data: i64) -> Option<T> {
+        None
+    }
+
     ///
     /// Creates a tag that does not conform to the spec.
     ///
@@ -132,7 +193,6 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
 ///
 /// This trait and [`EbmlSpecification`] are typically implemented simultaneously.  They are separate traits as they have primarily different uses - [`EbmlSpecification`] should be brought into scope when dealing with the specification as a whole, whereas [`EbmlTag`] should be brought into scope when dealing with specific tags.
 ///
-
 pub trait EbmlTag<T: Clone> {
     ///
     /// Gets the id of `self`.
@@ -182,6 +242,15 @@ pub trait EbmlTag<T: Clone> {
     /// This function *must* return `None` if the associated data type of `self` is not [`TagDataType::Master`].  Implementors can reference [webm-iterable](https://crates.io/crates/webm_iterable) for an example.
     ///
     fn as_master(&self) -> Option<&Master<T>>;
+
+    ///
+    /// Gets a reference to the data contained in `self` as a date, in nanoseconds since 2001-01-01T00:00:00 UTC.
+    ///
+    /// This function *must* return `None` if the associated data type of `self` is not [`TagDataType::Date`].  Default implementation returns `None`, for specifications without any date elements.
+    ///
+    fn as_date(&self) -> Option<&i64> {
+        None
+    }
 }
 
 ///
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use crate::error::TagIteratorError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::TagIterator;
+
+///
+/// Totals for all tags sharing an id.
+///
+/// `data_bytes` counts the data of each tag - for "Master" tags, this includes all of their children.
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ElementStats {
+    pub count: u64,
+    pub header_bytes: u64,
+    pub data_bytes: u64,
+}
+
+///
+/// One of the largest tags in a document, as listed in [`DocumentStats::largest`].
+///
+/// `size` includes the tag's header, and `depth` is 0 for top-level tags.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LargeElement {
+    pub tag_id: u64,
+    pub offset: usize,
+    pub size: u64,
+    pub depth: usize,
+}
+
+///
+/// Statistics describing where the bytes of an EBML document go.  Produced by [`analyze()`].
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+
+    ///
+    /// Total size of all top-level tags.
+    ///
+    pub total_bytes: u64,
+
+    ///
+    /// Bytes used by tag ids and sizes, across all tags.
+    ///
+    pub header_bytes: u64,
+
+    ///
+    /// Bytes of data in non-"Master" tags.  Together with [`Self::header_bytes`], this accounts for [`Self::total_bytes`] unless the document contains "Master" tags larger than their children.
+    ///
+    pub payload_bytes: u64,
+
+    ///
+    /// Totals for each tag id.
+    ///
+    pub elements: BTreeMap<u64, ElementStats>,
+
+    ///
+    /// Number of tags at each nesting depth, starting with top-level tags.
+    ///
+    pub depth_histogram: Vec<u64>,
+
+    ///
+    /// The largest tags in the document, largest first.
+    ///
+    pub largest: Vec<LargeElement>,
+}
+
+impl DocumentStats {
+
+    ///
+    /// Gets the fraction of [`Self::total_bytes`] used by tag headers rather than data.
+    ///
+    pub fn header_overhead(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.header_bytes as f64 / self.total_bytes as f64
+        }
+    }
+
+    fn record(&mut self, tag_id: u64, offset: usize, header_len: usize, data_len: u64, depth: usize, max_largest: usize) -> u64 {
+        let size = header_len as u64 + data_len;
+        let element = self.elements.entry(tag_id).or_default();
+        element.count += 1;
+        element.header_bytes += header_len as u64;
+        element.data_bytes += data_len;
+        self.header_bytes += header_len as u64;
+
+        if self.depth_histogram.len() <= depth {
+            self.depth_histogram.resize(depth + 1, 0);
+        }
+        self.depth_histogram[depth] += 1;
+
+        if max_largest > 0 && (self.largest.len() < max_largest || self.largest.last().is_some_and(|smallest| size > smallest.size)) {
+            let index = self.largest.partition_point(|large| large.size >= size);
+            self.largest.insert(index, LargeElement { tag_id, offset, size, depth });
+            self.largest.truncate(max_largest);
+        }
+        size
+    }
+}
+
+///
+/// Reads an entire document and collects statistics on the size of its tags.
+///
+/// This answers questions like "why is this file so big": how many tags of each id there are and how many bytes they use, how much space goes to tag headers, how deeply tags are nested, and which `max_largest` tags are the largest.  The source is streamed through a [`TagIterator`] reading `<TSpec>`, so documents of any size can be analyzed without being held in memory.
+///
+/// The size of an unknown sized "Master" tag is the size of its children.
+///
+/// ## Errors
+///
+/// Returns the first error encountered while reading the document.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::stats::analyze;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let stats = analyze::<EmptySpec, _>(Cursor::new(vec![0x81, 0x82, 0x01, 0x02, 0x82, 0x80]), 1).unwrap();
+/// assert_eq!(6, stats.total_bytes);
+/// assert_eq!(4, stats.header_bytes);
+/// assert_eq!(0x81, stats.largest[0].tag_id);
+/// ```
+///
+pub fn analyze<TSpec, R>(source: R, max_largest: usize) -> Result<DocumentStats, TagIteratorError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    R: Read,
+{
+    let mut stats = DocumentStats::default();
+    // Total size of the children read so far, for each open "Master" tag
+    let mut open: Vec<u64> = Vec::new();
+    let mut iter: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+
+    while let Some(tag) = iter.next() {
+        let tag = tag?;
+        let span = iter.last_emitted_tag_span();
+        let size = match tag.as_master() {
+            Some(Master::Start) => {
+                open.push(0);
+                continue;
+            },
+            Some(Master::End) => {
+                let children = open.pop().unwrap_or_default();
+                let data_len = span.data_len.map_or(children, |len| len as u64);
+                stats.record(tag.get_id(), span.offset, span.header_len, data_len, open.len(), max_largest)
+            },
+            _ => {
+                let data_len = span.data_len.unwrap_or_default() as u64;
+                stats.payload_bytes += data_len;
+                stats.record(tag.get_id(), span.offset, span.header_len, data_len, open.len(), max_largest)
+            },
+        };
+
+        match open.last_mut() {
+            Some(parent) => *parent += size,
+            None => stats.total_bytes += size,
+        }
+    }
+    Ok(stats)
+}
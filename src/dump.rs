@@ -0,0 +1,101 @@
+use std::io::{self, Read, Write};
+
+use crate::error::DumpError;
+use crate::iterator::AllowableErrors;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::TagIterator;
+
+///
+/// Writes an indented tree of every tag in a document to `out`, similar to the output of `mkvinfo`.
+///
+/// Each tag is written on its own line, indented by two spaces for each "Master" tag it is inside of, with its name from [`EbmlSpecification::get_tag_name()`] (if `<TSpec>` has one), its id, its offset and the size of its data.  Values are written after the size: strings are quoted and cut off after `max_value_len` characters, binary data is written in hex and cut off after `max_value_len` bytes, and numbers are written in full.  Tags that aren't in `<TSpec>` are written with their data as binary, rather than stopping the dump.
+///
+/// This is meant for debugging and exploring documents, so the exact format may change between releases.  Each line is written separately, so `out` should be buffered (e.g. with a [`std::io::BufWriter`]) if it is a file or socket.
+///
+/// ## Errors
+///
+/// Returns a [`DumpError::Read`] with the first error reading the document, and a [`DumpError::Write`] if `out` can't be written to.  Everything read before an error has already been written to `out`.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::header::EbmlHeaderSpec;
+///
+/// let data = [0x1a, 0x45, 0xdf, 0xa3, 0x8c, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm', 0xec, 0x83, 0x00, 0x00, 0x00];
+/// let mut out = Vec::new();
+/// ebml_iterable::dump::<EbmlHeaderSpec, _, _>(Cursor::new(data), &mut out, 2).unwrap();
+/// assert_eq!(concat!(
+///     "EBML [0x1a45dfa3] at 0, size 12\n",
+///     "  DocType [0x4282] at 5, size 4: \"we\"...\n",
+///     "  Void [0xec] at 12, size 3: 00 00 ...\n",
+/// ), String::from_utf8(out).unwrap());
+/// ```
+///
+pub fn dump<TSpec, R, W>(source: R, out: &mut W, max_value_len: usize) -> Result<(), DumpError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    R: Read,
+    W: Write,
+{
+    let mut iter: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+    iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+    let mut depth: usize = 0;
+
+    while let Some(tag) = iter.next() {
+        let tag = tag?;
+        let span = iter.last_emitted_tag_span();
+        let tag_id = tag.get_id();
+        if let Some(Master::End) = tag.as_master() {
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+
+        write!(out, "{:indent$}", "", indent = depth * 2)?;
+        match TSpec::get_tag_name(tag_id) {
+            Some(name) => write!(out, "{name} [0x{tag_id:x?}] at {}", span.offset)?,
+            None => write!(out, "[0x{tag_id:x?}] at {}", span.offset)?,
+        }
+        match span.data_len {
+            Some(len) => write!(out, ", size {len}")?,
+            None => write!(out, ", unknown size")?,
+        }
+
+        if let Some(Master::Start) = tag.as_master() {
+            depth += 1;
+        } else {
+            write!(out, ": ")?;
+            write_value(out, &tag, max_value_len)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn write_value<TSpec: EbmlTag<TSpec> + Clone, W: Write>(out: &mut W, tag: &TSpec, max_value_len: usize) -> io::Result<()> {
+    if let Some(value) = tag.as_unsigned_int() {
+        write!(out, "{value}")
+    } else if let Some(value) = tag.as_signed_int() {
+        write!(out, "{value}")
+    } else if let Some(value) = tag.as_float() {
+        write!(out, "{value}")
+    } else if let Some(value) = tag.as_date() {
+        write!(out, "{value} ns after 2001-01-01")
+    } else if let Some(value) = tag.as_utf8() {
+        match value.char_indices().nth(max_value_len) {
+            Some((end, _)) => write!(out, "{:?}...", &value[..end]),
+            None => write!(out, "{value:?}"),
+        }
+    } else if let Some(value) = tag.as_binary() {
+        let shown = &value[..value.len().min(max_value_len)];
+        for (index, byte) in shown.iter().enumerate() {
+            write!(out, "{}{byte:02x}", if index == 0 { "" } else { " " })?;
+        }
+        if shown.len() < value.len() {
+            write!(out, "{}...", if shown.is_empty() { "" } else { " " })?;
+        }
+        Ok(())
+    } else {
+        Ok(())
+    }
+}
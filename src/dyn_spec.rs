@@ -0,0 +1,865 @@
+use std::cell::RefCell;
+use std::io::{Read, Seek, Write};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::error::{HeaderError, TagIteratorError, TagWriterError};
+use crate::header::{EbmlHeader, EbmlHeaderSpec, ReaderSupport};
+use crate::ids;
+use crate::iterator::{AllowableErrors, Quirk, SkippedTag, TagSpan};
+use crate::specs::{EbmlSpecification, EbmlTag, Master, PathPart, TagDataType};
+use crate::{TagIterator, TagWriter};
+
+///
+/// An object safe view of an EBML specification.
+///
+/// [`EbmlSpecification`] is implemented through associated functions, so every specification produces its own copy of the parser.  This trait exposes the lookups the parser needs through `&self` instead, allowing a specification to be chosen at runtime (e.g. by a plugin system or language binding) and used with [`DynTagIterator`] and [`DynTagWriter`].
+///
+/// Any existing specification can be used through [`StaticSpec`].
+///
+pub trait DynSpecification: Send + Sync {
+
+    ///
+    /// Pulls the data type for a tag from the spec, based on the tag id.  Must return [`None`] if the id is not in the specification.
+    ///
+    fn tag_data_type(&self, id: u64) -> Option<TagDataType>;
+
+    ///
+    /// Gets the document path of a tag based on the tag id.
+    ///
+    fn path_by_id(&self, id: u64) -> &'static [PathPart];
+
+    ///
+    /// Gets the minimum `DocTypeVersion` required for a tag.  Defaults to [`None`].
+    ///
+    fn min_version(&self, _id: u64) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the minimum `DocTypeReadVersion` required for a tag.  Defaults to [`None`].
+    ///
+    fn min_read_version(&self, _id: u64) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the ids of the children that must be present in a "Master" tag.  Defaults to an empty slice.
+    ///
+    fn mandatory_children(&self, _id: u64) -> &'static [u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the human readable name of a tag.  Defaults to [`None`].
+    ///
+    fn tag_name(&self, _id: u64) -> Option<&'static str> {
+        None
+    }
+
+    ///
+    /// Returns whether a string tag may only contain printable ASCII characters.  Defaults to `false`.
+    ///
+    fn is_ascii_string(&self, _id: u64) -> bool {
+        false
+    }
+}
+
+///
+/// Adapts a compile-time specification to [`DynSpecification`].
+///
+/// ## Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use ebml_iterable::dynamic::{DynSpecification, StaticSpec};
+/// use ebml_iterable::specs::TagDataType;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let spec: Arc<dyn DynSpecification> = StaticSpec::<EmptySpec>::shared();
+/// assert_eq!(Some(TagDataType::Binary), spec.tag_data_type(0x81));
+/// ```
+///
+pub struct StaticSpec<TSpec>(PhantomData<fn() -> TSpec>);
+
+impl<TSpec> StaticSpec<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + 'static
+{
+    ///
+    /// Returns a new [`StaticSpec`] for `<TSpec>`.
+    ///
+    pub fn new() -> Self {
+        StaticSpec(PhantomData)
+    }
+
+    ///
+    /// Returns `<TSpec>` as a shared trait object, ready to pass to [`DynTagIterator`] or [`DynTagWriter`].
+    ///
+    pub fn shared() -> Arc<dyn DynSpecification> {
+        Arc::new(StaticSpec::<TSpec>::new())
+    }
+}
+
+impl<TSpec> Default for StaticSpec<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + 'static
+{
+    fn default() -> Self {
+        StaticSpec::new()
+    }
+}
+
+impl<TSpec> DynSpecification for StaticSpec<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + 'static
+{
+    fn tag_data_type(&self, id: u64) -> Option<TagDataType> {
+        TSpec::get_tag_data_type(id)
+    }
+
+    fn path_by_id(&self, id: u64) -> &'static [PathPart] {
+        TSpec::get_path_by_id(id)
+    }
+
+    fn min_version(&self, id: u64) -> Option<u64> {
+        TSpec::get_min_version(id)
+    }
+
+    fn min_read_version(&self, id: u64) -> Option<u64> {
+        TSpec::get_min_read_version(id)
+    }
+
+    fn mandatory_children(&self, id: u64) -> &'static [u64] {
+        TSpec::get_mandatory_children(id)
+    }
+
+    fn tag_name(&self, id: u64) -> Option<&'static str> {
+        TSpec::get_tag_name(id)
+    }
+
+    fn is_ascii_string(&self, id: u64) -> bool {
+        TSpec::is_ascii_string(id)
+    }
+}
+
+///
+/// The data held by a [`DynTag`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynValue {
+    Master(Master<DynTag>),
+    UnsignedInt(u64),
+    Integer(i64),
+    Utf8(String),
+    Binary(Vec<u8>),
+    Float(f64),
+
+    ///
+    /// Nanoseconds since 2001-01-01T00:00:00 UTC.
+    ///
+    Date(i64),
+
+    ///
+    /// Binary data for a tag id that is not part of the specification.
+    ///
+    Raw(Vec<u8>),
+}
+
+///
+/// A spec-independent tag produced by [`DynTagIterator`] and consumed by [`DynTagWriter`].
+///
+/// The data type of a [`DynTag`] is carried in its [`DynValue`] rather than in its Rust type.  Lookups such as [`EbmlSpecification::get_tag_data_type()`] only resolve while a [`DynTagIterator`] or [`DynTagWriter`] call is in progress; outside of those calls every id is reported as unknown.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynTag {
+    pub id: u64,
+    pub value: DynValue,
+}
+
+impl DynTag {
+
+    ///
+    /// Returns a new [`DynTag`] with the given id and value.
+    ///
+    pub fn new(id: u64, value: DynValue) -> Self {
+        DynTag { id, value }
+    }
+
+    ///
+    /// Converts a tag from a compile-time specification into a [`DynTag`].
+    ///
+    pub fn from_spec<TSpec>(tag: &TSpec) -> Self
+        where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let id = tag.get_id();
+        let value = match TSpec::get_tag_data_type(id) {
+            Some(TagDataType::Master) => DynValue::Master(match tag.as_master() {
+                Some(Master::Start) => Master::Start,
+                Some(Master::End) => Master::End,
+                Some(Master::Full(children)) => Master::Full(children.iter().map(DynTag::from_spec).collect()),
+                None => panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", id),
+            }),
+            Some(TagDataType::UnsignedInt) => DynValue::UnsignedInt(*tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was unsigned int, but could not get tag!", id))),
+            Some(TagDataType::Integer) => DynValue::Integer(*tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was integer, but could not get tag!", id))),
+            Some(TagDataType::Utf8) => DynValue::Utf8(tag.as_utf8().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", id)).to_owned()),
+            Some(TagDataType::Binary) => DynValue::Binary(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", id)).to_vec()),
+            Some(TagDataType::Float) => DynValue::Float(*tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was float, but could not get tag!", id))),
+            Some(TagDataType::Date) => DynValue::Date(*tag.as_date().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was date, but could not get tag!", id))),
+            None => DynValue::Raw(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was raw tag, but could not get binary data!", id)).to_vec()),
+        };
+        DynTag { id, value }
+    }
+
+    ///
+    /// Converts this tag into a tag of a compile-time specification.
+    ///
+    /// Returns [`None`] if `<TSpec>` does not accept the tag's data type for its id.  Ids that are not in `<TSpec>` are converted using [`EbmlSpecification::get_raw_tag()`].
+    ///
+    pub fn into_spec<TSpec>(self) -> Option<TSpec>
+        where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        if TSpec::get_tag_data_type(self.id).is_none() {
+            return match self.value {
+                DynValue::Raw(data) | DynValue::Binary(data) => Some(TSpec::get_raw_tag(self.id, &data)),
+                _ => None,
+            };
+        }
+
+        match self.value {
+            DynValue::Master(Master::Start) => TSpec::get_master_tag(self.id, Master::Start),
+            DynValue::Master(Master::End) => TSpec::get_master_tag(self.id, Master::End),
+            DynValue::Master(Master::Full(children)) => {
+                let children = children.into_iter().map(DynTag::into_spec).collect::<Option<Vec<TSpec>>>()?;
+                TSpec::get_master_tag(self.id, Master::Full(children))
+            },
+            DynValue::UnsignedInt(val) => TSpec::get_unsigned_int_tag(self.id, val),
+            DynValue::Integer(val) => TSpec::get_signed_int_tag(self.id, val),
+            DynValue::Utf8(val) => TSpec::get_utf8_tag(self.id, val),
+            DynValue::Binary(val) | DynValue::Raw(val) => TSpec::get_binary_tag(self.id, &val),
+            DynValue::Float(val) => TSpec::get_float_tag(self.id, val),
+            DynValue::Date(val) => TSpec::get_date_tag(self.id, val),
+        }
+    }
+
+    fn data_type(&self) -> Option<TagDataType> {
+        match self.value {
+            DynValue::Master(_) => Some(TagDataType::Master),
+            DynValue::UnsignedInt(_) => Some(TagDataType::UnsignedInt),
+            DynValue::Integer(_) => Some(TagDataType::Integer),
+            DynValue::Utf8(_) => Some(TagDataType::Utf8),
+            DynValue::Binary(_) => Some(TagDataType::Binary),
+            DynValue::Float(_) => Some(TagDataType::Float),
+            DynValue::Date(_) => Some(TagDataType::Date),
+            DynValue::Raw(_) => None,
+        }
+    }
+}
+
+thread_local! {
+    static ACTIVE_SPEC: RefCell<Option<Arc<dyn DynSpecification>>> = RefCell::new(None);
+}
+
+///
+/// Makes a specification the one [`DynTag`] resolves against on this thread until dropped.
+///
+pub(crate) struct ActiveSpec {
+    previous: Option<Arc<dyn DynSpecification>>,
+}
+
+impl ActiveSpec {
+    pub(crate) fn enter(spec: &Arc<dyn DynSpecification>) -> Self {
+        let previous = ACTIVE_SPEC.with(|active| active.replace(Some(Arc::clone(spec))));
+        ActiveSpec { previous }
+    }
+}
+
+impl Drop for ActiveSpec {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ACTIVE_SPEC.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+fn with_active_spec<T>(lookup: impl FnOnce(&dyn DynSpecification) -> Option<T>) -> Option<T> {
+    ACTIVE_SPEC.with(|active| active.borrow().as_deref().and_then(lookup))
+}
+
+impl EbmlSpecification<DynTag> for DynTag {
+    fn get_tag_data_type(id: u64) -> Option<TagDataType> {
+        with_active_spec(|spec| spec.tag_data_type(id))
+    }
+
+    fn get_path_by_id(id: u64) -> &'static [PathPart] {
+        with_active_spec(|spec| Some(spec.path_by_id(id))).unwrap_or(&[])
+    }
+
+    fn get_min_version(id: u64) -> Option<u64> {
+        with_active_spec(|spec| spec.min_version(id))
+    }
+
+    fn get_min_read_version(id: u64) -> Option<u64> {
+        with_active_spec(|spec| spec.min_read_version(id))
+    }
+
+    fn get_mandatory_children(id: u64) -> &'static [u64] {
+        with_active_spec(|spec| Some(spec.mandatory_children(id))).unwrap_or(&[])
+    }
+
+    fn get_tag_name(id: u64) -> Option<&'static str> {
+        with_active_spec(|spec| spec.tag_name(id))
+    }
+
+    fn is_ascii_string(id: u64) -> bool {
+        with_active_spec(|spec| Some(spec.is_ascii_string(id))).unwrap_or(false)
+    }
+
+    fn get_unsigned_int_tag(id: u64, data: u64) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::UnsignedInt(data)))
+    }
+
+    fn get_signed_int_tag(id: u64, data: i64) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::Integer(data)))
+    }
+
+    fn get_utf8_tag(id: u64, data: String) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::Utf8(data)))
+    }
+
+    fn get_binary_tag(id: u64, data: &[u8]) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::Binary(data.to_vec())))
+    }
+
+    fn get_float_tag(id: u64, data: f64) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::Float(data)))
+    }
+
+    fn get_master_tag(id: u64, data: Master<DynTag>) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::Master(data)))
+    }
+
+    fn get_date_tag(id: u64, data: i64) -> Option<DynTag> {
+        Some(DynTag::new(id, DynValue::Date(data)))
+    }
+
+    fn get_raw_tag(id: u64, data: &[u8]) -> DynTag {
+        DynTag::new(id, DynValue::Raw(data.to_vec()))
+    }
+}
+
+impl EbmlTag<DynTag> for DynTag {
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_unsigned_int(&self) -> Option<&u64> {
+        match &self.value {
+            DynValue::UnsignedInt(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_signed_int(&self) -> Option<&i64> {
+        match &self.value {
+            DynValue::Integer(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_utf8(&self) -> Option<&str> {
+        match &self.value {
+            DynValue::Utf8(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_binary(&self) -> Option<&[u8]> {
+        match &self.value {
+            DynValue::Binary(val) | DynValue::Raw(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<&f64> {
+        match &self.value {
+            DynValue::Float(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_master(&self) -> Option<&Master<DynTag>> {
+        match &self.value {
+            DynValue::Master(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_date(&self) -> Option<&i64> {
+        match &self.value {
+            DynValue::Date(val) => Some(val),
+            _ => None,
+        }
+    }
+}
+
+///
+/// A [`TagIterator`] over a specification selected at runtime.
+///
+/// This wraps a `TagIterator<R, DynTag>` and resolves tag ids through a [`DynSpecification`] trait object, so a single copy of the parser serves every specification.  Tags are emitted as [`DynTag`]s and can be converted with [`DynTag::into_spec()`] if the concrete specification is known.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::dynamic::{DynTag, DynTagIterator, DynValue, StaticSpec};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let source = Cursor::new(vec![0x81, 0x82, 0x01, 0x02]);
+/// let mut iter = DynTagIterator::new(source, StaticSpec::<EmptySpec>::shared(), &[]);
+/// assert_eq!(Some(DynTag::new(0x81, DynValue::Binary(vec![0x01, 0x02]))), iter.next().transpose().unwrap());
+/// ```
+///
+pub struct DynTagIterator<R: Read> {
+    inner: TagIterator<R, DynTag>,
+    spec: Arc<dyn DynSpecification>,
+}
+
+impl<R: Read> DynTagIterator<R> {
+
+    ///
+    /// Returns a new [`DynTagIterator`] reading `source` according to `spec`.
+    ///
+    /// `tags_to_buffer` lists the ids of "Master" tags that should be read as [`Master::Full`]s; see [`TagIterator::new()`].
+    ///
+    pub fn new(source: R, spec: Arc<dyn DynSpecification>, tags_to_buffer: &[u64]) -> Self {
+        let tags_to_buffer: Vec<DynTag> = tags_to_buffer.iter().map(|id| DynTag::new(*id, DynValue::Master(Master::Start))).collect();
+        DynTagIterator {
+            inner: TagIterator::new(source, &tags_to_buffer),
+            spec,
+        }
+    }
+
+    ///
+    /// Returns a new [`DynTagIterator`] with the specified internal buffer capacity.  See [`TagIterator::with_capacity()`].
+    ///
+    pub fn with_capacity(source: R, spec: Arc<dyn DynSpecification>, tags_to_buffer: &[u64], capacity: usize) -> Self {
+        let tags_to_buffer: Vec<DynTag> = tags_to_buffer.iter().map(|id| DynTag::new(*id, DynValue::Master(Master::Start))).collect();
+        DynTagIterator {
+            inner: TagIterator::with_capacity(source, &tags_to_buffer, capacity),
+            spec,
+        }
+    }
+
+    ///
+    /// Returns a new [`DynTagIterator`] that starts reading inside of the "Master" tags with ids `parents`.  See [`TagIterator::with_parents()`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if any of `parents` is not a "Master" tag according to `spec`.
+    ///
+    pub fn with_parents(source: R, spec: Arc<dyn DynSpecification>, parents: &[u64], tags_to_buffer: &[u64]) -> Self {
+        let parents: Vec<DynTag> = parents.iter().map(|id| DynTag::new(*id, DynValue::Master(Master::Start))).collect();
+        let tags_to_buffer: Vec<DynTag> = tags_to_buffer.iter().map(|id| DynTag::new(*id, DynValue::Master(Master::Start))).collect();
+        let inner = {
+            let _active = ActiveSpec::enter(&spec);
+            TagIterator::with_parents(source, &parents, &tags_to_buffer)
+        };
+        DynTagIterator {
+            inner,
+            spec,
+        }
+    }
+
+    ///
+    /// Returns a new [`DynTagIterator`] whose specification is chosen by `select` once the EBML header of `source` has been read.
+    ///
+    /// Tools that support several document types often can't choose a specification until they know the `DocType` of a document.  This reads the header with [`EbmlHeaderSpec`], checks it as [`EbmlHeader::check()`] does (accepting any `DocType`), and passes it to `select`, which returns the specification to read the rest of the document with (for example from a map of `DocType`s to specifications).  Specifications held in a `Box<dyn DynSpecification>` can be converted with `Arc::from()`.
+    ///
+    /// The tags of the header are consumed, but the header remains available through [`Self::ebml_header()`].  The iterator continues with the first tag after the header, and offsets are still relative to the start of `source`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`HeaderError::MissingHeader`] if `source` doesn't start with an EBML header, [`HeaderError::UnsupportedDocType`] if `select` returns [`None`], and another [`HeaderError`] if the header can't be read or describes a document that can't be read.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use std::sync::Arc;
+    /// use ebml_iterable::dynamic::{DynSpecification, DynTag, DynTagIterator, DynValue, StaticSpec};
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data = vec![0x1a, 0x45, 0xdf, 0xa3, 0x84, 0x42, 0x82, 0x81, b'e', 0x81, 0x81, 0x01];
+    /// let iter = DynTagIterator::from_header(Cursor::new(data), &[], |header| match header.doc_type.as_str() {
+    ///     "e" => Some(StaticSpec::<EmptySpec>::shared()),
+    ///     _ => None,
+    /// }).unwrap();
+    /// assert_eq!("e", iter.ebml_header().unwrap().doc_type);
+    /// assert_eq!(vec![DynTag::new(0x81, DynValue::Binary(vec![0x01]))], iter.collect::<Result<Vec<_>, _>>().unwrap());
+    /// ```
+    ///
+    pub fn from_header<F>(source: R, tags_to_buffer: &[u64], select: F) -> Result<Self, HeaderError>
+        where F: FnOnce(&EbmlHeader) -> Option<Arc<dyn DynSpecification>>
+    {
+        let mut iter = DynTagIterator::new(source, StaticSpec::<EbmlHeaderSpec>::shared(), tags_to_buffer);
+        // Unknown ids are allowed so that a document without a header is reported as such
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        while iter.ebml_header().is_none() {
+            let tag = match iter.next() {
+                Some(tag) => tag?,
+                None => return Err(HeaderError::Read(TagIteratorError::UnexpectedEOF { tag_start: iter.inner.bytes_read(), tag_id: None, tag_size: None, partial_data: None })),
+            };
+            if tag.id != ids::EBML && iter.current_path().is_empty() {
+                return Err(HeaderError::MissingHeader { found_tag_id: tag.id });
+            }
+        }
+        iter.allow_errors(&[]);
+
+        let header = iter.ebml_header().cloned().unwrap_or_default();
+        header.check(&ReaderSupport::default())?;
+        let spec = select(&header).ok_or(HeaderError::UnsupportedDocType(header.doc_type))?;
+        iter.set_spec(spec);
+        Ok(iter)
+    }
+
+    ///
+    /// Gets the specification this iterator reads with.
+    ///
+    pub fn spec(&self) -> &Arc<dyn DynSpecification> {
+        &self.spec
+    }
+
+    ///
+    /// Changes the specification used to read the tags that follow, e.g. once the `DocType` of the document is known (see [`Self::from_header()`]).
+    ///
+    /// This is best done between top-level tags, since "Master" tags that are already open were read with the previous specification.
+    ///
+    pub fn set_spec(&mut self, spec: Arc<dyn DynSpecification>) {
+        self.spec = spec;
+        self.inner.clear_spec_cache();
+    }
+
+    ///
+    /// Gets the EBML header of the document, once it has been read.  See [`TagIterator::ebml_header()`].
+    ///
+    pub fn ebml_header(&self) -> Option<&EbmlHeader> {
+        self.inner.ebml_header()
+    }
+
+    ///
+    /// Configures how strictly the iterator abides the specification.  See [`TagIterator::allow_errors()`].
+    ///
+    pub fn allow_errors(&mut self, errors: &[AllowableErrors]) {
+        self.inner.allow_errors(errors)
+    }
+
+    ///
+    /// Enables or disables strict conformance checking.  See [`TagIterator::set_strict_mode()`].
+    ///
+    pub fn set_strict_mode(&mut self, strict: bool) {
+        self.inner.set_strict_mode(strict)
+    }
+
+    ///
+    /// Enables or disables tolerance for common muxer bugs.  See [`TagIterator::set_quirks_mode()`].
+    ///
+    pub fn set_quirks_mode(&mut self, quirks: bool) {
+        self.inner.set_quirks_mode(quirks)
+    }
+
+    ///
+    /// Configures how deeply "Master" tags may be nested.  See [`TagIterator::set_max_depth()`].
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.inner.set_max_depth(max_depth)
+    }
+
+    ///
+    /// Configures the largest amount of data a single non-"Master" tag may have.  See [`TagIterator::set_max_tag_size()`].
+    ///
+    pub fn set_max_tag_size(&mut self, max_size: Option<usize>) {
+        self.inner.set_max_tag_size(max_size)
+    }
+
+    ///
+    /// Returns and clears the problems tolerated in quirks mode.  See [`TagIterator::take_warnings()`].
+    ///
+    pub fn take_warnings(&mut self) -> Vec<Quirk> {
+        self.inner.take_warnings()
+    }
+
+    ///
+    /// Configures tag ids whose data should be skipped without being parsed.  See [`TagIterator::skip_tags()`].
+    ///
+    pub fn skip_tags(&mut self, tag_ids: &[u64]) {
+        let _active = ActiveSpec::enter(&self.spec);
+        let tags: Vec<DynTag> = tag_ids.iter().map(|id| DynTag::new(*id, DynValue::Raw(Vec::new()))).collect();
+        self.inner.skip_tags(&tags)
+    }
+
+    ///
+    /// Configures whether tag ids unknown to the specification are skipped.  See [`TagIterator::skip_unknown_tags()`].
+    ///
+    pub fn skip_unknown_tags(&mut self, skip: bool) {
+        self.inner.skip_unknown_tags(skip)
+    }
+
+    ///
+    /// Configures a limit on the data size of emitted tags.  See [`TagIterator::skip_oversized_tags()`].
+    ///
+    pub fn skip_oversized_tags(&mut self, max_size: Option<usize>) {
+        self.inner.skip_oversized_tags(max_size)
+    }
+
+    ///
+    /// Sets a callback that is called for every skipped tag.  See [`TagIterator::on_skipped_tag()`].
+    ///
+    pub fn on_skipped_tag<F: FnMut(&SkippedTag) + Send + 'static>(&mut self, callback: F) {
+        self.inner.on_skipped_tag(callback)
+    }
+
+    ///
+    /// Configures the tag ids the iterator resynchronizes at after corrupted data.  See [`TagIterator::recover_at_tags()`].
+    ///
+    pub fn recover_at_tags(&mut self, tag_ids: &[u64]) {
+        let _active = ActiveSpec::enter(&self.spec);
+        let tags: Vec<DynTag> = tag_ids.iter().map(|id| DynTag::new(*id, DynValue::Raw(Vec::new()))).collect();
+        self.inner.recover_at_tags(&tags)
+    }
+
+    ///
+    /// Attempts to recover from a [`TagIteratorError::CorruptedFileData`] error.  See [`TagIterator::try_recover()`].
+    ///
+    pub fn try_recover(&mut self) -> Result<(), TagIteratorError> {
+        let _active = ActiveSpec::enter(&self.spec);
+        self.inner.try_recover()
+    }
+
+    ///
+    /// Replaces the underlying read stream, returning the previous one.  See [`TagIterator::reset()`].
+    ///
+    pub fn reset(&mut self, source: R) -> R {
+        self.inner.reset(source)
+    }
+
+    ///
+    /// Returns the byte offset of the last emitted tag.  See [`TagIterator::last_emitted_tag_offset()`].
+    ///
+    pub fn last_emitted_tag_offset(&self) -> usize {
+        self.inner.last_emitted_tag_offset()
+    }
+
+    ///
+    /// Returns the position and size of the last emitted tag.  See [`TagIterator::last_emitted_tag_span()`].
+    ///
+    pub fn last_emitted_tag_span(&self) -> TagSpan {
+        self.inner.last_emitted_tag_span()
+    }
+
+    ///
+    /// Returns the ids of the "Master" tags the last emitted tag is inside of.  See [`TagIterator::current_path()`].
+    ///
+    pub fn current_path(&self) -> &[u64] {
+        self.inner.current_path()
+    }
+
+    ///
+    /// Returns the names of the tags in [`Self::current_path()`], as given by [`DynSpecification::tag_name()`].
+    ///
+    pub fn current_path_names(&self) -> Vec<Option<&'static str>> {
+        self.inner.current_path().iter().map(|id| self.spec.tag_name(*id)).collect()
+    }
+
+    ///
+    /// Consumes self and returns the underlying read stream.
+    ///
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying read stream.
+    ///
+    pub fn get_mut(&mut self) -> &mut R {
+        self.inner.get_mut()
+    }
+
+    ///
+    /// Gets a reference to the underlying read stream.
+    ///
+    pub fn get_ref(&self) -> &R {
+        self.inner.get_ref()
+    }
+}
+
+impl<R: Read + Seek> DynTagIterator<R> {
+
+    ///
+    /// Seeks past skipped tags instead of reading them.  See [`TagIterator::seek_when_skipping()`].
+    ///
+    pub fn seek_when_skipping(&mut self) {
+        self.inner.seek_when_skipping()
+    }
+
+    ///
+    /// Creates a copy of this iterator reading from `source`.  See [`TagIterator::fork_with()`].
+    ///
+    pub fn fork_with(&mut self, source: R) -> Result<Self, TagIteratorError> {
+        Ok(DynTagIterator { inner: self.inner.fork_with(source)?, spec: self.spec.clone() })
+    }
+}
+
+impl<R: Read> Iterator for DynTagIterator<R> {
+    type Item = Result<DynTag, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let _active = ActiveSpec::enter(&self.spec);
+        self.inner.next()
+    }
+}
+
+///
+/// A [`TagWriter`] for a specification selected at runtime.
+///
+/// Tags are validated against the [`DynSpecification`] before being written: a [`DynTag`] whose [`DynValue`] does not match the data type the specification declares for its id is rejected with [`TagWriterError::TagDataMismatch`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::dynamic::{DynTag, DynTagWriter, DynValue, StaticSpec};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut writer = DynTagWriter::new(Vec::new(), StaticSpec::<EmptySpec>::shared());
+/// writer.write(&DynTag::new(0x81, DynValue::Binary(vec![0x01, 0x02]))).unwrap();
+/// assert_eq!(vec![0x81, 0x82, 0x01, 0x02], writer.into_inner().unwrap());
+/// ```
+///
+pub struct DynTagWriter<W: Write> {
+    inner: TagWriter<W>,
+    spec: Arc<dyn DynSpecification>,
+}
+
+impl<W: Write> DynTagWriter<W> {
+
+    ///
+    /// Returns a new [`DynTagWriter`] writing to `dest` according to `spec`.
+    ///
+    pub fn new(dest: W, spec: Arc<dyn DynSpecification>) -> Self {
+        DynTagWriter {
+            inner: TagWriter::new(dest),
+            spec,
+        }
+    }
+
+    ///
+    /// Gets the specification this writer validates with.
+    ///
+    pub fn spec(&self) -> &Arc<dyn DynSpecification> {
+        &self.spec
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagWriter`], e.g. to configure its flush policy.
+    ///
+    pub fn writer_mut(&mut self) -> &mut TagWriter<W> {
+        &mut self.inner
+    }
+
+    ///
+    /// Writes a tag to this instance's destination.  See [`TagWriter::write()`].
+    ///
+    /// ## Errors
+    ///
+    /// In addition to the errors returned by [`TagWriter::write()`], this returns [`TagWriterError::TagDataMismatch`] if `tag` (or any of its children) holds a value whose type does not match the specification.
+    ///
+    pub fn write(&mut self, tag: &DynTag) -> Result<(), TagWriterError> {
+        let _active = ActiveSpec::enter(&self.spec);
+        check_data_types(self.spec.as_ref(), tag)?;
+        self.inner.write(tag)
+    }
+
+    ///
+    /// Writes a tag at its location in the specification, starting and ending parents as needed.  See [`TagWriter::write_at_path()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::write()`].
+    ///
+    pub fn write_at_path(&mut self, tag: &DynTag) -> Result<(), TagWriterError> {
+        let _active = ActiveSpec::enter(&self.spec);
+        check_data_types(self.spec.as_ref(), tag)?;
+        self.inner.write_at_path(tag)
+    }
+
+    ///
+    /// Writes a tag with an unknown size.  See [`TagWriter::write_unknown_size()`].
+    ///
+    pub fn write_unknown_size(&mut self, tag: &DynTag) -> Result<(), TagWriterError> {
+        let _active = ActiveSpec::enter(&self.spec);
+        self.inner.write_unknown_size(tag)
+    }
+
+    ///
+    /// Starts a tag with a size that is already known.  See [`TagWriter::start_tag_with_size()`].
+    ///
+    pub fn start_tag_with_size(&mut self, tag: &DynTag, size: u64) -> Result<(), TagWriterError> {
+        let _active = ActiveSpec::enter(&self.spec);
+        self.inner.start_tag_with_size(tag, size)
+    }
+
+    ///
+    /// Writes raw tag data.  See [`TagWriter::write_raw()`].
+    ///
+    pub fn write_raw(&mut self, tag_id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        self.inner.write_raw(tag_id, data)
+    }
+
+    ///
+    /// Attempts to flush all completed tags to the destination.  See [`TagWriter::flush()`].
+    ///
+    pub fn flush(&mut self) -> Result<(), TagWriterError> {
+        self.inner.flush()
+    }
+
+    ///
+    /// Flushes and returns the underlying destination.  See [`TagWriter::into_inner()`].
+    ///
+    pub fn into_inner(self) -> Result<W, TagWriterError> {
+        self.inner.into_inner()
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying destination.
+    ///
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    ///
+    /// Gets a reference to the underlying destination.
+    ///
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+}
+
+fn check_data_types(spec: &dyn DynSpecification, tag: &DynTag) -> Result<(), TagWriterError> {
+    let expected = spec.tag_data_type(tag.id);
+    let matches = match expected {
+        None => matches!(tag.value, DynValue::Raw(_) | DynValue::Binary(_)),
+        Some(TagDataType::Binary) => matches!(tag.value, DynValue::Raw(_) | DynValue::Binary(_)),
+        Some(data_type) => tag.data_type() == Some(data_type),
+    };
+    if !matches {
+        return Err(TagWriterError::TagDataMismatch { tag_id: tag.id, expected });
+    }
+
+    if let DynValue::Master(Master::Full(children)) = &tag.value {
+        for child in children {
+            check_data_types(spec, child)?;
+        }
+    }
+    Ok(())
+}
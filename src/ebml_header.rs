@@ -0,0 +1,300 @@
+use std::io::{self, Read};
+
+use crate::error::{CorruptedFileError, HeaderError, TagIteratorError};
+use crate::ids;
+use crate::tag_iterator_util::EBMLSize;
+use crate::errors::tool::ToolError;
+use crate::tools;
+
+// Headers are tiny; anything larger than this is treated as corrupt rather than allocated
+const MAX_HEADER_SIZE: usize = 1024 * 64;
+
+///
+/// The documents a reader is able to interpret, for checking an [`EbmlHeader`].
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReaderSupport<'a> {
+
+    ///
+    /// The `DocType`s the reader understands (e.g. `["matroska", "webm"]`).  An empty list accepts any `DocType`.
+    ///
+    pub doc_types: &'a [&'a str],
+
+    ///
+    /// The highest `DocTypeReadVersion` the reader understands, or `None` to accept any version.
+    ///
+    pub doc_type_read_version: Option<u64>,
+}
+
+///
+/// The values from the EBML header at the start of a document.
+///
+/// Elements missing from the header take their default values from [RFC 8794][rfc8794].  The header of a document can be read on its own with [`Self::read()`], or collected while iterating with [`TagIterator::ebml_header()`](crate::TagIterator::ebml_header).
+///
+/// [rfc8794]: https://datatracker.ietf.org/doc/rfc8794/
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EbmlHeader {
+    pub version: u64,
+    pub read_version: u64,
+    pub max_id_length: u64,
+    pub max_size_length: u64,
+    pub doc_type: String,
+    pub doc_type_version: u64,
+    pub doc_type_read_version: u64,
+
+    ///
+    /// The number of bytes used by the header, including its own id and size.
+    ///
+    pub header_len: usize,
+}
+
+impl Default for EbmlHeader {
+
+    ///
+    /// Returns a header with the default value of every element and no `DocType`, as a starting point for [`TagWriter::write_ebml_header()`](crate::TagWriter::write_ebml_header).
+    ///
+    fn default() -> Self {
+        EbmlHeader {
+            version: 1,
+            read_version: 1,
+            max_id_length: 4,
+            max_size_length: 8,
+            doc_type: String::new(),
+            doc_type_version: 1,
+            doc_type_read_version: 1,
+            header_len: 0,
+        }
+    }
+}
+
+impl EbmlHeader {
+
+    ///
+    /// Reads the EBML header from the start of `source` and checks that the document can be read.
+    ///
+    /// This validates everything a reader should check before iterating through a document: that `EBMLReadVersion` is 1, that `EBMLMaxIDLength` and `EBMLMaxSizeLength` are values this crate can parse, and that the `DocType` and `DocTypeReadVersion` are allowed by `support`.  Exactly the bytes of the header are consumed, so a [`TagIterator`](crate::TagIterator) can be created on `source` afterwards to read the rest of the document.  The header is parsed without a specification, so this works even if `<TSpec>` does not define the EBML header elements.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`HeaderError`] describing why the document can't be read.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::header::{EbmlHeader, ReaderSupport};
+    ///
+    /// let data = [0x1a, 0x45, 0xdf, 0xa3, 0x87, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+    /// let support = ReaderSupport { doc_types: &["matroska", "webm"], doc_type_read_version: Some(2) };
+    /// let header = EbmlHeader::read(&mut &data[..], &support).unwrap();
+    /// assert_eq!("webm", header.doc_type);
+    /// assert_eq!(1, header.doc_type_read_version);
+    /// ```
+    ///
+    pub fn read<R: Read>(source: &mut R, support: &ReaderSupport<'_>) -> Result<Self, HeaderError> {
+        let header = EbmlHeader::parse(source)?;
+        header.check(support)?;
+        Ok(header)
+    }
+
+    ///
+    /// Checks that a document with this header can be read by a reader with `support`.  This is done by [`Self::read()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`HeaderError`] describing why the document can't be read.
+    ///
+    pub fn check(&self, support: &ReaderSupport<'_>) -> Result<(), HeaderError> {
+        if self.read_version != 1 {
+            return Err(HeaderError::UnsupportedEbmlVersion(self.read_version));
+        }
+        if !(4..=8).contains(&self.max_id_length) {
+            return Err(HeaderError::InvalidValue { tag_id: ids::EBML_MAX_ID_LENGTH, value: self.max_id_length });
+        }
+        if !(1..=8).contains(&self.max_size_length) {
+            return Err(HeaderError::InvalidValue { tag_id: ids::EBML_MAX_SIZE_LENGTH, value: self.max_size_length });
+        }
+        if self.doc_type.is_empty() {
+            return Err(HeaderError::MissingDocType);
+        }
+        if !support.doc_types.is_empty() && !support.doc_types.contains(&self.doc_type.as_str()) {
+            return Err(HeaderError::UnsupportedDocType(self.doc_type.clone()));
+        }
+        if self.doc_type_read_version == 0 || self.doc_type_read_version > self.doc_type_version {
+            return Err(HeaderError::InvalidValue { tag_id: ids::DOC_TYPE_READ_VERSION, value: self.doc_type_read_version });
+        }
+        if let Some(supported_version) = support.doc_type_read_version {
+            if self.doc_type_read_version > supported_version {
+                return Err(HeaderError::UnsupportedDocTypeVersion { read_version: self.doc_type_read_version, supported_version });
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns a header with the default value for every element, to be filled in by [`Self::apply()`].
+    ///
+    pub(crate) fn with_len(header_len: usize) -> Self {
+        EbmlHeader { header_len, ..Default::default() }
+    }
+
+    ///
+    /// Sets the value of a child of the header from the child's data.  Children that don't affect whether a document can be read are ignored.
+    ///
+    pub(crate) fn apply(&mut self, child_id: u64, value: &[u8]) -> Result<(), ToolError> {
+        match child_id {
+            ids::EBML_VERSION => self.version = tools::arr_to_u64(value)?,
+            ids::EBML_READ_VERSION => self.read_version = tools::arr_to_u64(value)?,
+            ids::EBML_MAX_ID_LENGTH => self.max_id_length = tools::arr_to_u64(value)?,
+            ids::EBML_MAX_SIZE_LENGTH => self.max_size_length = tools::arr_to_u64(value)?,
+            ids::DOC_TYPE_VERSION => self.doc_type_version = tools::arr_to_u64(value)?,
+            ids::DOC_TYPE_READ_VERSION => self.doc_type_read_version = tools::arr_to_u64(value)?,
+            ids::DOC_TYPE => {
+                // Strings may be padded with trailing zeros
+                let end = value.iter().rposition(|byte| *byte != 0).map_or(0, |last| last + 1);
+                self.doc_type = tools::arr_to_utf8(&value[..end])?;
+            },
+            // Void, CRC-32 and DocTypeExtension elements don't affect whether the document can be read
+            _ => {},
+        }
+        Ok(())
+    }
+
+    ///
+    /// Sets the values of the header from `data`, the data of the EBML element (which ends at [`Self::header_len`]).
+    ///
+    pub(crate) fn apply_children(&mut self, data: &[u8]) -> Result<(), HeaderError> {
+        let mut children = data;
+        while !children.is_empty() {
+            let child_start = self.header_len - children.len();
+            let mut child_position = child_start;
+            let child_id = read_header_id(&mut children, &mut child_position)?;
+            let child_size = match read_header_size(&mut children, &mut child_position, child_id)? {
+                EBMLSize::Known(size) if size <= children.len() => size,
+                _ => return Err(invalid_data(child_id, child_start)),
+            };
+            let (value, rest) = children.split_at(child_size);
+            children = rest;
+            self.apply(child_id, value).map_err(|problem| TagIteratorError::CorruptedTagData { position: child_start, tag_id: child_id, problem })?;
+        }
+        Ok(())
+    }
+
+    fn parse<R: Read>(source: &mut R) -> Result<Self, HeaderError> {
+        let mut position = 0;
+        let tag_id = read_header_id(source, &mut position)?;
+        if tag_id != ids::EBML {
+            return Err(HeaderError::MissingHeader { found_tag_id: tag_id });
+        }
+        let size = match read_header_size(source, &mut position, tag_id)? {
+            EBMLSize::Known(size) if size <= MAX_HEADER_SIZE => size,
+            _ => return Err(invalid_data(tag_id, 0)),
+        };
+
+        let mut data = vec![0; size];
+        source.read_exact(&mut data).map_err(|source| match source.kind() {
+            io::ErrorKind::UnexpectedEof => TagIteratorError::UnexpectedEOF { tag_start: 0, tag_id: Some(tag_id), tag_size: Some(size), partial_data: None },
+            _ => TagIteratorError::ReadError { source },
+        })?;
+
+        let mut header = EbmlHeader::with_len(position + size);
+        header.apply_children(&data)?;
+        Ok(header)
+    }
+}
+
+fn invalid_data(tag_id: u64, position: usize) -> HeaderError {
+    HeaderError::Read(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id, position }))
+}
+
+fn read_vint_bytes<R: Read>(source: &mut R, position: &mut usize) -> Result<Option<(u64, usize)>, HeaderError> {
+    let mut buffer = [0u8; 8];
+    let read = |source: &mut R, buffer: &mut [u8]| source.read_exact(buffer).map_err(|source| match source.kind() {
+        io::ErrorKind::UnexpectedEof => TagIteratorError::UnexpectedEOF { tag_start: *position, tag_id: None, tag_size: None, partial_data: None },
+        _ => TagIteratorError::ReadError { source },
+    });
+    read(source, &mut buffer[..1])?;
+    if buffer[0] == 0 {
+        return Ok(None);
+    }
+    let length = buffer[0].leading_zeros() as usize + 1;
+    read(source, &mut buffer[1..length])?;
+    *position += length;
+    Ok(tools::read_vint(&buffer[..length]).ok().flatten())
+}
+
+fn read_header_id<R: Read>(source: &mut R, position: &mut usize) -> Result<u64, HeaderError> {
+    let start = *position;
+    match read_vint_bytes(source, position)? {
+        Some((value, length)) => Ok(value + (1 << (7 * length))),
+        None => Err(invalid_data(0, start)),
+    }
+}
+
+fn read_header_size<R: Read>(source: &mut R, position: &mut usize, tag_id: u64) -> Result<EBMLSize, HeaderError> {
+    let start = *position;
+    match read_vint_bytes(source, position)? {
+        Some((value, length)) => Ok(EBMLSize::new(value, length)),
+        None => Err(invalid_data(tag_id, start)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(children: &[u8]) -> Vec<u8> {
+        let mut data = vec![0x1a, 0x45, 0xdf, 0xa3, 0x80 | children.len() as u8];
+        data.extend_from_slice(children);
+        data
+    }
+
+    fn read(data: &[u8]) -> Result<EbmlHeader, HeaderError> {
+        EbmlHeader::read(&mut &data[..], &ReaderSupport { doc_types: &["webm"], doc_type_read_version: Some(2) })
+    }
+
+    #[test]
+    fn reads_header_values() {
+        let mut data = header(&[
+            0x42, 0x86, 0x81, 0x01,
+            0x42, 0xf7, 0x81, 0x01,
+            0x42, 0xf2, 0x81, 0x04,
+            0x42, 0xf3, 0x81, 0x08,
+            0x42, 0x82, 0x86, b'w', b'e', b'b', b'm', 0x00, 0x00,
+            0x42, 0x87, 0x81, 0x04,
+            0x42, 0x85, 0x81, 0x02,
+            0xec, 0x81, 0x00,
+        ]);
+        let len = data.len();
+        data.extend_from_slice(&[0x18, 0x53, 0x80, 0x67]);
+
+        let mut source = &data[..];
+        let header = EbmlHeader::read(&mut source, &ReaderSupport::default()).unwrap();
+        assert_eq!(EbmlHeader {
+            version: 1,
+            read_version: 1,
+            max_id_length: 4,
+            max_size_length: 8,
+            doc_type: String::from("webm"),
+            doc_type_version: 4,
+            doc_type_read_version: 2,
+            header_len: len,
+        }, header);
+        assert_eq!(&[0x18, 0x53, 0x80, 0x67], source);
+    }
+
+    #[test]
+    fn rejects_unreadable_documents() {
+        assert!(matches!(read(&[0x18, 0x53, 0x80, 0x67, 0x80]), Err(HeaderError::MissingHeader { found_tag_id: 0x18538067 })));
+        assert!(matches!(read(&header(&[])), Err(HeaderError::MissingDocType)));
+        assert!(matches!(read(&header(&[0x42, 0x82, 0x81, b'x'])), Err(HeaderError::UnsupportedDocType(doc_type)) if doc_type == "x"));
+        assert!(matches!(read(&header(&[0x42, 0xf7, 0x81, 0x02, 0x42, 0x82, 0x81, b'x'])), Err(HeaderError::UnsupportedEbmlVersion(2))));
+        assert!(matches!(read(&header(&[0x42, 0xf3, 0x81, 0x09, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'])), Err(HeaderError::InvalidValue { tag_id: ids::EBML_MAX_SIZE_LENGTH, value: 9 })));
+        assert!(matches!(read(&header(&[0x42, 0xf2, 0x81, 0x02, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'])), Err(HeaderError::InvalidValue { tag_id: ids::EBML_MAX_ID_LENGTH, value: 2 })));
+        assert!(matches!(read(&header(&[0x42, 0x87, 0x81, 0x04, 0x42, 0x85, 0x81, 0x03, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'])), Err(HeaderError::UnsupportedDocTypeVersion { read_version: 3, supported_version: 2 })));
+        assert!(matches!(read(&header(&[0x42, 0x85, 0x81, 0x02, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'])), Err(HeaderError::InvalidValue { tag_id: ids::DOC_TYPE_READ_VERSION, value: 2 })));
+
+        assert!(matches!(read(&header(&[0x42, 0x82, 0x84, b'w'])), Err(HeaderError::Read(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: 0x4282, position: 5 })))));
+        assert!(matches!(read(&header(&[0x42, 0x82, 0x84, b'w'])[..6]), Err(HeaderError::Read(TagIteratorError::UnexpectedEOF { .. }))));
+    }
+}
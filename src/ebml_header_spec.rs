@@ -0,0 +1,229 @@
+use crate::ids;
+use crate::specs::{EbmlSpecification, EbmlTag, Master, PathPart, TagDataType};
+
+const EBML_PATH: &[PathPart] = &[PathPart::Id(ids::EBML)];
+const DOC_TYPE_EXTENSION_PATH: &[PathPart] = &[PathPart::Id(ids::EBML), PathPart::Id(ids::DOC_TYPE_EXTENSION)];
+const GLOBAL_PATH: &[PathPart] = &[PathPart::Global((Some(1), None))];
+
+///
+/// A specification containing only the elements of the EBML header, which are the same for every document type.
+///
+/// This can be used to read the header of any document with a [`TagIterator`](crate::TagIterator) or [`TagTree`](crate::TagTree), and to write a header with a [`TagWriter`](crate::TagWriter), without a specification for the rest of the document.  Elements that aren't part of the header are returned as [`EbmlHeaderSpec::RawTag`].
+///
+/// Custom specifications that don't define the header elements themselves can fall back to this one, e.g. by returning `EbmlHeaderSpec::get_tag_data_type(id)` and `EbmlHeaderSpec::get_path_by_id(id)` for ids they don't recognize.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::header::EbmlHeaderSpec;
+/// use ebml_iterable::specs::Master;
+///
+/// let data = vec![0x1a, 0x45, 0xdf, 0xa3, 0x87, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+/// let mut iter: TagIterator<_, EbmlHeaderSpec> = TagIterator::new(Cursor::new(data), &[EbmlHeaderSpec::Ebml(Master::Start)]);
+/// assert_eq!(EbmlHeaderSpec::Ebml(Master::Full(vec![EbmlHeaderSpec::DocType(String::from("webm"))])), iter.next().unwrap().unwrap());
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum EbmlHeaderSpec {
+    Ebml(Master<EbmlHeaderSpec>),
+    EbmlVersion(u64),
+    EbmlReadVersion(u64),
+    EbmlMaxIdLength(u64),
+    EbmlMaxSizeLength(u64),
+    DocType(String),
+    DocTypeVersion(u64),
+    DocTypeReadVersion(u64),
+    DocTypeExtension(Master<EbmlHeaderSpec>),
+    DocTypeExtensionName(String),
+    DocTypeExtensionVersion(u64),
+    Crc32(Vec<u8>),
+    Void(Vec<u8>),
+
+    ///
+    /// Any element that isn't part of the EBML header, with its id and unparsed data.
+    ///
+    RawTag(u64, Vec<u8>),
+}
+
+impl EbmlSpecification<EbmlHeaderSpec> for EbmlHeaderSpec {
+    fn get_tag_data_type(id: u64) -> Option<TagDataType> {
+        match id {
+            ids::EBML | ids::DOC_TYPE_EXTENSION => Some(TagDataType::Master),
+            ids::EBML_VERSION | ids::EBML_READ_VERSION | ids::EBML_MAX_ID_LENGTH | ids::EBML_MAX_SIZE_LENGTH
+                | ids::DOC_TYPE_VERSION | ids::DOC_TYPE_READ_VERSION | ids::DOC_TYPE_EXTENSION_VERSION => Some(TagDataType::UnsignedInt),
+            ids::DOC_TYPE | ids::DOC_TYPE_EXTENSION_NAME => Some(TagDataType::Utf8),
+            ids::CRC32 | ids::VOID => Some(TagDataType::Binary),
+            _ => None,
+        }
+    }
+
+    fn get_path_by_id(id: u64) -> &'static [PathPart] {
+        match id {
+            ids::DOC_TYPE_EXTENSION_NAME | ids::DOC_TYPE_EXTENSION_VERSION => DOC_TYPE_EXTENSION_PATH,
+            ids::CRC32 | ids::VOID => GLOBAL_PATH,
+            ids::EBML => &[],
+            _ if EbmlHeaderSpec::get_tag_data_type(id).is_some() => EBML_PATH,
+            _ => &[],
+        }
+    }
+
+    fn get_tag_name(id: u64) -> Option<&'static str> {
+        ids::EBML_NAMES.iter().find(|(tag_id, _)| *tag_id == id).map(|(_, name)| *name)
+    }
+
+    fn is_ascii_string(id: u64) -> bool {
+        matches!(id, ids::DOC_TYPE | ids::DOC_TYPE_EXTENSION_NAME)
+    }
+
+    fn get_unsigned_int_tag(id: u64, data: u64) -> Option<EbmlHeaderSpec> {
+        match id {
+            ids::EBML_VERSION => Some(EbmlHeaderSpec::EbmlVersion(data)),
+            ids::EBML_READ_VERSION => Some(EbmlHeaderSpec::EbmlReadVersion(data)),
+            ids::EBML_MAX_ID_LENGTH => Some(EbmlHeaderSpec::EbmlMaxIdLength(data)),
+            ids::EBML_MAX_SIZE_LENGTH => Some(EbmlHeaderSpec::EbmlMaxSizeLength(data)),
+            ids::DOC_TYPE_VERSION => Some(EbmlHeaderSpec::DocTypeVersion(data)),
+            ids::DOC_TYPE_READ_VERSION => Some(EbmlHeaderSpec::DocTypeReadVersion(data)),
+            ids::DOC_TYPE_EXTENSION_VERSION => Some(EbmlHeaderSpec::DocTypeExtensionVersion(data)),
+            _ => None,
+        }
+    }
+
+    fn get_signed_int_tag(_id: u64, _data: i64) -> Option<EbmlHeaderSpec> {
+        None
+    }
+
+    fn get_utf8_tag(id: u64, data: String) -> Option<EbmlHeaderSpec> {
+        match id {
+            ids::DOC_TYPE => Some(EbmlHeaderSpec::DocType(data)),
+            ids::DOC_TYPE_EXTENSION_NAME => Some(EbmlHeaderSpec::DocTypeExtensionName(data)),
+            _ => None,
+        }
+    }
+
+    fn get_binary_tag(id: u64, data: &[u8]) -> Option<EbmlHeaderSpec> {
+        match id {
+            ids::CRC32 => Some(EbmlHeaderSpec::Crc32(data.to_vec())),
+            ids::VOID => Some(EbmlHeaderSpec::Void(data.to_vec())),
+            _ => None,
+        }
+    }
+
+    fn get_float_tag(_id: u64, _data: f64) -> Option<EbmlHeaderSpec> {
+        None
+    }
+
+    fn get_master_tag(id: u64, data: Master<EbmlHeaderSpec>) -> Option<EbmlHeaderSpec> {
+        match id {
+            ids::EBML => Some(EbmlHeaderSpec::Ebml(data)),
+            ids::DOC_TYPE_EXTENSION => Some(EbmlHeaderSpec::DocTypeExtension(data)),
+            _ => None,
+        }
+    }
+
+    fn get_raw_tag(id: u64, data: &[u8]) -> EbmlHeaderSpec {
+        EbmlHeaderSpec::RawTag(id, data.to_vec())
+    }
+}
+
+impl EbmlTag<EbmlHeaderSpec> for EbmlHeaderSpec {
+    fn get_id(&self) -> u64 {
+        match self {
+            EbmlHeaderSpec::Ebml(_) => ids::EBML,
+            EbmlHeaderSpec::EbmlVersion(_) => ids::EBML_VERSION,
+            EbmlHeaderSpec::EbmlReadVersion(_) => ids::EBML_READ_VERSION,
+            EbmlHeaderSpec::EbmlMaxIdLength(_) => ids::EBML_MAX_ID_LENGTH,
+            EbmlHeaderSpec::EbmlMaxSizeLength(_) => ids::EBML_MAX_SIZE_LENGTH,
+            EbmlHeaderSpec::DocType(_) => ids::DOC_TYPE,
+            EbmlHeaderSpec::DocTypeVersion(_) => ids::DOC_TYPE_VERSION,
+            EbmlHeaderSpec::DocTypeReadVersion(_) => ids::DOC_TYPE_READ_VERSION,
+            EbmlHeaderSpec::DocTypeExtension(_) => ids::DOC_TYPE_EXTENSION,
+            EbmlHeaderSpec::DocTypeExtensionName(_) => ids::DOC_TYPE_EXTENSION_NAME,
+            EbmlHeaderSpec::DocTypeExtensionVersion(_) => ids::DOC_TYPE_EXTENSION_VERSION,
+            EbmlHeaderSpec::Crc32(_) => ids::CRC32,
+            EbmlHeaderSpec::Void(_) => ids::VOID,
+            EbmlHeaderSpec::RawTag(id, _) => *id,
+        }
+    }
+
+    fn as_unsigned_int(&self) -> Option<&u64> {
+        match self {
+            EbmlHeaderSpec::EbmlVersion(val) | EbmlHeaderSpec::EbmlReadVersion(val) | EbmlHeaderSpec::EbmlMaxIdLength(val)
+                | EbmlHeaderSpec::EbmlMaxSizeLength(val) | EbmlHeaderSpec::DocTypeVersion(val) | EbmlHeaderSpec::DocTypeReadVersion(val)
+                | EbmlHeaderSpec::DocTypeExtensionVersion(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_signed_int(&self) -> Option<&i64> {
+        None
+    }
+
+    fn as_utf8(&self) -> Option<&str> {
+        match self {
+            EbmlHeaderSpec::DocType(val) | EbmlHeaderSpec::DocTypeExtensionName(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_binary(&self) -> Option<&[u8]> {
+        match self {
+            EbmlHeaderSpec::Crc32(val) | EbmlHeaderSpec::Void(val) | EbmlHeaderSpec::RawTag(_, val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<&f64> {
+        None
+    }
+
+    fn as_master(&self) -> Option<&Master<EbmlHeaderSpec>> {
+        match self {
+            EbmlHeaderSpec::Ebml(val) | EbmlHeaderSpec::DocTypeExtension(val) => Some(val),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{TagIterator, TagWriter};
+
+    use super::*;
+
+    #[test]
+    fn round_trip_header() {
+        let header = EbmlHeaderSpec::Ebml(Master::Full(vec![
+            EbmlHeaderSpec::EbmlVersion(1),
+            EbmlHeaderSpec::EbmlReadVersion(1),
+            EbmlHeaderSpec::DocType(String::from("matroska")),
+            EbmlHeaderSpec::DocTypeVersion(4),
+            EbmlHeaderSpec::DocTypeReadVersion(2),
+            EbmlHeaderSpec::DocTypeExtension(Master::Full(vec![
+                EbmlHeaderSpec::DocTypeExtensionName(String::from("extension")),
+                EbmlHeaderSpec::DocTypeExtensionVersion(1),
+            ])),
+            EbmlHeaderSpec::Void(vec![0; 4]),
+        ]));
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&header).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, EbmlHeaderSpec> = TagIterator::new(Cursor::new(data), &[EbmlHeaderSpec::Ebml(Master::Start)]);
+        assert_eq!(header, iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn covers_header_ids() {
+        for (id, name) in ids::EBML_NAMES {
+            assert!(EbmlHeaderSpec::get_tag_data_type(*id).is_some(), "Missing element {}", name);
+            assert_eq!(Some(*name), EbmlHeaderSpec::get_tag_name(*id));
+        }
+        assert_eq!(None, EbmlHeaderSpec::get_tag_data_type(ids::SEGMENT));
+        assert_eq!(ids::SEGMENT, EbmlHeaderSpec::get_raw_tag(ids::SEGMENT, &[]).get_id());
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::ops::RangeInclusive;
+
+use roxmltree::{Document, Node};
+
+use crate::dyn_spec::DynSpecification;
+use crate::dynamic::DynTag;
+use crate::error::SchemaError;
+use crate::header::EbmlHeaderSpec;
+use crate::specs::{EbmlSpecification, PathPart, TagDataType};
+use crate::validate::{Validator, ValueRange};
+
+///
+/// A single element of an EBML Schema.
+///
+#[derive(Clone, Debug)]
+struct SchemaElement {
+    name: &'static str,
+    data_type: TagDataType,
+    path: &'static [PathPart],
+    ascii: bool,
+    min_version: Option<u64>,
+    max_occurs: Option<usize>,
+    range: Option<ValueRange>,
+    mandatory_children: &'static [u64],
+}
+
+///
+/// One part of an element's path, before parent names are resolved to ids.
+///
+enum PathAtom<'a> {
+    Name { name: &'a str, recursive: bool },
+    Global((Option<u64>, Option<u64>)),
+}
+
+///
+/// A specification loaded at runtime from an [EBML Schema](https://www.rfc-editor.org/rfc/rfc8794.html#name-ebml-schema).
+///
+/// EBML Schemas are the XML documents that [RFC 8794](https://www.rfc-editor.org/rfc/rfc8794.html) uses to define document types - the Matroska specification is published as one.  Loading a schema with [`Self::from_ebml_schema()`] allows tools to support any document type without recompiling, by reading and writing through [`DynTagIterator`](crate::dynamic::DynTagIterator) and [`DynTagWriter`](crate::dynamic::DynTagWriter), and checking documents with the [`Validator`] returned by [`Self::validator()`].
+///
+/// The elements of the EBML header, `Void` and `CRC-32` are part of every document type, so they are included even if the schema doesn't define them (see [`EbmlHeaderSpec`]).
+///
+/// [`DynSpecification`] hands out names, paths and mandatory children as `'static` references, so these are leaked when a schema is loaded.  Schemas are expected to be loaded once and kept for the life of the program, rather than loaded for each document.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use std::sync::Arc;
+/// use ebml_iterable::dynamic::{DynamicSpec, DynSpecification, DynTagIterator, DynValue};
+/// use ebml_iterable::specs::TagDataType;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let schema = r#"<?xml version="1.0" encoding="utf-8"?>
+/// <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="example" version="1">
+///   <element name="Document" path="\Document" id="0x1A45DFA0" type="master"/>
+///   <element name="Title" path="\Document\Title" id="0x4100" type="utf-8" minOccurs="1"/>
+/// </EBMLSchema>"#;
+/// let spec = DynamicSpec::from_ebml_schema(schema)?;
+/// assert_eq!("example", spec.doc_type());
+/// assert_eq!(Some(TagDataType::Utf8), spec.tag_data_type(0x4100));
+/// assert_eq!(&[0x4100], spec.mandatory_children(0x1a45dfa0));
+///
+/// let data = vec![0x1a, 0x45, 0xdf, 0xa0, 0x85, 0x41, 0x00, 0x82, b'h', b'i'];
+/// let iter = DynTagIterator::new(Cursor::new(data), Arc::new(spec), &[]);
+/// let tags = iter.collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(DynValue::Utf8(String::from("hi")), tags[1].value);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct DynamicSpec {
+    doc_type: String,
+    version: Option<u64>,
+    elements: BTreeMap<u64, SchemaElement>,
+}
+
+impl DynamicSpec {
+
+    ///
+    /// Loads a specification from the text of an EBML Schema.
+    ///
+    /// Each `element` of the schema is read for its `name`, `path`, `id` and `type`, as well as the optional `minOccurs`, `maxOccurs`, `range`, `length`, `default`, `minver` and `recursive` attributes.  Elements that occur at least once and have no `default` are mandatory children of their parent (see [`DynSpecification::mandatory_children()`]).  Recursive elements, marked with `recursive="1"` or a `+` in their path, are allowed at any depth below their first parent, the same way [`EbmlSpecification`] paths handle them (e.g. `Segment/Chapters/EditionEntry/(0-)/ChapterAtom`).  Documentation and restrictions in the schema are ignored.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`SchemaError`] if the schema isn't valid XML, is missing the `EBMLSchema` root, has an element with missing or invalid attributes, or defines the same element name or id more than once.
+    ///
+    pub fn from_ebml_schema(xml: &str) -> Result<Self, SchemaError> {
+        let document = Document::parse(xml).map_err(|err| SchemaError::InvalidXml(err.to_string()))?;
+        let root = document.root_element();
+        if !root.has_tag_name("EBMLSchema") {
+            return Err(SchemaError::MissingSchema);
+        }
+        let doc_type = attribute(root, "EBMLSchema", "docType")?.to_string();
+        let version = root.attribute("version").map(|version| parse_attribute(version, "EBMLSchema", "version", |value| value.parse().ok())).transpose()?;
+
+        let nodes: Vec<Node> = root.children().filter(|node| node.has_tag_name("element")).collect();
+        let mut ids: BTreeMap<&str, u64> = BTreeMap::new();
+        for (index, node) in nodes.iter().enumerate() {
+            let name = node.attribute("name").ok_or_else(|| SchemaError::MissingAttribute { element: format!("element {index}"), attribute: "name" })?;
+            let id = attribute(*node, name, "id")?;
+            let id = parse_attribute(id, name, "id", |value| u64::from_str_radix(value.trim_start_matches("0x").trim_start_matches("0X"), 16).ok())?;
+            if ids.insert(name, id).is_some() {
+                return Err(SchemaError::DuplicateName(String::from(name)));
+            }
+        }
+
+        let mut elements = BTreeMap::new();
+        let mut mandatory: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for node in nodes {
+            let name = node.attribute("name").unwrap_or_default();
+            let id = ids[name];
+            let (data_type, ascii) = match attribute(node, name, "type")? {
+                "master" => (TagDataType::Master, false),
+                "uinteger" => (TagDataType::UnsignedInt, false),
+                "integer" => (TagDataType::Integer, false),
+                "float" => (TagDataType::Float, false),
+                "date" => (TagDataType::Date, false),
+                "string" => (TagDataType::Utf8, true),
+                "utf-8" => (TagDataType::Utf8, false),
+                "binary" => (TagDataType::Binary, false),
+                other => return Err(SchemaError::InvalidAttribute { element: name.to_string(), attribute: "type", value: other.to_string() }),
+            };
+
+            let path_attr = attribute(node, name, "path")?;
+            let mut atoms = parse_path(path_attr).ok_or_else(|| SchemaError::InvalidAttribute { element: name.to_string(), attribute: "path", value: path_attr.to_string() })?;
+            let recursive = match atoms.pop() {
+                Some(PathAtom::Name { name: last, recursive }) if last == name => recursive || matches!(node.attribute("recursive"), Some("1") | Some("true")),
+                _ => return Err(SchemaError::InvalidAttribute { element: name.to_string(), attribute: "path", value: path_attr.to_string() }),
+            };
+
+            let mut path = Vec::with_capacity(atoms.len() + 1);
+            let mut parent = None;
+            for atom in atoms {
+                match atom {
+                    PathAtom::Name { name: parent_name, recursive } => {
+                        let parent_id = *ids.get(parent_name).ok_or_else(|| SchemaError::UnknownParent { element: name.to_string(), parent: parent_name.to_string() })?;
+                        // Children of recursive elements can be at any depth below the recursive element's own parent
+                        path.push(if recursive { PathPart::Global((Some(1), None)) } else { PathPart::Id(parent_id) });
+                        parent = Some(parent_id);
+                    },
+                    PathAtom::Global(occurrences) => {
+                        path.push(PathPart::Global(occurrences));
+                        parent = None;
+                    },
+                }
+            }
+            if recursive {
+                path.push(PathPart::Global((Some(0), None)));
+            }
+
+            let min_occurs = node.attribute("minOccurs").map(|value| parse_attribute(value, name, "minOccurs", |value| value.parse::<usize>().ok())).transpose()?.unwrap_or(0);
+            if let Some(parent) = parent.filter(|_| min_occurs > 0 && node.attribute("default").is_none()) {
+                mandatory.entry(parent).or_default().push(id);
+            }
+
+            let range = match (data_type, node.attribute("range"), node.attribute("length")) {
+                (TagDataType::Utf8 | TagDataType::Binary, _, Some(length)) => parse_range(length, 0..=usize::MAX, |value| value.parse().ok(), |value| value.checked_add(1), |value| value.checked_sub(1)).map(ValueRange::Length),
+                (TagDataType::UnsignedInt, Some(range), _) if range.trim() == "not 0" => Some(ValueRange::Unsigned(1..=u64::MAX)),
+                (TagDataType::UnsignedInt, Some(range), _) => parse_range(range, 0..=u64::MAX, parse_int, |value| value.checked_add(1), |value| value.checked_sub(1)).map(ValueRange::Unsigned),
+                (TagDataType::Integer | TagDataType::Date, Some(range), _) => parse_range(range, i64::MIN..=i64::MAX, parse_int, |value| value.checked_add(1), |value| value.checked_sub(1)).map(ValueRange::Signed),
+                (TagDataType::Float, Some(range), _) => parse_range(range, f64::NEG_INFINITY..=f64::INFINITY, parse_float, |value| Some(next_up(value)), |value| Some(next_down(value))).map(ValueRange::Float),
+                _ => None,
+            };
+
+            let element = SchemaElement {
+                name: Box::leak(name.to_string().into_boxed_str()),
+                data_type,
+                path: Box::leak(path.into_boxed_slice()),
+                ascii,
+                min_version: node.attribute("minver").map(|value| parse_attribute(value, name, "minver", |value| value.parse().ok())).transpose()?,
+                max_occurs: node.attribute("maxOccurs").map(|value| parse_attribute(value, name, "maxOccurs", |value| value.parse().ok())).transpose()?,
+                range,
+                mandatory_children: &[],
+            };
+            if elements.insert(id, element).is_some() {
+                return Err(SchemaError::DuplicateId(id));
+            }
+        }
+
+        for (parent, children) in mandatory {
+            if let Some(element) = elements.get_mut(&parent) {
+                element.mandatory_children = Box::leak(children.into_boxed_slice());
+            }
+        }
+        Ok(DynamicSpec { doc_type, version, elements })
+    }
+
+    ///
+    /// Gets the `docType` the schema defines, e.g. `"matroska"`.
+    ///
+    pub fn doc_type(&self) -> &str {
+        &self.doc_type
+    }
+
+    ///
+    /// Gets the `version` of the document type the schema defines, if it declares one.
+    ///
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    ///
+    /// Gets the id of the schema element named `name`.
+    ///
+    pub fn id_by_name(&self, name: &str) -> Option<u64> {
+        self.elements.iter().find(|(_, element)| element.name == name).map(|(id, _)| *id)
+    }
+
+    ///
+    /// Returns a [`Validator`] for documents of this type, with the `maxOccurs`, `range` and `length` rules of the schema already set.
+    ///
+    /// Use [`Validator::validate_with_spec()`] to check documents with it, passing this specification.  Ranges that can't be expressed as a single interval (e.g. lists of values) are not checked.
+    ///
+    pub fn validator(&self) -> Validator<DynTag> {
+        let mut validator = Validator::new();
+        for (id, element) in &self.elements {
+            if let Some(max) = element.max_occurs {
+                validator.set_max_occurs(*id, max);
+            }
+            if let Some(range) = &element.range {
+                validator.set_range(*id, range.clone());
+            }
+        }
+        validator
+    }
+}
+
+impl DynSpecification for DynamicSpec {
+    fn tag_data_type(&self, id: u64) -> Option<TagDataType> {
+        self.elements.get(&id).map(|element| element.data_type).or_else(|| EbmlHeaderSpec::get_tag_data_type(id))
+    }
+
+    fn path_by_id(&self, id: u64) -> &'static [PathPart] {
+        self.elements.get(&id).map_or_else(|| EbmlHeaderSpec::get_path_by_id(id), |element| element.path)
+    }
+
+    fn min_version(&self, id: u64) -> Option<u64> {
+        self.elements.get(&id).and_then(|element| element.min_version)
+    }
+
+    fn mandatory_children(&self, id: u64) -> &'static [u64] {
+        self.elements.get(&id).map_or(&[], |element| element.mandatory_children)
+    }
+
+    fn tag_name(&self, id: u64) -> Option<&'static str> {
+        self.elements.get(&id).map(|element| element.name).or_else(|| EbmlHeaderSpec::get_tag_name(id))
+    }
+
+    fn is_ascii_string(&self, id: u64) -> bool {
+        self.elements.get(&id).map_or_else(|| EbmlHeaderSpec::is_ascii_string(id), |element| element.ascii)
+    }
+}
+
+fn attribute<'a>(node: Node<'a, '_>, element: &str, name: &'static str) -> Result<&'a str, SchemaError> {
+    node.attribute(name).ok_or_else(|| SchemaError::MissingAttribute { element: element.to_string(), attribute: name })
+}
+
+fn parse_attribute<T>(value: &str, element: &str, name: &'static str, parse: impl FnOnce(&str) -> Option<T>) -> Result<T, SchemaError> {
+    parse(value).ok_or_else(|| SchemaError::InvalidAttribute { element: element.to_string(), attribute: name, value: value.to_string() })
+}
+
+///
+/// Splits an EBML path such as `\Segment\(1-\)Tags\+SimpleTag` into its parts, or returns [`None`] if the path is invalid.
+///
+fn parse_path(path: &str) -> Option<Vec<PathAtom<'_>>> {
+    let mut rest = path.strip_prefix('\\')?;
+    let mut atoms = Vec::new();
+    while !rest.is_empty() {
+        if let Some(placeholder) = rest.strip_prefix('(') {
+            let end = placeholder.find("\\)")?;
+            let (min, max) = placeholder[..end].split_once('-')?;
+            let parse = |value: &str| if value.is_empty() { Some(None) } else { value.parse().ok().map(Some) };
+            atoms.push(PathAtom::Global((parse(min)?, parse(max)?)));
+            rest = &placeholder[(end + 2)..];
+        } else {
+            let end = rest.find('\\').unwrap_or(rest.len());
+            let (name, recursive) = match rest[..end].strip_prefix('+') {
+                Some(name) => (name, true),
+                None => (&rest[..end], false),
+            };
+            if name.is_empty() {
+                return None;
+            }
+            atoms.push(PathAtom::Name { name, recursive });
+            rest = rest[end..].strip_prefix('\\').unwrap_or_default();
+        }
+    }
+    Some(atoms)
+}
+
+///
+/// Parses a schema `range` or `length` made of a single interval (`1-8`, `>= 1`, `< 0x1p+0`, ...) or value.  Open ended intervals are limited by `bounds`, and exclusive bounds are converted to inclusive ones through `after` and `before`.
+///
+fn parse_range<T: Copy>(range: &str, bounds: RangeInclusive<T>, parse: impl Fn(&str) -> Option<T>, after: impl Fn(T) -> Option<T>, before: impl Fn(T) -> Option<T>) -> Option<RangeInclusive<T>> {
+    let range = range.trim();
+    if let Some(min) = range.strip_prefix(">=") {
+        return Some(parse(min.trim())?..=*bounds.end());
+    }
+    if let Some(min) = range.strip_prefix('>') {
+        return Some(after(parse(min.trim())?)?..=*bounds.end());
+    }
+    if let Some(max) = range.strip_prefix("<=") {
+        return Some(*bounds.start()..=parse(max.trim())?);
+    }
+    if let Some(max) = range.strip_prefix('<') {
+        return Some(*bounds.start()..=before(parse(max.trim())?)?);
+    }
+    if let Some(value) = parse(range) {
+        return Some(value..=value);
+    }
+    // The separator is the first '-' that doesn't start a negative number or exponent
+    (1..range.len()).filter(|index| range.as_bytes()[*index] == b'-' && !matches!(range.as_bytes()[index - 1], b'p' | b'P' | b'e' | b'E')).find_map(|index| {
+        Some(parse(range[..index].trim())?..=parse(range[(index + 1)..].trim())?)
+    })
+}
+
+fn parse_int<T: TryFrom<i128>>(value: &str) -> Option<T> {
+    let value = match value.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => value.parse().ok()?,
+    };
+    T::try_from(value).ok()
+}
+
+///
+/// Parses a decimal or hexadecimal (e.g. `0x1.8p+1`) floating point number.
+///
+fn parse_float(value: &str) -> Option<f64> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => {
+            let (mantissa, exponent) = hex.split_once(['p', 'P']).unwrap_or((hex, "0"));
+            let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+            let digits = u64::from_str_radix(&[whole, fraction].concat(), 16).ok()?;
+            let exponent: i32 = exponent.trim_start_matches('+').parse().ok()?;
+            digits as f64 * 2f64.powi(exponent - 4 * fraction.len() as i32)
+        },
+        None => value.parse().ok()?,
+    };
+    Some(if negative { -parsed } else { parsed })
+}
+
+///
+/// Gets the smallest float greater than `value`, like `f64::next_up()` (which is newer than this crate's minimum Rust version).
+///
+fn next_up(value: f64) -> f64 {
+    if value.is_nan() || value == f64::INFINITY {
+        value
+    } else if value == 0.0 {
+        f64::from_bits(1)
+    } else if value > 0.0 {
+        f64::from_bits(value.to_bits() + 1)
+    } else {
+        f64::from_bits(value.to_bits() - 1)
+    }
+}
+
+///
+/// Gets the largest float less than `value`, like `f64::next_down()`.
+///
+fn next_down(value: f64) -> f64 {
+    -next_up(-value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_down, next_up, parse_float, parse_int, parse_path, parse_range, PathAtom};
+
+    #[test]
+    fn paths() {
+        let atoms = parse_path("\\Segment\\(1-\\)Tags\\+SimpleTag").unwrap();
+        assert!(matches!(atoms[0], PathAtom::Name { name: "Segment", recursive: false }));
+        assert!(matches!(atoms[1], PathAtom::Global((Some(1), None))));
+        assert!(matches!(atoms[2], PathAtom::Name { name: "Tags", recursive: false }));
+        assert!(matches!(atoms[3], PathAtom::Name { name: "SimpleTag", recursive: true }));
+        assert!(matches!(parse_path("\\(-\\)Void").unwrap()[..], [PathAtom::Global((None, None)), PathAtom::Name { name: "Void", .. }]));
+        assert!(parse_path("Segment").is_none());
+        assert!(parse_path("\\Segment\\\\Cluster").is_none());
+    }
+
+    #[test]
+    fn ranges() {
+        let next = |value: u64| value.checked_add(1);
+        let prev = |value: u64| value.checked_sub(1);
+        assert_eq!(Some(1..=8), parse_range("1-8", 0..=u64::MAX, parse_int, next, prev));
+        assert_eq!(Some(1..=u64::MAX), parse_range("> 0", 0..=u64::MAX, parse_int, next, prev));
+        assert_eq!(Some(0..=0xff), parse_range("<= 0xff", 0..=u64::MAX, parse_int, next, prev));
+        assert_eq!(Some(4..=4), parse_range("4", 0..=u64::MAX, parse_int, next, prev));
+        assert_eq!(None, parse_range("1,3", 0..=u64::MAX, parse_int, next, prev));
+        assert_eq!(Some(-1..=1), parse_range("-1-1", i64::MIN..=i64::MAX, parse_int, |value: i64| value.checked_add(1), |value: i64| value.checked_sub(1)));
+
+        assert_eq!(Some(1.5), parse_float("0x1.8p+0"));
+        assert_eq!(Some(-4.0), parse_float("-0x1p2"));
+        let range = parse_range("> 0x0p+0", f64::NEG_INFINITY..=f64::INFINITY, parse_float, |value| Some(next_up(value)), |value| Some(next_down(value))).unwrap();
+        assert!(!range.contains(&0.0) && range.contains(&f64::MIN_POSITIVE) && range.contains(&f64::INFINITY));
+        let range = parse_range("< -0x1p+0", f64::NEG_INFINITY..=f64::INFINITY, parse_float, |value| Some(next_up(value)), |value| Some(next_down(value))).unwrap();
+        assert!(!range.contains(&-1.0) && range.contains(&-1.0000000000000002) && range.contains(&f64::NEG_INFINITY));
+        assert_eq!(f64::from_bits(1), next_up(-0.0));
+        assert_eq!(-0.9999999999999999, next_up(-1.0));
+        assert_eq!(-f64::from_bits(1), next_down(0.0));
+    }
+}
@@ -1,21 +1,25 @@
-use std::fmt;
-use std::error::Error;
+use core::fmt;
+use core::error::Error;
 
 pub mod tool {
     use super::fmt;
     use super::Error;
 
-    use std::string::FromUtf8Error;
+    use alloc::string::FromUtf8Error;
+    use alloc::vec::Vec;
 
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     pub enum ToolError {
         ReadVintOverflow,
         WriteVintOverflow(u64),
         WriteSignedVintOverflow(i64),
+        InvalidVintWidth(usize),
         ReadU64Overflow(Vec<u8>),
         ReadI64Overflow(Vec<u8>),
         ReadF64Mismatch(Vec<u8>),
-        FromUtf8Error(Vec<u8>, FromUtf8Error)
+        ReadDateMismatch(Vec<u8>),
+        FromUtf8Error(Vec<u8>, FromUtf8Error),
+        InvalidAscii(Vec<u8>),
     }
 
     impl fmt::Display for ToolError {
@@ -24,10 +28,13 @@ pub mod tool {
                 ToolError::ReadVintOverflow => write!(f, "Unrepresentable Vint size encountered."),
                 ToolError::WriteVintOverflow(val) => write!(f, "Value too large to be written as a vint: {val}"),
                 ToolError::WriteSignedVintOverflow(val) => write!(f, "Value outside range to be written as a vint: {val}"),
+                ToolError::InvalidVintWidth(width) => write!(f, "Vints cannot be {width} bytes wide"),
                 ToolError::ReadU64Overflow(arr) => write!(f, "Could not read unsigned int from array: {arr:?}"),
                 ToolError::ReadI64Overflow(arr) => write!(f, "Could not read int from array: {arr:?}"),
                 ToolError::ReadF64Mismatch(arr) => write!(f, "Could not read float from array: {arr:?}"),
+                ToolError::ReadDateMismatch(arr) => write!(f, "Could not read date from array: {arr:?}"),
                 ToolError::FromUtf8Error(arr, _source) => write!(f, "Could not read utf8 data: {arr:?}"),
+                ToolError::InvalidAscii(arr) => write!(f, "Could not read ascii string data: {arr:?}"),
             }
         }
     }
@@ -46,12 +53,15 @@ pub mod tag_iterator {
     use super::fmt;
     use super::Error;
     use super::tool::ToolError;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
     use std::io;
 
     ///
     /// Errors that indicate file data is corrupted.
     /// 
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     pub enum CorruptedFileError {
 
         ///
@@ -89,6 +99,11 @@ pub mod tag_iterator {
         /// 
         HierarchyError{
 
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
             ///
             /// The id of the tag that was found.
             /// 
@@ -102,6 +117,8 @@ pub mod tag_iterator {
 
         ///
         /// An error indicating the reader found a child element with incorrect sizing.
+        ///
+        /// This error occurs if the declared size of an element extends beyond the declared end of one of its parents.  Continuing to read such data would attribute elements to the wrong parent.
         /// 
         OversizedChildElement { 
             
@@ -118,7 +135,224 @@ pub mod tag_iterator {
             ///
             /// The size of the tag that was found.
             /// 
-            size: usize 
+            size: usize,
+
+            ///
+            /// The position at which the element's data would end.
+            ///
+            end: usize,
+
+            ///
+            /// The id of the innermost parent element that the element extends beyond.
+            ///
+            parent_id: u64,
+
+            ///
+            /// The position of the parent element.
+            ///
+            parent_position: usize,
+
+            ///
+            /// The position at which the parent element's data ends.
+            ///
+            parent_end: usize,
+        },
+
+        ///
+        /// An error indicating a "master" element ended without a child that the specification requires.
+        ///
+        /// This error is only returned by iterators in strict mode.
+        ///
+        MissingChildElement {
+
+            ///
+            /// The position of the element.  For elements nested inside a [`Master::Full`](crate::specs::Master::Full), this is the position of the outermost buffered element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element missing children.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The ids of the required children that were not found.
+            ///
+            missing_tag_ids: Vec<u64>,
+        },
+
+        ///
+        /// An error indicating "master" elements were nested more deeply than the iterator allows.
+        ///
+        /// See [`TagIterator::set_max_depth()`](crate::TagIterator::set_max_depth).
+        ///
+        MaxDepthExceeded {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element that would have exceeded the limit.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The configured depth limit.
+            ///
+            max_depth: usize,
+        },
+
+        ///
+        /// An error indicating an element has more data than the iterator allows.
+        ///
+        /// See [`TagIterator::set_max_tag_size()`](crate::TagIterator::set_max_tag_size), [`EbmlPushParser::set_max_tag_size()`](crate::EbmlPushParser::set_max_tag_size) and [`read_tree()`](crate::read_tree).
+        ///
+        MaxTagSizeExceeded {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the element's data.
+            ///
+            size: usize,
+
+            ///
+            /// The configured size limit.
+            ///
+            max_size: usize,
+        },
+
+        ///
+        /// An error indicating the id or size of an element is not a valid vint (e.g. its first byte is zero, so no length marker is set).
+        ///
+        InvalidVint {
+
+            ///
+            /// The position of the invalid vint.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element, if it was the element's size that was invalid.
+            ///
+            tag_id: Option<u64>,
+        },
+
+        ///
+        /// An error indicating a buffered "Master" element has more data than the iterator allows.
+        ///
+        /// See [`TagIterator::set_max_buffered_size()`](crate::TagIterator::set_max_buffered_size).
+        ///
+        MaxBufferedSizeExceeded {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The configured size limit.
+            ///
+            max_size: usize,
+        },
+
+        ///
+        /// An error indicating an element id is longer than the document allows.
+        ///
+        /// See [`TagIterator::set_max_lengths()`](crate::TagIterator::set_max_lengths).
+        ///
+        IdTooLong {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The length of the id in bytes.
+            ///
+            length: usize,
+
+            ///
+            /// The longest id allowed, from the document's `EBMLMaxIDLength`.
+            ///
+            max_length: usize,
+        },
+
+        ///
+        /// An error indicating the size of an element is encoded with more bytes than the document allows.
+        ///
+        /// See [`TagIterator::set_max_lengths()`](crate::TagIterator::set_max_lengths).
+        ///
+        SizeTooLong {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The length of the size in bytes.
+            ///
+            length: usize,
+
+            ///
+            /// The longest size allowed, from the document's `EBMLMaxSizeLength`.
+            ///
+            max_length: usize,
+        },
+
+        ///
+        /// An error indicating the data of a "master" element does not match the checksum in its `CRC-32` element.
+        ///
+        /// This error is only returned by iterators that verify checksums.  See [`TagIterator::set_verify_crc32()`](crate::TagIterator::set_verify_crc32).
+        ///
+        Crc32Mismatch {
+
+            ///
+            /// The position of the element whose data was checked.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element whose data was checked.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The checksum stored in the `CRC-32` element.
+            ///
+            expected: u32,
+
+            ///
+            /// The checksum of the data that was read.
+            ///
+            actual: u32,
         },
     }
 
@@ -134,18 +368,195 @@ pub mod tag_iterator {
                     tag_id 
                 } => write!(f, "Encountered invalid tag data for tag id [0x{tag_id:x?}] at position {position}"),
                 CorruptedFileError::HierarchyError {
+                    position,
                     found_tag_id,
                     current_parent_id,
-                } => write!(f, "Found child tag [{found_tag_id:x?}] when processing parent [{current_parent_id:x?}]"),
+                } => write!(f, "Found child tag [{found_tag_id:x?}] at position {position} when processing parent [{current_parent_id:x?}]"),
                 CorruptedFileError::OversizedChildElement { 
                     position, 
                     tag_id, 
-                    size : _
-                } => write!(f, "Found an oversized tag [0x{tag_id:x?}] at position {position}")
+                    size : _,
+                    end,
+                    parent_id,
+                    parent_position,
+                    parent_end,
+                } => write!(f, "Found an oversized tag [0x{tag_id:x?}] at position {position} ending at {end}, past the end of its parent [0x{parent_id:x?}] at position {parent_position} ending at {parent_end}"),
+                CorruptedFileError::MissingChildElement {
+                    position,
+                    tag_id,
+                    missing_tag_ids,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} is missing required children {missing_tag_ids:x?}"),
+                CorruptedFileError::MaxDepthExceeded {
+                    position,
+                    tag_id,
+                    max_depth,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} is nested more than {max_depth} levels deep"),
+                CorruptedFileError::MaxTagSizeExceeded {
+                    position,
+                    tag_id,
+                    size,
+                    max_size,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} has {size} bytes of data, more than the limit of {max_size} bytes"),
+                CorruptedFileError::InvalidVint {
+                    position,
+                    tag_id: Some(tag_id),
+                } => write!(f, "Tag [0x{tag_id:x?}] has an invalid size vint at position {position}"),
+                CorruptedFileError::InvalidVint {
+                    position,
+                    tag_id: None,
+                } => write!(f, "Encountered an invalid tag id vint at position {position}"),
+                CorruptedFileError::MaxBufferedSizeExceeded {
+                    position,
+                    tag_id,
+                    max_size,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} has more than the limit of {max_size} bytes of data to buffer"),
+                CorruptedFileError::IdTooLong {
+                    position,
+                    tag_id,
+                    length,
+                    max_length,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} has a {length} byte id, longer than the maximum of {max_length} bytes"),
+                CorruptedFileError::SizeTooLong {
+                    position,
+                    tag_id,
+                    length,
+                    max_length,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} has a {length} byte size, longer than the maximum of {max_length} bytes"),
+                CorruptedFileError::Crc32Mismatch {
+                    position,
+                    tag_id,
+                    expected,
+                    actual,
+                } => write!(f, "Tag [0x{tag_id:x?}] at position {position} has a CRC-32 of {actual:08x}, but its CRC-32 element holds {expected:08x}"),
+            }
+        }
+    }
+
+    impl CorruptedFileError {
+        ///
+        /// Gets the position of the element (or vint) the problem was found at.
+        ///
+        pub fn position(&self) -> usize {
+            match self {
+                CorruptedFileError::InvalidTagId { position, .. }
+                | CorruptedFileError::InvalidTagData { position, .. }
+                | CorruptedFileError::HierarchyError { position, .. }
+                | CorruptedFileError::OversizedChildElement { position, .. }
+                | CorruptedFileError::MissingChildElement { position, .. }
+                | CorruptedFileError::MaxDepthExceeded { position, .. }
+                | CorruptedFileError::MaxTagSizeExceeded { position, .. }
+                | CorruptedFileError::InvalidVint { position, .. }
+                | CorruptedFileError::MaxBufferedSizeExceeded { position, .. }
+                | CorruptedFileError::IdTooLong { position, .. }
+                | CorruptedFileError::SizeTooLong { position, .. }
+                | CorruptedFileError::Crc32Mismatch { position, .. } => *position,
+            }
+        }
+
+        ///
+        /// Gets the id of the element the problem was found in, if it was read.
+        ///
+        pub fn tag_id(&self) -> Option<u64> {
+            match self {
+                CorruptedFileError::InvalidTagId { tag_id, .. }
+                | CorruptedFileError::InvalidTagData { tag_id, .. }
+                | CorruptedFileError::OversizedChildElement { tag_id, .. }
+                | CorruptedFileError::MissingChildElement { tag_id, .. }
+                | CorruptedFileError::MaxDepthExceeded { tag_id, .. }
+                | CorruptedFileError::MaxTagSizeExceeded { tag_id, .. }
+                | CorruptedFileError::MaxBufferedSizeExceeded { tag_id, .. }
+                | CorruptedFileError::IdTooLong { tag_id, .. }
+                | CorruptedFileError::SizeTooLong { tag_id, .. }
+                | CorruptedFileError::Crc32Mismatch { tag_id, .. } => Some(*tag_id),
+                CorruptedFileError::HierarchyError { found_tag_id, .. } => Some(*found_tag_id),
+                CorruptedFileError::InvalidVint { tag_id, .. } => *tag_id,
+            }
+        }
+
+        ///
+        /// Gets the category of the problem.
+        ///
+        pub fn kind(&self) -> ReadErrorKind {
+            match self {
+                CorruptedFileError::InvalidTagId { .. } => ReadErrorKind::UnknownId,
+                CorruptedFileError::InvalidTagData { .. } => ReadErrorKind::InvalidData,
+                CorruptedFileError::HierarchyError { .. } | CorruptedFileError::MissingChildElement { .. } => ReadErrorKind::Hierarchy,
+                CorruptedFileError::OversizedChildElement { .. } | CorruptedFileError::SizeTooLong { .. } => ReadErrorKind::SizeOverflow,
+                CorruptedFileError::MaxDepthExceeded { .. } | CorruptedFileError::MaxTagSizeExceeded { .. } | CorruptedFileError::MaxBufferedSizeExceeded { .. } => ReadErrorKind::LimitExceeded,
+                CorruptedFileError::InvalidVint { .. } => ReadErrorKind::InvalidVint,
+                CorruptedFileError::IdTooLong { .. } => ReadErrorKind::IdTooLong,
+                CorruptedFileError::Crc32Mismatch { .. } => ReadErrorKind::Crc32Mismatch,
             }
         }
     }
 
+    ///
+    /// The category of a [`TagIteratorError`], for reporting diagnostics or choosing a recovery policy without matching every variant.
+    ///
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    pub enum ReadErrorKind {
+
+        ///
+        /// An element id or size is not a valid vint.
+        ///
+        InvalidVint,
+
+        ///
+        /// An element id is longer than the document allows.
+        ///
+        IdTooLong,
+
+        ///
+        /// An element's size is encoded with more bytes than the document allows, or its data extends past the end of its parent.
+        ///
+        SizeOverflow,
+
+        ///
+        /// The source ended part way through an element.
+        ///
+        UnexpectedEof,
+
+        ///
+        /// The data of a utf8 element is not valid utf8, or the data of an ASCII string element (see [`EbmlSpecification::is_ascii_string()`][`crate::specs::EbmlSpecification::is_ascii_string`]) is not printable ASCII.
+        ///
+        InvalidUtf8,
+
+        ///
+        /// An element id is not defined in the specification.
+        ///
+        UnknownId,
+
+        ///
+        /// An element's data can't be read as the type the specification declares for it (other than [`Self::InvalidUtf8`]).
+        ///
+        InvalidData,
+
+        ///
+        /// An element was found outside of its expected hierarchy, or a "Master" element is missing a mandatory child.
+        ///
+        Hierarchy,
+
+        ///
+        /// A limit configured on the iterator (depth, element size or buffered size) was exceeded.
+        ///
+        LimitExceeded,
+
+        ///
+        /// The data of a "Master" element doesn't match its `CRC-32` element.
+        ///
+        Crc32Mismatch,
+
+        ///
+        /// The document's `DocType` is not one the reader was configured to accept.
+        ///
+        UnsupportedDocType,
+
+        ///
+        /// The source returned an error.
+        ///
+        Io,
+    }
+
     ///
     /// Errors that can occur when reading ebml data.
     ///
@@ -192,6 +603,11 @@ pub mod tag_iterator {
         ///
         CorruptedTagData {
 
+            ///
+            /// The position of the corrupted tag.
+            ///
+            position: usize,
+
             ///
             /// The id of the corrupted tag.
             ///
@@ -203,9 +619,21 @@ pub mod tag_iterator {
             problem: ToolError,
         },
 
+        ///
+        /// An error indicating that the document's EBML header declares a `DocType` other than those passed to [`TagIterator::set_supported_doc_types()`](crate::TagIterator::set_supported_doc_types), or that the document doesn't start with an EBML header.
+        ///
+        UnsupportedDocType {
+
+            ///
+            /// The `DocType` declared by the document, or `None` if the document has no EBML header.
+            ///
+            doc_type: Option<String>,
+        },
+
         ///
         /// An error that wraps an IO error when reading from the underlying source.
         ///
+        #[cfg(feature = "std")]
         ReadError {
 
             ///
@@ -215,6 +643,52 @@ pub mod tag_iterator {
         },
     }
     
+    impl TagIteratorError {
+        ///
+        /// Gets the position of the element (or vint) being parsed when the error occurred.  This is `None` for errors from the source and for unsupported documents.
+        ///
+        pub fn position(&self) -> Option<usize> {
+            match self {
+                TagIteratorError::CorruptedFileData(err) => Some(err.position()),
+                TagIteratorError::UnexpectedEOF { tag_start, .. } => Some(*tag_start),
+                TagIteratorError::CorruptedTagData { position, .. } => Some(*position),
+                TagIteratorError::UnsupportedDocType { .. } => None,
+                #[cfg(feature = "std")]
+                TagIteratorError::ReadError { .. } => None,
+            }
+        }
+
+        ///
+        /// Gets the id of the element being parsed when the error occurred, if it was read.
+        ///
+        pub fn tag_id(&self) -> Option<u64> {
+            match self {
+                TagIteratorError::CorruptedFileData(err) => err.tag_id(),
+                TagIteratorError::UnexpectedEOF { tag_id, .. } => *tag_id,
+                TagIteratorError::CorruptedTagData { tag_id, .. } => Some(*tag_id),
+                TagIteratorError::UnsupportedDocType { .. } => None,
+                #[cfg(feature = "std")]
+                TagIteratorError::ReadError { .. } => None,
+            }
+        }
+
+        ///
+        /// Gets the category of the error.
+        ///
+        pub fn kind(&self) -> ReadErrorKind {
+            match self {
+                TagIteratorError::CorruptedFileData(err) => err.kind(),
+                TagIteratorError::UnexpectedEOF { .. } => ReadErrorKind::UnexpectedEof,
+                TagIteratorError::CorruptedTagData { problem: ToolError::FromUtf8Error(..) | ToolError::InvalidAscii(..), .. } => ReadErrorKind::InvalidUtf8,
+                TagIteratorError::CorruptedTagData { problem: ToolError::ReadVintOverflow, .. } => ReadErrorKind::InvalidVint,
+                TagIteratorError::CorruptedTagData { .. } => ReadErrorKind::InvalidData,
+                TagIteratorError::UnsupportedDocType { .. } => ReadErrorKind::UnsupportedDocType,
+                #[cfg(feature = "std")]
+                TagIteratorError::ReadError { .. } => ReadErrorKind::Io,
+            }
+        }
+    }
+
     impl fmt::Display for TagIteratorError {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self {
@@ -226,9 +700,13 @@ pub mod tag_iterator {
                     partial_data: _ 
                 } => write!(f, "Reached EOF unexpectedly. Partial tag data: {{tag offset:{tag_start}}} {{id:{tag_id:x?}}} {{size:{tag_size:?}}}"),
                 TagIteratorError::CorruptedTagData {
+                    position,
                     tag_id,
                     problem,
-                } => write!(f, "Error reading data for tag id (0x{tag_id:x?}). {problem}"),
+                } => write!(f, "Error reading data for tag id (0x{tag_id:x?}) at position {position}. {problem}"),
+                TagIteratorError::UnsupportedDocType { doc_type: Some(doc_type) } => write!(f, "Documents with DocType \"{doc_type}\" are not supported"),
+                TagIteratorError::UnsupportedDocType { doc_type: None } => write!(f, "Document does not start with an EBML header"),
+                #[cfg(feature = "std")]
                 TagIteratorError::ReadError { source: _ } => write!(f, "Error reading from source."),
             }
         }
@@ -239,7 +717,9 @@ pub mod tag_iterator {
             match self {
                 TagIteratorError::CorruptedFileData(_) => None,
                 TagIteratorError::UnexpectedEOF { tag_start: _, tag_id: _, tag_size: _, partial_data: _ } => None,
-                TagIteratorError::CorruptedTagData { tag_id: _, problem } => problem.source(),
+                TagIteratorError::CorruptedTagData { position: _, tag_id: _, problem } => problem.source(),
+                TagIteratorError::UnsupportedDocType { doc_type: _ } => None,
+                #[cfg(feature = "std")]
                 TagIteratorError::ReadError { source } => Some(source),
             }
         }
@@ -249,7 +729,11 @@ pub mod tag_iterator {
 pub mod tag_writer {
     use super::fmt;
     use super::Error;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
     use std::io;
+    use crate::specs::TagDataType;
 
     ///
     /// Errors that can occur when writing ebml data.
@@ -302,16 +786,136 @@ pub mod tag_writer {
         },
 
         ///
-        /// An error that wraps an IO error when writing to the underlying destination.
+        /// An error indicating a previously written tag could not be updated in place.
         ///
-        WriteError {
-            source: io::Error,
-        },
-    }
+        /// Can occur when patching header values (such as `DocTypeVersion`) if the tag was never written or if its original encoding is too narrow for the new value.
+        ///
+        TagPatchError {
 
-    impl fmt::Display for TagWriterError {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            match self {
+            ///
+            /// The id of the tag being patched.
+            ///
+            tag_id: u64,
+
+            ///
+            /// A description of the problem.
+            ///
+            message: String,
+        },
+
+        ///
+        /// An error indicating the writer still had open tags when it was expected to be idle.
+        ///
+        /// Can occur when calling [`TagWriter::reset()`][`crate::TagWriter::reset`] before every started tag has been ended.
+        ///
+        UnclosedTags {
+
+            ///
+            /// The ids of the open tags, outermost first.
+            ///
+            tag_ids: Vec<u64>,
+        },
+
+        ///
+        /// An error indicating a tag's data does not have the type its specification declares.
+        ///
+        /// Can occur when writing a [`DynTag`][`crate::dynamic::DynTag`] whose value does not match the type the [`DynSpecification`][`crate::dynamic::DynSpecification`] reports for its id.
+        ///
+        TagDataMismatch {
+
+            ///
+            /// The id of the tag being written.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The data type declared by the specification, or [`None`] if the id is not in the specification.
+            ///
+            expected: Option<TagDataType>,
+        },
+
+        ///
+        /// An error indicating a tag written to a [`TagWriter`][`crate::TagWriter`] created with [`TagWriter::with_spec()`][`crate::TagWriter::with_spec`] does not hold the data type the specification declares for its id.
+        ///
+        TypeMismatch {
+
+            ///
+            /// The id of the tag being written.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The data type declared by the specification, or [`None`] if the id is not in the specification.
+            ///
+            expected: Option<TagDataType>,
+
+            ///
+            /// The data type of the tag being written, or [`None`] for raw data.
+            ///
+            found: Option<TagDataType>,
+        },
+
+        ///
+        /// An error indicating a string tag the specification declares as an EBML `string` element holds characters other than printable ASCII.
+        ///
+        /// See [`EbmlSpecification::is_ascii_string()`][`crate::specs::EbmlSpecification::is_ascii_string`].
+        ///
+        InvalidAsciiString {
+
+            ///
+            /// The id of the tag being written.
+            ///
+            tag_id: u64,
+        },
+
+        ///
+        /// An error indicating "master" tags were nested more deeply than the writer allows.
+        ///
+        /// See [`TagWriter::set_max_depth()`][`crate::TagWriter::set_max_depth`].
+        ///
+        MaxDepthExceeded {
+
+            ///
+            /// The id of the tag that would have exceeded the limit.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The configured depth limit.
+            ///
+            max_depth: usize,
+        },
+
+        ///
+        /// An error indicating a tag doesn't fit in the space left in the destination.
+        ///
+        /// Can occur when writing to a fixed size buffer using a [`SliceWriter`][`crate::writer::SliceWriter`].
+        ///
+        BufferOverflow {
+
+            ///
+            /// The number of bytes needed to write the tag.
+            ///
+            needed: usize,
+
+            ///
+            /// The number of bytes left in the destination.
+            ///
+            remaining: usize,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the underlying destination.
+        ///
+        #[cfg(feature = "std")]
+        WriteError {
+            source: io::Error,
+        },
+    }
+
+    impl fmt::Display for TagWriterError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
                 TagWriterError::UnexpectedTag { tag_id, current_path } => write!(f, "Unexpected tag 0x{tag_id:x?} when writing to {current_path:x?}"),
                 TagWriterError::TagIdError(id) => write!(f, "Tag id 0x{id:x?} is not a valid vint"),
                 TagWriterError::TagSizeError(message) => write!(f, "Problem writing data tag size. {message}"),
@@ -319,6 +923,21 @@ pub mod tag_writer {
                     Some(expected) => write!(f, "Unexpected closing tag 0x'{tag_id:x?}'. Expected 0x'{expected:x?}'"),
                     None => write!(f, "Unexpected closing tag 0x'{tag_id:x?}'"),
                 },
+                TagWriterError::TagPatchError { tag_id, message } => write!(f, "Could not patch tag 0x{tag_id:x?}. {message}"),
+                TagWriterError::UnclosedTags { tag_ids } => write!(f, "Tags {tag_ids:x?} were never closed"),
+                TagWriterError::TagDataMismatch { tag_id, expected } => match expected {
+                    Some(expected) => write!(f, "Tag 0x{tag_id:x?} does not hold {expected:?} data"),
+                    None => write!(f, "Tag 0x{tag_id:x?} is not in the specification and must hold raw binary data"),
+                },
+                TagWriterError::TypeMismatch { tag_id, expected, found } => match (expected, found) {
+                    (Some(expected), Some(found)) => write!(f, "Tag 0x{tag_id:x?} holds {found:?} data, but the specification declares {expected:?} data"),
+                    (Some(expected), None) => write!(f, "Tag 0x{tag_id:x?} holds raw data, but the specification declares {expected:?} data"),
+                    (None, _) => write!(f, "Tag 0x{tag_id:x?} is not in the specification and must hold raw binary data"),
+                },
+                TagWriterError::InvalidAsciiString { tag_id } => write!(f, "Tag 0x{tag_id:x?} is an ascii string element, but holds characters that are not printable ascii"),
+                TagWriterError::MaxDepthExceeded { tag_id, max_depth } => write!(f, "Tag 0x{tag_id:x?} would be nested more than {max_depth} levels deep"),
+                TagWriterError::BufferOverflow { needed, remaining } => write!(f, "Writing the tag needs {needed} bytes, but only {remaining} bytes are left in the destination"),
+                #[cfg(feature = "std")]
                 TagWriterError::WriteError { source: _ } => write!(f, "Error writing to destination."),
             }
         }
@@ -331,8 +950,518 @@ pub mod tag_writer {
                 TagWriterError::TagIdError(_) => None,
                 TagWriterError::TagSizeError(_) => None,
                 TagWriterError::UnexpectedClosingTag { tag_id: _, expected_id: _ } => None,
+                TagWriterError::TagPatchError { tag_id: _, message: _ } => None,
+                TagWriterError::UnclosedTags { tag_ids: _ } => None,
+                TagWriterError::TagDataMismatch { tag_id: _, expected: _ } => None,
+                TagWriterError::TypeMismatch { tag_id: _, expected: _, found: _ } => None,
+                TagWriterError::InvalidAsciiString { tag_id: _ } => None,
+                TagWriterError::MaxDepthExceeded { tag_id: _, max_depth: _ } => None,
+                TagWriterError::BufferOverflow { needed: _, remaining: _ } => None,
+                #[cfg(feature = "std")]
                 TagWriterError::WriteError { source } => Some(source),
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub mod push_parser {
+    use super::fmt;
+    use super::Error;
+
+    ///
+    /// Errors that can occur when feeding data to an [`EbmlPushParser`](crate::EbmlPushParser).
+    ///
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum FeedError {
+
+        ///
+        /// Data was fed after [`EbmlPushParser::finish()`](crate::EbmlPushParser::finish) was called.  The data is not kept.
+        ///
+        Finished,
+    }
+
+    impl fmt::Display for FeedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                FeedError::Finished => write!(f, "Data can't be fed to a finished EbmlPushParser"),
+            }
+        }
+    }
+
+    impl Error for FeedError {}
+}
+
+#[cfg(feature = "std")]
+pub mod block {
+    use super::fmt;
+    use super::Error;
+
+    ///
+    /// Errors that can occur when parsing or serializing the payload of a Matroska `SimpleBlock` or `Block` element.
+    ///
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum BlockError {
+
+        ///
+        /// The data ended before the block header or lace sizes were complete.
+        ///
+        UnexpectedEnd,
+
+        ///
+        /// The track number or an EBML lace size is not a valid vint.
+        ///
+        InvalidVint,
+
+        ///
+        /// The lace sizes don't fit in the data of the block, or the data can't be split into equally sized frames for fixed-size lacing.
+        ///
+        InvalidLaceSizes,
+
+        ///
+        /// A block can't hold this number of frames.  Blocks without lacing hold exactly 1 frame, and laced blocks hold between 1 and 256 frames.
+        ///
+        FrameCount(usize),
+
+        ///
+        /// Frames written with fixed-size lacing don't all have the same size.
+        ///
+        MismatchedFrameSizes,
+
+        ///
+        /// The track number is too large to be written as a vint.
+        ///
+        TrackNumberOverflow(u64),
+    }
+
+    impl fmt::Display for BlockError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                BlockError::UnexpectedEnd => write!(f, "Block data ended unexpectedly"),
+                BlockError::InvalidVint => write!(f, "Block contains an invalid vint"),
+                BlockError::InvalidLaceSizes => write!(f, "Lace sizes do not match the size of the block"),
+                BlockError::FrameCount(count) => write!(f, "A block cannot hold {count} frames with the requested lacing"),
+                BlockError::MismatchedFrameSizes => write!(f, "Frames must all be the same size for fixed-size lacing"),
+                BlockError::TrackNumberOverflow(track) => write!(f, "Track number too large to be written as a vint: {track}"),
+            }
+        }
+    }
+
+    impl Error for BlockError {}
+}
+
+#[cfg(feature = "std")]
+pub mod pipe {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+
+    ///
+    /// Errors that can occur when piping tags from a reader to a writer.
+    ///
+    #[derive(Debug)]
+    pub enum PipeError {
+
+        ///
+        /// An error reading a tag from the source.
+        ///
+        Read(TagIteratorError),
+
+        ///
+        /// An error writing a tag to the destination.
+        ///
+        Write(TagWriterError),
+    }
+
+    impl fmt::Display for PipeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                PipeError::Read(err) => write!(f, "Error reading tag: {err}"),
+                PipeError::Write(err) => write!(f, "Error writing tag: {err}"),
+            }
+        }
+    }
+
+    impl Error for PipeError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                PipeError::Read(err) => Some(err),
+                PipeError::Write(err) => Some(err),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for PipeError {
+        fn from(err: TagIteratorError) -> Self {
+            PipeError::Read(err)
+        }
+    }
+
+    impl From<TagWriterError> for PipeError {
+        fn from(err: TagWriterError) -> Self {
+            PipeError::Write(err)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod dump {
+    use std::io;
+
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when dumping the structure of a document.
+    ///
+    #[derive(Debug)]
+    pub enum DumpError {
+
+        ///
+        /// An error reading a tag from the source.
+        ///
+        Read(TagIteratorError),
+
+        ///
+        /// An error writing to the output.
+        ///
+        Write(io::Error),
+    }
+
+    impl fmt::Display for DumpError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                DumpError::Read(err) => write!(f, "Error reading tag: {err}"),
+                DumpError::Write(err) => write!(f, "Error writing dump: {err}"),
+            }
+        }
+    }
+
+    impl Error for DumpError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                DumpError::Read(err) => Some(err),
+                DumpError::Write(err) => Some(err),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for DumpError {
+        fn from(err: TagIteratorError) -> Self {
+            DumpError::Read(err)
+        }
+    }
+
+    impl From<io::Error> for DumpError {
+        fn from(err: io::Error) -> Self {
+            DumpError::Write(err)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod header {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that indicate a document can't be read, based on its EBML header.
+    ///
+    #[derive(Debug)]
+    pub enum HeaderError {
+
+        ///
+        /// The header could not be read from the source.
+        ///
+        Read(TagIteratorError),
+
+        ///
+        /// The document does not start with an EBML header.
+        ///
+        MissingHeader {
+
+            ///
+            /// The id of the first tag in the document.
+            ///
+            found_tag_id: u64,
+        },
+
+        ///
+        /// The document requires a newer version of the EBML specification to read (`EBMLReadVersion` is greater than 1).
+        ///
+        UnsupportedEbmlVersion(u64),
+
+        ///
+        /// The header does not declare a `DocType`.
+        ///
+        MissingDocType,
+
+        ///
+        /// The `DocType` of the document is not one the reader supports.
+        ///
+        UnsupportedDocType(String),
+
+        ///
+        /// The document requires a newer version of its `DocType` than the reader supports.
+        ///
+        UnsupportedDocTypeVersion {
+
+            ///
+            /// The `DocTypeReadVersion` declared by the document.
+            ///
+            read_version: u64,
+
+            ///
+            /// The highest `DocTypeReadVersion` the reader supports.
+            ///
+            supported_version: u64,
+        },
+
+        ///
+        /// A header element holds a value that is out of range, such as an `EBMLMaxSizeLength` larger than 8.
+        ///
+        InvalidValue {
+
+            ///
+            /// The id of the offending element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The value that was found.
+            ///
+            value: u64,
+        },
+    }
+
+    impl fmt::Display for HeaderError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                HeaderError::Read(err) => write!(f, "Could not read EBML header: {err}"),
+                HeaderError::MissingHeader { found_tag_id } => write!(f, "Expected an EBML header, but found tag 0x{found_tag_id:x?}"),
+                HeaderError::UnsupportedEbmlVersion(version) => write!(f, "Cannot read EBML version {version} documents"),
+                HeaderError::MissingDocType => write!(f, "EBML header does not declare a DocType"),
+                HeaderError::UnsupportedDocType(doc_type) => write!(f, "Cannot read documents of type {doc_type:?}"),
+                HeaderError::UnsupportedDocTypeVersion { read_version, supported_version } => write!(f, "Document requires DocTypeReadVersion {read_version}, but only up to {supported_version} is supported"),
+                HeaderError::InvalidValue { tag_id, value } => write!(f, "Header element 0x{tag_id:x?} has invalid value {value}"),
+            }
+        }
+    }
+
+    impl Error for HeaderError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                HeaderError::Read(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for HeaderError {
+        fn from(err: TagIteratorError) -> Self {
+            HeaderError::Read(err)
+        }
+    }
+}
+
+#[cfg(feature = "ebml-schema")]
+pub mod schema {
+    use super::fmt;
+    use super::Error;
+
+    ///
+    /// Errors that can occur when loading an EBML Schema.
+    ///
+    #[derive(Debug, PartialEq)]
+    pub enum SchemaError {
+
+        ///
+        /// The schema is not well-formed XML.
+        ///
+        InvalidXml(String),
+
+        ///
+        /// The root element of the schema is not an `EBMLSchema`.
+        ///
+        MissingSchema,
+
+        ///
+        /// A schema element is missing a required attribute.
+        ///
+        MissingAttribute {
+
+            ///
+            /// The name of the offending element, or its position in the schema if it has no name.
+            ///
+            element: String,
+
+            ///
+            /// The name of the missing attribute.
+            ///
+            attribute: &'static str,
+        },
+
+        ///
+        /// A schema element has an attribute that can't be parsed, such as an `id` that isn't a hexadecimal number.
+        ///
+        InvalidAttribute {
+
+            ///
+            /// The name of the offending element.
+            ///
+            element: String,
+
+            ///
+            /// The name of the invalid attribute.
+            ///
+            attribute: &'static str,
+
+            ///
+            /// The value of the attribute.
+            ///
+            value: String,
+        },
+
+        ///
+        /// The `path` of a schema element refers to a parent that the schema does not define.
+        ///
+        UnknownParent {
+
+            ///
+            /// The name of the offending element.
+            ///
+            element: String,
+
+            ///
+            /// The name of the missing parent.
+            ///
+            parent: String,
+        },
+
+        ///
+        /// Two schema elements have the same id.
+        ///
+        DuplicateId(u64),
+
+        ///
+        /// Two schema elements have the same name.
+        ///
+        DuplicateName(String),
+    }
+
+    impl fmt::Display for SchemaError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                SchemaError::InvalidXml(message) => write!(f, "Schema is not valid XML: {message}"),
+                SchemaError::MissingSchema => write!(f, "Schema does not have an EBMLSchema root element"),
+                SchemaError::MissingAttribute { element, attribute } => write!(f, "Schema element {element:?} is missing the {attribute:?} attribute"),
+                SchemaError::InvalidAttribute { element, attribute, value } => write!(f, "Schema element {element:?} has invalid {attribute:?} attribute {value:?}"),
+                SchemaError::UnknownParent { element, parent } => write!(f, "Schema element {element:?} has unknown parent {parent:?}"),
+                SchemaError::DuplicateId(id) => write!(f, "Schema defines element id 0x{id:x?} more than once"),
+                SchemaError::DuplicateName(name) => write!(f, "Schema defines element name {name:?} more than once"),
+            }
+        }
+    }
+
+    impl Error for SchemaError {}
+}
+
+#[cfg(feature = "serde")]
+pub mod serialization {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+    use crate::specs::TagDataType;
+
+    ///
+    /// Errors that can occur when converting between EBML documents and types implementing `serde` traits.
+    ///
+    #[derive(Debug)]
+    pub enum SerdeError {
+
+        ///
+        /// An error reading the document.
+        ///
+        Read(TagIteratorError),
+
+        ///
+        /// An error writing the document.
+        ///
+        Write(TagWriterError),
+
+        ///
+        /// A field name that does not identify an element.  Fields must be named with a hexadecimal element id, like `#[serde(rename = "0x4d80")]`, to be serialized.
+        ///
+        InvalidFieldName(String),
+
+        ///
+        /// A value that can't be stored in an element of the given type, such as a string in an unsigned integer element.  `data_type` is `None` when the specification does not define the element.
+        ///
+        UnsupportedValue {
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The type of the element.
+            ///
+            data_type: Option<TagDataType>,
+        },
+
+        ///
+        /// An error reported by a `serde` implementation, such as a missing field or a value of the wrong type.
+        ///
+        Message(String),
+    }
+
+    impl fmt::Display for SerdeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                SerdeError::Read(err) => write!(f, "Error reading document: {err}"),
+                SerdeError::Write(err) => write!(f, "Error writing document: {err}"),
+                SerdeError::InvalidFieldName(name) => write!(f, "Field {name:?} is not a hexadecimal element id"),
+                SerdeError::UnsupportedValue { tag_id, data_type } => write!(f, "Value can't be stored in element 0x{tag_id:x?} of type {data_type:?}"),
+                SerdeError::Message(message) => write!(f, "{message}"),
+            }
+        }
+    }
+
+    impl Error for SerdeError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                SerdeError::Read(err) => Some(err),
+                SerdeError::Write(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for SerdeError {
+        fn from(err: TagIteratorError) -> Self {
+            SerdeError::Read(err)
+        }
+    }
+
+    impl From<TagWriterError> for SerdeError {
+        fn from(err: TagWriterError) -> Self {
+            SerdeError::Write(err)
+        }
+    }
+
+    impl serde::de::Error for SerdeError {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            SerdeError::Message(msg.to_string())
+        }
+    }
+
+    impl serde::ser::Error for SerdeError {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            SerdeError::Message(msg.to_string())
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//!
+//! Named ids for the EBML header elements and the [Matroska][mkv] element set.
+//!
+//! These constants are usable without any specification, so code that matches on tag ids (e.g. from [`EbmlTag::get_id()`](crate::specs::EbmlTag::get_id) or [`TagWriter::write_raw()`](crate::TagWriter::write_raw)) can read as `ids::SEGMENT` rather than `0x18538067`.  Names follow the element names in the specifications, including elements that have since been deprecated, so that older files can still be described.  [`name()`] looks up the specification name of an id for logging and debugging output.
+//!
+//! [mkv]: https://www.matroska.org/technical/elements.html
+//!
+
+///
+/// Defines a constant for each id, along with a table mapping ids to element names.
+///
+macro_rules! ids {
+    ($table:ident; $($name:ident = $id:expr, $element:literal;)*) => {
+        $(pub const $name: u64 = $id;)*
+
+        pub const $table: &[(u64, &str)] = &[$(($id, $element)),*];
+    };
+}
+
+ids! {
+    EBML_NAMES;
+    EBML = 0x1A45DFA3, "EBML";
+    EBML_VERSION = 0x4286, "EBMLVersion";
+    EBML_READ_VERSION = 0x42F7, "EBMLReadVersion";
+    EBML_MAX_ID_LENGTH = 0x42F2, "EBMLMaxIDLength";
+    EBML_MAX_SIZE_LENGTH = 0x42F3, "EBMLMaxSizeLength";
+    DOC_TYPE = 0x4282, "DocType";
+    DOC_TYPE_VERSION = 0x4287, "DocTypeVersion";
+    DOC_TYPE_READ_VERSION = 0x4285, "DocTypeReadVersion";
+    DOC_TYPE_EXTENSION = 0x4281, "DocTypeExtension";
+    DOC_TYPE_EXTENSION_NAME = 0x4283, "DocTypeExtensionName";
+    DOC_TYPE_EXTENSION_VERSION = 0x4284, "DocTypeExtensionVersion";
+    CRC32 = 0xBF, "CRC-32";
+    VOID = 0xEC, "Void";
+}
+
+ids! {
+    MATROSKA_NAMES;
+    SEGMENT = 0x18538067, "Segment";
+    SEEK_HEAD = 0x114D9B74, "SeekHead";
+    SEEK = 0x4DBB, "Seek";
+    SEEK_ID = 0x53AB, "SeekID";
+    SEEK_POSITION = 0x53AC, "SeekPosition";
+    INFO = 0x1549A966, "Info";
+    SEGMENT_UUID = 0x73A4, "SegmentUUID";
+    SEGMENT_FILENAME = 0x7384, "SegmentFilename";
+    PREV_UUID = 0x3CB923, "PrevUUID";
+    PREV_FILENAME = 0x3C83AB, "PrevFilename";
+    NEXT_UUID = 0x3EB923, "NextUUID";
+    NEXT_FILENAME = 0x3E83BB, "NextFilename";
+    SEGMENT_FAMILY = 0x4444, "SegmentFamily";
+    CHAPTER_TRANSLATE = 0x6924, "ChapterTranslate";
+    CHAPTER_TRANSLATE_ID = 0x69A5, "ChapterTranslateID";
+    CHAPTER_TRANSLATE_CODEC = 0x69BF, "ChapterTranslateCodec";
+    CHAPTER_TRANSLATE_EDITION_UID = 0x69FC, "ChapterTranslateEditionUID";
+    TIMESTAMP_SCALE = 0x2AD7B1, "TimestampScale";
+    DURATION = 0x4489, "Duration";
+    DATE_UTC = 0x4461, "DateUTC";
+    TITLE = 0x7BA9, "Title";
+    MUXING_APP = 0x4D80, "MuxingApp";
+    WRITING_APP = 0x5741, "WritingApp";
+    CLUSTER = 0x1F43B675, "Cluster";
+    TIMESTAMP = 0xE7, "Timestamp";
+    SILENT_TRACKS = 0x5854, "SilentTracks";
+    SILENT_TRACK_NUMBER = 0x58D7, "SilentTrackNumber";
+    POSITION = 0xA7, "Position";
+    PREV_SIZE = 0xAB, "PrevSize";
+    SIMPLE_BLOCK = 0xA3, "SimpleBlock";
+    BLOCK_GROUP = 0xA0, "BlockGroup";
+    BLOCK = 0xA1, "Block";
+    BLOCK_VIRTUAL = 0xA2, "BlockVirtual";
+    BLOCK_ADDITIONS = 0x75A1, "BlockAdditions";
+    BLOCK_MORE = 0xA6, "BlockMore";
+    BLOCK_ADDITIONAL = 0xA5, "BlockAdditional";
+    BLOCK_ADD_ID = 0xEE, "BlockAddID";
+    BLOCK_DURATION = 0x9B, "BlockDuration";
+    REFERENCE_PRIORITY = 0xFA, "ReferencePriority";
+    REFERENCE_BLOCK = 0xFB, "ReferenceBlock";
+    REFERENCE_VIRTUAL = 0xFD, "ReferenceVirtual";
+    CODEC_STATE = 0xA4, "CodecState";
+    DISCARD_PADDING = 0x75A2, "DiscardPadding";
+    SLICES = 0x8E, "Slices";
+    TIME_SLICE = 0xE8, "TimeSlice";
+    LACE_NUMBER = 0xCC, "LaceNumber";
+    FRAME_NUMBER = 0xCD, "FrameNumber";
+    BLOCK_ADDITION_ID = 0xCB, "BlockAdditionID";
+    DELAY = 0xCE, "Delay";
+    SLICE_DURATION = 0xCF, "SliceDuration";
+    REFERENCE_FRAME = 0xC8, "ReferenceFrame";
+    REFERENCE_OFFSET = 0xC9, "ReferenceOffset";
+    REFERENCE_TIMESTAMP = 0xCA, "ReferenceTimestamp";
+    ENCRYPTED_BLOCK = 0xAF, "EncryptedBlock";
+    TRACKS = 0x1654AE6B, "Tracks";
+    TRACK_ENTRY = 0xAE, "TrackEntry";
+    TRACK_NUMBER = 0xD7, "TrackNumber";
+    TRACK_UID = 0x73C5, "TrackUID";
+    TRACK_TYPE = 0x83, "TrackType";
+    FLAG_ENABLED = 0xB9, "FlagEnabled";
+    FLAG_DEFAULT = 0x88, "FlagDefault";
+    FLAG_FORCED = 0x55AA, "FlagForced";
+    FLAG_HEARING_IMPAIRED = 0x55AB, "FlagHearingImpaired";
+    FLAG_VISUAL_IMPAIRED = 0x55AC, "FlagVisualImpaired";
+    FLAG_TEXT_DESCRIPTIONS = 0x55AD, "FlagTextDescriptions";
+    FLAG_ORIGINAL = 0x55AE, "FlagOriginal";
+    FLAG_COMMENTARY = 0x55AF, "FlagCommentary";
+    FLAG_LACING = 0x9C, "FlagLacing";
+    MIN_CACHE = 0x6DE7, "MinCache";
+    MAX_CACHE = 0x6DF8, "MaxCache";
+    DEFAULT_DURATION = 0x23E383, "DefaultDuration";
+    DEFAULT_DECODED_FIELD_DURATION = 0x234E7A, "DefaultDecodedFieldDuration";
+    TRACK_TIMESTAMP_SCALE = 0x23314F, "TrackTimestampScale";
+    TRACK_OFFSET = 0x537F, "TrackOffset";
+    MAX_BLOCK_ADDITION_ID = 0x55EE, "MaxBlockAdditionID";
+    BLOCK_ADDITION_MAPPING = 0x41E4, "BlockAdditionMapping";
+    BLOCK_ADD_ID_VALUE = 0x41F0, "BlockAddIDValue";
+    BLOCK_ADD_ID_NAME = 0x41A4, "BlockAddIDName";
+    BLOCK_ADD_ID_TYPE = 0x41E7, "BlockAddIDType";
+    BLOCK_ADD_ID_EXTRA_DATA = 0x41ED, "BlockAddIDExtraData";
+    NAME = 0x536E, "Name";
+    LANGUAGE = 0x22B59C, "Language";
+    LANGUAGE_BCP47 = 0x22B59D, "LanguageBCP47";
+    CODEC_ID = 0x86, "CodecID";
+    CODEC_PRIVATE = 0x63A2, "CodecPrivate";
+    CODEC_NAME = 0x258688, "CodecName";
+    ATTACHMENT_LINK = 0x7446, "AttachmentLink";
+    CODEC_SETTINGS = 0x3A9697, "CodecSettings";
+    CODEC_INFO_URL = 0x3B4040, "CodecInfoURL";
+    CODEC_DOWNLOAD_URL = 0x26B240, "CodecDownloadURL";
+    CODEC_DECODE_ALL = 0xAA, "CodecDecodeAll";
+    TRACK_OVERLAY = 0x6FAB, "TrackOverlay";
+    CODEC_DELAY = 0x56AA, "CodecDelay";
+    SEEK_PRE_ROLL = 0x56BB, "SeekPreRoll";
+    TRACK_TRANSLATE = 0x6624, "TrackTranslate";
+    TRACK_TRANSLATE_TRACK_ID = 0x66A5, "TrackTranslateTrackID";
+    TRACK_TRANSLATE_CODEC = 0x66BF, "TrackTranslateCodec";
+    TRACK_TRANSLATE_EDITION_UID = 0x66FC, "TrackTranslateEditionUID";
+    VIDEO = 0xE0, "Video";
+    FLAG_INTERLACED = 0x9A, "FlagInterlaced";
+    FIELD_ORDER = 0x9D, "FieldOrder";
+    STEREO_MODE = 0x53B8, "StereoMode";
+    ALPHA_MODE = 0x53C0, "AlphaMode";
+    OLD_STEREO_MODE = 0x53B9, "OldStereoMode";
+    PIXEL_WIDTH = 0xB0, "PixelWidth";
+    PIXEL_HEIGHT = 0xBA, "PixelHeight";
+    PIXEL_CROP_BOTTOM = 0x54AA, "PixelCropBottom";
+    PIXEL_CROP_TOP = 0x54BB, "PixelCropTop";
+    PIXEL_CROP_LEFT = 0x54CC, "PixelCropLeft";
+    PIXEL_CROP_RIGHT = 0x54DD, "PixelCropRight";
+    DISPLAY_WIDTH = 0x54B0, "DisplayWidth";
+    DISPLAY_HEIGHT = 0x54BA, "DisplayHeight";
+    DISPLAY_UNIT = 0x54B2, "DisplayUnit";
+    ASPECT_RATIO_TYPE = 0x54B3, "AspectRatioType";
+    UNCOMPRESSED_FOURCC = 0x2EB524, "UncompressedFourCC";
+    GAMMA_VALUE = 0x2FB523, "GammaValue";
+    FRAME_RATE = 0x2383E3, "FrameRate";
+    COLOUR = 0x55B0, "Colour";
+    MATRIX_COEFFICIENTS = 0x55B1, "MatrixCoefficients";
+    BITS_PER_CHANNEL = 0x55B2, "BitsPerChannel";
+    CHROMA_SUBSAMPLING_HORZ = 0x55B3, "ChromaSubsamplingHorz";
+    CHROMA_SUBSAMPLING_VERT = 0x55B4, "ChromaSubsamplingVert";
+    CB_SUBSAMPLING_HORZ = 0x55B5, "CbSubsamplingHorz";
+    CB_SUBSAMPLING_VERT = 0x55B6, "CbSubsamplingVert";
+    CHROMA_SITING_HORZ = 0x55B7, "ChromaSitingHorz";
+    CHROMA_SITING_VERT = 0x55B8, "ChromaSitingVert";
+    RANGE = 0x55B9, "Range";
+    TRANSFER_CHARACTERISTICS = 0x55BA, "TransferCharacteristics";
+    PRIMARIES = 0x55BB, "Primaries";
+    MAX_CLL = 0x55BC, "MaxCLL";
+    MAX_FALL = 0x55BD, "MaxFALL";
+    MASTERING_METADATA = 0x55D0, "MasteringMetadata";
+    PRIMARY_R_CHROMATICITY_X = 0x55D1, "PrimaryRChromaticityX";
+    PRIMARY_R_CHROMATICITY_Y = 0x55D2, "PrimaryRChromaticityY";
+    PRIMARY_G_CHROMATICITY_X = 0x55D3, "PrimaryGChromaticityX";
+    PRIMARY_G_CHROMATICITY_Y = 0x55D4, "PrimaryGChromaticityY";
+    PRIMARY_B_CHROMATICITY_X = 0x55D5, "PrimaryBChromaticityX";
+    PRIMARY_B_CHROMATICITY_Y = 0x55D6, "PrimaryBChromaticityY";
+    WHITE_POINT_CHROMATICITY_X = 0x55D7, "WhitePointChromaticityX";
+    WHITE_POINT_CHROMATICITY_Y = 0x55D8, "WhitePointChromaticityY";
+    LUMINANCE_MAX = 0x55D9, "LuminanceMax";
+    LUMINANCE_MIN = 0x55DA, "LuminanceMin";
+    PROJECTION = 0x7670, "Projection";
+    PROJECTION_TYPE = 0x7671, "ProjectionType";
+    PROJECTION_PRIVATE = 0x7672, "ProjectionPrivate";
+    PROJECTION_POSE_YAW = 0x7673, "ProjectionPoseYaw";
+    PROJECTION_POSE_PITCH = 0x7674, "ProjectionPosePitch";
+    PROJECTION_POSE_ROLL = 0x7675, "ProjectionPoseRoll";
+    AUDIO = 0xE1, "Audio";
+    SAMPLING_FREQUENCY = 0xB5, "SamplingFrequency";
+    OUTPUT_SAMPLING_FREQUENCY = 0x78B5, "OutputSamplingFrequency";
+    CHANNELS = 0x9F, "Channels";
+    CHANNEL_POSITIONS = 0x7D7B, "ChannelPositions";
+    BIT_DEPTH = 0x6264, "BitDepth";
+    EMPHASIS = 0x52F1, "Emphasis";
+    TRACK_OPERATION = 0xE2, "TrackOperation";
+    TRACK_COMBINE_PLANES = 0xE3, "TrackCombinePlanes";
+    TRACK_PLANE = 0xE4, "TrackPlane";
+    TRACK_PLANE_UID = 0xE5, "TrackPlaneUID";
+    TRACK_PLANE_TYPE = 0xE6, "TrackPlaneType";
+    TRACK_JOIN_BLOCKS = 0xE9, "TrackJoinBlocks";
+    TRACK_JOIN_UID = 0xED, "TrackJoinUID";
+    TRICK_TRACK_UID = 0xC0, "TrickTrackUID";
+    TRICK_TRACK_SEGMENT_UID = 0xC1, "TrickTrackSegmentUID";
+    TRICK_TRACK_FLAG = 0xC6, "TrickTrackFlag";
+    TRICK_MASTER_TRACK_UID = 0xC7, "TrickMasterTrackUID";
+    TRICK_MASTER_TRACK_SEGMENT_UID = 0xC4, "TrickMasterTrackSegmentUID";
+    CONTENT_ENCODINGS = 0x6D80, "ContentEncodings";
+    CONTENT_ENCODING = 0x6240, "ContentEncoding";
+    CONTENT_ENCODING_ORDER = 0x5031, "ContentEncodingOrder";
+    CONTENT_ENCODING_SCOPE = 0x5032, "ContentEncodingScope";
+    CONTENT_ENCODING_TYPE = 0x5033, "ContentEncodingType";
+    CONTENT_COMPRESSION = 0x5034, "ContentCompression";
+    CONTENT_COMP_ALGO = 0x4254, "ContentCompAlgo";
+    CONTENT_COMP_SETTINGS = 0x4255, "ContentCompSettings";
+    CONTENT_ENCRYPTION = 0x5035, "ContentEncryption";
+    CONTENT_ENC_ALGO = 0x47E1, "ContentEncAlgo";
+    CONTENT_ENC_KEY_ID = 0x47E2, "ContentEncKeyID";
+    CONTENT_ENC_AES_SETTINGS = 0x47E7, "ContentEncAESSettings";
+    AES_SETTINGS_CIPHER_MODE = 0x47E8, "AESSettingsCipherMode";
+    CONTENT_SIGNATURE = 0x47E3, "ContentSignature";
+    CONTENT_SIG_KEY_ID = 0x47E4, "ContentSigKeyID";
+    CONTENT_SIG_ALGO = 0x47E5, "ContentSigAlgo";
+    CONTENT_SIG_HASH_ALGO = 0x47E6, "ContentSigHashAlgo";
+    CUES = 0x1C53BB6B, "Cues";
+    CUE_POINT = 0xBB, "CuePoint";
+    CUE_TIME = 0xB3, "CueTime";
+    CUE_TRACK_POSITIONS = 0xB7, "CueTrackPositions";
+    CUE_TRACK = 0xF7, "CueTrack";
+    CUE_CLUSTER_POSITION = 0xF1, "CueClusterPosition";
+    CUE_RELATIVE_POSITION = 0xF0, "CueRelativePosition";
+    CUE_DURATION = 0xB2, "CueDuration";
+    CUE_BLOCK_NUMBER = 0x5378, "CueBlockNumber";
+    CUE_CODEC_STATE = 0xEA, "CueCodecState";
+    CUE_REFERENCE = 0xDB, "CueReference";
+    CUE_REF_TIME = 0x96, "CueRefTime";
+    CUE_REF_CLUSTER = 0x97, "CueRefCluster";
+    CUE_REF_NUMBER = 0x535F, "CueRefNumber";
+    CUE_REF_CODEC_STATE = 0xEB, "CueRefCodecState";
+    ATTACHMENTS = 0x1941A469, "Attachments";
+    ATTACHED_FILE = 0x61A7, "AttachedFile";
+    FILE_DESCRIPTION = 0x467E, "FileDescription";
+    FILE_NAME = 0x466E, "FileName";
+    FILE_MEDIA_TYPE = 0x4660, "FileMediaType";
+    FILE_DATA = 0x465C, "FileData";
+    FILE_UID = 0x46AE, "FileUID";
+    FILE_REFERRAL = 0x4675, "FileReferral";
+    FILE_USED_START_TIME = 0x4661, "FileUsedStartTime";
+    FILE_USED_END_TIME = 0x4662, "FileUsedEndTime";
+    CHAPTERS = 0x1043A770, "Chapters";
+    EDITION_ENTRY = 0x45B9, "EditionEntry";
+    EDITION_UID = 0x45BC, "EditionUID";
+    EDITION_FLAG_HIDDEN = 0x45BD, "EditionFlagHidden";
+    EDITION_FLAG_DEFAULT = 0x45DB, "EditionFlagDefault";
+    EDITION_FLAG_ORDERED = 0x45DD, "EditionFlagOrdered";
+    EDITION_DISPLAY = 0x4520, "EditionDisplay";
+    EDITION_STRING = 0x4521, "EditionString";
+    EDITION_LANGUAGE_IETF = 0x45E4, "EditionLanguageIETF";
+    CHAPTER_ATOM = 0xB6, "ChapterAtom";
+    CHAPTER_UID = 0x73C4, "ChapterUID";
+    CHAPTER_STRING_UID = 0x5654, "ChapterStringUID";
+    CHAPTER_TIME_START = 0x91, "ChapterTimeStart";
+    CHAPTER_TIME_END = 0x92, "ChapterTimeEnd";
+    CHAPTER_FLAG_HIDDEN = 0x98, "ChapterFlagHidden";
+    CHAPTER_FLAG_ENABLED = 0x4598, "ChapterFlagEnabled";
+    CHAPTER_SEGMENT_UUID = 0x6E67, "ChapterSegmentUUID";
+    CHAPTER_SKIP_TYPE = 0x4588, "ChapterSkipType";
+    CHAPTER_SEGMENT_EDITION_UID = 0x6EBC, "ChapterSegmentEditionUID";
+    CHAPTER_PHYSICAL_EQUIV = 0x63C3, "ChapterPhysicalEquiv";
+    CHAPTER_TRACK = 0x8F, "ChapterTrack";
+    CHAPTER_TRACK_UID = 0x89, "ChapterTrackUID";
+    CHAPTER_DISPLAY = 0x80, "ChapterDisplay";
+    CHAP_STRING = 0x85, "ChapString";
+    CHAP_LANGUAGE = 0x437C, "ChapLanguage";
+    CHAP_LANGUAGE_BCP47 = 0x437D, "ChapLanguageBCP47";
+    CHAP_COUNTRY = 0x437E, "ChapCountry";
+    CHAP_PROCESS = 0x6944, "ChapProcess";
+    CHAP_PROCESS_CODEC_ID = 0x6955, "ChapProcessCodecID";
+    CHAP_PROCESS_PRIVATE = 0x450D, "ChapProcessPrivate";
+    CHAP_PROCESS_COMMAND = 0x6911, "ChapProcessCommand";
+    CHAP_PROCESS_TIME = 0x6922, "ChapProcessTime";
+    CHAP_PROCESS_DATA = 0x6933, "ChapProcessData";
+    TAGS = 0x1254C367, "Tags";
+    TAG = 0x7373, "Tag";
+    TARGETS = 0x63C0, "Targets";
+    TARGET_TYPE_VALUE = 0x68CA, "TargetTypeValue";
+    TARGET_TYPE = 0x63CA, "TargetType";
+    TAG_TRACK_UID = 0x63C5, "TagTrackUID";
+    TAG_EDITION_UID = 0x63C9, "TagEditionUID";
+    TAG_CHAPTER_UID = 0x63C4, "TagChapterUID";
+    TAG_ATTACHMENT_UID = 0x63C6, "TagAttachmentUID";
+    SIMPLE_TAG = 0x67C8, "SimpleTag";
+    TAG_NAME = 0x45A3, "TagName";
+    TAG_LANGUAGE = 0x447A, "TagLanguage";
+    TAG_LANGUAGE_BCP47 = 0x447B, "TagLanguageBCP47";
+    TAG_DEFAULT = 0x4484, "TagDefault";
+    TAG_DEFAULT_BOGUS = 0x44B4, "TagDefaultBogus";
+    TAG_STRING = 0x4487, "TagString";
+    TAG_BINARY = 0x4485, "TagBinary";
+}
+
+///
+/// Gets the specification name of an EBML header or Matroska element id (such as `"Cluster"` for [`CLUSTER`]).
+///
+/// Returns `None` for ids that aren't in [`EBML_NAMES`] or [`MATROSKA_NAMES`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::ids;
+///
+/// assert_eq!(Some("SimpleBlock"), ids::name(ids::SIMPLE_BLOCK));
+/// assert_eq!(Some("CRC-32"), ids::name(0xbf));
+/// assert_eq!(None, ids::name(0x4200));
+/// ```
+///
+pub fn name(id: u64) -> Option<&'static str> {
+    EBML_NAMES.iter().chain(MATROSKA_NAMES).find(|(tag_id, _)| *tag_id == id).map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::tools::is_vint;
+
+    use super::*;
+
+    #[test]
+    fn ids_are_unique_and_valid() {
+        let mut seen = HashSet::new();
+        for (id, name) in EBML_NAMES.iter().chain(MATROSKA_NAMES) {
+            assert!(seen.insert(*id), "Duplicate id 0x{:x} for {}", id, name);
+            assert!(is_vint(*id), "Invalid id 0x{:x} for {}", id, name);
+        }
+    }
+}
@@ -11,10 +11,35 @@
 //!
 //! # Features
 //!
-//! There is currently only one optional feature in this crate, but that may change over time as needs arise.
-//!
 //! * **derive-spec** -
-//!     When enabled, this provides the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro to simplify implementation of the [`EbmlSpecification`][`specs::EbmlSpecification`] and [`EbmlTag`][`specs::EbmlTag`] traits.  This introduces dependencies on [`syn`](https://crates.io/crates/syn), [`quote`](https://crates.io/crates/quote), and [`proc-macro2`](https://crates.io/crates/proc-macro2), so expect compile times to increase a little.
+//!   When enabled, this provides the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro to simplify implementation of the [`EbmlSpecification`][`specs::EbmlSpecification`] and [`EbmlTag`][`specs::EbmlTag`] traits.  This introduces dependencies on [`syn`](https://crates.io/crates/syn), [`quote`](https://crates.io/crates/quote), and [`proc-macro2`](https://crates.io/crates/proc-macro2), so expect compile times to increase a little.
+//!
+//! * **matroska-spec** -
+//!   When enabled, this also enables **derive-spec** and provides `specs::matroska::MatroskaSpec`, a complete specification for reading and writing Matroska and WebM files.
+//!
+//! * **futures** -
+//!   When enabled, this provides `TagIteratorAsync` for reading from [`futures::AsyncRead`](https://docs.rs/futures/latest/futures/io/trait.AsyncRead.html) sources, and `TagWriterAsync` for writing to [`futures::AsyncWrite`](https://docs.rs/futures/latest/futures/io/trait.AsyncWrite.html) destinations.
+//!
+//! * **tokio** -
+//!   When enabled, this also enables **futures** and allows `TagIteratorAsync` to read from [`tokio::io::AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html) sources and `TagWriterAsync` to write to [`tokio::io::AsyncWrite`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncWrite.html) destinations.
+//!
+//! * **std** -
+//!   Enabled by default.  Without it, the crate is `no_std` and only needs `alloc`: [`EbmlPushParser`] reads tags from data that is fed to it, [`writer::SliceWriter`] writes tags into a byte slice, and the [`tools`], [`ids`], and [`specs`] modules are available.  Everything that reads from [`std::io::Read`] sources or writes to [`std::io::Write`] destinations (such as `TagIterator` and `TagWriter`) requires this feature, as do all of the other features.
+//!
+//! * **proptest** -
+//!   When enabled, this provides the `strategies` module with [`proptest`](https://crates.io/crates/proptest) strategies that generate valid tag trees, extreme vint values, and boundary-size elements for property-testing code that handles EBML.
+//!
+//! * **parallel** -
+//!   When enabled, this provides `scan_clusters_parallel()` and `scan_clusters_parallel_with()`, which parse the clusters of a seekable document on multiple threads, e.g. to build a seek index for a large file quickly.
+//!
+//! * **mmap** -
+//!   When enabled, this provides `TagIterator::from_mmap()` for reading files through a memory map (using [`memmap2`](https://crates.io/crates/memmap2)) rather than through read calls.  The map is also accepted by `iterator::TagSliceIterator` for parsing without copying tag data, which is the cheapest way to index large collections of files.
+//!
+//! * **ebml-schema** -
+//!   When enabled, this provides `dynamic::DynamicSpec`, a specification loaded at runtime from an [EBML Schema](https://www.rfc-editor.org/rfc/rfc8794.html#name-ebml-schema) XML document (using [`roxmltree`](https://crates.io/crates/roxmltree)), so tools can read, write and validate any document type without recompiling.
+//!
+//! * **serde** -
+//!   When enabled, this provides the `serde_ebml` module with a [`serde`](https://crates.io/crates/serde) `Serializer` and `Deserializer`, so types implementing `Serialize` and `Deserialize` can be written to and read from EBML documents with `to_writer()`, `from_iterator()` and `from_tree()`.
 //!
 //! [EBML]: http://ebml.sourceforge.net/
 //! [webm]: https://www.webmproject.org/
@@ -23,23 +48,187 @@
 //! [webm-iterable]: https://crates.io/crates/webm_iterable
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod errors;
+#[cfg(feature = "std")]
 mod tag_iterator;
 #[cfg(feature = "futures")]
 mod tag_iterator_async;
+#[cfg(feature = "std")]
 mod tag_writer;
+mod tag_writer_util;
+#[cfg(feature = "futures")]
+mod tag_writer_async;
+mod slice_writer;
+#[cfg(feature = "std")]
+mod tag_tree;
+#[cfg(feature = "std")]
+mod tag_query;
+mod push_parser;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "std")]
+mod prefetch;
+#[cfg(feature = "std")]
+mod dyn_spec;
+#[cfg(feature = "std")]
+mod round_trip;
+#[cfg(feature = "std")]
+mod analysis;
+#[cfg(feature = "std")]
+mod sidecar_index;
+#[cfg(feature = "std")]
+mod pipe;
+#[cfg(feature = "std")]
+mod dump;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "std")]
+mod validator;
+#[cfg(feature = "ebml-schema")]
+mod ebml_schema;
+#[cfg(feature = "std")]
+mod ebml_header;
+#[cfg(feature = "std")]
+mod ebml_header_spec;
+// The specification macros refer to this crate by name
+#[cfg(feature = "matroska-spec")]
+extern crate self as ebml_iterable;
+
 pub mod tools;
+pub mod vint;
+pub mod ids;
+#[cfg(feature = "std")]
+pub mod matroska;
 pub mod specs;
+#[cfg(feature = "proptest")]
+pub mod strategies;
 mod tag_iterator_util;
 mod spec_util;
 
+#[cfg(feature = "std")]
 pub use self::tag_iterator::TagIterator;
 #[cfg(feature = "futures")]
 pub use self::tag_iterator_async::TagIteratorAsync;
+#[cfg(feature = "std")]
 pub use self::tag_writer::TagWriter;
+#[cfg(feature = "futures")]
+pub use self::tag_writer_async::TagWriterAsync;
+#[cfg(feature = "std")]
+pub use self::tag_tree::{read_tree, TagTree};
+#[cfg(feature = "std")]
+pub use self::tag_query::EbmlQuery;
+pub use self::push_parser::EbmlPushParser;
+#[cfg(feature = "std")]
+pub use self::pipe::{copy_ebml, pipe, Decision};
+#[cfg(feature = "std")]
+pub use self::dump::dump;
+#[cfg(feature = "parallel")]
+pub use self::parallel::{scan_clusters_parallel, scan_clusters_parallel_with};
 
 pub mod iterator {
     pub use super::tag_iterator_util::AllowableErrors;
+    pub use super::tag_iterator_util::Utf8Handling;
+    pub use super::tag_iterator_util::BorrowedTag;
+    pub use super::tag_iterator_util::DEFAULT_BUFFER_LEN;
+    pub use super::tag_iterator_util::DEFAULT_MAX_DEPTH;
+    pub use super::tag_iterator_util::Quirk;
+    pub use super::tag_iterator_util::{SkipReason, SkippedTag};
+    pub use super::tag_iterator_util::TagSpan;
+    pub use super::tag_iterator_util::TagIteratorOptions;
+    #[cfg(feature = "std")]
+    pub use super::tag_iterator::{TagPositions, TagSliceIterator, TagSubtrees};
+    #[cfg(feature = "std")]
+    pub use super::tag_iterator_util::ReadSeek;
+    #[cfg(feature = "std")]
+    pub use super::prefetch::{PrefetchReader, DEFAULT_PREFETCH_CHUNKS};
+    #[cfg(feature = "tokio")]
+    pub use super::tag_iterator_async::TokioReader;
+}
+
+pub mod writer {
+    //!
+    //! Types used to configure a [`TagWriter`](super::TagWriter).
+    //!
+    #[cfg(feature = "std")]
+    pub use super::tag_writer::{FlushPolicy, FloatWidth, MasterWriter, WriteSeek, WriteOptions};
+    pub use super::tag_writer_util::SizeWidth;
+    pub use super::slice_writer::SliceWriter;
+    #[cfg(feature = "futures")]
+    pub use super::tag_writer_async::DEFAULT_ASYNC_WRITE_CAPACITY;
+    #[cfg(feature = "tokio")]
+    pub use super::tag_writer_async::TokioWriter;
+    pub use super::tag_iterator_util::DEFAULT_MAX_DEPTH;
+}
+
+#[cfg(feature = "std")]
+pub mod dynamic {
+    //!
+    //! Type-erased reading and writing for specifications selected at runtime.
+    //!
+    pub use super::dyn_spec::{DynSpecification, DynTag, DynTagIterator, DynTagWriter, DynValue, StaticSpec};
+    #[cfg(feature = "ebml-schema")]
+    pub use super::ebml_schema::DynamicSpec;
+}
+
+#[cfg(feature = "std")]
+pub mod tree {
+    //!
+    //! Types used to navigate a [`TagTree`](super::TagTree).
+    //!
+    pub use super::tag_tree::{NodeId, Siblings, TreeNode, TreeValue};
+}
+
+#[cfg(feature = "std")]
+pub mod conformance {
+    //!
+    //! Test support for checking that documents survive being read and re-written.
+    //!
+    pub use super::round_trip::{round_trip, round_trip_dir, Difference, FileReport, RoundTripOutcome, RoundTripReport, MAX_REPORTED_DIFFERENCES};
+}
+
+#[cfg(feature = "std")]
+pub mod stats {
+    //!
+    //! Size statistics for EBML documents.
+    //!
+    pub use super::analysis::{analyze, DocumentStats, ElementStats, LargeElement};
+}
+
+#[cfg(feature = "std")]
+pub mod header {
+    //!
+    //! Reading and validating the EBML header at the start of a document.
+    //!
+    pub use super::ebml_header::{EbmlHeader, ReaderSupport};
+    pub use super::ebml_header_spec::EbmlHeaderSpec;
+}
+
+#[cfg(feature = "std")]
+pub mod index {
+    //!
+    //! Sidecar indexes of tag locations, for seeking within large documents without scanning them.
+    //!
+    pub use super::sidecar_index::{ElementIndex, IndexBuilder, IndexEntry};
+}
+
+#[cfg(feature = "std")]
+pub mod validate {
+    //!
+    //! Checking documents for conformance with a specification, reporting every violation found.
+    //!
+    pub use super::validator::{Validator, ValueRange, Violation, ViolationKind};
+}
+
+#[cfg(feature = "serde")]
+pub mod serde_ebml {
+    //!
+    //! Conversion between EBML documents and types implementing `serde`'s `Serialize` and `Deserialize` traits.
+    //!
+    pub use super::serde_impl::{from_iterator, from_tree, to_writer, Deserializer, Serializer};
 }
 
 pub mod error {
@@ -48,7 +237,21 @@ pub mod error {
     //!
     pub use super::errors::tag_iterator::TagIteratorError;
     pub use super::errors::tag_iterator::CorruptedFileError;
+    pub use super::errors::tag_iterator::ReadErrorKind;
     pub use super::errors::tag_writer::TagWriterError;
+    pub use super::errors::push_parser::FeedError;
+    #[cfg(feature = "std")]
+    pub use super::errors::pipe::PipeError;
+    #[cfg(feature = "std")]
+    pub use super::errors::dump::DumpError;
+    #[cfg(feature = "std")]
+    pub use super::errors::block::BlockError;
+    #[cfg(feature = "std")]
+    pub use super::errors::header::HeaderError;
+    #[cfg(feature = "serde")]
+    pub use super::errors::serialization::SerdeError;
+    #[cfg(feature = "ebml-schema")]
+    pub use super::errors::schema::SchemaError;
 
     ///
     /// Error details that may be included in some thrown errors
@@ -0,0 +1,97 @@
+//!
+//! Typed helpers for commonly used [Matroska][mkv] element groups.
+//!
+//! Like the rest of this crate, these helpers are spec-agnostic.  They work with any `TSpec` implementing [`EbmlSpecification`] and [`EbmlTag`] (such as the spec provided by [webm-iterable][webm-iterable]).  Reading requires a [`Master::Full`] variant of the relevant element, which can be obtained from a [`TagIterator`](crate::TagIterator) by including the element in `tags_to_buffer`.
+//!
+//! When converting a helper back into a tag, any child element that `TSpec` does not define is emitted as a "RawTag" holding the correctly encoded payload.  The [`TagWriter`](crate::TagWriter) produces identical bytes either way.
+//!
+//! [mkv]: https://www.matroska.org/technical/elements.html
+//! [webm-iterable]: https://crates.io/crates/webm_iterable
+//!
+
+pub mod block;
+pub mod block_additions;
+pub mod colour;
+pub mod projection;
+pub mod segment_uid;
+pub mod timestamps;
+
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::tools;
+
+///
+/// Returns the children of `tag` if it is a [`Master::Full`] variant with the given id.
+///
+pub(crate) fn get_children<TSpec>(tag: &TSpec, id: u64) -> Option<&[TSpec]>
+    where TSpec: EbmlTag<TSpec> + Clone
+{
+    if tag.get_id() != id {
+        return None;
+    }
+
+    match tag.as_master() {
+        Some(Master::Full(children)) => Some(children),
+        _ => None,
+    }
+}
+
+pub(crate) fn read_unsigned_int<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<u64> {
+    tag.as_unsigned_int().copied()
+        .or_else(|| tag.as_binary().and_then(|data| tools::arr_to_u64(data).ok()))
+}
+
+pub(crate) fn read_float<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<f64> {
+    tag.as_float().copied()
+        .or_else(|| tag.as_binary().and_then(|data| tools::arr_to_f64(data).ok()))
+}
+
+pub(crate) fn read_binary<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Vec<u8>> {
+    tag.as_binary().map(|data| data.to_vec())
+}
+
+///
+/// Collects child tags for a master element, falling back to raw tags for ids that `TSpec` does not define.
+///
+pub(crate) struct ChildBuilder<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    children: Vec<TSpec>,
+}
+
+impl<TSpec> ChildBuilder<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    pub fn new() -> Self {
+        ChildBuilder { children: Vec::new() }
+    }
+
+    pub fn unsigned_int(&mut self, id: u64, value: Option<u64>) {
+        if let Some(value) = value {
+            let bytes = value.to_be_bytes();
+            let first = bytes.iter().position(|b| *b != 0).unwrap_or(7);
+            self.children.push(TSpec::get_unsigned_int_tag(id, value).unwrap_or_else(|| TSpec::get_raw_tag(id, &bytes[first..])));
+        }
+    }
+
+    pub fn float(&mut self, id: u64, value: Option<f64>) {
+        if let Some(value) = value {
+            self.children.push(TSpec::get_float_tag(id, value).unwrap_or_else(|| TSpec::get_raw_tag(id, &value.to_be_bytes())));
+        }
+    }
+
+    pub fn binary(&mut self, id: u64, value: Option<&[u8]>) {
+        if let Some(value) = value {
+            self.children.push(TSpec::get_binary_tag(id, value).unwrap_or_else(|| TSpec::get_raw_tag(id, value)));
+        }
+    }
+
+    pub fn tag(&mut self, tag: Option<TSpec>) {
+        if let Some(tag) = tag {
+            self.children.push(tag);
+        }
+    }
+
+    pub fn build(self, id: u64) -> Option<TSpec> {
+        TSpec::get_master_tag(id, Master::Full(self.children))
+    }
+}
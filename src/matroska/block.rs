@@ -0,0 +1,457 @@
+//!
+//! Parsing and serializing the payloads of `SimpleBlock` and `Block` elements.
+//!
+//! Matroska stores frames in binary elements with their own internal structure: a track number (as a vint), a timestamp relative to the enclosing `Cluster`, a flags byte, and then the frame data.  Several frames can be packed into one block with "lacing", which stores the size of every frame but the last using one of three encodings (Xiph, EBML, or fixed-size).  [`SimpleBlock`] and [`Block`] handle all of this, so the frames can be worked with directly.
+//!
+//! See the [Matroska specification](https://www.matroska.org/technical/notes.html#block-structure) for details of the format.
+//!
+
+use std::convert::TryFrom;
+
+use crate::error::BlockError;
+use crate::specs::{EbmlSpecification, EbmlTag};
+use crate::vint::{read_vint, write_vint_with_width, Vint, MAX_WIDTH};
+
+pub use crate::ids::{BLOCK, SIMPLE_BLOCK};
+
+const KEYFRAME_FLAG: u8 = 0x80;
+const INVISIBLE_FLAG: u8 = 0x08;
+const DISCARDABLE_FLAG: u8 = 0x01;
+
+///
+/// The maximum number of frames a laced block can hold.
+///
+pub const MAX_LACED_FRAMES: usize = 256;
+
+///
+/// How the frames of a block are packed together.
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Lacing {
+
+    ///
+    /// The block holds a single frame.  This is the default.
+    ///
+    #[default]
+    None,
+
+    ///
+    /// Frame sizes are written as sums of bytes, where a byte of 255 means the size continues in the next byte.
+    ///
+    Xiph,
+
+    ///
+    /// Every frame has the same size, so no sizes are written.
+    ///
+    FixedSize,
+
+    ///
+    /// The first frame size is written as a vint, and every other size as a signed vint holding the difference from the previous size.
+    ///
+    Ebml,
+}
+
+impl Lacing {
+    fn from_flags(flags: u8) -> Self {
+        match (flags >> 1) & 0x03 {
+            0 => Lacing::None,
+            1 => Lacing::Xiph,
+            2 => Lacing::FixedSize,
+            _ => Lacing::Ebml,
+        }
+    }
+
+    fn flags(self) -> u8 {
+        let bits = match self {
+            Lacing::None => 0,
+            Lacing::Xiph => 1,
+            Lacing::FixedSize => 2,
+            Lacing::Ebml => 3,
+        };
+        bits << 1
+    }
+}
+
+///
+/// The contents of a `SimpleBlock` element.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::matroska::block::{Lacing, SimpleBlock};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let block = SimpleBlock {
+///     track: 1,
+///     timestamp: -20,
+///     keyframe: true,
+///     lacing: Lacing::Xiph,
+///     frames: vec![vec![0x01; 300], vec![0x02; 10]],
+///     ..Default::default()
+/// };
+///
+/// let data = block.to_bytes()?;
+/// assert_eq!(&[0x81, 0xff, 0xec, 0x82, 0x01, 0xff, 0x2d], &data[..7]);
+/// assert_eq!(block, SimpleBlock::parse(&data)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimpleBlock {
+    pub track: u64,
+    pub timestamp: i16,
+    pub keyframe: bool,
+    pub invisible: bool,
+    pub discardable: bool,
+    pub lacing: Lacing,
+    pub frames: Vec<Vec<u8>>,
+}
+
+impl SimpleBlock {
+
+    ///
+    /// Parses the data of a `SimpleBlock` element.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`BlockError`] if the data is too short or the lace sizes don't match the data.
+    ///
+    pub fn parse(data: &[u8]) -> Result<Self, BlockError> {
+        let RawBlock { track, timestamp, flags, frames } = parse_block(data)?;
+        Ok(SimpleBlock {
+            track,
+            timestamp,
+            keyframe: flags & KEYFRAME_FLAG != 0,
+            invisible: flags & INVISIBLE_FLAG != 0,
+            discardable: flags & DISCARDABLE_FLAG != 0,
+            lacing: Lacing::from_flags(flags),
+            frames,
+        })
+    }
+
+    ///
+    /// Serializes this block into the data of a `SimpleBlock` element.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`BlockError`] if the frames can't be written with [`Self::lacing`] or the track number is too large.
+    ///
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BlockError> {
+        let mut flags = 0;
+        if self.keyframe {
+            flags |= KEYFRAME_FLAG;
+        }
+        if self.invisible {
+            flags |= INVISIBLE_FLAG;
+        }
+        if self.discardable {
+            flags |= DISCARDABLE_FLAG;
+        }
+        write_block(self.track, self.timestamp, flags, self.lacing, &self.frames)
+    }
+
+    ///
+    /// Reads a [`SimpleBlock`] from a `SimpleBlock` tag.
+    ///
+    /// Returns `None` if `tag` is not a binary `SimpleBlock` element or its data can't be parsed.  Use [`Self::parse()`] to find out why parsing failed.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        if tag.get_id() != SIMPLE_BLOCK {
+            return None;
+        }
+        SimpleBlock::parse(tag.as_binary()?).ok()
+    }
+
+    ///
+    /// Builds a binary `SimpleBlock` tag holding this block.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::to_bytes()`].
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Result<TSpec, BlockError> {
+        let data = self.to_bytes()?;
+        Ok(TSpec::get_binary_tag(SIMPLE_BLOCK, &data).unwrap_or_else(|| TSpec::get_raw_tag(SIMPLE_BLOCK, &data)))
+    }
+}
+
+///
+/// The contents of a `Block` element, found inside of a `BlockGroup`.
+///
+/// Unlike [`SimpleBlock`], a `Block` has no keyframe or discardable flags.  That information is stored in the other children of the `BlockGroup` instead.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Block {
+    pub track: u64,
+    pub timestamp: i16,
+    pub invisible: bool,
+    pub lacing: Lacing,
+    pub frames: Vec<Vec<u8>>,
+}
+
+impl Block {
+
+    ///
+    /// Parses the data of a `Block` element.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`BlockError`] if the data is too short or the lace sizes don't match the data.
+    ///
+    pub fn parse(data: &[u8]) -> Result<Self, BlockError> {
+        let RawBlock { track, timestamp, flags, frames } = parse_block(data)?;
+        Ok(Block {
+            track,
+            timestamp,
+            invisible: flags & INVISIBLE_FLAG != 0,
+            lacing: Lacing::from_flags(flags),
+            frames,
+        })
+    }
+
+    ///
+    /// Serializes this block into the data of a `Block` element.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`BlockError`] if the frames can't be written with [`Self::lacing`] or the track number is too large.
+    ///
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BlockError> {
+        let flags = if self.invisible { INVISIBLE_FLAG } else { 0 };
+        write_block(self.track, self.timestamp, flags, self.lacing, &self.frames)
+    }
+
+    ///
+    /// Reads a [`Block`] from a `Block` tag.
+    ///
+    /// Returns `None` if `tag` is not a binary `Block` element or its data can't be parsed.  Use [`Self::parse()`] to find out why parsing failed.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        if tag.get_id() != BLOCK {
+            return None;
+        }
+        Block::parse(tag.as_binary()?).ok()
+    }
+
+    ///
+    /// Builds a binary `Block` tag holding this block.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::to_bytes()`].
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Result<TSpec, BlockError> {
+        let data = self.to_bytes()?;
+        Ok(TSpec::get_binary_tag(BLOCK, &data).unwrap_or_else(|| TSpec::get_raw_tag(BLOCK, &data)))
+    }
+}
+
+///
+/// The fields shared by `SimpleBlock` and `Block` elements.
+///
+struct RawBlock {
+    track: u64,
+    timestamp: i16,
+    flags: u8,
+    frames: Vec<Vec<u8>>,
+}
+
+fn parse_block(data: &[u8]) -> Result<RawBlock, BlockError> {
+    let (track, track_len) = read_vint(data).map_err(|_| BlockError::InvalidVint)?.ok_or(BlockError::UnexpectedEnd)?;
+    let header = data.get(track_len..(track_len + 3)).ok_or(BlockError::UnexpectedEnd)?;
+    let timestamp = i16::from_be_bytes([header[0], header[1]]);
+    let flags = header[2];
+    let frames = read_frames(&data[(track_len + 3)..], Lacing::from_flags(flags))?;
+    Ok(RawBlock { track, timestamp, flags, frames })
+}
+
+fn read_frames(payload: &[u8], lacing: Lacing) -> Result<Vec<Vec<u8>>, BlockError> {
+    if lacing == Lacing::None {
+        return Ok(vec![payload.to_vec()]);
+    }
+
+    let (count, mut rest) = payload.split_first().ok_or(BlockError::UnexpectedEnd)?;
+    let count = *count as usize + 1;
+    let mut sizes: Vec<usize> = Vec::with_capacity(count);
+    match lacing {
+        Lacing::None => unreachable!("unlaced blocks were handled above"),
+        Lacing::Xiph => {
+            for _ in 1..count {
+                let mut size = 0;
+                loop {
+                    let (byte, remaining) = rest.split_first().ok_or(BlockError::UnexpectedEnd)?;
+                    rest = remaining;
+                    size += *byte as usize;
+                    if *byte != 0xff {
+                        break;
+                    }
+                }
+                sizes.push(size);
+            }
+        },
+        Lacing::Ebml => {
+            if count > 1 {
+                let (first, len) = read_vint(rest).map_err(|_| BlockError::InvalidVint)?.ok_or(BlockError::UnexpectedEnd)?;
+                rest = &rest[len..];
+                let mut size = i64::try_from(first).map_err(|_| BlockError::InvalidLaceSizes)?;
+                sizes.push(size as usize);
+                for _ in 2..count {
+                    let (diff, len) = read_lace_difference(rest)?;
+                    rest = &rest[len..];
+                    size = size.checked_add(diff).filter(|size| *size >= 0).ok_or(BlockError::InvalidLaceSizes)?;
+                    sizes.push(size as usize);
+                }
+            }
+        },
+        Lacing::FixedSize => {
+            if rest.len() % count != 0 {
+                return Err(BlockError::InvalidLaceSizes);
+            }
+            sizes.resize(count - 1, rest.len() / count);
+        },
+    }
+
+    let laced = sizes.iter().try_fold(0usize, |total, size| total.checked_add(*size)).ok_or(BlockError::InvalidLaceSizes)?;
+    if laced > rest.len() {
+        return Err(BlockError::InvalidLaceSizes);
+    }
+    sizes.push(rest.len() - laced);
+
+    let mut frames = Vec::with_capacity(count);
+    for size in sizes {
+        let (frame, remaining) = rest.split_at(size);
+        frames.push(frame.to_vec());
+        rest = remaining;
+    }
+    Ok(frames)
+}
+
+fn write_block(track: u64, timestamp: i16, flags: u8, lacing: Lacing, frames: &[Vec<u8>]) -> Result<Vec<u8>, BlockError> {
+    let count = frames.len();
+    let max_count = if lacing == Lacing::None { 1 } else { MAX_LACED_FRAMES };
+    if count == 0 || count > max_count {
+        return Err(BlockError::FrameCount(count));
+    }
+
+    let mut data = track.as_vint().map_err(|_| BlockError::TrackNumberOverflow(track))?;
+    data.extend_from_slice(&timestamp.to_be_bytes());
+    data.push(flags | lacing.flags());
+
+    if lacing != Lacing::None {
+        data.push((count - 1) as u8);
+        let laced = &frames[..(count - 1)];
+        match lacing {
+            Lacing::None => unreachable!("unlaced blocks have no lace header"),
+            Lacing::Xiph => {
+                for frame in laced {
+                    data.resize(data.len() + frame.len() / 0xff, 0xff);
+                    data.push((frame.len() % 0xff) as u8);
+                }
+            },
+            Lacing::Ebml => {
+                if let Some((first, others)) = laced.split_first() {
+                    (first.len() as u64).write_vint(&mut data).map_err(|_| BlockError::InvalidLaceSizes)?;
+                    let mut previous = first.len();
+                    for frame in others {
+                        write_lace_difference(frame.len() as i64 - previous as i64, &mut data)?;
+                        previous = frame.len();
+                    }
+                }
+            },
+            Lacing::FixedSize => {
+                if frames.iter().any(|frame| frame.len() != frames[0].len()) {
+                    return Err(BlockError::MismatchedFrameSizes);
+                }
+            },
+        }
+    }
+
+    for frame in frames {
+        data.extend_from_slice(frame);
+    }
+    Ok(data)
+}
+
+///
+/// The offset subtracted from the raw value of an EBML lace size difference written with `width` bytes.
+///
+/// Lace differences don't use the two's complement notation of [`SignedVint`](crate::vint::SignedVint).  Instead the range of the vint is shifted so that half of it is negative.
+///
+fn lace_difference_bias(width: usize) -> i64 {
+    (1 << (7 * width - 1)) - 1
+}
+
+fn read_lace_difference(data: &[u8]) -> Result<(i64, usize), BlockError> {
+    let (raw, len) = read_vint(data).map_err(|_| BlockError::InvalidVint)?.ok_or(BlockError::UnexpectedEnd)?;
+    Ok((raw as i64 - lace_difference_bias(len), len))
+}
+
+fn write_lace_difference(diff: i64, dest: &mut Vec<u8>) -> Result<(), BlockError> {
+    let width = (1..=MAX_WIDTH).find(|width| diff.abs() <= lace_difference_bias(*width)).ok_or(BlockError::InvalidLaceSizes)?;
+    let raw = (diff + lace_difference_bias(width)) as u64;
+    dest.extend(write_vint_with_width(raw, width).map_err(|_| BlockError::InvalidLaceSizes)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::*;
+
+    fn frames() -> Vec<Vec<u8>> {
+        vec![vec![1; 800], vec![2; 500], vec![3; 1000], vec![4; 2]]
+    }
+
+    #[test]
+    fn lacing_round_trip() {
+        for lacing in [Lacing::Xiph, Lacing::Ebml] {
+            let block = SimpleBlock { track: 2, timestamp: 300, keyframe: true, discardable: true, lacing, frames: frames(), ..Default::default() };
+            let data = block.to_bytes().unwrap();
+            assert_eq!(block, SimpleBlock::parse(&data).unwrap());
+        }
+
+        let block = Block { track: 200, timestamp: -1, invisible: true, lacing: Lacing::FixedSize, frames: vec![vec![5; 3]; 256] };
+        let data = block.to_bytes().unwrap();
+        assert_eq!(block.frames.len() * 3 + 6, data.len());
+        assert_eq!(block, Block::parse(&data).unwrap());
+    }
+
+    #[test]
+    fn ebml_lace_sizes() {
+        // Example from the Matroska specification: sizes of 800, 500, and 1000 bytes, then the rest
+        let block = SimpleBlock { track: 1, lacing: Lacing::Ebml, frames: frames(), ..Default::default() };
+        let data = block.to_bytes().unwrap();
+        assert_eq!(&[0x81, 0x00, 0x00, 0x06, 0x03, 0x43, 0x20, 0x5e, 0xd3, 0x61, 0xf3], &data[..11]);
+    }
+
+    #[test]
+    fn invalid_blocks() {
+        assert_eq!(Err(BlockError::UnexpectedEnd), SimpleBlock::parse(&[0x81, 0x00]));
+        assert_eq!(Err(BlockError::InvalidVint), SimpleBlock::parse(&[0x00, 0x00, 0x00, 0x00]));
+        assert_eq!(Err(BlockError::UnexpectedEnd), SimpleBlock::parse(&[0x81, 0x00, 0x00, 0x02]));
+        // Xiph lace size larger than the data
+        assert_eq!(Err(BlockError::InvalidLaceSizes), SimpleBlock::parse(&[0x81, 0x00, 0x00, 0x02, 0x01, 0x05, 0x01]));
+        // Fixed-size lacing that doesn't divide the data
+        assert_eq!(Err(BlockError::InvalidLaceSizes), SimpleBlock::parse(&[0x81, 0x00, 0x00, 0x04, 0x01, 0x01, 0x02, 0x03]));
+
+        let block = SimpleBlock { frames: frames(), ..Default::default() };
+        assert_eq!(Err(BlockError::FrameCount(4)), block.to_bytes());
+        let block = SimpleBlock { lacing: Lacing::FixedSize, frames: frames(), ..Default::default() };
+        assert_eq!(Err(BlockError::MismatchedFrameSizes), block.to_bytes());
+        let block = SimpleBlock { lacing: Lacing::Xiph, frames: vec![vec![]; 257], ..Default::default() };
+        assert_eq!(Err(BlockError::FrameCount(257)), block.to_bytes());
+        let block = SimpleBlock { track: u64::MAX, frames: vec![vec![]], ..Default::default() };
+        assert_eq!(Err(BlockError::TrackNumberOverflow(u64::MAX)), block.to_bytes());
+    }
+
+    #[test]
+    fn block_tags() {
+        let block = SimpleBlock { track: 1, keyframe: true, frames: vec![vec![0x10, 0x20]], ..Default::default() };
+        let tag: EmptySpec = block.to_tag().unwrap();
+        assert_eq!(EmptySpec::with_data(SIMPLE_BLOCK, &[0x81, 0x00, 0x00, 0x80, 0x10, 0x20]), tag);
+        assert_eq!(Some(block), SimpleBlock::from_tag(&tag));
+        assert_eq!(None, Block::from_tag(&tag));
+    }
+}
@@ -0,0 +1,160 @@
+//!
+//! Typed access to the `BlockAdditions` element that carries extra per-frame data.
+//!
+//! `BlockAdditions` lives inside a `BlockGroup` next to the `Block` it extends.  Each `BlockMore` child pairs a `BlockAddID` with an opaque `BlockAdditional` payload - e.g. the alpha channel of a VP9 frame in WebM, or ITU-T T.35 metadata.  The meaning of each id is declared by the track's `BlockAdditionMapping` elements (or by the codec, for id 1).
+//!
+
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+
+use super::{get_children, read_binary, read_unsigned_int, ChildBuilder};
+
+pub use crate::ids::{BLOCK_GROUP, BLOCK_ADDITIONS, BLOCK_MORE, BLOCK_ADD_ID, BLOCK_ADDITIONAL};
+
+///
+/// The value of `BlockAddID` when the element is absent, as defined by the Matroska specification.
+///
+pub const DEFAULT_BLOCK_ADD_ID: u64 = 1;
+
+///
+/// The contents of a single `BlockMore` element.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockMore {
+    pub add_id: u64,
+    pub additional: Vec<u8>,
+}
+
+impl BlockMore {
+
+    ///
+    /// Reads a [`BlockMore`] from a `BlockMore` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`] `BlockMore` element or if it has no `BlockAdditional` child.  A missing `BlockAddID` is read as [`DEFAULT_BLOCK_ADD_ID`].
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        let children = get_children(tag, BLOCK_MORE)?;
+        let mut add_id = None;
+        let mut additional = None;
+        for child in children {
+            match child.get_id() {
+                BLOCK_ADD_ID => add_id = read_unsigned_int(child),
+                BLOCK_ADDITIONAL => additional = read_binary(child),
+                _ => {},
+            }
+        }
+        Some(BlockMore {
+            add_id: add_id.unwrap_or(DEFAULT_BLOCK_ADD_ID),
+            additional: additional?,
+        })
+    }
+
+    ///
+    /// Builds a [`Master::Full`] `BlockMore` tag.
+    ///
+    /// `BlockAddID` is always written, even if it matches [`DEFAULT_BLOCK_ADD_ID`].  Returns `None` if `TSpec` does not define the `BlockMore` master element.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Option<TSpec> {
+        let mut builder = ChildBuilder::new();
+        builder.unsigned_int(BLOCK_ADD_ID, Some(self.add_id));
+        builder.binary(BLOCK_ADDITIONAL, Some(&self.additional));
+        builder.build(BLOCK_MORE)
+    }
+}
+
+///
+/// The contents of a `BlockAdditions` element.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::matroska::block_additions::{BlockAdditions, BlockMore};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let additions = BlockAdditions {
+///     more: vec![BlockMore { add_id: 1, additional: vec![0x82, 0x49, 0x83] }],
+/// };
+///
+/// let tag: EmptySpec = additions.to_tag().unwrap();
+/// assert_eq!(BlockAdditions::from_tag(&tag), Some(additions));
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockAdditions {
+    pub more: Vec<BlockMore>,
+}
+
+impl BlockAdditions {
+
+    ///
+    /// Reads a [`BlockAdditions`] from a `BlockAdditions` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`] `BlockAdditions` element.  Malformed `BlockMore` children are skipped.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        let children = get_children(tag, BLOCK_ADDITIONS)?;
+        Some(BlockAdditions {
+            more: children.iter().filter_map(BlockMore::from_tag).collect(),
+        })
+    }
+
+    ///
+    /// Reads the [`BlockAdditions`] attached to a frame from its `BlockGroup` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`] `BlockGroup` element or if it does not contain `BlockAdditions`.
+    ///
+    pub fn from_block_group<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        get_children(tag, BLOCK_GROUP)?.iter().find_map(BlockAdditions::from_tag)
+    }
+
+    ///
+    /// Finds the payload for a specific `BlockAddID`, if present.
+    ///
+    pub fn get(&self, add_id: u64) -> Option<&[u8]> {
+        self.more.iter().find(|m| m.add_id == add_id).map(|m| m.additional.as_slice())
+    }
+
+    ///
+    /// Builds a [`Master::Full`] `BlockAdditions` tag.
+    ///
+    /// Returns `None` if `TSpec` does not define the `BlockAdditions` or `BlockMore` master elements.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Option<TSpec> {
+        let more = self.more.iter().map(BlockMore::to_tag).collect::<Option<Vec<TSpec>>>()?;
+        TSpec::get_master_tag(BLOCK_ADDITIONS, Master::Full(more))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::*;
+
+    #[test]
+    fn block_additions_round_trip() {
+        let additions = BlockAdditions {
+            more: vec![
+                BlockMore { add_id: 1, additional: vec![1, 2, 3] },
+                BlockMore { add_id: 4, additional: vec![0xB5, 0x00, 0x3C] },
+            ],
+        };
+        let tag: EmptySpec = additions.to_tag().expect("EmptySpec supports master tags");
+        assert_eq!(Some(additions), BlockAdditions::from_tag(&tag));
+    }
+
+    #[test]
+    fn block_additions_from_block_group() {
+        let group = EmptySpec::with_children(BLOCK_GROUP, vec![
+            EmptySpec::with_data(0xA1, &[0x81, 0x00, 0x00, 0x80]),
+            EmptySpec::with_children(BLOCK_ADDITIONS, vec![
+                EmptySpec::with_children(BLOCK_MORE, vec![
+                    EmptySpec::with_data(BLOCK_ADDITIONAL, &[9, 9]),
+                ]),
+            ]),
+        ]);
+
+        let additions = BlockAdditions::from_block_group(&group).expect("BlockGroup has additions");
+        assert_eq!(Some(&[9u8, 9][..]), additions.get(DEFAULT_BLOCK_ADD_ID));
+        assert_eq!(None, additions.get(2));
+    }
+}
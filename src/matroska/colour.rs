@@ -0,0 +1,261 @@
+//!
+//! Typed access to the `Colour` element tree used to describe HDR video.
+//!
+//! `Colour` lives under `Segment/Tracks/TrackEntry/Video` and carries the colour primaries, transfer characteristics, matrix coefficients, and (optionally) the `MasteringMetadata` of the mastering display.
+//!
+
+use crate::specs::{EbmlSpecification, EbmlTag};
+
+use super::{get_children, read_float, read_unsigned_int, ChildBuilder};
+
+pub use crate::ids::{
+    COLOUR,
+    MATRIX_COEFFICIENTS,
+    BITS_PER_CHANNEL,
+    CHROMA_SUBSAMPLING_HORZ,
+    CHROMA_SUBSAMPLING_VERT,
+    CB_SUBSAMPLING_HORZ,
+    CB_SUBSAMPLING_VERT,
+    CHROMA_SITING_HORZ,
+    CHROMA_SITING_VERT,
+    RANGE,
+    TRANSFER_CHARACTERISTICS,
+    PRIMARIES,
+    MAX_CLL,
+    MAX_FALL,
+    MASTERING_METADATA,
+    PRIMARY_R_CHROMATICITY_X,
+    PRIMARY_R_CHROMATICITY_Y,
+    PRIMARY_G_CHROMATICITY_X,
+    PRIMARY_G_CHROMATICITY_Y,
+    PRIMARY_B_CHROMATICITY_X,
+    PRIMARY_B_CHROMATICITY_Y,
+    WHITE_POINT_CHROMATICITY_X,
+    WHITE_POINT_CHROMATICITY_Y,
+    LUMINANCE_MAX,
+    LUMINANCE_MIN,
+};
+
+///
+/// The contents of a `Colour` element.
+///
+/// Each field is `None` when the corresponding child element is absent.  Numeric values are stored exactly as they appear in the file (e.g. [`Self::primaries`] uses the ITU-T H.273 code points).
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::matroska::colour::{Colour, MasteringMetadata};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let colour = Colour {
+///     primaries: Some(9),
+///     transfer_characteristics: Some(16),
+///     mastering_metadata: Some(MasteringMetadata {
+///         luminance_max: Some(1000.0),
+///         luminance_min: Some(0.005),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+///
+/// let tag: EmptySpec = colour.to_tag().unwrap();
+/// assert_eq!(Colour::from_tag(&tag), Some(colour));
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Colour {
+    pub matrix_coefficients: Option<u64>,
+    pub bits_per_channel: Option<u64>,
+    pub chroma_subsampling_horz: Option<u64>,
+    pub chroma_subsampling_vert: Option<u64>,
+    pub cb_subsampling_horz: Option<u64>,
+    pub cb_subsampling_vert: Option<u64>,
+    pub chroma_siting_horz: Option<u64>,
+    pub chroma_siting_vert: Option<u64>,
+    pub range: Option<u64>,
+    pub transfer_characteristics: Option<u64>,
+    pub primaries: Option<u64>,
+    pub max_cll: Option<u64>,
+    pub max_fall: Option<u64>,
+    pub mastering_metadata: Option<MasteringMetadata>,
+}
+
+impl Colour {
+
+    ///
+    /// Reads a [`Colour`] from a `Colour` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`](crate::specs::Master::Full) `Colour` element.  Unrecognized children are ignored.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        let children = get_children(tag, COLOUR)?;
+        let mut colour = Colour::default();
+        for child in children {
+            match child.get_id() {
+                MATRIX_COEFFICIENTS => colour.matrix_coefficients = read_unsigned_int(child),
+                BITS_PER_CHANNEL => colour.bits_per_channel = read_unsigned_int(child),
+                CHROMA_SUBSAMPLING_HORZ => colour.chroma_subsampling_horz = read_unsigned_int(child),
+                CHROMA_SUBSAMPLING_VERT => colour.chroma_subsampling_vert = read_unsigned_int(child),
+                CB_SUBSAMPLING_HORZ => colour.cb_subsampling_horz = read_unsigned_int(child),
+                CB_SUBSAMPLING_VERT => colour.cb_subsampling_vert = read_unsigned_int(child),
+                CHROMA_SITING_HORZ => colour.chroma_siting_horz = read_unsigned_int(child),
+                CHROMA_SITING_VERT => colour.chroma_siting_vert = read_unsigned_int(child),
+                RANGE => colour.range = read_unsigned_int(child),
+                TRANSFER_CHARACTERISTICS => colour.transfer_characteristics = read_unsigned_int(child),
+                PRIMARIES => colour.primaries = read_unsigned_int(child),
+                MAX_CLL => colour.max_cll = read_unsigned_int(child),
+                MAX_FALL => colour.max_fall = read_unsigned_int(child),
+                MASTERING_METADATA => colour.mastering_metadata = MasteringMetadata::from_tag(child),
+                _ => {},
+            }
+        }
+        Some(colour)
+    }
+
+    ///
+    /// Builds a [`Master::Full`](crate::specs::Master::Full) `Colour` tag containing every field that is set.
+    ///
+    /// Returns `None` if `TSpec` does not define the `Colour` (or `MasteringMetadata`, if present) master element.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Option<TSpec> {
+        let mut builder = ChildBuilder::new();
+        builder.unsigned_int(MATRIX_COEFFICIENTS, self.matrix_coefficients);
+        builder.unsigned_int(BITS_PER_CHANNEL, self.bits_per_channel);
+        builder.unsigned_int(CHROMA_SUBSAMPLING_HORZ, self.chroma_subsampling_horz);
+        builder.unsigned_int(CHROMA_SUBSAMPLING_VERT, self.chroma_subsampling_vert);
+        builder.unsigned_int(CB_SUBSAMPLING_HORZ, self.cb_subsampling_horz);
+        builder.unsigned_int(CB_SUBSAMPLING_VERT, self.cb_subsampling_vert);
+        builder.unsigned_int(CHROMA_SITING_HORZ, self.chroma_siting_horz);
+        builder.unsigned_int(CHROMA_SITING_VERT, self.chroma_siting_vert);
+        builder.unsigned_int(RANGE, self.range);
+        builder.unsigned_int(TRANSFER_CHARACTERISTICS, self.transfer_characteristics);
+        builder.unsigned_int(PRIMARIES, self.primaries);
+        builder.unsigned_int(MAX_CLL, self.max_cll);
+        builder.unsigned_int(MAX_FALL, self.max_fall);
+        if let Some(mastering_metadata) = &self.mastering_metadata {
+            builder.tag(Some(mastering_metadata.to_tag()?));
+        }
+        builder.build(COLOUR)
+    }
+}
+
+///
+/// The contents of a `MasteringMetadata` element, describing the display the content was mastered on.
+///
+/// Chromaticity values are CIE 1931 coordinates and luminance values are in candelas per square meter.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MasteringMetadata {
+    pub primary_r_chromaticity_x: Option<f64>,
+    pub primary_r_chromaticity_y: Option<f64>,
+    pub primary_g_chromaticity_x: Option<f64>,
+    pub primary_g_chromaticity_y: Option<f64>,
+    pub primary_b_chromaticity_x: Option<f64>,
+    pub primary_b_chromaticity_y: Option<f64>,
+    pub white_point_chromaticity_x: Option<f64>,
+    pub white_point_chromaticity_y: Option<f64>,
+    pub luminance_max: Option<f64>,
+    pub luminance_min: Option<f64>,
+}
+
+impl MasteringMetadata {
+
+    ///
+    /// Reads a [`MasteringMetadata`] from a `MasteringMetadata` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`](crate::specs::Master::Full) `MasteringMetadata` element.  Unrecognized children are ignored.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        let children = get_children(tag, MASTERING_METADATA)?;
+        let mut metadata = MasteringMetadata::default();
+        for child in children {
+            match child.get_id() {
+                PRIMARY_R_CHROMATICITY_X => metadata.primary_r_chromaticity_x = read_float(child),
+                PRIMARY_R_CHROMATICITY_Y => metadata.primary_r_chromaticity_y = read_float(child),
+                PRIMARY_G_CHROMATICITY_X => metadata.primary_g_chromaticity_x = read_float(child),
+                PRIMARY_G_CHROMATICITY_Y => metadata.primary_g_chromaticity_y = read_float(child),
+                PRIMARY_B_CHROMATICITY_X => metadata.primary_b_chromaticity_x = read_float(child),
+                PRIMARY_B_CHROMATICITY_Y => metadata.primary_b_chromaticity_y = read_float(child),
+                WHITE_POINT_CHROMATICITY_X => metadata.white_point_chromaticity_x = read_float(child),
+                WHITE_POINT_CHROMATICITY_Y => metadata.white_point_chromaticity_y = read_float(child),
+                LUMINANCE_MAX => metadata.luminance_max = read_float(child),
+                LUMINANCE_MIN => metadata.luminance_min = read_float(child),
+                _ => {},
+            }
+        }
+        Some(metadata)
+    }
+
+    ///
+    /// Builds a [`Master::Full`](crate::specs::Master::Full) `MasteringMetadata` tag containing every field that is set.
+    ///
+    /// Returns `None` if `TSpec` does not define the `MasteringMetadata` master element.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Option<TSpec> {
+        let mut builder = ChildBuilder::new();
+        builder.float(PRIMARY_R_CHROMATICITY_X, self.primary_r_chromaticity_x);
+        builder.float(PRIMARY_R_CHROMATICITY_Y, self.primary_r_chromaticity_y);
+        builder.float(PRIMARY_G_CHROMATICITY_X, self.primary_g_chromaticity_x);
+        builder.float(PRIMARY_G_CHROMATICITY_Y, self.primary_g_chromaticity_y);
+        builder.float(PRIMARY_B_CHROMATICITY_X, self.primary_b_chromaticity_x);
+        builder.float(PRIMARY_B_CHROMATICITY_Y, self.primary_b_chromaticity_y);
+        builder.float(WHITE_POINT_CHROMATICITY_X, self.white_point_chromaticity_x);
+        builder.float(WHITE_POINT_CHROMATICITY_Y, self.white_point_chromaticity_y);
+        builder.float(LUMINANCE_MAX, self.luminance_max);
+        builder.float(LUMINANCE_MIN, self.luminance_min);
+        builder.build(MASTERING_METADATA)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::*;
+
+    fn hdr_colour() -> Colour {
+        Colour {
+            matrix_coefficients: Some(9),
+            bits_per_channel: Some(10),
+            range: Some(1),
+            transfer_characteristics: Some(16),
+            primaries: Some(9),
+            max_cll: Some(1000),
+            max_fall: Some(400),
+            mastering_metadata: Some(MasteringMetadata {
+                primary_r_chromaticity_x: Some(0.708),
+                primary_r_chromaticity_y: Some(0.292),
+                white_point_chromaticity_x: Some(0.3127),
+                white_point_chromaticity_y: Some(0.329),
+                luminance_max: Some(1000.0),
+                luminance_min: Some(0.0001),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn colour_round_trip() {
+        let colour = hdr_colour();
+        let tag: EmptySpec = colour.to_tag().expect("EmptySpec supports master tags");
+        assert_eq!(Some(colour), Colour::from_tag(&tag));
+    }
+
+    #[test]
+    fn colour_encodes_unsupported_children() {
+        let colour = Colour { primaries: Some(1), max_cll: Some(0x0400), ..Default::default() };
+        let tag: EmptySpec = colour.to_tag().unwrap();
+        let children = get_children(&tag, COLOUR).unwrap();
+
+        assert_eq!(2, children.len());
+        assert_eq!((PRIMARIES, Some(&[0x01][..])), (children[0].get_id(), children[0].as_binary()));
+        assert_eq!((MAX_CLL, Some(&[0x04, 0x00][..])), (children[1].get_id(), children[1].as_binary()));
+    }
+
+    #[test]
+    fn colour_from_other_tag() {
+        let tag = EmptySpec::with_children(0x55B1, vec![]);
+        assert_eq!(None, Colour::from_tag(&tag));
+    }
+}
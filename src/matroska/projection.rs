@@ -0,0 +1,145 @@
+//!
+//! Typed access to the `Projection` element used by 360° and VR video.
+//!
+//! `Projection` lives under `Segment/Tracks/TrackEntry/Video` and describes how the decoded frames map onto a sphere, along with the pose (yaw/pitch/roll) that should be applied when rendering.
+//!
+
+use crate::specs::{EbmlSpecification, EbmlTag};
+
+use super::{get_children, read_binary, read_float, read_unsigned_int, ChildBuilder};
+
+pub use crate::ids::{PROJECTION, PROJECTION_TYPE, PROJECTION_PRIVATE, PROJECTION_POSE_YAW, PROJECTION_POSE_PITCH, PROJECTION_POSE_ROLL};
+
+///
+/// The possible values of the `ProjectionType` element.
+///
+/// Values not defined by the Matroska specification are preserved in [`ProjectionType::Other`].
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ProjectionType {
+    Rectangular,
+    Equirectangular,
+    Cubemap,
+    Mesh,
+    Other(u64),
+}
+
+impl From<u64> for ProjectionType {
+    fn from(value: u64) -> Self {
+        match value {
+            0 => ProjectionType::Rectangular,
+            1 => ProjectionType::Equirectangular,
+            2 => ProjectionType::Cubemap,
+            3 => ProjectionType::Mesh,
+            other => ProjectionType::Other(other),
+        }
+    }
+}
+
+impl From<ProjectionType> for u64 {
+    fn from(value: ProjectionType) -> Self {
+        match value {
+            ProjectionType::Rectangular => 0,
+            ProjectionType::Equirectangular => 1,
+            ProjectionType::Cubemap => 2,
+            ProjectionType::Mesh => 3,
+            ProjectionType::Other(other) => other,
+        }
+    }
+}
+
+///
+/// The contents of a `Projection` element.
+///
+/// Each field is `None` when the corresponding child element is absent.  `private` holds the raw `ProjectionPrivate` payload (e.g. an `equi` or `cbmp` box for the corresponding projection types).  Pose values are in degrees.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::matroska::projection::{Projection, ProjectionType};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let projection = Projection {
+///     projection_type: Some(ProjectionType::Equirectangular),
+///     pose_yaw: Some(90.0),
+///     ..Default::default()
+/// };
+///
+/// let tag: EmptySpec = projection.to_tag().unwrap();
+/// assert_eq!(Projection::from_tag(&tag), Some(projection));
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Projection {
+    pub projection_type: Option<ProjectionType>,
+    pub private: Option<Vec<u8>>,
+    pub pose_yaw: Option<f64>,
+    pub pose_pitch: Option<f64>,
+    pub pose_roll: Option<f64>,
+}
+
+impl Projection {
+
+    ///
+    /// Reads a [`Projection`] from a `Projection` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`](crate::specs::Master::Full) `Projection` element.  Unrecognized children are ignored.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        let children = get_children(tag, PROJECTION)?;
+        let mut projection = Projection::default();
+        for child in children {
+            match child.get_id() {
+                PROJECTION_TYPE => projection.projection_type = read_unsigned_int(child).map(ProjectionType::from),
+                PROJECTION_PRIVATE => projection.private = read_binary(child),
+                PROJECTION_POSE_YAW => projection.pose_yaw = read_float(child),
+                PROJECTION_POSE_PITCH => projection.pose_pitch = read_float(child),
+                PROJECTION_POSE_ROLL => projection.pose_roll = read_float(child),
+                _ => {},
+            }
+        }
+        Some(projection)
+    }
+
+    ///
+    /// Builds a [`Master::Full`](crate::specs::Master::Full) `Projection` tag containing every field that is set.
+    ///
+    /// Returns `None` if `TSpec` does not define the `Projection` master element.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Option<TSpec> {
+        let mut builder = ChildBuilder::new();
+        builder.unsigned_int(PROJECTION_TYPE, self.projection_type.map(u64::from));
+        builder.binary(PROJECTION_PRIVATE, self.private.as_deref());
+        builder.float(PROJECTION_POSE_YAW, self.pose_yaw);
+        builder.float(PROJECTION_POSE_PITCH, self.pose_pitch);
+        builder.float(PROJECTION_POSE_ROLL, self.pose_roll);
+        builder.build(PROJECTION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::*;
+
+    #[test]
+    fn projection_round_trip() {
+        let projection = Projection {
+            projection_type: Some(ProjectionType::Cubemap),
+            private: Some(vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            pose_yaw: Some(-90.0),
+            pose_pitch: Some(12.5),
+            pose_roll: Some(0.0),
+        };
+        let tag: EmptySpec = projection.to_tag().expect("EmptySpec supports master tags");
+        assert_eq!(Some(projection), Projection::from_tag(&tag));
+    }
+
+    #[test]
+    fn projection_type_preserves_unknown_values() {
+        assert_eq!(ProjectionType::Mesh, ProjectionType::from(3));
+        assert_eq!(ProjectionType::Other(42), ProjectionType::from(42));
+        assert_eq!(42u64, ProjectionType::Other(42).into());
+    }
+}
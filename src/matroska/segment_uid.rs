@@ -0,0 +1,270 @@
+//!
+//! Utilities for generating and matching the 128-bit ids used to link Matroska segments.
+//!
+//! The `Info` element of a segment can carry its own `SegmentUUID`, the ids of the previous and next segments (`PrevUUID`/`NextUUID`), and any number of `SegmentFamily` ids shared by related segments.  These elements are typically used for ordered chapters and for splitting a single presentation across files.
+//!
+
+use std::collections::hash_map::RandomState;
+use std::convert::TryInto;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::specs::{EbmlSpecification, EbmlTag};
+
+use super::{get_children, ChildBuilder};
+
+pub use crate::ids::{INFO, SEGMENT_FAMILY};
+pub use crate::ids::SEGMENT_UUID as SEGMENT_UID;
+pub use crate::ids::PREV_UUID as PREV_UID;
+pub use crate::ids::NEXT_UUID as NEXT_UID;
+
+///
+/// A 128-bit segment id as stored in `SegmentUUID`, `PrevUUID`, `NextUUID`, or `SegmentFamily`.
+///
+/// Displays as 32 lowercase hex digits.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct SegmentUid([u8; 16]);
+
+impl SegmentUid {
+
+    ///
+    /// Generates a new pseudo-random [`SegmentUid`].
+    ///
+    /// Ids are seeded from the standard library's randomly keyed hasher, the system clock, and a process-wide counter, so repeated calls never collide within a process.  The result is suitable for stamping new segments but is *not* cryptographically secure.
+    ///
+    pub fn generate() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+        let state = RandomState::new();
+        let mut bytes = [0u8; 16];
+        for half in bytes.chunks_mut(8) {
+            let mut hasher = state.build_hasher();
+            hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+            hasher.write_u128(nanos);
+            half.copy_from_slice(&hasher.finish().to_be_bytes());
+        }
+        SegmentUid(bytes)
+    }
+
+    ///
+    /// Creates a [`SegmentUid`] from a slice, returning `None` unless it is exactly 16 bytes long.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(SegmentUid)
+    }
+
+    ///
+    /// Parses a [`SegmentUid`] from 32 hex digits.  Hyphens (as in UUID notation) are ignored.
+    ///
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits: Vec<u8> = hex.bytes().filter(|b| *b != b'-').collect();
+        // from_str_radix() alone would accept a leading sign in each pair
+        if digits.len() != 32 || !digits.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+
+        let mut bytes = [0u8; 16];
+        for (byte, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+            let pair = std::str::from_utf8(pair).ok()?;
+            *byte = u8::from_str_radix(pair, 16).ok()?;
+        }
+        Some(SegmentUid(bytes))
+    }
+
+    ///
+    /// Returns the raw bytes of this id.
+    ///
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    ///
+    /// Reads a [`SegmentUid`] from any binary tag (`SegmentUUID`, `PrevUUID`, `NextUUID`, or `SegmentFamily`).
+    ///
+    /// Returns `None` if the tag has no binary data or the data is not 16 bytes long.
+    ///
+    pub fn from_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        tag.as_binary().and_then(SegmentUid::from_bytes)
+    }
+
+    ///
+    /// Builds a binary tag with the given id (`SegmentUUID`, `PrevUUID`, `NextUUID`, or `SegmentFamily`) holding this id.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, id: u64) -> TSpec {
+        TSpec::get_binary_tag(id, &self.0).unwrap_or_else(|| TSpec::get_raw_tag(id, &self.0))
+    }
+}
+
+impl fmt::Display for SegmentUid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// The segment linking information stored in an `Info` element.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::matroska::segment_uid::{SegmentLinks, SegmentUid};
+///
+/// let family = SegmentUid::generate();
+/// let mut first = SegmentLinks { uid: Some(SegmentUid::generate()), families: vec![family], ..Default::default() };
+/// let mut second = SegmentLinks { uid: Some(SegmentUid::generate()), families: vec![family], ..Default::default() };
+/// first.link_to_next(&mut second);
+///
+/// assert!(first.precedes(&second));
+/// assert!(first.shares_family_with(&second));
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SegmentLinks {
+    pub uid: Option<SegmentUid>,
+    pub prev: Option<SegmentUid>,
+    pub next: Option<SegmentUid>,
+    pub families: Vec<SegmentUid>,
+}
+
+impl SegmentLinks {
+
+    ///
+    /// Reads the [`SegmentLinks`] from an `Info` tag.
+    ///
+    /// Returns `None` if `tag` is not a [`Master::Full`](crate::specs::Master::Full) `Info` element.  Ids that are not 16 bytes long are ignored.
+    ///
+    pub fn from_info_tag<TSpec: EbmlTag<TSpec> + Clone>(tag: &TSpec) -> Option<Self> {
+        let children = get_children(tag, INFO)?;
+        let mut links = SegmentLinks::default();
+        for child in children {
+            match child.get_id() {
+                SEGMENT_UID => links.uid = SegmentUid::from_tag(child),
+                PREV_UID => links.prev = SegmentUid::from_tag(child),
+                NEXT_UID => links.next = SegmentUid::from_tag(child),
+                SEGMENT_FAMILY => links.families.extend(SegmentUid::from_tag(child)),
+                _ => {},
+            }
+        }
+        Some(links)
+    }
+
+    ///
+    /// Builds the tags for every id that is set, in the order they should appear inside `Info`.
+    ///
+    pub fn to_tags<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Vec<TSpec> {
+        let mut tags = Vec::new();
+        tags.extend(self.uid.map(|uid| uid.to_tag(SEGMENT_UID)));
+        tags.extend(self.prev.map(|uid| uid.to_tag(PREV_UID)));
+        tags.extend(self.next.map(|uid| uid.to_tag(NEXT_UID)));
+        tags.extend(self.families.iter().map(|uid| uid.to_tag(SEGMENT_FAMILY)));
+        tags
+    }
+
+    ///
+    /// Builds a [`Master::Full`](crate::specs::Master::Full) `Info` tag containing only the linking elements.
+    ///
+    /// Returns `None` if `TSpec` does not define the `Info` master element.
+    ///
+    pub fn to_info_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self) -> Option<TSpec> {
+        let mut builder = ChildBuilder::new();
+        for tag in self.to_tags() {
+            builder.tag(Some(tag));
+        }
+        builder.build(INFO)
+    }
+
+    ///
+    /// Returns whether this segment belongs to the given family.
+    ///
+    pub fn is_member_of(&self, family: &SegmentUid) -> bool {
+        self.families.contains(family)
+    }
+
+    ///
+    /// Adds this segment to a family if it is not already a member.
+    ///
+    pub fn join_family(&mut self, family: SegmentUid) {
+        if !self.is_member_of(&family) {
+            self.families.push(family);
+        }
+    }
+
+    ///
+    /// Returns whether both segments belong to at least one common family.
+    ///
+    pub fn shares_family_with(&self, other: &SegmentLinks) -> bool {
+        self.families.iter().any(|f| other.is_member_of(f))
+    }
+
+    ///
+    /// Returns whether `other` is the segment directly after this one.
+    ///
+    /// Segments are considered linked if either side references the other, since files in the wild often only set one direction.
+    ///
+    pub fn precedes(&self, other: &SegmentLinks) -> bool {
+        matches!((self.next, other.uid), (Some(next), Some(uid)) if next == uid) ||
+        matches!((self.uid, other.prev), (Some(uid), Some(prev)) if uid == prev)
+    }
+
+    ///
+    /// Sets `NextUUID` on this segment and `PrevUUID` on `other` so that they reference each other.
+    ///
+    /// Any missing `SegmentUUID` is generated first.
+    ///
+    pub fn link_to_next(&mut self, other: &mut SegmentLinks) {
+        let uid = *self.uid.get_or_insert_with(SegmentUid::generate);
+        let other_uid = *other.uid.get_or_insert_with(SegmentUid::generate);
+        self.next = Some(other_uid);
+        other.prev = Some(uid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::*;
+
+    #[test]
+    fn generated_uids_are_unique() {
+        let uids: HashSet<_> = (0..1000).map(|_| SegmentUid::generate()).collect();
+        assert_eq!(1000, uids.len());
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let uid = SegmentUid::from_hex("92b2ce31-8a96-4ac6-8707-a0a5c4ae2c44").expect("valid uuid");
+        assert_eq!("92b2ce318a964ac68707a0a5c4ae2c44", uid.to_string());
+        assert_eq!(Some(uid), SegmentUid::from_hex(&uid.to_string()));
+        assert_eq!(None, SegmentUid::from_hex("92b2"));
+        assert_eq!(None, SegmentUid::from_hex("zzb2ce318a964ac68707a0a5c4ae2c44"));
+        assert_eq!(None, SegmentUid::from_hex("+fb2ce318a964ac68707a0a5c4ae2c44"));
+    }
+
+    #[test]
+    fn links_round_trip() {
+        let mut links = SegmentLinks { uid: Some(SegmentUid::generate()), next: Some(SegmentUid::generate()), ..Default::default() };
+        links.join_family(SegmentUid::generate());
+        links.join_family(SegmentUid::generate());
+
+        let tag: EmptySpec = links.to_info_tag().expect("EmptySpec supports master tags");
+        assert_eq!(Some(links), SegmentLinks::from_info_tag(&tag));
+    }
+
+    #[test]
+    fn one_sided_links_match() {
+        let first = SegmentLinks { uid: Some(SegmentUid::generate()), ..Default::default() };
+        let second = SegmentLinks { uid: Some(SegmentUid::generate()), prev: first.uid, ..Default::default() };
+        assert!(first.precedes(&second));
+        assert!(!second.precedes(&first));
+    }
+}
@@ -0,0 +1,229 @@
+//!
+//! Conversions between raw Matroska timestamps and [`Duration`]s.
+//!
+//! Matroska timestamps are counted in "ticks" whose length is set by the segment's `Info.TimestampScale` element (in nanoseconds, 1,000,000 by default, making ticks milliseconds).  `Cluster.Timestamp` and `Info.Duration` are in ticks, and each block's timestamp is a signed 16-bit tick offset from its cluster's timestamp.  [`TimestampTracker`] watches tags as they are iterated so these values can be converted without tracking the scale by hand.
+//!
+
+use std::convert::TryInto;
+use std::time::Duration;
+
+use crate::specs::{EbmlTag, Master};
+
+use super::{read_float, read_unsigned_int};
+
+pub use crate::ids::{INFO, TIMESTAMP_SCALE, DURATION, CLUSTER};
+pub use crate::ids::TIMESTAMP as CLUSTER_TIMESTAMP;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+///
+/// The length of a timestamp tick, as stored in `TimestampScale`.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TimestampScale(u64);
+
+impl TimestampScale {
+
+    ///
+    /// The scale used when a segment has no `TimestampScale` element: 1,000,000 nanoseconds (one millisecond) per tick.
+    ///
+    pub const DEFAULT: TimestampScale = TimestampScale(1_000_000);
+
+    ///
+    /// Creates a [`TimestampScale`] of `nanos` nanoseconds per tick.  Returns `None` if `nanos` is 0, which the Matroska specification forbids.
+    ///
+    pub fn new(nanos: u64) -> Option<Self> {
+        if nanos == 0 {
+            None
+        } else {
+            Some(TimestampScale(nanos))
+        }
+    }
+
+    ///
+    /// Returns the number of nanoseconds per tick.
+    ///
+    pub fn nanos(&self) -> u64 {
+        self.0
+    }
+
+    ///
+    /// Converts a number of ticks into a [`Duration`].  Saturates at [`Duration::MAX`].
+    ///
+    pub fn to_duration(&self, ticks: u64) -> Duration {
+        let nanos = ticks as u128 * self.0 as u128;
+        match (nanos / NANOS_PER_SEC).try_into() {
+            Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+
+    ///
+    /// Converts a fractional number of ticks (such as `Info.Duration`) into a [`Duration`].  Returns `None` if `ticks` is negative, not finite, or too large.
+    ///
+    pub fn float_to_duration(&self, ticks: f64) -> Option<Duration> {
+        Duration::try_from_secs_f64(ticks * self.0 as f64 / NANOS_PER_SEC as f64).ok()
+    }
+
+    ///
+    /// Converts a [`Duration`] into the nearest number of ticks, e.g. for writing a `Cluster.Timestamp`.  Returns `None` if the result doesn't fit in a `u64`.
+    ///
+    pub fn to_ticks(&self, duration: Duration) -> Option<u64> {
+        let scale = self.0 as u128;
+        ((duration.as_nanos() + scale / 2) / scale).try_into().ok()
+    }
+
+    ///
+    /// Converts a [`Duration`] into the nearest number of fractional ticks, e.g. for writing `Info.Duration`.
+    ///
+    pub fn to_float_ticks(&self, duration: Duration) -> f64 {
+        duration.as_nanos() as f64 / self.0 as f64
+    }
+}
+
+impl Default for TimestampScale {
+    fn default() -> Self {
+        TimestampScale::DEFAULT
+    }
+}
+
+///
+/// Captures the current `TimestampScale` and `Cluster` timestamp while iterating over a document.
+///
+/// Pass every tag read to [`Self::observe()`].  Both [`Master::Start`]/[`Master::End`] streams and buffered [`Master::Full`] `Info` and `Cluster` tags are understood.
+///
+/// ## Example
+///
+/// ```
+/// use std::time::Duration;
+/// use ebml_iterable::matroska::timestamps::{TimestampTracker, TIMESTAMP_SCALE, CLUSTER_TIMESTAMP};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut tracker = TimestampTracker::new();
+/// // Microsecond ticks
+/// tracker.observe(&EmptySpec::with_data(TIMESTAMP_SCALE, &[0x03, 0xe8]));
+/// tracker.observe(&EmptySpec::with_data(CLUSTER_TIMESTAMP, &[0x0f, 0x42, 0x40]));
+///
+/// assert_eq!(Some(Duration::from_secs(1)), tracker.cluster_time());
+/// assert_eq!(Some(Duration::from_micros(999_900)), tracker.block_time(-100));
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimestampTracker {
+    scale: TimestampScale,
+    cluster_timestamp: Option<u64>,
+    duration: Option<f64>,
+}
+
+impl TimestampTracker {
+
+    ///
+    /// Creates a tracker using [`TimestampScale::DEFAULT`] until a `TimestampScale` element is seen.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    ///
+    /// Updates the tracker from a tag read from the document.
+    ///
+    /// `TimestampScale` elements change the scale (invalid values of 0 are ignored), `Cluster` timestamps are remembered until the next cluster starts, and `Info.Duration` is kept for [`Self::duration()`].
+    ///
+    pub fn observe<TSpec: EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) {
+        match tag.get_id() {
+            TIMESTAMP_SCALE => {
+                if let Some(scale) = read_unsigned_int(tag).and_then(TimestampScale::new) {
+                    self.scale = scale;
+                }
+            },
+            CLUSTER_TIMESTAMP => self.cluster_timestamp = read_unsigned_int(tag),
+            DURATION => self.duration = read_float(tag),
+            INFO | CLUSTER => {
+                if tag.get_id() == CLUSTER && !matches!(tag.as_master(), Some(Master::End)) {
+                    self.cluster_timestamp = None;
+                }
+                if let Some(Master::Full(children)) = tag.as_master() {
+                    children.iter().for_each(|child| self.observe(child));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    ///
+    /// Returns the current `TimestampScale`.
+    ///
+    pub fn scale(&self) -> TimestampScale {
+        self.scale
+    }
+
+    ///
+    /// Returns the timestamp of the current cluster, or `None` if its `Timestamp` element hasn't been read.
+    ///
+    pub fn cluster_time(&self) -> Option<Duration> {
+        self.cluster_timestamp.map(|ticks| self.scale.to_duration(ticks))
+    }
+
+    ///
+    /// Returns the absolute timestamp of a block in the current cluster from its relative timestamp.
+    ///
+    /// Returns `None` if the cluster's timestamp hasn't been read or if the block would start before the beginning of the segment.
+    ///
+    pub fn block_time(&self, relative: i16) -> Option<Duration> {
+        let ticks = self.cluster_timestamp?.checked_add_signed(relative as i64)?;
+        Some(self.scale.to_duration(ticks))
+    }
+
+    ///
+    /// Returns the segment duration from `Info.Duration`, if it has been read.
+    ///
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration.and_then(|ticks| self.scale.float_to_duration(ticks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::*;
+
+    #[test]
+    fn scale_conversions() {
+        let scale = TimestampScale::new(1_000).unwrap();
+        assert_eq!(Duration::from_micros(1_500), scale.to_duration(1_500));
+        assert_eq!(Some(1_500), scale.to_ticks(Duration::from_micros(1_500)));
+        assert_eq!(Some(2), scale.to_ticks(Duration::from_nanos(1_500)));
+        assert_eq!(Some(Duration::from_nanos(2_500)), scale.float_to_duration(2.5));
+        assert_eq!(None, scale.float_to_duration(-1.0));
+        assert_eq!(2.5, scale.to_float_ticks(Duration::from_nanos(2_500)));
+
+        assert_eq!(None, TimestampScale::new(0));
+        assert_eq!(Duration::MAX, TimestampScale::new(u64::MAX).unwrap().to_duration(u64::MAX));
+        assert_eq!(None, TimestampScale::new(1).unwrap().to_ticks(Duration::MAX));
+    }
+
+    #[test]
+    fn tracker_reads_buffered_tags() {
+        let mut tracker = TimestampTracker::new();
+        assert_eq!(TimestampScale::DEFAULT, tracker.scale());
+        assert_eq!(None, tracker.block_time(0));
+
+        tracker.observe(&EmptySpec::with_children(INFO, vec![
+            EmptySpec::with_data(TIMESTAMP_SCALE, &[0x27, 0x10]),
+            EmptySpec::with_data(DURATION, &1_000f64.to_be_bytes()),
+        ]));
+        assert_eq!(10_000, tracker.scale().nanos());
+        assert_eq!(Some(Duration::from_millis(10)), tracker.duration());
+
+        tracker.observe(&EmptySpec::with_children(CLUSTER, vec![EmptySpec::with_data(CLUSTER_TIMESTAMP, &[0x64])]));
+        assert_eq!(Some(Duration::from_millis(1)), tracker.cluster_time());
+        assert_eq!(Some(Duration::ZERO), tracker.block_time(-100));
+        assert_eq!(None, tracker.block_time(-101));
+
+        tracker.observe(&EmptySpec::with_children(CLUSTER, vec![]));
+        assert_eq!(None, tracker.cluster_time());
+    }
+}
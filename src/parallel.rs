@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::thread;
+
+use crate::error::TagIteratorError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::TagIterator;
+
+///
+/// Parses every `cluster_id` "Master" tag of the file at `path` on multiple threads, returning the result of `f` for each in document order.
+///
+/// This is [`scan_clusters_parallel_with()`] for files: each thread opens the file itself, so no source needs to be cloned.
+///
+/// ## Errors
+///
+/// Returns a [`TagIteratorError::ReadError`] if the file can't be opened, or the first error encountered reading it.
+///
+/// ## Panics
+///
+/// Panics if `cluster_id` is not a "Master" tag according to `<TSpec>`, or if `f` panics.
+///
+pub fn scan_clusters_parallel<P, TSpec, T, F>(path: P, cluster_id: u64, f: F) -> Result<Vec<T>, TagIteratorError>
+    where
+    P: AsRef<Path>,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    T: Send,
+    F: Fn(TSpec, usize) -> T + Sync,
+{
+    let path = path.as_ref();
+    scan(|| File::open(path), cluster_id, f)
+}
+
+///
+/// Parses every `cluster_id` "Master" tag of `source` on multiple threads, returning the result of `f` for each in document order.
+///
+/// The document is scanned once to find where each cluster starts, seeking over the data of clusters with a known size rather than reading it.  The clusters are then split into contiguous ranges, one for each available thread (see [`std::thread::available_parallelism()`]), and each range is parsed from its own clone of `source`.  `f` receives each cluster as a [`Master::Full`] tag along with the offset it starts at, so it can be used to build an index (such as Matroska `Cues`) or to gather statistics without holding the whole document in memory.  Tags outside of clusters are ignored.
+///
+/// Offsets are relative to the position of `source` when this is called, and each clone of `source` is expected to read the same data (as clones of a [`std::io::Cursor`] do).
+///
+/// ## Errors
+///
+/// Returns the first error encountered reading `source`, in document order.
+///
+/// ## Panics
+///
+/// Panics if `cluster_id` is not a "Master" tag according to `<TSpec>`, or if `f` panics.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::scan_clusters_parallel_with;
+/// use ebml_iterable::header::EbmlHeaderSpec;
+/// use ebml_iterable::specs::EbmlTag;
+///
+/// // Two EBML headers, each holding a DocTypeExtension
+/// let header = [0x1a, 0x45, 0xdf, 0xa3, 0x87, 0x42, 0x81, 0x84, 0x42, 0x84, 0x81, 0x01];
+/// let data = [header, header].concat();
+/// let offsets = scan_clusters_parallel_with(Cursor::new(data), 0x1a45dfa3, |header: EbmlHeaderSpec, offset| {
+///     assert_eq!(0x1a45dfa3, header.get_id());
+///     offset
+/// }).unwrap();
+/// assert_eq!(vec![0, 12], offsets);
+/// ```
+///
+pub fn scan_clusters_parallel_with<R, TSpec, T, F>(source: R, cluster_id: u64, f: F) -> Result<Vec<T>, TagIteratorError>
+    where
+    R: Read + Seek + Clone + Send,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    T: Send,
+    F: Fn(TSpec, usize) -> T + Sync,
+{
+    let mut source = source;
+    let origin = source.stream_position().map_err(|source| TagIteratorError::ReadError { source })?;
+    scan(|| {
+        let mut source = source.clone();
+        source.seek(SeekFrom::Start(origin))?;
+        Ok(source)
+    }, cluster_id, f)
+}
+
+fn scan<R, TSpec, T, F, O>(open: O, cluster_id: u64, f: F) -> Result<Vec<T>, TagIteratorError>
+    where
+    R: Read + Seek + Send,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    T: Send,
+    F: Fn(TSpec, usize) -> T + Sync,
+    O: Fn() -> io::Result<R>,
+{
+    let open = || open().map_err(|source| TagIteratorError::ReadError { source });
+    let mut source = open()?;
+    let origin = source.stream_position().map_err(|source| TagIteratorError::ReadError { source })?;
+    let (starts, end) = find_clusters::<_, TSpec>(source, cluster_id)?;
+    if starts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get()).min(starts.len());
+    let per_thread = starts.len().div_ceil(threads);
+    let ranges = starts.chunks(per_thread).enumerate().map(|(index, chunk)| {
+        let range_end = starts.get((index + 1) * per_thread).copied().unwrap_or(end);
+        Ok((open()?, chunk[0], range_end))
+    }).collect::<Result<Vec<_>, TagIteratorError>>()?;
+
+    let results: Vec<Result<Vec<T>, TagIteratorError>> = thread::scope(|scope| {
+        let handles: Vec<_> = ranges.into_iter().map(|(source, start, range_end)| {
+            let f = &f;
+            scope.spawn(move || {
+                let iter: TagIterator<R, TSpec> = TagIterator::with_window(source, origin + start as u64, (range_end - start) as u64, &[])?;
+                let mut clusters = iter.into_subtree_iter(cluster_id);
+                let mut results = Vec::new();
+                while let Some(cluster) = clusters.next() {
+                    let offset = start + clusters.get_ref().last_emitted_tag_offset();
+                    results.push(f(cluster?, offset));
+                }
+                Ok(results)
+            })
+        }).collect();
+        handles.into_iter().map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+    });
+
+    let mut merged = Vec::with_capacity(starts.len());
+    for result in results {
+        merged.extend(result?);
+    }
+    Ok(merged)
+}
+
+///
+/// Finds where each cluster starts, and where the document ends.
+///
+fn find_clusters<R: Read + Seek, TSpec>(source: R, cluster_id: u64) -> Result<(Vec<usize>, usize), TagIteratorError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut iter: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+    iter.seek_when_skipping();
+    let mut starts = Vec::new();
+    while let Some(tag) = iter.next() {
+        let tag = tag?;
+        if tag.get_id() == cluster_id && matches!(tag.as_master(), Some(Master::Start)) {
+            starts.push(iter.last_emitted_tag_offset());
+            // Clusters with an unknown size can't be skipped, so their children are read to find where they end
+            iter.skip_master_data()?;
+        }
+    }
+    Ok((starts, iter.bytes_read()))
+}
@@ -0,0 +1,186 @@
+use std::io::{Read, Write};
+
+use crate::error::PipeError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::{TagIterator, TagWriter};
+
+///
+/// Forwards every remaining tag from `source` to `dest`, passing each through `transform` on the way.
+///
+/// This is the building block for simple remuxing tools: `transform` receives each tag and returns the tag to write in its place, or `None` to drop it.  Returning `Some(tag)` unchanged copies the tag through.  [`Master::End`] variants are not passed to `transform`; they follow the decision made for the matching [`Master::Start`], so the master boundaries of the output always match the input.  Dropping a [`Master::Start`] drops all of its children, and replacing it with something other than a [`Master::Start`] (like a [`Master::Full`] holding rewritten children) skips the original children.  "Master" tags that had an unknown size in the source are written with an unknown size.
+///
+/// Tags are re-encoded by `dest`, so values come through unchanged but vint widths may differ from the source.  `dest` is flushed once `source` is exhausted.
+///
+/// ## Errors
+///
+/// Returns the first error encountered reading from `source` or writing to `dest`.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::{pipe, TagIterator, TagWriter};
+/// use ebml_iterable::specs::EbmlTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut source: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0xec, 0x80, 0x82, 0x80]), &[]);
+/// let mut dest = TagWriter::new(Vec::new());
+///
+/// // Remove Void elements
+/// pipe(&mut source, &mut dest, |tag| if tag.get_id() == 0xec { None } else { Some(tag) }).unwrap();
+/// assert_eq!(vec![0x81, 0x81, 0x01, 0x82, 0x80], dest.into_inner().unwrap());
+/// ```
+///
+pub fn pipe<R, W, TSpec, F>(source: &mut TagIterator<R, TSpec>, dest: &mut TagWriter<W>, mut transform: F) -> Result<(), PipeError>
+    where
+    R: Read,
+    W: Write,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    F: FnMut(TSpec) -> Option<TSpec>,
+{
+    copy_tags(source, dest, |tag| transform(tag).map_or(Output::Dropped, Output::Replaced))
+}
+
+///
+/// What [`copy_ebml()`] does with a tag read from the source.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decision<TSpec> {
+
+    ///
+    /// Copy the tag to the destination unchanged.  Non-"Master" tags are copied byte-for-byte, and the children of a [`Master::Start`] are passed to the filter as usual.
+    ///
+    Keep,
+
+    ///
+    /// Leave the tag out of the destination.  Dropping a [`Master::Start`] drops all of its children.
+    ///
+    Drop,
+
+    ///
+    /// Write a different tag in place of this one.  This follows the same rules as the tags returned by the `transform` of [`pipe()`].
+    ///
+    Replace(TSpec),
+}
+
+///
+/// Copies an EBML document from `source` to `dest`, letting `filter` decide what happens to each tag along the way.
+///
+/// This is a convenience over [`pipe()`] for the common case of filtering a whole document: the [`TagIterator`] and [`TagWriter`] are created internally, and tags that are kept are copied with their original encoding (see [`TagIterator::set_keep_encoding()`]), so anything that isn't dropped or replaced is written back exactly as it was read.  Master boundaries are handled in the same way as [`pipe()`]: `filter` is never called for [`Master::End`] variants, and "Master" tags that had an unknown size in the source keep an unknown size.
+///
+/// Once `source` is exhausted, the writer is finished with [`TagWriter::finish()`] and `dest` is returned.
+///
+/// ## Errors
+///
+/// Returns the first error encountered reading from `source` or writing to `dest`.  A source that ends inside of a "Master" tag produces a [`TagWriterError::UnclosedTags`](crate::error::TagWriterError::UnclosedTags) error.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::{copy_ebml, Decision};
+/// use ebml_iterable::specs::EbmlTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// // A Void element, and a tag with a wider size vint than it needs
+/// let source = Cursor::new(vec![0xec, 0x80, 0x81, 0x40, 0x01, 0x01]);
+/// let copied = copy_ebml::<_, _, EmptySpec, _>(source, Vec::new(), |tag| {
+///     if tag.get_id() == 0xec { Decision::Drop } else { Decision::Keep }
+/// }).unwrap();
+/// assert_eq!(vec![0x81, 0x40, 0x01, 0x01], copied);
+/// ```
+///
+pub fn copy_ebml<R, W, TSpec, F>(source: R, dest: W, mut filter: F) -> Result<W, PipeError>
+    where
+    R: Read,
+    W: Write,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    F: FnMut(&TSpec) -> Decision<TSpec>,
+{
+    let mut source: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+    source.set_keep_encoding(true);
+    let mut dest = TagWriter::new(dest);
+    copy_tags(&mut source, &mut dest, |tag| match filter(&tag) {
+        Decision::Keep => Output::Original(tag),
+        Decision::Drop => Output::Dropped,
+        Decision::Replace(output) => Output::Replaced(output),
+    })?;
+    Ok(dest.finish()?)
+}
+
+///
+/// The tag to write in place of a tag read by [`copy_tags()`].
+///
+enum Output<TSpec> {
+    Original(TSpec),
+    Replaced(TSpec),
+    Dropped,
+}
+
+fn copy_tags<R, W, TSpec, F>(source: &mut TagIterator<R, TSpec>, dest: &mut TagWriter<W>, mut decide: F) -> Result<(), PipeError>
+    where
+    R: Read,
+    W: Write,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    F: FnMut(TSpec) -> Output<TSpec>,
+{
+    // The id written for each open master in the source, or None if its contents are being dropped
+    let mut open: Vec<Option<u64>> = Vec::new();
+
+    while let Some(tag) = source.next() {
+        let tag = tag?;
+        let dropping = open.last().is_some_and(Option::is_none);
+
+        match tag.as_master() {
+            Some(Master::End) => {
+                // Nothing is open if the source was already inside of this master when piping started
+                if let Some(Some(id)) = open.pop() {
+                    let end = TSpec::get_master_tag(id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", id));
+                    dest.write(&end)?;
+                }
+            },
+            Some(Master::Start) => {
+                if dropping {
+                    open.push(None);
+                    continue;
+                }
+                let unknown_size = source.last_emitted_tag_span().data_len.is_none();
+                let output = match decide(tag) {
+                    Output::Original(output) | Output::Replaced(output) => Some(output),
+                    Output::Dropped => None,
+                };
+                match output {
+                    Some(output) if matches!(output.as_master(), Some(Master::Start)) => {
+                        if unknown_size {
+                            dest.write_unknown_size(&output)?;
+                        } else {
+                            dest.write(&output)?;
+                        }
+                        open.push(Some(output.get_id()));
+                    },
+                    Some(output) => {
+                        dest.write(&output)?;
+                        open.push(None);
+                    },
+                    None => open.push(None),
+                }
+            },
+            _ => {
+                if dropping {
+                    continue;
+                }
+                match decide(tag) {
+                    Output::Original(output) => match source.last_emitted_tag_encoding() {
+                        Some(encoding) => dest.write_encoded(&output, encoding)?,
+                        None => dest.write(&output)?,
+                    },
+                    Output::Replaced(output) => dest.write(&output)?,
+                    Output::Dropped => {},
+                }
+            },
+        }
+    }
+
+    dest.flush()?;
+    Ok(())
+}
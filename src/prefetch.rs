@@ -0,0 +1,181 @@
+use std::io::{self, ErrorKind, Read};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use super::tag_iterator_util::DEFAULT_BUFFER_LEN;
+
+///
+/// Number of chunks [`PrefetchReader::new()`] keeps read ahead of the consumer.
+///
+pub const DEFAULT_PREFETCH_CHUNKS: usize = 2;
+
+///
+/// An opt-in read-ahead layer that reads from a source on a background thread.
+///
+/// The wrapped source is moved to a dedicated thread which keeps up to `chunks_ahead` chunks of data read ahead of the consumer.  While a [`TagIterator`](crate::TagIterator) is processing the current elements, the next chunk is already being fetched, which hides IO latency for network streams or cold-cache files.  For sources that are already in memory this only adds overhead, so it is never used implicitly.
+///
+/// Dropping the reader stops the background thread after its current read completes.  Chunk allocations are handed back to the thread once consumed, so steady-state reading does not allocate.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::iterator::PrefetchReader;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let source = Cursor::new(vec![0x81, 0x82, 0x01, 0x02]);
+/// let iter: TagIterator<_, EmptySpec> = TagIterator::new(PrefetchReader::new(source), &[]);
+/// assert_eq!(1, iter.map(|tag| tag.unwrap()).count());
+/// ```
+///
+pub struct PrefetchReader {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    spare_chunks: SyncSender<Vec<u8>>,
+    current: Vec<u8>,
+    position: usize,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl PrefetchReader {
+
+    ///
+    /// Returns a new [`PrefetchReader`] that reads [`DEFAULT_BUFFER_LEN`](crate::iterator::DEFAULT_BUFFER_LEN) byte chunks from `source`, keeping [`DEFAULT_PREFETCH_CHUNKS`] chunks ahead.
+    ///
+    pub fn new<R: Read + Send + 'static>(source: R) -> Self {
+        PrefetchReader::with_capacity(source, DEFAULT_BUFFER_LEN, DEFAULT_PREFETCH_CHUNKS)
+    }
+
+    ///
+    /// Returns a new [`PrefetchReader`] that reads `chunk_len` byte chunks from `source` and keeps up to `chunks_ahead` of them buffered.
+    ///
+    /// Both values are raised to at least 1.  Memory use is bounded by roughly `chunk_len * (chunks_ahead + 2)` bytes.
+    ///
+    pub fn with_capacity<R: Read + Send + 'static>(mut source: R, chunk_len: usize, chunks_ahead: usize) -> Self {
+        let chunk_len = chunk_len.max(1);
+        let chunks_ahead = chunks_ahead.max(1);
+        let (chunk_sender, chunks) = mpsc::sync_channel(chunks_ahead);
+        let (spare_chunks, spare_receiver) = mpsc::sync_channel::<Vec<u8>>(chunks_ahead + 1);
+
+        let worker = thread::spawn(move || loop {
+            let mut chunk = spare_receiver.try_recv().unwrap_or_default();
+            chunk.resize(chunk_len, 0);
+            let result = match source.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(len) => {
+                    chunk.truncate(len);
+                    Ok(chunk)
+                },
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => Err(err),
+            };
+            let failed = result.is_err();
+            if chunk_sender.send(result).is_err() || failed {
+                break;
+            }
+        });
+
+        PrefetchReader {
+            chunks,
+            spare_chunks,
+            current: Vec::new(),
+            position: 0,
+            worker: Some(worker),
+        }
+    }
+
+    ///
+    /// Waits for the next chunk from the background thread.  Returns `false` once the source is exhausted.
+    ///
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        if self.worker.is_none() {
+            return Ok(false);
+        }
+
+        match self.chunks.recv() {
+            Ok(Ok(chunk)) => {
+                let consumed = std::mem::replace(&mut self.current, chunk);
+                if consumed.capacity() > 0 {
+                    let _ = self.spare_chunks.try_send(consumed);
+                }
+                self.position = 0;
+                Ok(true)
+            },
+            Ok(Err(err)) => {
+                self.worker = None;
+                Err(err)
+            },
+            Err(_) => {
+                let worker = self.worker.take().expect("worker checked above");
+                if worker.join().is_err() {
+                    return Err(io::Error::other("prefetch thread panicked while reading from the source"));
+                }
+                Ok(false)
+            },
+        }
+    }
+}
+
+impl Read for PrefetchReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.position >= self.current.len() {
+            if !self.next_chunk()? {
+                return Ok(0);
+            }
+        }
+
+        let len = buf.len().min(self.current.len() - self.position);
+        buf[..len].copy_from_slice(&self.current[self.position..(self.position + len)]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, ErrorKind, Read};
+
+    use super::PrefetchReader;
+
+    struct FailingReader {
+        remaining: usize,
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(io::Error::new(ErrorKind::ConnectionReset, "source went away"));
+            }
+            let len = buf.len().min(self.remaining).min(3);
+            buf[..len].fill(1);
+            self.remaining -= len;
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn prefetch_reads_whole_source() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        for (chunk_len, chunks_ahead) in [(1, 1), (7, 3), (4096, 2), (20_000, 1)] {
+            let mut reader = PrefetchReader::with_capacity(Cursor::new(data.clone()), chunk_len, chunks_ahead);
+            let mut read = Vec::new();
+            reader.read_to_end(&mut read).unwrap();
+            assert_eq!(data, read);
+            assert_eq!(0, reader.read(&mut [0; 4]).unwrap());
+        }
+    }
+
+    #[test]
+    fn prefetch_reports_source_errors() {
+        let mut reader = PrefetchReader::with_capacity(FailingReader { remaining: 10 }, 4, 1);
+        let mut read = Vec::new();
+        let err = reader.read_to_end(&mut read).expect_err("source error should be reported");
+        assert_eq!(ErrorKind::ConnectionReset, err.kind());
+        assert_eq!(vec![1; 10], read);
+        assert_eq!(0, reader.read(&mut [0; 4]).unwrap());
+    }
+}
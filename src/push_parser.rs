@@ -0,0 +1,296 @@
+use alloc::borrow::ToOwned;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::error::{FeedError, TagIteratorError};
+use crate::errors::tag_iterator::CorruptedFileError;
+use crate::spec_util::{read_string, SpecCache};
+use crate::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
+use crate::tag_iterator_util::{check_child_size, EBMLSize, ProcessingTag, DEFAULT_MAX_DEPTH};
+use crate::tools;
+
+///
+/// A parser that is given data as it arrives rather than reading it from a source, for driving parsing from an event loop (e.g. chunks from a network protocol).
+///
+/// Data is passed in with [`Self::feed()`], and tags are read by iterating over the parser.  Iteration returns `None` whenever the fed data ends before the next complete tag: this is the signal that more data is needed, and iteration can resume once more has been fed.  Once the input is over, [`Self::finish()`] lets the parser end any open "Master" tags, or report an [`TagIteratorError::UnexpectedEOF`] if the data stopped part way through a tag.
+///
+/// Tags are emitted the same way [`TagIterator`](crate::TagIterator) emits them without any `tags_to_buffer`: "Master" tags are emitted as [`Master::Start`] and [`Master::End`] variants, and tags with ids that aren't in the specification are emitted as "RawTag" variants.  Only the unread data is kept in memory, plus at most one tag's worth of data that is waiting to be completed.  The parser stops after returning an error.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::EbmlPushParser;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut parser: EbmlPushParser<EmptySpec> = EbmlPushParser::new();
+/// parser.feed(&[0x81, 0x83, 0x01]).unwrap();
+/// assert!(parser.next().is_none());
+///
+/// parser.feed(&[0x02, 0x03, 0x82]).unwrap();
+/// assert_eq!(EmptySpec::with_data(0x81, &[0x01, 0x02, 0x03]), parser.next().unwrap().unwrap());
+/// assert!(parser.next().is_none());
+///
+/// parser.finish();
+/// assert!(parser.next().unwrap().is_err());
+/// assert!(parser.feed(&[0x01]).is_err());
+/// ```
+///
+pub struct EbmlPushParser<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    buf: VecDeque<u8>,
+    offset: usize,
+    finished: bool,
+    failed: bool,
+    max_depth: usize,
+    max_tag_size: Option<usize>,
+    tag_stack: Vec<ProcessingTag<TSpec>>,
+    spec_cache: SpecCache,
+}
+
+impl<TSpec> Default for EbmlPushParser<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TSpec> EbmlPushParser<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`EbmlPushParser`] that hasn't been given any data.
+    ///
+    pub fn new() -> Self {
+        EbmlPushParser {
+            buf: VecDeque::new(),
+            offset: 0,
+            finished: false,
+            failed: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_tag_size: None,
+            tag_stack: Vec::new(),
+            spec_cache: SpecCache::default(),
+        }
+    }
+
+    ///
+    /// Adds `bytes` to the end of the data to be parsed.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns [`FeedError::Finished`] without keeping `bytes` if it is called after [`Self::finish()`].
+    ///
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<(), FeedError> {
+        if self.finished {
+            return Err(FeedError::Finished);
+        }
+        self.buf.extend(bytes);
+        Ok(())
+    }
+
+    ///
+    /// Signals that all data has been fed, so the remaining tags are emitted and open "Master" tags are ended.
+    ///
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    ///
+    /// Sets how deeply "Master" tags may be nested before an error is returned.  Defaults to [`DEFAULT_MAX_DEPTH`](crate::iterator::DEFAULT_MAX_DEPTH).
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    ///
+    /// Configures the largest amount of data a single non-"Master" tag may have.
+    ///
+    /// The data of a tag is kept until all of it has been fed, so without a limit a single corrupted or malicious size can make the parser buffer an arbitrary amount of data.  Tags with more than `max_size` bytes of data produce a [`CorruptedFileError::MaxTagSizeExceeded`] error as soon as their size has been read, before any of their data is buffered.  `None` (the default) disables the limit.
+    ///
+    pub fn set_max_tag_size(&mut self, max_size: Option<usize>) {
+        self.max_tag_size = max_size;
+    }
+
+    ///
+    /// Gets the number of bytes that have been fed but not parsed yet.
+    ///
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    ///
+    /// Gets the number of bytes that have been parsed since the parser was created.
+    ///
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    ///
+    /// Reads a vint that starts `skip` bytes into the unread data, returning `None` if more data is needed.  `tag_id` is the id of the tag when reading its size.
+    ///
+    fn peek_vint(&self, skip: usize, tag_id: Option<u64>) -> Result<Option<(u64, usize)>, TagIteratorError> {
+        let mut bytes = [0u8; 8];
+        let mut len = 0;
+        for (dest, byte) in bytes.iter_mut().zip(self.buf.iter().skip(skip)) {
+            *dest = *byte;
+            len += 1;
+        }
+        tools::read_vint(&bytes[..len]).map_err(|_| TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidVint { position: self.offset + skip, tag_id }))
+    }
+
+    ///
+    /// Removes `len` bytes from the front of the unread data.
+    ///
+    fn consume(&mut self, len: usize) -> Vec<u8> {
+        self.offset += len;
+        self.buf.drain(..len).collect()
+    }
+
+    fn end_tag(&mut self) -> Option<TSpec> {
+        self.tag_stack.pop().map(|tag| tag.tag)
+    }
+
+    fn read_tag(&mut self) -> Result<Option<TSpec>, TagIteratorError> {
+        let tag_start = self.offset;
+        let (id_value, id_len) = match self.peek_vint(0, None)? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let tag_id = id_value + (1 << (7 * id_len));
+
+        // A tag that can't be a child of an unknown sized parent ends it
+        if let Some(parent) = self.tag_stack.last() {
+            if !parent.size.is_known() && parent.is_ended_by(tag_id) {
+                return Ok(self.end_tag());
+            }
+        }
+
+        let (size_value, size_len) = match self.peek_vint(id_len, Some(tag_id))? {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+        let size = EBMLSize::new(size_value, size_len);
+        let header_len = id_len + size_len;
+        if let Known(size) = size {
+            check_child_size(&self.tag_stack, tag_start, tag_id, header_len, size).map_err(TagIteratorError::CorruptedFileData)?;
+        }
+
+        let spec_tag_type = self.spec_cache.lookup::<TSpec>(tag_id).data_type;
+        if matches!(spec_tag_type, Some(TagDataType::Master)) {
+            if self.tag_stack.len() >= self.max_depth {
+                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxDepthExceeded { tag_id, position: tag_start, max_depth: self.max_depth }));
+            }
+            self.consume(header_len);
+            self.tag_stack.push(ProcessingTag {
+                tag: TSpec::get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id)),
+                size,
+                tag_start,
+                data_start: self.offset,
+            });
+            return Ok(Some(TSpec::get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id))));
+        }
+
+        let size = match size {
+            Known(size) => size,
+            Unknown => return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id, position: tag_start + header_len })),
+        };
+        if let Some(max_size) = self.max_tag_size.filter(|max_size| size > *max_size) {
+            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { position: tag_start, tag_id, size, max_size }));
+        }
+        if self.buf.len() - header_len < size {
+            return Ok(None);
+        }
+        self.consume(header_len);
+        let raw_data = self.consume(size);
+        let tag = match spec_tag_type {
+            Some(TagDataType::Master) => unreachable!("Master should have been handled before reading data"),
+            Some(TagDataType::UnsignedInt) => {
+                let val = tools::arr_to_u64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
+                TSpec::get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was unsigned int, but could not get tag!", tag_id))
+            },
+            Some(TagDataType::Integer) => {
+                let val = tools::arr_to_i64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
+                TSpec::get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was integer, but could not get tag!", tag_id))
+            },
+            Some(TagDataType::Utf8) => {
+                let val = read_string::<TSpec>(tag_id, &raw_data).map(str::to_owned).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
+                TSpec::get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", tag_id))
+            },
+            Some(TagDataType::Binary) => {
+                TSpec::get_binary_tag(tag_id, &raw_data).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", tag_id))
+            },
+            Some(TagDataType::Float) => {
+                let val = tools::arr_to_f64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
+                TSpec::get_float_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was float, but could not get tag!", tag_id))
+            },
+            Some(TagDataType::Date) => {
+                let val = tools::arr_to_date(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
+                TSpec::get_date_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was date, but could not get tag!", tag_id))
+            },
+            None => TSpec::get_raw_tag(tag_id, &raw_data),
+        };
+        Ok(Some(tag))
+    }
+
+    ///
+    /// Handles the end of the input once no more complete tags are available.
+    ///
+    fn read_eof(&mut self) -> Option<Result<TSpec, TagIteratorError>> {
+        if !self.buf.is_empty() {
+            let id = self.peek_vint(0, None).ok().flatten();
+            let tag_size = id.and_then(|(value, id_len)| self.peek_vint(id_len, Some(value + (1 << (7 * id_len)))).ok().flatten()).and_then(|(value, length)| match EBMLSize::new(value, length) {
+                Known(size) => Some(size),
+                Unknown => None,
+            });
+            let tag_start = self.offset;
+            let partial_data = self.consume(self.buf.len());
+            return Some(Err(TagIteratorError::UnexpectedEOF {
+                tag_start,
+                tag_id: id.map(|(value, length)| value + (1 << (7 * length))),
+                tag_size,
+                partial_data: Some(partial_data),
+            }));
+        }
+
+        let open = self.tag_stack.last()?;
+        if open.data_end().is_some_and(|end| end > self.offset) {
+            let (tag_start, tag_id, tag_size) = (open.tag_start, open.tag.get_id(), open.data_end().map(|end| end - open.data_start));
+            self.tag_stack.clear();
+            return Some(Err(TagIteratorError::UnexpectedEOF { tag_start, tag_id: Some(tag_id), tag_size, partial_data: None }));
+        }
+        self.end_tag().map(Ok)
+    }
+}
+
+impl<TSpec> Iterator for EbmlPushParser<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<TSpec, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        if let Some(tag) = self.tag_stack.last() {
+            if tag.data_end().is_some_and(|end| self.offset >= end) {
+                return self.end_tag().map(Ok);
+            }
+        }
+
+        let result = match self.read_tag() {
+            Ok(Some(tag)) => Some(Ok(tag)),
+            Ok(None) if self.finished => self.read_eof(),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        };
+        if matches!(result, Some(Err(_))) {
+            self.failed = true;
+            self.buf.clear();
+        }
+        result
+    }
+}
@@ -0,0 +1,357 @@
+use std::fmt::{self, Debug, Write as _};
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::{Path, PathBuf};
+
+use crate::specs::{EbmlSpecification, EbmlTag};
+use crate::{TagIterator, TagWriter};
+
+///
+/// Maximum number of [`Difference`]s recorded for a single file.
+///
+pub const MAX_REPORTED_DIFFERENCES: usize = 16;
+
+///
+/// How closely re-written data matched the original.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum RoundTripOutcome {
+
+    ///
+    /// The re-written bytes are identical to the original.
+    ///
+    ByteExact,
+
+    ///
+    /// The re-written bytes differ (e.g. in vint widths or unknown sizes) but they read back as the same tags.
+    ///
+    Structural,
+
+    ///
+    /// The re-written data reads back as different tags.  At most [`MAX_REPORTED_DIFFERENCES`] are listed.
+    ///
+    Mismatch(Vec<Difference>),
+
+    ///
+    /// The original data could not be read.
+    ///
+    ReadError {
+
+        ///
+        /// Byte offset of the last tag that was read before the error.
+        ///
+        offset: usize,
+        message: String,
+    },
+
+    ///
+    /// The tags read from the original could not be written.
+    ///
+    WriteError {
+
+        ///
+        /// Index of the tag that could not be written.
+        ///
+        tag_index: usize,
+        message: String,
+    },
+}
+
+impl RoundTripOutcome {
+
+    ///
+    /// Returns `true` for [`RoundTripOutcome::ByteExact`] and [`RoundTripOutcome::Structural`].
+    ///
+    pub fn is_equivalent(&self) -> bool {
+        matches!(self, RoundTripOutcome::ByteExact | RoundTripOutcome::Structural)
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            RoundTripOutcome::ByteExact => "byte_exact",
+            RoundTripOutcome::Structural => "structural",
+            RoundTripOutcome::Mismatch(_) => "mismatch",
+            RoundTripOutcome::ReadError { .. } => "read_error",
+            RoundTripOutcome::WriteError { .. } => "write_error",
+        }
+    }
+}
+
+///
+/// A tag that did not survive a round trip.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+
+    ///
+    /// Index of the tag in the sequence of tags read from the original.
+    ///
+    pub tag_index: usize,
+
+    ///
+    /// Byte offset of the tag in the original data, if the original has a tag at this index.
+    ///
+    pub offset: Option<usize>,
+
+    ///
+    /// Debug formatting of the original tag, or [`None`] if the re-written data has extra tags.
+    ///
+    pub expected: Option<String>,
+
+    ///
+    /// Debug formatting of the re-read tag, or [`None`] if the re-written data is missing tags.
+    ///
+    pub found: Option<String>,
+}
+
+///
+/// The result of round-tripping a single file.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub outcome: RoundTripOutcome,
+}
+
+///
+/// The result of round-tripping every file in a directory.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoundTripReport {
+    pub files: Vec<FileReport>,
+}
+
+impl RoundTripReport {
+
+    ///
+    /// Returns `true` if every file round-tripped to equivalent data.
+    ///
+    pub fn is_success(&self) -> bool {
+        self.files.iter().all(|file| file.outcome.is_equivalent())
+    }
+
+    ///
+    /// Returns the reports of files that did not round-trip to equivalent data.
+    ///
+    pub fn failures(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| !file.outcome.is_equivalent())
+    }
+
+    ///
+    /// Formats the report as JSON, for consumption by CI tooling.
+    ///
+    /// The output is an object with a `"success"` boolean and a `"files"` array.  Each file has a `"path"`, an `"outcome"` (one of `"byte_exact"`, `"structural"`, `"mismatch"`, `"read_error"`, or `"write_error"`), and, depending on the outcome, `"differences"`, `"offset"`, `"tag_index"`, and `"message"` fields.
+    ///
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        let _ = write!(json, "{{\"success\":{},\"files\":[", self.is_success());
+        for (i, file) in self.files.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str("{\"path\":");
+            push_json_string(&mut json, &file.path.to_string_lossy());
+            let _ = write!(json, ",\"outcome\":\"{}\"", file.outcome.kind());
+            match &file.outcome {
+                RoundTripOutcome::ByteExact | RoundTripOutcome::Structural => {},
+                RoundTripOutcome::Mismatch(differences) => {
+                    json.push_str(",\"differences\":[");
+                    for (j, difference) in differences.iter().enumerate() {
+                        if j > 0 {
+                            json.push(',');
+                        }
+                        let _ = write!(json, "{{\"tag_index\":{},\"offset\":", difference.tag_index);
+                        push_json_option(&mut json, difference.offset.map(|offset| offset.to_string()));
+                        json.push_str(",\"expected\":");
+                        push_json_string_option(&mut json, difference.expected.as_deref());
+                        json.push_str(",\"found\":");
+                        push_json_string_option(&mut json, difference.found.as_deref());
+                        json.push('}');
+                    }
+                    json.push(']');
+                },
+                RoundTripOutcome::ReadError { offset, message } => {
+                    let _ = write!(json, ",\"offset\":{offset},\"message\":");
+                    push_json_string(&mut json, message);
+                },
+                RoundTripOutcome::WriteError { tag_index, message } => {
+                    let _ = write!(json, ",\"tag_index\":{tag_index},\"message\":");
+                    push_json_string(&mut json, message);
+                },
+            }
+            json.push('}');
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+impl fmt::Display for RoundTripReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            write!(f, "{}: ", file.path.display())?;
+            match &file.outcome {
+                RoundTripOutcome::ByteExact => writeln!(f, "byte exact")?,
+                RoundTripOutcome::Structural => writeln!(f, "structurally equivalent")?,
+                RoundTripOutcome::Mismatch(differences) => {
+                    writeln!(f, "{} difference(s)", differences.len())?;
+                    for difference in differences {
+                        writeln!(f, "  tag {} at {:?}: expected {:?}, found {:?}", difference.tag_index, difference.offset, difference.expected, difference.found)?;
+                    }
+                },
+                RoundTripOutcome::ReadError { offset, message } => writeln!(f, "read error at {offset}: {message}")?,
+                RoundTripOutcome::WriteError { tag_index, message } => writeln!(f, "write error at tag {tag_index}: {message}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Reads `data` with `<TSpec>`, writes the tags back out with a [`TagWriter`], and compares the result with the original.
+///
+/// Tags are read as [`Master::Start`](crate::specs::Master::Start) and [`Master::End`](crate::specs::Master::End) pairs so that differences can be attributed to individual tags.
+///
+pub fn round_trip<TSpec>(data: &[u8]) -> RoundTripOutcome
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + Debug + PartialEq
+{
+    let original = match read_tags::<TSpec>(data) {
+        Ok(tags) => tags,
+        Err((offset, message)) => return RoundTripOutcome::ReadError { offset, message },
+    };
+
+    let mut writer = TagWriter::new(Vec::new());
+    for (tag_index, (tag, _)) in original.iter().enumerate() {
+        if let Err(err) = writer.write(tag) {
+            return RoundTripOutcome::WriteError { tag_index, message: err.to_string() };
+        }
+    }
+    let written = match writer.into_inner() {
+        Ok(written) => written,
+        Err(err) => return RoundTripOutcome::WriteError { tag_index: original.len(), message: err.to_string() },
+    };
+
+    if written == data {
+        return RoundTripOutcome::ByteExact;
+    }
+
+    let rewritten = match read_tags::<TSpec>(&written) {
+        Ok(tags) => tags,
+        Err((offset, message)) => return RoundTripOutcome::Mismatch(vec![Difference {
+            tag_index: original.len(),
+            offset: None,
+            expected: None,
+            found: Some(format!("unreadable data at offset {offset} of the re-written output: {message}")),
+        }]),
+    };
+
+    let differences: Vec<Difference> = (0..original.len().max(rewritten.len()))
+        .filter_map(|tag_index| {
+            let expected = original.get(tag_index);
+            let found = rewritten.get(tag_index);
+            if expected.map(|t| &t.0) == found.map(|t| &t.0) {
+                return None;
+            }
+            Some(Difference {
+                tag_index,
+                offset: expected.map(|t| t.1),
+                expected: expected.map(|t| format!("{:?}", t.0)),
+                found: found.map(|t| format!("{:?}", t.0)),
+            })
+        })
+        .take(MAX_REPORTED_DIFFERENCES)
+        .collect();
+
+    if differences.is_empty() {
+        RoundTripOutcome::Structural
+    } else {
+        RoundTripOutcome::Mismatch(differences)
+    }
+}
+
+///
+/// Runs [`round_trip()`] on every file directly inside `dir`, in path order.
+///
+/// ## Errors
+///
+/// Returns an error if the directory or one of its files cannot be read.  Problems with the contents of a file are recorded in the returned report instead.
+///
+/// ## Example
+///
+/// ```no_run
+/// use ebml_iterable::conformance::round_trip_dir;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let report = round_trip_dir::<EmptySpec>("tests/reference")?;
+/// assert!(report.is_success(), "{}", report.to_json());
+/// # Ok(())
+/// # }
+/// ```
+///
+pub fn round_trip_dir<TSpec>(dir: impl AsRef<Path>) -> io::Result<RoundTripReport>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + Debug + PartialEq
+{
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    let mut report = RoundTripReport::default();
+    for path in paths {
+        let data = fs::read(&path)?;
+        let outcome = round_trip::<TSpec>(&data);
+        report.files.push(FileReport { path, outcome });
+    }
+    Ok(report)
+}
+
+fn read_tags<TSpec>(data: &[u8]) -> Result<Vec<(TSpec, usize)>, (usize, String)>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut iter: TagIterator<_, TSpec> = TagIterator::new(Cursor::new(data), &[]);
+    let mut tags = Vec::new();
+    while let Some(tag) = iter.next() {
+        match tag {
+            Ok(tag) => tags.push((tag, iter.last_emitted_tag_offset())),
+            Err(err) => return Err((iter.last_emitted_tag_offset(), err.to_string())),
+        }
+    }
+    Ok(tags)
+}
+
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            },
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn push_json_string_option(json: &mut String, value: Option<&str>) {
+    match value {
+        Some(value) => push_json_string(json, value),
+        None => json.push_str("null"),
+    }
+}
+
+fn push_json_option(json: &mut String, value: Option<String>) {
+    json.push_str(value.as_deref().unwrap_or("null"));
+}
@@ -0,0 +1,1030 @@
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use serde::forward_to_deserialize_any;
+
+use crate::error::SerdeError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use crate::tree::{TreeNode, TreeValue};
+use crate::{TagIterator, TagTree, TagWriter};
+
+///
+/// Parses a field name written as a hexadecimal element id, like `0x4d80`.
+///
+fn field_id(name: &str) -> Option<u64> {
+    let hex = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X"))?;
+    u64::from_str_radix(hex, 16).ok()
+}
+
+fn field_matches<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(name: &str, id: u64) -> bool {
+    field_id(name) == Some(id) || TSpec::get_tag_name(id) == Some(name)
+}
+
+///
+/// Reads every remaining tag from `iter` and deserializes the document into a `T`.
+///
+/// See [`Deserializer`] for how elements are matched to fields.  The whole document is read into a [`TagTree`] first, so this is meant for documents (or sections of documents) that fit in memory.  Use [`TagIterator::set_max_depth()`] and [`TagIterator::set_max_tag_size()`] to limit what `iter` accepts.
+///
+/// ## Errors
+///
+/// Returns [`SerdeError::Read`] if reading the document fails, or another [`SerdeError`] if the document does not match `T`.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use serde::Deserialize;
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::serde_ebml::from_iterator;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// #[derive(Deserialize)]
+/// struct Document {
+///     #[serde(rename = "0x81")]
+///     first: Vec<u8>,
+///     #[serde(rename = "0x82")]
+///     others: Vec<Vec<u8>>,
+/// }
+///
+/// let iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x80, 0x82, 0x81, 0x02]), &[]);
+/// let document: Document = from_iterator(iter).unwrap();
+/// assert_eq!(vec![0x01], document.first);
+/// assert_eq!(2, document.others.len());
+/// ```
+///
+pub fn from_iterator<TSpec, R, T>(iter: TagIterator<R, TSpec>) -> Result<T, SerdeError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    R: Read,
+    T: DeserializeOwned
+{
+    let tree = TagTree::read_from(iter)?;
+    from_tree::<TSpec, T>(&tree)
+}
+
+///
+/// Deserializes a `T` from the top level elements of `tree`.  See [`Deserializer`].
+///
+/// ## Errors
+///
+/// Returns a [`SerdeError`] if the document does not match `T`.
+///
+pub fn from_tree<'de, TSpec, T>(tree: &'de TagTree) -> Result<T, SerdeError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    T: de::Deserialize<'de>
+{
+    T::deserialize(Deserializer::<TSpec>::from_tree(tree))
+}
+
+///
+/// A `serde` deserializer over a group of sibling elements, such as the top level of a document or the children of a "Master" element.
+///
+/// Structs are matched to elements by field name: a field named with a hexadecimal element id (like `#[serde(rename = "0x4d80")]`) or with the element's name in `TSpec` (see [`EbmlSpecification::get_tag_name()`]) receives the elements with that id.  Elements without a matching field are ignored.  Fields are deserialized as follows:
+///
+///   * Structs read the children of a "Master" element.
+///   * Sequences like `Vec<T>` read every element with the id, in order, so repeated elements like `TrackEntry` can be collected.  A `Vec<u8>` reads the bytes of a binary element instead.  Sequences are only read from elements that are present, so use `#[serde(default)]` for sequences that may be empty.
+///   * `Option<T>` is `None` when the element is missing.
+///   * Integers, floats, strings and bytes read the element's value, and `bool` reads an unsigned integer as `true` when nonzero.  Dates are read as integers.
+///   * Enums with only unit variants read a string element as the variant name or an unsigned integer element as the variant index.
+///   * Any other field reads the first element with the id.
+///
+/// Maps (and `deserialize_any`) receive each element keyed by its hexadecimal id.
+///
+pub struct Deserializer<'de, TSpec> {
+    nodes: Vec<TreeNode<'de>>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'de, TSpec> Deserializer<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a [`Deserializer`] over the top level elements of `tree`.
+    ///
+    pub fn from_tree(tree: &'de TagTree) -> Self {
+        Deserializer { nodes: tree.roots().collect(), spec: PhantomData }
+    }
+
+    ///
+    /// Returns a [`Deserializer`] over the children of `node`.
+    ///
+    pub fn from_node(node: TreeNode<'de>) -> Self {
+        Deserializer { nodes: node.children().collect(), spec: PhantomData }
+    }
+}
+
+impl<'de, TSpec> de::Deserializer<'de> for Deserializer<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_map(ElementMap::<TSpec> { nodes: self.nodes.into_iter(), value: None, spec: PhantomData })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_map(FieldMap::<TSpec> { nodes: self.nodes, fields: fields.iter(), value: Vec::new(), spec: PhantomData })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        unit_struct seq tuple tuple_struct map enum identifier
+    }
+}
+
+struct ElementMap<'de, TSpec> {
+    nodes: std::vec::IntoIter<TreeNode<'de>>,
+    value: Option<TreeNode<'de>>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'de, TSpec> MapAccess<'de> for ElementMap<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SerdeError> {
+        let node = match self.nodes.next() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        self.value = Some(node);
+        seed.deserialize(format!("0x{:x}", node.id()).into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerdeError> {
+        let node = self.value.take().ok_or_else(|| SerdeError::Message("value requested before key".to_string()))?;
+        seed.deserialize(NodeDeserializer::<TSpec> { node, spec: PhantomData })
+    }
+}
+
+struct FieldMap<'de, TSpec> {
+    nodes: Vec<TreeNode<'de>>,
+    fields: std::slice::Iter<'static, &'static str>,
+    value: Vec<TreeNode<'de>>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'de, TSpec> MapAccess<'de> for FieldMap<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SerdeError> {
+        for field in self.fields.by_ref() {
+            self.value = self.nodes.iter().copied().filter(|node| field_matches::<TSpec>(field, node.id())).collect();
+            if !self.value.is_empty() {
+                return seed.deserialize(de::value::StrDeserializer::<SerdeError>::new(field)).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerdeError> {
+        seed.deserialize(FieldDeserializer::<TSpec> { nodes: std::mem::take(&mut self.value), spec: PhantomData })
+    }
+}
+
+///
+/// Deserializes the value of a struct field from every element with the field's id.
+///
+struct FieldDeserializer<'de, TSpec> {
+    nodes: Vec<TreeNode<'de>>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'de, TSpec> FieldDeserializer<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn first(self) -> NodeDeserializer<'de, TSpec> {
+        NodeDeserializer { node: self.nodes[0], spec: PhantomData }
+    }
+}
+
+macro_rules! deserialize_first {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                self.first().$method(visitor)
+            }
+        )*
+    }
+}
+
+impl<'de, TSpec> de::Deserializer<'de> for FieldDeserializer<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if let [node] = self.nodes[..] {
+            if node.value() == TreeValue::Binary(&[]) {
+                // A single empty binary element is read as an empty `Vec<u8>`
+                return visitor.visit_seq(de::value::SeqDeserializer::<_, SerdeError>::new(std::iter::empty::<u8>()));
+            }
+        }
+        visitor.visit_seq(Repeated::<TSpec> { nodes: self.nodes.into_iter(), bytes: &[], spec: PhantomData })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    deserialize_first! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32 deserialize_f64
+        deserialize_char deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_unit deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+}
+
+///
+/// The elements of a sequence field.  Sequences of `u8` read the bytes of binary elements.
+///
+struct Repeated<'de, TSpec> {
+    nodes: std::vec::IntoIter<TreeNode<'de>>,
+    bytes: &'de [u8],
+    spec: PhantomData<TSpec>,
+}
+
+impl<'de, TSpec> SeqAccess<'de> for Repeated<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, SerdeError> {
+        if let Some((byte, rest)) = self.bytes.split_first() {
+            self.bytes = rest;
+            return seed.deserialize(de::value::U8Deserializer::<SerdeError>::new(*byte)).map(Some);
+        }
+        match self.nodes.next() {
+            Some(node) => seed.deserialize(RepeatedElement::<TSpec> { node, bytes: &mut self.bytes, spec: PhantomData }).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct RepeatedElement<'a, 'de, TSpec> {
+    node: TreeNode<'de>,
+    bytes: &'a mut &'de [u8],
+    spec: PhantomData<TSpec>,
+}
+
+impl<'a, 'de, TSpec> RepeatedElement<'a, 'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn first(self) -> NodeDeserializer<'de, TSpec> {
+        NodeDeserializer { node: self.node, spec: PhantomData }
+    }
+}
+
+impl<'a, 'de, TSpec> de::Deserializer<'de> for RepeatedElement<'a, 'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.node.value() {
+            TreeValue::Binary(data) => {
+                let (byte, rest) = data.split_first().ok_or_else(|| SerdeError::Message(format!("Binary element 0x{:x} is empty", self.node.id())))?;
+                *self.bytes = rest;
+                visitor.visit_u8(*byte)
+            },
+            _ => self.first().deserialize_u8(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, name: &'static str, len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.first().deserialize_tuple_struct(name, len, visitor)
+    }
+
+    deserialize_first! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32 deserialize_f64
+        deserialize_char deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+}
+
+///
+/// Deserializes a single element.
+///
+struct NodeDeserializer<'de, TSpec> {
+    node: TreeNode<'de>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'de, TSpec> NodeDeserializer<'de, TSpec> {
+    fn invalid_type<V: Visitor<'de>>(&self, visitor: &V) -> SerdeError {
+        let unexpected = match self.node.value() {
+            TreeValue::Master => de::Unexpected::Map,
+            TreeValue::UnsignedInt(value) => de::Unexpected::Unsigned(value),
+            TreeValue::Integer(value) | TreeValue::Date(value) => de::Unexpected::Signed(value),
+            TreeValue::Utf8(value) => de::Unexpected::Str(value),
+            TreeValue::Binary(value) => de::Unexpected::Bytes(value),
+            TreeValue::Float(value) => de::Unexpected::Float(value),
+        };
+        de::Error::invalid_type(unexpected, visitor)
+    }
+}
+
+impl<'de, TSpec> de::Deserializer<'de> for NodeDeserializer<'de, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.node.value() {
+            TreeValue::Master => Deserializer::<TSpec>::from_node(self.node).deserialize_any(visitor),
+            TreeValue::UnsignedInt(value) => visitor.visit_u64(value),
+            TreeValue::Integer(value) | TreeValue::Date(value) => visitor.visit_i64(value),
+            TreeValue::Utf8(value) => visitor.visit_borrowed_str(value),
+            TreeValue::Binary(value) => visitor.visit_borrowed_bytes(value),
+            TreeValue::Float(value) => visitor.visit_f64(value),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.node.value() {
+            TreeValue::UnsignedInt(value) => visitor.visit_bool(value != 0),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.node.value() {
+            TreeValue::Binary(value) => visitor.visit_seq(de::value::SeqDeserializer::<_, SerdeError>::new(value.iter().copied())),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        match self.node.value() {
+            TreeValue::Master => Deserializer::<TSpec>::from_node(self.node).deserialize_struct(name, fields, visitor),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        match self.node.value() {
+            TreeValue::Utf8(value) => visitor.visit_enum(de::value::StrDeserializer::<SerdeError>::new(value)),
+            TreeValue::UnsignedInt(value) => {
+                let index = u32::try_from(value).map_err(|_| <SerdeError as de::Error>::invalid_value(de::Unexpected::Unsigned(value), &"a variant index"))?;
+                visitor.visit_enum(de::value::U32Deserializer::<SerdeError>::new(index))
+            },
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        tuple tuple_struct map identifier
+    }
+}
+
+///
+/// Serializes `value` into `writer` as a group of sibling elements.  See [`Serializer`].
+///
+/// ## Errors
+///
+/// Returns [`SerdeError::Write`] if writing fails, or another [`SerdeError`] if `value` can't be represented as EBML elements.
+///
+/// ## Example
+///
+/// ```
+/// use serde::Serialize;
+/// use ebml_iterable::TagWriter;
+/// use ebml_iterable::serde_ebml::to_writer;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// #[derive(Serialize)]
+/// struct Document {
+///     #[serde(rename = "0x81")]
+///     first: Vec<u8>,
+///     #[serde(rename = "0x82")]
+///     second: Option<Vec<u8>>,
+/// }
+///
+/// let mut writer = TagWriter::new(Vec::new());
+/// to_writer::<EmptySpec, _, _>(&mut writer, &Document { first: vec![0x01], second: None }).unwrap();
+/// assert_eq!(vec![0x81, 0x81, 0x01], writer.into_inner().unwrap());
+/// ```
+///
+pub fn to_writer<TSpec, W, T>(writer: &mut TagWriter<W>, value: &T) -> Result<(), SerdeError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    W: Write,
+    T: Serialize + ?Sized
+{
+    value.serialize(Serializer::<W, TSpec>::new(writer))
+}
+
+///
+/// A `serde` serializer that writes a struct or map as a group of sibling elements to a [`TagWriter`].
+///
+/// This mirrors [`Deserializer`], except that fields must be named with hexadecimal element ids (like `#[serde(rename = "0x4d80")]`), since element names can't be looked up in a specification.  The type of each element is taken from `TSpec`, so for example an `i32` field can fill an unsigned integer, integer, date or float element.  Structs and maps are written as "Master" elements, sequences as repeated elements (except for sequences of bytes in binary elements), and unit enum variants as their name or index depending on the element type.  `None` fields and empty sequences are left out.
+///
+pub struct Serializer<'w, W: Write, TSpec> {
+    writer: &'w mut TagWriter<W>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'w, W: Write, TSpec> Serializer<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a [`Serializer`] writing to `writer`.
+    ///
+    pub fn new(writer: &'w mut TagWriter<W>) -> Self {
+        Serializer { writer, spec: PhantomData }
+    }
+}
+
+fn expected_elements() -> SerdeError {
+    SerdeError::Message("only structs and maps can be serialized as a group of elements".to_string())
+}
+
+impl<'w, W: Write, TSpec> ser::Serializer for Serializer<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<(), SerdeError>;
+    type SerializeTuple = Impossible<(), SerdeError>;
+    type SerializeTupleStruct = Impossible<(), SerdeError>;
+    type SerializeTupleVariant = Impossible<(), SerdeError>;
+    type SerializeMap = Fields<'w, W, TSpec>;
+    type SerializeStruct = Fields<'w, W, TSpec>;
+    type SerializeStructVariant = Impossible<(), SerdeError>;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_i8(self, _v: i8) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_i16(self, _v: i16) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_i32(self, _v: i32) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_i64(self, _v: i64) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_u8(self, _v: u8) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_u16(self, _v: u16) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_u32(self, _v: u32) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_u64(self, _v: u64) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_f32(self, _v: f32) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_f64(self, _v: f64) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_char(self, _v: char) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_str(self, _v: &str) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), SerdeError> { Err(expected_elements()) }
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, _variant: &'static str) -> Result<(), SerdeError> { Err(expected_elements()) }
+
+    fn serialize_none(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, _variant: &'static str, _value: &T) -> Result<(), SerdeError> {
+        Err(expected_elements())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        Err(expected_elements())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        Err(expected_elements())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        Err(expected_elements())
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(expected_elements())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        Ok(Fields { writer: self.writer, end: None, key: None, spec: PhantomData })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerdeError> {
+        Ok(Fields { writer: self.writer, end: None, key: None, spec: PhantomData })
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(expected_elements())
+    }
+}
+
+///
+/// Writes the fields of a struct or map as elements, optionally ending a "Master" element afterwards.
+///
+pub struct Fields<'w, W: Write, TSpec> {
+    writer: &'w mut TagWriter<W>,
+    end: Option<u64>,
+    key: Option<u64>,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'w, W: Write, TSpec> Fields<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn write_field<T: Serialize + ?Sized>(&mut self, id: u64, value: &T) -> Result<(), SerdeError> {
+        value.serialize(ElementSerializer::<W, TSpec> { writer: self.writer, id, spec: PhantomData })
+    }
+
+    fn finish(self) -> Result<(), SerdeError> {
+        if let Some(id) = self.end {
+            write_master::<W, TSpec>(self.writer, id, Master::End)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'w, W: Write, TSpec> ser::SerializeStruct for Fields<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        let id = field_id(key).ok_or_else(|| SerdeError::InvalidFieldName(key.to_string()))?;
+        self.write_field(id, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'w, W: Write, TSpec> ser::SerializeMap for Fields<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let id = self.key.take().ok_or_else(|| SerdeError::Message("value serialized before key".to_string()))?;
+        self.write_field(id, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.finish()
+    }
+}
+
+fn write_master<W: Write, TSpec>(writer: &mut TagWriter<W>, id: u64, master: Master<TSpec>) -> Result<(), SerdeError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let tag = TSpec::get_master_tag(id, master).ok_or(SerdeError::UnsupportedValue { tag_id: id, data_type: TSpec::get_tag_data_type(id) })?;
+    writer.write(&tag)?;
+    Ok(())
+}
+
+///
+/// Serializes the value of a field as the element (or elements) `id`.
+///
+struct ElementSerializer<'w, W: Write, TSpec> {
+    writer: &'w mut TagWriter<W>,
+    id: u64,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'w, W: Write, TSpec> ElementSerializer<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn unsupported(&self) -> SerdeError {
+        SerdeError::UnsupportedValue { tag_id: self.id, data_type: TSpec::get_tag_data_type(self.id) }
+    }
+
+    fn write(self, tag: Option<TSpec>) -> Result<(), SerdeError> {
+        let tag = tag.ok_or_else(|| self.unsupported())?;
+        self.writer.write(&tag)?;
+        Ok(())
+    }
+
+    fn write_signed(self, value: i64) -> Result<(), SerdeError> {
+        let tag = match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::UnsignedInt) => u64::try_from(value).ok().and_then(|value| TSpec::get_unsigned_int_tag(self.id, value)),
+            Some(TagDataType::Integer) => TSpec::get_signed_int_tag(self.id, value),
+            Some(TagDataType::Date) => TSpec::get_date_tag(self.id, value),
+            Some(TagDataType::Float) => TSpec::get_float_tag(self.id, value as f64),
+            _ => None,
+        };
+        self.write(tag)
+    }
+
+    fn write_unsigned(self, value: u64) -> Result<(), SerdeError> {
+        let tag = match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::UnsignedInt) => TSpec::get_unsigned_int_tag(self.id, value),
+            Some(TagDataType::Integer) => i64::try_from(value).ok().and_then(|value| TSpec::get_signed_int_tag(self.id, value)),
+            Some(TagDataType::Date) => i64::try_from(value).ok().and_then(|value| TSpec::get_date_tag(self.id, value)),
+            Some(TagDataType::Float) => TSpec::get_float_tag(self.id, value as f64),
+            _ => None,
+        };
+        self.write(tag)
+    }
+
+    fn write_float(self, value: f64) -> Result<(), SerdeError> {
+        let tag = match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::Float) => TSpec::get_float_tag(self.id, value),
+            _ => None,
+        };
+        self.write(tag)
+    }
+}
+
+impl<'w, W: Write, TSpec> ser::Serializer for ElementSerializer<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+    type SerializeSeq = Repeat<'w, W, TSpec>;
+    type SerializeTuple = Repeat<'w, W, TSpec>;
+    type SerializeTupleStruct = Repeat<'w, W, TSpec>;
+    type SerializeTupleVariant = Impossible<(), SerdeError>;
+    type SerializeMap = Fields<'w, W, TSpec>;
+    type SerializeStruct = Fields<'w, W, TSpec>;
+    type SerializeStructVariant = Impossible<(), SerdeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), SerdeError> { self.write_unsigned(v as u64) }
+    fn serialize_i8(self, v: i8) -> Result<(), SerdeError> { self.write_signed(v.into()) }
+    fn serialize_i16(self, v: i16) -> Result<(), SerdeError> { self.write_signed(v.into()) }
+    fn serialize_i32(self, v: i32) -> Result<(), SerdeError> { self.write_signed(v.into()) }
+    fn serialize_i64(self, v: i64) -> Result<(), SerdeError> { self.write_signed(v) }
+    fn serialize_u8(self, v: u8) -> Result<(), SerdeError> { self.write_unsigned(v.into()) }
+    fn serialize_u16(self, v: u16) -> Result<(), SerdeError> { self.write_unsigned(v.into()) }
+    fn serialize_u32(self, v: u32) -> Result<(), SerdeError> { self.write_unsigned(v.into()) }
+    fn serialize_u64(self, v: u64) -> Result<(), SerdeError> { self.write_unsigned(v) }
+    fn serialize_f32(self, v: f32) -> Result<(), SerdeError> { self.write_float(v.into()) }
+    fn serialize_f64(self, v: f64) -> Result<(), SerdeError> { self.write_float(v) }
+
+    fn serialize_char(self, v: char) -> Result<(), SerdeError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerdeError> {
+        let tag = match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::Utf8) => TSpec::get_utf8_tag(self.id, v.to_string()),
+            Some(TagDataType::Binary) => TSpec::get_binary_tag(self.id, v.as_bytes()),
+            _ => None,
+        };
+        self.write(tag)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SerdeError> {
+        let tag = match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::Binary) => TSpec::get_binary_tag(self.id, v),
+            _ => None,
+        };
+        self.write(tag)
+    }
+
+    fn serialize_none(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerdeError> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerdeError> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, index: u32, variant: &'static str) -> Result<(), SerdeError> {
+        match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::Utf8) => self.serialize_str(variant),
+            _ => self.write_unsigned(index.into()),
+        }
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, _variant: &'static str, _value: &T) -> Result<(), SerdeError> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        let mode = match TSpec::get_tag_data_type(self.id) {
+            Some(TagDataType::Binary) => RepeatMode::Undecided,
+            _ => RepeatMode::Elements,
+        };
+        Ok(Repeat { writer: self.writer, id: self.id, mode, spec: PhantomData })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        write_master::<W, TSpec>(self.writer, self.id, Master::Start)?;
+        Ok(Fields { writer: self.writer, end: Some(self.id), key: None, spec: PhantomData })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(self.unsupported())
+    }
+}
+
+enum RepeatMode {
+    // Binary elements hold either the bytes of a single element or a sequence of elements, depending on the items
+    Undecided,
+    Bytes(Vec<u8>),
+    Elements,
+}
+
+///
+/// Writes each item of a sequence as a separate element, or collects the bytes of a binary element.
+///
+pub struct Repeat<'w, W: Write, TSpec> {
+    writer: &'w mut TagWriter<W>,
+    id: u64,
+    mode: RepeatMode,
+    spec: PhantomData<TSpec>,
+}
+
+impl<'w, W: Write, TSpec> Repeat<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        match &mut self.mode {
+            RepeatMode::Undecided => match value.serialize(ByteSerializer) {
+                Ok(byte) => self.mode = RepeatMode::Bytes(vec![byte]),
+                Err(_) => {
+                    self.mode = RepeatMode::Elements;
+                    return self.push(value);
+                },
+            },
+            RepeatMode::Bytes(bytes) => bytes.push(value.serialize(ByteSerializer)?),
+            RepeatMode::Elements => value.serialize(ElementSerializer::<W, TSpec> { writer: self.writer, id: self.id, spec: PhantomData })?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), SerdeError> {
+        if let RepeatMode::Bytes(bytes) = self.mode {
+            ElementSerializer::<W, TSpec> { writer: self.writer, id: self.id, spec: PhantomData }.write(TSpec::get_binary_tag(self.id, &bytes))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'w, W: Write, TSpec> ser::SerializeSeq for Repeat<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'w, W: Write, TSpec> ser::SerializeTuple for Repeat<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'w, W: Write, TSpec> ser::SerializeTupleStruct for Repeat<'w, W, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.finish()
+    }
+}
+
+fn not_a_byte() -> SerdeError {
+    SerdeError::Message("expected a byte".to_string())
+}
+
+///
+/// Extracts a single byte from an item of a sequence written to a binary element.
+///
+struct ByteSerializer;
+
+impl ser::Serializer for ByteSerializer {
+    type Ok = u8;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<u8, SerdeError>;
+    type SerializeTuple = Impossible<u8, SerdeError>;
+    type SerializeTupleStruct = Impossible<u8, SerdeError>;
+    type SerializeTupleVariant = Impossible<u8, SerdeError>;
+    type SerializeMap = Impossible<u8, SerdeError>;
+    type SerializeStruct = Impossible<u8, SerdeError>;
+    type SerializeStructVariant = Impossible<u8, SerdeError>;
+
+    fn serialize_u8(self, v: u8) -> Result<u8, SerdeError> { Ok(v) }
+    fn serialize_bool(self, _v: bool) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_i8(self, _v: i8) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_i16(self, _v: i16) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_i32(self, _v: i32) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_i64(self, _v: i64) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_u16(self, _v: u16) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_u32(self, _v: u32) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_u64(self, _v: u64) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_f32(self, _v: f32) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_f64(self, _v: f64) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_char(self, _v: char) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_str(self, _v: &str) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_none(self) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_unit(self) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, _variant: &'static str) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<u8, SerdeError> { value.serialize(self) }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, _variant: &'static str, _value: &T) -> Result<u8, SerdeError> { Err(not_a_byte()) }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> { Err(not_a_byte()) }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> { Err(not_a_byte()) }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerdeError> { Err(not_a_byte()) }
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, SerdeError> { Err(not_a_byte()) }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> { Err(not_a_byte()) }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerdeError> { Err(not_a_byte()) }
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, SerdeError> { Err(not_a_byte()) }
+}
+
+///
+/// Reads an element id from a map key, written either as an integer or as a hexadecimal string.
+///
+struct KeySerializer;
+
+fn invalid_key() -> SerdeError {
+    SerdeError::Message("map keys must be element ids".to_string())
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = u64;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<u64, SerdeError>;
+    type SerializeTuple = Impossible<u64, SerdeError>;
+    type SerializeTupleStruct = Impossible<u64, SerdeError>;
+    type SerializeTupleVariant = Impossible<u64, SerdeError>;
+    type SerializeMap = Impossible<u64, SerdeError>;
+    type SerializeStruct = Impossible<u64, SerdeError>;
+    type SerializeStructVariant = Impossible<u64, SerdeError>;
+
+    fn serialize_u8(self, v: u8) -> Result<u64, SerdeError> { Ok(v.into()) }
+    fn serialize_u16(self, v: u16) -> Result<u64, SerdeError> { Ok(v.into()) }
+    fn serialize_u32(self, v: u32) -> Result<u64, SerdeError> { Ok(v.into()) }
+    fn serialize_u64(self, v: u64) -> Result<u64, SerdeError> { Ok(v) }
+    fn serialize_str(self, v: &str) -> Result<u64, SerdeError> { field_id(v).ok_or_else(|| SerdeError::InvalidFieldName(v.to_string())) }
+    fn serialize_bool(self, _v: bool) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_i8(self, _v: i8) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_i16(self, _v: i16) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_i32(self, _v: i32) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_i64(self, _v: i64) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_f32(self, _v: f32) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_f64(self, _v: f64) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_char(self, _v: char) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_none(self) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<u64, SerdeError> { value.serialize(self) }
+    fn serialize_unit(self) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<u64, SerdeError> { self.serialize_str(variant) }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<u64, SerdeError> { value.serialize(self) }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, _variant: &'static str, _value: &T) -> Result<u64, SerdeError> { Err(invalid_key()) }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> { Err(invalid_key()) }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> { Err(invalid_key()) }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerdeError> { Err(invalid_key()) }
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, SerdeError> { Err(invalid_key()) }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> { Err(invalid_key()) }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerdeError> { Err(invalid_key()) }
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, SerdeError> { Err(invalid_key()) }
+}
@@ -0,0 +1,352 @@
+use std::io::{self, Read, Write};
+
+use crate::error::TagIteratorError;
+use crate::ids;
+use crate::iterator::TagSpan;
+use crate::matroska::read_unsigned_int;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::TagIterator;
+
+const MAGIC: &[u8; 4] = b"EBIX";
+const FORMAT_VERSION: u8 = 1;
+
+///
+/// The location of a tag recorded in an [`ElementIndex`].
+///
+/// `offset` is the position of the tag's header and `size` includes the header.  `timestamp` holds the value of the `Timestamp` child of a Matroska `Cluster`, and is `None` for all other tags.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IndexEntry {
+    pub tag_id: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub timestamp: Option<u64>,
+}
+
+///
+/// A list of tag locations that can be stored next to a large document (a "sidecar" file), so later opens can seek straight to the tags they need rather than scanning the document again.
+///
+/// Indexes are built by [`Self::scan()`] or, when the document is already being read, by passing each tag to an [`IndexBuilder`].  [`Self::write_to()`] and [`Self::read_from()`] store and load the index in a compact binary format.  To read a tag found in the index, seek the source to [`IndexEntry::offset`] and create a [`TagIterator`] there (using [`TagIterator::with_parents()`] if the tag's parents are needed for context).
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::index::ElementIndex;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let index = ElementIndex::scan::<EmptySpec, _>(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x80]), &[0x82]).unwrap();
+/// assert_eq!(3, index.entries()[0].offset);
+///
+/// let mut stored = Vec::new();
+/// index.write_to(&mut stored).unwrap();
+/// assert_eq!(index, ElementIndex::read_from(stored.as_slice()).unwrap());
+/// ```
+///
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ElementIndex {
+    entries: Vec<IndexEntry>,
+}
+
+impl ElementIndex {
+
+    ///
+    /// Reads an entire document and records the location of every tag with an id in `tag_ids`.
+    ///
+    /// Typically `tag_ids` lists the top level elements of a `Segment` (`Cluster`, `Cues`, `Tags`, ...).  See [`IndexBuilder`] for building an index while reading a document for another purpose.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the first error encountered while reading the document.
+    ///
+    pub fn scan<TSpec, R>(source: R, tag_ids: &[u64]) -> Result<Self, TagIteratorError>
+        where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+        R: Read,
+    {
+        let mut builder = IndexBuilder::new(tag_ids);
+        let mut iter: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+        while let Some(tag) = iter.next() {
+            builder.observe(&tag?, iter.last_emitted_tag_span());
+        }
+        Ok(builder.finish())
+    }
+
+    ///
+    /// Gets all entries in the index, in the order the tags appear in the document.
+    ///
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    ///
+    /// Gets the entries for tags with id `tag_id`.
+    ///
+    pub fn find(&self, tag_id: u64) -> impl Iterator<Item = &IndexEntry> + '_ {
+        self.entries.iter().filter(move |entry| entry.tag_id == tag_id)
+    }
+
+    ///
+    /// Gets the `Cluster` containing `timestamp`; that is, the cluster with the latest timestamp that is not after `timestamp`.
+    ///
+    /// Returns `None` if no indexed cluster starts at or before `timestamp`.
+    ///
+    pub fn cluster_at(&self, timestamp: u64) -> Option<&IndexEntry> {
+        self.find(ids::CLUSTER)
+            .filter(|entry| entry.timestamp.is_some_and(|start| start <= timestamp))
+            .max_by_key(|entry| entry.timestamp)
+    }
+
+    ///
+    /// Writes the index to `dest`.
+    ///
+    /// Each entry is stored as a handful of variable length integers, with offsets stored relative to the previous entry, so an index typically needs only a few bytes per tag.
+    ///
+    /// ## Errors
+    ///
+    /// Returns any error from writing to `dest`, or an error of kind [`io::ErrorKind::InvalidInput`] if the entries are not in document order or a timestamp is `u64::MAX` (which the format can't store).  Nothing is written to `dest` in that case.
+    ///
+    pub fn write_to<W: Write>(&self, mut dest: W) -> io::Result<()> {
+        let mut data = Vec::with_capacity(16 + self.entries.len() * 8);
+        data.extend_from_slice(MAGIC);
+        data.push(FORMAT_VERSION);
+        write_varint(&mut data, self.entries.len() as u64);
+        let mut previous_offset = 0;
+        for entry in self.entries.iter() {
+            write_varint(&mut data, entry.tag_id);
+            let offset_delta = entry.offset.checked_sub(previous_offset).ok_or_else(|| invalid_input("element index entries are not in document order"))?;
+            write_varint(&mut data, offset_delta);
+            write_varint(&mut data, entry.size);
+            // Zero is reserved for entries without a timestamp
+            let timestamp = match entry.timestamp {
+                Some(timestamp) => timestamp.checked_add(1).ok_or_else(|| invalid_input("element index timestamp is too large"))?,
+                None => 0,
+            };
+            write_varint(&mut data, timestamp);
+            previous_offset = entry.offset;
+        }
+        dest.write_all(&data)
+    }
+
+    ///
+    /// Reads an index previously stored by [`Self::write_to()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns any error from reading `source`, or an error of kind [`io::ErrorKind::InvalidData`] if `source` does not hold a valid index.
+    ///
+    pub fn read_from<R: Read>(mut source: R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        source.read_to_end(&mut data)?;
+        if data.len() < 5 || &data[..4] != MAGIC {
+            return Err(invalid_data("not an element index"));
+        }
+        if data[4] != FORMAT_VERSION {
+            return Err(invalid_data("unsupported element index version"));
+        }
+
+        let mut position = 5;
+        let count = read_varint(&data, &mut position)?;
+        let mut entries = Vec::new();
+        let mut previous_offset: u64 = 0;
+        for _ in 0..count {
+            let tag_id = read_varint(&data, &mut position)?;
+            let offset = previous_offset.checked_add(read_varint(&data, &mut position)?).ok_or_else(|| invalid_data("element index offset overflowed"))?;
+            let size = read_varint(&data, &mut position)?;
+            let timestamp = read_varint(&data, &mut position)?.checked_sub(1);
+            entries.push(IndexEntry { tag_id, offset, size, timestamp });
+            previous_offset = offset;
+        }
+        if position != data.len() {
+            return Err(invalid_data("unexpected data after element index"));
+        }
+        Ok(ElementIndex { entries })
+    }
+}
+
+///
+/// Builds an [`ElementIndex`] from tags read by an existing [`TagIterator`], so a document can be indexed during a scan done for another purpose.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::index::IndexBuilder;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x80]), &[]);
+/// let mut builder = IndexBuilder::new(&[0x81]);
+/// while let Some(tag) = iter.next() {
+///     builder.observe(&tag.unwrap(), iter.last_emitted_tag_span());
+/// }
+/// assert_eq!(3, builder.finish().entries()[0].size);
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct IndexBuilder {
+    tag_ids: Vec<u64>,
+    entries: Vec<IndexEntry>,
+    // Index of the entry for each open "Master" tag, if it is indexed
+    open: Vec<Option<usize>>,
+    // End of the furthest tag observed, for sizing unknown sized masters
+    end: u64,
+}
+
+impl IndexBuilder {
+
+    ///
+    /// Returns a new builder that records tags with an id in `tag_ids`.
+    ///
+    pub fn new(tag_ids: &[u64]) -> Self {
+        IndexBuilder {
+            tag_ids: tag_ids.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Records `tag` if it should be indexed.  `span` must be the span reported by [`TagIterator::last_emitted_tag_span()`] after the tag was emitted, and every tag emitted by the iterator must be observed.
+    ///
+    pub fn observe<TSpec>(&mut self, tag: &TSpec, span: TagSpan)
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let id = tag.get_id();
+        let offset = span.offset as u64;
+        let known_end = span.data_len.map(|len| offset + (span.header_len + len) as u64);
+
+        match tag.as_master() {
+            Some(Master::Start) => {
+                let entry = self.record(id, offset, known_end);
+                self.open.push(entry);
+                self.end = self.end.max(offset + span.header_len as u64);
+                return;
+            },
+            Some(Master::End) => {
+                if let Some(Some(index)) = self.open.pop() {
+                    if known_end.is_none() {
+                        self.entries[index].size = self.end.saturating_sub(offset);
+                    }
+                }
+            },
+            _ => {
+                self.record(id, offset, known_end);
+                if id == ids::TIMESTAMP {
+                    if let Some(Some(parent)) = self.open.last() {
+                        let cluster = &mut self.entries[*parent];
+                        if cluster.tag_id == ids::CLUSTER {
+                            cluster.timestamp = read_unsigned_int(tag);
+                        }
+                    }
+                }
+            },
+        }
+        if let Some(end) = known_end {
+            self.end = self.end.max(end);
+        }
+    }
+
+    fn record(&mut self, tag_id: u64, offset: u64, end: Option<u64>) -> Option<usize> {
+        if !self.tag_ids.contains(&tag_id) {
+            return None;
+        }
+        self.entries.push(IndexEntry { tag_id, offset, size: end.map_or(0, |end| end - offset), timestamp: None });
+        Some(self.entries.len() - 1)
+    }
+
+    ///
+    /// Returns the index of the observed tags.
+    ///
+    pub fn finish(mut self) -> ElementIndex {
+        self.entries.sort_by_key(|entry| entry.offset);
+        ElementIndex { entries: self.entries }
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> io::Result<u64> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*position).ok_or_else(|| invalid_data("element index ended unexpectedly"))?;
+        *position += 1;
+        let bits = u64::from(byte & 0x7f);
+        if shift == 63 && bits > 1 {
+            return Err(invalid_data("element index value overflowed"));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("element index value overflowed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u64::MAX - 1, u64::MAX] {
+            let mut data = Vec::new();
+            write_varint(&mut data, value);
+            let mut position = 0;
+            assert_eq!(value, read_varint(&data, &mut position).unwrap());
+            assert_eq!(data.len(), position);
+        }
+
+        let mut position = 0;
+        assert!(read_varint(&[0xff; 10], &mut position).is_err());
+        let mut position = 0;
+        assert!(read_varint(&[0x80], &mut position).is_err());
+    }
+
+    #[test]
+    fn read_invalid_index() {
+        let index = ElementIndex { entries: vec![
+            IndexEntry { tag_id: ids::CLUSTER, offset: 0x40, size: 0x1000, timestamp: Some(0) },
+            IndexEntry { tag_id: ids::CUES, offset: 0x1040, size: 0x20, timestamp: None },
+        ] };
+        let mut data = Vec::new();
+        index.write_to(&mut data).unwrap();
+        assert_eq!(index, ElementIndex::read_from(data.as_slice()).unwrap());
+
+        assert_eq!(io::ErrorKind::InvalidData, ElementIndex::read_from(&data[..data.len() - 1]).unwrap_err().kind());
+        assert_eq!(io::ErrorKind::InvalidData, ElementIndex::read_from(&data[1..]).unwrap_err().kind());
+        data.push(0x00);
+        assert_eq!(io::ErrorKind::InvalidData, ElementIndex::read_from(data.as_slice()).unwrap_err().kind());
+        data[4] = FORMAT_VERSION + 1;
+        assert_eq!(io::ErrorKind::InvalidData, ElementIndex::read_from(data.as_slice()).unwrap_err().kind());
+    }
+
+    #[test]
+    fn write_invalid_index() {
+        let cluster = IndexEntry { tag_id: ids::CLUSTER, offset: 0x40, size: 0x1000, timestamp: Some(0) };
+        let cues = IndexEntry { tag_id: ids::CUES, offset: 0x1040, size: 0x20, timestamp: None };
+        let mut data = Vec::new();
+        let out_of_order = ElementIndex { entries: vec![cues, cluster] };
+        assert_eq!(io::ErrorKind::InvalidInput, out_of_order.write_to(&mut data).unwrap_err().kind());
+        let too_large = ElementIndex { entries: vec![IndexEntry { timestamp: Some(u64::MAX), ..cluster }] };
+        assert_eq!(io::ErrorKind::InvalidInput, too_large.write_to(&mut data).unwrap_err().kind());
+        assert!(data.is_empty());
+
+        let max_timestamp = ElementIndex { entries: vec![IndexEntry { timestamp: Some(u64::MAX - 1), ..cluster }] };
+        max_timestamp.write_to(&mut data).unwrap();
+        assert_eq!(max_timestamp, ElementIndex::read_from(data.as_slice()).unwrap());
+    }
+}
@@ -0,0 +1,328 @@
+use alloc::format;
+use core::convert::TryInto;
+
+use crate::spec_util::{check_ascii_string, validate_tag_path};
+
+use super::tag_iterator_util::EBMLSize::{self, Known, Unknown};
+use super::tag_iterator_util::DEFAULT_MAX_DEPTH;
+use super::tag_writer_util::{id_len, signed_int_width, size_len, unsigned_int_width, SizeWidth};
+
+use super::tools::{is_vint, vint_bytes_u64};
+use super::specs::{EbmlSpecification, EbmlTag, TagDataType, Master};
+
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// Number of bytes reserved for the size of an open "Master" tag.  Once the tag ends the size is shrunk to its minimal width and the body is moved up to follow it.
+///
+const RESERVED_SIZE_LEN: usize = 8;
+
+///
+/// Writes EBML tags into a caller-provided byte slice without allocating.
+///
+/// This is an alternative to `TagWriter` for embedded and other constrained environments.  Tags are encoded straight into `dest`, and writing a tag that doesn't fit returns a [`TagWriterError::BufferOverflow`] error rather than growing a buffer.  "Master" tags are written the same way as by a `TagWriter` using the default settings: the children of [`Master::Start`] tags (and [`Master::Full`] tags) are written in place after space reserved for the tag's size, which is filled in and shrunk to its minimal width once the tag ends.  The open tags are tracked inside `dest` as well, so there is no limit to how deeply tags can be nested other than [`Self::set_max_depth()`].
+///
+/// Nothing on the write path uses the heap.  Only some errors (such as [`TagWriterError::UnexpectedTag`], which lists the open tags) allocate to describe the problem.  A failed write leaves the writer as it was before the write.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::writer::SliceWriter;
+/// use ebml_iterable::error::TagWriterError;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut buffer = [0u8; 6];
+/// let mut writer = SliceWriter::new(&mut buffer);
+/// writer.write(&EmptySpec::with_data(0x4286, &[0x01])).unwrap();
+/// assert!(matches!(writer.write(&EmptySpec::with_data(0x4287, &[0x01, 0x02])), Err(TagWriterError::BufferOverflow { needed: 5, remaining: 2 })));
+/// assert_eq!(&[0x42, 0x86, 0x81, 0x01], writer.finish().unwrap());
+/// ```
+///
+pub struct SliceWriter<'a> {
+    dest: &'a mut [u8],
+    len: usize,
+    last_open: Option<usize>,
+    depth: usize,
+    max_depth: usize,
+}
+
+///
+/// An open "Master" tag, as recorded in the space reserved for its size.
+///
+struct OpenTag {
+    id: u64,
+    unknown_size: bool,
+    parent: Option<usize>,
+}
+
+impl<'a> SliceWriter<'a> {
+
+    ///
+    /// Returns a new [`SliceWriter`] that writes tags to the start of `dest`.
+    ///
+    pub fn new(dest: &'a mut [u8]) -> Self {
+        SliceWriter {
+            dest,
+            len: 0,
+            last_open: None,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    ///
+    /// Configures how deeply "Master" tags may be nested.
+    ///
+    /// The default is [`DEFAULT_MAX_DEPTH`](crate::writer::DEFAULT_MAX_DEPTH).  Writing a "Master" tag that would be nested deeper returns a [`TagWriterError::MaxDepthExceeded`] error.  [`Master::Full`] tags are written recursively, so this also limits how much of the call stack writing them can use.
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    ///
+    /// Returns the number of bytes written to the destination so far, including the space reserved for the sizes of open tags.
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    ///
+    /// Returns `true` if nothing has been written yet.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///
+    /// Returns the number of unused bytes left in the destination.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.dest.len() - self.len
+    }
+
+    ///
+    /// Ends any open tags and returns the part of the destination that was written.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if an open tag's size can't be written, which can only happen for tags larger than the biggest size a vint can hold.
+    ///
+    pub fn finish(mut self) -> Result<&'a mut [u8], TagWriterError> {
+        while let Some(size_start) = self.last_open {
+            let id = self.open_tag(size_start).id;
+            self.end_tag(id)?;
+        }
+        let SliceWriter { dest, len, .. } = self;
+        Ok(&mut dest[..len])
+    }
+
+    ///
+    /// Write a tag to the destination.
+    ///
+    /// This behaves like [`TagWriter::write()`](crate::TagWriter::write): tags are validated against `<TSpec>`'s hierarchy and [`Master::Start`] tags must be closed by a matching [`Master::End`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`TagWriterError::BufferOverflow`] error if the tag doesn't fit in the rest of the destination.  The other possible error states are enumerated in [`TagWriterError`].  The destination is left as it was before the call if an error occurs.
+    ///
+    /// ## Panics
+    ///
+    /// This method can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag variant is a specific data type but it is not).  This won't happen if the specification being used was created using the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro.
+    ///
+    pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        let saved = (self.len, self.last_open, self.depth);
+        let result = self.write_tag(tag);
+        if result.is_err() {
+            (self.len, self.last_open, self.depth) = saved;
+        }
+        result
+    }
+
+    ///
+    /// Write a tag with an unknown size to the destination.  This method can *only* be used on Master type tags.  See [`TagWriter::write_unknown_size()`](crate::TagWriter::write_unknown_size).
+    ///
+    /// ## Errors
+    ///
+    /// This method will return an error if the input tag is not a Master type tag or if it doesn't fit in the rest of the destination.
+    ///
+    pub fn write_unknown_size<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        match tag_type {
+            Some(TagDataType::Master) => self.start_tag(tag_id, true),
+            _ => Err(TagWriterError::TagSizeError(format!("Cannot write an unknown size for tag of type {tag_type:?}"))),
+        }
+    }
+
+    ///
+    /// Write raw tag data to the destination.  See [`TagWriter::write_raw()`](crate::TagWriter::write_raw).
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if `tag_id` is not a valid vint or if the tag doesn't fit in the rest of the destination.
+    ///
+    pub fn write_raw(&mut self, tag_id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        if !is_vint(tag_id) {
+            return Err(TagWriterError::TagIdError(tag_id));
+        }
+        self.write_data_tag(tag_id, data)
+    }
+
+    fn write_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        if let Some(val) = tag.as_utf8() {
+            check_ascii_string(TSpec::is_ascii_string, tag_id, val)?;
+        }
+
+        let should_validate = tag_type.is_some() && (!matches!(tag_type, Some(TagDataType::Master)) || !matches!(tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)), Master::End));
+        if should_validate && !validate_tag_path::<TSpec>(tag_id, self.open_path()) {
+            return Err(TagWriterError::UnexpectedTag { tag_id, current_path: self.open_path().map(|t| t.0).collect() });
+        }
+
+        match tag_type {
+            Some(TagDataType::UnsignedInt) => {
+                let val = *tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id));
+                self.write_data_tag(tag_id, &val.to_be_bytes()[(8 - unsigned_int_width(val))..])
+            },
+            Some(TagDataType::Integer) => {
+                let val = *tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id));
+                self.write_data_tag(tag_id, &val.to_be_bytes()[(8 - signed_int_width(val))..])
+            },
+            Some(TagDataType::Utf8) => {
+                let val = tag.as_utf8().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id));
+                self.write_data_tag(tag_id, val.as_bytes())
+            },
+            Some(TagDataType::Binary) => {
+                let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", tag_id));
+                self.write_data_tag(tag_id, val)
+            },
+            Some(TagDataType::Float) => {
+                let val = tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id));
+                self.write_data_tag(tag_id, &val.to_be_bytes())
+            },
+            Some(TagDataType::Date) => {
+                let val = tag.as_date().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was date, but could not get tag!", tag_id));
+                self.write_data_tag(tag_id, &val.to_be_bytes())
+            },
+            Some(TagDataType::Master) => {
+                let position = tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
+
+                match position {
+                    Master::Start => self.start_tag(tag_id, false),
+                    Master::End => self.end_tag(tag_id),
+                    Master::Full(children) => {
+                        self.start_tag(tag_id, false)?;
+                        children.iter().try_for_each(|child| self.write_tag(child))?;
+                        self.end_tag(tag_id)
+                    },
+                }
+            },
+            None => { // Should be a "raw tag"
+                let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was raw tag, but could not get binary data!", tag_id));
+                self.write_raw(tag_id, val)
+            }
+        }
+    }
+
+    ///
+    /// Returns the next `needed` bytes of the destination, or an error if there aren't enough left.
+    ///
+    fn claim(&mut self, needed: usize) -> Result<&mut [u8], TagWriterError> {
+        let remaining = self.remaining();
+        if needed > remaining {
+            return Err(TagWriterError::BufferOverflow { needed, remaining });
+        }
+        let start = self.len;
+        self.len += needed;
+        Ok(&mut self.dest[start..self.len])
+    }
+
+    fn write_data_tag(&mut self, id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        let id_len = id_len(id);
+        let size: u64 = data.len().try_into().expect("couldn't convert usize to u64");
+        let size_len = size_len(size, SizeWidth::Minimal);
+        if size_len > 8 {
+            return Err(TagWriterError::TagSizeError(format!("Size {size} is too large to be written as a vint")));
+        }
+
+        let dest = self.claim(id_len + size_len + data.len())?;
+        dest[..id_len].copy_from_slice(&id.to_be_bytes()[(8 - id_len)..]);
+        dest[id_len..(id_len + size_len)].copy_from_slice(&vint_bytes_u64(size, size_len)[(8 - size_len)..]);
+        dest[(id_len + size_len)..].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn start_tag(&mut self, id: u64, unknown_size: bool) -> Result<(), TagWriterError> {
+        if self.depth >= self.max_depth {
+            return Err(TagWriterError::MaxDepthExceeded { tag_id: id, max_depth: self.max_depth });
+        }
+
+        let id_len = id_len(id);
+        let size_start = self.len + id_len;
+        // Until the tag ends, the space reserved for its size records the id length, whether the size is unknown, and where the parent's size starts
+        let parent = self.last_open.map_or(0, |parent| parent as u64 + 1);
+        let dest = self.claim(id_len + RESERVED_SIZE_LEN)?;
+        dest[..id_len].copy_from_slice(&id.to_be_bytes()[(8 - id_len)..]);
+        dest[id_len..].copy_from_slice(&parent.to_be_bytes());
+        dest[id_len] = ((id_len as u8) << 1) | unknown_size as u8;
+
+        self.last_open = Some(size_start);
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn end_tag(&mut self, id: u64) -> Result<(), TagWriterError> {
+        let size_start = self.last_open.ok_or(TagWriterError::UnexpectedClosingTag { tag_id: id, expected_id: None })?;
+        let open_tag = self.open_tag(size_start);
+        if open_tag.id != id {
+            return Err(TagWriterError::UnexpectedClosingTag { tag_id: id, expected_id: Some(open_tag.id) });
+        }
+
+        let body_start = size_start + RESERVED_SIZE_LEN;
+        if open_tag.unknown_size {
+            self.dest[size_start..body_start].copy_from_slice(&(u64::MAX >> 7).to_be_bytes());
+        } else {
+            let size = (self.len - body_start) as u64;
+            let size_len = size_len(size, SizeWidth::Minimal);
+            if size_len > RESERVED_SIZE_LEN {
+                return Err(TagWriterError::TagSizeError(format!("Size {size} is too large to be written as a vint")));
+            }
+            self.dest[size_start..(size_start + size_len)].copy_from_slice(&vint_bytes_u64(size, size_len)[(8 - size_len)..]);
+            self.dest.copy_within(body_start..self.len, size_start + size_len);
+            self.len -= RESERVED_SIZE_LEN - size_len;
+        }
+
+        self.last_open = open_tag.parent;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn open_tag(&self, size_start: usize) -> OpenTag {
+        let flags = self.dest[size_start];
+        let id_len = (flags >> 1) as usize;
+        let mut id = [0u8; 8];
+        id[(8 - id_len)..].copy_from_slice(&self.dest[(size_start - id_len)..size_start]);
+        let mut parent = [0u8; 8];
+        parent[1..].copy_from_slice(&self.dest[(size_start + 1)..(size_start + RESERVED_SIZE_LEN)]);
+        OpenTag {
+            id: u64::from_be_bytes(id),
+            unknown_size: flags & 1 == 1,
+            parent: u64::from_be_bytes(parent).checked_sub(1).map(|parent| parent as usize),
+        }
+    }
+
+    ///
+    /// Iterates over the open tags, outermost first.  Open tags only link to their parent, so this walks the chain once per tag - documents are never nested deeply enough for that to matter.
+    ///
+    fn open_path(&self) -> impl Iterator<Item = (u64, EBMLSize)> + '_ {
+        (0..self.depth).rev().map(move |steps| {
+            let mut position = self.last_open.expect("open tags should be linked to their parents");
+            for _ in 0..steps {
+                position = self.open_tag(position).parent.expect("open tags should be linked to their parents");
+            }
+            let open_tag = self.open_tag(position);
+            (open_tag.id, if open_tag.unknown_size { Unknown } else { Known(0) })
+        })
+    }
+}
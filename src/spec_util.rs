@@ -1,6 +1,11 @@
-use ebml_iterable_specification::{EbmlSpecification, EbmlTag, PathPart};
+#[cfg(feature = "std")]
+use alloc::string::String;
+use ebml_iterable_specification::{EbmlSpecification, EbmlTag, PathPart, TagDataType};
 
+use crate::errors::tag_writer::TagWriterError;
+use crate::errors::tool::ToolError;
 use crate::tag_iterator_util::EBMLSize;
+use crate::tools;
 
 ///
 /// Returns whether or not the a `test_id` is a parent of `current_id`.
@@ -38,9 +43,47 @@ pub fn is_ended_by<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(current_id: u64
     )
 }
 
+///
+/// Reads the data of a utf8 tag, which must also be printable ASCII if the specification declares it as an EBML `string` element (see [`EbmlSpecification::is_ascii_string`]).
+///
+pub fn read_string<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, data: &[u8]) -> Result<&str, ToolError> {
+    if T::is_ascii_string(tag_id) && !tools::is_ascii_string(data) {
+        return Err(ToolError::InvalidAscii(data.to_vec()));
+    }
+    core::str::from_utf8(data).map_err(|_| tools::arr_to_utf8(data).expect_err("data was not valid utf8"))
+}
+
+///
+/// Same as [`read_string`], but replaces anything that isn't allowed in the tag with U+FFFD instead of failing.
+///
+#[cfg(feature = "std")]
+pub fn read_string_lossy<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, data: &[u8]) -> String {
+    if T::is_ascii_string(tag_id) {
+        data.iter().map(|b| if *b == 0x00 || (0x20..=0x7e).contains(b) { char::from(*b) } else { char::REPLACEMENT_CHARACTER }).collect()
+    } else {
+        String::from_utf8_lossy(data).into_owned()
+    }
+}
+
+///
+/// Checks that `value` may be written to `tag_id`, given a specification's [`EbmlSpecification::is_ascii_string`] function.
+///
+pub fn check_ascii_string(is_ascii_string: fn(u64) -> bool, tag_id: u64, value: &str) -> Result<(), TagWriterError> {
+    if is_ascii_string(tag_id) && !tools::is_ascii_string(value.as_bytes()) {
+        return Err(TagWriterError::InvalidAsciiString { tag_id });
+    }
+    Ok(())
+}
+
 #[inline(always)]
 pub fn validate_tag_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, doc_path: impl Iterator<Item = (u64, EBMLSize)>) -> bool {
-    let path = <T>::get_path_by_id(tag_id);
+    validate_path::<T>(tag_id, <T>::get_path_by_id(tag_id), doc_path)
+}
+
+///
+/// Same as [`validate_tag_path`], but uses an already looked up `path` for `tag_id`.
+///
+pub fn validate_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, path: &[PathPart], doc_path: impl Iterator<Item = (u64, EBMLSize)>) -> bool {
     let mut path_marker = 0;
     let mut global_counter = 0;
     for item in doc_path {
@@ -82,4 +125,65 @@ pub fn validate_tag_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u
     // or that the last parent was a global whose minimum was met
         ((path.len() - 1) == path_marker && matches!(path[path_marker], PathPart::Global((min, _)) if global_counter >= min.unwrap_or(0)))
     
-}
\ No newline at end of file
+}
+
+const SPEC_CACHE_LEN: usize = 4;
+
+///
+/// The results of looking up a single tag id in a specification.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct SpecEntry {
+    pub id: u64,
+    pub data_type: Option<TagDataType>,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub path: &'static [PathPart],
+}
+
+///
+/// Remembers the last few tag ids looked up in a specification.
+///
+/// Clusters are made of long runs of the same handful of ids (`SimpleBlock`, `BlockGroup`, `Block`...), so even a tiny cache skips the specification dispatch for nearly every tag read.  This matters most for specifications that can't be resolved by a simple `match` (e.g. ones loaded at runtime).  Entries are replaced in round-robin order.
+///
+#[derive(Clone, Debug, Default)]
+pub struct SpecCache {
+    entries: [Option<SpecEntry>; SPEC_CACHE_LEN],
+    next: usize,
+}
+
+impl SpecCache {
+    #[inline]
+    pub fn lookup<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(&mut self, id: u64) -> SpecEntry {
+        if let Some(entry) = self.entries.iter().flatten().find(|entry| entry.id == id) {
+            return *entry;
+        }
+
+        let entry = SpecEntry { id, data_type: <T>::get_tag_data_type(id), path: <T>::get_path_by_id(id) };
+        self.entries[self.next] = Some(entry);
+        self.next = (self.next + 1) % SPEC_CACHE_LEN;
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+    use ebml_iterable_specification::TagDataType;
+
+    use super::{SpecCache, SPEC_CACHE_LEN};
+
+    #[test]
+    fn spec_cache_replaces_oldest_entry() {
+        let mut cache = SpecCache::default();
+        for id in 0..(SPEC_CACHE_LEN as u64 + 1) {
+            let entry = cache.lookup::<EmptySpec>(0x80 + id);
+            assert_eq!(0x80 + id, entry.id);
+            assert_eq!(Some(TagDataType::Binary), entry.data_type);
+        }
+
+        let cached: Vec<u64> = cache.entries.iter().flatten().map(|entry| entry.id).collect();
+        assert_eq!(SPEC_CACHE_LEN, cached.len());
+        assert!(!cached.contains(&0x80), "The first id looked up should have been replaced");
+        assert!(cached.contains(&(0x80 + SPEC_CACHE_LEN as u64)));
+    }
+}
@@ -0,0 +1,315 @@
+//!
+//! A ready-made specification for [Matroska][mkv] and [WebM][webm] documents.
+//!
+//! [`MatroskaSpec`] covers the EBML header and every element of the Matroska specification (including deprecated elements, so older files can still be read), using the same names as [`ids`](crate::ids).  It is generated with [`easy_ebml!`](super::easy_ebml), so it behaves exactly like a specification written by hand with the macro.
+//!
+//! [mkv]: https://www.matroska.org/technical/elements.html
+//! [webm]: https://www.webmproject.org/docs/container/
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::Cursor;
+//! use ebml_iterable::{TagIterator, TagWriter};
+//! use ebml_iterable::specs::Master;
+//! use ebml_iterable::specs::matroska::MatroskaSpec;
+//!
+//! let mut writer = TagWriter::new(Vec::new());
+//! writer.write(&MatroskaSpec::Segment(Master::Full(vec![
+//!     MatroskaSpec::Info(Master::Full(vec![MatroskaSpec::TimestampScale(1_000_000), MatroskaSpec::Title(String::from("Example"))])),
+//! ]))).unwrap();
+//! let data = writer.into_inner().unwrap();
+//!
+//! let tags: Vec<MatroskaSpec> = TagIterator::new(Cursor::new(data), &[MatroskaSpec::Info(Master::Start)]).map(|tag| tag.unwrap()).collect();
+//! assert_eq!(MatroskaSpec::Info(Master::Full(vec![MatroskaSpec::TimestampScale(1_000_000), MatroskaSpec::Title(String::from("Example"))])), tags[1]);
+//! ```
+//!
+
+use super::{easy_ebml, TagDataType};
+
+easy_ebml! {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum MatroskaSpec {
+        Ebml: Master = 0x1A45DFA3,
+        Ebml/EbmlVersion: UnsignedInt = 0x4286,
+        Ebml/EbmlReadVersion: UnsignedInt = 0x42F7,
+        Ebml/EbmlMaxIdLength: UnsignedInt = 0x42F2,
+        Ebml/EbmlMaxSizeLength: UnsignedInt = 0x42F3,
+        Ebml/DocType: Ascii = 0x4282,
+        Ebml/DocTypeVersion: UnsignedInt = 0x4287,
+        Ebml/DocTypeReadVersion: UnsignedInt = 0x4285,
+        Ebml/DocTypeExtension: Master = 0x4281,
+        Ebml/DocTypeExtension/DocTypeExtensionName: Ascii = 0x4283,
+        Ebml/DocTypeExtension/DocTypeExtensionVersion: UnsignedInt = 0x4284,
+
+        Segment: Master = 0x18538067,
+
+        Segment/SeekHead: Master = 0x114D9B74,
+        Segment/SeekHead/Seek: Master = 0x4DBB,
+        Segment/SeekHead/Seek/SeekId: Binary = 0x53AB,
+        Segment/SeekHead/Seek/SeekPosition: UnsignedInt = 0x53AC,
+
+        Segment/Info: Master = 0x1549A966,
+        Segment/Info/SegmentUuid: Binary = 0x73A4,
+        Segment/Info/SegmentFilename: Utf8 = 0x7384,
+        Segment/Info/PrevUuid: Binary = 0x3CB923,
+        Segment/Info/PrevFilename: Utf8 = 0x3C83AB,
+        Segment/Info/NextUuid: Binary = 0x3EB923,
+        Segment/Info/NextFilename: Utf8 = 0x3E83BB,
+        Segment/Info/SegmentFamily: Binary = 0x4444,
+        Segment/Info/ChapterTranslate: Master = 0x6924,
+        Segment/Info/ChapterTranslate/ChapterTranslateId: Binary = 0x69A5,
+        Segment/Info/ChapterTranslate/ChapterTranslateCodec: UnsignedInt = 0x69BF,
+        Segment/Info/ChapterTranslate/ChapterTranslateEditionUid: UnsignedInt = 0x69FC,
+        Segment/Info/TimestampScale: UnsignedInt = 0x2AD7B1,
+        Segment/Info/Duration: Float = 0x4489,
+        Segment/Info/DateUtc: Date = 0x4461,
+        Segment/Info/Title: Utf8 = 0x7BA9,
+        Segment/Info/MuxingApp: Utf8 = 0x4D80,
+        Segment/Info/WritingApp: Utf8 = 0x5741,
+
+        Segment/Cluster: Master = 0x1F43B675,
+        Segment/Cluster/Timestamp: UnsignedInt = 0xE7,
+        Segment/Cluster/SilentTracks: Master = 0x5854,
+        Segment/Cluster/SilentTracks/SilentTrackNumber: UnsignedInt = 0x58D7,
+        Segment/Cluster/Position: UnsignedInt = 0xA7,
+        Segment/Cluster/PrevSize: UnsignedInt = 0xAB,
+        Segment/Cluster/SimpleBlock: Binary = 0xA3,
+        Segment/Cluster/BlockGroup: Master = 0xA0,
+        Segment/Cluster/BlockGroup/Block: Binary = 0xA1,
+        Segment/Cluster/BlockGroup/BlockVirtual: Binary = 0xA2,
+        Segment/Cluster/BlockGroup/BlockAdditions: Master = 0x75A1,
+        Segment/Cluster/BlockGroup/BlockAdditions/BlockMore: Master = 0xA6,
+        Segment/Cluster/BlockGroup/BlockAdditions/BlockMore/BlockAdditional: Binary = 0xA5,
+        Segment/Cluster/BlockGroup/BlockAdditions/BlockMore/BlockAddId: UnsignedInt = 0xEE,
+        Segment/Cluster/BlockGroup/BlockDuration: UnsignedInt = 0x9B,
+        Segment/Cluster/BlockGroup/ReferencePriority: UnsignedInt = 0xFA,
+        Segment/Cluster/BlockGroup/ReferenceBlock: Integer = 0xFB,
+        Segment/Cluster/BlockGroup/ReferenceVirtual: Integer = 0xFD,
+        Segment/Cluster/BlockGroup/CodecState: Binary = 0xA4,
+        Segment/Cluster/BlockGroup/DiscardPadding: Integer = 0x75A2,
+        Segment/Cluster/BlockGroup/Slices: Master = 0x8E,
+        Segment/Cluster/BlockGroup/Slices/TimeSlice: Master = 0xE8,
+        Segment/Cluster/BlockGroup/Slices/TimeSlice/LaceNumber: UnsignedInt = 0xCC,
+        Segment/Cluster/BlockGroup/Slices/TimeSlice/FrameNumber: UnsignedInt = 0xCD,
+        Segment/Cluster/BlockGroup/Slices/TimeSlice/BlockAdditionId: UnsignedInt = 0xCB,
+        Segment/Cluster/BlockGroup/Slices/TimeSlice/Delay: UnsignedInt = 0xCE,
+        Segment/Cluster/BlockGroup/Slices/TimeSlice/SliceDuration: UnsignedInt = 0xCF,
+        Segment/Cluster/BlockGroup/ReferenceFrame: Master = 0xC8,
+        Segment/Cluster/BlockGroup/ReferenceFrame/ReferenceOffset: UnsignedInt = 0xC9,
+        Segment/Cluster/BlockGroup/ReferenceFrame/ReferenceTimestamp: UnsignedInt = 0xCA,
+        Segment/Cluster/EncryptedBlock: Binary = 0xAF,
+
+        Segment/Tracks: Master = 0x1654AE6B,
+        Segment/Tracks/TrackEntry: Master = 0xAE,
+        Segment/Tracks/TrackEntry/TrackNumber: UnsignedInt = 0xD7,
+        Segment/Tracks/TrackEntry/TrackUid: UnsignedInt = 0x73C5,
+        Segment/Tracks/TrackEntry/TrackType: UnsignedInt = 0x83,
+        Segment/Tracks/TrackEntry/FlagEnabled: UnsignedInt = 0xB9,
+        Segment/Tracks/TrackEntry/FlagDefault: UnsignedInt = 0x88,
+        Segment/Tracks/TrackEntry/FlagForced: UnsignedInt = 0x55AA,
+        Segment/Tracks/TrackEntry/FlagHearingImpaired: UnsignedInt = 0x55AB,
+        Segment/Tracks/TrackEntry/FlagVisualImpaired: UnsignedInt = 0x55AC,
+        Segment/Tracks/TrackEntry/FlagTextDescriptions: UnsignedInt = 0x55AD,
+        Segment/Tracks/TrackEntry/FlagOriginal: UnsignedInt = 0x55AE,
+        Segment/Tracks/TrackEntry/FlagCommentary: UnsignedInt = 0x55AF,
+        Segment/Tracks/TrackEntry/FlagLacing: UnsignedInt = 0x9C,
+        Segment/Tracks/TrackEntry/MinCache: UnsignedInt = 0x6DE7,
+        Segment/Tracks/TrackEntry/MaxCache: UnsignedInt = 0x6DF8,
+        Segment/Tracks/TrackEntry/DefaultDuration: UnsignedInt = 0x23E383,
+        Segment/Tracks/TrackEntry/DefaultDecodedFieldDuration: UnsignedInt = 0x234E7A,
+        Segment/Tracks/TrackEntry/TrackTimestampScale: Float = 0x23314F,
+        Segment/Tracks/TrackEntry/TrackOffset: Integer = 0x537F,
+        Segment/Tracks/TrackEntry/MaxBlockAdditionId: UnsignedInt = 0x55EE,
+        Segment/Tracks/TrackEntry/BlockAdditionMapping: Master = 0x41E4,
+        Segment/Tracks/TrackEntry/BlockAdditionMapping/BlockAddIdValue: UnsignedInt = 0x41F0,
+        Segment/Tracks/TrackEntry/BlockAdditionMapping/BlockAddIdName: Ascii = 0x41A4,
+        Segment/Tracks/TrackEntry/BlockAdditionMapping/BlockAddIdType: UnsignedInt = 0x41E7,
+        Segment/Tracks/TrackEntry/BlockAdditionMapping/BlockAddIdExtraData: Binary = 0x41ED,
+        Segment/Tracks/TrackEntry/Name: Utf8 = 0x536E,
+        Segment/Tracks/TrackEntry/Language: Ascii = 0x22B59C,
+        Segment/Tracks/TrackEntry/LanguageBcp47: Ascii = 0x22B59D,
+        Segment/Tracks/TrackEntry/CodecId: Ascii = 0x86,
+        Segment/Tracks/TrackEntry/CodecPrivate: Binary = 0x63A2,
+        Segment/Tracks/TrackEntry/CodecName: Utf8 = 0x258688,
+        Segment/Tracks/TrackEntry/AttachmentLink: UnsignedInt = 0x7446,
+        Segment/Tracks/TrackEntry/CodecSettings: Utf8 = 0x3A9697,
+        Segment/Tracks/TrackEntry/CodecInfoUrl: Ascii = 0x3B4040,
+        Segment/Tracks/TrackEntry/CodecDownloadUrl: Ascii = 0x26B240,
+        Segment/Tracks/TrackEntry/CodecDecodeAll: UnsignedInt = 0xAA,
+        Segment/Tracks/TrackEntry/TrackOverlay: UnsignedInt = 0x6FAB,
+        Segment/Tracks/TrackEntry/CodecDelay: UnsignedInt = 0x56AA,
+        Segment/Tracks/TrackEntry/SeekPreRoll: UnsignedInt = 0x56BB,
+        Segment/Tracks/TrackEntry/TrackTranslate: Master = 0x6624,
+        Segment/Tracks/TrackEntry/TrackTranslate/TrackTranslateTrackId: Binary = 0x66A5,
+        Segment/Tracks/TrackEntry/TrackTranslate/TrackTranslateCodec: UnsignedInt = 0x66BF,
+        Segment/Tracks/TrackEntry/TrackTranslate/TrackTranslateEditionUid: UnsignedInt = 0x66FC,
+        Segment/Tracks/TrackEntry/Video: Master = 0xE0,
+        Segment/Tracks/TrackEntry/Video/FlagInterlaced: UnsignedInt = 0x9A,
+        Segment/Tracks/TrackEntry/Video/FieldOrder: UnsignedInt = 0x9D,
+        Segment/Tracks/TrackEntry/Video/StereoMode: UnsignedInt = 0x53B8,
+        Segment/Tracks/TrackEntry/Video/AlphaMode: UnsignedInt = 0x53C0,
+        Segment/Tracks/TrackEntry/Video/OldStereoMode: UnsignedInt = 0x53B9,
+        Segment/Tracks/TrackEntry/Video/PixelWidth: UnsignedInt = 0xB0,
+        Segment/Tracks/TrackEntry/Video/PixelHeight: UnsignedInt = 0xBA,
+        Segment/Tracks/TrackEntry/Video/PixelCropBottom: UnsignedInt = 0x54AA,
+        Segment/Tracks/TrackEntry/Video/PixelCropTop: UnsignedInt = 0x54BB,
+        Segment/Tracks/TrackEntry/Video/PixelCropLeft: UnsignedInt = 0x54CC,
+        Segment/Tracks/TrackEntry/Video/PixelCropRight: UnsignedInt = 0x54DD,
+        Segment/Tracks/TrackEntry/Video/DisplayWidth: UnsignedInt = 0x54B0,
+        Segment/Tracks/TrackEntry/Video/DisplayHeight: UnsignedInt = 0x54BA,
+        Segment/Tracks/TrackEntry/Video/DisplayUnit: UnsignedInt = 0x54B2,
+        Segment/Tracks/TrackEntry/Video/AspectRatioType: UnsignedInt = 0x54B3,
+        Segment/Tracks/TrackEntry/Video/UncompressedFourcc: Binary = 0x2EB524,
+        Segment/Tracks/TrackEntry/Video/GammaValue: Float = 0x2FB523,
+        Segment/Tracks/TrackEntry/Video/FrameRate: Float = 0x2383E3,
+        Segment/Tracks/TrackEntry/Video/Colour: Master = 0x55B0,
+        Segment/Tracks/TrackEntry/Video/Colour/MatrixCoefficients: UnsignedInt = 0x55B1,
+        Segment/Tracks/TrackEntry/Video/Colour/BitsPerChannel: UnsignedInt = 0x55B2,
+        Segment/Tracks/TrackEntry/Video/Colour/ChromaSubsamplingHorz: UnsignedInt = 0x55B3,
+        Segment/Tracks/TrackEntry/Video/Colour/ChromaSubsamplingVert: UnsignedInt = 0x55B4,
+        Segment/Tracks/TrackEntry/Video/Colour/CbSubsamplingHorz: UnsignedInt = 0x55B5,
+        Segment/Tracks/TrackEntry/Video/Colour/CbSubsamplingVert: UnsignedInt = 0x55B6,
+        Segment/Tracks/TrackEntry/Video/Colour/ChromaSitingHorz: UnsignedInt = 0x55B7,
+        Segment/Tracks/TrackEntry/Video/Colour/ChromaSitingVert: UnsignedInt = 0x55B8,
+        Segment/Tracks/TrackEntry/Video/Colour/Range: UnsignedInt = 0x55B9,
+        Segment/Tracks/TrackEntry/Video/Colour/TransferCharacteristics: UnsignedInt = 0x55BA,
+        Segment/Tracks/TrackEntry/Video/Colour/Primaries: UnsignedInt = 0x55BB,
+        Segment/Tracks/TrackEntry/Video/Colour/MaxCll: UnsignedInt = 0x55BC,
+        Segment/Tracks/TrackEntry/Video/Colour/MaxFall: UnsignedInt = 0x55BD,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata: Master = 0x55D0,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/PrimaryRChromaticityX: Float = 0x55D1,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/PrimaryRChromaticityY: Float = 0x55D2,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/PrimaryGChromaticityX: Float = 0x55D3,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/PrimaryGChromaticityY: Float = 0x55D4,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/PrimaryBChromaticityX: Float = 0x55D5,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/PrimaryBChromaticityY: Float = 0x55D6,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/WhitePointChromaticityX: Float = 0x55D7,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/WhitePointChromaticityY: Float = 0x55D8,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/LuminanceMax: Float = 0x55D9,
+        Segment/Tracks/TrackEntry/Video/Colour/MasteringMetadata/LuminanceMin: Float = 0x55DA,
+        Segment/Tracks/TrackEntry/Video/Projection: Master = 0x7670,
+        Segment/Tracks/TrackEntry/Video/Projection/ProjectionType: UnsignedInt = 0x7671,
+        Segment/Tracks/TrackEntry/Video/Projection/ProjectionPrivate: Binary = 0x7672,
+        Segment/Tracks/TrackEntry/Video/Projection/ProjectionPoseYaw: Float = 0x7673,
+        Segment/Tracks/TrackEntry/Video/Projection/ProjectionPosePitch: Float = 0x7674,
+        Segment/Tracks/TrackEntry/Video/Projection/ProjectionPoseRoll: Float = 0x7675,
+        Segment/Tracks/TrackEntry/Audio: Master = 0xE1,
+        Segment/Tracks/TrackEntry/Audio/SamplingFrequency: Float = 0xB5,
+        Segment/Tracks/TrackEntry/Audio/OutputSamplingFrequency: Float = 0x78B5,
+        Segment/Tracks/TrackEntry/Audio/Channels: UnsignedInt = 0x9F,
+        Segment/Tracks/TrackEntry/Audio/ChannelPositions: Binary = 0x7D7B,
+        Segment/Tracks/TrackEntry/Audio/BitDepth: UnsignedInt = 0x6264,
+        Segment/Tracks/TrackEntry/Audio/Emphasis: UnsignedInt = 0x52F1,
+        Segment/Tracks/TrackEntry/TrackOperation: Master = 0xE2,
+        Segment/Tracks/TrackEntry/TrackOperation/TrackCombinePlanes: Master = 0xE3,
+        Segment/Tracks/TrackEntry/TrackOperation/TrackCombinePlanes/TrackPlane: Master = 0xE4,
+        Segment/Tracks/TrackEntry/TrackOperation/TrackCombinePlanes/TrackPlane/TrackPlaneUid: UnsignedInt = 0xE5,
+        Segment/Tracks/TrackEntry/TrackOperation/TrackCombinePlanes/TrackPlane/TrackPlaneType: UnsignedInt = 0xE6,
+        Segment/Tracks/TrackEntry/TrackOperation/TrackJoinBlocks: Master = 0xE9,
+        Segment/Tracks/TrackEntry/TrackOperation/TrackJoinBlocks/TrackJoinUid: UnsignedInt = 0xED,
+        Segment/Tracks/TrackEntry/TrickTrackUid: UnsignedInt = 0xC0,
+        Segment/Tracks/TrackEntry/TrickTrackSegmentUid: Binary = 0xC1,
+        Segment/Tracks/TrackEntry/TrickTrackFlag: UnsignedInt = 0xC6,
+        Segment/Tracks/TrackEntry/TrickMasterTrackUid: UnsignedInt = 0xC7,
+        Segment/Tracks/TrackEntry/TrickMasterTrackSegmentUid: Binary = 0xC4,
+        Segment/Tracks/TrackEntry/ContentEncodings: Master = 0x6D80,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding: Master = 0x6240,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncodingOrder: UnsignedInt = 0x5031,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncodingScope: UnsignedInt = 0x5032,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncodingType: UnsignedInt = 0x5033,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentCompression: Master = 0x5034,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentCompression/ContentCompAlgo: UnsignedInt = 0x4254,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentCompression/ContentCompSettings: Binary = 0x4255,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption: Master = 0x5035,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentEncAlgo: UnsignedInt = 0x47E1,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentEncKeyId: Binary = 0x47E2,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentEncAesSettings: Master = 0x47E7,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentEncAesSettings/AesSettingsCipherMode: UnsignedInt = 0x47E8,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentSignature: Binary = 0x47E3,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentSigKeyId: Binary = 0x47E4,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentSigAlgo: UnsignedInt = 0x47E5,
+        Segment/Tracks/TrackEntry/ContentEncodings/ContentEncoding/ContentEncryption/ContentSigHashAlgo: UnsignedInt = 0x47E6,
+
+        Segment/Cues: Master = 0x1C53BB6B,
+        Segment/Cues/CuePoint: Master = 0xBB,
+        Segment/Cues/CuePoint/CueTime: UnsignedInt = 0xB3,
+        Segment/Cues/CuePoint/CueTrackPositions: Master = 0xB7,
+        Segment/Cues/CuePoint/CueTrackPositions/CueTrack: UnsignedInt = 0xF7,
+        Segment/Cues/CuePoint/CueTrackPositions/CueClusterPosition: UnsignedInt = 0xF1,
+        Segment/Cues/CuePoint/CueTrackPositions/CueRelativePosition: UnsignedInt = 0xF0,
+        Segment/Cues/CuePoint/CueTrackPositions/CueDuration: UnsignedInt = 0xB2,
+        Segment/Cues/CuePoint/CueTrackPositions/CueBlockNumber: UnsignedInt = 0x5378,
+        Segment/Cues/CuePoint/CueTrackPositions/CueCodecState: UnsignedInt = 0xEA,
+        Segment/Cues/CuePoint/CueTrackPositions/CueReference: Master = 0xDB,
+        Segment/Cues/CuePoint/CueTrackPositions/CueReference/CueRefTime: UnsignedInt = 0x96,
+        Segment/Cues/CuePoint/CueTrackPositions/CueReference/CueRefCluster: UnsignedInt = 0x97,
+        Segment/Cues/CuePoint/CueTrackPositions/CueReference/CueRefNumber: UnsignedInt = 0x535F,
+        Segment/Cues/CuePoint/CueTrackPositions/CueReference/CueRefCodecState: UnsignedInt = 0xEB,
+
+        Segment/Attachments: Master = 0x1941A469,
+        Segment/Attachments/AttachedFile: Master = 0x61A7,
+        Segment/Attachments/AttachedFile/FileDescription: Utf8 = 0x467E,
+        Segment/Attachments/AttachedFile/FileName: Utf8 = 0x466E,
+        Segment/Attachments/AttachedFile/FileMediaType: Ascii = 0x4660,
+        Segment/Attachments/AttachedFile/FileData: Binary = 0x465C,
+        Segment/Attachments/AttachedFile/FileUid: UnsignedInt = 0x46AE,
+        Segment/Attachments/AttachedFile/FileReferral: Binary = 0x4675,
+        Segment/Attachments/AttachedFile/FileUsedStartTime: UnsignedInt = 0x4661,
+        Segment/Attachments/AttachedFile/FileUsedEndTime: UnsignedInt = 0x4662,
+
+        Segment/Chapters: Master = 0x1043A770,
+        Segment/Chapters/EditionEntry: Master = 0x45B9,
+        Segment/Chapters/EditionEntry/EditionUid: UnsignedInt = 0x45BC,
+        Segment/Chapters/EditionEntry/EditionFlagHidden: UnsignedInt = 0x45BD,
+        Segment/Chapters/EditionEntry/EditionFlagDefault: UnsignedInt = 0x45DB,
+        Segment/Chapters/EditionEntry/EditionFlagOrdered: UnsignedInt = 0x45DD,
+        Segment/Chapters/EditionEntry/EditionDisplay: Master = 0x4520,
+        Segment/Chapters/EditionEntry/EditionDisplay/EditionString: Utf8 = 0x4521,
+        Segment/Chapters/EditionEntry/EditionDisplay/EditionLanguageIetf: Ascii = 0x45E4,
+        // Chapter atoms can be nested, so their contents are allowed at any depth below the edition
+        Segment/Chapters/EditionEntry/(0-)/ChapterAtom: Master = 0xB6,
+        Segment/Chapters/EditionEntry/(1-)/ChapterUid: UnsignedInt = 0x73C4,
+        Segment/Chapters/EditionEntry/(1-)/ChapterStringUid: Utf8 = 0x5654,
+        Segment/Chapters/EditionEntry/(1-)/ChapterTimeStart: UnsignedInt = 0x91,
+        Segment/Chapters/EditionEntry/(1-)/ChapterTimeEnd: UnsignedInt = 0x92,
+        Segment/Chapters/EditionEntry/(1-)/ChapterFlagHidden: UnsignedInt = 0x98,
+        Segment/Chapters/EditionEntry/(1-)/ChapterFlagEnabled: UnsignedInt = 0x4598,
+        Segment/Chapters/EditionEntry/(1-)/ChapterSegmentUuid: Binary = 0x6E67,
+        Segment/Chapters/EditionEntry/(1-)/ChapterSkipType: UnsignedInt = 0x4588,
+        Segment/Chapters/EditionEntry/(1-)/ChapterSegmentEditionUid: UnsignedInt = 0x6EBC,
+        Segment/Chapters/EditionEntry/(1-)/ChapterPhysicalEquiv: UnsignedInt = 0x63C3,
+        Segment/Chapters/EditionEntry/(1-)/ChapterTrack: Master = 0x8F,
+        Segment/Chapters/EditionEntry/(1-)/ChapterTrack/ChapterTrackUid: UnsignedInt = 0x89,
+        Segment/Chapters/EditionEntry/(1-)/ChapterDisplay: Master = 0x80,
+        Segment/Chapters/EditionEntry/(1-)/ChapterDisplay/ChapString: Utf8 = 0x85,
+        Segment/Chapters/EditionEntry/(1-)/ChapterDisplay/ChapLanguage: Ascii = 0x437C,
+        Segment/Chapters/EditionEntry/(1-)/ChapterDisplay/ChapLanguageBcp47: Ascii = 0x437D,
+        Segment/Chapters/EditionEntry/(1-)/ChapterDisplay/ChapCountry: Ascii = 0x437E,
+        Segment/Chapters/EditionEntry/(1-)/ChapProcess: Master = 0x6944,
+        Segment/Chapters/EditionEntry/(1-)/ChapProcess/ChapProcessCodecId: UnsignedInt = 0x6955,
+        Segment/Chapters/EditionEntry/(1-)/ChapProcess/ChapProcessPrivate: Binary = 0x450D,
+        Segment/Chapters/EditionEntry/(1-)/ChapProcess/ChapProcessCommand: Master = 0x6911,
+        Segment/Chapters/EditionEntry/(1-)/ChapProcess/ChapProcessCommand/ChapProcessTime: UnsignedInt = 0x6922,
+        Segment/Chapters/EditionEntry/(1-)/ChapProcess/ChapProcessCommand/ChapProcessData: Binary = 0x6933,
+
+        Segment/Tags: Master = 0x1254C367,
+        Segment/Tags/Tag: Master = 0x7373,
+        Segment/Tags/Tag/Targets: Master = 0x63C0,
+        Segment/Tags/Tag/Targets/TargetTypeValue: UnsignedInt = 0x68CA,
+        Segment/Tags/Tag/Targets/TargetType: Ascii = 0x63CA,
+        Segment/Tags/Tag/Targets/TagTrackUid: UnsignedInt = 0x63C5,
+        Segment/Tags/Tag/Targets/TagEditionUid: UnsignedInt = 0x63C9,
+        Segment/Tags/Tag/Targets/TagChapterUid: UnsignedInt = 0x63C4,
+        Segment/Tags/Tag/Targets/TagAttachmentUid: UnsignedInt = 0x63C6,
+        // Simple tags can be nested, so their contents are allowed at any depth below the tag
+        Segment/Tags/Tag/(0-)/SimpleTag: Master = 0x67C8,
+        Segment/Tags/Tag/(1-)/TagName: Utf8 = 0x45A3,
+        Segment/Tags/Tag/(1-)/TagLanguage: Ascii = 0x447A,
+        Segment/Tags/Tag/(1-)/TagLanguageBcp47: Ascii = 0x447B,
+        Segment/Tags/Tag/(1-)/TagDefault: UnsignedInt = 0x4484,
+        Segment/Tags/Tag/(1-)/TagDefaultBogus: UnsignedInt = 0x44B4,
+        Segment/Tags/Tag/(1-)/TagString: Utf8 = 0x4487,
+        Segment/Tags/Tag/(1-)/TagBinary: Binary = 0x4485,
+    }
+}
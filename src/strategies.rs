@@ -0,0 +1,149 @@
+//!
+//! [`proptest`](https://docs.rs/proptest) strategies for property-testing code that handles EBML.
+//!
+//! These strategies generate inputs that this crate can write and read back without loss: valid nested tag trees for any specification, vint values around every length boundary, and element sizes on either side of those boundaries.
+//!
+//! This module is only available with the `"proptest"` feature enabled.
+//!
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use proptest::collection;
+use proptest::prelude::*;
+use proptest::strategy::Union;
+
+use crate::specs::{EbmlSpecification, EbmlTag, Master, PathPart, TagDataType};
+
+///
+/// The largest value that can be written as a vint (`2^56 - 1`).
+///
+pub const MAX_VINT_VALUE: u64 = (1 << 56) - 1;
+
+///
+/// Generates vint values, favoring the smallest and largest value of every vint length.
+///
+/// Every value produced is at most [`MAX_VINT_VALUE`].  The all-ones value of each length (which marks an unknown size when used as a tag size) is included.
+///
+pub fn vint_values() -> impl Strategy<Value = u64> {
+    let boundaries: Vec<u64> = (1..=8u32)
+        .flat_map(|length| {
+            let limit = 1u64 << (7 * length);
+            vec![limit / 128, limit - 2, limit - 1]
+        })
+        .chain([0, 1])
+        .collect();
+
+    prop_oneof![
+        3 => proptest::sample::select(boundaries),
+        1 => 0..=MAX_VINT_VALUE,
+    ]
+}
+
+///
+/// Generates element body sizes on either side of the 1, 2, and 3 byte vint size boundaries.
+///
+/// Sizes are kept small enough (at most `2^21`) to allocate freely in tests.
+///
+pub fn boundary_sizes() -> impl Strategy<Value = usize> {
+    proptest::sample::select(vec![0, 1, 126, 127, 128, 16_382, 16_383, 16_384, 2_097_150, 2_097_151, 2_097_152])
+}
+
+///
+/// Generates valid tag trees for `<TSpec>`, built from the tag ids in `ids`.
+///
+/// Each generated value is a top-level element (an id in `ids` whose path is empty).  Children are picked from the ids whose path matches the ancestry of the master they are placed in, so every tree produced passes the path validation done by [`TagWriter`](crate::TagWriter) and [`TagIterator`](crate::TagIterator).  Ids with global paths (like `Void` or `Crc32`) are not generated.  Masters are always [`Master::Full`], at most `max_depth` levels deep and with at most `max_children` children each.
+///
+/// Generated strings never contain control characters and generated floats are never NaN, so trees compare equal after a round trip.  Binary data occasionally uses [`boundary_sizes()`] lengths.
+///
+/// ## Panics
+///
+/// Panics if `ids` contains no top-level id, or if `<TSpec>` is internally inconsistent.
+///
+/// ## Example
+///
+/// ```
+/// use proptest::prelude::*;
+/// use ebml_iterable::specs::EbmlTag;
+/// use ebml_iterable::strategies::tag_trees;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// proptest!(|(tree in tag_trees::<EmptySpec>(&[0x81], 2, 4))| {
+///     assert!(tree.as_binary().is_some());
+/// });
+/// ```
+///
+pub fn tag_trees<TSpec>(ids: &[u64], max_depth: u32, max_children: usize) -> BoxedStrategy<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + Debug + 'static
+{
+    let ids: Arc<Vec<u64>> = Arc::new(ids.to_vec());
+    let roots = children_of::<TSpec>(&ids, &[]);
+    assert!(!roots.is_empty(), "tag_trees needs at least one top-level id");
+
+    Union::new(roots.into_iter().map(|id| element::<TSpec>(Arc::clone(&ids), Vec::new(), id, max_depth, max_children))).boxed()
+}
+
+fn children_of<TSpec>(ids: &[u64], ancestry: &[u64]) -> Vec<u64>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ids.iter()
+        .copied()
+        .filter(|id| {
+            let path = TSpec::get_path_by_id(*id);
+            path.len() == ancestry.len() && path.iter().zip(ancestry).all(|(part, ancestor)| matches!(part, PathPart::Id(part_id) if part_id == ancestor))
+        })
+        .collect()
+}
+
+fn element<TSpec>(ids: Arc<Vec<u64>>, ancestry: Vec<u64>, id: u64, depth: u32, max_children: usize) -> BoxedStrategy<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + Debug + 'static
+{
+    match TSpec::get_tag_data_type(id) {
+        Some(TagDataType::Master) => {
+            let mut ancestry = ancestry;
+            ancestry.push(id);
+            let children = children_of::<TSpec>(&ids, &ancestry);
+            if depth == 0 || children.is_empty() || max_children == 0 {
+                return Just(master_tag(id, Vec::new())).boxed();
+            }
+
+            let child = Union::new(children.into_iter().map(|child| element::<TSpec>(Arc::clone(&ids), ancestry.clone(), child, depth - 1, max_children)));
+            collection::vec(child, 0..=max_children).prop_map(move |children| master_tag(id, children)).boxed()
+        },
+        Some(TagDataType::UnsignedInt) => prop_oneof![Just(0), Just(u64::MAX), any::<u64>()]
+            .prop_map(move |val| TSpec::get_unsigned_int_tag(id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was unsigned int, but could not get tag!", id)))
+            .boxed(),
+        Some(TagDataType::Integer) => prop_oneof![Just(0), Just(i64::MIN), Just(i64::MAX), any::<i64>()]
+            .prop_map(move |val| TSpec::get_signed_int_tag(id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was integer, but could not get tag!", id)))
+            .boxed(),
+        Some(TagDataType::Utf8) => "\\PC{0,32}"
+            .prop_map(move |val| TSpec::get_utf8_tag(id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", id)))
+            .boxed(),
+        Some(TagDataType::Binary) | None => binary_data()
+            .prop_map(move |val| TSpec::get_binary_tag(id, &val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", id)))
+            .boxed(),
+        Some(TagDataType::Float) => (prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO | prop::num::f64::INFINITE)
+            .prop_map(move |val| TSpec::get_float_tag(id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was float, but could not get tag!", id)))
+            .boxed(),
+        Some(TagDataType::Date) => prop_oneof![Just(0), Just(i64::MIN), Just(i64::MAX), any::<i64>()]
+            .prop_map(move |val| TSpec::get_date_tag(id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was date, but could not get tag!", id)))
+            .boxed(),
+    }
+}
+
+fn master_tag<TSpec>(id: u64, children: Vec<TSpec>) -> TSpec
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    TSpec::get_master_tag(id, Master::Full(children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", id))
+}
+
+fn binary_data() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        15 => collection::vec(any::<u8>(), 0..16),
+        1 => (boundary_sizes(), any::<u8>()).prop_map(|(len, fill)| vec![fill; len]),
+    ]
+}
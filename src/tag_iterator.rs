@@ -1,25 +1,78 @@
-use std::io::Read;
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::collections::{HashSet, VecDeque};
 
-use crate::spec_util::validate_tag_path;
+use crate::spec_util::{read_string, read_string_lossy, validate_path, SpecCache};
 use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
-use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, EBMLSize, ProcessingTag, AllowableErrors};
+use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, DEFAULT_MAX_DEPTH, EBMLSize, ProcessingTag, check_child_size, AllowableErrors, BorrowedTag, Quirk, SkipReason, SkippedTag, TagIteratorOptions, TagSpan, Utf8Handling};
 
 use super::tools;
 use super::specs::{EbmlSpecification, EbmlTag, Master, TagDataType, PathPart};
 use super::errors::tag_iterator::{CorruptedFileError, TagIteratorError};
-use super::errors::tool::ToolError;
+use super::ids::{CRC32, EBML, EBML_MAX_ID_LENGTH, EBML_MAX_SIZE_LENGTH};
+use super::ebml_header::EbmlHeader;
 
 const INVALID_TAG_ID_ERROR         : u8 = 0x01;
 const INVALID_HIERARCHY_ERROR      : u8 = 0x02;
 const OVERSIZED_CHILD_ERROR        : u8 = 0x04;
 
+///
+/// The `EBMLMaxIDLength` assumed in strict mode until a document's header says otherwise.
+///
+const DEFAULT_MAX_ID_LENGTH: usize = 4;
+
+///
+/// A tag whose header and data have been read, but whose data has not been decoded into a `TSpec` yet.
+///
+#[derive(Clone)]
+struct TagRead {
+    tag_id: u64,
+    spec_tag_type: Option<TagDataType>,
+    size: EBMLSize,
+    tag_start: usize,
+    data_start: usize,
+    data: Range<usize>,
+}
+
+impl TagRead {
+    #[inline(always)]
+    fn is_borrowable(&self) -> bool {
+        matches!(self.spec_tag_type, Some(TagDataType::Utf8) | Some(TagDataType::Binary) | None)
+    }
+
+    fn span(&self) -> TagSpan {
+        TagSpan::new(self.tag_id, self.tag_start, self.data_start, self.size)
+    }
+}
+
+///
+/// A `CRC-32` element being checked against the data of its parent.  `crc` covers the data from the end of the `CRC-32` element up to `next`.
+///
+#[derive(Clone)]
+struct Crc32Check {
+    tag_id: u64,
+    position: usize,
+    end: usize,
+    next: usize,
+    expected: u32,
+    crc: u32,
+}
+
+type SkipCallback = Box<dyn FnMut(&SkippedTag) + Send>;
+type ProgressCallback = Box<dyn FnMut(usize) + Send>;
+type SeekSource<R> = fn(&mut R, u64) -> io::Result<()>;
+
 ///
 /// Provides an iterator over EBML files (read from a source implementing the [`std::io::Read`] trait). Can be configured to read specific "Master" tags as complete objects rather than just emitting when they start and end.
 ///
 /// This is a generic struct that requires a specification implementing [`EbmlSpecification`] and [`EbmlTag`]. No specifications are included in this crate - you will need to either use another crate providing a spec (such as the Matroska spec implemented in the [webm-iterable](https://crates.io/crates/webm_iterable) or write your own spec if you want to iterate over a custom EBML file. The iterator outputs `TSpec` variants representing the type of tag (defined by the specification) and the accompanying tag data. "Master" tags (defined by the specification) usually will be read as `Start` and `End` variants, but the iterator can be configured to buffer Master tags into a `Full` variant using the `tags_to_buffer` parameter.
 ///
-/// Note: The [`Self::with_capacity()`] method can be used to construct a `TagIterator` with a specified default buffer size.  This is only useful as a microoptimization to memory management if you know the maximum tag size of the file you're reading.
+/// "Master" tags with an unknown size (an all-ones size vint, as written for live streams) are supported.  Such a tag is ended by the first tag that can't be one of its descendants according to the specification - typically a sibling like the next `Cluster`, or a tag from a parent level - or by the end of the stream.  Global tags such as `Void` don't end it.
+///
+/// The source only needs to implement [`Read`], so data can be parsed straight from pipes, sockets or stdin.  Data is read in chunks into an internal buffer, which only grows as far as needed to hold the tag being parsed.  [`Self::set_max_tag_size()`] can be used to bound it.
+///
+/// Note: The [`Self::with_capacity()`] method can be used to construct a `TagIterator` with a specified default buffer size.  This is only useful as a microoptimization to memory management if you know the maximum tag size of the file you're reading, or to tune how much is requested from the source per read (e.g. larger reads for network filesystems or smaller buffers for constrained devices).
 ///
 /// ## Example
 ///
@@ -48,23 +101,59 @@ const OVERSIZED_CHILD_ERROR        : u8 = 0x04;
 ///
 /// The iterator can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag id has a specific data type but fails to produce a tag variant using data of that type).  This won't happen if the specification being used was created using the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro.
 ///
-
 pub struct TagIterator<R: Read, TSpec>
     where
     TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
     source: R,
     tag_ids_to_buffer: HashSet<u64>,
+    tag_ids_to_skip: HashSet<u64>,
+    recovery_ids: Vec<Vec<u8>>,
+    skip_source: Option<fn(&mut R, u64) -> io::Result<()>>,
+    shared_source: Option<(u64, SeekSource<R>)>,
+    window_remaining: Option<u64>,
+    max_lengths: (Option<usize>, Option<usize>),
+    max_id_length: Option<usize>,
+    max_size_length: Option<usize>,
+    skip_unknown: bool,
+    max_unskipped_size: Option<usize>,
+    on_skip: Option<SkipCallback>,
+    on_progress: Option<(usize, ProgressCallback)>,
     allowed_errors: u8,
+    strict: bool,
+    quirks: bool,
+    max_depth: usize,
+    max_tag_size: Option<usize>,
+    max_buffered_size: Option<usize>,
+    keep_encoding: bool,
+    utf8_handling: Utf8Handling,
+    verify_crc32: bool,
+    resync_on_error: bool,
+    emit_skipped: bool,
+    supported_doc_types: Vec<String>,
 
-    buffer: Box<[u8]>,
+    buffer: Vec<u8>,
     buffer_offset: Option<usize>,
     buffered_byte_length: usize,
     internal_buffer_position: usize,
     tag_stack: Vec<ProcessingTag<TSpec>>,
-    emission_queue: VecDeque<Result<(TSpec, usize), TagIteratorError>>,
-    last_emitted_tag_offset: usize,
+    emission_queue: VecDeque<Result<(TSpec, TagSpan), TagIteratorError>>,
+    pending_borrowed: Option<TagRead>,
+    pending_skipped: Option<(SkippedTag, TagSpan)>,
+    last_emitted_tag_span: TagSpan,
+    emitted_path: Vec<u64>,
     has_determined_doc_path: bool,
+    spec_cache: SpecCache,
+    strict_children: Vec<(u64, usize, Vec<u64>)>,
+    warnings: Vec<Quirk>,
+    ignore_remaining: bool,
+    encodings: VecDeque<(usize, Vec<u8>)>,
+    last_encoding: Option<Vec<u8>>,
+    crc32_checks: Vec<Crc32Check>,
+    has_read_first_tag: bool,
+    partial_header: Option<EbmlHeader>,
+    ebml_header: Option<EbmlHeader>,
+    last_progress: usize,
 }
 
 impl<R: Read, TSpec> TagIterator<R, TSpec>
@@ -84,26 +173,82 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
     ///
     /// Returns a new [`TagIterator<TSpec>`] instance with the specified internal buffer capacity.
     ///
-    /// This initializes the [`TagIterator`] with a specific byte capacity.  The iterator will still reallocate if necessary. (Reallocation occurs if the iterator comes across a tag that should be output as a [`Master::Full`] and its size in bytes is greater than the iterator's current buffer capacity.)
+    /// This initializes the [`TagIterator`] with a specific byte capacity (the default is [`DEFAULT_BUFFER_LEN`](crate::iterator::DEFAULT_BUFFER_LEN)).  Each read from `source` requests enough bytes to fill the unused portion of this buffer.  The iterator will still reallocate if necessary. (Reallocation occurs if the iterator comes across a tag that should be output as a [`Master::Full`] and its size in bytes is greater than the iterator's current buffer capacity.)
     ///
     pub fn with_capacity(source: R, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
-        let buffer = vec![0;capacity];
-
         TagIterator {
             source,
             tag_ids_to_buffer: tags_to_buffer.iter().map(|tag| tag.get_id()).collect(),
+            tag_ids_to_skip: HashSet::new(),
+            recovery_ids: Vec::new(),
+            skip_source: None,
+            shared_source: None,
+            window_remaining: None,
+            max_lengths: (None, None),
+            max_id_length: None,
+            max_size_length: None,
+            skip_unknown: false,
+            max_unskipped_size: None,
+            on_skip: None,
+            on_progress: None,
             allowed_errors: 0,
-            buffer: buffer.into_boxed_slice(),
+            strict: false,
+            quirks: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_tag_size: None,
+            max_buffered_size: None,
+            keep_encoding: false,
+            utf8_handling: Utf8Handling::Strict,
+            verify_crc32: false,
+            resync_on_error: false,
+            emit_skipped: false,
+            supported_doc_types: Vec::new(),
+            buffer: vec![0; capacity],
             buffered_byte_length: 0,
             buffer_offset: None,
             internal_buffer_position: 0,
             tag_stack: Vec::new(),
             emission_queue: VecDeque::new(),
-            last_emitted_tag_offset: 0,
+            pending_borrowed: None,
+            pending_skipped: None,
+            last_emitted_tag_span: TagSpan::default(),
+            emitted_path: Vec::new(),
             has_determined_doc_path: false,
+            spec_cache: SpecCache::default(),
+            strict_children: Vec::new(),
+            warnings: Vec::new(),
+            ignore_remaining: false,
+            encodings: VecDeque::new(),
+            last_encoding: None,
+            crc32_checks: Vec::new(),
+            has_read_first_tag: false,
+            partial_header: None,
+            ebml_header: None,
+            last_progress: 0,
         }
     }
 
+    ///
+    /// Returns a new [`TagIterator<TSpec>`] instance that starts reading inside of `parents`.
+    ///
+    /// `source` must be positioned at the start of a tag (for example, using an offset from a `SeekHead` or an index stored elsewhere), and `parents` lists the "Master" tags that contain it, outermost first.  A new iterator trusts the first tag it reads to establish where it is in the document, which fails when that tag can appear anywhere (like `Void` or `CRC-32`); this constructor provides the context up front instead, so no EBML header or parent headers need to be read and the first tag is validated against `parents`.
+    ///
+    /// The parents are treated as having an unknown size: a [`Master::End`] is emitted for each one when a tag that can't be its child is read or when the stream ends, and [`Self::current_path()`] includes them from the start.  Offsets reported by the iterator are relative to where `source` was positioned.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if any of `parents` is not a "Master" tag according to `<TSpec>`.
+    ///
+    pub fn with_parents(source: R, parents: &[TSpec], tags_to_buffer: &[TSpec]) -> Self {
+        let mut iter = TagIterator::new(source, tags_to_buffer);
+        iter.set_implied_parents(parents.iter().map(|parent| {
+            let id = parent.get_id();
+            assert!(matches!(TSpec::get_tag_data_type(id), Some(TagDataType::Master)), "Tag id 0x{:x?} can't be a parent because it is not a \"Master\" tag", id);
+            id
+        }));
+        iter
+    }
+
     ///
     /// Configures how strictly the iterator abides `<TSpec>`.
     /// 
@@ -125,33 +270,415 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         });
     }
 
+    ///
+    /// Enables or disables strict conformance checking.
+    ///
+    /// In strict mode every violation of `<TSpec>` is a hard error, regardless of [`Self::allow_errors()`]: unknown tag ids, tags outside of their expected hierarchy, oversized children, and "Master" tags that end without a child listed in [`EbmlSpecification::get_mandatory_children()`].  The last of these is reported as a [`CorruptedFileError::MissingChildElement`] immediately after the incomplete tag is emitted.  Tag data of the wrong width for its type (such as a 3 byte float) is always an error.  This is intended for validators and compliance checks rather than for playback, where [`Self::allow_errors()`] and [`Self::try_recover()`] are usually more useful.
+    ///
+    /// Tags configured through [`Self::skip_tags()`] are never seen by the iterator, so they count as missing if they are mandatory.
+    ///
+    /// Strict mode also rejects tag ids longer than the document's `EBMLMaxIDLength` header element (4 bytes if the header doesn't set it, as defined in [RFC 8794](https://www.rfc-editor.org/rfc/rfc8794.html#section-11.2.4)) with a [`CorruptedFileError::InvalidTagId`] error.  Otherwise, ids of up to 8 bytes are always accepted.
+    ///
+    pub fn set_strict_mode(&mut self, strict: bool) {
+        self.strict = strict;
+        self.strict_children.clear();
+    }
+
+    ///
+    /// Enables or disables tolerance for bugs commonly found in files written by real-world muxers.
+    ///
+    /// In quirks mode the following are accepted instead of producing errors:
+    ///   * "Master" tags with a declared size of zero are read as unknown sized, since some muxers write zero when they cannot go back and fill in the size.
+    ///   * Integer tags wider than 8 bytes are read if the extra leading bytes are only padding.
+    ///   * Unreadable data after the last top-level tag ends iteration instead of returning an error.
+    ///
+    /// Each tolerated problem is recorded as a [`Quirk`](crate::iterator::Quirk) warning, which can be collected with [`Self::take_warnings()`].  Quirks mode has no effect while [`Self::set_strict_mode()`] is enabled.
+    ///
+    pub fn set_quirks_mode(&mut self, quirks: bool) {
+        self.quirks = quirks;
+    }
+
+    ///
+    /// Returns the [`Quirk`](crate::iterator::Quirk)s tolerated so far in quirks mode, in the order they were found, and clears them.
+    ///
+    pub fn take_warnings(&mut self) -> Vec<Quirk> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    ///
+    /// Configures how deeply "Master" tags may be nested.
+    ///
+    /// The default is [`DEFAULT_MAX_DEPTH`](crate::iterator::DEFAULT_MAX_DEPTH).  A "Master" tag that would be nested deeper produces a [`CorruptedFileError::MaxDepthExceeded`] error, so crafted files can't exhaust memory or overflow the stack while buffering [`Master::Full`] tags.
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    ///
+    /// Configures the largest amount of data a single non-"Master" tag may have.
+    ///
+    /// The data of each tag is held in the internal buffer while it is parsed, so without a limit a single corrupted or malicious size can make the iterator try to buffer an arbitrary amount of data.  Tags with more than `max_size` bytes of data produce a [`CorruptedFileError::MaxTagSizeExceeded`] error instead.  Their data is skipped without being buffered, so iteration can continue with the next tag.  This keeps memory use bounded even for sources that can't be seeked, like pipes or stdin.  `None` (the default) disables the limit.
+    ///
+    /// Tags skipped through [`Self::skip_tags()`], [`Self::skip_unknown_tags()`] or [`Self::skip_oversized_tags()`] are skipped as usual rather than producing an error.  Note that buffering "Master" tags (see `tags_to_buffer`) still holds all of their children in memory.
+    ///
+    pub fn set_max_tag_size(&mut self, max_size: Option<usize>) {
+        self.max_tag_size = max_size;
+    }
+
+    ///
+    /// Configures the largest amount of data a "Master" tag in `tags_to_buffer` may have.
+    ///
+    /// A buffered [`Master::Full`] tag holds all of its children in memory, so a single buffered tag with a huge (or unknown) size can make the iterator hold an arbitrary amount of data.  A buffered tag with more than `max_size` bytes of data produces a [`CorruptedFileError::MaxBufferedSizeExceeded`] error in its place instead.  Its children are read and discarded as they are parsed, so memory use stays bounded and iteration continues with the tag after it.  `None` (the default) disables the limit.
+    ///
+    pub fn set_max_buffered_size(&mut self, max_size: Option<usize>) {
+        self.max_buffered_size = max_size;
+    }
+
+    ///
+    /// Applies all of the resource limits in `options` at once.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    /// use ebml_iterable::iterator::TagIteratorOptions;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x84, 0x01, 0x02, 0x03, 0x04]), &[]);
+    /// iter.set_options(TagIteratorOptions { max_element_size: Some(2), ..Default::default() });
+    /// assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { size: 4, max_size: 2, .. })))));
+    /// ```
+    ///
+    pub fn set_options(&mut self, options: TagIteratorOptions) {
+        self.max_depth = options.max_depth;
+        self.max_tag_size = options.max_element_size;
+        self.max_buffered_size = options.max_total_buffered;
+    }
+
+    ///
+    /// Gets the resource limits the iterator is currently using.
+    ///
+    pub fn options(&self) -> TagIteratorOptions {
+        TagIteratorOptions {
+            max_depth: self.max_depth,
+            max_element_size: self.max_tag_size,
+            max_total_buffered: self.max_buffered_size,
+        }
+    }
+
+    ///
+    /// Configures the longest tag ids and sizes (in bytes) the iterator accepts, like the `EBMLMaxIDLength` and `EBMLMaxSizeLength` elements of an EBML header.
+    ///
+    /// These limits are updated whenever an `EBMLMaxIDLength` or `EBMLMaxSizeLength` element is read, so documents are checked against the limits they declare.  A tag with a longer id or size produces a [`CorruptedFileError::IdTooLong`] or [`CorruptedFileError::SizeTooLong`] error.  `None` doesn't limit the length, except that in [strict mode](Self::set_strict_mode()) ids are limited to the default `EBMLMaxIDLength` of 4 bytes.  Both limits default to `None`, and [`Self::reset()`] restores the values configured here.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// // This tag's size is two bytes long
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x40, 0x01, 0x01]), &[]);
+    /// iter.set_max_lengths(None, Some(1));
+    /// assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::SizeTooLong { length: 2, max_length: 1, .. })))));
+    /// ```
+    ///
+    pub fn set_max_lengths(&mut self, max_id_length: Option<usize>, max_size_length: Option<usize>) {
+        self.max_lengths = (max_id_length, max_size_length);
+        (self.max_id_length, self.max_size_length) = self.max_lengths;
+    }
+
+    ///
+    /// Configures whether the iterator checks `CRC-32` elements against the data they cover.
+    ///
+    /// A `CRC-32` element that is the first child of a "Master" tag holds a checksum of the rest of that tag's data.  With verification enabled, a tag whose data doesn't match produces a [`CorruptedFileError::Crc32Mismatch`] error right after the tag is emitted (as its [`Master::End`], or as a [`Master::Full`]), and iteration can continue as normal.  Checksums can only be calculated over data that is read, so tags containing skipped children (see [`Self::skip_tags()`]) are not checked.  Verification is off by default.
+    ///
+    pub fn set_verify_crc32(&mut self, verify: bool) {
+        self.verify_crc32 = verify;
+        if !verify {
+            self.crc32_checks.clear();
+        }
+    }
+
+    ///
+    /// Configures which `DocType`s the iterator accepts, based on the document's EBML header.
+    ///
+    /// When `doc_types` isn't empty, a document whose header declares a different `DocType` produces a [`TagIteratorError::UnsupportedDocType`] error right after the header is emitted, and a document that doesn't start with an EBML header produces one before its first tag.  Iteration can continue past the error, so callers that want to stop reading unsupported documents should stop at the first error.  An empty list (the default) accepts any document.  See [`EbmlHeader::check()`](crate::header::EbmlHeader::check) for validating the rest of the header.
+    ///
+    pub fn set_supported_doc_types(&mut self, doc_types: &[&str]) {
+        self.supported_doc_types = doc_types.iter().map(|doc_type| doc_type.to_string()).collect();
+    }
+
+    ///
+    /// Configures how utf8 tags with invalid data are read.
+    ///
+    /// Real files sometimes contain strings (such as titles and tag values) that aren't valid utf8.  By default ([`Utf8Handling::Strict`]) these produce a [`TagIteratorError::CorruptedTagData`] error.  [`Utf8Handling::Lossy`] reads them with invalid sequences replaced instead, and [`Utf8Handling::Raw`] emits them as "RawTag" variants holding the original bytes.  Valid utf8 tags are unaffected.  This also applies to [`Self::next_borrowed()`], where lossy strings are emitted as owned [`BorrowedTag::Tag`] variants.
+    ///
+    pub fn set_utf8_handling(&mut self, handling: Utf8Handling) {
+        self.utf8_handling = handling;
+    }
+
+    ///
+    /// Configures whether the iterator keeps the original encoding of each non-"Master" tag it emits, available through [`Self::last_emitted_tag_encoding()`].
+    ///
+    /// This is meant for remuxing: passing the encoding to [`crate::TagWriter::write_encoded()`] copies a tag byte-for-byte, keeping its vint widths (and so any CRC-32 covering it) intact instead of re-encoding the decoded value.  Keeping encodings costs a copy of every tag's bytes, so it is off by default.
+    ///
+    pub fn set_keep_encoding(&mut self, keep_encoding: bool) {
+        self.keep_encoding = keep_encoding;
+        if !keep_encoding {
+            self.encodings.clear();
+            self.last_encoding = None;
+        }
+    }
+
+    ///
+    /// Configures tags that the iterator should skip entirely.
+    ///
+    /// Skipped tags (and, for "Master" tags, all of their children) are never emitted.  Their contents are not parsed, so no errors will be returned for corrupted data inside of them.  By default the skipped bytes are still read from the source and discarded - if the source implements [`std::io::Seek`], [`Self::seek_when_skipping()`] can be used to jump over them instead.
+    ///
+    /// Only tags with a known size can be skipped.  Unknown sized tags in `tags_to_skip` are emitted as usual.
+    ///
+    pub fn skip_tags(&mut self, tags_to_skip: &[TSpec]) {
+        self.tag_ids_to_skip = tags_to_skip.iter().map(|tag| tag.get_id()).collect();
+    }
+
+    ///
+    /// Configures whether the iterator should skip tags that are not in `<TSpec>`.
+    ///
+    /// By default, unknown tags cause a [`CorruptedFileError::InvalidTagId`] error (or are emitted as "RawTag"s if [`AllowableErrors::InvalidTagIds`] is allowed).  When enabled, unknown tags with a known size are skipped like tags configured through [`Self::skip_tags()`] instead (and reported to [`Self::on_skipped_tag()`]), even if [`AllowableErrors::InvalidTagIds`] is allowed.  Strict mode (see [`Self::set_strict_mode()`]) overrides this setting.
+    ///
+    pub fn skip_unknown_tags(&mut self, skip: bool) {
+        self.skip_unknown = skip;
+    }
+
+    ///
+    /// Configures a limit on the data size of tags the iterator emits.
+    ///
+    /// Non-"Master" tags with more than `max_size` bytes of data are skipped without being read into memory, which can be used to ignore large payloads (such as attachments) when only metadata is of interest.  `None` (the default) disables the limit.
+    ///
+    pub fn skip_oversized_tags(&mut self, max_size: Option<usize>) {
+        self.max_unskipped_size = max_size;
+    }
+
+    ///
+    /// Sets a callback that is called for every tag the iterator skips.
+    ///
+    /// Tags can be skipped because they were filtered through [`Self::skip_tags()`], because they are unknown (see [`Self::skip_unknown_tags()`]), or because they are too large (see [`Self::skip_oversized_tags()`]).  The callback receives the id, position and size of each skipped tag so applications can log what wasn't processed rather than silently dropping data.  The children of skipped "Master" tags are not reported individually.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use std::sync::{Arc, Mutex};
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::iterator::{SkipReason, SkippedTag};
+    /// # use ebml_iterable::specs::EbmlTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let skipped = Arc::new(Mutex::new(Vec::new()));
+    /// let log = skipped.clone();
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x84, 0, 0, 0, 0, 0x82, 0x81, 0]), &[]);
+    /// iter.skip_oversized_tags(Some(2));
+    /// iter.on_skipped_tag(move |tag| log.lock().unwrap().push(*tag));
+    ///
+    /// assert_eq!(Some(0x82), iter.next().map(|tag| tag.unwrap().get_id()));
+    /// assert_eq!(vec![SkippedTag { tag_id: 0x81, position: 0, size: 4, reason: SkipReason::Oversized }], *skipped.lock().unwrap());
+    /// ```
+    ///
+    pub fn on_skipped_tag<F: FnMut(&SkippedTag) + Send + 'static>(&mut self, callback: F) {
+        self.on_skip = Some(Box::new(callback));
+    }
+
+    ///
+    /// Registers a callback that receives [`Self::bytes_read()`] each time at least `interval` more bytes have been parsed, and once more when the end of the stream is reached.
+    ///
+    /// This lets long-running scans drive a progress bar without wrapping the source.  Combined with [`Self::total_len()`] for seekable sources, the callback can report a percentage.  The callback is called between tags, so a single large tag can advance the count by more than `interval`.  Skipped data counts towards the interval like any other data.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use std::sync::{Arc, Mutex};
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let progress = Arc::new(Mutex::new(Vec::new()));
+    /// let log = progress.clone();
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x81, 0x02, 0x83, 0x81, 0x03]), &[]);
+    /// iter.on_progress(4, move |bytes_read| log.lock().unwrap().push(bytes_read));
+    ///
+    /// assert_eq!(3, iter.by_ref().count());
+    /// assert_eq!(vec![6, 9], *progress.lock().unwrap());
+    /// ```
+    ///
+    pub fn on_progress<F: FnMut(usize) + Send + 'static>(&mut self, interval: usize, callback: F) {
+        self.on_progress = Some((interval, Box::new(callback)));
+    }
+
+    ///
+    /// Calls the [`Self::on_progress()`] callback if enough data has been parsed since it was last called, or if `finished` and any has been parsed at all.
+    ///
+    fn report_progress(&mut self, finished: bool) {
+        let bytes_read = self.current_offset();
+        if let Some((interval, callback)) = self.on_progress.as_mut() {
+            let parsed = bytes_read.saturating_sub(self.last_progress);
+            if (parsed >= *interval && parsed > 0) || (finished && bytes_read != self.last_progress) {
+                callback(bytes_read);
+                self.last_progress = bytes_read;
+            }
+        }
+    }
+
+    ///
+    /// Configures whether [`Self::next_borrowed()`] reports skipped tags as [`BorrowedTag::Skipped`] items.
+    ///
+    /// This is useful when scanning for metadata: by skipping large payloads like `SimpleBlock`s with [`Self::skip_tags()`], their data is never copied into memory (and with [`Self::seek_when_skipping()`], never read), but their ids, positions and sizes are still reported in order with the other tags.  [`Self::last_emitted_tag_span()`] gives the span of each skipped tag.  Tags skipped inside of buffered "Master" tags are not reported, and [`Iterator::next()`] never reports skipped tags (see [`Self::on_skipped_tag()`] instead).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::iterator::{BorrowedTag, SkippedTag, SkipReason};
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x84, 0, 0, 0, 0, 0x82, 0x81, 0]), &[]);
+    /// iter.skip_tags(&[EmptySpec::with_data(0x81, &[])]);
+    /// iter.set_emit_skipped_tags(true);
+    ///
+    /// assert_eq!(Some(BorrowedTag::Skipped(SkippedTag { tag_id: 0x81, position: 0, size: 4, reason: SkipReason::Filtered })), iter.next_borrowed().map(|tag| tag.unwrap()));
+    /// assert_eq!(Some(0x82), iter.next_borrowed().map(|tag| tag.unwrap().get_id()));
+    /// ```
+    ///
+    pub fn set_emit_skipped_tags(&mut self, emit: bool) {
+        self.emit_skipped = emit;
+    }
+
+    ///
+    /// Configures which tags [`Self::try_recover()`] should resynchronize on.
+    ///
+    /// By default, recovery stops at the first position that parses as a valid tag header, which can be any tag in the specification.  Restricting recovery to a few tags with long, distinctive ids (such as a Matroska `Cluster`, `0x1F43B675`) lets the iterator search the buffered data for those ids directly rather than attempting to parse a header at every byte, which makes recovering from large corrupted sections far quicker.  It also makes it much less likely that recovery stops on random bytes that happen to look like a short tag.
+    ///
+    /// Passing an empty slice restores the default behavior.
+    ///
+    pub fn recover_at_tags(&mut self, recovery_tags: &[TSpec]) {
+        self.recovery_ids = recovery_tags.iter()
+            .map(|tag| tag.get_id().to_be_bytes().iter().copied().skip_while(|b| *b == 0).collect::<Vec<u8>>())
+            .filter(|id| !id.is_empty())
+            .collect();
+    }
+
     ///
     /// Instructs the iterator to attempt to recover after reaching corrupted file data.
     /// 
-    /// This method can be used to skip over corrupted sections of a read stream without recreating a new iterator.  The iterator will seek forward from its current internal position until it reaches either a valid EBML tag id or EOF.  After recovery, [`Iterator::next()`] *should* return an [`Ok`] result.
+    /// This method can be used to skip over corrupted sections of a read stream without recreating a new iterator.  The iterator will seek forward from its current internal position until it reaches either a valid EBML tag id or EOF.  After recovery, [`Iterator::next()`] *should* return an [`Ok`] result.  Recovery can be limited to specific tags using [`Self::recover_at_tags()`].
     /// 
     pub fn try_recover(&mut self) -> Result<(), TagIteratorError> {
-        let original_position = self.current_offset();        
+        let mut skipped_to = self.current_offset();
         loop {
             if !self.ensure_data_read(1)? {
                 return Err(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: None, tag_size: None, partial_data: None });
             }
 
             self.internal_buffer_position += 1;
+            if !self.seek_recovery_candidate()? {
+                return Err(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: None, tag_size: None, partial_data: None });
+            }
+
+            // As part of recovery, update internal tag stack sizes so that we don't get "oversized children" errors after skipping corrupted data
+            let diff = self.current_offset() - skipped_to;
+            skipped_to = self.current_offset();
+            for tag in self.tag_stack.iter_mut() {
+                if let EBMLSize::Known(size) = &tag.size {
+                    tag.size = EBMLSize::Known(size + diff);
+                }
+            }
+
             if self.peek_valid_tag_header().is_ok() {
                 break;
             }
         }
 
-        // As part of recovery, update internal tag stack sizes so that we don't get "oversized children" errors after skipping corrupted data
-        let diff = self.current_offset() - original_position;
-        for tag in self.tag_stack.iter_mut() {
-            if let EBMLSize::Known(size) = &tag.size {
-                tag.size = EBMLSize::Known(size + diff);
-            }
+        Ok(())
+    }
+
+    ///
+    /// Configures whether the iterator recovers from corrupted data on its own.
+    ///
+    /// Normally, an error about corrupted data leaves the iterator where the problem was found, and calling [`Iterator::next()`] again returns the same error until [`Self::try_recover()`] is called.  With this mode enabled, the error is still returned, but the iterator calls [`Self::try_recover()`] before returning it, so the next call continues with the first valid tag after the corrupted section.  Combined with [`Self::recover_at_tags()`], this lets a damaged file be read from one `Cluster` to the next without the caller handling each error.  If no tag is found before the end of the source, iteration ends.
+    ///
+    /// Only errors found reading a tag header (invalid ids, sizes or hierarchy errors) trigger recovery.  Errors about tags that were read completely, like [`CorruptedFileError::Crc32Mismatch`], don't need it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::specs::EbmlTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// // A tag with an invalid size between two valid tags
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0xec, 0x00, 0x82, 0x81, 0x02]), &[]);
+    /// iter.set_resync_on_error(true);
+    /// assert!(iter.next().unwrap().is_ok());
+    /// assert!(iter.next().unwrap().is_err());
+    /// assert_eq!(Some(0x82), iter.next().map(|tag| tag.unwrap().get_id()));
+    /// assert!(iter.next().is_none());
+    /// ```
+    ///
+    pub fn set_resync_on_error(&mut self, resync: bool) {
+        self.resync_on_error = resync;
+    }
+
+    ///
+    /// Recovers after `err` was emitted, if resyncing is enabled and `err` left the iterator in front of corrupted data.
+    ///
+    fn resync_after(&mut self, err: &TagIteratorError) {
+        let needs_resync = matches!(err, TagIteratorError::CorruptedFileData(
+            CorruptedFileError::InvalidTagId { .. } | CorruptedFileError::InvalidTagData { .. } | CorruptedFileError::InvalidVint { .. } | CorruptedFileError::HierarchyError { .. } | CorruptedFileError::OversizedChildElement { .. }
+            | CorruptedFileError::IdTooLong { .. } | CorruptedFileError::SizeTooLong { .. }
+        ));
+        if !self.resync_on_error || !needs_resync {
+            return;
+        }
+        match self.try_recover() {
+            Ok(()) => {},
+            // Nothing valid is left, so the rest of the source is skipped
+            Err(TagIteratorError::UnexpectedEOF { .. }) => self.ignore_remaining = true,
+            Err(err) => self.emission_queue.push_back(Err(err)),
         }
+    }
 
-        Ok(())
+    ///
+    /// Advances to the next position in the source that could be the start of a tag to recover at, scanning whole buffers at a time.  Returns `false` if the source ends first.
+    ///
+    fn seek_recovery_candidate(&mut self) -> Result<bool, TagIteratorError> {
+        let longest_id = self.recovery_ids.iter().map(Vec::len).max().unwrap_or(1);
+        self.ensure_capacity(longest_id);
+        loop {
+            let window = &self.buffer[self.internal_buffer_position..self.buffered_byte_length];
+            let found = if self.recovery_ids.is_empty() {
+                // Tag ids can never start with a zero byte, and corrupted sections are often zeroed out
+                window.iter().position(|b| *b != 0)
+            } else {
+                find_any_id(window, &self.recovery_ids)
+            };
+            if let Some(offset) = found {
+                self.internal_buffer_position += offset;
+                return Ok(true);
+            }
+
+            // Keep enough of the end of the window to match an id that continues past it
+            let keep = window.len().min(longest_id - 1);
+            self.internal_buffer_position = self.buffered_byte_length - keep;
+            if !self.ensure_data_read(keep + 1)? {
+                return Ok(false);
+            }
+        }
     }
 
     ///
@@ -163,6 +690,26 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         self.source
     }
 
+    ///
+    /// Replaces the underlying read stream, returning the previous one, so the iterator can be reused for another document.
+    ///
+    /// All parsing state (open tags, queued tags and buffered data) is discarded and offsets restart at zero for `source`.  Configuration set through [`Self::allow_errors()`], [`Self::set_strict_mode()`], [`Self::set_quirks_mode()`], [`Self::set_max_depth()`], [`Self::set_max_tag_size()`], [`Self::set_max_buffered_size()`], [`Self::set_max_lengths()`], [`Self::set_keep_encoding()`], [`Self::set_utf8_handling()`], [`Self::set_verify_crc32()`], [`Self::set_supported_doc_types()`], [`Self::skip_tags()`], [`Self::skip_unknown_tags()`], [`Self::skip_oversized_tags()`], [`Self::on_skipped_tag()`], [`Self::on_progress()`], [`Self::set_emit_skipped_tags()`], [`Self::recover_at_tags()`], [`Self::set_resync_on_error()`] and [`Self::seek_when_skipping()`] is kept, as are the tags to buffer and the allocation of the internal buffer.  This avoids repeating setup work when processing many documents in a loop.
+    ///
+    pub fn reset(&mut self, source: R) -> R {
+        self.buffer_offset = None;
+        self.buffered_byte_length = 0;
+        self.internal_buffer_position = 0;
+        self.clear_parse_state();
+        self.warnings.clear();
+        self.has_read_first_tag = false;
+        self.ebml_header = None;
+        self.last_progress = 0;
+        self.shared_source = None;
+        self.window_remaining = None;
+        (self.max_id_length, self.max_size_length) = self.max_lengths;
+        std::mem::replace(&mut self.source, source)
+    }
+
     ///
     /// Gets a mutable reference to the underlying read stream.
     /// 
@@ -187,64 +734,507 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
     /// This function returns a byte index specifying the start of the last emitted tag in the context of the [`TagIterator`]'s source read stream.  This value is *not guaranteed to always increase as the file is read*.  Whenever the iterator emits a [`Master::End`] variant, [`Self::last_emitted_tag_offset()`] will reflect the start index of the "Master" tag, which will be before previous values that were obtainable when any children of the master were emitted.
     /// 
     pub fn last_emitted_tag_offset(&self) -> usize {
-        self.last_emitted_tag_offset
+        self.last_emitted_tag_span.offset
     }
 
-    #[inline(always)]
-    fn current_offset(&self) -> usize {
-        self.buffer_offset.unwrap_or(0) + self.internal_buffer_position
+    ///
+    /// Returns how many bytes of the source have been parsed so far.
+    ///
+    /// This is the offset the next tag will be read from, in the same coordinates as [`Self::last_emitted_tag_offset()`].  It includes the data of skipped tags, but not data the iterator has read ahead into its internal buffer, so it advances smoothly as tags are parsed and can be used to show progress through a document.  See [`Self::on_progress()`] to be notified as it grows.
+    ///
+    pub fn bytes_read(&self) -> usize {
+        self.current_offset()
     }
 
-    fn private_read(&mut self, internal_buffer_start: usize) -> Result<bool, TagIteratorError> {
-        let bytes_read = self.source.read(&mut self.buffer[internal_buffer_start..]).map_err(|source| TagIteratorError::ReadError { source })?;
-        if bytes_read == 0 {
-            Ok(false)
-        } else {
-            self.buffered_byte_length += bytes_read;
-            Ok(true)
-        }
+    ///
+    /// Returns the position and size of the last emitted tag.
+    ///
+    /// Along with the offset returned by [`Self::last_emitted_tag_offset()`], this includes the length of the tag's header and of its data, which tools can use to account for the bytes used by each tag.  Like the offset, the span of a [`Master::End`] variant is the span of the whole "Master" tag.
+    ///
+    pub fn last_emitted_tag_span(&self) -> TagSpan {
+        self.last_emitted_tag_span
     }
 
-    fn ensure_capacity(&mut self, required_capacity: usize) {
-        if required_capacity > self.buffer.len() {
-            let mut new_buffer = Vec::from(&self.buffer[..]);
-            new_buffer.resize(required_capacity, 0);
-            self.buffer = new_buffer.into_boxed_slice();
-        }
+    ///
+    /// Consumes self and returns an iterator that emits the [`TagSpan`] of each tag along with the tag, e.g. to build a seek index while parsing.
+    ///
+    /// Each span is the value [`Self::last_emitted_tag_span()`] would return after the tag was emitted.  The iterator can be recovered with [`TagPositions::into_inner()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::iterator::TagSpan;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0x42, 0x82, 0x82, 0x02, 0x03]), &[]);
+    /// let spans: Vec<TagSpan> = iter.with_positions().map(|tag| tag.unwrap().1).collect();
+    /// assert_eq!(vec![
+    ///     TagSpan { offset: 0, header_len: 2, size_len: 1, data_len: Some(1) },
+    ///     TagSpan { offset: 3, header_len: 3, size_len: 1, data_len: Some(2) },
+    /// ], spans);
+    /// ```
+    ///
+    pub fn with_positions(self) -> TagPositions<R, TSpec> {
+        TagPositions { iter: self }
     }
 
-    fn ensure_data_read(&mut self, length: usize) -> Result<bool, TagIteratorError> {
-        if self.internal_buffer_position + length <= self.buffered_byte_length {
-            return Ok(true)
-        }
-
-        if self.buffer_offset.is_none() {
-            if !self.private_read(0)? {
-                return Ok(false);
-            }
-            self.buffer_offset = Some(0);
-            self.internal_buffer_position = 0;
-        } else {
-            while self.internal_buffer_position + length > self.buffered_byte_length {
-                self.buffer.copy_within(self.internal_buffer_position..self.buffered_byte_length, 0);
-                self.buffered_byte_length -= self.internal_buffer_position;
-                self.buffer_offset = Some(self.current_offset());
-                self.internal_buffer_position = 0;
-                if !self.private_read(self.buffered_byte_length)? {
-                    return Ok(false);
-                }
-            }
-        }
-        Ok(true)
+    ///
+    /// Consumes self and returns an iterator that emits each `master_id` "Master" tag in the document as a complete [`Master::Full`] tree.
+    ///
+    /// `master_id` is added to the tags to buffer, so only one subtree is held in memory at a time.  All other tags are read and discarded, using [`Self::next_borrowed()`] so that their data isn't copied.  Errors are emitted as they occur, and iteration can continue past them as usual.  This is useful for processing documents made of many similar elements, such as each `Cluster` of a Matroska file.  The iterator can be recovered with [`TagSubtrees::into_inner()`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `master_id` is not a "Master" tag according to `<TSpec>`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::header::EbmlHeaderSpec;
+    /// use ebml_iterable::specs::Master;
+    ///
+    /// let data = vec![
+    ///     0x1a, 0x45, 0xdf, 0xa3, 0x95,
+    ///     0x42, 0x82, 0x84, b'w', b'e', b'b', b'm',
+    ///     0x42, 0x81, 0x84, 0x42, 0x84, 0x81, 0x01,
+    ///     0x42, 0x81, 0x84, 0x42, 0x84, 0x81, 0x02,
+    /// ];
+    /// let iter: TagIterator<_, EbmlHeaderSpec> = TagIterator::new(Cursor::new(data), &[]);
+    /// let extensions: Vec<EbmlHeaderSpec> = iter.into_subtree_iter(0x4281).map(|tag| tag.unwrap()).collect();
+    /// assert_eq!(vec![
+    ///     EbmlHeaderSpec::DocTypeExtension(Master::Full(vec![EbmlHeaderSpec::DocTypeExtensionVersion(1)])),
+    ///     EbmlHeaderSpec::DocTypeExtension(Master::Full(vec![EbmlHeaderSpec::DocTypeExtensionVersion(2)])),
+    /// ], extensions);
+    /// ```
+    ///
+    pub fn into_subtree_iter(mut self, master_id: u64) -> TagSubtrees<R, TSpec> {
+        assert!(matches!(TSpec::get_tag_data_type(master_id), Some(TagDataType::Master)), "Tag id 0x{:x?} can't be iterated as subtrees because it is not a \"Master\" tag", master_id);
+        self.tag_ids_to_buffer.insert(master_id);
+        TagSubtrees { iter: self, master_id }
     }
 
-    #[inline(always)]
-    fn peek_tag_id(&mut self) -> Result<(u64, usize), TagIteratorError> {
+    ///
+    /// Returns the original bytes of the last emitted tag, including its id and size, if [`Self::set_keep_encoding()`] is enabled.
+    ///
+    /// Returns `None` for "Master" tags (their children are reported individually), when keeping encodings is disabled, or when the tag was not read from the source as-is, e.g. a tag inside a [`Master::Full`] variant or a tag that was [recovered](Self::try_recover()).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::{TagIterator, TagWriter};
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// // The size of this tag uses two bytes even though one would be enough
+    /// let data = vec![0x81, 0x40, 0x01, 0x01];
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(&data), &[]);
+    /// iter.set_keep_encoding(true);
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// while let Some(tag) = iter.next() {
+    ///     match iter.last_emitted_tag_encoding() {
+    ///         Some(encoding) => writer.write_encoded(&tag.unwrap(), encoding).unwrap(),
+    ///         None => writer.write(&tag.unwrap()).unwrap(),
+    ///     }
+    /// }
+    /// assert_eq!(data, writer.into_inner().unwrap());
+    /// ```
+    ///
+    pub fn last_emitted_tag_encoding(&self) -> Option<&[u8]> {
+        self.last_encoding.as_deref()
+    }
+
+    ///
+    /// Returns the ids of the "Master" tags the last emitted tag is inside of, outermost first.
+    ///
+    /// The path is updated as tags are emitted rather than as they are read, so it always matches the tags seen by the consumer: after a [`Master::Start`] the path ends with that tag, and after its [`Master::End`] the tag is removed again.  [`Master::Full`] tags do not change the path.  This lets handlers for tags that appear in many places (like names or uids) tell which parent they belong to without tracking masters themselves.  If the iterator started in the middle of a document, the parents implied by the first tag read are included.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01]), &[]);
+    /// iter.next();
+    /// assert!(iter.current_path().is_empty());
+    /// ```
+    ///
+    pub fn current_path(&self) -> &[u64] {
+        &self.emitted_path
+    }
+
+    ///
+    /// Returns the names of the tags in [`Self::current_path()`], as given by [`EbmlSpecification::get_tag_name()`].
+    ///
+    pub fn current_path_names(&self) -> Vec<Option<&'static str>> {
+        self.emitted_path.iter().map(|id| TSpec::get_tag_name(*id)).collect()
+    }
+
+    ///
+    /// Returns the EBML header at the start of the document, once all of it has been emitted.
+    ///
+    /// The header values are collected as the header's children are read, so this is `None` until the header's [`Master::End`] (or [`Master::Full`]) has been emitted, and stays `None` if the document doesn't start with an EBML header.  Header elements that are missing or unreadable take their default values.  The header is kept after [`Self::seek_to()`], but is cleared by [`Self::reset()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::header::EbmlHeaderSpec;
+    ///
+    /// let data = vec![0x1a, 0x45, 0xdf, 0xa3, 0x8b, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm', 0x42, 0x87, 0x81, 0x04];
+    /// let mut iter: TagIterator<_, EbmlHeaderSpec> = TagIterator::new(Cursor::new(data), &[]);
+    /// for _ in 0..3 {
+    ///     iter.next().unwrap().unwrap();
+    ///     assert!(iter.ebml_header().is_none());
+    /// }
+    /// iter.next().unwrap().unwrap();
+    /// let header = iter.ebml_header().unwrap();
+    /// assert_eq!("webm", header.doc_type);
+    /// assert_eq!(4, header.doc_type_version);
+    /// assert_eq!(16, header.header_len);
+    /// ```
+    ///
+    pub fn ebml_header(&self) -> Option<&EbmlHeader> {
+        self.ebml_header.as_ref()
+    }
+
+    ///
+    /// Forgets the specification lookups cached by the iterator, for when the lookups of `<TSpec>` change (as they do when a [`DynTagIterator`](crate::dynamic::DynTagIterator) switches specifications).
+    ///
+    pub(crate) fn clear_spec_cache(&mut self) {
+        self.spec_cache = SpecCache::default();
+    }
+
+    ///
+    /// Opens "Master" tags that the stream is assumed to be inside of, without their headers having been read.  They have an unknown size, so they are ended by the first tag that can't be their child or by the end of the stream.
+    ///
+    fn set_implied_parents(&mut self, ids: impl Iterator<Item = u64>) {
+        self.tag_stack = ids.map(|id| {
+            ProcessingTag {
+                tag: <TSpec>::get_master_tag(id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was in path, but could not get master tag!", id)),
+                size: EBMLSize::Unknown,
+                tag_start: 0,
+                data_start: 0,
+            }
+        }).collect();
+        self.emitted_path = self.tag_stack.iter().map(|tag| tag.tag.get_id()).collect();
+        self.has_determined_doc_path = true;
+    }
+
+    ///
+    /// Removes and returns the encoding kept for the tag starting at `offset`, dropping any encodings of tags before it.
+    ///
+    fn take_encoding(&mut self, offset: usize) -> Option<Vec<u8>> {
+        // Encodings are kept in the order tags were read, and tags are emitted in that order too (except for the ends of masters)
+        while self.encodings.front().is_some_and(|(start, _)| *start < offset) {
+            self.encodings.pop_front();
+        }
+        if self.encodings.front().is_some_and(|(start, _)| *start == offset) {
+            self.encodings.pop_front().map(|(_, encoding)| encoding)
+        } else {
+            None
+        }
+    }
+
+    fn record_emission(&mut self, tag: &TSpec, span: TagSpan) {
+        self.last_emitted_tag_span = span;
+        if self.keep_encoding {
+            self.last_encoding = if tag.as_master().is_none() { self.take_encoding(span.offset) } else { None };
+        }
+        match tag.as_master() {
+            Some(Master::Start) => self.emitted_path.push(tag.get_id()),
+            Some(Master::End) => {
+                let id = tag.get_id();
+                if let Some(index) = self.emitted_path.iter().rposition(|open| *open == id) {
+                    self.emitted_path.truncate(index);
+                }
+            },
+            _ => {},
+        }
+        if matches!(tag.as_master(), Some(Master::End) | Some(Master::Full(_))) {
+            self.check_crc32(span.offset);
+        }
+        if tag.get_id() == EBML && !matches!(tag.as_master(), Some(Master::Start)) && self.emitted_path.is_empty() {
+            self.finish_header();
+        }
+        self.check_strict_children(tag, span.offset);
+    }
+
+    ///
+    /// Stores the EBML header once all of it has been emitted, queueing an error if its `DocType` isn't supported.
+    ///
+    fn finish_header(&mut self) {
+        if let Some(header) = self.partial_header.take() {
+            if !self.supported_doc_types.is_empty() && !self.supported_doc_types.contains(&header.doc_type) {
+                self.emission_queue.push_front(Err(TagIteratorError::UnsupportedDocType { doc_type: Some(header.doc_type.clone()) }));
+            }
+            self.ebml_header = Some(header);
+        }
+    }
+
+    ///
+    /// Finishes the `CRC-32` checks of the tag at `position` and of any tags inside it, queueing an error for each mismatch.
+    ///
+    fn check_crc32(&mut self, position: usize) {
+        while self.crc32_checks.last().is_some_and(|check| check.position >= position) {
+            let check = self.crc32_checks.pop().unwrap();
+            // Checks that didn't see all of the data (because children were skipped or reading failed) can't be concluded
+            if check.next == check.end && check.crc != check.expected {
+                self.emission_queue.push_front(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::Crc32Mismatch { position: check.position, tag_id: check.tag_id, expected: check.expected, actual: check.crc })));
+            }
+        }
+    }
+
+    ///
+    /// Adds the bytes at `position` to every `CRC-32` check that covers them.
+    ///
+    fn update_crc32(&mut self, position: usize, data: Range<usize>) {
+        for check in self.crc32_checks.iter_mut() {
+            if check.next == position && position + data.len() <= check.end {
+                check.crc = tools::crc32_update(check.crc, &self.buffer[data.clone()]);
+                check.next += data.len();
+            }
+        }
+    }
+
+    ///
+    /// Reads the next tag without copying its data out of the iterator's internal buffer.
+    ///
+    /// This is a lending alternative to [`Iterator::next()`]: utf8, binary, and raw tags are returned as references into the iterator's buffer, so consumers that process each tag immediately pay no allocation per tag.  The returned tag borrows the iterator, so it must be dropped (or converted using [`BorrowedTag::into_owned()`]) before the iterator can be advanced again.  Tags inside masters listed in `tags_to_buffer` are still collected into owned [`Master::Full`] tags.
+    ///
+    /// Calls to this method can be freely mixed with calls to [`Iterator::next()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::iterator::BorrowedTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x82, 0x01, 0x02, 0x82, 0x80]), &[]);
+    /// let mut total = 0;
+    /// while let Some(tag) = iter.next_borrowed() {
+    ///     if let BorrowedTag::Binary { value, .. } = tag.unwrap() {
+    ///         total += value.len();
+    ///     }
+    /// }
+    /// assert_eq!(2, total);
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Errors are returned in the same situations as for [`Iterator::next()`].
+    ///
+    pub fn next_borrowed(&mut self) -> Option<Result<BorrowedTag<'_, TSpec>, TagIteratorError>> {
+        while self.emission_queue.is_empty() && self.pending_borrowed.is_none() && self.pending_skipped.is_none() && self.read_next(true) {}
+
+        if let Some(next_item) = self.emission_queue.pop_front() {
+            match next_item {
+                Ok(ref tuple) => self.record_emission(&tuple.0, tuple.1),
+                Err(ref err) => self.resync_after(err),
+            }
+            return Some(next_item.map(|t| BorrowedTag::Tag(t.0)));
+        }
+
+        if let Some((skipped, span)) = self.pending_skipped.take() {
+            self.last_emitted_tag_span = span;
+            if self.keep_encoding {
+                // Skipped tags have no encoding, but older ones still need to be dropped
+                self.take_encoding(span.offset);
+                self.last_encoding = None;
+            }
+            return Some(Ok(BorrowedTag::Skipped(skipped)));
+        }
+
+        let read = self.pending_borrowed.take()?;
+        self.last_emitted_tag_span = read.span();
+        if self.keep_encoding {
+            self.last_encoding = self.take_encoding(read.tag_start);
+        }
+        if read.tag_id == EBML && self.emitted_path.is_empty() {
+            self.finish_header();
+        }
+        if self.strict {
+            self.record_strict_child(read.tag_id);
+        }
+        let id = read.tag_id;
+        let data = &self.buffer[read.data];
+        let tag = match read.spec_tag_type {
+            Some(TagDataType::Utf8) => match read_string::<TSpec>(id, data) {
+                Ok(value) => Ok(BorrowedTag::Utf8 { id, value }),
+                Err(problem) => match self.utf8_handling {
+                    Utf8Handling::Strict => Err(TagIteratorError::CorruptedTagData { position: read.tag_start, tag_id: id, problem }),
+                    Utf8Handling::Lossy => Ok(BorrowedTag::Tag(TSpec::get_utf8_tag(id, read_string_lossy::<TSpec>(id, data)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", id)))),
+                    Utf8Handling::Raw => Ok(BorrowedTag::Raw { id, data }),
+                },
+            },
+            Some(TagDataType::Binary) => Ok(BorrowedTag::Binary { id, value: data }),
+            _ => Ok(BorrowedTag::Raw { id, data }),
+        };
+        Some(tag)
+    }
+
+    ///
+    /// Tracks the children of open "Master" tags in strict mode, queueing an error when one ends without all of its mandatory children.
+    ///
+    fn check_strict_children(&mut self, tag: &TSpec, tag_start: usize) {
+        if !self.strict {
+            return;
+        }
+
+        let id = tag.get_id();
+        match tag.as_master() {
+            Some(Master::Start) => {
+                self.record_strict_child(id);
+                self.strict_children.push((id, tag_start, Vec::new()));
+            },
+            Some(Master::End) => {
+                if let Some((id, position, seen)) = self.strict_children.pop() {
+                    let missing_tag_ids: Vec<u64> = TSpec::get_mandatory_children(id).iter().copied().filter(|child| !seen.contains(child)).collect();
+                    if !missing_tag_ids.is_empty() {
+                        self.emission_queue.push_front(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MissingChildElement { position, tag_id: id, missing_tag_ids })));
+                    }
+                }
+            },
+            Some(Master::Full(_)) => {
+                self.record_strict_child(id);
+                if let Some(err) = find_missing_children(tag, tag_start) {
+                    self.emission_queue.push_front(Err(TagIteratorError::CorruptedFileData(err)));
+                }
+            },
+            None => self.record_strict_child(id),
+        }
+    }
+
+    fn record_strict_child(&mut self, id: u64) {
+        if let Some((_, _, seen)) = self.strict_children.last_mut() {
+            seen.push(id);
+        }
+    }
+
+    #[inline(always)]
+    fn quirks_enabled(&self) -> bool {
+        self.quirks && !self.strict
+    }
+
+    ///
+    /// Skips the data of the "Master" tag that was just emitted as a [`Master::Start`], so that its [`Master::End`] is the next tag emitted.  Returns `false` without skipping if the master has an unknown size or other tags have already been read.
+    ///
+    pub(crate) fn skip_master_data(&mut self) -> Result<bool, TagIteratorError> {
+        if !self.emission_queue.is_empty() || self.pending_borrowed.is_some() || self.pending_skipped.is_some() {
+            return Ok(false);
+        }
+        let (tag_id, tag_start, end) = match self.tag_stack.last() {
+            Some(open) if open.tag_start == self.last_emitted_tag_span.offset && open.data_start == self.current_offset() => match open.data_end() {
+                Some(end) => (open.tag.get_id(), open.tag_start, end),
+                None => return Ok(false),
+            },
+            _ => return Ok(false),
+        };
+        let size = end - self.current_offset();
+        if !self.skip_tag_data(size)? {
+            return Err(TagIteratorError::UnexpectedEOF { tag_start, tag_id: Some(tag_id), tag_size: Some(size), partial_data: None });
+        }
+        Ok(true)
+    }
+
+    fn clear_parse_state(&mut self) {
+        self.tag_stack.clear();
+        self.emission_queue.clear();
+        self.pending_borrowed = None;
+        self.pending_skipped = None;
+        self.last_emitted_tag_span = TagSpan::default();
+        self.emitted_path.clear();
+        self.has_determined_doc_path = false;
+        self.strict_children.clear();
+        self.ignore_remaining = false;
+        self.encodings.clear();
+        self.last_encoding = None;
+        self.crc32_checks.clear();
+        self.partial_header = None;
+    }
+
+    #[inline(always)]
+    fn current_offset(&self) -> usize {
+        self.buffer_offset.unwrap_or(0) + self.internal_buffer_position
+    }
+
+    ///
+    /// Moves a forked iterator's source back to where this iterator left off, since another branch may have moved it.
+    ///
+    fn sync_source(&mut self, position: usize) -> Result<(), TagIteratorError> {
+        if let Some((origin, seek)) = self.shared_source {
+            seek(&mut self.source, origin + position as u64).map_err(|source| TagIteratorError::ReadError { source })?;
+        }
+        Ok(())
+    }
+
+    fn private_read(&mut self, internal_buffer_start: usize) -> Result<bool, TagIteratorError> {
+        self.sync_source(self.buffer_offset.unwrap_or(0) + self.buffered_byte_length)?;
+        let mut read_end = self.buffer.len();
+        if let Some(remaining) = self.window_remaining {
+            read_end = read_end.min(internal_buffer_start.saturating_add(usize::try_from(remaining).unwrap_or(usize::MAX)));
+        }
+        let bytes_read = self.source.read(&mut self.buffer[internal_buffer_start..read_end]).map_err(|source| TagIteratorError::ReadError { source })?;
+        if let Some(remaining) = self.window_remaining.as_mut() {
+            *remaining -= bytes_read as u64;
+        }
+        if bytes_read == 0 {
+            Ok(false)
+        } else {
+            self.buffered_byte_length += bytes_read;
+            Ok(true)
+        }
+    }
+
+    fn ensure_capacity(&mut self, required_capacity: usize) {
+        if required_capacity > self.buffer.len() {
+            self.buffer.resize(required_capacity, 0);
+        }
+    }
+
+    fn ensure_data_read(&mut self, length: usize) -> Result<bool, TagIteratorError> {
+        if self.internal_buffer_position + length <= self.buffered_byte_length {
+            return Ok(true)
+        }
+
+        if self.buffer_offset.is_none() {
+            if !self.private_read(0)? {
+                return Ok(false);
+            }
+            self.buffer_offset = Some(0);
+            self.internal_buffer_position = 0;
+        } else {
+            while self.internal_buffer_position + length > self.buffered_byte_length {
+                self.buffer.copy_within(self.internal_buffer_position..self.buffered_byte_length, 0);
+                self.buffered_byte_length -= self.internal_buffer_position;
+                self.buffer_offset = Some(self.current_offset());
+                self.internal_buffer_position = 0;
+                if !self.private_read(self.buffered_byte_length)? {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    #[inline(always)]
+    fn peek_tag_id(&mut self) -> Result<(u64, usize), TagIteratorError> {
         self.ensure_data_read(8)?;
         if self.buffer[self.internal_buffer_position] == 0 {
             return Ok((0, 1));
         }
         let length = 8 - self.buffer[self.internal_buffer_position].ilog2() as usize;
+        if self.internal_buffer_position + length > self.buffered_byte_length {
+            return Err(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: None, tag_size: None, partial_data: None });
+        }
         let mut val = self.buffer[self.internal_buffer_position] as u64;
         for i in 1..length {
             val <<= 8;
@@ -257,13 +1247,22 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
     fn peek_valid_tag_header(&mut self) -> Result<(u64, Option<TagDataType>, EBMLSize, usize), TagIteratorError> {
         self.ensure_data_read(16)?;
         let (tag_id, id_len) = self.peek_tag_id()?;
-        let spec_tag_type = <TSpec>::get_tag_data_type(tag_id);
+        let max_id_length = self.max_id_length.or(if self.strict { Some(DEFAULT_MAX_ID_LENGTH) } else { None });
+        if let Some(max_length) = max_id_length.filter(|max_length| id_len > *max_length) {
+            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::IdTooLong { position: self.current_offset(), tag_id, length: id_len, max_length }));
+        }
+        let spec_entry = self.spec_cache.lookup::<TSpec>(tag_id);
+        let spec_tag_type = spec_entry.data_type;
 
-        let (size, size_len) = tools::read_vint(&self.buffer[(self.internal_buffer_position + id_len)..])
-                .or(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{tag_id, position: self.current_offset() })))?
+        let (size, size_len) = tools::read_vint(&self.buffer[(self.internal_buffer_position + id_len)..self.buffered_byte_length])
+                .or(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidVint { position: self.current_offset() + id_len, tag_id: Some(tag_id) })))?
                 .ok_or(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: Some(tag_id), tag_size: None, partial_data: None })?;
+        if let Some(max_length) = self.max_size_length.filter(|max_length| size_len > *max_length) {
+            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::SizeTooLong { position: self.current_offset(), tag_id, length: size_len, max_length }));
+        }
 
-        if matches!(spec_tag_type, Some(TagDataType::UnsignedInt) | Some(TagDataType::Integer) | Some(TagDataType::Float)) && size > 8 {
+        let overlong_allowed = self.quirks_enabled() && matches!(spec_tag_type, Some(TagDataType::UnsignedInt) | Some(TagDataType::Integer));
+        if matches!(spec_tag_type, Some(TagDataType::UnsignedInt) | Some(TagDataType::Integer) | Some(TagDataType::Float) | Some(TagDataType::Date)) && size > 8 && !overlong_allowed {
             return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{tag_id, position: self.current_offset() }));
         }
 
@@ -271,40 +1270,33 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
 
         let header_len = id_len + size_len;
 
-        if (self.allowed_errors & INVALID_TAG_ID_ERROR == 0) && spec_tag_type.is_none() {
+        let allowed_errors = if self.strict { 0 } else { self.allowed_errors };
+        if (allowed_errors & INVALID_TAG_ID_ERROR == 0) && spec_tag_type.is_none() && !(self.skip_unknown && !self.strict && size.is_known()) {
             return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagId{tag_id, position: self.current_offset() }));
         }
         
-        if (self.allowed_errors & INVALID_HIERARCHY_ERROR == 0) && spec_tag_type.is_some() {
+        if (allowed_errors & INVALID_HIERARCHY_ERROR == 0) && spec_tag_type.is_some() {
             // Do not run check for raw tags    ^^^^^^^^^^^^^^^^^^^^^^^
             if !self.has_determined_doc_path {
                 //Trust that the first tag in the stream is valid (like if the read stream was seeked to this location)
-                let path = <TSpec>::get_path_by_id(tag_id);
+                let path = spec_entry.path;
                 if path.iter().all(|p| matches!(p, PathPart::Id(_))) {
                     //We only know the current path if we read a tag that is non-global
-                    self.tag_stack = path.iter().map(|id| {
+                    self.set_implied_parents(path.iter().map(|id| {
                         match id {
-                            PathPart::Id(id) => {
-                                ProcessingTag { 
-                                    tag: <TSpec>::get_master_tag(*id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was in path, but could not get master tag!", id)),
-                                    size: EBMLSize::Unknown,
-                                    tag_start: 0,
-                                    data_start: 0,
-                                }
-                            },
+                            PathPart::Id(id) => *id,
                             PathPart::Global(_) => unreachable!()
                         }
-                    }).collect();
-                    self.has_determined_doc_path = true;
+                    }));
                 }
             }
-            if self.has_determined_doc_path && !self.validate_tag_path(tag_id) {
-                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::HierarchyError { found_tag_id: tag_id, current_parent_id: self.tag_stack.last().map(|tag| tag.tag.get_id()) }));
+            if self.has_determined_doc_path && !self.validate_tag_path(tag_id, spec_entry.path) {
+                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::HierarchyError { position: self.current_offset(), found_tag_id: tag_id, current_parent_id: self.tag_stack.last().map(|tag| tag.tag.get_id()) }));
             }
         }
 
-        if (self.allowed_errors & OVERSIZED_CHILD_ERROR == 0) && size.is_known() && self.is_invalid_tag_size(header_len + size.value()) {
-            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement{ position: self.current_offset(), tag_id, size: size.value()}));
+        if let (0, Known(size)) = (allowed_errors & OVERSIZED_CHILD_ERROR, size) {
+            check_child_size(&self.tag_stack, self.current_offset(), tag_id, header_len, size).map_err(TagIteratorError::CorruptedFileData)?;
         }
 
         Ok((tag_id, spec_tag_type, size, header_len))
@@ -318,27 +1310,146 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         Ok((tag_id, spec_tag_type, size))
     }
 
-    fn read_tag_data(&mut self, size: usize) -> Result<Option<&[u8]>, TagIteratorError> {
-        self.ensure_capacity(size);
-        if !self.ensure_data_read(size)? {
-            return Ok(None);
+    fn read_tag_data(&mut self, size: usize) -> Result<Option<Range<usize>>, TagIteratorError> {
+        // Grow the buffer along with the data actually read so a corrupt size can't force a huge allocation up front
+        let mut target = size.min(self.buffer.len().max(1));
+        loop {
+            self.ensure_capacity(target);
+            if !self.ensure_data_read(target)? {
+                return Ok(None);
+            }
+            if target == size {
+                break;
+            }
+            target = size.min(target.saturating_mul(2));
         }
 
         self.internal_buffer_position += size;
-        Ok(Some(&self.buffer[(self.internal_buffer_position-size)..self.internal_buffer_position]))
+        Ok(Some((self.internal_buffer_position-size)..self.internal_buffer_position))
+    }
+
+    fn skip_tag_data(&mut self, size: usize) -> Result<bool, TagIteratorError> {
+        let buffered = self.buffered_byte_length - self.internal_buffer_position;
+        if size <= buffered {
+            self.internal_buffer_position += size;
+            return Ok(true);
+        }
+
+        let end = self.current_offset() + size;
+        let remaining = (size - buffered) as u64;
+        self.sync_source(self.buffer_offset.unwrap_or(0) + self.buffered_byte_length)?;
+        self.buffer_offset = Some(end);
+        self.internal_buffer_position = 0;
+        self.buffered_byte_length = 0;
+
+        if let Some(window_remaining) = self.window_remaining.as_mut() {
+            if remaining > *window_remaining {
+                // The tag runs past the end of the window, which is the end of the stream as far as this iterator is concerned
+                *window_remaining = 0;
+                return Ok(false);
+            }
+            *window_remaining -= remaining;
+        }
+
+        match self.skip_source {
+            Some(skip) => skip(&mut self.source, remaining).map_err(|source| TagIteratorError::ReadError { source })?,
+            None => {
+                let skipped = io::copy(&mut self.source.by_ref().take(remaining), &mut io::sink()).map_err(|source| TagIteratorError::ReadError { source })?;
+                if skipped < remaining {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
     }
 
-    fn read_tag(&mut self) -> Result<ProcessingTag<TSpec>, TagIteratorError> {
+    fn read_tag(&mut self) -> Result<Option<TagRead>, TagIteratorError> {
         let tag_start = self.current_offset();
 
-        let (tag_id, spec_tag_type, size) = self.read_valid_tag_header()?;
+        let (tag_id, spec_tag_type, mut size) = self.read_valid_tag_header()?;
+
+        if self.quirks_enabled() && size == Known(0) && matches!(spec_tag_type, Some(TagDataType::Master)) {
+            self.warnings.push(Quirk::ZeroSizedMaster { position: tag_start, tag_id });
+            size = Unknown;
+        }
+
+        if let Known(size) = size {
+            let reason = if self.tag_ids_to_skip.contains(&tag_id) {
+                Some(SkipReason::Filtered)
+            } else if spec_tag_type.is_none() && self.skip_unknown && !self.strict {
+                Some(SkipReason::Unknown)
+            } else if !matches!(spec_tag_type, Some(TagDataType::Master)) && matches!(self.max_unskipped_size, Some(max_size) if size > max_size) {
+                Some(SkipReason::Oversized)
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                let span = TagSpan::new(tag_id, tag_start, self.current_offset(), Known(size));
+                if !self.skip_tag_data(size)? {
+                    return Err(TagIteratorError::UnexpectedEOF { tag_start, tag_id: Some(tag_id), tag_size: Some(size), partial_data: None });
+                }
+                let skipped = SkippedTag { tag_id, position: tag_start, size, reason };
+                if let Some(on_skip) = self.on_skip.as_mut() {
+                    on_skip(&skipped);
+                }
+                if self.emit_skipped {
+                    self.pending_skipped = Some((skipped, span));
+                }
+                return Ok(None);
+            }
+
+            if let Some(max_size) = self.max_tag_size {
+                if size > max_size && !matches!(spec_tag_type, Some(TagDataType::Master)) {
+                    if !self.skip_tag_data(size)? {
+                        return Err(TagIteratorError::UnexpectedEOF { tag_start, tag_id: Some(tag_id), tag_size: Some(size), partial_data: None });
+                    }
+                    return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { position: tag_start, tag_id, size, max_size }));
+                }
+            }
+        }
 
         let data_start = self.current_offset();
-        let raw_data = if matches!(spec_tag_type, Some(TagDataType::Master)) {
-            &[]
+        if !self.crc32_checks.is_empty() {
+            self.update_crc32(tag_start, (self.internal_buffer_position - (data_start - tag_start))..self.internal_buffer_position);
+        }
+        // The header has to be copied now, since reading the data may move it out of the buffer
+        let mut encoding = if self.keep_encoding && !matches!(spec_tag_type, Some(TagDataType::Master)) {
+            self.buffer[(self.internal_buffer_position - (data_start - tag_start))..self.internal_buffer_position].to_vec()
+        } else {
+            Vec::new()
+        };
+        let data = if matches!(spec_tag_type, Some(TagDataType::Master)) {
+            0..0
         } else if let Known(size) = size {
             if let Some(data) = self.read_tag_data(size)? {
-                data
+                if self.keep_encoding {
+                    encoding.extend_from_slice(&self.buffer[data.clone()]);
+                    self.encodings.push_back((tag_start, encoding));
+                }
+                if !self.crc32_checks.is_empty() {
+                    self.update_crc32(data_start, data.clone());
+                }
+                if self.verify_crc32 && tag_id == CRC32 && size == 4 {
+                    if let Some(parent) = self.tag_stack.last().filter(|parent| parent.data_start == tag_start) {
+                        if let Some(end) = parent.data_end() {
+                            let expected = u32::from_le_bytes(<[u8; 4]>::try_from(&self.buffer[data.clone()]).expect("data should be 4 bytes"));
+                            self.crc32_checks.push(Crc32Check { tag_id: parent.tag.get_id(), position: parent.tag_start, end, next: self.current_offset(), expected, crc: 0 });
+                        }
+                    }
+                }
+                if size > 8 && matches!(spec_tag_type, Some(TagDataType::UnsignedInt) | Some(TagDataType::Integer)) {
+                    // Only reachable in quirks mode - the header check rejects wide integers otherwise
+                    let value_start = data.end - 8;
+                    let padding = if matches!(spec_tag_type, Some(TagDataType::Integer)) && self.buffer[value_start] & 0x80 != 0 { 0xff } else { 0x00 };
+                    if self.buffer[data.start..value_start].iter().any(|b| *b != padding) {
+                        return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{ tag_id, position: tag_start }));
+                    }
+                    self.warnings.push(Quirk::OverlongInteger { position: tag_start, tag_id, size });
+                    value_start..data.end
+                } else {
+                    data
+                }
             } else {
                 return Err(TagIteratorError::UnexpectedEOF { tag_start, tag_id: Some(tag_id), tag_size: Some(size), partial_data: Some(self.buffer[self.internal_buffer_position..].to_vec()) });
             }
@@ -346,38 +1457,87 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
             return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{ tag_id, position: tag_start }));
         };
 
-        let tag = match spec_tag_type {
+        if !self.has_read_first_tag && self.tag_stack.is_empty() {
+            self.has_read_first_tag = true;
+            if tag_id == EBML {
+                let data_len = if let Known(size) = size { size } else { 0 };
+                let mut header = EbmlHeader::with_len(data_start - tag_start + data_len);
+                // Headers that aren't "Master" tags in `<TSpec>` are read in one go, otherwise their children are applied as they are read
+                if matches!(spec_tag_type, Some(TagDataType::Master)) || header.apply_children(&self.buffer[data.clone()]).is_ok() {
+                    self.partial_header = Some(header);
+                }
+            } else if !self.supported_doc_types.is_empty() {
+                // The tag itself is still emitted after the error
+                self.emission_queue.push_back(Err(TagIteratorError::UnsupportedDocType { doc_type: None }));
+            }
+        } else if self.tag_stack.len() == 1 && self.tag_stack[0].tag.get_id() == EBML {
+            // Values that can't be read keep their defaults, and produce an error when the tag is decoded
+            if let Some(header) = self.partial_header.as_mut() {
+                let _ = header.apply(tag_id, &self.buffer[data.clone()]);
+            }
+        }
+
+        if tag_id == EBML_MAX_ID_LENGTH || tag_id == EBML_MAX_SIZE_LENGTH {
+            if let Ok(max_length) = tools::arr_to_u64(&self.buffer[data.clone()]) {
+                let max_length = Some(usize::try_from(max_length).unwrap_or(usize::MAX));
+                if tag_id == EBML_MAX_ID_LENGTH {
+                    self.max_id_length = max_length;
+                } else {
+                    self.max_size_length = max_length;
+                }
+            }
+        }
+
+        Ok(Some(TagRead { tag_id, spec_tag_type, size, tag_start, data_start, data }))
+    }
+
+    fn decode_tag(&self, read: &TagRead) -> Result<TSpec, TagIteratorError> {
+        let tag_id = read.tag_id;
+        let raw_data = &self.buffer[read.data.clone()];
+        let tag = match read.spec_tag_type {
             Some(TagDataType::Master) => {
                 TSpec::get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id))
             },
             Some(TagDataType::UnsignedInt) => {
-                let val = tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                let val = tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: read.tag_start, tag_id, problem: e })?;
                 TSpec::get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was unsigned int, but could not get tag!", tag_id))
             },
             Some(TagDataType::Integer) => {
-                let val = tools::arr_to_i64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                let val = tools::arr_to_i64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: read.tag_start, tag_id, problem: e })?;
                 TSpec::get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was integer, but could not get tag!", tag_id))
             },
             Some(TagDataType::Utf8) => {
-                let val = String::from_utf8(raw_data.to_vec()).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(raw_data.to_vec(), e) })?;
+                let val = match (read_string::<TSpec>(tag_id, raw_data), self.utf8_handling) {
+                    (Ok(val), _) => val.to_owned(),
+                    (Err(_), Utf8Handling::Lossy) => read_string_lossy::<TSpec>(tag_id, raw_data),
+                    (Err(_), Utf8Handling::Raw) => return Ok(TSpec::get_raw_tag(tag_id, raw_data)),
+                    (Err(e), Utf8Handling::Strict) => return Err(TagIteratorError::CorruptedTagData { position: read.tag_start, tag_id, problem: e }),
+                };
                 TSpec::get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", tag_id))
             },
             Some(TagDataType::Binary) => {
                 TSpec::get_binary_tag(tag_id, raw_data).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", tag_id))
             },
             Some(TagDataType::Float) => {
-                let val = tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                let val = tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: read.tag_start, tag_id, problem: e })?;
                 TSpec::get_float_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was float, but could not get tag!", tag_id))
             },
+            Some(TagDataType::Date) => {
+                let val = tools::arr_to_date(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: read.tag_start, tag_id, problem: e })?;
+                TSpec::get_date_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was date, but could not get tag!", tag_id))
+            },
             None => {
                 TSpec::get_raw_tag(tag_id, raw_data)
             }
         };
-
-        Ok(ProcessingTag { tag, size, tag_start, data_start })
+        Ok(tag)
     }
 
-    fn read_tag_checked(&mut self) -> Option<Result<ProcessingTag<TSpec>, TagIteratorError>> {
+    fn read_tag_checked(&mut self) -> Option<Result<Option<TagRead>, TagIteratorError>> {
+        if self.ignore_remaining {
+            return None;
+        }
+
         if self.internal_buffer_position == self.buffered_byte_length {
             //If we've already consumed the entire internal buffer
             //ensure there is nothing else in the data source before returning `None`
@@ -399,60 +1559,121 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         Some(self.read_tag())
     }
 
-    fn read_next(&mut self) {
+    ///
+    /// Reads the next tag into the emission queue.  Returns `true` if a tag was skipped, in which case nothing new may have been queued even though the source has more data.
+    ///
+    /// If `borrow` is set, utf8, binary, and raw tags are not decoded.  They are left in `pending_borrowed` (to be emitted once the queue is empty) so their data can be lent out of the buffer.
+    ///
+    fn read_next(&mut self, borrow: bool) -> bool {
+        self.report_progress(false);
+
         //If we have reached the known end of any open master tags, queue that tag and all children to emit ends
-        let ended_tag_index = self.tag_stack.iter().position(|tag| matches!(tag.size, Known(size) if self.current_offset() >= tag.data_start + size));
+        let ended_tag_index = self.tag_stack.iter().position(|tag| tag.data_end().is_some_and(|end| self.current_offset() >= end));
         if let Some(index) = ended_tag_index {
-            self.emission_queue.extend(self.tag_stack.drain(index..).map(|t| Ok((t.tag, t.tag_start))).rev());
+            self.emission_queue.extend(self.tag_stack.drain(index..).map(|t| Ok(t.into_emitted())).rev());
+            // Don't read ahead of the ends - the next tag may be lent out of the buffer, and a buffering master wants to stop here
+            return false;
         }
 
+        let tag_start = self.current_offset();
         if let Some(next_read) = self.read_tag_checked() {
-            if let Ok(next_tag) = &next_read {
-                while matches!(self.tag_stack.last(), Some(open_tag) if open_tag.size == Unknown) {
-                    let open_tag = self.tag_stack.last().unwrap();
-                    let previous_tag_ended = open_tag.is_ended_by(next_tag.tag.get_id());
-        
-                    if previous_tag_ended {
-                        let t = self.tag_stack.pop().unwrap();
-                        self.emission_queue.push_back(Ok((t.tag, t.tag_start)));
+            let next_read = match next_read.transpose() {
+                Some(next_read) => next_read,
+                None if borrow && self.pending_skipped.is_some() => return false,
+                None => {
+                    self.pending_skipped = None;
+                    return true;
+                },
+            };
+
+            let (next_read, tag) = match next_read.and_then(|read| {
+                let tag = if borrow && read.is_borrowable() { None } else { Some(self.decode_tag(&read)?) };
+                Ok((read, tag))
+            }) {
+                Ok(next) => next,
+                Err(err) => {
+                    if self.is_trailing_garbage(&err, tag_start) {
+                        self.warnings.push(Quirk::TrailingGarbage { position: tag_start });
+                        self.ignore_remaining = true;
                     } else {
-                        break;
+                        self.emission_queue.push_back(Err(err));
                     }
+                    return false;
+                }
+            };
+
+            while matches!(self.tag_stack.last(), Some(open_tag) if open_tag.size == Unknown) {
+                let open_tag = self.tag_stack.last().unwrap();
+                let previous_tag_ended = open_tag.is_ended_by(next_read.tag_id);
+    
+                if previous_tag_ended {
+                    let t = self.tag_stack.pop().unwrap();
+                    self.emission_queue.push_back(Ok(t.into_emitted()));
+                } else {
+                    break;
                 }
+            }
 
-                if let Some(Master::Start) = next_tag.tag.as_master() {
-                    let tag_id = next_tag.tag.get_id();
+            let tag = match tag {
+                Some(tag) => tag,
+                None => {
+                    self.pending_borrowed = Some(next_read);
+                    return false;
+                }
+            };
 
-                    self.tag_stack.push(ProcessingTag {
-                        tag: TSpec::get_master_tag(tag_id, Master::End).unwrap(),
-                        size: next_tag.size,
-                        tag_start: next_tag.tag_start,
-                        data_start: next_tag.data_start,
-                    });
+            if let Some(Master::Start) = tag.as_master() {
+                let tag_id = next_read.tag_id;
+                if self.tag_stack.len() >= self.max_depth {
+                    self.emission_queue.push_back(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxDepthExceeded { position: next_read.tag_start, tag_id, max_depth: self.max_depth })));
+                    return false;
+                }
 
-                    if self.tag_ids_to_buffer.contains(&tag_id) {
-                        self.buffer_master(tag_id);
-                        return;
-                    }
+                self.tag_stack.push(ProcessingTag {
+                    tag: TSpec::get_master_tag(tag_id, Master::End).unwrap(),
+                    size: next_read.size,
+                    tag_start: next_read.tag_start,
+                    data_start: next_read.data_start,
+                });
+
+                if self.tag_ids_to_buffer.contains(&tag_id) {
+                    self.buffer_master(tag_id);
+                    return false;
                 }
             }
 
-            self.emission_queue.push_back(next_read.map(|r| (r.tag, r.tag_start)));
+            self.emission_queue.push_back(Ok((tag, next_read.span())));
         } else {
+            self.report_progress(true);
             while let Some(tag) = self.tag_stack.pop() {
-                self.emission_queue.push_back(Ok((tag.tag, tag.tag_start)));
+                self.emission_queue.push_back(Ok(tag.into_emitted()));
             }
         }
+        false
+    }
+
+    ///
+    /// Checks whether `err`, found reading a tag at `tag_start`, should be tolerated as garbage after the last top-level tag in quirks mode.
+    ///
+    fn is_trailing_garbage(&self, err: &TagIteratorError, tag_start: usize) -> bool {
+        self.quirks_enabled()
+            && tag_start > 0
+            && self.tag_stack.is_empty()
+            && matches!(err, TagIteratorError::CorruptedFileData(_) | TagIteratorError::CorruptedTagData { .. } | TagIteratorError::UnexpectedEOF { .. })
     }
 
     fn buffer_master(&mut self, tag_id: u64) {
         let tag_start = self.current_offset();
+        let span = self.tag_stack.last().map(ProcessingTag::span).unwrap_or_default();
         let pre_queue_len = self.emission_queue.len();
+        let exceeds_max_size = |size: usize, max_size: Option<usize>| matches!(max_size, Some(max_size) if size > max_size);
+        let mut oversized = exceeds_max_size(span.data_len.unwrap_or(0), self.max_buffered_size);
 
         let mut position = pre_queue_len;
         'endTagSearch: loop {
+
             if position >= self.emission_queue.len() {
-                self.read_next();
+                while self.read_next(false) && position >= self.emission_queue.len() {}
     
                 if position >= self.emission_queue.len() {
                     self.emission_queue.push_back(Err(TagIteratorError::UnexpectedEOF{ tag_start, tag_id: Some(tag_id), tag_size: None, partial_data: None }));
@@ -473,50 +1694,406 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
                 }
                 position += 1;
             }
+            oversized = oversized || exceeds_max_size(self.current_offset() - tag_start, self.max_buffered_size);
+            if oversized {
+                // The children are discarded as they are read, so memory use stays bounded
+                self.emission_queue.truncate(pre_queue_len);
+                position = pre_queue_len;
+            }
         }
 
-        let mut children = self.emission_queue.split_off(pre_queue_len);
-        let split_to = position - pre_queue_len;
-        if children.get(split_to).unwrap().is_ok() {
-            let remaining = children.split_off(split_to).into_iter().skip(1);
-            let full_tag = Self::roll_up_children(tag_id, children.into_iter().map(|c| c.unwrap().0).collect());
-            self.emission_queue.push_back(Ok((full_tag, tag_start)));
-            self.emission_queue.extend(remaining);
+        if oversized {
+            let max_size = self.max_buffered_size.expect("only a limit can be exceeded");
+            let ended = self.emission_queue.get(position).unwrap().is_ok();
+            self.emission_queue.drain(pre_queue_len..(if ended { position + 1 } else { position }));
+            self.emission_queue.insert(pre_queue_len, Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxBufferedSizeExceeded { position: span.offset, tag_id, max_size })));
+            return;
+        }
+
+        // Drain the children in place rather than splitting the queue, so the queue's allocation is reused
+        if self.emission_queue.get(position).unwrap().is_ok() {
+            let children = self.emission_queue.drain(pre_queue_len..position).map(|c| c.unwrap().0).collect();
+            let full_tag = Self::roll_up_children(tag_id, children);
+            self.emission_queue[pre_queue_len] = Ok((full_tag, span));
         } else {
-            self.emission_queue.extend(children.drain(split_to..).take(1));
+            self.emission_queue.drain(pre_queue_len..position);
+            self.emission_queue.truncate(pre_queue_len + 1);
         }
     }
 
+    ///
+    /// Nests the flat list of tags read inside a buffered master into [`Master::Full`] tags, using an explicit stack so that deeply nested data can't overflow the call stack.
+    ///
     fn roll_up_children(tag_id: u64, children: Vec<TSpec>) -> TSpec {
-        let mut rolled_children = Vec::new();
-
-        let mut iter = children.into_iter();
-        while let Some(child) = iter.next() {
-            if let Some(Master::Start) = child.as_master() {
-                let child_id = child.get_id();
-                let subchildren = iter.by_ref().take_while(|c| !matches!(c.as_master(), Some(Master::End)) || c.get_id() != child_id).collect();
-                rolled_children.push(Self::roll_up_children(child_id, subchildren));
-            } else {
-                rolled_children.push(child);
+        // The masters that are still open, each with the children rolled up so far (innermost last)
+        let mut open: Vec<(u64, Vec<TSpec>)> = vec![(tag_id, Vec::new())];
+        for child in children {
+            match child.as_master() {
+                Some(Master::Start) => open.push((child.get_id(), Vec::new())),
+                Some(Master::End) if open.len() > 1 && open.last().map(|(id, _)| *id) == Some(child.get_id()) => {
+                    let (id, rolled) = open.pop().unwrap();
+                    open.last_mut().unwrap().1.push(Self::full_master(id, rolled));
+                },
+                _ => open.last_mut().unwrap().1.push(child),
             }
         }
 
-        TSpec::get_master_tag(tag_id, Master::Full(rolled_children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id))
+        while open.len() > 1 {
+            let (id, rolled) = open.pop().unwrap();
+            open.last_mut().unwrap().1.push(Self::full_master(id, rolled));
+        }
+        let (id, rolled) = open.pop().unwrap();
+        Self::full_master(id, rolled)
     }
 
-    #[inline(always)]
-    fn validate_tag_path(&self, tag_id: u64) -> bool {
-        validate_tag_path::<TSpec>(tag_id, self.tag_stack.iter().map(|p| (p.tag.get_id(), p.size)))
+    fn full_master(tag_id: u64, children: Vec<TSpec>) -> TSpec {
+        TSpec::get_master_tag(tag_id, Master::Full(children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id))
     }
 
     #[inline(always)]
-    fn is_invalid_tag_size(&self, size: usize) -> bool {
-        self.tag_stack.iter().filter(|p| p.size.is_known()).any(|t| {
-            (t.data_start + t.size.value()) < (self.current_offset() + size)
+    fn validate_tag_path(&self, tag_id: u64, path: &[PathPart]) -> bool {
+        validate_path::<TSpec>(tag_id, path, self.tag_stack.iter().map(|p| (p.tag.get_id(), p.size)))
+    }
+}
+
+///
+/// Finds the first position in `data` at which any of `ids` (as big-endian bytes) appears in full.
+///
+fn find_any_id(data: &[u8], ids: &[Vec<u8>]) -> Option<usize> {
+    let mut first_bytes = [false; 256];
+    for id in ids {
+        first_bytes[id[0] as usize] = true;
+    }
+
+    let mut start = 0;
+    while let Some(offset) = data[start..].iter().position(|b| first_bytes[*b as usize]) {
+        let candidate = start + offset;
+        if ids.iter().any(|id| data[candidate..].starts_with(id)) {
+            return Some(candidate);
+        }
+        start = candidate + 1;
+    }
+    None
+}
+
+///
+/// Checks a [`Master::Full`] tag and all of its descendants for missing mandatory children, reporting the first incomplete tag found.
+///
+fn find_missing_children<TSpec>(tag: &TSpec, position: usize) -> Option<CorruptedFileError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let children = match tag.as_master() {
+        Some(Master::Full(children)) => children,
+        _ => return None,
+    };
+
+    let tag_id = tag.get_id();
+    let missing_tag_ids: Vec<u64> = TSpec::get_mandatory_children(tag_id).iter()
+        .copied()
+        .filter(|id| !children.iter().any(|child| child.get_id() == *id))
+        .collect();
+    if !missing_tag_ids.is_empty() {
+        return Some(CorruptedFileError::MissingChildElement { position, tag_id, missing_tag_ids });
+    }
+
+    children.iter().find_map(|child| find_missing_children(child, position))
+}
+
+impl<'a, TSpec> TagIterator<Box<dyn Read + 'a>, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagIterator`] reading from a boxed trait object, so iterators over different kinds of sources share one type.
+    ///
+    /// This is equivalent to `TagIterator::new(Box::new(source) as Box<dyn Read>, tags_to_buffer)`.  Use a `Box<dyn ReadSeek>` (see [`ReadSeek`](crate::iterator::ReadSeek)) with [`Self::new()`] instead for sources that should be seekable.
+    ///
+    pub fn boxed<S: Read + 'a>(source: S, tags_to_buffer: &[TSpec]) -> Self {
+        TagIterator::new(Box::new(source), tags_to_buffer)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<TSpec> TagIterator<io::Cursor<memmap2::Mmap>, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagIterator`] reading the file at `path` through a memory map.
+    ///
+    /// Reading from the map avoids a system call for every fill of the internal buffer, and skipped tags (see [`Self::seek_when_skipping()`], which is enabled) cost nothing at all, since the skipped pages are never touched.  For parsing without copying tag data out of the map, pass the mapped bytes to a [`TagSliceIterator`] instead: `TagSliceIterator::new(iter.get_ref().get_ref(), tags_to_buffer)`.
+    ///
+    /// ## Safety
+    ///
+    /// The file must not be modified or truncated (by this or any other process) while the iterator exists.  Changes to a mapped file are visible through the map, so they can change data the iterator has already validated, and truncation can make reads fault.  See [`memmap2::Mmap::map()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] if the file can't be opened or mapped.
+    ///
+    pub unsafe fn from_mmap<P: AsRef<std::path::Path>>(path: P, tags_to_buffer: &[TSpec]) -> Result<Self, TagIteratorError> {
+        let file = std::fs::File::open(path).map_err(|source| TagIteratorError::ReadError { source })?;
+        let map = memmap2::Mmap::map(&file).map_err(|source| TagIteratorError::ReadError { source })?;
+        let mut iter = TagIterator::new(io::Cursor::new(map), tags_to_buffer);
+        iter.seek_when_skipping();
+        Ok(iter)
+    }
+}
+
+impl<R: Read + Seek, TSpec> TagIterator<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagIterator<TSpec>`] instance that only reads `len` bytes of `source`, starting `start` bytes into it.
+    ///
+    /// The end of the window is treated as the end of the stream, so EBML data embedded in another format (or a single `Segment` of a file containing several) can be parsed in isolation.  A tag extending past the end of the window produces the same [`TagIteratorError::UnexpectedEOF`] error as a truncated file.  Offsets reported by the iterator are relative to the start of the window.  The window is dropped by [`Self::reset()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] if `source` can't be seeked to `start`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable::specs::EbmlTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// // EBML data surrounded by bytes from some other container format
+    /// let data = vec![0xff, 0xff, 0x81, 0x81, 0x01, 0x82, 0x80, 0xff];
+    /// let iter: TagIterator<_, EmptySpec> = TagIterator::with_window(Cursor::new(data), 2, 5, &[]).unwrap();
+    /// assert_eq!(vec![0x81, 0x82], iter.map(|tag| tag.unwrap().get_id()).collect::<Vec<_>>());
+    /// ```
+    ///
+    pub fn with_window(mut source: R, start: u64, len: u64, tags_to_buffer: &[TSpec]) -> Result<Self, TagIteratorError> {
+        source.seek(SeekFrom::Start(start)).map_err(|source| TagIteratorError::ReadError { source })?;
+        let mut iter = TagIterator::new(source, tags_to_buffer);
+        iter.window_remaining = Some(len);
+        Ok(iter)
+    }
+
+    ///
+    /// Moves the iterator to `offset` in its source, so that reading resumes with the tag starting there.
+    ///
+    /// This is useful for jumping straight to elements listed in an index, such as the positions in a Matroska `SeekHead` or `Cues` element, without reading everything before them.  `offset` uses the same coordinates as [`Self::last_emitted_tag_offset()`] (relative to the start of the window for iterators created with [`Self::with_window()`]).  All parsing state is discarded: "Master" tags that were open are not ended, and queued or buffered tags are dropped.  Like an iterator created over a source that was seeked to the middle of a document, the parents of the first tag read are implied from the specification, so their [`Master::End`] variants are emitted once they end.  Configuration is kept, as are limits declared by the document's EBML header.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] if the source can't be seeked.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable::specs::EbmlTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x80, 0x82, 0x80, 0x83, 0x80]), &[]);
+    /// assert_eq!(Some(0x81), iter.next().map(|tag| tag.unwrap().get_id()));
+    ///
+    /// iter.seek_to(4).unwrap();
+    /// assert_eq!(Some(0x83), iter.next().map(|tag| tag.unwrap().get_id()));
+    /// iter.seek_to(2).unwrap();
+    /// assert_eq!(Some(0x82), iter.next().map(|tag| tag.unwrap().get_id()));
+    /// ```
+    ///
+    pub fn seek_to(&mut self, offset: usize) -> Result<(), TagIteratorError> {
+        let read = self.buffer_offset.unwrap_or(0) + self.buffered_byte_length;
+        if self.shared_source.is_none() {
+            // Forked iterators seek to their position before every read, but other sources are wherever the last read left them
+            let distance = i64::try_from(offset as i128 - read as i128).map_err(|_| TagIteratorError::ReadError { source: io::Error::new(io::ErrorKind::InvalidInput, "offset is too far away to seek to") })?;
+            self.source.seek(SeekFrom::Current(distance)).map_err(|source| TagIteratorError::ReadError { source })?;
+        }
+        if let Some(remaining) = self.window_remaining.as_mut() {
+            *remaining = (*remaining + read as u64).saturating_sub(offset as u64);
+        }
+
+        self.buffer_offset = Some(offset);
+        self.buffered_byte_length = 0;
+        self.internal_buffer_position = 0;
+        self.clear_parse_state();
+        self.last_progress = offset;
+        Ok(())
+    }
+
+    ///
+    /// Returns the length of the document being read, for reporting progress along with [`Self::bytes_read()`].
+    ///
+    /// This is the number of bytes from where the iterator started reading to the end of `source` (or to the end of the window, for iterators created with [`Self::with_window()`]).  The source is seeked to find its end and then returned to where it was.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] if the source can't be seeked.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x81, 0x02]), &[]);
+    /// iter.next();
+    /// assert_eq!((3, 6), (iter.bytes_read(), iter.total_len().unwrap()));
+    /// ```
+    ///
+    pub fn total_len(&mut self) -> Result<u64, TagIteratorError> {
+        let read = (self.buffer_offset.unwrap_or(0) + self.buffered_byte_length) as u64;
+        if let Some(remaining) = self.window_remaining {
+            return Ok(read + remaining);
+        }
+
+        let position = self.source.stream_position().map_err(|source| TagIteratorError::ReadError { source })?;
+        let end = self.source.seek(SeekFrom::End(0)).map_err(|source| TagIteratorError::ReadError { source })?;
+        self.source.seek(SeekFrom::Start(position)).map_err(|source| TagIteratorError::ReadError { source })?;
+        let origin = match self.shared_source {
+            Some((origin, _)) => origin,
+            None => position.checked_sub(read).ok_or_else(|| TagIteratorError::ReadError { source: io::Error::new(io::ErrorKind::InvalidInput, "source is positioned before data the iterator already read") })?,
+        };
+        Ok(end.saturating_sub(origin))
+    }
+
+    ///
+    /// Instructs the iterator to seek over the contents of skipped tags rather than reading them.
+    ///
+    /// Tags can be configured to be skipped using [`Self::skip_tags()`].  Seeking can make skipping large tags (such as clusters when only reading metadata) dramatically cheaper.  Note that if a skipped tag claims to extend past the end of the source, the iterator will simply report the end of the stream on its next read rather than returning an [`TagIteratorError::UnexpectedEOF`] error.
+    ///
+    pub fn seek_when_skipping(&mut self) {
+        self.skip_source = Some(|source, length| {
+            let length = i64::try_from(length).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "skipped tag is too large to seek over"))?;
+            source.seek(SeekFrom::Current(length)).map(|_| ())
+        });
+    }
+
+    ///
+    /// Creates a copy of this iterator reading from `source`, so that one copy can read ahead while the other continues from the current position.
+    ///
+    /// `source` must be another handle to the same data, such as a clone of a [`std::io::Cursor`] or the result of [`std::fs::File::try_clone()`].  The fork continues from exactly where this iterator is, with the same open tags, queued tags and configuration, except that the [`Self::on_skipped_tag()`] callback is not copied.  Each copy is independent: for example, a fork can look for a Matroska `Cues` element near the end of a file and then be dropped without affecting this iterator.
+    ///
+    /// Because handles like [`std::fs::File::try_clone()`] share a single position in the file, both iterators seek to their own position before every read from their source after forking.  Both iterators also use [`Self::seek_when_skipping()`] if this iterator does.  [`Self::reset()`] stops this behavior for the iterator being reset.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] if the current position of this iterator's source can't be determined.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable::specs::EbmlTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x80, 0x82, 0x80, 0x83, 0x80]), &[]);
+    /// assert_eq!(Some(0x81), iter.next().map(|tag| tag.unwrap().get_id()));
+    ///
+    /// let source = iter.get_ref().clone();
+    /// let lookahead = iter.fork_with(source).unwrap();
+    /// assert_eq!(vec![0x82, 0x83], lookahead.map(|tag| tag.unwrap().get_id()).collect::<Vec<_>>());
+    /// assert_eq!(Some(0x82), iter.next().map(|tag| tag.unwrap().get_id()));
+    /// ```
+    ///
+    pub fn fork_with(&mut self, source: R) -> Result<Self, TagIteratorError> {
+        let origin = match self.shared_source {
+            Some((origin, _)) => origin,
+            None => {
+                let read = (self.buffer_offset.unwrap_or(0) + self.buffered_byte_length) as u64;
+                let position = self.source.stream_position().map_err(|source| TagIteratorError::ReadError { source })?;
+                position.checked_sub(read).ok_or_else(|| TagIteratorError::ReadError { source: io::Error::new(io::ErrorKind::InvalidInput, "source is positioned before data the iterator already read") })?
+            }
+        };
+        let seek: SeekSource<R> = |source, position| source.seek(SeekFrom::Start(position)).map(|_| ());
+        self.shared_source = Some((origin, seek));
+
+        Ok(TagIterator {
+            source,
+            tag_ids_to_buffer: self.tag_ids_to_buffer.clone(),
+            tag_ids_to_skip: self.tag_ids_to_skip.clone(),
+            recovery_ids: self.recovery_ids.clone(),
+            skip_source: self.skip_source,
+            shared_source: self.shared_source,
+            window_remaining: self.window_remaining,
+            max_lengths: self.max_lengths,
+            max_id_length: self.max_id_length,
+            max_size_length: self.max_size_length,
+            skip_unknown: self.skip_unknown,
+            max_unskipped_size: self.max_unskipped_size,
+            on_skip: None,
+            on_progress: None,
+            allowed_errors: self.allowed_errors,
+            strict: self.strict,
+            quirks: self.quirks,
+            max_depth: self.max_depth,
+            max_tag_size: self.max_tag_size,
+            max_buffered_size: self.max_buffered_size,
+            keep_encoding: self.keep_encoding,
+            utf8_handling: self.utf8_handling,
+            verify_crc32: self.verify_crc32,
+            resync_on_error: self.resync_on_error,
+            emit_skipped: self.emit_skipped,
+            supported_doc_types: self.supported_doc_types.clone(),
+            buffer: self.buffer.clone(),
+            buffered_byte_length: self.buffered_byte_length,
+            buffer_offset: self.buffer_offset,
+            internal_buffer_position: self.internal_buffer_position,
+            tag_stack: self.tag_stack.clone(),
+            emission_queue: self.emission_queue.iter().map(|item| match item {
+                Ok(tag) => Ok(tag.clone()),
+                Err(err) => Err(duplicate_error(err)),
+            }).collect(),
+            pending_borrowed: self.pending_borrowed.clone(),
+            pending_skipped: self.pending_skipped,
+            last_emitted_tag_span: self.last_emitted_tag_span,
+            emitted_path: self.emitted_path.clone(),
+            has_determined_doc_path: self.has_determined_doc_path,
+            spec_cache: self.spec_cache.clone(),
+            strict_children: self.strict_children.clone(),
+            warnings: self.warnings.clone(),
+            ignore_remaining: self.ignore_remaining,
+            encodings: self.encodings.clone(),
+            last_encoding: self.last_encoding.clone(),
+            crc32_checks: self.crc32_checks.clone(),
+            has_read_first_tag: self.has_read_first_tag,
+            partial_header: self.partial_header.clone(),
+            ebml_header: self.ebml_header.clone(),
+            last_progress: self.last_progress,
         })
     }
 }
 
+impl<R: Read + Seek + Clone, TSpec> TagIterator<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Creates a copy of this iterator reading from a clone of its source.  See [`Self::fork_with()`] for details.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] if the current position of this iterator's source can't be determined.
+    ///
+    pub fn fork(&mut self) -> Result<Self, TagIteratorError> {
+        let source = self.source.clone();
+        self.fork_with(source)
+    }
+}
+
+///
+/// Copies an error queued for emission into a forked iterator.  [`io::Error`]s can't be cloned, so they are recreated from their kind and message.
+///
+fn duplicate_error(err: &TagIteratorError) -> TagIteratorError {
+    match err {
+        TagIteratorError::CorruptedFileData(err) => TagIteratorError::CorruptedFileData(err.clone()),
+        TagIteratorError::UnexpectedEOF { tag_start, tag_id, tag_size, partial_data } => TagIteratorError::UnexpectedEOF { tag_start: *tag_start, tag_id: *tag_id, tag_size: *tag_size, partial_data: partial_data.clone() },
+        TagIteratorError::CorruptedTagData { position, tag_id, problem } => TagIteratorError::CorruptedTagData { position: *position, tag_id: *tag_id, problem: problem.clone() },
+        TagIteratorError::UnsupportedDocType { doc_type } => TagIteratorError::UnsupportedDocType { doc_type: doc_type.clone() },
+        TagIteratorError::ReadError { source } => TagIteratorError::ReadError { source: io::Error::new(source.kind(), source.to_string()) },
+    }
+}
+
 impl<R: Read, TSpec> Iterator for TagIterator<R, TSpec>
     where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
@@ -524,12 +2101,196 @@ impl<R: Read, TSpec> Iterator for TagIterator<R, TSpec>
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.emission_queue.is_empty() {
-            self.read_next();
+            if let Some(read) = self.pending_borrowed.take() {
+                let tag = self.decode_tag(&read).map(|tag| (tag, read.span()));
+                self.emission_queue.push_back(tag);
+            }
         }
+        while self.emission_queue.is_empty() && self.read_next(false) {}
         let next_item = self.emission_queue.pop_front();
-        if let Some(Ok(ref tuple)) = next_item {
-            self.last_emitted_tag_offset = tuple.1;
+        match next_item {
+            Some(Ok(ref tuple)) => self.record_emission(&tuple.0, tuple.1),
+            Some(Err(ref err)) => self.resync_after(err),
+            None => {},
         }
         next_item.map(|r| r.map(|t| t.0))
     }
 }
+
+///
+/// An iterator that emits each tag along with its [`TagSpan`].  Created by [`TagIterator::with_positions()`].
+///
+pub struct TagPositions<R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iter: TagIterator<R, TSpec>,
+}
+
+impl<R: Read, TSpec> TagPositions<R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Consumes self and returns the wrapped [`TagIterator`].
+    ///
+    pub fn into_inner(self) -> TagIterator<R, TSpec> {
+        self.iter
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_mut(&mut self) -> &mut TagIterator<R, TSpec> {
+        &mut self.iter
+    }
+
+    ///
+    /// Gets a reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_ref(&self) -> &TagIterator<R, TSpec> {
+        &self.iter
+    }
+}
+
+impl<R: Read, TSpec> Iterator for TagPositions<R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<(TSpec, TagSpan), TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag = self.iter.next()?;
+        Some(tag.map(|tag| (tag, self.iter.last_emitted_tag_span())))
+    }
+}
+
+///
+/// An iterator that emits each occurrence of a "Master" tag as a [`Master::Full`] tree.  Created by [`TagIterator::into_subtree_iter()`].
+///
+pub struct TagSubtrees<R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iter: TagIterator<R, TSpec>,
+    master_id: u64,
+}
+
+impl<R: Read, TSpec> TagSubtrees<R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Consumes self and returns the wrapped [`TagIterator`].  The tag passed to [`TagIterator::into_subtree_iter()`] is still buffered.
+    ///
+    pub fn into_inner(self) -> TagIterator<R, TSpec> {
+        self.iter
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_mut(&mut self) -> &mut TagIterator<R, TSpec> {
+        &mut self.iter
+    }
+
+    ///
+    /// Gets a reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_ref(&self) -> &TagIterator<R, TSpec> {
+        &self.iter
+    }
+}
+
+impl<R: Read, TSpec> Iterator for TagSubtrees<R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<TSpec, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next_borrowed()? {
+                Ok(BorrowedTag::Tag(tag)) if tag.get_id() == self.master_id && matches!(tag.as_master(), Some(Master::Full(_))) => return Some(Ok(tag)),
+                Ok(_) => {},
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+///
+/// An iterator over the tags of an in-memory document whose utf8, binary, and raw tags borrow their data from the document itself.
+///
+/// Unlike [`TagIterator::next_borrowed()`], the emitted tags refer to the source slice rather than to the iterator's buffer, so this is a regular [`Iterator`] and tags can be kept while reading further.  No allocations are made for utf8 or binary data, which makes this the cheapest way to parse documents that are already in memory (e.g. a memory-mapped file).  Tags inside masters listed in `tags_to_buffer` are still collected into owned [`Master::Full`] tags.  Tags are converted into regular `TSpec` values with [`BorrowedTag::into_owned()`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::iterator::{BorrowedTag, TagSliceIterator};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = vec![0x81, 0x82, 0x01, 0x02, 0x82, 0x80];
+/// let iter: TagSliceIterator<EmptySpec> = TagSliceIterator::new(&data, &[]);
+/// let values: Vec<&[u8]> = iter.map(|tag| match tag.unwrap() {
+///     BorrowedTag::Binary { value, .. } => value,
+///     _ => unreachable!(),
+/// }).collect();
+/// assert_eq!(vec![&[0x01, 0x02][..], &[]], values);
+/// ```
+///
+pub struct TagSliceIterator<'a, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iter: TagIterator<&'a [u8], TSpec>,
+    data: &'a [u8],
+}
+
+impl<'a, TSpec> TagSliceIterator<'a, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagSliceIterator`] reading the tags in `data`.  `tags_to_buffer` is used as in [`TagIterator::new()`].
+    ///
+    pub fn new(data: &'a [u8], tags_to_buffer: &[TSpec]) -> Self {
+        TagSliceIterator { iter: TagIterator::new(data, tags_to_buffer), data }
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagIterator`], e.g. to change its settings.
+    ///
+    /// ## Panics
+    ///
+    /// Reading will panic if the source of the wrapped iterator is replaced using [`TagIterator::reset()`] with a slice that is shorter than the original.  Replacing the source isn't supported, so create a new [`TagSliceIterator`] instead.
+    ///
+    pub fn get_mut(&mut self) -> &mut TagIterator<&'a [u8], TSpec> {
+        &mut self.iter
+    }
+
+    ///
+    /// Gets a reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_ref(&self) -> &TagIterator<&'a [u8], TSpec> {
+        &self.iter
+    }
+}
+
+impl<'a, TSpec> Iterator for TagSliceIterator<'a, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<BorrowedTag<'a, TSpec>, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, data_type) = match self.iter.next_borrowed()? {
+            Ok(BorrowedTag::Utf8 { id, .. }) => (id, Some(TagDataType::Utf8)),
+            Ok(BorrowedTag::Binary { id, .. }) => (id, Some(TagDataType::Binary)),
+            Ok(BorrowedTag::Raw { id, .. }) => (id, None),
+            Ok(BorrowedTag::Tag(tag)) => return Some(Ok(BorrowedTag::Tag(tag))),
+            Ok(BorrowedTag::Skipped(skipped)) => return Some(Ok(BorrowedTag::Skipped(skipped))),
+            Err(err) => return Some(Err(err)),
+        };
+
+        let span = self.iter.last_emitted_tag_span();
+        let start = span.data_offset();
+        let data = self.data.get(start..(start + span.data_len.unwrap_or(0))).expect("source of a TagSliceIterator should not be replaced");
+        Some(Ok(match data_type {
+            Some(TagDataType::Utf8) => BorrowedTag::Utf8 { id, value: std::str::from_utf8(data).expect("utf8 data was already validated") },
+            Some(TagDataType::Binary) => BorrowedTag::Binary { id, value: data },
+            _ => BorrowedTag::Raw { id, data },
+        }))
+    }
+}
@@ -1,13 +1,18 @@
 use std::io::ErrorKind;
-use std::iter::repeat;
 use std::mem;
+use std::ops::Range;
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
 use ebml_iterable_specification::{EbmlSpecification, EbmlTag, Master, TagDataType};
 
 #[cfg(feature = "futures")]
 use futures::{AsyncRead, AsyncReadExt, Stream};
-use crate::error::{TagIteratorError, ToolError};
+use crate::error::TagIteratorError;
 use crate::errors::tag_iterator::CorruptedFileError;
-use crate::tag_iterator_util::{EBMLSize, ProcessingTag};
+use crate::spec_util::{read_string, SpecCache};
+use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, EBMLSize, ProcessingTag, check_child_size};
 use crate::tag_iterator_util::EBMLSize::Known;
 use crate::tools;
 
@@ -23,8 +28,11 @@ pub struct TagIteratorAsync<R: AsyncRead + Unpin, TSpec>
 {
     read: R,
     buf: Vec<u8>,
+    buf_position: usize,
+    read_size: usize,
     offset: usize,
     tag_stack: Vec<ProcessingTag<TSpec>>,
+    spec_cache: SpecCache,
 }
 
 #[cfg(feature = "futures")]
@@ -32,12 +40,29 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
     where
         TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
+    ///
+    /// Returns a new [`TagIteratorAsync<TSpec>`] instance that reads from `read` in chunks of up to [`DEFAULT_BUFFER_LEN`](crate::iterator::DEFAULT_BUFFER_LEN) bytes.
+    ///
     pub fn new(read: R) -> Self {
+        Self::with_capacity(read, DEFAULT_BUFFER_LEN)
+    }
+
+    ///
+    /// Returns a new [`TagIteratorAsync<TSpec>`] instance that reads from `read` in chunks of up to `read_size` bytes.
+    ///
+    /// Each read requests at least `read_size` bytes (or however many the current tag needs, if that is more), so larger values mean fewer reads from the source (useful for network streams and slow disks) while smaller values keep memory use down.  A `read_size` of 0 reads only what is needed for each tag.
+    ///
+    /// Note that the iterator may read past the end of the last tag it emits, up to `read_size` bytes.
+    ///
+    pub fn with_capacity(read: R, read_size: usize) -> Self {
         Self {
             read,
-            buf: Default::default(),
+            buf: Vec::with_capacity(read_size),
+            buf_position: 0,
+            read_size,
             offset: 0,
             tag_stack: Default::default(),
+            spec_cache: SpecCache::default(),
         }
     }
 
@@ -45,29 +70,40 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
         self.offset
     }
 
-    fn advance(&mut self, length: usize) {
-        self.offset += length;
-        self.buf.drain(0..length);
+    fn unread(&self) -> &[u8] {
+        &self.buf[self.buf_position..]
     }
 
-    fn advance_get(&mut self, length: usize) -> Vec<u8> {
+    fn advance(&mut self, length: usize) -> Range<usize> {
         self.offset += length;
-        let upper = self.buf.split_off(length);
-        mem::replace(&mut self.buf, upper)
+        self.buf_position += length;
+        (self.buf_position - length)..self.buf_position
     }
 
     async fn ensure_data_read(&mut self, len: usize) -> Result<bool, TagIteratorError> {
-        let size = self.buf.len();
-        if size < len {
-            let remaining = len - size;
-            self.buf.extend(repeat(0).take(remaining));
-            if let Err(source) = self.read.read_exact(&mut self.buf[size..]).await {
-                return match source.kind() {
-                    ErrorKind::UnexpectedEof => {
-                        Ok(false)
+        if self.buf.len() - self.buf_position < len {
+            // Only shift consumed bytes out when we need to read more, so the allocation is reused across tags
+            self.buf.drain(..self.buf_position);
+            self.buf_position = 0;
+
+            while self.buf.len() < len {
+                let size = self.buf.len();
+                self.buf.resize(size + (len - size).max(self.read_size), 0);
+                let bytes_read = loop {
+                    match self.read.read(&mut self.buf[size..]).await {
+                        Ok(bytes_read) => break bytes_read,
+                        Err(source) if source.kind() == ErrorKind::Interrupted => continue,
+                        Err(source) => {
+                            self.buf.truncate(size);
+                            return Err(TagIteratorError::ReadError { source });
+                        }
                     }
-                    _ => Err(TagIteratorError::ReadError { source })
                 };
+                // Drop any padding that wasn't filled so it is never mistaken for data
+                self.buf.truncate(size + bytes_read);
+                if bytes_read == 0 {
+                    return Ok(false);
+                }
             }
         }
         Ok(true)
@@ -75,7 +111,7 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
 
     async fn read_tag_id(&mut self) -> Result<u64, TagIteratorError> {
         self.ensure_data_read(8).await?;
-        match tools::read_vint(&self.buf).unwrap_or(Some((0, 1))) {
+        match tools::read_vint(self.unread()).unwrap_or(Some((0, 1))) {
             Some((value, length)) => {
                 self.advance(length);
                 Ok(value + (1 << (7 * length)))
@@ -84,9 +120,9 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
         }
     }
 
-    async fn read_tag_size(&mut self) -> Result<EBMLSize, TagIteratorError> {
+    async fn read_tag_size(&mut self, tag_id: u64) -> Result<EBMLSize, TagIteratorError> {
         self.ensure_data_read(8).await?;
-        match tools::read_vint(&self.buf).or(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: 0, position: self.current_offset() })))? {
+        match tools::read_vint(self.unread()).or(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidVint { position: self.current_offset(), tag_id: Some(tag_id) })))? {
             Some((value, length)) => {
                 self.advance(length);
                 Ok(EBMLSize::new(value, length))
@@ -95,18 +131,22 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
         }
     }
 
-    async fn read_tag_data(&mut self, size: usize) -> Result<Vec<u8>, TagIteratorError> {
+    async fn read_tag_data(&mut self, size: usize) -> Result<Range<usize>, TagIteratorError> {
         if !self.ensure_data_read(size).await? {
             return Err(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: None, tag_size: None, partial_data: None });
         }
-        Ok(self.advance_get(size))
+        Ok(self.advance(size))
     }
 
     async fn read_tag(&mut self) -> Result<TSpec, TagIteratorError> {
+        let tag_start = self.current_offset();
         let tag_id = self.read_tag_id().await?;
-        let spec_tag_type = TSpec::get_tag_data_type(tag_id);
-        let size = self.read_tag_size().await?;
+        let spec_tag_type = self.spec_cache.lookup::<TSpec>(tag_id).data_type;
+        let size = self.read_tag_size(tag_id).await?;
         let current_offset = self.current_offset();
+        if let Known(size) = size {
+            check_child_size(&self.tag_stack, tag_start, tag_id, current_offset - tag_start, size).map_err(TagIteratorError::CorruptedFileData)?;
+        }
 
         let is_master = matches!(spec_tag_type, Some(TagDataType::Master));
         if is_master {
@@ -114,7 +154,7 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
                 tag: TSpec::get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)),
                 size,
                 data_start: current_offset,
-                tag_start: 0, //not implemented here
+                tag_start,
             });
             Ok(TSpec::get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)))
         } else {
@@ -124,28 +164,33 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
                 return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id, position: current_offset }));
             };
 
-            let raw_data = self.read_tag_data(size).await?;
+            let data_range = self.read_tag_data(size).await?;
+            let raw_data = &self.buf[data_range];
             let tag = match spec_tag_type {
                 Some(TagDataType::Master) => { unreachable!("Master should have been handled before querying data") }
                 Some(TagDataType::UnsignedInt) => {
-                    let val = tools::arr_to_u64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { tag_id, problem: e })?;
+                    let val = tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
                     TSpec::get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id))
                 }
                 Some(TagDataType::Integer) => {
-                    let val = tools::arr_to_i64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { tag_id, problem: e })?;
+                    let val = tools::arr_to_i64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
                     TSpec::get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id))
                 }
                 Some(TagDataType::Utf8) => {
-                    let val = String::from_utf8(raw_data.to_vec()).map_err(|e| TagIteratorError::CorruptedTagData { tag_id, problem: ToolError::FromUtf8Error(raw_data, e) })?;
+                    let val = read_string::<TSpec>(tag_id, raw_data).map(str::to_owned).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
                     TSpec::get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id))
                 }
                 Some(TagDataType::Binary) | None => {
-                    TSpec::get_binary_tag(tag_id, &raw_data).unwrap_or_else(|| TSpec::get_raw_tag(tag_id, &raw_data))
+                    TSpec::get_binary_tag(tag_id, raw_data).unwrap_or_else(|| TSpec::get_raw_tag(tag_id, raw_data))
                 }
                 Some(TagDataType::Float) => {
-                    let val = tools::arr_to_f64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData { tag_id, problem: e })?;
+                    let val = tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
                     TSpec::get_float_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id))
                 }
+                Some(TagDataType::Date) => {
+                    let val = tools::arr_to_date(raw_data).map_err(|e| TagIteratorError::CorruptedTagData { position: tag_start, tag_id, problem: e })?;
+                    TSpec::get_date_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was date, but could not get tag!", tag_id))
+                }
             };
 
             match self.tag_stack.last() {
@@ -154,7 +199,7 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
                     let previous_tag_ended = previous_tag.is_ended_by(tag_id);
 
                     if previous_tag_ended {
-                        Ok(mem::replace(self.tag_stack.last_mut().unwrap(), ProcessingTag { tag, size: Known(size), data_start: current_offset, tag_start: 0 }).into_inner())
+                        Ok(mem::replace(self.tag_stack.last_mut().unwrap(), ProcessingTag { tag, size: Known(size), data_start: current_offset, tag_start }).into_inner())
                     } else {
                         Ok(tag)
                     }
@@ -166,10 +211,8 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
     /// can be consumed
     pub async fn next(&mut self) -> Option<Result<TSpec, TagIteratorError>> {
         if let Some(tag) = self.tag_stack.pop() {
-            if let Known(size) = tag.size {
-                if self.current_offset() >= tag.data_start + size {
-                    return Some(Ok(tag.tag));
-                }
+            if tag.data_end().is_some_and(|end| self.current_offset() >= end) {
+                return Some(Ok(tag.tag));
             }
             self.tag_stack.push(tag);
         }
@@ -192,3 +235,67 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
         })
     }
 }
+
+///
+/// Adapts a [`tokio::io::AsyncRead`] source so it can be read by a [`TagIteratorAsync`].  Created by [`TagIteratorAsync::from_tokio()`].
+///
+#[cfg(feature = "tokio")]
+pub struct TokioReader<R>(R);
+
+#[cfg(feature = "tokio")]
+impl<R> TokioReader<R> {
+
+    ///
+    /// Consumes self and returns the wrapped source.
+    ///
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncRead for TokioReader<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        let mut read_buf = tokio::io::ReadBuf::new(buf);
+        match Pin::new(&mut self.get_mut().0).poll_read(cx, &mut read_buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(read_buf.filled().len())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin, TSpec> TagIteratorAsync<TokioReader<R>, TSpec>
+    where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagIteratorAsync<TSpec>`] instance that reads from a [`tokio::io::AsyncRead`] source, such as a `TcpStream` or the bytes of a WebSocket.
+    ///
+    /// Tags are read as bytes arrive, either by calling [`.next().await`](Self::next) in a loop or through [`Self::into_stream()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use ebml_iterable::TagIteratorAsync;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x81, 0x81, 0x01];
+    /// let mut iter = TagIteratorAsync::<_, EmptySpec>::from_tokio(data);
+    /// let tag = block_on(iter.next()).unwrap().unwrap();
+    /// assert_eq!(EmptySpec::with_data(0x81, &[0x01]), tag);
+    /// ```
+    ///
+    pub fn from_tokio(read: R) -> Self {
+        Self::new(TokioReader(read))
+    }
+
+    ///
+    /// Returns a new [`TagIteratorAsync<TSpec>`] instance that reads from a [`tokio::io::AsyncRead`] source in chunks of up to `read_size` bytes.  See [`Self::with_capacity()`].
+    ///
+    pub fn from_tokio_with_capacity(read: R, read_size: usize) -> Self {
+        Self::with_capacity(TokioReader(read), read_size)
+    }
+}
@@ -1,6 +1,11 @@
 use ebml_iterable_specification::{EbmlSpecification, EbmlTag};
-use std::convert::TryInto;
+use alloc::borrow::ToOwned;
+use core::convert::TryInto;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek};
 use crate::{tag_iterator_util::EBMLSize::{Known, Unknown}, spec_util::is_ended_by};
+use crate::errors::tag_iterator::CorruptedFileError;
+use crate::vint::is_unknown_size;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum EBMLSize {
@@ -10,41 +15,18 @@ pub enum EBMLSize {
 
 impl EBMLSize {
     pub fn new(size: u64, vint_length: usize) -> Self {
-        match vint_length {
-            1 => if size == ((1 << (7))     - 1) { return Unknown; },
-            2 => if size == ((1 << (7 * 2)) - 1) { return Unknown; },
-            3 => if size == ((1 << (7 * 3)) - 1) { return Unknown; },
-            4 => if size == ((1 << (7 * 4)) - 1) { return Unknown; },
-            5 => if size == ((1 << (7 * 5)) - 1) { return Unknown; },
-            6 => if size == ((1 << (7 * 6)) - 1) { return Unknown; },
-            7 => if size == ((1 << (7 * 7)) - 1) { return Unknown; },
-            8 => if size == ((1 << (7 * 8)) - 1) { return Unknown; },
-            _ => {},
+        if is_unknown_size(size, vint_length) {
+            return Unknown;
         }
 
-        match size.try_into() {
-            Ok(value) => Known(value),
-            Err(_) => Unknown
-        }
+        // Sizes too large for this platform can't be read anyway, so saturate them to be caught by size checks instead of wrapping
+        Known(size.try_into().unwrap_or(usize::MAX))
     }
 
     #[inline(always)]
     pub fn is_known(&self) -> bool {
         matches!(&self, &EBMLSize::Known(_))
     }
-
-    ///
-    /// # Panics
-    /// 
-    /// Panics if the current variant is not EBMLSize::Known
-    /// 
-    #[inline(always)]
-    pub fn value(&self) -> usize {
-        match &self {
-            EBMLSize::Known(val) => *val,
-            _ => panic!("Called EBMLSize::value() on an unknown size!"),
-        }
-    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -58,6 +40,18 @@ pub struct ProcessingTag<TSpec>
 }
 
 impl<TSpec> ProcessingTag<TSpec> where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone {
+    #[cfg(feature = "std")]
+    pub fn span(&self) -> TagSpan {
+        TagSpan::new(self.tag.get_id(), self.tag_start, self.data_start, self.size)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn into_emitted(self) -> (TSpec, TagSpan) {
+        let span = self.span();
+        (self.tag, span)
+    }
+
+    #[cfg(feature = "futures")]
     pub fn into_inner(self) -> TSpec {
         self.tag
     }
@@ -65,16 +59,210 @@ impl<TSpec> ProcessingTag<TSpec> where TSpec: EbmlSpecification<TSpec> + EbmlTag
     pub fn is_ended_by(&self, id: u64) -> bool {
         is_ended_by::<TSpec>(self.tag.get_id(), id)
     }
+
+    ///
+    /// Gets the position where this tag's data ends, or `None` if it is unknown sized.  Saturates rather than wrapping for corrupt sizes.
+    ///
+    pub fn data_end(&self) -> Option<usize> {
+        match self.size {
+            Known(size) => Some(self.data_start.saturating_add(size)),
+            Unknown => None,
+        }
+    }
 }
 
+///
+/// Checks that a tag starting at `position` doesn't extend past the end of any known sized tag in `tag_stack`.
+///
+pub fn check_child_size<TSpec>(tag_stack: &[ProcessingTag<TSpec>], position: usize, tag_id: u64, header_len: usize, size: usize) -> Result<(), CorruptedFileError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let end = position.saturating_add(header_len).saturating_add(size);
+    match tag_stack.iter().rev().find_map(|parent| parent.data_end().filter(|parent_end| *parent_end < end).map(|parent_end| (parent, parent_end))) {
+        Some((parent, parent_end)) => Err(CorruptedFileError::OversizedChildElement {
+            position,
+            tag_id,
+            size,
+            end,
+            parent_id: parent.tag.get_id(),
+            parent_position: parent.tag_start,
+            parent_end,
+        }),
+        None => Ok(()),
+    }
+}
+
+///
+/// The location of a tag in the source of a [`TagIterator`](crate::TagIterator), as reported by [`TagIterator::last_emitted_tag_span()`](crate::TagIterator::last_emitted_tag_span).
+///
+/// `offset` is the position of the tag's header, `header_len` is the combined length of its id and size, `size_len` is the length of the size alone, and `data_len` is the length of its data (`None` for unknown sized "Master" tags).
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TagSpan {
+    pub offset: usize,
+    pub header_len: usize,
+    pub size_len: usize,
+    pub data_len: Option<usize>,
+}
+
+impl TagSpan {
+    #[cfg(feature = "std")]
+    pub(crate) fn new(tag_id: u64, tag_start: usize, data_start: usize, size: EBMLSize) -> Self {
+        let header_len = data_start - tag_start;
+        // Ids keep their marker bits, so the length of an id is the number of bytes in its value
+        let id_len = (64 - tag_id.leading_zeros() as usize).div_ceil(8).max(1);
+        TagSpan {
+            offset: tag_start,
+            header_len,
+            size_len: header_len.saturating_sub(id_len),
+            data_len: match size {
+                Known(len) => Some(len),
+                Unknown => None,
+            },
+        }
+    }
+
+    ///
+    /// Gets the position of the tag's data.
+    ///
+    pub fn data_offset(&self) -> usize {
+        self.offset + self.header_len
+    }
+
+    ///
+    /// Gets the full length of the tag, including its header.  Returns `None` for unknown sized "Master" tags.
+    ///
+    pub fn total_len(&self) -> Option<usize> {
+        self.data_len.map(|len| self.header_len + len)
+    }
+
+    ///
+    /// Gets the [`SizeWidth`](crate::writer::SizeWidth) that reproduces the width of this tag's size when writing it with [`TagWriter::write_with_options()`](crate::TagWriter::write_with_options), so tags that were written with padded sizes keep their length.
+    ///
+    pub fn size_width(&self) -> crate::writer::SizeWidth {
+        crate::writer::SizeWidth::Fixed(self.size_len.clamp(1, 8) as u8)
+    }
+}
+
+///
+/// A tag emitted by [`TagIterator::next_borrowed()`](crate::TagIterator::next_borrowed).
+///
+/// Utf8, binary, and raw tags refer directly to the iterator's internal buffer, so they can be processed without allocating.  Every other tag (masters and numeric tags, which don't allocate anyway) is emitted as a regular `TSpec` in the [`BorrowedTag::Tag`] variant.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedTag<'a, TSpec> {
+    ///
+    /// A tag that does not refer to the iterator's buffer.  This includes [`Master::Full`](crate::specs::Master::Full) tags that were buffered using `tags_to_buffer`.
+    ///
+    Tag(TSpec),
+
+    ///
+    /// A [`TagDataType::Utf8`](crate::specs::TagDataType::Utf8) tag.
+    ///
+    Utf8 { id: u64, value: &'a str },
+
+    ///
+    /// A [`TagDataType::Binary`](crate::specs::TagDataType::Binary) tag.
+    ///
+    Binary { id: u64, value: &'a [u8] },
+
+    ///
+    /// A tag that is not in the specification (see [`AllowableErrors::InvalidTagIds`]).
+    ///
+    Raw { id: u64, data: &'a [u8] },
+
+    ///
+    /// A tag that was skipped without reading its data, reported when [`TagIterator::set_emit_skipped_tags()`](crate::TagIterator::set_emit_skipped_tags) is enabled.
+    ///
+    Skipped(SkippedTag),
+}
+
+impl<'a, TSpec> BorrowedTag<'a, TSpec> where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone {
+
+    ///
+    /// Gets the id of the tag.
+    ///
+    pub fn get_id(&self) -> u64 {
+        match self {
+            BorrowedTag::Tag(tag) => tag.get_id(),
+            BorrowedTag::Utf8 { id, .. } | BorrowedTag::Binary { id, .. } | BorrowedTag::Raw { id, .. } => *id,
+            BorrowedTag::Skipped(skipped) => skipped.tag_id,
+        }
+    }
+
+    ///
+    /// Converts the tag into an owned `TSpec`, copying any borrowed data.
+    ///
+    /// Skipped tags have no data, so they are converted into "RawTag" variants without any data.
+    ///
+    /// # Panics
+    ///
+    /// This can panic if `TSpec` is an internally inconsistent specification, in the same way [`TagIterator`](crate::TagIterator) can.
+    ///
+    pub fn into_owned(self) -> TSpec {
+        match self {
+            BorrowedTag::Tag(tag) => tag,
+            BorrowedTag::Utf8 { id, value } => TSpec::get_utf8_tag(id, value.to_owned()).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", id)),
+            BorrowedTag::Binary { id, value } => TSpec::get_binary_tag(id, value).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", id)),
+            BorrowedTag::Raw { id, data } => TSpec::get_raw_tag(id, data),
+            BorrowedTag::Skipped(skipped) => TSpec::get_raw_tag(skipped.tag_id, &[]),
+        }
+    }
+}
+
+///
+/// The default size (in bytes) of the internal read buffer used by [`TagIterator::new()`](crate::TagIterator::new), and of each read made by `TagIteratorAsync::new()`.
+///
 pub const DEFAULT_BUFFER_LEN: usize = 1024 * 64;
 
+///
+/// The default limit on how deeply "Master" tags may be nested by a [`TagIterator`](crate::TagIterator) or [`TagWriter`](crate::TagWriter).
+///
+/// Real specifications rarely nest more than 10 levels deep, even with recursive elements like Matroska's `ChapterAtom`.  The limit guards against crafted files that would otherwise overflow the stack.
+///
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+///
+/// Resource limits for a [`TagIterator`](crate::TagIterator), applied together with [`TagIterator::set_options()`](crate::TagIterator::set_options).
+///
+/// The defaults only limit the nesting depth, so that untrusted input should be read with the other limits set as well.  Every limit produces a [`CorruptedFileError`](crate::error::CorruptedFileError) when it is exceeded rather than letting a crafted file exhaust memory or the stack.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TagIteratorOptions {
+    ///
+    /// How deeply "Master" tags may be nested.  See [`TagIterator::set_max_depth()`](crate::TagIterator::set_max_depth).
+    ///
+    pub max_depth: usize,
+
+    ///
+    /// The largest amount of data a single non-"Master" tag may have.  See [`TagIterator::set_max_tag_size()`](crate::TagIterator::set_max_tag_size).
+    ///
+    pub max_element_size: Option<usize>,
+
+    ///
+    /// The largest amount of data a buffered [`Master::Full`](crate::specs::Master::Full) tag may have.  See [`TagIterator::set_max_buffered_size()`](crate::TagIterator::set_max_buffered_size).
+    ///
+    pub max_total_buffered: Option<usize>,
+}
+
+impl Default for TagIteratorOptions {
+    fn default() -> Self {
+        TagIteratorOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_element_size: None,
+            max_total_buffered: None,
+        }
+    }
+}
+
 ///
 /// Used to relax rules on how strictly a [`TagIterator`](crate::TagIterator) should validate the read stream.
 /// 
 pub enum AllowableErrors {
     ///
     /// Causes the [`TagIterator`](crate::TagIterator) to produce "RawTag" binary variants for any unknown tag ids rather than throwing an error.
+    ///
+    /// "RawTag"s hold the complete data of the unknown tag (including any children, if it is actually a "Master" tag), and [`TagWriter`](crate::TagWriter) writes them back unchanged.  Remuxers can use this to preserve vendor-specific elements they don't understand.  Unknown tags must have a known size to be emitted, and [`TagIterator::skip_unknown_tags()`](crate::TagIterator::skip_unknown_tags) takes precedence over this setting.
     /// 
     InvalidTagIds,
 
@@ -87,4 +275,95 @@ pub enum AllowableErrors {
     /// Causes the [`TagIterator`](crate::TagIterator) to emit tags even if they exceed the length of a parent element.
     /// 
     OversizedTags,
-}
\ No newline at end of file
+}
+
+///
+/// How a [`TagIterator`](crate::TagIterator) handles [`TagDataType::Utf8`](crate::specs::TagDataType::Utf8) tags whose data is not valid utf8.
+///
+/// See [`TagIterator::set_utf8_handling()`](crate::TagIterator::set_utf8_handling).
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Utf8Handling {
+    ///
+    /// Return a [`TagIteratorError::CorruptedTagData`](crate::error::TagIteratorError::CorruptedTagData) error for the tag.
+    ///
+    #[default]
+    Strict,
+
+    ///
+    /// Replace invalid sequences with `U+FFFD REPLACEMENT CHARACTER` and emit the tag as usual.
+    ///
+    Lossy,
+
+    ///
+    /// Emit the tag as a "RawTag" variant holding its data as is, so the original bytes are available.
+    ///
+    Raw,
+}
+///
+/// A known muxer bug that a [`TagIterator`](crate::TagIterator) in quirks mode tolerated rather than returning an error.
+///
+/// See [`TagIterator::set_quirks_mode()`](crate::TagIterator::set_quirks_mode).
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Quirk {
+    ///
+    /// A "Master" tag declared a size of zero but was treated as unknown sized, so that the children following it are still read as its children.
+    ///
+    ZeroSizedMaster { position: usize, tag_id: u64 },
+
+    ///
+    /// An integer tag was wider than 8 bytes, but every extra leading byte was padding (zeros, or sign extension for signed integers).
+    ///
+    OverlongInteger { position: usize, tag_id: u64, size: usize },
+
+    ///
+    /// Data after the end of the last top-level tag could not be read as a tag, so the iterator stopped there.
+    ///
+    TrailingGarbage { position: usize },
+}
+
+///
+/// Why a [`TagIterator`](crate::TagIterator) skipped a tag rather than emitting it.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+    ///
+    /// The tag was configured to be skipped through [`TagIterator::skip_tags()`](crate::TagIterator::skip_tags).
+    ///
+    Filtered,
+
+    ///
+    /// The tag id is not in the specification and [`TagIterator::skip_unknown_tags()`](crate::TagIterator::skip_unknown_tags) is enabled.
+    ///
+    Unknown,
+
+    ///
+    /// The tag's data exceeds the limit set through [`TagIterator::skip_oversized_tags()`](crate::TagIterator::skip_oversized_tags).
+    ///
+    Oversized,
+}
+
+///
+/// A tag skipped by a [`TagIterator`](crate::TagIterator), as reported to the callback set through [`TagIterator::on_skipped_tag()`](crate::TagIterator::on_skipped_tag).
+///
+/// `position` is the offset of the tag's header and `size` is the length of its data.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SkippedTag {
+    pub tag_id: u64,
+    pub position: usize,
+    pub size: usize,
+    pub reason: SkipReason,
+}
+
+///
+/// A source that can be both read and seeked, so seekable sources can be used as trait objects.
+///
+/// `Box<dyn Read + Seek>` isn't a valid type, but `TagIterator<Box<dyn ReadSeek>, TSpec>` supports everything that needs a seekable source, such as [`TagIterator::seek_when_skipping()`](crate::TagIterator::seek_when_skipping).  This is implemented for every type that implements [`Read`] and [`Seek`].
+///
+#[cfg(feature = "std")]
+pub trait ReadSeek: Read + Seek {}
+
+#[cfg(feature = "std")]
+impl<T: Read + Seek + ?Sized> ReadSeek for T {}
@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::io::Read;
+
+use crate::error::TagIteratorError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::TagIterator;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum PathSegment {
+    Id(u64),
+    Name(String),
+}
+
+impl PathSegment {
+    fn parse(segment: &str) -> Self {
+        let hex = segment.strip_prefix("0x").or_else(|| segment.strip_prefix("0X"));
+        match hex.and_then(|hex| u64::from_str_radix(hex, 16).ok()) {
+            Some(id) => PathSegment::Id(id),
+            None => PathSegment::Name(segment.to_string()),
+        }
+    }
+
+    fn matches<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, id: u64) -> bool {
+        match self {
+            PathSegment::Id(expected) => *expected == id,
+            PathSegment::Name(name) => TSpec::get_tag_name(id) == Some(name.as_str()),
+        }
+    }
+}
+
+///
+/// Reads only the elements at a hierarchical path, such as `Segment/Tracks/TrackEntry/CodecID`, out of a [`TagIterator`].
+///
+/// Each segment of the path is either the name of a tag in the specification (see [`EbmlSpecification::get_tag_name()`]) or a hexadecimal id like `0x86`.  The path starts at the top level of the document, or at the level the iterator starts reading at.  Every tag at the end of the path is emitted in full: "Master" tags are emitted as [`Master::Full`] variants containing all of their children.  Any other tags are discarded, and "Master" tags that are not on the path are skipped over without reading their contents, unless they have an unknown size.
+///
+/// Combine with [`TagIterator::seek_when_skipping()`] to seek over skipped tags instead of reading past them.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::{EbmlQuery, TagIterator};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x81, 0x02, 0x81, 0x81, 0x03]), &[]);
+/// let tags: Vec<EmptySpec> = EbmlQuery::new(iter, "0x81").map(|tag| tag.unwrap()).collect();
+/// assert_eq!(vec![EmptySpec::with_data(0x81, &[0x01]), EmptySpec::with_data(0x81, &[0x03])], tags);
+/// ```
+///
+pub struct EbmlQuery<R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iter: TagIterator<R, TSpec>,
+    path: Vec<PathSegment>,
+
+    // The number of open masters that match the start of the path
+    depth: usize,
+    // The number of open masters inside of a master that does not match the path
+    ignored: usize,
+    // The children read so far for each open master inside of a match
+    collecting: Vec<(u64, Vec<TSpec>)>,
+    // Matches found inside of buffered masters
+    found: VecDeque<TSpec>,
+}
+
+impl<R: Read, TSpec> EbmlQuery<R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`EbmlQuery`] that reads the tags at `path` from `iter`.  Segments of the path are separated by `/`.
+    ///
+    pub fn new(iter: TagIterator<R, TSpec>, path: &str) -> Self {
+        EbmlQuery {
+            iter,
+            path: path.trim_matches('/').split('/').map(PathSegment::parse).collect(),
+            depth: 0,
+            ignored: 0,
+            collecting: Vec::new(),
+            found: VecDeque::new(),
+        }
+    }
+
+    ///
+    /// Consumes self and returns the wrapped [`TagIterator`].
+    ///
+    pub fn into_inner(self) -> TagIterator<R, TSpec> {
+        self.iter
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_mut(&mut self) -> &mut TagIterator<R, TSpec> {
+        &mut self.iter
+    }
+
+    ///
+    /// Gets a reference to the wrapped [`TagIterator`].
+    ///
+    pub fn get_ref(&self) -> &TagIterator<R, TSpec> {
+        &self.iter
+    }
+
+    fn is_last(&self, depth: usize) -> bool {
+        depth + 1 == self.path.len()
+    }
+
+    ///
+    /// Queues the matches among the children of a buffered master at `depth` in the path.
+    ///
+    fn search(&mut self, children: &[TSpec], depth: usize) {
+        for child in children {
+            if !self.path[depth].matches::<TSpec>(child.get_id()) {
+                continue;
+            }
+            if self.is_last(depth) {
+                self.found.push_back(child.clone());
+            } else if let Some(Master::Full(grandchildren)) = child.as_master() {
+                self.search(grandchildren, depth + 1);
+            }
+        }
+    }
+
+    ///
+    /// Handles a tag read inside of a match, returning the match once it is complete.
+    ///
+    fn collect(&mut self, tag: TSpec) -> Option<TSpec> {
+        let tag = match tag.as_master() {
+            Some(Master::Start) => {
+                self.collecting.push((tag.get_id(), Vec::new()));
+                return None;
+            },
+            Some(Master::End) => {
+                let (id, children) = self.collecting.pop().unwrap();
+                TSpec::get_master_tag(id, Master::Full(children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", id))
+            },
+            _ => tag,
+        };
+        match self.collecting.last_mut() {
+            Some((_, children)) => {
+                children.push(tag);
+                None
+            },
+            None => Some(tag),
+        }
+    }
+}
+
+impl<R: Read, TSpec> Iterator for EbmlQuery<R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<TSpec, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(found) = self.found.pop_front() {
+                return Some(Ok(found));
+            }
+
+            let tag = match self.iter.next()? {
+                Ok(tag) => tag,
+                Err(err) => return Some(Err(err)),
+            };
+
+            if !self.collecting.is_empty() {
+                if let Some(found) = self.collect(tag) {
+                    return Some(Ok(found));
+                }
+                continue;
+            }
+
+            let master = tag.as_master();
+            if self.ignored > 0 {
+                match master {
+                    Some(Master::Start) => self.ignored += 1,
+                    Some(Master::End) => self.ignored -= 1,
+                    _ => {},
+                }
+                continue;
+            }
+
+            if let Some(Master::End) = master {
+                // Masters that were open before the query started are not counted
+                self.depth = self.depth.saturating_sub(1);
+                continue;
+            }
+
+            if !self.path[self.depth].matches::<TSpec>(tag.get_id()) {
+                if let Some(Master::Start) = master {
+                    self.ignored += 1;
+                    if let Err(err) = self.iter.skip_master_data() {
+                        return Some(Err(err));
+                    }
+                }
+                continue;
+            }
+
+            if self.is_last(self.depth) {
+                match self.collect(tag) {
+                    Some(found) => return Some(Ok(found)),
+                    None => continue,
+                }
+            }
+
+            match master {
+                Some(Master::Start) => self.depth += 1,
+                Some(Master::Full(children)) => self.search(children, self.depth + 1),
+                _ => {},
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use std::io::Read;
+use std::ops::Range;
+
+use crate::error::{CorruptedFileError, TagIteratorError};
+use crate::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use crate::TagIterator;
+
+///
+/// Identifies a single node in a [`TagTree`].
+///
+/// Ids are only meaningful for the tree that produced them.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NodeId(usize);
+
+///
+/// The data held by a node in a [`TagTree`].
+///
+/// String and binary values borrow from the tree's shared data buffer.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TreeValue<'a> {
+    Master,
+    UnsignedInt(u64),
+    Integer(i64),
+    Utf8(&'a str),
+    Binary(&'a [u8]),
+    Float(f64),
+
+    ///
+    /// Nanoseconds since 2001-01-01T00:00:00 UTC.
+    ///
+    Date(i64),
+}
+
+#[derive(Copy, Clone, Debug)]
+enum StoredValue {
+    Master,
+    UnsignedInt(u64),
+    Integer(i64),
+    Utf8(usize, usize),
+    Binary(usize, usize),
+    Float(f64),
+    Date(i64),
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    id: u64,
+    value: StoredValue,
+    parent: Option<usize>,
+    first_child: Option<usize>,
+    last_child: Option<usize>,
+    next_sibling: Option<usize>,
+}
+
+///
+/// A read-only view of a single node in a [`TagTree`].
+///
+#[derive(Copy, Clone, Debug)]
+pub struct TreeNode<'a> {
+    tree: &'a TagTree,
+    index: usize,
+}
+
+impl<'a> TreeNode<'a> {
+
+    ///
+    /// Returns the [`NodeId`] of this node.
+    ///
+    pub fn node_id(&self) -> NodeId {
+        NodeId(self.index)
+    }
+
+    ///
+    /// Returns the EBML tag id of this node.
+    ///
+    pub fn id(&self) -> u64 {
+        self.node().id
+    }
+
+    ///
+    /// Returns the data held by this node.
+    ///
+    pub fn value(&self) -> TreeValue<'a> {
+        let data = &self.tree.data;
+        match self.node().value {
+            StoredValue::Master => TreeValue::Master,
+            StoredValue::UnsignedInt(val) => TreeValue::UnsignedInt(val),
+            StoredValue::Integer(val) => TreeValue::Integer(val),
+            StoredValue::Utf8(start, end) => TreeValue::Utf8(std::str::from_utf8(&data[start..end]).expect("utf8 data was validated when it was added to the tree")),
+            StoredValue::Binary(start, end) => TreeValue::Binary(&data[start..end]),
+            StoredValue::Float(val) => TreeValue::Float(val),
+            StoredValue::Date(val) => TreeValue::Date(val),
+        }
+    }
+
+    ///
+    /// Returns the parent of this node, or `None` if it is a root node.
+    ///
+    pub fn parent(&self) -> Option<TreeNode<'a>> {
+        self.node().parent.map(|index| TreeNode { tree: self.tree, index })
+    }
+
+    ///
+    /// Returns an iterator over the direct children of this node.
+    ///
+    pub fn children(&self) -> Siblings<'a> {
+        Siblings { tree: self.tree, next: self.node().first_child }
+    }
+
+    ///
+    /// Returns the first direct child of this node with the given tag id.
+    ///
+    pub fn child(&self, id: u64) -> Option<TreeNode<'a>> {
+        self.children().find(|c| c.id() == id)
+    }
+
+    fn node(&self) -> &'a Node {
+        &self.tree.nodes[self.index]
+    }
+}
+
+///
+/// An iterator over sibling nodes in a [`TagTree`].
+///
+pub struct Siblings<'a> {
+    tree: &'a TagTree,
+    next: Option<usize>,
+}
+
+impl<'a> Iterator for Siblings<'a> {
+    type Item = TreeNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.next?;
+        self.next = self.tree.nodes[index].next_sibling;
+        Some(TreeNode { tree: self.tree, index })
+    }
+}
+
+///
+/// An arena-backed tree of tags.
+///
+/// All nodes of the tree live in a single allocation and reference each other by index, and every string and binary payload is stored in one shared byte buffer.  This makes loading entire metadata sections (e.g. `Tags` or `Chapters`) considerably cheaper than building nested [`Master::Full`] variants, which allocate separately for every element.  The tree can be converted back into `TSpec` tags using [`Self::to_tag()`] when needed.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagTree;
+/// use ebml_iterable::tree::TreeValue;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let tags = vec![EmptySpec::with_children(0x1a45dfa3, vec![EmptySpec::with_data(0x4282, b"webm")])];
+/// let tree = TagTree::from_tags(tags.iter());
+///
+/// let header = tree.roots().next().unwrap();
+/// assert_eq!(Some(TreeValue::Binary(b"webm")), header.child(0x4282).map(|c| c.value()));
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct TagTree {
+    nodes: Vec<Node>,
+    data: Vec<u8>,
+    first_root: Option<usize>,
+    last_root: Option<usize>,
+    open: Vec<usize>,
+}
+
+impl TagTree {
+
+    ///
+    /// Returns a new, empty [`TagTree`].
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    ///
+    /// Returns a new, empty [`TagTree`] with room for `nodes` nodes and `data` bytes of string/binary data before reallocating.
+    ///
+    pub fn with_capacity(nodes: usize, data: usize) -> Self {
+        TagTree {
+            nodes: Vec::with_capacity(nodes),
+            data: Vec::with_capacity(data),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Builds a tree from a sequence of tags.
+    ///
+    /// See [`Self::push()`] for how the tags are arranged.
+    ///
+    pub fn from_tags<'t, TSpec, I>(tags: I) -> Self
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + 't,
+        I: IntoIterator<Item = &'t TSpec>
+    {
+        let mut tree = TagTree::new();
+        for tag in tags {
+            tree.push(tag);
+        }
+        tree
+    }
+
+    ///
+    /// Builds a tree from every tag produced by an iterator, such as a [`TagIterator`](crate::TagIterator).
+    ///
+    /// ## Errors
+    ///
+    /// Returns the first error produced by `tags`.
+    ///
+    pub fn read_from<TSpec, I>(tags: I) -> Result<Self, TagIteratorError>
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+        I: IntoIterator<Item = Result<TSpec, TagIteratorError>>
+    {
+        let mut tree = TagTree::new();
+        for tag in tags {
+            tree.push(&tag?);
+        }
+        Ok(tree)
+    }
+
+    ///
+    /// Adds a tag to the tree.
+    ///
+    /// Tags are added as children of the most recently started master that has not ended yet, or as roots if there is none.  [`Master::Start`] opens a new master node and [`Master::End`] closes it again, while [`Master::Full`] adds the master along with all of its children.  An [`Master::End`] that does not match an open master closes any masters opened inside it if that tag is open further up, and is ignored otherwise.
+    ///
+    pub fn push<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) {
+        let id = tag.get_id();
+        if let Some(master) = tag.as_master() {
+            match master {
+                Master::Start => {
+                    let index = self.add_node(id, StoredValue::Master);
+                    self.open.push(index);
+                },
+                Master::End => {
+                    if let Some(position) = self.open.iter().rposition(|index| self.nodes[*index].id == id) {
+                        self.open.truncate(position);
+                    }
+                },
+                Master::Full(children) => {
+                    let index = self.add_node(id, StoredValue::Master);
+                    self.open.push(index);
+                    for child in children {
+                        self.push(child);
+                    }
+                    // Children may have left masters open; these all belong to this tag
+                    let position = self.open.iter().rposition(|i| *i == index).expect("full master should still be open");
+                    self.open.truncate(position);
+                },
+            }
+            return;
+        }
+
+        let value = if let Some(val) = tag.as_unsigned_int() {
+            StoredValue::UnsignedInt(*val)
+        } else if let Some(val) = tag.as_signed_int() {
+            StoredValue::Integer(*val)
+        } else if let Some(val) = tag.as_float() {
+            StoredValue::Float(*val)
+        } else if let Some(val) = tag.as_date() {
+            StoredValue::Date(*val)
+        } else if let Some(val) = tag.as_utf8() {
+            let range = self.store(val.as_bytes());
+            StoredValue::Utf8(range.start, range.end)
+        } else {
+            let range = self.store(tag.as_binary().unwrap_or(&[]));
+            StoredValue::Binary(range.start, range.end)
+        };
+        self.add_node(id, value);
+    }
+
+    ///
+    /// Returns the number of nodes in the tree.
+    ///
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    ///
+    /// Returns `true` if the tree contains no nodes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    ///
+    /// Returns the node with the given id, or `None` if it is not part of this tree.
+    ///
+    pub fn get(&self, node: NodeId) -> Option<TreeNode<'_>> {
+        if node.0 < self.nodes.len() {
+            Some(TreeNode { tree: self, index: node.0 })
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns an iterator over the root nodes of the tree.
+    ///
+    pub fn roots(&self) -> Siblings<'_> {
+        Siblings { tree: self, next: self.first_root }
+    }
+
+    ///
+    /// Returns an iterator over every node in the tree, in document order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = TreeNode<'_>> {
+        (0..self.nodes.len()).map(move |index| TreeNode { tree: self, index })
+    }
+
+    ///
+    /// Converts a node (and all of its descendants) back into a `TSpec` tag.
+    ///
+    /// Master nodes are returned as [`Master::Full`] variants.  Nodes that `TSpec` does not recognize (or that were stored with a different type than `TSpec` expects) are returned as a "RawTag" when they hold binary data.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the node does not belong to this tree, or if `TSpec` does not define a master node's id as a master.
+    ///
+    pub fn to_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, node: NodeId) -> TSpec {
+        let node = self.get(node).expect("node id should belong to this tree");
+        let id = node.id();
+        match node.value() {
+            TreeValue::Master => {
+                let children = node.children().map(|child| self.to_tag(child.node_id())).collect();
+                TSpec::get_master_tag(id, Master::Full(children)).unwrap_or_else(|| panic!("Tag id 0x{:x?} was stored as a master, but the specification could not create a master tag", id))
+            },
+            TreeValue::UnsignedInt(val) => TSpec::get_unsigned_int_tag(id, val).unwrap_or_else(|| TSpec::get_raw_tag(id, &val.to_be_bytes())),
+            TreeValue::Integer(val) => TSpec::get_signed_int_tag(id, val).unwrap_or_else(|| TSpec::get_raw_tag(id, &val.to_be_bytes())),
+            TreeValue::Float(val) => TSpec::get_float_tag(id, val).unwrap_or_else(|| TSpec::get_raw_tag(id, &val.to_be_bytes())),
+            TreeValue::Date(val) => TSpec::get_date_tag(id, val).unwrap_or_else(|| TSpec::get_raw_tag(id, &val.to_be_bytes())),
+            TreeValue::Utf8(val) => TSpec::get_utf8_tag(id, val.to_string()).unwrap_or_else(|| TSpec::get_raw_tag(id, val.as_bytes())),
+            TreeValue::Binary(val) => match TSpec::get_tag_data_type(id) {
+                Some(TagDataType::Binary) => TSpec::get_binary_tag(id, val).unwrap_or_else(|| TSpec::get_raw_tag(id, val)),
+                _ => TSpec::get_raw_tag(id, val),
+            },
+        }
+    }
+
+    fn store(&mut self, bytes: &[u8]) -> Range<usize> {
+        let start = self.data.len();
+        self.data.extend_from_slice(bytes);
+        start..self.data.len()
+    }
+
+    fn add_node(&mut self, id: u64, value: StoredValue) -> usize {
+        let index = self.nodes.len();
+        let parent = self.open.last().copied();
+        self.nodes.push(Node { id, value, parent, first_child: None, last_child: None, next_sibling: None });
+
+        let previous = match parent {
+            Some(parent) => {
+                let parent = &mut self.nodes[parent];
+                let previous = parent.last_child.replace(index);
+                parent.first_child.get_or_insert(index);
+                previous
+            },
+            None => {
+                let previous = self.last_root.replace(index);
+                self.first_root.get_or_insert(index);
+                previous
+            }
+        };
+        if let Some(previous) = previous {
+            self.nodes[previous].next_sibling = Some(index);
+        }
+        index
+    }
+}
+
+///
+/// Reads an entire document from `source` into a [`TagTree`], with the children of every "Master" tag nested beneath it.
+///
+/// This is meant for small documents (or sections like an EBML header, `Tags` or `Chapters`) where navigating a tree is more convenient than handling a stream of tags.  Because the whole document is held in memory, the input is limited: "Master" tags nested more than `max_depth` levels deep produce a [`CorruptedFileError::MaxDepthExceeded`] error, and a tag that extends past the first `max_size` bytes of `source` produces a [`CorruptedFileError::MaxTagSizeExceeded`] error (with `max_size` as its limit) before its data is read.
+///
+/// ## Errors
+///
+/// Returns the first error encountered while reading `source`, or an error if the document exceeds one of the limits.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::read_tree;
+/// use ebml_iterable::tree::TreeValue;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let tree = read_tree::<EmptySpec, _>(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x80]), 8, 1024).unwrap();
+/// assert_eq!(vec![(0x81, TreeValue::Binary(&[0x01])), (0x82, TreeValue::Binary(&[]))], tree.roots().map(|node| (node.id(), node.value())).collect::<Vec<_>>());
+///
+/// assert!(read_tree::<EmptySpec, _>(Cursor::new(vec![0x81, 0x81, 0x01, 0x82, 0x80]), 8, 4).is_err());
+/// ```
+///
+pub fn read_tree<TSpec, R>(source: R, max_depth: usize, max_size: usize) -> Result<TagTree, TagIteratorError>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    R: Read
+{
+    let mut iter: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+    iter.set_max_depth(max_depth);
+    iter.set_max_tag_size(Some(max_size));
+
+    let mut tree = TagTree::new();
+    while let Some(tag) = iter.next() {
+        let tag = tag?;
+        let span = iter.last_emitted_tag_span();
+        // Unknown sized masters are limited by the children read inside of them
+        if span.offset + span.total_len().unwrap_or(span.header_len) > max_size {
+            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { position: span.offset, tag_id: tag.get_id(), size: span.data_len.unwrap_or(0), max_size }));
+        }
+        tree.push(&tag);
+    }
+    Ok(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+    use ebml_iterable_specification::{EbmlSpecification, Master};
+
+    use super::{TagTree, TreeValue};
+
+    #[test]
+    fn builds_tree_from_start_and_end_tags() {
+        let tags = [
+            EmptySpec::with_data(0x1a45dfa3, &[]),
+            EmptySpec::get_master_tag(0x18538067, Master::Start).unwrap(),
+            EmptySpec::with_data(0x4282, &[1, 2]),
+            EmptySpec::with_children(0x1254c367, vec![EmptySpec::with_data(0x7373, &[3])]),
+            EmptySpec::get_master_tag(0x18538067, Master::End).unwrap(),
+        ];
+        let tree = TagTree::from_tags(tags.iter());
+        assert_eq!(5, tree.len());
+
+        let roots: Vec<_> = tree.roots().map(|n| n.id()).collect();
+        assert_eq!(vec![0x1a45dfa3, 0x18538067], roots);
+
+        let segment = tree.roots().nth(1).unwrap();
+        let children: Vec<_> = segment.children().map(|n| (n.id(), n.value())).collect();
+        assert_eq!(vec![(0x4282, TreeValue::Binary(&[1, 2])), (0x1254c367, TreeValue::Master)], children);
+
+        let grandchild = segment.child(0x1254c367).and_then(|n| n.child(0x7373)).unwrap();
+        assert_eq!(TreeValue::Binary(&[3]), grandchild.value());
+        assert_eq!(Some(0x18538067), grandchild.parent().and_then(|p| p.parent()).map(|p| p.id()));
+    }
+
+    #[test]
+    fn converts_nodes_back_to_tags() {
+        let tag = EmptySpec::with_children(0x18538067, vec![
+            EmptySpec::with_data(0x4282, &[1, 2]),
+            EmptySpec::with_children(0x1254c367, vec![EmptySpec::with_data(0x7373, &[3])]),
+        ]);
+        let tree = TagTree::from_tags(std::iter::once(&tag));
+        let root = tree.roots().next().unwrap().node_id();
+        assert_eq!(tag, tree.to_tag(root));
+    }
+}
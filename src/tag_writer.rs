@@ -1,357 +1,1981 @@
-use std::io::Write;
-use std::convert::{TryInto, TryFrom};
-
-use crate::spec_util::validate_tag_path;
-
-use super::tag_iterator_util::EBMLSize::{self, Known, Unknown};
-
-use super::tools::{Vint, is_vint};
-use super::specs::{EbmlSpecification, EbmlTag, TagDataType, Master};
-
-use super::errors::tag_writer::TagWriterError;
-
-///
-/// Provides a tool to write EBML files based on Tags.  Writes to a destination that implements [`std::io::Write`].
-///
-/// Unlike the [`TagIterator`][`super::TagIterator`], this does not require a specification to write data. This writer provides the [`write_raw()`](#method.write_raw) method which can be used to write data that is outside of any specification.  The regular [`write()`](#method.write) method can be used to write any `TSpec` objects regardless of whether they came from a [`TagIterator`][`super::TagIterator`] or not.
-///
-
-pub struct TagWriter<W: Write>
-{
-    dest: W,
-    open_tags: Vec<(u64, EBMLSize)>,
-    working_buffer: Vec<u8>,
-}
-
-impl<W: Write> TagWriter<W>
-{
-    /// 
-    /// Returns a new [`TagWriter`] instance.
-    ///
-    /// The `dest` parameter can be anything that implements [`std::io::Write`].
-    ///
-    pub fn new(dest: W) -> Self {
-        TagWriter {
-            dest,
-            open_tags: Vec::new(),
-            working_buffer: Vec::new(),
-        }
-    }
-
-    ///
-    /// Consumes self and returns the underlying write stream.
-    /// 
-    /// Any incomplete tags are written out before returning the stream.
-    /// 
-    pub fn into_inner(mut self) -> Result<W, TagWriterError> {
-        self.flush()?;
-        Ok(self.dest)
-    }
-
-    ///
-    /// Gets a mutable reference to the underlying write stream.
-    /// 
-    pub fn get_mut(&mut self) -> &mut W {
-        &mut self.dest
-    }
-
-    ///
-    /// Gets a reference to the underlying write stream.
-    /// 
-    pub fn get_ref(&self) -> &W {
-        &self.dest
-    }
-
-    fn start_tag(&mut self, id: u64) {
-        self.open_tags.push((id, Known(self.working_buffer.len())));
-    }
-
-    fn end_tag(&mut self, id: u64) -> Result<(), TagWriterError> {
-        match self.open_tags.pop() {
-            Some(open_tag) => {
-                if open_tag.0 == id {
-                    if let Known(start) = open_tag.1 {
-                        let size: u64 = self.working_buffer.len()
-                            .checked_sub(start).expect("overflow subtracting tag size from working buffer length")
-                            .try_into().expect("couldn't convert usize to u64");
-    
-                        let size_vint = size.as_vint()
-                            .map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
-    
-                        self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied());
-                    }
-                    Ok(())
-                } else {
-                    Err(TagWriterError::UnexpectedClosingTag { tag_id: id, expected_id: Some(open_tag.0) })
-                }
-            },
-            None => Err(TagWriterError::UnexpectedClosingTag { tag_id: id, expected_id: None })
-        }
-    }
-
-    fn private_flush(&mut self) -> Result<(), TagWriterError> {
-        self.dest.write_all(self.working_buffer.drain(..).as_slice()).map_err(|source| TagWriterError::WriteError { source })?;
-        self.dest.flush().map_err(|source| TagWriterError::WriteError { source })
-    }
-
-    fn write_unsigned_int_tag(&mut self, id: u64, data: &u64) -> Result<(), TagWriterError> {
-        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-        let data = *data;
-        u8::try_from(data).map(|n| {
-                self.working_buffer.push(0x81); // vint representation of "1"
-                self.working_buffer.extend_from_slice(&n.to_be_bytes());
-            })
-            .or_else(|_| u16::try_from(data).map(|n| { 
-                self.working_buffer.push(0x82); // vint representation of "2"
-                self.working_buffer.extend_from_slice(&n.to_be_bytes());
-            }))
-            .or_else(|_| u32::try_from(data).map(|n| { 
-                self.working_buffer.push(0x84); // vint representation of "4"
-                self.working_buffer.extend_from_slice(&n.to_be_bytes());
-            }))
-            .unwrap_or_else(|_| { 
-                self.working_buffer.push(0x88); // vint representation of "8"
-                self.working_buffer.extend_from_slice(&data.to_be_bytes());
-            });
-        Ok(())
-    }
-
-    fn write_signed_int_tag(&mut self, id: u64, data: &i64) -> Result<(), TagWriterError> {
-        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-        let data = *data;
-        i8::try_from(data).map(|n| { 
-                self.working_buffer.push(0x81); // vint representation of "1"
-                self.working_buffer.extend_from_slice(&n.to_be_bytes());
-            })
-            .or_else(|_| i16::try_from(data).map(|n| { 
-                self.working_buffer.push(0x82); // vint representation of "2"
-                self.working_buffer.extend_from_slice(&n.to_be_bytes());
-            }))
-            .or_else(|_| i32::try_from(data).map(|n| { 
-                self.working_buffer.push(0x84); // vint representation of "4"
-                self.working_buffer.extend_from_slice(&n.to_be_bytes());
-            }))
-            .unwrap_or_else(|_| { 
-                self.working_buffer.push(0x88); // vint representation of "8"
-                self.working_buffer.extend_from_slice(&data.to_be_bytes());
-            });
-        Ok(())
-    }
-
-    fn write_utf8_tag(&mut self, id: u64, data: &str) -> Result<(), TagWriterError> {
-        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-
-        let slice: &[u8] = data.as_bytes();
-        let size: u64 = slice.len().try_into().expect("couldn't convert usize to u64");
-        let size_vint = size.as_vint().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
-        self.working_buffer.extend_from_slice(&size_vint);
-
-        self.working_buffer.extend_from_slice(slice);
-        Ok(())
-    }
-
-    fn write_binary_tag(&mut self, id: u64, data: &[u8]) -> Result<(), TagWriterError> {
-        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-
-        let size: u64 = data.len().try_into().expect("couldn't convert usize to u64");
-        let size_vint = size.as_vint().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
-        self.working_buffer.extend_from_slice(&size_vint);
-
-        self.working_buffer.extend_from_slice(data);
-        Ok(())
-    }
-
-    fn write_float_tag(&mut self, id: u64, data: &f64) -> Result<(), TagWriterError> {
-        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-        self.working_buffer.push(0x88); // vint representation of "8"
-        self.working_buffer.extend_from_slice(&data.to_be_bytes());
-        Ok(())
-    }
-
-    ///
-    /// Write a tag to this instance's destination.
-    ///
-    /// This method writes a tag from any specification.  There are no restrictions on the type of specification being written - it simply needs to implement the [`EbmlSpecification`] and [`EbmlTag`] traits.
-    ///
-    /// ## Errors
-    /// 
-    /// This method can error if there is a problem writing the input tag.  The different possible error states are enumerated in [`TagWriterError`].
-    ///
-    /// ## Panics
-    ///
-    /// This method can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag variant is a specific data type but it is not).  This won't happen if the specification being used was created using the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro.
-    ///
-    /// ## Examples
-    ///
-    /// ```no_run
-    /// use std::fs::File;
-    /// use ebml_iterable::TagWriter;
-    /// use ebml_iterable::specs::Master;
-    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
-    ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut file = File::create("my_ebml_file.ebml")?;
-    /// let mut my_writer = TagWriter::new(&mut file);
-    /// my_writer.write(&EmptySpec::with_children(
-    ///   0x1a45dfa3, 
-    ///   vec![EmptySpec::with_data(0x18538067, &[0x01])])
-    /// )?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    ///
-    pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
-        let tag_id = tag.get_id();
-        let tag_type = TSpec::get_tag_data_type(tag_id);
-
-        let should_validate = tag_type.is_some() && (!matches!(tag_type, Some(TagDataType::Master)) || !matches!(tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)), Master::End));
-        if should_validate && !validate_tag_path::<TSpec>(tag.get_id(), self.open_tags.iter().copied()) {
-            return Err(TagWriterError::UnexpectedTag { tag_id: tag.get_id(), current_path: self.open_tags.iter().map(|t| t.0).collect() });
-        }
-
-        match tag_type {
-            Some(TagDataType::UnsignedInt) => {
-                let val = tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id));
-                self.write_unsigned_int_tag(tag_id, val)?
-            },
-            Some(TagDataType::Integer) => {
-                let val = tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id));
-                self.write_signed_int_tag(tag_id, val)?
-            },
-            Some(TagDataType::Utf8) => {
-                let val = tag.as_utf8().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id));
-                self.write_utf8_tag(tag_id, val)?
-            },
-            Some(TagDataType::Binary) => {
-                let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", tag_id));
-                self.write_binary_tag(tag_id, val)?
-            },
-            Some(TagDataType::Float) => {
-                let val = tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id));
-                self.write_float_tag(tag_id, val)?
-            },
-            Some(TagDataType::Master) => {
-                let position = tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
-
-                match position {
-                    Master::Start => self.start_tag(tag_id),
-                    Master::End => self.end_tag(tag_id)?,
-                    Master::Full(children) => {
-                        self.start_tag(tag_id);
-                        for child in children {
-                            self.write(child)?;
-                        }
-                        self.end_tag(tag_id)?;
-                    }
-                }
-            },
-            None => { // Should be a "raw tag"
-                if !is_vint(tag_id) {
-                    return Err(TagWriterError::TagIdError(tag_id));
-                } else {
-                    let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was raw tag, but could not get binary data!", tag_id));
-                    self.write_binary_tag(tag_id, val)?
-                }
-            }
-        }
-
-        if !self.open_tags.iter().any(|t| matches!(t.1, Known(_))) {
-            self.private_flush()
-        } else {
-            Ok(())
-        }
-    }
-
-    ///
-    /// Write a tag with an unknown size to this instance's destination.
-    /// 
-    /// This method allows you to start a tag that doesn't have a known size.  Useful for streaming, or when the data is expected to be too large to fit into memory.  This method can *only* be used on Master type tags.
-    /// 
-    /// ## Errors
-    /// 
-    /// This method will return an error if the input tag is not a Master type tag, as those are the only types allowed to be of unknown size.
-    /// 
-    pub fn write_unknown_size<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
-        let tag_id = tag.get_id();
-        let tag_type = TSpec::get_tag_data_type(tag_id);
-        match tag_type {
-            Some(TagDataType::Master) => {},
-            _ => {
-                return Err(TagWriterError::TagSizeError(format!("Cannot write an unknown size for tag of type {tag_type:?}")))
-            }
-        };
-        self.working_buffer.extend(tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-        self.working_buffer.extend_from_slice(&(u64::MAX >> 7).to_be_bytes());
-        self.open_tags.push((tag_id, Unknown));
-        Ok(())
-    }
-
-    ///
-    /// Write raw tag data to this instance's destination.
-    ///
-    /// This method allows writing any tag id with any arbitrary data without using a specification.  Specifications should generally provide an `Unknown` variant to handle arbitrary unknown data which can be written through the regular [`write()`](#method.write) method, so use of this method is typically discouraged.
-    ///
-    /// ## Errors
-    /// 
-    /// This method can error if there is a problem writing the input tag.  The different possible error states are enumerated in [`TagWriterError`].
-    ///
-    /// ## Examples
-    ///
-    /// ```no_run
-    /// use std::fs::File;
-    /// use ebml_iterable::TagWriter;
-    ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut file = File::create("my_ebml_file.ebml")?;
-    /// let mut my_writer = TagWriter::new(&mut file);
-    /// my_writer.write_raw(0x1a45dfa3, &[0x18, 0x53, 0x80, 0x67, 0x81, 0x01])?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    ///
-    pub fn write_raw(&mut self, tag_id: u64, data: &[u8]) -> Result<(), TagWriterError> {
-        self.write_binary_tag(tag_id, data)?;
-        
-        if !self.open_tags.iter().any(|t| matches!(t.1, Known(_))) {
-            self.private_flush()
-        } else {
-            Ok(())
-        }        
-    }
-
-    ///
-    /// Attempts to flush all unwritten tags to the underlying destination.
-    /// 
-    /// This method can be used to finalize any open [`Master`] type tags that have not been ended.  The writer makes an attempt to close every open tag and write all bytes to the instance's destination.
-    /// 
-    /// ## Errors
-    /// 
-    /// This method can error if there is a problem writing to the destination.
-    /// 
-    pub fn flush(&mut self) -> Result<(), TagWriterError> {
-        while let Some(id) = self.open_tags.last().map(|t| t.0) {
-            self.end_tag(id)?;
-        }
-        self.private_flush()
-    }
-
-    //TODO: panic on drop if there is an open tag that hasn't been written.  Or maybe flush stream of any open tags?
-}
-
-#[cfg(test)]
-mod tests {
-    use std::io::Cursor;
-
-    use super::super::tools::Vint;
-    use super::TagWriter;
-
-    #[test]
-    fn write_ebml_tag() {
-        let mut dest = Cursor::new(Vec::new());
-        let mut writer = TagWriter::new(&mut dest);
-        writer.write_raw(0x1a45dfa3, &[]).expect("Error writing tag");
-
-        let zero_size = 0u64.as_vint().expect("Error converting [0] to vint")[0];
-        assert_eq!(vec![0x1a, 0x45, 0xdf, 0xa3, zero_size], dest.get_ref().to_vec());
-    }
-}
+use std::io::{ErrorKind, IoSlice, Seek, SeekFrom, Write};
+use std::convert::TryInto;
+
+use crate::spec_util::{check_ascii_string, validate_tag_path};
+
+use super::tag_iterator_util::EBMLSize::{Known, Unknown};
+use super::tag_iterator_util::DEFAULT_MAX_DEPTH;
+use super::tag_writer_util::{id_len, signed_int_width, size_len, unsigned_int_width, SizeWidth};
+
+use super::ebml_header::EbmlHeader;
+use super::ids::{self, CRC32, DOC_TYPE_READ_VERSION, DOC_TYPE_VERSION, VOID};
+use super::tools::{crc32_update, is_vint, read_vint, vint_bytes_u64, write_vint_with_length};
+use super::specs::{EbmlSpecification, EbmlTag, TagDataType, Master, PathPart};
+
+use super::errors::tag_writer::TagWriterError;
+
+
+///
+/// Binary payloads at least this large are written straight from the caller's slice rather than being copied into the root buffer first.
+///
+const DIRECT_WRITE_THRESHOLD: usize = 4096;
+
+///
+/// Finished child tags with bodies up to this size are copied into their parent's active chunk instead of having their chunks moved.  This keeps documents made of many small masters from growing a chunk per tag, and lets the child's buffer be reused as is.
+///
+const SMALL_TAG_COPY_LEN: usize = 256;
+
+///
+/// Overwrites bytes that were already written to a seekable destination.  The second argument is how far before the current end of the destination the bytes start.
+///
+type PatchDest<W> = fn(&mut W, u64, &[u8]) -> std::io::Result<()>;
+
+fn patch_dest<W: Write + Seek>(dest: &mut W, back: u64, bytes: &[u8]) -> std::io::Result<()> {
+    let end = dest.stream_position()?;
+    dest.seek(SeekFrom::Start(end - back))?;
+    dest.write_all(bytes)?;
+    dest.seek(SeekFrom::Start(end))?;
+    dest.flush()
+}
+
+///
+/// Appends a tag id and the vint encoding of `size` to `dest` without any intermediate allocation.  Nothing is appended if `size` cannot be represented as a vint of the requested width.
+///
+fn write_header(dest: &mut Vec<u8>, id: u64, size: u64, width: SizeWidth) -> Result<(), TagWriterError> {
+    let length = size_len(size, width);
+    if matches!(width, SizeWidth::Fixed(_)) && size >= (1 << (7 * length)) - 1 {
+        return Err(TagWriterError::TagSizeError(format!("Size {size} does not fit in a {length} byte vint")));
+    }
+
+    let start = dest.len();
+    dest.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+    match write_vint_with_length(size, length, dest) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            dest.truncate(start);
+            Err(TagWriterError::TagSizeError(e.to_string()))
+        }
+    }
+}
+
+///
+/// Width of the size of a `Void` element that is `total_len` bytes long in total, or `None` if no element can be exactly that long.
+///
+/// Widening the size by a byte takes a byte from the payload, so the narrowest width whose size limit fits the remaining payload is used.
+///
+fn void_size_len(total_len: usize) -> Option<usize> {
+    (1..=8).find(|&width| {
+        matches!(total_len.checked_sub(1 + width), Some(payload) if (payload as u64) < (1 << (7 * width)) - 1)
+    })
+}
+
+fn sized_len(id: u64, body_len: u64, width: SizeWidth) -> u64 {
+    (id_len(id) + size_len(body_len, width)) as u64 + body_len
+}
+
+///
+/// Calculates the encoded length of `tag` (header included) as [`TagWriter::write()`] would write it.
+///
+/// The body size of every [`Master::Full`] tag in the tree is pushed to `sizes` in the order the tags are written.  Returns `None` if the tree contains [`Master::Start`] or [`Master::End`] tags, whose sizes can't be known up front.
+///
+fn measure_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(tag: &TSpec, sizes: &mut Vec<u64>, width: SizeWidth, float_width: FloatWidth, int_width: Option<u8>) -> Option<u64> {
+    let id = tag.get_id();
+    let len = match TSpec::get_tag_data_type(id) {
+        Some(TagDataType::UnsignedInt) => sized_len(id, int_width.map_or(unsigned_int_width(*tag.as_unsigned_int()?), usize::from) as u64, width),
+        Some(TagDataType::Integer) => sized_len(id, int_width.map_or(signed_int_width(*tag.as_signed_int()?), usize::from) as u64, width),
+        Some(TagDataType::Float) => sized_len(id, float_width.len(*tag.as_float()?) as u64, width),
+        Some(TagDataType::Date) => sized_len(id, 8, width),
+        Some(TagDataType::Utf8) => sized_len(id, tag.as_utf8()?.len() as u64, width),
+        Some(TagDataType::Binary) | None => sized_len(id, tag.as_binary()?.len() as u64, width),
+        Some(TagDataType::Master) => match tag.as_master()? {
+            Master::Full(children) => {
+                let index = sizes.len();
+                sizes.push(0);
+                let mut body_len = 0;
+                for child in children {
+                    body_len += measure_tag(child, sizes, width, float_width, int_width)?;
+                }
+                sizes[index] = body_len;
+                sized_len(id, body_len, width)
+            },
+            _ => return None,
+        },
+    };
+    Some(len)
+}
+
+///
+/// Finds the first [`Master::Full`] tag in `tag`'s tree (`tag` itself included) that is nested more than `max_depth` levels deep.
+///
+/// The tree is walked with an explicit stack so that trees too deep to write can't overflow the call stack while being checked.
+///
+fn find_too_deep<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(tag: &TSpec, max_depth: usize) -> Option<u64> {
+    let mut pending = vec![(tag, 1)];
+    while let Some((tag, depth)) = pending.pop() {
+        if let Some(Master::Full(children)) = tag.as_master() {
+            if depth > max_depth {
+                return Some(tag.get_id());
+            }
+            pending.extend(children.iter().map(|child| (child, depth + 1)));
+        }
+    }
+    None
+}
+
+///
+/// Checks whether `tag`'s tree (`tag` itself included) contains a "Master" tag with one of `ids`.
+///
+fn contains_master<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(tag: &TSpec, ids: &[u64]) -> bool {
+    let mut pending = vec![tag];
+    while let Some(tag) = pending.pop() {
+        if let Some(master) = tag.as_master() {
+            if ids.contains(&tag.get_id()) {
+                return true;
+            }
+            if let Master::Full(children) = master {
+                pending.extend(children.iter());
+            }
+        }
+    }
+    false
+}
+
+///
+/// Location of a written `DocTypeVersion` or `DocTypeReadVersion` payload, kept so it can be patched later.
+///
+/// `offset` is relative to the start of the buffer at `depth`, except for the root buffer (depth 0), whose offsets are relative to the start of the destination.
+///
+struct VersionSite {
+    id: u64,
+    depth: usize,
+    offset: usize,
+    width: usize,
+    value: u64,
+}
+
+///
+/// Location of the size of a finished tag that was written with an unknown size, kept so the actual size can be patched in later.  Offsets work like in [`VersionSite`].
+///
+struct SizeSite {
+    depth: usize,
+    offset: usize,
+    width: usize,
+    size: u64,
+}
+
+///
+/// How the size of an open master tag is determined.
+///
+#[derive(Copy, Clone)]
+enum OpenTagSize {
+    /// The body is collected in `buffers[depth]` and the size is written in the given width once the tag ends.
+    Buffered(usize, SizeWidth),
+    /// The size was written up front, either by the caller or after measuring a [`Master::Full`] tree; the body was expected to start at `start` (in the coordinates of `buffers[depth]`).
+    Declared { depth: usize, start: usize, size: usize },
+    /// The tag was written with an unknown size `width` bytes wide; the body started at `start` (in the coordinates of `buffers[depth]`).
+    Unknown { depth: usize, start: usize, width: usize },
+}
+
+///
+/// The stack of open master tags.
+///
+/// A writer that is dropped with open tags leaves a truncated document in its destination, so this fails a `debug_assert!` when dropped with tags still open.  (The writer itself can't end the tags on drop without holding borrowed destinations for its whole scope.)
+///
+#[derive(Default)]
+struct OpenTags(Vec<(u64, OpenTagSize)>);
+
+impl OpenTags {
+    fn ids(&self) -> Vec<u64> {
+        self.0.iter().map(|t| t.0).collect()
+    }
+}
+
+impl std::ops::Deref for OpenTags {
+    type Target = Vec<(u64, OpenTagSize)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for OpenTags {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Drop for OpenTags {
+    fn drop(&mut self) {
+        // Don't turn an unrelated panic into an abort
+        if !std::thread::panicking() {
+            debug_assert!(self.0.is_empty(), "TagWriter dropped while tags {:x?} were still open, so the written document is truncated.  Use TagWriter::into_inner() or TagWriter::finish() to end the document.", self.ids());
+        }
+    }
+}
+
+///
+/// Bytes buffered for a single open tag.
+///
+/// The data is kept as a list of chunks so that a finished child tag can be added to its parent by moving its chunks rather than copying (or shifting) the bytes.  Small children are copied instead (see [`SMALL_TAG_COPY_LEN`]).  Only the last chunk is written to directly.
+///
+struct TagBuffer {
+    chunks: Vec<Vec<u8>>,
+    sealed_len: usize,
+}
+
+impl TagBuffer {
+    fn new() -> Self {
+        TagBuffer { chunks: vec![Vec::new()], sealed_len: 0 }
+    }
+
+    fn len(&self) -> usize {
+        self.sealed_len + self.chunks.last().map_or(0, Vec::len)
+    }
+
+    fn active(&mut self) -> &mut Vec<u8> {
+        self.chunks.last_mut().expect("tag buffer should always have an active chunk")
+    }
+
+    ///
+    /// Appends the body of a finished child tag (whose header has already been written to the active chunk), leaving `body` empty and ready for reuse.
+    ///
+    fn append_body(&mut self, body: &mut TagBuffer) {
+        if body.chunks.len() == 1 && body.sealed_len == 0 && body.active().len() <= SMALL_TAG_COPY_LEN {
+            self.active().extend_from_slice(&body.chunks[0]);
+            body.chunks[0].clear();
+        } else {
+            self.sealed_len = self.len() + body.sealed_len;
+            self.chunks.append(&mut body.chunks);
+            body.chunks.push(Vec::new());
+            body.sealed_len = 0;
+        }
+    }
+
+    ///
+    /// Returns the length of the `CRC-32` element at the start of the buffer, if there is one.
+    ///
+    fn leading_crc32_len(&self) -> Option<usize> {
+        let first = &self.chunks[0];
+        if first.first() != Some(&(CRC32 as u8)) {
+            return None;
+        }
+        match read_vint(&first[1..]) {
+            Ok(Some((size, size_len))) => {
+                let size: usize = size.try_into().ok()?;
+                Some(1 + size_len + size).filter(|len| *len <= first.len())
+            },
+            _ => None,
+        }
+    }
+
+    fn remove_prefix(&mut self, len: usize) {
+        self.chunks[0].drain(..len);
+        if self.chunks.len() > 1 {
+            self.sealed_len -= len;
+        }
+    }
+
+    fn crc32(&self) -> u32 {
+        self.chunks.iter().fold(0, |crc, chunk| crc32_update(crc, chunk))
+    }
+
+    fn patch(&mut self, offset: usize, bytes: &[u8]) {
+        let mut chunk_start = 0;
+        for chunk in self.chunks.iter_mut() {
+            let chunk_end = chunk_start + chunk.len();
+            let from = offset.max(chunk_start);
+            let to = (offset + bytes.len()).min(chunk_end);
+            if from < to {
+                chunk[(from - chunk_start)..(to - chunk_start)].copy_from_slice(&bytes[(from - offset)..(to - offset)]);
+            }
+            chunk_start = chunk_end;
+        }
+    }
+
+    fn write_to<W: Write>(&mut self, dest: &mut W, trailing: &[u8]) -> std::io::Result<()> {
+        let mut slices: Vec<IoSlice> = self.chunks.iter()
+            .map(|chunk| chunk.as_slice())
+            .chain(std::iter::once(trailing))
+            .filter(|slice| !slice.is_empty())
+            .map(IoSlice::new)
+            .collect();
+        let mut remaining = &mut slices[..];
+        while !remaining.is_empty() {
+            match dest.write_vectored(remaining) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => IoSlice::advance_slices(&mut remaining, written),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {},
+                Err(err) => return Err(err),
+            }
+        }
+        // Keep the first chunk's allocation around for the next batch of writes
+        self.chunks.truncate(1);
+        self.chunks[0].clear();
+        self.sealed_len = 0;
+        Ok(())
+    }
+}
+
+///
+/// A destination that can be both written and seeked, so seekable destinations can be used as trait objects.
+///
+/// `Box<dyn Write + Seek>` isn't a valid type, but `TagWriter<Box<dyn WriteSeek>>` supports everything that needs a seekable destination, such as [`TagWriter::patch_doc_type_versions()`].  This is implemented for every type that implements [`Write`] and [`Seek`].
+///
+pub trait WriteSeek: Write + Seek {}
+
+impl<T: Write + Seek + ?Sized> WriteSeek for T {}
+
+///
+/// Controls when a [`TagWriter`] writes finished tags to its destination and flushes it.
+///
+/// Tags can only be written out once nothing before them is waiting on a size (i.e. once every open tag was started with [`TagWriter::write_unknown_size()`] or [`TagWriter::start_tag_with_size()`], or there are no open tags).  The policy decides what happens at those points.  Regardless of the policy, [`TagWriter::flush()`] and [`TagWriter::into_inner()`] always write out everything and flush the destination.
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FlushPolicy {
+    ///
+    /// Write finished tags out and call [`Write::flush()`] on the destination every time.  This is the default, and keeps the destination as up to date as possible (useful for live streaming), but is slow for buffered or network destinations.
+    ///
+    #[default]
+    EveryTopLevel,
+
+    ///
+    /// Keep finished tags in memory until at least this many bytes are ready, then write them out and flush the destination.
+    ///
+    EveryNBytes(usize),
+
+    ///
+    /// Write finished tags out immediately, but never call [`Write::flush()`] on the destination unless [`TagWriter::flush()`] is called.
+    ///
+    Never,
+
+    ///
+    /// Keep everything in memory until [`TagWriter::flush()`] is called.
+    ///
+    Manual,
+}
+
+///
+/// Controls how many bytes a [`TagWriter`] uses to write float tags.
+///
+/// EBML floats are stored as either 4 or 8 bytes, and both are read by [`TagIterator`](crate::TagIterator).
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FloatWidth {
+    ///
+    /// Always use 8 bytes.  This is the default.
+    ///
+    #[default]
+    Double,
+
+    ///
+    /// Always use 4 bytes, rounding values to the nearest `f32`.
+    ///
+    Single,
+
+    ///
+    /// Use 4 bytes for values that can be written as an `f32` without losing precision, and 8 bytes otherwise.
+    ///
+    Lossless,
+}
+
+impl FloatWidth {
+    fn len(self, val: f64) -> usize {
+        match self {
+            FloatWidth::Double => 8,
+            FloatWidth::Single => 4,
+            FloatWidth::Lossless if val.is_nan() || f64::from(val as f32) == val => 4,
+            FloatWidth::Lossless => 8,
+        }
+    }
+}
+
+///
+/// Encoding options for a single call to [`TagWriter::write_with_options()`].
+///
+/// Options left as `None` use the writer's configuration.
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct WriteOptions {
+    ///
+    /// Write integer tags with exactly this many bytes (1 to 8) instead of the narrowest width that holds their value.
+    ///
+    pub int_width: Option<u8>,
+
+    ///
+    /// Write float tags with this width instead of the one configured with [`TagWriter::set_float_width()`].
+    ///
+    pub float_width: Option<FloatWidth>,
+
+    ///
+    /// Write sizes with this width instead of the one configured with [`TagWriter::set_size_width()`].  For a [`Master::Start`] tag this applies to its size, which is written once the tag ends.
+    ///
+    /// [`TagSpan::size_width()`](crate::iterator::TagSpan::size_width) gives the width a tag was read with, so a read-modify-write pipeline can keep the original layout of the tags it doesn't change.
+    ///
+    pub size_width: Option<SizeWidth>,
+}
+
+///
+/// Provides a tool to write EBML files based on Tags.  Writes to a destination that implements [`std::io::Write`].
+///
+/// Unlike the [`TagIterator`][`super::TagIterator`], this does not require a specification to write data. This writer provides the [`write_raw()`](#method.write_raw) method which can be used to write data that is outside of any specification.  The regular [`write()`](#method.write) method can be used to write any `TSpec` objects regardless of whether they came from a [`TagIterator`][`super::TagIterator`] or not.  A writer created with [`with_spec()`](#method.with_spec) additionally checks every tag against the data types of a specification.
+///
+/// A document should be ended with [`into_inner()`](#method.into_inner) or [`finish()`](#method.finish).  Dropping a writer while tags are still open leaves a truncated document in the destination, so it fails a `debug_assert!` (debug builds panic, release builds don't check).  To deliberately abandon an incomplete document, e.g. after a write error, call [`finish()`](#method.finish) and ignore the [`TagWriterError::UnclosedTags`] it returns.
+///
+pub struct TagWriter<W: Write>
+{
+    dest: W,
+    open_tags: OpenTags,
+    buffers: Vec<TagBuffer>,
+    spare_buffers: Vec<TagBuffer>,
+    tree_sizes: Vec<u64>,
+    tree_depth: usize,
+    bytes_flushed: usize,
+    version_sites: Vec<VersionSite>,
+    required_version: Option<u64>,
+    required_read_version: Option<u64>,
+    flush_policy: FlushPolicy,
+    max_depth: usize,
+    size_width: SizeWidth,
+    float_width: FloatWidth,
+    int_width: Option<u8>,
+    crc32_tags: Vec<u64>,
+    deterministic: bool,
+    streaming: bool,
+    close_nested: bool,
+    size_sites: Option<Vec<SizeSite>>,
+    seekable_dest: Option<PatchDest<W>>,
+    spec_data_type: Option<fn(u64) -> Option<TagDataType>>,
+    spec_ascii_string: Option<fn(u64) -> bool>,
+}
+
+impl<W: Write> TagWriter<W>
+{
+    /// 
+    /// Returns a new [`TagWriter`] instance.
+    ///
+    /// The `dest` parameter can be anything that implements [`std::io::Write`].
+    ///
+    pub fn new(dest: W) -> Self {
+        TagWriter {
+            dest,
+            open_tags: OpenTags::default(),
+            buffers: vec![TagBuffer::new()],
+            spare_buffers: Vec::new(),
+            tree_sizes: Vec::new(),
+            tree_depth: 0,
+            bytes_flushed: 0,
+            version_sites: Vec::new(),
+            required_version: None,
+            required_read_version: None,
+            flush_policy: FlushPolicy::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            size_width: SizeWidth::default(),
+            float_width: FloatWidth::default(),
+            int_width: None,
+            crc32_tags: Vec::new(),
+            deterministic: false,
+            streaming: false,
+            close_nested: false,
+            size_sites: None,
+            seekable_dest: None,
+            spec_data_type: None,
+            spec_ascii_string: None,
+        }
+    }
+
+    ///
+    /// Returns a new [`TagWriter`] instance that validates tags against the specification `T`.
+    ///
+    /// Every tag written is checked against the data type `T` declares for its id, so tags from a different specification (or raw data written with [`Self::write_raw()`]) can't produce an element that readers using `T` would fail to parse.  A tag that doesn't match is rejected with [`TagWriterError::TypeMismatch`] before anything is written.  Raw data is only accepted for ids that `T` declares as binary or doesn't know, and strings passed to [`Self::write_utf8()`] must be printable ASCII for ids that `T` declares as EBML `string` elements (see [`EbmlSpecification::is_ascii_string()`]).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    /// use ebml_iterable::error::TagWriterError;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut writer = TagWriter::with_spec::<EmptySpec>(Vec::new());
+    /// writer.write_raw(0x81, &[0x01]).unwrap();
+    /// assert!(matches!(writer.write_utf8(0x81, "text"), Err(TagWriterError::TypeMismatch { tag_id: 0x81, .. })));
+    /// ```
+    ///
+    pub fn with_spec<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(dest: W) -> Self {
+        let mut writer = TagWriter::new(dest);
+        writer.spec_data_type = Some(T::get_tag_data_type);
+        writer.spec_ascii_string = Some(T::is_ascii_string);
+        writer
+    }
+
+    ///
+    /// Checks that a tag holding `data_type` data (`None` for raw data) matches the specification passed to [`Self::with_spec()`], if any.
+    ///
+    fn check_spec_data_type(&self, tag_id: u64, data_type: Option<TagDataType>) -> Result<(), TagWriterError> {
+        let expected = match self.spec_data_type {
+            Some(spec_data_type) => spec_data_type(tag_id),
+            None => return Ok(()),
+        };
+        let matches = match expected {
+            None | Some(TagDataType::Binary) => matches!(data_type, None | Some(TagDataType::Binary)),
+            Some(expected) => data_type == Some(expected),
+        };
+        if !matches {
+            return Err(TagWriterError::TypeMismatch { tag_id, expected, found: data_type });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns a new [`TagWriter`] instance in streaming mode.  See [`Self::set_streaming()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let writer = TagWriter::new_streaming(Vec::new());
+    /// ```
+    ///
+    pub fn new_streaming(dest: W) -> Self {
+        let mut writer = TagWriter::new(dest);
+        writer.set_streaming(true);
+        writer
+    }
+
+    ///
+    /// Configures when finished tags are written to the destination and when the destination is flushed.
+    ///
+    /// The default is [`FlushPolicy::EveryTopLevel`].  See [`FlushPolicy`] for the available options.
+    ///
+    pub fn set_flush_policy(&mut self, policy: FlushPolicy) {
+        self.flush_policy = policy;
+    }
+
+    ///
+    /// Configures how deeply "Master" tags may be nested.
+    ///
+    /// The default is [`DEFAULT_MAX_DEPTH`](crate::writer::DEFAULT_MAX_DEPTH).  Writing a tag that would be nested deeper returns [`TagWriterError::MaxDepthExceeded`] instead of risking a stack overflow while writing [`Master::Full`] trees.
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    ///
+    /// Configures how many bytes are used to write the size of each tag.
+    ///
+    /// The default is [`SizeWidth::Minimal`].  A fixed width keeps the layout of a document independent of the size of its contents (for example so it can be edited in place later).
+    ///
+    /// ## Errors
+    ///
+    /// This method returns [`TagWriterError::TagSizeError`] and keeps the current width if `width` is a [`SizeWidth::Fixed`] width outside of 1 to 8.
+    ///
+    pub fn set_size_width(&mut self, width: SizeWidth) -> Result<(), TagWriterError> {
+        if let SizeWidth::Fixed(width @ (0 | 9..)) = width {
+            return Err(TagWriterError::TagSizeError(format!("Vint widths must be between 1 and 8 bytes, got {width}")));
+        }
+        self.size_width = width;
+        Ok(())
+    }
+
+    ///
+    /// Configures how many bytes are used to write float tags.
+    ///
+    /// The default is [`FloatWidth::Double`].  Many Matroska muxers write 4-byte floats for elements like `SamplingFrequency`, which [`FloatWidth::Lossless`] reproduces without changing any values.
+    ///
+    pub fn set_float_width(&mut self, width: FloatWidth) {
+        self.float_width = width;
+    }
+
+    ///
+    /// Enables or disables deterministic output.
+    ///
+    /// Without this mode, a document can be written in different ways - e.g. [`Self::write_unknown_size()`] produces different bytes than writing the same tags with [`Master::Start`] or as a [`Master::Full`].  In deterministic mode, tags started with [`Self::write_unknown_size()`] are buffered like [`Master::Start`] tags and written with their actual size, so the output depends only on the sequence of tags and the [`SizeWidth`], never on how the tags were submitted or on the [`FlushPolicy`].  The writer never generates data of its own (such as timestamps or UIDs), so identical tags always produce identical bytes, as needed for reproducible builds and content-addressed storage.
+    ///
+    /// Since nothing can be written until the buffered tags are closed, this mode isn't suitable for live streaming.
+    ///
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    ///
+    /// Enables or disables streaming mode.
+    ///
+    /// Normally the children of a [`Master::Start`] tag are held in memory until the tag ends, since its size has to be written before them.  In streaming mode, [`Master::Start`] tags are written like [`Self::write_unknown_size()`] instead, so children go straight to the destination and a document of any size (such as a multi-gigabyte `Segment`) can be written in constant memory.  [`Master::Full`] tags are unaffected, as their size is measured up front.
+    ///
+    /// Readers have to find the end of unknown-size tags from the tags that follow them, which is less efficient and not allowed for every tag by some specifications.  When the destination implements [`Seek`], [`TagWriter::new_seekable()`] fills in the actual size of each tag when it ends, or [`Self::set_backpatch_sizes()`] can be used to fill them in later.  Deterministic mode takes precedence over streaming mode.
+    ///
+    pub fn set_streaming(&mut self, streaming: bool) {
+        self.streaming = streaming;
+    }
+
+    ///
+    /// Enables or disables ending nested tags along with their parent.
+    ///
+    /// By default, writing the [`Master::End`] variant of a tag that isn't the most recently started open tag returns a [`TagWriterError::UnexpectedClosingTag`].  When this is enabled, every tag opened inside of it is ended first (writing their sizes), the way many event based sources deliver EBML - e.g. a `Segment` end closes the last `Cluster` too.  Ending a tag that isn't open at all is still an error.
+    ///
+    pub fn set_close_nested_on_end(&mut self, close_nested: bool) {
+        self.close_nested = close_nested;
+    }
+
+    ///
+    /// Configures which "Master" tags get a `CRC-32` element as their first child.
+    ///
+    /// The checksum covers all of the tag's other children and is calculated when the tag ends, so these tags are always held in memory until then - even in streaming mode or when started with [`Self::write_unknown_size()`].  A `CRC-32` element written as the first child of one of these tags (e.g. when copying a document) is replaced by the new checksum.  Tags started with [`Self::start_tag_with_size()`] don't get a checksum, as their size has already been written.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.set_crc32_tags(&[0x1a45dfa3]);
+    /// writer.write_unknown_size_start(0x1a45dfa3).unwrap();
+    /// writer.write_raw(0x4286, &[0x01]).unwrap();
+    /// // Open tags are ended by `into_inner()`
+    /// assert_eq!(vec![0x1a, 0x45, 0xdf, 0xa3, 0x8a, 0xbf, 0x84, 0x04, 0xb1, 0x7e, 0xa0, 0x42, 0x86, 0x81, 0x01], writer.into_inner().unwrap());
+    /// ```
+    ///
+    pub fn set_crc32_tags(&mut self, tag_ids: &[u64]) {
+        self.crc32_tags = tag_ids.to_vec();
+    }
+
+    fn check_depth(&self, tag_id: u64) -> Result<(), TagWriterError> {
+        if self.open_tags.len() >= self.max_depth {
+            return Err(TagWriterError::MaxDepthExceeded { tag_id, max_depth: self.max_depth });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Consumes self and returns the underlying write stream.
+    /// 
+    /// Any incomplete tags are written out before returning the stream.
+    /// 
+    pub fn into_inner(mut self) -> Result<W, TagWriterError> {
+        self.flush()?;
+        Ok(self.dest)
+    }
+
+    ///
+    /// Consumes self and returns the underlying write stream, after checking that every tag was ended.
+    ///
+    /// Unlike [`Self::into_inner()`], open tags are not ended automatically, so a document that was left incomplete by mistake is reported instead of being quietly closed.
+    ///
+    /// ## Errors
+    ///
+    /// This method will return [`TagWriterError::UnclosedTags`] if any tags are still open, or an error if writing to the destination fails.  Nothing more is written to the destination when tags are still open.
+    ///
+    pub fn finish(mut self) -> Result<W, TagWriterError> {
+        if !self.open_tags.is_empty() {
+            let tag_ids = self.open_tags.ids();
+            // The error already reports the open tags, so the drop doesn't need to
+            self.open_tags.clear();
+            return Err(TagWriterError::UnclosedTags { tag_ids });
+        }
+        self.flush_with(&[], true)?;
+        Ok(self.dest)
+    }
+
+    ///
+    /// Replaces the destination so the writer can be reused for another document, and returns the previous destination.
+    ///
+    /// Any finished tags still held in memory are written to the previous destination (which is then flushed) before it is returned.  The flush policy, depth limit, size width and deterministic mode are kept, and so are the writer's internal buffers, so services writing many small documents don't need to reallocate them for each one.  Everything else, including the state tracked for [`Self::required_doc_type_version()`], [`Self::patch_doc_type_versions()`] and [`Self::patch_sizes()`], starts over.
+    ///
+    /// ## Errors
+    ///
+    /// This method will return [`TagWriterError::UnclosedTags`] (leaving the writer unchanged) if any tags are still open, or an error if writing to the previous destination fails.
+    ///
+    pub fn reset(&mut self, dest: W) -> Result<W, TagWriterError> {
+        if !self.open_tags.is_empty() {
+            return Err(TagWriterError::UnclosedTags { tag_ids: self.open_tags.ids() });
+        }
+        self.flush_with(&[], true)?;
+
+        self.bytes_flushed = 0;
+        self.version_sites.clear();
+        if let Some(sites) = self.size_sites.as_mut() {
+            sites.clear();
+        }
+        self.required_version = None;
+        self.required_read_version = None;
+        Ok(std::mem::replace(&mut self.dest, dest))
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying write stream.
+    /// 
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.dest
+    }
+
+    ///
+    /// Gets a reference to the underlying write stream.
+    /// 
+    pub fn get_ref(&self) -> &W {
+        &self.dest
+    }
+
+    fn buffer(&mut self) -> &mut Vec<u8> {
+        self.buffers.last_mut().expect("root buffer should never be removed").active()
+    }
+
+    fn start_tag(&mut self, id: u64) -> Result<(), TagWriterError> {
+        self.check_depth(id)?;
+        self.open_tags.push((id, OpenTagSize::Buffered(self.buffers.len(), self.size_width)));
+        let buffer = self.spare_buffers.pop().unwrap_or_else(TagBuffer::new);
+        self.buffers.push(buffer);
+        Ok(())
+    }
+
+    fn end_tag(&mut self, id: u64) -> Result<(), TagWriterError> {
+        match self.open_tags.pop() {
+            Some(open_tag) => {
+                if open_tag.0 == id {
+                    match open_tag.1 {
+                        OpenTagSize::Buffered(depth, width) => {
+                            let mut body = self.buffers.pop().expect("known size tag should have its own buffer");
+                            debug_assert_eq!(depth, self.buffers.len());
+                            let size: u64 = body.len().try_into().expect("couldn't convert usize to u64");
+
+                            let crc32 = self.crc32_tags.contains(&id);
+                            let replaced_len = if crc32 { body.leading_crc32_len().unwrap_or(0) } else { 0 };
+                            let size = if crc32 { size - replaced_len as u64 + sized_len(CRC32, 4, width) } else { size };
+
+                            let parent_depth = depth - 1;
+                            let origin = self.buffer_origin(parent_depth);
+                            let parent = &mut self.buffers[parent_depth];
+                            if let Err(err) = write_header(parent.active(), open_tag.0, size, width) {
+                                self.buffers.push(body);
+                                self.open_tags.push(open_tag);
+                                return Err(err);
+                            }
+                            if crc32 {
+                                if replaced_len > 0 {
+                                    body.remove_prefix(replaced_len);
+                                    for site in self.version_sites.iter_mut().filter(|site| site.depth == depth) {
+                                        site.offset -= replaced_len;
+                                    }
+                                    for site in self.size_sites.iter_mut().flatten().filter(|site| site.depth == depth) {
+                                        site.offset -= replaced_len;
+                                    }
+                                }
+                                write_header(parent.active(), CRC32, 4, width).expect("a 4 byte size should fit in any width");
+                                parent.active().extend_from_slice(&body.crc32().to_le_bytes());
+                            }
+
+                            let body_offset = origin + parent.len();
+                            for site in self.version_sites.iter_mut().filter(|site| site.depth == depth) {
+                                site.depth = parent_depth;
+                                site.offset += body_offset;
+                            }
+                            for site in self.size_sites.iter_mut().flatten().filter(|site| site.depth == depth) {
+                                site.depth = parent_depth;
+                                site.offset += body_offset;
+                            }
+                            parent.append_body(&mut body);
+                            self.spare_buffers.push(body);
+                        },
+                        OpenTagSize::Declared { depth, start, size } => {
+                            let written = self.buffer_origin(depth) + self.buffers[depth].len() - start;
+                            if written != size {
+                                return Err(TagWriterError::TagSizeError(format!("Tag 0x{id:x?} was started with a size of {size} bytes, but {written} bytes were written")));
+                            }
+                        },
+                        OpenTagSize::Unknown { depth, start, width } => {
+                            let size = (self.buffer_origin(depth) + self.buffers[depth].len() - start) as u64;
+                            let site = SizeSite { depth, offset: start - width, width, size };
+                            if let Some(patch) = self.seekable_dest {
+                                self.patch_size_site(&site, patch)?;
+                            } else if let Some(sites) = self.size_sites.as_mut() {
+                                sites.push(site);
+                            }
+                        },
+                    }
+                    Ok(())
+                } else {
+                    self.open_tags.push(open_tag);
+                    Err(TagWriterError::UnexpectedClosingTag { tag_id: id, expected_id: Some(open_tag.0) })
+                }
+            },
+            None => Err(TagWriterError::UnexpectedClosingTag { tag_id: id, expected_id: None })
+        }
+    }
+
+    ///
+    /// Ends the open tag `id`, along with any tags opened inside of it if [`Self::set_close_nested_on_end()`] is enabled.
+    ///
+    fn end_master(&mut self, id: u64) -> Result<(), TagWriterError> {
+        if self.close_nested {
+            if let Some(index) = self.open_tags.iter().rposition(|t| t.0 == id) {
+                while self.open_tags.len() > index + 1 {
+                    let nested = self.open_tags[self.open_tags.len() - 1].0;
+                    self.end_tag(nested)?;
+                }
+            }
+        }
+        self.end_tag(id)
+    }
+
+    ///
+    /// Writes the actual size of a finished unknown-size tag over its unknown size marker, unless it doesn't fit in the reserved width.
+    ///
+    fn patch_size_site(&mut self, site: &SizeSite, patch: PatchDest<W>) -> Result<(), TagWriterError> {
+        if site.width < 8 && site.size >= (1 << (7 * site.width)) - 1 {
+            return Ok(());
+        }
+
+        let bytes = vint_bytes_u64(site.size, site.width);
+        let bytes = &bytes[(8 - site.width)..];
+        if site.depth > 0 || site.offset >= self.bytes_flushed {
+            let start = site.offset - self.buffer_origin(site.depth);
+            self.buffers[site.depth].patch(start, bytes);
+            Ok(())
+        } else {
+            patch(&mut self.dest, (self.bytes_flushed - site.offset) as u64, bytes).map_err(|source| TagWriterError::WriteError { source })
+        }
+    }
+
+    fn buffer_origin(&self, depth: usize) -> usize {
+        if depth == 0 {
+            self.bytes_flushed
+        } else {
+            0
+        }
+    }
+
+    ///
+    /// Called whenever the root buffer only holds finished tags.  Writes them out and flushes the destination as allowed by the flush policy.
+    ///
+    fn private_flush(&mut self) -> Result<(), TagWriterError> {
+        match self.flush_policy {
+            FlushPolicy::EveryTopLevel => self.flush_with(&[], true),
+            FlushPolicy::EveryNBytes(bytes) if self.buffers[0].len() >= bytes => self.flush_with(&[], true),
+            FlushPolicy::Never => self.flush_with(&[], false),
+            FlushPolicy::EveryNBytes(_) | FlushPolicy::Manual => Ok(()),
+        }
+    }
+
+    ///
+    /// Writes the root buffer followed by `trailing` to the destination in a single vectored write, then flushes the destination if `flush_dest` is set.
+    ///
+    fn flush_with(&mut self, trailing: &[u8], flush_dest: bool) -> Result<(), TagWriterError> {
+        let root = &mut self.buffers[0];
+        self.bytes_flushed += root.len() + trailing.len();
+        root.write_to(&mut self.dest, trailing).map_err(|source| TagWriterError::WriteError { source })?;
+        if flush_dest {
+            self.dest.flush().map_err(|source| TagWriterError::WriteError { source })?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Gets the number of bytes to write an integer in, given the narrowest width that holds it.
+    ///
+    fn int_width(&self, id: u64, minimal_width: usize) -> Result<usize, TagWriterError> {
+        match self.int_width {
+            Some(width) if usize::from(width) < minimal_width => Err(TagWriterError::TagSizeError(format!("Value of tag 0x{id:x} does not fit in {width} bytes"))),
+            Some(width) => Ok(usize::from(width)),
+            None => Ok(minimal_width),
+        }
+    }
+
+    fn write_unsigned_int_tag(&mut self, id: u64, data: &u64) -> Result<(), TagWriterError> {
+        let depth = self.buffers.len() - 1;
+        let data = *data;
+        let width = self.int_width(id, unsigned_int_width(data))?;
+        let size_width = self.size_width;
+        write_header(self.buffer(), id, width as u64, size_width)?;
+        let payload_start = self.buffer_origin(depth) + self.buffers[depth].len();
+        self.buffer().extend_from_slice(&data.to_be_bytes()[(8 - width)..]);
+
+        if id == DOC_TYPE_VERSION || id == DOC_TYPE_READ_VERSION {
+            let width = self.buffers[depth].len() + self.buffer_origin(depth) - payload_start;
+            self.version_sites.push(VersionSite { id, depth, offset: payload_start, width, value: data });
+        }
+        Ok(())
+    }
+
+    fn write_signed_int_tag(&mut self, id: u64, data: &i64) -> Result<(), TagWriterError> {
+        let data = *data;
+        let width = self.int_width(id, signed_int_width(data))?;
+        let size_width = self.size_width;
+        let buffer = self.buffer();
+        write_header(buffer, id, width as u64, size_width)?;
+        buffer.extend_from_slice(&data.to_be_bytes()[(8 - width)..]);
+        Ok(())
+    }
+
+    fn write_utf8_tag(&mut self, id: u64, data: &str) -> Result<(), TagWriterError> {
+        let size_width = self.size_width;
+        let buffer = self.buffer();
+        let slice: &[u8] = data.as_bytes();
+        let size: u64 = slice.len().try_into().expect("couldn't convert usize to u64");
+        write_header(buffer, id, size, size_width)?;
+        buffer.extend_from_slice(slice);
+        Ok(())
+    }
+
+    fn write_binary_tag(&mut self, id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        let write_through = self.buffers.len() == 1 && data.len() >= DIRECT_WRITE_THRESHOLD && self.flush_policy != FlushPolicy::Manual;
+        let size_width = self.size_width;
+        let buffer = self.buffer();
+        let size: u64 = data.len().try_into().expect("couldn't convert usize to u64");
+        write_header(buffer, id, size, size_width)?;
+
+        if write_through {
+            // Nothing needs buffering, so the payload can go out with the header without being copied
+            let flush_dest = match self.flush_policy {
+                FlushPolicy::EveryTopLevel => true,
+                FlushPolicy::EveryNBytes(bytes) => self.buffers[0].len() + data.len() >= bytes,
+                FlushPolicy::Never | FlushPolicy::Manual => false,
+            };
+            return self.flush_with(data, flush_dest);
+        }
+
+        buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn write_float_tag(&mut self, id: u64, data: &f64) -> Result<(), TagWriterError> {
+        let size_width = self.size_width;
+        let len = self.float_width.len(*data);
+        let buffer = self.buffer();
+        write_header(buffer, id, len as u64, size_width)?;
+        if len == 4 {
+            buffer.extend_from_slice(&(*data as f32).to_be_bytes());
+        } else {
+            buffer.extend_from_slice(&data.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    fn write_date_tag(&mut self, id: u64, data: &i64) -> Result<(), TagWriterError> {
+        let size_width = self.size_width;
+        let buffer = self.buffer();
+        write_header(buffer, id, 8, size_width)?;
+        buffer.extend_from_slice(&data.to_be_bytes());
+        Ok(())
+    }
+
+    ///
+    /// Write a tag to this instance's destination.
+    ///
+    /// This method writes a tag from any specification.  There are no restrictions on the type of specification being written - it simply needs to implement the [`EbmlSpecification`] and [`EbmlTag`] traits.
+    ///
+    /// ## Errors
+    /// 
+    /// This method can error if there is a problem writing the input tag.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    /// ## Panics
+    ///
+    /// This method can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag variant is a specific data type but it is not).  This won't happen if the specification being used was created using the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use ebml_iterable::TagWriter;
+    /// use ebml_iterable::specs::Master;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = File::create("my_ebml_file.ebml")?;
+    /// let mut my_writer = TagWriter::new(&mut file);
+    /// my_writer.write(&EmptySpec::with_children(
+    ///   0x1a45dfa3, 
+    ///   vec![EmptySpec::with_data(0x18538067, &[0x01])])
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = self.check_tag(tag)?;
+
+        match tag_type {
+            Some(TagDataType::UnsignedInt) => {
+                let val = tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id));
+                self.write_unsigned_int_tag(tag_id, val)?
+            },
+            Some(TagDataType::Integer) => {
+                let val = tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id));
+                self.write_signed_int_tag(tag_id, val)?
+            },
+            Some(TagDataType::Utf8) => {
+                let val = tag.as_utf8().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id));
+                self.write_utf8_tag(tag_id, val)?
+            },
+            Some(TagDataType::Binary) => {
+                let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", tag_id));
+                self.write_binary_tag(tag_id, val)?
+            },
+            Some(TagDataType::Float) => {
+                let val = tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id));
+                self.write_float_tag(tag_id, val)?
+            },
+            Some(TagDataType::Date) => {
+                let val = tag.as_date().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was date, but could not get tag!", tag_id));
+                self.write_date_tag(tag_id, val)?
+            },
+            Some(TagDataType::Master) => {
+                let position = tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
+
+                match position {
+                    Master::Start if self.streaming && !self.crc32_tags.contains(&tag_id) => self.write_unknown_size_start(tag_id)?,
+                    Master::Start => self.start_tag(tag_id)?,
+                    Master::End => self.end_master(tag_id)?,
+                    Master::Full(children) => self.write_full_master(tag, children)?,
+                }
+            },
+            None => { // Should be a "raw tag"
+                if !is_vint(tag_id) {
+                    return Err(TagWriterError::TagIdError(tag_id));
+                } else {
+                    let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was raw tag, but could not get binary data!", tag_id));
+                    self.write_binary_tag(tag_id, val)?
+                }
+            }
+        }
+
+        if self.tree_depth == 0 && !self.open_tags.iter().any(|t| matches!(t.1, OpenTagSize::Buffered(..))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    ///
+    /// Checks that `tag` may be written at the current position and tracks the document versions it requires, returning its data type.
+    ///
+    fn check_tag<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<Option<TagDataType>, TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        self.check_spec_data_type(tag_id, tag_type)?;
+        if let Some(val) = tag.as_utf8() {
+            check_ascii_string(TSpec::is_ascii_string, tag_id, val)?;
+        }
+
+        let should_validate = tag_type.is_some() && (!matches!(tag_type, Some(TagDataType::Master)) || !matches!(tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)), Master::End));
+        if should_validate && !validate_tag_path::<TSpec>(tag.get_id(), self.open_tags.iter().map(|t| (t.0, if matches!(t.1, OpenTagSize::Unknown { .. }) { Unknown } else { Known(0) }))) {
+            return Err(TagWriterError::UnexpectedTag { tag_id: tag.get_id(), current_path: self.open_tags.ids() });
+        }
+
+        self.required_version = self.required_version.max(TSpec::get_min_version(tag_id));
+        self.required_read_version = self.required_read_version.max(TSpec::get_min_read_version(tag_id));
+        Ok(tag_type)
+    }
+
+    ///
+    /// Writes a [`Master::Full`] tag and all of its descendants.
+    ///
+    /// The sizes of every master in the tree are calculated before anything is written, so each header can be written up front and the children follow it directly rather than being collected in a working buffer and spliced into place.  The root buffer is only flushed once the whole tree has been written (or when a large binary payload is written through).  Trees containing [`Master::Start`] or [`Master::End`] tags fall back to buffering.
+    ///
+    fn write_full_master<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, children: &[TSpec]) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        let is_tree_root = self.tree_depth == 0;
+        if is_tree_root {
+            if let Some(tag_id) = find_too_deep(tag, self.max_depth.saturating_sub(self.open_tags.len())) {
+                return Err(TagWriterError::MaxDepthExceeded { tag_id, max_depth: self.max_depth });
+            }
+
+            // Checksums can only be calculated once the children have been written, so trees with checksummed tags are buffered
+            let mut sizes = Vec::new();
+            if (!self.crc32_tags.is_empty() && contains_master(tag, &self.crc32_tags)) || measure_tag(tag, &mut sizes, self.size_width, self.float_width, self.int_width).is_none() {
+                self.start_tag(tag_id)?;
+                for child in children {
+                    self.write(child)?;
+                }
+                return self.end_tag(tag_id);
+            }
+            self.tree_sizes.clear();
+            self.tree_sizes.extend(sizes.into_iter().rev());
+        }
+
+        let size = self.tree_sizes.pop().expect("sizes should have been measured for every master in the tree");
+        self.tree_depth += 1;
+        let result = self.start_declared_tag(tag_id, size)
+            .and_then(|_| children.iter().try_for_each(|child| self.write(child)))
+            .and_then(|_| self.end_tag(tag_id));
+        self.tree_depth -= 1;
+        if is_tree_root {
+            self.tree_sizes.clear();
+        }
+        result
+    }
+
+    fn start_declared_tag(&mut self, tag_id: u64, size: u64) -> Result<(), TagWriterError> {
+        self.check_depth(tag_id)?;
+        let declared_size: usize = size.try_into().map_err(|_| TagWriterError::TagSizeError(format!("Size {size} does not fit in memory on this platform")))?;
+        let size_width = self.size_width;
+        write_header(self.buffer(), tag_id, size, size_width)?;
+
+        let depth = self.buffers.len() - 1;
+        let start = self.buffer_origin(depth) + self.buffers[depth].len();
+        self.open_tags.push((tag_id, OpenTagSize::Declared { depth, start, size: declared_size }));
+        Ok(())
+    }
+
+    ///
+    /// Writes a tag like [`Self::write()`], using `options` to control how it is encoded.
+    ///
+    /// The options apply to `tag` and, for a [`Master::Full`] tag, to all of its descendants.  This can be used to give an element a fixed width so it can be patched in place later, e.g. an 8-byte cluster `Timestamp`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::write()`], or a [`TagWriterError::TagSizeError`] if an integer does not fit in the requested `int_width`, or if `options.int_width` or a [`SizeWidth::Fixed`] `options.size_width` is outside of 1 to 8.  Nothing is written when the options are invalid.
+    ///
+    pub fn write_with_options<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, options: WriteOptions) -> Result<(), TagWriterError> {
+        if let Some(width @ (0 | 9..)) = options.int_width {
+            return Err(TagWriterError::TagSizeError(format!("Integer widths must be between 1 and 8 bytes, got {width}")));
+        }
+        if let Some(SizeWidth::Fixed(width @ (0 | 9..))) = options.size_width {
+            return Err(TagWriterError::TagSizeError(format!("Vint widths must be between 1 and 8 bytes, got {width}")));
+        }
+        let previous = (self.int_width, self.float_width, self.size_width);
+        self.int_width = options.int_width;
+        self.float_width = options.float_width.unwrap_or(self.float_width);
+        self.size_width = options.size_width.unwrap_or(self.size_width);
+        let result = self.write(tag);
+        (self.int_width, self.float_width, self.size_width) = previous;
+        result
+    }
+
+    ///
+    /// Write a tag with an unknown size to this instance's destination.
+    /// 
+    /// This method allows you to start a tag that doesn't have a known size.  Useful for streaming, or when the data is expected to be too large to fit into memory.  This method can *only* be used on Master type tags.
+    /// 
+    /// ## Errors
+    /// 
+    /// This method will return an error if the input tag is not a Master type tag, as those are the only types allowed to be of unknown size.
+    /// 
+    pub fn write_unknown_size<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        match tag_type {
+            Some(TagDataType::Master) => {},
+            _ => {
+                return Err(TagWriterError::TagSizeError(format!("Cannot write an unknown size for tag of type {tag_type:?}")))
+            }
+        };
+        self.write_unknown_size_start(tag_id)
+    }
+
+    ///
+    /// Starts a tag with an unknown size from its id alone.
+    ///
+    /// This is the same as [`Self::write_unknown_size()`] but doesn't need a specification, which is useful when generating live streams (e.g. a Matroska `Segment` or `Cluster`) from raw ids.  The size is written as an all-ones vint and, as long as no other open tag needs buffering, the children of the tag are written straight through to the destination rather than held in memory.  The tag is ended by writing its [`Master::End`] variant or by [`Self::flush()`].
+    ///
+    /// Since no specification is consulted, the caller is responsible for only using this with "Master" tag ids.
+    ///
+    /// ## Errors
+    ///
+    /// This method will return an error if the maximum depth would be exceeded.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.write_unknown_size_start(0x1f43b675).unwrap();
+    /// writer.write_raw(0xe7, &[0x00]).unwrap();
+    ///
+    /// // The child has already been written out
+    /// assert_eq!(&[0x1f, 0x43, 0xb6, 0x75, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xe7, 0x81, 0x00], writer.get_ref().as_slice());
+    /// writer.into_inner().unwrap();
+    /// ```
+    ///
+    pub fn write_unknown_size_start(&mut self, tag_id: u64) -> Result<(), TagWriterError> {
+        self.check_spec_data_type(tag_id, Some(TagDataType::Master))?;
+        if self.deterministic || self.crc32_tags.contains(&tag_id) {
+            return self.start_tag(tag_id);
+        }
+
+        self.check_depth(tag_id)?;
+        let size_width = self.size_width;
+        let buffer = self.buffer();
+        buffer.extend(tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+        let width = match size_width {
+            SizeWidth::Minimal => {
+                buffer.extend_from_slice(&(u64::MAX >> 7).to_be_bytes());
+                8
+            },
+            SizeWidth::Fixed(width) => {
+                buffer.push(0xff >> (width - 1));
+                buffer.resize(buffer.len() + width as usize - 1, 0xff);
+                width as usize
+            },
+        };
+        let depth = self.buffers.len() - 1;
+        let start = self.buffer_origin(depth) + self.buffers[depth].len();
+        self.open_tags.push((tag_id, OpenTagSize::Unknown { depth, start, width }));
+        Ok(())
+    }
+
+    ///
+    /// Write a tag with a size that is already known to this instance's destination.
+    ///
+    /// This method starts a Master type tag using a body size supplied by the caller (e.g. when copying an element from another file).  Unlike [`Self::write()`], the children of this tag are not buffered in memory - as long as no other open tag needs buffering, they are written straight through to the destination.  The tag must still be closed by writing its [`Master::End`] variant.
+    ///
+    /// ## Errors
+    ///
+    /// This method will return an error if the input tag is not a Master type tag or if `size` cannot be represented as a vint.  Closing the tag will return an error if the number of bytes written as children does not exactly match `size`.
+    ///
+    pub fn start_tag_with_size<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, size: u64) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        match tag_type {
+            Some(TagDataType::Master) => {},
+            _ => {
+                return Err(TagWriterError::TagSizeError(format!("Cannot start a sized tag of type {tag_type:?}")))
+            }
+        };
+        self.check_spec_data_type(tag_id, tag_type)?;
+        self.start_declared_tag(tag_id, size)?;
+
+        if !self.open_tags.iter().any(|t| matches!(t.1, OpenTagSize::Buffered(..))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    ///
+    /// Write raw tag data to this instance's destination.
+    ///
+    /// This method allows writing any tag id with any arbitrary data without using a specification.  Specifications should generally provide an `Unknown` variant to handle arbitrary unknown data which can be written through the regular [`write()`](#method.write) method, so use of this method is typically discouraged.
+    ///
+    /// ## Errors
+    /// 
+    /// This method can error if there is a problem writing the input tag.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = File::create("my_ebml_file.ebml")?;
+    /// let mut my_writer = TagWriter::new(&mut file);
+    /// my_writer.write_raw(0x1a45dfa3, &[0x18, 0x53, 0x80, 0x67, 0x81, 0x01])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn write_raw(&mut self, tag_id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        self.check_spec_data_type(tag_id, None)?;
+        self.write_binary_tag(tag_id, data)?;
+        
+        if !self.open_tags.iter().any(|t| matches!(t.1, OpenTagSize::Buffered(..))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }        
+    }
+
+    ///
+    /// Writes a `Void` element that takes up exactly `total_size` bytes, id and size included.
+    ///
+    /// Muxers use this to reserve space that can be overwritten later, e.g. for a `SeekHead` or `Cues` that is only known once the rest of the file has been written.  The width of the element's size is picked so that the element has the requested length (a 129 byte element needs a 2 byte size, since a 1 byte size can't hold the remaining 127 bytes of payload), regardless of [`Self::set_size_width()`].  The payload is zeroed.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagWriterError::TagSizeError`] if `total_size` is less than 2, the smallest possible element, or an error if `Void` elements can't be written at the current position.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.write_void(4).unwrap();
+    /// writer.write_void(129).unwrap();
+    /// let data = writer.into_inner().unwrap();
+    /// assert_eq!(133, data.len());
+    /// assert_eq!(&[0xec, 0x82, 0x00, 0x00, 0xec, 0x40, 0x7e], &data[..7]);
+    /// ```
+    ///
+    pub fn write_void(&mut self, total_size: usize) -> Result<(), TagWriterError> {
+        self.check_spec_data_type(VOID, Some(TagDataType::Binary))?;
+        let width = void_size_len(total_size).ok_or_else(|| TagWriterError::TagSizeError(format!("A Void element can't be {total_size} bytes long")))?;
+        let payload = total_size - 1 - width;
+
+        let buffer = self.buffer();
+        write_header(buffer, VOID, payload as u64, SizeWidth::Fixed(width as u8))?;
+        buffer.resize(buffer.len() + payload, 0);
+
+        if !self.open_tags.iter().any(|t| matches!(t.1, OpenTagSize::Buffered(..))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    ///
+    /// Writes a tag using its original encoding, such as one returned by [`TagIterator::last_emitted_tag_encoding()`](crate::TagIterator::last_emitted_tag_encoding).
+    ///
+    /// `encoded` must be the complete element (id, size and data) for `tag`.  It is validated like [`Self::write()`] would validate `tag`, but the bytes are copied to the output unchanged rather than re-encoding `tag`, so remuxing tools can copy elements without changing their vint widths, padding or checksums.  "Master" tags are written as with [`Self::write()`], since their size depends on the children written after them.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::write()`], or a [`TagWriterError::TagSizeError`] if `encoded` is not a single element with the id of `tag`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// // The size uses two bytes, even though one would do
+    /// writer.write_encoded(&EmptySpec::with_data(0x81, &[0x01]), &[0x81, 0x40, 0x01, 0x01]).unwrap();
+    /// assert_eq!(vec![0x81, 0x40, 0x01, 0x01], writer.into_inner().unwrap());
+    /// ```
+    ///
+    pub fn write_encoded<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, encoded: &[u8]) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        if matches!(TSpec::get_tag_data_type(tag_id), Some(TagDataType::Master)) {
+            return self.write(tag);
+        }
+
+        let id_len = id_len(tag_id);
+        let is_element = encoded.len() >= id_len && encoded[..id_len] == tag_id.to_be_bytes()[(8 - id_len)..]
+            && matches!(read_vint(&encoded[id_len..]), Ok(Some((size, size_len))) if size == (encoded.len() - id_len - size_len) as u64);
+        if !is_element {
+            return Err(TagWriterError::TagSizeError(format!("Encoded data is not a single element with id 0x{tag_id:x}")));
+        }
+
+        self.check_tag(tag)?;
+        self.buffer().extend_from_slice(encoded);
+
+        if self.tree_depth == 0 && !self.open_tags.iter().any(|t| matches!(t.1, OpenTagSize::Buffered(..))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    ///
+    /// Writes a tag at its location in the specification, starting and ending "Master" tags as needed.
+    ///
+    /// The parents of `tag` are taken from [`EbmlSpecification::get_path_by_id()`].  Open tags that are already on that path are continued, any other open tags are ended, and missing parents are started, so a document can be generated from a flat list of values (a `Title` followed by a `MuxingApp` both end up in one `\Segment\Info`) without writing every [`Master::Start`] and [`Master::End`] by hand.  Tags whose path allows them anywhere below a parent (such as `CRC-32` or `Void` elements) are written into the currently open tags if those are inside that parent.  [`Master::End`] variants are written as with [`Self::write()`].
+    ///
+    /// Parents stay open after the tag is written so later tags can share them.  They are ended by writing a tag at a different path, or by [`Self::flush()`] or [`Self::into_inner()`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns the same errors as [`Self::write()`].  Parents started before an error occurred are left open.
+    ///
+    /// ## Panics
+    ///
+    /// This method can panic if `<TSpec>` is an internally inconsistent specification (i.e. it lists a tag in a path that it can't produce as a "Master" tag).
+    ///
+    pub fn write_at_path<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        if matches!(tag.as_master(), Some(Master::End)) {
+            return self.write(tag);
+        }
+
+        let path = TSpec::get_path_by_id(tag.get_id());
+        let parents: Vec<u64> = path.iter().map_while(|part| match part {
+            PathPart::Id(id) => Some(*id),
+            PathPart::Global(_) => None,
+        }).collect();
+        let is_global = parents.len() < path.len();
+
+        let shared = self.open_tags.iter().zip(parents.iter()).take_while(|(open, parent)| open.0 == **parent).count();
+        if !(is_global && shared == parents.len()) {
+            while self.open_tags.len() > shared {
+                let id = self.open_tags[self.open_tags.len() - 1].0;
+                self.end_tag(id)?;
+            }
+        }
+        for id in parents.iter().skip(shared) {
+            let parent = TSpec::get_master_tag(*id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was in path, but could not get master tag!", id));
+            self.write(&parent)?;
+        }
+        self.write(tag)
+    }
+
+    ///
+    /// Writes a "Master" tag whose children are written by `build`.
+    ///
+    /// `build` is given a [`MasterWriter`] that can only write complete elements and nested masters, so the tag is always ended once `build` returns and starts and ends can't get out of balance the way separate [`Master::Start`] and [`Master::End`] writes can.  The tag is started the same way [`Self::write()`] starts a [`Master::Start`] variant, so it has an unknown size in streaming mode.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns any error returned by `build`, or an error if the tag can't be written at the current position.  The tag is still ended if `build` fails, keeping whatever children were written before the error.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.master(0x1549a966, |w| {
+    ///     w.uint(0x2ad7b1, 1_000_000)?;
+    ///     w.utf8(0x4d80, "muxer")
+    /// }).unwrap();
+    /// assert_eq!(&[0x15, 0x49, 0xa9, 0x66, 0x8f, 0x2a, 0xd7, 0xb1, 0x83, 0x0f, 0x42, 0x40, 0x4d, 0x80, 0x85, b'm', b'u', b'x', b'e', b'r'], writer.get_ref().as_slice());
+    /// ```
+    ///
+    pub fn master<F>(&mut self, tag_id: u64, build: F) -> Result<(), TagWriterError>
+        where F: FnOnce(&mut MasterWriter<'_, W>) -> Result<(), TagWriterError>
+    {
+        if self.streaming && !self.crc32_tags.contains(&tag_id) {
+            self.write_unknown_size_start(tag_id)?;
+        } else {
+            self.check_spec_data_type(tag_id, Some(TagDataType::Master))?;
+            self.start_tag(tag_id)?;
+        }
+
+        let result = build(&mut MasterWriter { writer: self, tag_id });
+        self.end_tag(tag_id)?;
+        result?;
+        self.flush_unbuffered()
+    }
+
+    ///
+    /// Writes an unsigned integer element without building a tag from a specification.
+    ///
+    /// This and the other typed methods ([`Self::write_int()`], [`Self::write_utf8()`], [`Self::write_binary()`] and [`Self::write_float()`]) write the value straight from the arguments, so strings and binary data can be borrowed rather than copied into a tag first.  The value is checked against the specification passed to [`Self::with_spec()`], if any, but the document path of the element isn't validated.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem writing the element.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.write_uint(0x4286, 1).unwrap();
+    /// writer.write_utf8(0x4282, "webm").unwrap();
+    /// assert_eq!(&[0x42, 0x86, 0x81, 0x01, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'], writer.get_ref().as_slice());
+    /// ```
+    ///
+    pub fn write_uint(&mut self, tag_id: u64, value: u64) -> Result<(), TagWriterError> {
+        self.write_value(tag_id, TagDataType::UnsignedInt, |w| w.write_unsigned_int_tag(tag_id, &value))
+    }
+
+    ///
+    /// Writes a signed integer element.  See [`Self::write_uint()`].
+    ///
+    pub fn write_int(&mut self, tag_id: u64, value: i64) -> Result<(), TagWriterError> {
+        self.write_value(tag_id, TagDataType::Integer, |w| w.write_signed_int_tag(tag_id, &value))
+    }
+
+    ///
+    /// Writes a UTF-8 string element.  See [`Self::write_uint()`].
+    ///
+    pub fn write_utf8(&mut self, tag_id: u64, value: &str) -> Result<(), TagWriterError> {
+        if let Some(is_ascii_string) = self.spec_ascii_string {
+            check_ascii_string(is_ascii_string, tag_id, value)?;
+        }
+        self.write_value(tag_id, TagDataType::Utf8, |w| w.write_utf8_tag(tag_id, value))
+    }
+
+    ///
+    /// Writes a binary element.  See [`Self::write_uint()`].
+    ///
+    /// This writes the same bytes as [`Self::write_raw()`].
+    ///
+    pub fn write_binary(&mut self, tag_id: u64, value: &[u8]) -> Result<(), TagWriterError> {
+        self.write_value(tag_id, TagDataType::Binary, |w| w.write_binary_tag(tag_id, value))
+    }
+
+    ///
+    /// Writes a float element, using the width set by [`Self::set_float_width()`].  See [`Self::write_uint()`].
+    ///
+    pub fn write_float(&mut self, tag_id: u64, value: f64) -> Result<(), TagWriterError> {
+        self.write_value(tag_id, TagDataType::Float, |w| w.write_float_tag(tag_id, &value))
+    }
+
+    ///
+    /// Writes an EBML header holding the values in `header`.
+    ///
+    /// Every element a reader checks is written, even ones holding their default value: `EBMLVersion`, `EBMLReadVersion`, `EBMLMaxIDLength`, `EBMLMaxSizeLength`, `DocType`, `DocTypeVersion` and `DocTypeReadVersion`, in that order.  [`EbmlHeader::header_len`] is ignored.  The header always has a known size, even in streaming mode, because readers need it to find the rest of the document.  Like [`Self::write_uint()`], this doesn't need a specification that defines the header elements.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`TagWriterError::InvalidAsciiString`] error if `header.doc_type` isn't printable ASCII, and otherwise the same errors as [`Self::master()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    /// use ebml_iterable::header::EbmlHeader;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.write_ebml_header(&EbmlHeader { doc_type: "webm".to_string(), doc_type_version: 4, doc_type_read_version: 2, ..Default::default() }).unwrap();
+    /// assert_eq!(&[
+    ///     0x1a, 0x45, 0xdf, 0xa3, 0x9f,
+    ///     0x42, 0x86, 0x81, 0x01,
+    ///     0x42, 0xf7, 0x81, 0x01,
+    ///     0x42, 0xf2, 0x81, 0x04,
+    ///     0x42, 0xf3, 0x81, 0x08,
+    ///     0x42, 0x82, 0x84, b'w', b'e', b'b', b'm',
+    ///     0x42, 0x87, 0x81, 0x04,
+    ///     0x42, 0x85, 0x81, 0x02,
+    /// ], writer.get_ref().as_slice());
+    /// ```
+    ///
+    pub fn write_ebml_header(&mut self, header: &EbmlHeader) -> Result<(), TagWriterError> {
+        check_ascii_string(|id| id == ids::DOC_TYPE, ids::DOC_TYPE, &header.doc_type)?;
+        self.check_spec_data_type(ids::EBML, Some(TagDataType::Master))?;
+        self.start_tag(ids::EBML)?;
+
+        let result = self.write_ebml_header_children(header);
+        self.end_tag(ids::EBML)?;
+        result?;
+        self.flush_unbuffered()
+    }
+
+    fn write_ebml_header_children(&mut self, header: &EbmlHeader) -> Result<(), TagWriterError> {
+        self.write_uint(ids::EBML_VERSION, header.version)?;
+        self.write_uint(ids::EBML_READ_VERSION, header.read_version)?;
+        self.write_uint(ids::EBML_MAX_ID_LENGTH, header.max_id_length)?;
+        self.write_uint(ids::EBML_MAX_SIZE_LENGTH, header.max_size_length)?;
+        self.write_utf8(ids::DOC_TYPE, &header.doc_type)?;
+        self.write_uint(DOC_TYPE_VERSION, header.doc_type_version)?;
+        self.write_uint(DOC_TYPE_READ_VERSION, header.doc_type_read_version)
+    }
+
+    ///
+    /// Writes a single element of `data_type` data using `write`, checking it against the specification passed to [`Self::with_spec()`] first.
+    ///
+    fn write_value<F>(&mut self, tag_id: u64, data_type: TagDataType, write: F) -> Result<(), TagWriterError>
+        where F: FnOnce(&mut Self) -> Result<(), TagWriterError>
+    {
+        self.check_spec_data_type(tag_id, Some(data_type))?;
+        write(self)?;
+        self.flush_unbuffered()
+    }
+
+    fn flush_unbuffered(&mut self) -> Result<(), TagWriterError> {
+        if self.tree_depth == 0 && !self.open_tags.iter().any(|t| matches!(t.1, OpenTagSize::Buffered(..))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    ///
+    /// Attempts to flush all unwritten tags to the underlying destination.
+    /// 
+    /// This method can be used to finalize any open [`Master`] type tags that have not been ended.  The writer makes an attempt to close every open tag and write all bytes to the instance's destination.
+    /// 
+    /// ## Errors
+    /// 
+    /// This method can error if there is a problem writing to the destination.
+    /// 
+    pub fn flush(&mut self) -> Result<(), TagWriterError> {
+        while let Some(id) = self.open_tags.last().map(|t| t.0) {
+            self.end_tag(id)?;
+        }
+        self.flush_with(&[], true)
+    }
+
+    ///
+    /// Returns the minimum `DocTypeVersion` required by the tags written so far.
+    ///
+    /// This is the highest [`EbmlSpecification::get_min_version()`] of every tag passed to [`Self::write()`], or `None` if the specification does not track element versions.
+    ///
+    pub fn required_doc_type_version(&self) -> Option<u64> {
+        self.required_version
+    }
+
+    ///
+    /// Returns the minimum `DocTypeReadVersion` required by the tags written so far.
+    ///
+    /// This is the highest [`EbmlSpecification::get_min_read_version()`] of every tag passed to [`Self::write()`], or `None` if no written tag declared one.
+    ///
+    pub fn required_doc_type_read_version(&self) -> Option<u64> {
+        self.required_read_version
+    }
+}
+
+///
+/// Writes the children of a "Master" tag started by [`TagWriter::master()`].
+///
+/// Only complete elements can be written, so the master is guaranteed to be ended exactly once when the closure writing its children returns.  No specification is needed for the typed methods: each writes its value with the given id, checked against the specification passed to [`TagWriter::with_spec()`] if there is one.
+///
+pub struct MasterWriter<'a, W: Write> {
+    writer: &'a mut TagWriter<W>,
+    tag_id: u64,
+}
+
+impl<W: Write> MasterWriter<'_, W> {
+    ///
+    /// Writes a nested "Master" tag whose children are written by `build`.  See [`TagWriter::master()`].
+    ///
+    pub fn master<F>(&mut self, tag_id: u64, build: F) -> Result<(), TagWriterError>
+        where F: FnOnce(&mut MasterWriter<'_, W>) -> Result<(), TagWriterError>
+    {
+        self.writer.master(tag_id, build)
+    }
+
+    ///
+    /// Writes an unsigned integer element.
+    ///
+    pub fn uint(&mut self, tag_id: u64, value: u64) -> Result<(), TagWriterError> {
+        self.writer.write_uint(tag_id, value)
+    }
+
+    ///
+    /// Writes a signed integer element.
+    ///
+    pub fn int(&mut self, tag_id: u64, value: i64) -> Result<(), TagWriterError> {
+        self.writer.write_int(tag_id, value)
+    }
+
+    ///
+    /// Writes a UTF-8 string element.
+    ///
+    pub fn utf8(&mut self, tag_id: u64, value: &str) -> Result<(), TagWriterError> {
+        self.writer.write_utf8(tag_id, value)
+    }
+
+    ///
+    /// Writes a binary element.
+    ///
+    pub fn binary(&mut self, tag_id: u64, value: &[u8]) -> Result<(), TagWriterError> {
+        self.writer.write_binary(tag_id, value)
+    }
+
+    ///
+    /// Writes a float element, using the width set by [`TagWriter::set_float_width()`].
+    ///
+    pub fn float(&mut self, tag_id: u64, value: f64) -> Result<(), TagWriterError> {
+        self.writer.write_float(tag_id, value)
+    }
+
+    ///
+    /// Writes a date element, given in nanoseconds since the EBML epoch (2001-01-01T00:00:00 UTC).
+    ///
+    pub fn date(&mut self, tag_id: u64, value: i64) -> Result<(), TagWriterError> {
+        self.writer.write_value(tag_id, TagDataType::Date, |w| w.write_date_tag(tag_id, &value))
+    }
+
+    ///
+    /// Writes a tag from a specification, as with [`TagWriter::write()`].
+    ///
+    /// ## Errors
+    ///
+    /// Along with the errors from [`TagWriter::write()`], this returns a [`TagWriterError::UnexpectedClosingTag`] for [`Master::End`] variants and a [`TagWriterError::UnexpectedTag`] for [`Master::Start`] variants, since those would leave the enclosing master unbalanced.  Use [`Self::master()`] or [`Master::Full`] variants for nested masters.
+    ///
+    pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        match tag.as_master() {
+            Some(Master::End) => Err(TagWriterError::UnexpectedClosingTag { tag_id: tag.get_id(), expected_id: Some(self.tag_id) }),
+            Some(Master::Start) => Err(TagWriterError::UnexpectedTag { tag_id: tag.get_id(), current_path: self.writer.open_tags.ids() }),
+            _ => self.writer.write(tag),
+        }
+    }
+}
+
+impl<'a> TagWriter<Box<dyn Write + 'a>>
+{
+    ///
+    /// Returns a new [`TagWriter`] writing to a boxed trait object, so writers for different kinds of destinations share one type.
+    ///
+    /// This is equivalent to `TagWriter::new(Box::new(dest) as Box<dyn Write>)`.
+    ///
+    pub fn boxed<D: Write + 'a>(dest: D) -> Self {
+        TagWriter::new(Box::new(dest))
+    }
+}
+
+impl<W: Write + Seek> TagWriter<W>
+{
+    ///
+    /// Returns a new [`TagWriter`] that writes "Master" tags as they are started and seeks back to fill in their size when they end.
+    ///
+    /// This is [streaming mode](Self::set_streaming()) with the sizes patched automatically: each [`Master::Start`] tag is written right away with a placeholder size, its children go straight to the destination, and its [`Master::End`] overwrites the placeholder with the exact size.  Large documents can therefore be written in constant memory while still getting a size for each tag.  Tags started with [`Self::write_unknown_size()`] are patched the same way.  Placeholders are as wide as the unknown size of the configured [`SizeWidth`] (8 bytes by default), and a tag that grows too large for a [`SizeWidth::Fixed`] placeholder keeps its unknown size.
+    ///
+    /// This assumes that everything written to the destination since the writer was created (or last [reset](Self::reset())) came from this writer.  Patching a tag that has already been written out flushes the destination, regardless of the [`FlushPolicy`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new_seekable(Cursor::new(Vec::new()));
+    /// writer.write_unknown_size_start(0x1f43b675).unwrap();
+    /// writer.write_raw(0xe7, &[0x00]).unwrap();
+    /// writer.flush().unwrap();
+    ///
+    /// let data = writer.into_inner().unwrap().into_inner();
+    /// assert_eq!(&[0x1f, 0x43, 0xb6, 0x75, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe7, 0x81, 0x00], data.as_slice());
+    /// ```
+    ///
+    pub fn new_seekable(dest: W) -> Self {
+        let mut writer = TagWriter::new_streaming(dest);
+        writer.seekable_dest = Some(patch_dest::<W>);
+        writer
+    }
+
+    ///
+    /// Raises the `DocTypeVersion` and `DocTypeReadVersion` values in the previously written EBML header so they cover every tag written so far.
+    ///
+    /// Values are only ever increased, never lowered.  They are overwritten in place using the byte width they were originally written with, so a header written with a placeholder such as `DocTypeVersion(1)` can be bumped to any version up to 255.  This can be called at any point (typically right before finishing a document), and does not close any open tags.
+    ///
+    /// This assumes that everything written to the destination since the writer was created (or last [reset](Self::reset())) came from this writer.
+    ///
+    /// ## Errors
+    ///
+    /// This method will return an error if a version bump is required but the header tag was never written, or if the new value does not fit in the originally written width.  It can also error if there is a problem writing to or seeking the destination.
+    ///
+    pub fn patch_doc_type_versions(&mut self) -> Result<(), TagWriterError> {
+        for (id, required) in [(DOC_TYPE_VERSION, self.required_version), (DOC_TYPE_READ_VERSION, self.required_read_version)] {
+            let required = match required {
+                Some(required) => required,
+                None => continue,
+            };
+
+            if !self.version_sites.iter().any(|site| site.id == id) {
+                return Err(TagWriterError::TagPatchError { tag_id: id, message: format!("A version of at least {required} is required, but the tag was never written.") });
+            }
+
+            for index in 0..self.version_sites.len() {
+                let site = &self.version_sites[index];
+                if site.id != id || site.value >= required {
+                    continue;
+                }
+
+                if site.width < 8 && required >= (1 << (site.width * 8)) {
+                    return Err(TagWriterError::TagPatchError { tag_id: id, message: format!("Version {required} does not fit in the {} byte(s) originally written.", site.width) });
+                }
+
+                let bytes = required.to_be_bytes();
+                let bytes = &bytes[(8 - site.width)..];
+                let (depth, offset) = (site.depth, site.offset);
+                if depth > 0 || offset >= self.bytes_flushed {
+                    let start = offset - self.buffer_origin(depth);
+                    self.buffers[depth].patch(start, bytes);
+                } else {
+                    self.patch_flushed(offset, bytes)?;
+                }
+                self.version_sites[index].value = required;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Enables or disables recording the sizes of tags written with an unknown size, so they can be filled in by [`Self::patch_sizes()`].
+    ///
+    /// This is disabled by default, since a few bytes are kept for every such tag until the sizes are patched.  Disabling it discards any sizes that haven't been patched yet.
+    ///
+    pub fn set_backpatch_sizes(&mut self, backpatch: bool) {
+        self.size_sites = if backpatch { Some(self.size_sites.take().unwrap_or_default()) } else { None };
+    }
+
+    ///
+    /// Overwrites the unknown sizes of finished tags with their actual size.
+    ///
+    /// This covers every tag written by [`Self::write_unknown_size()`], [`Self::write_unknown_size_start()`] or in [streaming mode](Self::set_streaming()) that has ended since [`Self::set_backpatch_sizes()`] was enabled, and not been patched yet.  Sizes are written in the width originally reserved for them, so a tag that has grown too large for a [`SizeWidth::Fixed`] width keeps its unknown size.  Open tags are not affected, so this can be called at any point - e.g. after every `Cluster` and once more at the end of the document.
+    ///
+    /// This assumes that everything written to the destination since the writer was created (or last [reset](Self::reset())) came from this writer.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem writing to or seeking the destination.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use ebml_iterable::TagWriter;
+    ///
+    /// let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+    /// writer.set_backpatch_sizes(true);
+    /// writer.write_unknown_size_start(0x1f43b675).unwrap();
+    /// writer.write_raw(0xe7, &[0x00]).unwrap();
+    /// writer.flush().unwrap();
+    /// writer.patch_sizes().unwrap();
+    ///
+    /// let data = writer.into_inner().unwrap().into_inner();
+    /// assert_eq!(&[0x1f, 0x43, 0xb6, 0x75, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe7, 0x81, 0x00], data.as_slice());
+    /// ```
+    ///
+    pub fn patch_sizes(&mut self) -> Result<(), TagWriterError> {
+        let mut sites = match self.size_sites.take() {
+            Some(sites) => sites,
+            None => return Ok(()),
+        };
+
+        let result = sites.iter().try_for_each(|site| self.patch_size_site(site, patch_dest::<W>));
+        if result.is_ok() {
+            sites.clear();
+        }
+        self.size_sites = Some(sites);
+        result
+    }
+
+    fn patch_flushed(&mut self, offset: usize, bytes: &[u8]) -> Result<(), TagWriterError> {
+        patch_dest(&mut self.dest, (self.bytes_flushed - offset) as u64, bytes).map_err(|source| TagWriterError::WriteError { source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::super::tools::Vint;
+    use super::super::tools::read_vint;
+    use super::{FlushPolicy, TagBuffer, TagWriter, SMALL_TAG_COPY_LEN};
+
+    #[test]
+    fn write_ebml_tag() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write_raw(0x1a45dfa3, &[]).expect("Error writing tag");
+
+        let zero_size = 0u64.as_vint().expect("Error converting [0] to vint")[0];
+        assert_eq!(vec![0x1a, 0x45, 0xdf, 0xa3, zero_size], dest.get_ref().to_vec());
+    }
+
+    #[test]
+    fn tag_buffer_moves_children_without_copying() {
+        let mut parent = TagBuffer::new();
+        parent.active().extend_from_slice(&[1, 2, 3, 4]);
+
+        let mut child = TagBuffer::new();
+        child.active().extend_from_slice(&[5; SMALL_TAG_COPY_LEN + 1]);
+        let child_ptr = child.chunks[0].as_ptr();
+        parent.append_body(&mut child);
+        assert_eq!(child_ptr, parent.chunks[1].as_ptr());
+        assert_eq!(0, child.len());
+        parent.active().push(7);
+        assert_eq!(SMALL_TAG_COPY_LEN + 6, parent.len());
+
+        parent.patch(3, &[0xa, 0xb]);
+        let mut dest = Vec::new();
+        parent.write_to(&mut dest, &[8]).expect("Error writing buffer");
+        assert_eq!(&[1, 2, 3, 0xa, 0xb, 5], &dest[..6]);
+        assert_eq!(&[5, 7, 8], &dest[(dest.len() - 3)..]);
+        assert_eq!(0, parent.len());
+    }
+
+    #[test]
+    fn tag_buffer_copies_small_children() {
+        let mut parent = TagBuffer::new();
+        parent.active().extend_from_slice(&[1, 2]);
+
+        let mut child = TagBuffer::new();
+        child.active().extend_from_slice(&[3, 4]);
+        let child_capacity = child.chunks[0].capacity();
+        parent.append_body(&mut child);
+        assert_eq!(1, parent.chunks.len());
+        assert_eq!(vec![1, 2, 3, 4], parent.chunks[0]);
+
+        // The child's allocation is kept so the buffer can be reused for the next tag
+        assert_eq!(0, child.len());
+        assert_eq!(child_capacity, child.chunks[0].capacity());
+    }
+
+    struct TrickleWriter(Vec<u8>);
+
+    impl Write for TrickleWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(3);
+            self.0.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_large_payload_with_partial_writes() {
+        let payload: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+        let mut writer = TagWriter::new(TrickleWriter(Vec::new()));
+        writer.write_raw(0x81, &[1, 2]).expect("Error writing tag");
+        writer.write_raw(0xa3, &payload).expect("Error writing tag");
+
+        let mut expected = vec![0x81, 0x82, 1, 2, 0xa3];
+        expected.extend(5000u64.as_vint().expect("Error converting 5000 to vint"));
+        expected.extend(&payload);
+        assert_eq!(expected, writer.into_inner().expect("Error flushing writer").0);
+    }
+
+    #[test]
+    fn reset_reuses_buffers() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_flush_policy(FlushPolicy::Manual);
+        writer.write_raw(0x81, &[1; 100]).expect("Error writing tag");
+        let capacity = writer.buffers[0].chunks[0].capacity();
+
+        let first = writer.reset(Vec::new()).expect("Error resetting writer");
+        assert_eq!(102, first.len());
+        assert_eq!(capacity, writer.buffers[0].chunks[0].capacity());
+        assert_eq!(0, writer.get_ref().len(), "Flush policy should be kept");
+
+        writer.write_raw(0x82, &[2]).expect("Error writing tag");
+        assert_eq!(vec![0x82, 0x81, 2], writer.into_inner().expect("Error flushing writer"));
+    }
+
+    #[test]
+    fn write_all_ones_size_with_extra_byte() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_raw(0x81, &[0; 127]).expect("Error writing tag");
+        let data = writer.into_inner().expect("Error flushing writer");
+        assert_eq!(&[0x81, 0x40, 0x7f], &data[..3]);
+        assert_eq!(130, data.len());
+    }
+
+    #[test]
+    fn write_void_of_exact_size() {
+        assert!(TagWriter::new(Vec::new()).write_void(0).is_err());
+        assert!(TagWriter::new(Vec::new()).write_void(1).is_err());
+
+        for total_size in (2..300).chain(16_380..16_390).chain([(1 << 21) + 2, (1 << 21) + 3, (1 << 21) + 4]) {
+            let mut writer = TagWriter::new(Vec::new());
+            writer.write_void(total_size).expect("Error writing tag");
+            let data = writer.into_inner().expect("Error flushing writer");
+            assert_eq!(total_size, data.len());
+            assert_eq!(0xec, data[0]);
+            let (size, size_len) = read_vint(&data[1..]).unwrap().unwrap();
+            assert_eq!(total_size, 1 + size_len + size as usize);
+            // All ones would be read back as an unknown size
+            assert_ne!((1 << (7 * size_len)) - 1, size);
+        }
+    }
+
+    #[test]
+    fn write_minimal_width_integers() {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        writer.write_unsigned_int_tag(0x81, &0).unwrap();
+        writer.write_unsigned_int_tag(0x81, &0xff).unwrap();
+        writer.write_unsigned_int_tag(0x81, &0x01_0000).unwrap();
+        writer.write_unsigned_int_tag(0x81, &0x0100_0000_0000).unwrap();
+        writer.write_unsigned_int_tag(0x81, &u64::MAX).unwrap();
+        writer.write_signed_int_tag(0x82, &-1).unwrap();
+        writer.write_signed_int_tag(0x82, &127).unwrap();
+        writer.write_signed_int_tag(0x82, &128).unwrap();
+        writer.write_signed_int_tag(0x82, &-0x80_0000).unwrap();
+        writer.write_signed_int_tag(0x82, &-0x80_0001).unwrap();
+        writer.write_signed_int_tag(0x82, &i64::MIN).unwrap();
+
+        let data = writer.into_inner().unwrap().into_inner();
+        assert_eq!(vec![
+            0x81, 0x81, 0x00,
+            0x81, 0x81, 0xff,
+            0x81, 0x83, 0x01, 0x00, 0x00,
+            0x81, 0x86, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x81, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0x82, 0x81, 0xff,
+            0x82, 0x81, 0x7f,
+            0x82, 0x82, 0x00, 0x80,
+            0x82, 0x83, 0x80, 0x00, 0x00,
+            0x82, 0x84, 0xff, 0x7f, 0xff, 0xff,
+            0x82, 0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ], data);
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::{self, ErrorKind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{AsyncWrite, Sink};
+use futures::future::poll_fn;
+
+use crate::error::TagWriterError;
+use crate::specs::{EbmlSpecification, EbmlTag};
+use crate::TagWriter;
+
+///
+/// The default number of encoded bytes a [`TagWriterAsync`] holds before waiting for its destination.
+///
+pub const DEFAULT_ASYNC_WRITE_CAPACITY: usize = 1024 * 64;
+
+///
+/// Writes EBML data to a [`futures::AsyncWrite`] destination with bounded buffering.
+///
+/// Tags are encoded by an internal [`TagWriter`] and the resulting bytes are held until they can be written to the destination.  Once more than `capacity` bytes are waiting, [`Self::write()`] (or the [`Sink`] implementation's `poll_ready`) waits for the destination to accept them, so a slow consumer like a network socket slows the producer down instead of letting encoded data pile up in memory.
+///
+/// Note that the writer can only send the contents of a "Master" tag once its size is known.  Tags inside masters started with [`Self::write()`] are held until the master ends, regardless of `capacity`.  To stream large masters such as clusters, start them with [`Self::write_unknown_size()`], which lets their children be sent as they are written.
+///
+/// [`Self::flush()`] sends everything that is ready and flushes the destination without ending any open tags.  [`Self::close()`] ends all open tags, sends the remaining data and closes the destination.
+///
+/// ## Example
+///
+/// ```
+/// use futures::executor::block_on;
+/// use ebml_iterable::TagWriterAsync;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// block_on(async {
+///     let mut writer = TagWriterAsync::new(futures::io::Cursor::new(Vec::new()));
+///     writer.write(&EmptySpec::with_data(0x81, &[0x01])).await.unwrap();
+///     writer.close().await.unwrap();
+///     assert_eq!(vec![0x81, 0x81, 0x01], writer.into_inner().into_inner());
+/// });
+/// ```
+///
+pub struct TagWriterAsync<W: AsyncWrite + Unpin> {
+    dest: W,
+    writer: TagWriter<Vec<u8>>,
+    written: usize,
+    capacity: usize,
+}
+
+impl<W: AsyncWrite + Unpin> TagWriterAsync<W> {
+
+    ///
+    /// Returns a new [`TagWriterAsync`] that buffers up to [`DEFAULT_ASYNC_WRITE_CAPACITY`] bytes.
+    ///
+    pub fn new(dest: W) -> Self {
+        TagWriterAsync::with_capacity(dest, DEFAULT_ASYNC_WRITE_CAPACITY)
+    }
+
+    ///
+    /// Returns a new [`TagWriterAsync`] that buffers up to `capacity` bytes before waiting for `dest`.  A `capacity` of 0 waits for each tag to be sent before accepting another.
+    ///
+    pub fn with_capacity(dest: W, capacity: usize) -> Self {
+        TagWriterAsync {
+            dest,
+            writer: TagWriter::new(Vec::with_capacity(capacity)),
+            written: 0,
+            capacity,
+        }
+    }
+
+    ///
+    /// Gets a mutable reference to the internal [`TagWriter`], e.g. to configure its size width.  Its flush policy has no effect, since buffered data is sent by this writer.
+    ///
+    pub fn writer_mut(&mut self) -> &mut TagWriter<Vec<u8>> {
+        &mut self.writer
+    }
+
+    ///
+    /// Returns the number of encoded bytes waiting to be sent to the destination.
+    ///
+    pub fn buffered_len(&self) -> usize {
+        self.writer.get_ref().len() - self.written
+    }
+
+    ///
+    /// Writes a tag, first waiting for the destination if more than `capacity` bytes are buffered.  See [`TagWriter::write()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`TagWriter::write()`], or a [`TagWriterError::WriteError`] if sending buffered data to the destination fails.
+    ///
+    pub async fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        poll_fn(|cx| self.poll_send(cx, self.capacity)).await?;
+        self.writer.write(tag)
+    }
+
+    ///
+    /// Starts a "Master" tag with an unknown size, first waiting for the destination if more than `capacity` bytes are buffered.  See [`TagWriter::write_unknown_size()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`TagWriter::write_unknown_size()`], or a [`TagWriterError::WriteError`] if sending buffered data to the destination fails.
+    ///
+    pub async fn write_unknown_size<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        poll_fn(|cx| self.poll_send(cx, self.capacity)).await?;
+        self.writer.write_unknown_size(tag)
+    }
+
+    ///
+    /// Sends all buffered data and flushes the destination.  Unlike [`TagWriter::flush()`], open tags are not ended.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagWriterError::WriteError`] if writing to or flushing the destination fails.
+    ///
+    pub async fn flush(&mut self) -> Result<(), TagWriterError> {
+        poll_fn(|cx| self.poll_flush_dest(cx)).await
+    }
+
+    ///
+    /// Ends all open tags, sends all buffered data and closes the destination.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if an open tag can't be ended, or a [`TagWriterError::WriteError`] if writing to or closing the destination fails.
+    ///
+    pub async fn close(&mut self) -> Result<(), TagWriterError> {
+        self.writer.flush()?;
+        poll_fn(|cx| self.poll_close_dest(cx)).await
+    }
+
+    ///
+    /// Consumes self and returns the destination.  Any data that has not been sent (see [`Self::close()`]) is lost.
+    ///
+    pub fn into_inner(self) -> W {
+        self.dest
+    }
+
+    ///
+    /// Gets a mutable reference to the destination.
+    ///
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.dest
+    }
+
+    ///
+    /// Gets a reference to the destination.
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.dest
+    }
+
+    ///
+    /// Writes buffered data to the destination until no more than `limit` bytes remain.
+    ///
+    fn poll_send(&mut self, cx: &mut Context<'_>, limit: usize) -> Poll<Result<(), TagWriterError>> {
+        while self.buffered_len() > limit {
+            let buffer = &self.writer.get_ref()[self.written..];
+            match Pin::new(&mut self.dest).poll_write(cx, buffer) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(TagWriterError::WriteError { source: io::Error::from(ErrorKind::WriteZero) })),
+                Poll::Ready(Ok(len)) => self.written += len,
+                Poll::Ready(Err(source)) if source.kind() == ErrorKind::Interrupted => continue,
+                Poll::Ready(Err(source)) => return Poll::Ready(Err(TagWriterError::WriteError { source })),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        if self.buffered_len() == 0 {
+            // Reuse the allocation once everything has been sent
+            self.writer.get_mut().clear();
+            self.written = 0;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_flush_dest(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), TagWriterError>> {
+        match self.poll_send(cx, 0) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.dest).poll_flush(cx).map_err(|source| TagWriterError::WriteError { source }),
+            other => other,
+        }
+    }
+
+    fn poll_close_dest(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), TagWriterError>> {
+        match self.poll_send(cx, 0) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.dest).poll_close(cx).map_err(|source| TagWriterError::WriteError { source }),
+            other => other,
+        }
+    }
+}
+
+///
+/// Tags can be sent using [`futures::SinkExt`].  `poll_ready` waits until no more than `capacity` bytes are buffered, `poll_flush` behaves like [`TagWriterAsync::flush()`], and `poll_close` like [`TagWriterAsync::close()`].
+///
+impl<W: AsyncWrite + Unpin, TSpec> Sink<TSpec> for TagWriterAsync<W>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = TagWriterError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        let capacity = this.capacity;
+        this.poll_send(cx, capacity)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: TSpec) -> Result<(), Self::Error> {
+        self.get_mut().writer.write(&item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_flush_dest(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        this.writer.flush()?;
+        this.poll_close_dest(cx)
+    }
+}
+
+///
+/// Adapts a [`tokio::io::AsyncWrite`] destination so it can be written by a [`TagWriterAsync`].  Created by [`TagWriterAsync::from_tokio()`].
+///
+#[cfg(feature = "tokio")]
+pub struct TokioWriter<W>(W);
+
+#[cfg(feature = "tokio")]
+impl<W> TokioWriter<W> {
+
+    ///
+    /// Consumes self and returns the wrapped destination.
+    ///
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+
+    ///
+    /// Gets a reference to the wrapped destination.
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.0
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> AsyncWrite for TokioWriter<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> TagWriterAsync<TokioWriter<W>> {
+
+    ///
+    /// Returns a new [`TagWriterAsync`] writing to a [`tokio::io::AsyncWrite`] destination, such as a `TcpStream` or a `tokio::fs::File`.
+    ///
+    /// [`Self::close()`] shuts the destination down once all data has been written.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use ebml_iterable::TagWriterAsync;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// block_on(async {
+    ///     let mut writer = TagWriterAsync::from_tokio(Vec::new());
+    ///     writer.write(&EmptySpec::with_data(0x81, &[0x01])).await.unwrap();
+    ///     writer.close().await.unwrap();
+    ///     assert_eq!(vec![0x81, 0x81, 0x01], writer.into_inner().into_inner());
+    /// });
+    /// ```
+    ///
+    pub fn from_tokio(dest: W) -> Self {
+        TagWriterAsync::new(TokioWriter(dest))
+    }
+
+    ///
+    /// Returns a new [`TagWriterAsync`] writing to a [`tokio::io::AsyncWrite`] destination that buffers up to `capacity` bytes.  See [`Self::with_capacity()`].
+    ///
+    pub fn from_tokio_with_capacity(dest: W, capacity: usize) -> Self {
+        TagWriterAsync::with_capacity(TokioWriter(dest), capacity)
+    }
+}
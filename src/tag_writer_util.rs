@@ -0,0 +1,43 @@
+use crate::vint::size_width;
+
+///
+/// Width of the vint used to write a tag size.  For minimal widths, a value of all ones is read back as an unknown size, so those get one more byte.
+///
+pub(crate) fn size_len(size: u64, width: SizeWidth) -> usize {
+    if let SizeWidth::Fixed(width) = width {
+        return width as usize;
+    }
+
+    size_width(size)
+}
+
+pub(crate) fn id_len(id: u64) -> usize {
+    id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count()
+}
+
+pub(crate) fn unsigned_int_width(data: u64) -> usize {
+    (8 - (data.leading_zeros() / 8) as usize).max(1)
+}
+
+pub(crate) fn signed_int_width(data: i64) -> usize {
+    // One sign bit plus every bit that differs from it
+    let significant_bits = 65 - if data < 0 { (!data).leading_zeros() } else { data.leading_zeros() };
+    significant_bits.div_ceil(8) as usize
+}
+
+///
+/// Controls how many bytes a [`TagWriter`](crate::TagWriter) uses to write the size of each tag.
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum SizeWidth {
+    ///
+    /// Use the narrowest vint that can hold each size.  This is the default.
+    ///
+    #[default]
+    Minimal,
+
+    ///
+    /// Always use a vint of this many bytes (1 to 8), including for unknown sizes.  Tags too large to be described in this width can't be written.
+    ///
+    Fixed(u8),
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use crate::dyn_spec::{ActiveSpec, DynSpecification, DynTag};
+use crate::error::TagIteratorError;
+use crate::iterator::AllowableErrors;
+use crate::spec_util::validate_tag_path;
+use crate::specs::{EbmlSpecification, EbmlTag, Master};
+use crate::tag_iterator_util::EBMLSize;
+use crate::TagIterator;
+
+///
+/// The values a tag is allowed to have, as configured through [`Validator::set_range()`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueRange {
+
+    ///
+    /// Allowed values of a [`TagDataType::UnsignedInt`](crate::specs::TagDataType::UnsignedInt) tag.
+    ///
+    Unsigned(RangeInclusive<u64>),
+
+    ///
+    /// Allowed values of a [`TagDataType::Integer`](crate::specs::TagDataType::Integer) or [`TagDataType::Date`](crate::specs::TagDataType::Date) tag.
+    ///
+    Signed(RangeInclusive<i64>),
+
+    ///
+    /// Allowed values of a [`TagDataType::Float`](crate::specs::TagDataType::Float) tag.  `NaN` is never in range.
+    ///
+    Float(RangeInclusive<f64>),
+
+    ///
+    /// Allowed lengths, in bytes, of a [`TagDataType::Utf8`](crate::specs::TagDataType::Utf8) or [`TagDataType::Binary`](crate::specs::TagDataType::Binary) tag.
+    ///
+    Length(RangeInclusive<usize>),
+}
+
+impl ValueRange {
+    fn check<TSpec: EbmlTag<TSpec> + Clone>(&self, tag: &TSpec) -> Option<String> {
+        match self {
+            ValueRange::Unsigned(range) => tag.as_unsigned_int().filter(|value| !range.contains(value)).map(|value| value.to_string()),
+            ValueRange::Signed(range) => tag.as_signed_int().or_else(|| tag.as_date()).filter(|value| !range.contains(value)).map(|value| value.to_string()),
+            ValueRange::Float(range) => tag.as_float().filter(|value| !range.contains(value)).map(|value| value.to_string()),
+            ValueRange::Length(range) => tag.as_utf8().map(str::len).or_else(|| tag.as_binary().map(<[u8]>::len))
+                .filter(|len| !range.contains(len)).map(|len| format!("{len} bytes")),
+        }
+    }
+}
+
+///
+/// A way in which a document does not conform to its specification.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViolationKind {
+
+    ///
+    /// The tag id is not in the specification.
+    ///
+    UnknownId,
+
+    ///
+    /// The tag is not allowed inside its parent, or at the top level of the document if `parent` is [`None`].
+    ///
+    /// The children of a misplaced "Master" tag aren't checked for this, since they would otherwise all be reported as well.
+    ///
+    WrongParent { parent: Option<u64> },
+
+    ///
+    /// The "Master" tag ended without the mandatory child `child_id` (see [`EbmlSpecification::get_mandatory_children()`]).
+    ///
+    MissingChild { child_id: u64 },
+
+    ///
+    /// The tag occurred more than `max` times in its parent (see [`Validator::set_max_occurs()`]).
+    ///
+    TooManyOccurrences { max: usize },
+
+    ///
+    /// The tag's data is outside of the range configured through [`Validator::set_range()`].  `value` describes the data that was found.
+    ///
+    OutOfRange { value: String },
+}
+
+///
+/// A single violation found by a [`Validator`].
+///
+/// `offset` is the position of the header of the offending tag, and `tag_id` is its id.  For [`ViolationKind::MissingChild`], these refer to the "Master" tag the child is missing from.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub offset: usize,
+    pub tag_id: u64,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Violation { offset, tag_id, kind } = self;
+        match kind {
+            ViolationKind::UnknownId => write!(f, "Unknown tag id [0x{tag_id:x?}] at position {offset}"),
+            ViolationKind::WrongParent { parent: Some(parent) } => write!(f, "Tag [0x{tag_id:x?}] at position {offset} is not allowed in [0x{parent:x?}]"),
+            ViolationKind::WrongParent { parent: None } => write!(f, "Tag [0x{tag_id:x?}] at position {offset} is not allowed at the top level"),
+            ViolationKind::MissingChild { child_id } => write!(f, "Tag [0x{tag_id:x?}] at position {offset} is missing mandatory child [0x{child_id:x?}]"),
+            ViolationKind::TooManyOccurrences { max } => write!(f, "Tag [0x{tag_id:x?}] at position {offset} occurs more than {max} times in its parent"),
+            ViolationKind::OutOfRange { value } => write!(f, "Tag [0x{tag_id:x?}] at position {offset} has out of range value {value}"),
+        }
+    }
+}
+
+struct OpenTag {
+    id: u64,
+    offset: usize,
+    size: EBMLSize,
+    misplaced: bool,
+    children: BTreeMap<u64, usize>,
+}
+
+///
+/// Checks documents for conformance with a specification, reporting every violation rather than stopping at the first one.
+///
+/// [`TagIterator::set_strict_mode()`] is the quickest way to reject documents that don't conform to `<TSpec>`, but it stops reading at the first problem.  A [`Validator`] reads the whole document and lists each unknown tag id, tag outside of its expected parent, and "Master" tag missing a mandatory child, in document order - the basis of an `mkvalidator` style tool.  Rules that `<TSpec>` can't express, such as how often a tag may occur or the values it may hold, can be added through [`Self::set_max_occurs()`] and [`Self::set_range()`].
+///
+/// The same validator can be used for any number of documents.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::header::EbmlHeaderSpec;
+/// use ebml_iterable::validate::{ValueRange, Validator, ViolationKind};
+///
+/// let mut validator = Validator::<EbmlHeaderSpec>::new();
+/// validator.set_range(0x4286, ValueRange::Unsigned(1..=1));
+///
+/// // An EBML header with EBMLVersion set to 2
+/// let violations = validator.validate(Cursor::new([0x1a, 0x45, 0xdf, 0xa3, 0x84, 0x42, 0x86, 0x81, 0x02])).unwrap();
+/// assert_eq!(1, violations.len());
+/// assert_eq!(0x4286, violations[0].tag_id);
+/// assert_eq!(ViolationKind::OutOfRange { value: String::from("2") }, violations[0].kind);
+/// ```
+///
+pub struct Validator<TSpec> {
+    max_occurs: BTreeMap<u64, usize>,
+    ranges: BTreeMap<u64, ValueRange>,
+    _spec: PhantomData<fn() -> TSpec>,
+}
+
+impl<TSpec> Validator<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`Validator`] that checks documents against `<TSpec>` alone.
+    ///
+    pub fn new() -> Self {
+        Validator {
+            max_occurs: BTreeMap::new(),
+            ranges: BTreeMap::new(),
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Limits how many times the tag `tag_id` may occur within a single parent (or at the top level of the document).  Each occurrence past `max` is reported as a [`ViolationKind::TooManyOccurrences`].
+    ///
+    pub fn set_max_occurs(&mut self, tag_id: u64, max: usize) {
+        self.max_occurs.insert(tag_id, max);
+    }
+
+    ///
+    /// Limits the values the tag `tag_id` may have.  Values outside of `range` are reported as a [`ViolationKind::OutOfRange`].
+    ///
+    /// Ranges that don't apply to the tag's data type (e.g. a [`ValueRange::Float`] for an unsigned integer tag) are ignored.
+    ///
+    pub fn set_range(&mut self, tag_id: u64, range: ValueRange) {
+        self.ranges.insert(tag_id, range);
+    }
+
+    ///
+    /// Reads an entire document and returns every violation found in it, in document order.  An empty list means the document conforms.
+    ///
+    /// The document is streamed through a [`TagIterator`] that allows [`AllowableErrors::InvalidTagIds`] and [`AllowableErrors::HierarchyProblems`], so those problems are reported as violations instead of stopping the read.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the first error that prevents the rest of the document from being read, such as corrupted tag data or an unexpected end of the source.
+    ///
+    pub fn validate<R: Read>(&self, source: R) -> Result<Vec<Violation>, TagIteratorError> {
+        let mut iter: TagIterator<R, TSpec> = TagIterator::new(source, &[]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds, AllowableErrors::HierarchyProblems]);
+
+        let mut violations = Vec::new();
+        let mut open: Vec<OpenTag> = Vec::new();
+        let mut top_level: BTreeMap<u64, usize> = BTreeMap::new();
+        while let Some(tag) = iter.next() {
+            let tag = tag?;
+            let tag_id = tag.get_id();
+            let span = iter.last_emitted_tag_span();
+            let mut report = |kind| violations.push(Violation { offset: span.offset, tag_id, kind });
+
+            if let Some(Master::End) = tag.as_master() {
+                if let Some(closed) = open.pop() {
+                    for child_id in TSpec::get_mandatory_children(closed.id).iter().filter(|child_id| !closed.children.contains_key(child_id)) {
+                        violations.push(Violation { offset: closed.offset, tag_id: closed.id, kind: ViolationKind::MissingChild { child_id: *child_id } });
+                    }
+                }
+                continue;
+            }
+
+            let siblings = open.last_mut().map_or(&mut top_level, |parent| &mut parent.children);
+            let count = siblings.entry(tag_id).or_default();
+            *count += 1;
+            if let Some(max) = self.max_occurs.get(&tag_id).filter(|max| *count > **max) {
+                report(ViolationKind::TooManyOccurrences { max: *max });
+            }
+
+            if TSpec::get_tag_data_type(tag_id).is_none() {
+                report(ViolationKind::UnknownId);
+                continue;
+            }
+            // Misplaced "Master" tags pass this on to their children, so that only the outermost one is reported
+            let in_misplaced = open.last().is_some_and(|parent| parent.misplaced);
+            let misplaced = in_misplaced || !validate_tag_path::<TSpec>(tag_id, open.iter().map(|parent| (parent.id, parent.size)));
+            if misplaced && !in_misplaced {
+                report(ViolationKind::WrongParent { parent: open.last().map(|parent| parent.id) });
+            }
+            if let Some(value) = self.ranges.get(&tag_id).and_then(|range| range.check(&tag)) {
+                report(ViolationKind::OutOfRange { value });
+            }
+
+            if let Some(Master::Start) = tag.as_master() {
+                let size = span.data_len.map_or(EBMLSize::Unknown, EBMLSize::Known);
+                open.push(OpenTag { id: tag_id, offset: span.offset, size, misplaced, children: BTreeMap::new() });
+            }
+        }
+        Ok(violations)
+    }
+}
+
+impl Validator<DynTag> {
+
+    ///
+    /// Same as [`Self::validate()`], but checks the document against a specification selected at runtime, such as a `dynamic::DynamicSpec` loaded from an EBML Schema (with the `ebml-schema` feature).
+    ///
+    /// ## Errors
+    ///
+    /// Returns the first error that prevents the rest of the document from being read.
+    ///
+    pub fn validate_with_spec<R: Read>(&self, source: R, spec: &Arc<dyn DynSpecification>) -> Result<Vec<Violation>, TagIteratorError> {
+        let _active = ActiveSpec::enter(spec);
+        self.validate(source)
+    }
+}
+
+impl<TSpec> Default for Validator<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn default() -> Self {
+        Validator::new()
+    }
+}
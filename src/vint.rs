@@ -0,0 +1,205 @@
+//!
+//! Reading and writing the variable length integers (vints) that EBML uses for element ids and sizes.
+//!
+//! Vints are also used inside of some element data, such as the lace sizes of Matroska `SimpleBlock` and `Block` elements, so these functions are useful on their own.  The first byte of a vint has a leading `1` bit (the marker) after as many `0` bits as there are extra bytes, and the remaining bits hold the value.  A vint can be between 1 and [`MAX_WIDTH`] bytes wide.
+//!
+//! When a vint is used as an element size, a value with every bit set (e.g. `0xFF` or `0x7F 0xFF`) means the size is unknown.  [`read_size()`] and [`is_unknown_size()`] account for this, and [`size_width()`] picks a width that avoids it.
+//!
+//! ## Example
+//!
+//! ```
+//! use ebml_iterable::vint;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bytes = vint::write_vint_with_width(300, 3)?;
+//! assert_eq!(vec![0x20, 0x01, 0x2c], bytes);
+//! assert_eq!(Some((300, 3)), vint::read_vint(&bytes)?);
+//!
+//! assert_eq!(Some((None, 1)), vint::read_size(&vint::unknown_size(1)?)?);
+//! assert_eq!(Some((-2, 1)), vint::read_signed_vint(&vint::write_signed_vint_with_width(-2, 1)?)?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::errors::tool::ToolError;
+use crate::tools::{minimal_vint_length, vint_bytes_u64};
+
+pub use crate::tools::{is_vint, read_signed_vint, read_vint, SignedVint, Vint};
+
+///
+/// The widest vint, in bytes, that can be read or written.
+///
+pub const MAX_WIDTH: usize = 8;
+
+#[inline]
+fn check_width(width: usize) -> Result<(), ToolError> {
+    if (1..=MAX_WIDTH).contains(&width) {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidVintWidth(width))
+    }
+}
+
+///
+/// Gets the width of a vint from its first byte, or [`None`] if the byte doesn't start a valid vint (i.e. it is `0x00`).
+///
+/// ## Example
+///
+/// ```
+/// # use ebml_iterable::vint::vint_width;
+/// assert_eq!(Some(1), vint_width(0x81));
+/// assert_eq!(Some(4), vint_width(0x1a));
+/// assert_eq!(None, vint_width(0x00));
+/// ```
+///
+pub fn vint_width(first_byte: u8) -> Option<usize> {
+    if first_byte == 0 {
+        None
+    } else {
+        Some(first_byte.leading_zeros() as usize + 1)
+    }
+}
+
+///
+/// Gets the narrowest width a vint holding `value` can be written with.
+///
+/// The result is [`MAX_WIDTH`] for values too large to be written as a vint.  Use [`size_width()`] instead for element sizes, which can't be written as all ones.
+///
+pub fn min_width(value: u64) -> usize {
+    minimal_vint_length(value)
+}
+
+///
+/// Gets the narrowest width a vint holding the element size `size` can be written with without being read back as an unknown size.
+///
+/// ## Example
+///
+/// ```
+/// # use ebml_iterable::vint::{min_width, size_width};
+/// assert_eq!(1, size_width(126));
+/// assert_eq!(1, min_width(127));
+/// assert_eq!(2, size_width(127));
+/// ```
+///
+pub fn size_width(size: u64) -> usize {
+    let width = minimal_vint_length(size);
+    if is_unknown_size(size, width) {
+        width + 1
+    } else {
+        width
+    }
+}
+
+///
+/// Returns a vint of exactly `width` bytes holding `value`.
+///
+/// Note that a value with every bit set is read as an unknown size when the vint is used as an element size.
+///
+/// # Errors
+///
+/// This returns a [`ToolError::InvalidVintWidth`] error if `width` isn't between 1 and [`MAX_WIDTH`], and a [`ToolError::WriteVintOverflow`] error if `value` doesn't fit in `width` bytes.
+///
+pub fn write_vint_with_width(value: u64, width: usize) -> Result<Vec<u8>, ToolError> {
+    check_width(width)?;
+    if value >= 1 << (width * 7) {
+        return Err(ToolError::WriteVintOverflow(value));
+    }
+    Ok(Vec::from(&vint_bytes_u64(value, width)[(8 - width)..]))
+}
+
+///
+/// Returns a signed vint of exactly `width` bytes holding `value`.  See [`SignedVint`] for how signed values are encoded.
+///
+/// # Errors
+///
+/// This returns a [`ToolError::InvalidVintWidth`] error if `width` isn't between 1 and [`MAX_WIDTH`], and a [`ToolError::WriteSignedVintOverflow`] error if `value` doesn't fit in `width` bytes.
+///
+pub fn write_signed_vint_with_width(value: i64, width: usize) -> Result<Vec<u8>, ToolError> {
+    check_width(width)?;
+    value.as_signed_vint_with_length(width)
+}
+
+///
+/// Returns whether a vint of `width` bytes holding `value` would be read as an unknown element size.
+///
+pub fn is_unknown_size(value: u64, width: usize) -> bool {
+    (1..=MAX_WIDTH).contains(&width) && value == (1 << (7 * width)) - 1
+}
+
+///
+/// Returns a vint of `width` bytes that marks an element size as unknown.
+///
+/// # Errors
+///
+/// This returns a [`ToolError::InvalidVintWidth`] error if `width` isn't between 1 and [`MAX_WIDTH`].
+///
+pub fn unknown_size(width: usize) -> Result<Vec<u8>, ToolError> {
+    check_width(width)?;
+    let mut bytes = vec![0xff; width];
+    bytes[0] >>= width - 1;
+    Ok(bytes)
+}
+
+///
+/// Reads an element size from the beginning of the input array slice.
+///
+/// This behaves like [`read_vint()`], except that the value is [`None`] if the size is unknown.
+///
+/// # Errors
+///
+/// This method can return a `ToolError` if the input array cannot be read as a vint.
+///
+pub fn read_size(buffer: &[u8]) -> Result<Option<(Option<u64>, usize)>, ToolError> {
+    Ok(read_vint(buffer)?.map(|(value, width)| {
+        if is_unknown_size(value, width) {
+            (None, width)
+        } else {
+            (Some(value), width)
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        for width in 1..=MAX_WIDTH {
+            let bytes = unknown_size(width).unwrap();
+            assert_eq!(width, bytes.len());
+            assert_eq!(Some(width), vint_width(bytes[0]));
+            assert_eq!(Some((None, width)), read_size(&bytes).unwrap());
+
+            let max = (1u64 << (7 * width)) - 2;
+            assert_eq!(width, size_width(max));
+            let bytes = write_vint_with_width(max, width).unwrap();
+            assert_eq!(Some((Some(max), width)), read_size(&bytes).unwrap());
+            assert!(matches!(write_vint_with_width(max + 2, width), Err(ToolError::WriteVintOverflow(_))));
+        }
+    }
+
+    #[test]
+    fn invalid_widths() {
+        assert!(matches!(write_vint_with_width(0, 0), Err(ToolError::InvalidVintWidth(0))));
+        assert!(matches!(write_vint_with_width(0, 9), Err(ToolError::InvalidVintWidth(9))));
+        assert!(matches!(write_signed_vint_with_width(0, 9), Err(ToolError::InvalidVintWidth(9))));
+        assert!(matches!(unknown_size(0), Err(ToolError::InvalidVintWidth(0))));
+        assert!(!is_unknown_size(0, 0));
+    }
+
+    #[test]
+    fn signed_widths() {
+        for value in [-63, -1, 0, 1, 63] {
+            for width in 1..=MAX_WIDTH {
+                let bytes = write_signed_vint_with_width(value, width).unwrap();
+                assert_eq!(Some((value, width)), read_signed_vint(&bytes).unwrap());
+            }
+        }
+        assert!(write_signed_vint_with_width(64, 1).is_err());
+    }
+}
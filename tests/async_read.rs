@@ -0,0 +1,112 @@
+#[cfg(feature = "futures")]
+mod test_spec;
+
+#[cfg(feature = "futures")]
+pub mod async_read {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIteratorAsync, TagWriter};
+    use futures::executor::block_on;
+
+    use super::test_spec::TestSpec;
+
+    fn sample_tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Block(vec![0x01; 300]),
+            TestSpec::Count(2),
+            TestSpec::SimpleBlock(vec![0x02; 5]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ]
+    }
+
+    fn write_tags(tags: &[TestSpec]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        for tag in tags.iter() {
+            writer.write(tag).expect("Test shouldn't error");
+        }
+        writer.into_inner().expect("Test shouldn't error").into_inner()
+    }
+
+    fn read_all(mut iter: TagIteratorAsync<futures::io::Cursor<Vec<u8>>, TestSpec>) -> Vec<TestSpec> {
+        block_on(async {
+            let mut read_tags = Vec::new();
+            while let Some(tag) = iter.next().await {
+                read_tags.push(tag.expect("Test shouldn't error"));
+            }
+            read_tags
+        })
+    }
+
+    #[test]
+    pub fn read_tags_async() {
+        let tags = sample_tags();
+        let iter = TagIteratorAsync::new(futures::io::Cursor::new(write_tags(&tags)));
+        assert_eq!(tags, read_all(iter));
+    }
+
+    #[test]
+    pub fn read_tags_async_with_capacity() {
+        let tags = sample_tags();
+        for read_size in [0, 1, 7, 1024] {
+            let iter = TagIteratorAsync::with_capacity(futures::io::Cursor::new(write_tags(&tags)), read_size);
+            assert_eq!(tags, read_all(iter), "read size {}", read_size);
+        }
+    }
+
+    #[test]
+    pub fn oversized_child_async() {
+        let mut data = write_tags(&[TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))]))]);
+        // Extend the size of the count past the end of its cluster
+        data[12] = 0x84;
+        data.extend_from_slice(&[0, 0, 0]);
+
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(data));
+        block_on(async {
+            assert!(matches!(iter.next().await, Some(Ok(TestSpec::Segment(Master::Start)))));
+            assert!(matches!(iter.next().await, Some(Ok(TestSpec::Cluster(Master::Start)))));
+            assert!(matches!(iter.next().await, Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement { position: 10, tag_id: 0x4100, size: 4, end: 17, parent_id: 0x1f43b675, parent_position: 5, parent_end: 14 })))));
+        });
+    }
+
+    ///
+    /// A tokio source that delivers a few bytes at a time, like a network stream.
+    ///
+    #[cfg(feature = "tokio")]
+    struct TrickleReader {
+        data: Vec<u8>,
+        position: usize,
+        ready: bool,
+    }
+
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncRead for TrickleReader {
+        fn poll_read(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> std::task::Poll<std::io::Result<()>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return std::task::Poll::Pending;
+            }
+            let end = self.data.len().min(self.position + 3).min(self.position + buf.remaining());
+            buf.put_slice(&self.data[self.position..end]);
+            self.position = end;
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    pub fn read_tokio_stream() {
+        use futures::StreamExt;
+
+        let tags = sample_tags();
+        let reader = TrickleReader { data: write_tags(&tags), position: 0, ready: false };
+        let stream = TagIteratorAsync::<_, TestSpec>::from_tokio_with_capacity(reader, 16).into_stream();
+        let read: Vec<TestSpec> = block_on(stream.map(|tag| tag.unwrap()).collect());
+        assert_eq!(tags, read);
+    }
+}
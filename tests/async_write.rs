@@ -0,0 +1,155 @@
+#[cfg(feature = "futures")]
+mod test_spec;
+
+#[cfg(feature = "futures")]
+pub mod async_write {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagWriter, TagWriterAsync};
+    use futures::executor::block_on;
+    use futures::{AsyncWrite, SinkExt};
+
+    use super::test_spec::TestSpec;
+
+    ///
+    /// A destination that accepts a few bytes at a time and is only ready on every other poll.
+    ///
+    #[derive(Default)]
+    struct SlowSink {
+        data: Vec<u8>,
+        ready: bool,
+        closed: bool,
+    }
+
+    impl AsyncWrite for SlowSink {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let len = buf.len().min(3);
+            self.data.extend_from_slice(&buf[..len]);
+            Poll::Ready(Ok(len))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.closed = true;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn tags() -> Vec<TestSpec> {
+        let mut tags = vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1)];
+        for count in 0..20 {
+            tags.push(TestSpec::Cluster(Master::Full(vec![TestSpec::Count(count), TestSpec::Block(vec![count as u8; 10])])));
+        }
+        tags.push(TestSpec::Segment(Master::End));
+        tags
+    }
+
+    fn write_sync(tags: &[TestSpec], unknown_size: bool) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            if unknown_size && matches!(tag, TestSpec::Segment(Master::Start)) {
+                writer.write_unknown_size(tag).unwrap();
+            } else {
+                writer.write(tag).unwrap();
+            }
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn bounded_buffering() {
+        let tags = tags();
+        let mut writer = TagWriterAsync::with_capacity(SlowSink::default(), 16);
+        block_on(async {
+            writer.write_unknown_size(&tags[0]).await.unwrap();
+            for tag in tags[1..].iter() {
+                writer.write(tag).await.unwrap();
+                // At most the capacity plus the tag that was just written
+                assert!(writer.buffered_len() <= 16 + 20);
+            }
+            writer.close().await.unwrap();
+        });
+        assert_eq!(0, writer.buffered_len());
+        assert!(writer.get_ref().closed);
+        assert_eq!(write_sync(&tags, true), writer.into_inner().data);
+    }
+
+    #[test]
+    pub fn flush_keeps_tags_open() {
+        let mut writer = TagWriterAsync::new(SlowSink::default());
+        block_on(async {
+            writer.write_unknown_size(&TestSpec::Segment(Master::Start)).await.unwrap();
+            writer.write(&TestSpec::TrackType(1)).await.unwrap();
+            writer.flush().await.unwrap();
+        });
+        assert_eq!(0, writer.buffered_len());
+        let sent = writer.get_ref().data.clone();
+        assert!(!sent.is_empty());
+        assert!(!writer.get_ref().closed);
+
+        block_on(async {
+            writer.write(&TestSpec::Segment(Master::End)).await.unwrap();
+            writer.close().await.unwrap();
+        });
+        let data = writer.into_inner().data;
+        assert!(data.starts_with(&sent));
+        assert_eq!(write_sync(&[TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Segment(Master::End)], true), data);
+    }
+
+    #[test]
+    pub fn sink() {
+        let tags = tags();
+        let mut writer = TagWriterAsync::with_capacity(SlowSink::default(), 0);
+        block_on(async {
+            for tag in tags.iter() {
+                writer.feed(tag.clone()).await.unwrap();
+            }
+            SinkExt::<TestSpec>::close(&mut writer).await.unwrap();
+        });
+        assert!(writer.get_ref().closed);
+        assert_eq!(write_sync(&tags, false), writer.into_inner().data);
+    }
+
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncWrite for SlowSink {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            AsyncWrite::poll_write(self, cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            AsyncWrite::poll_flush(self, cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            AsyncWrite::poll_close(self, cx)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    pub fn write_tokio_destination() {
+        let tags = tags();
+        let mut writer = TagWriterAsync::from_tokio_with_capacity(SlowSink::default(), 16);
+        block_on(async {
+            writer.write_unknown_size(&tags[0]).await.unwrap();
+            for tag in tags[1..].iter() {
+                writer.write(tag).await.unwrap();
+            }
+            writer.close().await.unwrap();
+        });
+        let dest = writer.into_inner().into_inner();
+        assert!(dest.closed);
+        assert_eq!(write_sync(&tags, true), dest.data);
+    }
+}
@@ -0,0 +1,70 @@
+mod test_spec;
+
+pub mod conformance {
+    use ebml_iterable::conformance::{round_trip, round_trip_dir, RoundTripOutcome};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::TagWriter;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::test_spec::TestSpec;
+
+    fn write_exact() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![1, 2, 3])])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn write_unknown_size() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn reference_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ebml-iterable-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    pub fn classify_round_trips() {
+        assert_eq!(RoundTripOutcome::ByteExact, round_trip::<TestSpec>(&write_exact()));
+        assert_eq!(RoundTripOutcome::Structural, round_trip::<TestSpec>(&write_unknown_size()));
+
+        // TrackType followed by an id that isn't in the spec
+        let corrupt = vec![0x18, 0x53, 0x80, 0x67, 0x86, 0x83, 0x81, 0x01, 0xf2, 0x81, 0x01];
+        assert!(matches!(round_trip::<TestSpec>(&corrupt), RoundTripOutcome::ReadError { offset: 5, .. }));
+    }
+
+    #[test]
+    pub fn report_for_directory() {
+        let dir = reference_dir("conformance");
+        fs::write(dir.join("a_exact.ebml"), write_exact()).unwrap();
+        fs::write(dir.join("b_unknown_size.ebml"), write_unknown_size()).unwrap();
+        fs::create_dir(dir.join("nested")).unwrap();
+
+        let report = round_trip_dir::<TestSpec>(&dir).unwrap();
+        assert!(report.is_success(), "{}", report);
+        assert_eq!(2, report.files.len());
+
+        fs::write(dir.join("c_corrupt.ebml"), [0x18, 0x53, 0x80, 0x67, 0x83, 0xf2, 0x81, 0x01]).unwrap();
+        let report = round_trip_dir::<TestSpec>(&dir).unwrap();
+        assert!(!report.is_success());
+        assert_eq!(vec![dir.join("c_corrupt.ebml")], report.failures().map(|file| file.path.clone()).collect::<Vec<_>>());
+
+        let json = report.to_json();
+        assert!(json.starts_with("{\"success\":false,\"files\":[{\"path\":"));
+        assert!(json.contains("\"outcome\":\"byte_exact\""));
+        assert!(json.contains("\"outcome\":\"structural\""));
+        assert!(json.contains("\"outcome\":\"read_error\",\"offset\":0,\"message\":\"Encountered corrupted data."), "{}", json);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
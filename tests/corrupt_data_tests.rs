@@ -1,8 +1,8 @@
 mod test_spec;
 
 pub mod corrupt_data_tests {
-    use ebml_iterable::error::{TagIteratorError, CorruptedFileError};
-    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::error::{TagIteratorError, CorruptedFileError, ReadErrorKind};
+    use ebml_iterable::iterator::{AllowableErrors, Quirk, TagIteratorOptions};
     use ebml_iterable::specs::Master;
     use ebml_iterable::{TagIterator, TagWriter};
     use std::io::Cursor;
@@ -67,7 +67,7 @@ pub mod corrupt_data_tests {
         let mut cursor = get_data_with_hierarchy_problems();
         let mut reader: TagIterator<_, TestSpec> = TagIterator::new(&mut cursor, &[]);
         assert!(reader.next().unwrap().is_ok());
-        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::HierarchyError{found_tag_id: _, current_parent_id: _}))));
+        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::HierarchyError{position: _, found_tag_id: _, current_parent_id: _}))));
     }
 
     #[test]
@@ -110,7 +110,7 @@ pub mod corrupt_data_tests {
         let mut reader: TagIterator<_, TestSpec> = TagIterator::new(&mut cursor, &[]);
         assert!(reader.next().unwrap().is_ok());
         assert!(reader.next().unwrap().is_ok());
-        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement{position: _, tag_id: _, size: _}))));
+        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement{position: 10, tag_id: 0xa1, size: 6, end: 18, parent_id: 0x1f43b675, parent_position: 5, parent_end: 16}))));
     }
 
     #[test]
@@ -154,9 +154,240 @@ pub mod corrupt_data_tests {
         assert!(reader.try_recover().is_ok());
         reader.for_each(|t| 
             if let Err(err) = t {
-                println!("{err:?}");
-                assert!(false);
+                panic!("{:?}", err);
             }
         );
     }
-}
\ No newline at end of file
+
+    fn get_data_with_junk_before_clusters() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let mut data = writer.into_inner().unwrap();
+
+        // Junk containing a valid looking TrackType and a partial Cluster id
+        let junk = [0xff, 0x00, 0x00, 0x83, 0x81, 0x01, 0x1f, 0x43, 0xb6, 0x00, 0xff];
+        data.splice(5..5, junk.iter().copied());
+        data
+    }
+
+    #[test]
+    pub fn recover_at_any_tag() {
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data_with_junk_before_clusters()), &[]);
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(reader.try_recover().is_ok());
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::TrackType(1)))));
+    }
+
+    #[test]
+    pub fn recover_at_specific_tags() {
+        for capacity in [16, 1024] {
+            let mut reader: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(get_data_with_junk_before_clusters()), &[], capacity);
+            reader.recover_at_tags(&[TestSpec::Cluster(Master::Start)]);
+            assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+            assert!(matches!(reader.next(), Some(Err(_))));
+            assert!(reader.try_recover().is_ok());
+
+            let tags: Vec<TestSpec> = reader.map(|t| t.unwrap()).collect();
+            assert_eq!(vec![
+                TestSpec::Cluster(Master::Start),
+                TestSpec::Count(1),
+                TestSpec::Cluster(Master::End),
+                TestSpec::Cluster(Master::Start),
+                TestSpec::Count(2),
+                TestSpec::Cluster(Master::End),
+                TestSpec::Segment(Master::End),
+            ], tags);
+        }
+    }
+
+    #[test]
+    pub fn resync_on_error() {
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(get_data_with_junk_before_clusters()), &[], 16);
+        reader.recover_at_tags(&[TestSpec::Cluster(Master::Start)]);
+        reader.set_resync_on_error(true);
+        let tags: Vec<_> = reader.collect();
+        assert_eq!(9, tags.len());
+        assert!(matches!(tags[0], Ok(TestSpec::Segment(Master::Start))));
+        assert!(matches!(tags[1], Err(TagIteratorError::CorruptedFileData(_))));
+        assert_eq!(vec![
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], tags.into_iter().skip(2).map(|t| t.unwrap()).collect::<Vec<_>>());
+
+        // Nothing to resync on before the end of the source
+        let mut data = get_data_with_junk_before_clusters();
+        data.truncate(14);
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.recover_at_tags(&[TestSpec::Cluster(Master::Start)]);
+        reader.set_resync_on_error(true);
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(_)))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::End)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    pub fn recover_at_specific_tags_eof() {
+        let mut data = get_data_with_junk_before_clusters();
+        data.truncate(14);
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.recover_at_tags(&[TestSpec::Cluster(Master::Start)]);
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(matches!(reader.try_recover(), Err(TagIteratorError::UnexpectedEOF { .. })));
+    }
+
+    #[test]
+    pub fn truncated_tag_header() {
+        // TrackType's two byte id is cut off after the first byte
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(vec![0x18, 0x53, 0x80, 0x67, 0x88, 0x41]), &[]);
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::UnexpectedEOF { tag_start: 5, tag_id: None, .. }))));
+
+        // Size vint is cut off
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(vec![0x18, 0x53, 0x80, 0x67, 0x88, 0x83, 0x40]), &[]);
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::UnexpectedEOF { tag_start: 5, tag_id: Some(0x83), tag_size: None, .. }))));
+    }
+
+    #[test]
+    pub fn oversized_tag_does_not_preallocate() {
+        // SimpleBlock claiming a 1 TiB body with only a few bytes of data
+        let mut data = vec![0xa3, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00];
+        data.extend_from_slice(&[0x01; 100]);
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::UnexpectedEOF { tag_start: 0, tag_id: Some(0xa3), tag_size: Some(0x100_0000_0000), .. }))));
+    }
+
+    #[test]
+    pub fn max_tag_size() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 100]), TestSpec::Count(1)]))]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        // A plain slice can't be seeked, like a pipe
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(data.as_slice(), &[]);
+        reader.set_max_tag_size(Some(16));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Cluster(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { tag_id: 0xa1, size: 100, max_size: 16, .. })))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Count(1)))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Cluster(Master::End)))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::End)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    pub fn error_kinds() {
+        let first_error = |data: &[u8]| {
+            let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.to_vec()), &[]);
+            reader.find_map(|tag| tag.err()).expect("data should be invalid")
+        };
+
+        let error = first_error(&[0x81, 0x84, 0x41, 0x02, 0x81, 0xff]);
+        assert_eq!((ReadErrorKind::InvalidUtf8, Some(2), Some(0x4102)), (error.kind(), error.position(), error.tag_id()));
+
+        let error = first_error(&[0x81, 0x00, 0x00, 0x00]);
+        assert!(matches!(error, TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidVint { position: 1, tag_id: Some(0x81) })));
+        assert_eq!((ReadErrorKind::InvalidVint, Some(1), Some(0x81)), (error.kind(), error.position(), error.tag_id()));
+
+        let error = first_error(&[0x81, 0x85, 0x41, 0x01]);
+        assert_eq!((ReadErrorKind::UnexpectedEof, Some(2), Some(0x4101)), (error.kind(), error.position(), error.tag_id()));
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(vec![0x18, 0x53, 0x80, 0x67, 0x83, 0x81, 0x81, 0x01]), &[]);
+        reader.set_max_lengths(Some(2), None);
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!((ReadErrorKind::IdTooLong, Some(0), Some(0x18538067)), (error.kind(), error.position(), error.tag_id()));
+    }
+
+    #[test]
+    pub fn max_buffered_size() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 100])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        writer.write_unknown_size(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        for _ in 0..4 {
+            writer.write(&TestSpec::Block(vec![0; 20])).unwrap();
+        }
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(data.as_slice(), &[TestSpec::Cluster(Master::Start)]);
+        reader.set_options(TagIteratorOptions { max_total_buffered: Some(50), ..Default::default() });
+        assert_eq!(Some(50), reader.options().max_total_buffered);
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxBufferedSizeExceeded { position: 6, tag_id: 0x1f43b675, max_size: 50 })))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Cluster(Master::Full(children)))) if children == vec![TestSpec::Count(1)]));
+        // Unknown sized tags are checked as they are read
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxBufferedSizeExceeded { tag_id: 0x1f43b675, max_size: 50, .. })))));
+        assert!(matches!(reader.next(), Some(Ok(TestSpec::Segment(Master::End)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    pub fn quirks_zero_sized_master() {
+        let data = vec![0x18, 0x53, 0x80, 0x67, 0x80, 0x83, 0x81, 0x01];
+        let reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        assert!(reader.into_iter().any(|t| t.is_err()));
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.set_quirks_mode(true);
+        let tags: Vec<TestSpec> = reader.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Segment(Master::End),
+        ], tags);
+        assert_eq!(vec![Quirk::ZeroSizedMaster { position: 0, tag_id: 0x18538067 }], reader.take_warnings());
+        assert!(reader.take_warnings().is_empty());
+    }
+
+    #[test]
+    pub fn quirks_overlong_integer() {
+        let data = vec![0x18, 0x53, 0x80, 0x67, 0x8c, 0x83, 0x8a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05];
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        assert!(matches!(reader.nth(1), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: 0x83, .. })))));
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        reader.set_quirks_mode(true);
+        assert!(matches!(reader.nth(1), Some(Ok(TestSpec::TrackType(0x05)))));
+        assert_eq!(vec![Quirk::OverlongInteger { position: 5, tag_id: 0x83, size: 10 }], reader.take_warnings());
+
+        let mut data = data;
+        data[7] = 0x01;
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.set_quirks_mode(true);
+        assert!(matches!(reader.nth(1), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: 0x83, .. })))));
+    }
+
+    #[test]
+    pub fn quirks_trailing_garbage() {
+        let mut data = vec![0x18, 0x53, 0x80, 0x67, 0x83, 0x83, 0x81, 0x01];
+        data.extend_from_slice(&[0x00, 0x00, 0x12, 0x34]);
+        let reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        assert!(reader.into_iter().any(|t| t.is_err()));
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        reader.set_quirks_mode(true);
+        let tags: Vec<TestSpec> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(3, tags.len());
+        assert_eq!(vec![Quirk::TrailingGarbage { position: 8 }], reader.take_warnings());
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.set_quirks_mode(true);
+        reader.set_strict_mode(true);
+        assert!(reader.into_iter().any(|t| t.is_err()));
+    }
+}
@@ -0,0 +1,88 @@
+mod test_spec;
+
+pub mod crc32 {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::ids;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::tools::crc32;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn write_document(streaming: bool) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_streaming(streaming);
+        writer.set_crc32_tags(&[ids::CLUSTER]);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![1; 8])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        // A stale checksum (e.g. from a copied document) is replaced
+        writer.write(&TestSpec::Crc32(vec![0; 4])).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        writer.write(&TestSpec::Block(vec![2; 8])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn read_clusters(data: &[u8]) -> Vec<Vec<TestSpec>> {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Cluster(Master::Start)]);
+        iter.filter_map(|tag| match tag.unwrap() {
+            TestSpec::Cluster(Master::Full(children)) => Some(children),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    pub fn write_checksums() {
+        for streaming in [false, true] {
+            let data = write_document(streaming);
+            let clusters = read_clusters(&data);
+            assert_eq!(2, clusters.len());
+            for (index, children) in clusters.iter().enumerate() {
+                let count = index as u64 + 1;
+                assert_eq!(3, children.len());
+                assert_eq!(&children[1..], &[TestSpec::Count(count), TestSpec::Block(vec![count as u8; 8])]);
+
+                let mut covered = vec![0x41, 0x00, 0x81, count as u8, 0xa1, 0x88];
+                covered.extend_from_slice(&[count as u8; 8]);
+                assert_eq!(TestSpec::Crc32(crc32(&covered).to_le_bytes().to_vec()), children[0]);
+            }
+        }
+    }
+
+    #[test]
+    pub fn verify_checksums() {
+        let mut data = write_document(false);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.set_verify_crc32(true);
+        assert!(iter.all(|tag| tag.is_ok()));
+
+        // Corrupt the data of the second cluster's Block
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.set_verify_crc32(true);
+        let tags: Vec<_> = iter.collect();
+        let errors: Vec<_> = tags.iter().enumerate().filter(|(_, tag)| tag.is_err()).collect();
+        assert_eq!(1, errors.len());
+        let (index, error) = errors[0];
+        assert!(matches!(error, Err(TagIteratorError::CorruptedFileData(CorruptedFileError::Crc32Mismatch { tag_id: ids::CLUSTER, .. }))));
+        assert!(matches!(tags[index - 1], Ok(TestSpec::Cluster(Master::End))));
+        assert!(matches!(tags[index + 1], Ok(TestSpec::Segment(Master::End))));
+
+        // Buffered tags are checked too
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Segment(Master::Start)]);
+        iter.set_verify_crc32(true);
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Segment(Master::Full(_))))));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::Crc32Mismatch { tag_id: ids::CLUSTER, .. })))));
+        assert!(iter.next().is_none());
+
+        // Without verification, the data is read as usual
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        assert!(iter.all(|tag| tag.is_ok()));
+    }
+}
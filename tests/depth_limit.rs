@@ -0,0 +1,92 @@
+#[cfg(feature = "derive-spec")]
+pub mod depth_limit {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError, TagWriterError};
+    use ebml_iterable::iterator::DEFAULT_MAX_DEPTH;
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Nested {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root/(-))]
+        Group,
+    }
+
+    fn nested_tree(depth: usize) -> Nested {
+        let mut tag = Nested::Group(Master::Full(Vec::new()));
+        for _ in 1..depth {
+            tag = Nested::Group(Master::Full(vec![tag]));
+        }
+        Nested::Root(Master::Full(vec![tag]))
+    }
+
+    fn nested_start_tags(depth: usize) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_max_depth(usize::MAX);
+        writer.write_unknown_size(&Nested::Root(Master::Start)).unwrap();
+        for _ in 0..depth {
+            writer.write(&Nested::Group(Master::Start)).unwrap();
+        }
+        for _ in 0..depth {
+            writer.write(&Nested::Group(Master::End)).unwrap();
+        }
+        writer.write(&Nested::Root(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn write_nested_within_limit() {
+        let tree = nested_tree(DEFAULT_MAX_DEPTH - 1);
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&tree).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let iter: TagIterator<_, Nested> = TagIterator::new(Cursor::new(data), &[Nested::Root(Master::Start)]);
+        let read: Vec<Nested> = iter.map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![tree], read);
+    }
+
+    #[test]
+    pub fn write_full_tree_too_deep() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_max_depth(10);
+        assert!(writer.write(&nested_tree(9)).is_ok());
+        assert!(matches!(writer.write(&nested_tree(10)), Err(TagWriterError::MaxDepthExceeded { tag_id: 0x4100, max_depth: 10 })));
+
+        // Deep enough to overflow the stack if the tree were walked recursively
+        let mut writer = TagWriter::new(Vec::new());
+        assert!(matches!(writer.write(&nested_tree(10_000)), Err(TagWriterError::MaxDepthExceeded { tag_id: 0x4100, .. })));
+    }
+
+    #[test]
+    pub fn write_start_tags_too_deep() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_max_depth(3);
+        writer.write(&Nested::Root(Master::Start)).unwrap();
+        writer.write(&Nested::Group(Master::Start)).unwrap();
+        writer.write(&Nested::Group(Master::Start)).unwrap();
+        assert!(matches!(writer.write(&Nested::Group(Master::Start)), Err(TagWriterError::MaxDepthExceeded { tag_id: 0x4100, max_depth: 3 })));
+        assert!(matches!(writer.finish(), Err(TagWriterError::UnclosedTags { .. })));
+    }
+
+    #[test]
+    pub fn read_too_deep() {
+        let data = nested_start_tags(DEFAULT_MAX_DEPTH);
+        let iter: TagIterator<_, Nested> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let error = iter.into_iter().find_map(|tag| tag.err());
+        assert!(matches!(error, Some(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxDepthExceeded { tag_id: 0x4100, max_depth: DEFAULT_MAX_DEPTH, .. }))));
+
+        let mut iter: TagIterator<_, Nested> = TagIterator::new(Cursor::new(data), &[Nested::Root(Master::Start)]);
+        iter.set_max_depth(DEFAULT_MAX_DEPTH + 1);
+        assert!(matches!(iter.next(), Some(Ok(Nested::Root(Master::Full(_))))));
+        assert!(iter.next().is_none());
+    }
+}
@@ -32,6 +32,10 @@ pub mod derive_spec_compile {
         #[id(0x101)]
         #[data_type(TagDataType::Integer)]
         Id,  
+
+        #[id(0x103)]
+        #[data_type(TagDataType::Date)]
+        Created,
     }
 
     #[test]
@@ -42,4 +46,15 @@ pub mod derive_spec_compile {
         let tag = Trial::get_master_tag(0x01, Master::Start).unwrap();
         assert_eq!(Trial::Root(Master::Start), tag);
     }
+
+    #[test]
+    pub fn date_variant() {
+        use ebml_iterable::specs::EbmlTag;
+
+        assert_eq!(Some(TagDataType::Date), Trial::get_tag_data_type(0x103));
+        let tag = Trial::get_date_tag(0x103, -5).unwrap();
+        assert_eq!(Trial::Created(-5), tag);
+        assert_eq!(Some(&-5), tag.as_date());
+        assert_eq!(None, Trial::get_date_tag(0x101, -5));
+    }
 }
\ No newline at end of file
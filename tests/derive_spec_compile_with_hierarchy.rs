@@ -47,5 +47,8 @@ pub mod derive_spec_compile {
         
         let tag = Trial::get_master_tag(0x01, Master::Start).unwrap();
         assert_eq!(Trial::Root(Master::Start), tag);
+
+        assert_eq!(Some("Count"), Trial::get_tag_name(0x100));
+        assert_eq!(None, Trial::get_tag_name(0x300));
     }
 }
\ No newline at end of file
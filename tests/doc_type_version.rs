@@ -0,0 +1,203 @@
+#[cfg(feature = "derive-spec")]
+pub mod doc_type_version {
+    use std::io::Cursor;
+
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::error::{TagIteratorError, TagWriterError};
+    use ebml_iterable::header::{EbmlHeader, EbmlHeaderSpec, ReaderSupport};
+    use ebml_iterable::iterator::BorrowedTag;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Versioned {
+        #[id(0x1a45dfa3)]
+        #[data_type(TagDataType::Master)]
+        Ebml,
+
+        #[id(0x4287)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Ebml)]
+        DocTypeVersion,
+
+        #[id(0x4285)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Ebml)]
+        DocTypeReadVersion,
+
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment)]
+        Original,
+
+        #[id(0x4101)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment)]
+        #[min_version(3)]
+        Newer,
+
+        #[id(0x4102)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment)]
+        #[min_version(4)]
+        #[min_read_version(2)]
+        Newest,
+    }
+
+    fn write_header(writer: &mut TagWriter<&mut Cursor<Vec<u8>>>) {
+        writer.write(&Versioned::Ebml(Master::Full(vec![
+            Versioned::DocTypeVersion(1),
+            Versioned::DocTypeReadVersion(1),
+        ]))).unwrap();
+    }
+
+    fn read_versions(data: Vec<u8>) -> (u64, u64) {
+        let iter: TagIterator<_, Versioned> = TagIterator::new(Cursor::new(data), &[Versioned::Ebml(Master::Start)]);
+        let header = iter.into_iter().next().unwrap().unwrap();
+        let children = match header {
+            Versioned::Ebml(master) => master.get_children(),
+            _ => panic!("Expected EBML header"),
+        };
+        match children.as_slice() {
+            [Versioned::DocTypeVersion(version), Versioned::DocTypeReadVersion(read_version)] => (*version, *read_version),
+            other => panic!("Unexpected header children {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn tracks_required_versions() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        write_header(&mut writer);
+        writer.write(&Versioned::Segment(Master::Start)).unwrap();
+        writer.write(&Versioned::Original(1)).unwrap();
+        assert_eq!(None, writer.required_doc_type_version());
+
+        writer.write(&Versioned::Newer(1)).unwrap();
+        assert_eq!(Some(3), writer.required_doc_type_version());
+        assert_eq!(None, writer.required_doc_type_read_version());
+
+        writer.write(&Versioned::Newest(1)).unwrap();
+        writer.write(&Versioned::Newer(1)).unwrap();
+        assert_eq!(Some(4), writer.required_doc_type_version());
+        assert_eq!(Some(2), writer.required_doc_type_read_version());
+        writer.write(&Versioned::Segment(Master::End)).unwrap();
+    }
+
+    #[test]
+    pub fn patches_flushed_header() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        write_header(&mut writer);
+        writer.write(&Versioned::Segment(Master::Start)).unwrap();
+        writer.write(&Versioned::Newest(1)).unwrap();
+        writer.write(&Versioned::Segment(Master::End)).unwrap();
+        writer.patch_doc_type_versions().unwrap();
+
+        // Writing after patching should still append to the end of the stream
+        writer.write(&Versioned::Segment(Master::Full(vec![]))).unwrap();
+        drop(writer);
+
+        let data = dest.into_inner();
+        assert_eq!((4, 2), read_versions(data.clone()));
+        assert_eq!(&data[(data.len() - 5)..], &[0x18, 0x53, 0x80, 0x67, 0x80]);
+    }
+
+    #[test]
+    pub fn patches_buffered_header() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&Versioned::Segment(Master::Start)).unwrap();
+        writer.write(&Versioned::Newest(1)).unwrap();
+        writer.write(&Versioned::Segment(Master::End)).unwrap();
+
+        // The header is still in the working buffer while it is open, and existing higher values are kept
+        writer.write(&Versioned::Ebml(Master::Start)).unwrap();
+        writer.write(&Versioned::DocTypeVersion(5)).unwrap();
+        writer.write(&Versioned::DocTypeReadVersion(1)).unwrap();
+        writer.patch_doc_type_versions().unwrap();
+        writer.write(&Versioned::Ebml(Master::End)).unwrap();
+        drop(writer);
+
+        let data = dest.into_inner();
+        assert_eq!((5, 2), read_versions(data[9..].to_vec()));
+    }
+
+    #[test]
+    pub fn errors_without_header() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&Versioned::Segment(Master::Start)).unwrap();
+        writer.write(&Versioned::Newer(1)).unwrap();
+        assert!(matches!(writer.patch_doc_type_versions(), Err(TagWriterError::TagPatchError { tag_id: 0x4287, message: _ })));
+        writer.write(&Versioned::Segment(Master::End)).unwrap();
+    }
+
+    #[test]
+    pub fn iterator_reads_header() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&Versioned::Ebml(Master::Full(vec![
+            Versioned::DocTypeVersion(4),
+            Versioned::DocTypeReadVersion(2),
+        ]))).unwrap();
+        writer.write(&Versioned::Segment(Master::Full(vec![Versioned::Original(1)]))).unwrap();
+        drop(writer);
+        let data = dest.into_inner();
+
+        for tags_to_buffer in [vec![], vec![Versioned::Ebml(Master::Start)]] {
+            let mut iter: TagIterator<_, Versioned> = TagIterator::new(Cursor::new(data.clone()), &tags_to_buffer);
+            while iter.ebml_header().is_none() {
+                assert!(!matches!(iter.next().unwrap().unwrap(), Versioned::Segment(_)));
+            }
+            let header = iter.ebml_header().unwrap();
+            assert_eq!((1, 4, 2, 13), (header.read_version, header.doc_type_version, header.doc_type_read_version, header.header_len));
+            assert_eq!("", header.doc_type);
+            assert_eq!(Some(Versioned::Segment(Master::Start)), iter.next().map(|tag| tag.unwrap()));
+        }
+
+        // The header doesn't declare a DocType, so it can't match
+        let mut iter: TagIterator<_, Versioned> = TagIterator::new(Cursor::new(data.clone()), &[Versioned::Ebml(Master::Start)]);
+        iter.set_supported_doc_types(&["matroska", "webm"]);
+        assert!(matches!(iter.next(), Some(Ok(Versioned::Ebml(_)))));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::UnsupportedDocType { doc_type: Some(doc_type) })) if doc_type.is_empty()));
+        assert!(iter.ebml_header().is_some());
+        assert_eq!(Some(Versioned::Segment(Master::Start)), iter.next().map(|tag| tag.unwrap()));
+
+        let mut iter: TagIterator<_, Versioned> = TagIterator::new(Cursor::new(data[13..].to_vec()), &[]);
+        iter.set_supported_doc_types(&["webm"]);
+        assert!(matches!(iter.next_borrowed(), Some(Err(TagIteratorError::UnsupportedDocType { doc_type: None }))));
+        assert!(matches!(iter.next_borrowed(), Some(Ok(BorrowedTag::Tag(Versioned::Segment(Master::Start))))));
+        assert!(iter.ebml_header().is_none());
+
+        // Resetting the iterator checks the next document again
+        iter.reset(Cursor::new(data[13..].to_vec()));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::UnsupportedDocType { doc_type: None }))));
+    }
+
+    #[test]
+    pub fn writes_standard_header() {
+        let header = EbmlHeader { doc_type: "matroska".to_string(), doc_type_version: 4, doc_type_read_version: 2, ..Default::default() };
+        let mut writer = TagWriter::new_streaming(Vec::new());
+        writer.write_ebml_header(&header).unwrap();
+        writer.write(&Versioned::Segment(Master::Full(vec![Versioned::Original(1)]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        // The header has a known size even though the writer is streaming
+        let read = EbmlHeader::read(&mut &data[..], &ReaderSupport { doc_types: &["matroska"], doc_type_read_version: Some(2) }).unwrap();
+        assert_eq!(EbmlHeader { header_len: 40, ..header.clone() }, read);
+
+        let mut iter: TagIterator<_, EbmlHeaderSpec> = TagIterator::new(&data[..40], &[EbmlHeaderSpec::Ebml(Master::Start)]);
+        iter.set_supported_doc_types(&["matroska"]);
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(Some(&read), iter.ebml_header());
+        assert!(iter.next().is_none());
+
+        let mut writer = TagWriter::new(Vec::new());
+        assert!(matches!(writer.write_ebml_header(&EbmlHeader { doc_type: "matroska\n".to_string(), ..header }), Err(TagWriterError::InvalidAsciiString { tag_id: 0x4282 })));
+    }
+}
@@ -0,0 +1,77 @@
+mod test_spec;
+
+pub mod document_stats {
+    use std::io::Cursor;
+
+    use ebml_iterable::iterator::TagSpan;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::stats::{analyze, ElementStats, LargeElement};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn document() -> Vec<TestSpec> {
+        vec![TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0; 300])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)])),
+        ]))]
+    }
+
+    #[test]
+    pub fn analyze_document() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&document()[0]).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let stats = analyze::<TestSpec, _>(Cursor::new(data.clone()), 2).unwrap();
+        assert_eq!(data.len() as u64, stats.total_bytes);
+        assert_eq!(331, stats.total_bytes);
+        assert_eq!(28, stats.header_bytes);
+        assert_eq!(303, stats.payload_bytes);
+        assert_eq!(vec![1, 3, 3], stats.depth_histogram);
+        assert_eq!(Some(&ElementStats { count: 2, header_bytes: 11, data_bytes: 311 }), stats.elements.get(&0x1f43b675));
+        assert_eq!(Some(&ElementStats { count: 2, header_bytes: 6, data_bytes: 2 }), stats.elements.get(&0x4100));
+        assert_eq!(vec![
+            LargeElement { tag_id: 0x18538067, offset: 0, size: 331, depth: 0 },
+            LargeElement { tag_id: 0x1f43b675, offset: 9, size: 313, depth: 1 },
+        ], stats.largest);
+        assert!((stats.header_overhead() - 28.0 / 331.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    pub fn analyze_unknown_sized_master() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        if let TestSpec::Segment(Master::Full(children)) = &document()[0] {
+            children.iter().for_each(|child| writer.write(child).unwrap());
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let stats = analyze::<TestSpec, _>(Cursor::new(data.clone()), 0).unwrap();
+        assert_eq!(data.len() as u64, stats.total_bytes);
+        assert_eq!(Some(&ElementStats { count: 1, header_bytes: 12, data_bytes: 325 }), stats.elements.get(&0x18538067));
+        assert!(stats.largest.is_empty());
+    }
+
+    #[test]
+    pub fn last_emitted_tag_span() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&document()[0]).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Cluster(Master::Start)]);
+        assert_eq!(Some(TestSpec::Segment(Master::Start)), iter.next().map(|t| t.unwrap()));
+        assert_eq!(TagSpan { offset: 0, header_len: 6, size_len: 2, data_len: Some(325) }, iter.last_emitted_tag_span());
+        assert_eq!(Some(TestSpec::TrackType(1)), iter.next().map(|t| t.unwrap()));
+        assert_eq!(TagSpan { offset: 6, header_len: 2, size_len: 1, data_len: Some(1) }, iter.last_emitted_tag_span());
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Cluster(Master::Full(_))))));
+        assert_eq!(TagSpan { offset: 9, header_len: 6, size_len: 2, data_len: Some(307) }, iter.last_emitted_tag_span());
+        assert_eq!(Some(313), iter.last_emitted_tag_span().total_len());
+        iter.next();
+        assert_eq!(Some(TestSpec::Segment(Master::End)), iter.next().map(|t| t.unwrap()));
+        assert_eq!(0, iter.last_emitted_tag_span().offset);
+        assert_eq!(6, iter.last_emitted_tag_span().data_offset());
+    }
+}
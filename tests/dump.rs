@@ -0,0 +1,69 @@
+mod test_spec;
+
+pub mod dump {
+    use std::io::{self, Cursor, Write};
+
+    use ebml_iterable::error::{DumpError, TagIteratorError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::TagWriter;
+
+    use super::test_spec::TestSpec;
+
+    fn document() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![
+            TestSpec::Int(7),
+            TestSpec::String(String::from("a longer string")),
+            TestSpec::Parent(Master::Full(vec![TestSpec::Child(1)])),
+        ]))).unwrap();
+        writer.write_raw(0x4200, &[0x01, 0x02]).unwrap();
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0xab; 10])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn dump_document() {
+        let mut out = Vec::new();
+        ebml_iterable::dump::<TestSpec, _, _>(Cursor::new(document()), &mut out, 4).unwrap();
+        assert_eq!(concat!(
+            "Root [0x81] at 0, size 30\n",
+            "  Int [0x4101] at 2, size 1: 7\n",
+            "  String [0x4102] at 6, size 15: \"a lo\"...\n",
+            "  Parent [0x4103] at 24, size 5\n",
+            "    Child [0x210301] at 27, size 1: 1\n",
+            "[0x4200] at 32, size 2: 01 02\n",
+            "Segment [0x18538067] at 37, unknown size\n",
+            "  Cluster [0x1f43b675] at 49, size 16\n",
+            "    Count [0x4100] at 54, size 1: 2\n",
+            "    Block [0xa1] at 58, size 10: ab ab ab ab ...\n",
+        ), String::from_utf8(out).unwrap());
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn dump_errors() {
+        let mut data = document();
+        data.truncate(40);
+        let mut out = Vec::new();
+        let err = ebml_iterable::dump::<TestSpec, _, _>(Cursor::new(data), &mut out, 4).unwrap_err();
+        assert!(matches!(err, DumpError::Read(TagIteratorError::UnexpectedEOF { .. })));
+        // Everything before the truncated tag was still written
+        assert!(String::from_utf8(out).unwrap().ends_with("[0x4200] at 32, size 2: 01 02\n"));
+
+        let err = ebml_iterable::dump::<TestSpec, _, _>(Cursor::new(document()), &mut FailingWriter, 4).unwrap_err();
+        assert!(matches!(err, DumpError::Write(ref source) if source.kind() == io::ErrorKind::BrokenPipe));
+    }
+}
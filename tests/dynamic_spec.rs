@@ -0,0 +1,127 @@
+mod test_spec;
+
+pub mod dynamic_spec {
+    use ebml_iterable::dynamic::{DynSpecification, DynTag, DynTagIterator, DynTagWriter, DynValue, StaticSpec};
+    use ebml_iterable::error::{HeaderError, TagIteratorError, TagWriterError};
+    use ebml_iterable::header::EbmlHeader;
+    use ebml_iterable::specs::{EbmlTag, Master, TagDataType};
+    use ebml_iterable::TagWriter;
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use super::test_spec::TestSpec;
+
+    fn sample_tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Count(7),
+                TestSpec::Block(vec![0x01, 0x02, 0x03]),
+            ])),
+            TestSpec::Segment(Master::End),
+            TestSpec::Root(Master::Full(vec![
+                TestSpec::String(String::from("dynamic")),
+                TestSpec::Parent(Master::Full(vec![TestSpec::Child(0x1234)])),
+            ])),
+        ]
+    }
+
+    #[test]
+    pub fn round_trip_through_dynamic_spec() {
+        let mut expected = TagWriter::new(Vec::new());
+        for tag in sample_tags() {
+            expected.write(&tag).unwrap();
+        }
+        let expected = expected.into_inner().unwrap();
+
+        let spec = StaticSpec::<TestSpec>::shared();
+        let mut writer = DynTagWriter::new(Vec::new(), Arc::clone(&spec));
+        for tag in sample_tags() {
+            writer.write(&DynTag::from_spec(&tag)).unwrap();
+        }
+        let written = writer.into_inner().unwrap();
+        assert_eq!(expected, written);
+
+        let iter = DynTagIterator::new(Cursor::new(written), spec, &[TestSpec::Cluster(Master::Start).get_id(), 0x81]);
+        let read: Vec<TestSpec> = iter.map(|tag| tag.unwrap().into_spec().unwrap()).collect();
+        assert_eq!(sample_tags(), read);
+    }
+
+    #[test]
+    pub fn select_spec_at_runtime() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(5)]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let specs: Vec<Arc<dyn DynSpecification>> = vec![StaticSpec::<TestSpec>::shared(), StaticSpec::<EmptySpec>::shared()];
+        let read: Vec<Vec<DynTag>> = specs.into_iter()
+            .map(|spec| DynTagIterator::new(Cursor::new(data.clone()), spec, &[]).map(|tag| tag.unwrap()).collect())
+            .collect();
+
+        assert_eq!(vec![
+            DynTag::new(0x81, DynValue::Master(Master::Start)),
+            DynTag::new(0x4101, DynValue::UnsignedInt(5)),
+            DynTag::new(0x81, DynValue::Master(Master::End)),
+        ], read[0]);
+        assert_eq!(vec![DynTag::new(0x81, DynValue::Binary(vec![0x41, 0x01, 0x81, 0x05]))], read[1]);
+    }
+
+    #[test]
+    pub fn reject_mismatched_data() {
+        let mut writer = DynTagWriter::new(Vec::new(), StaticSpec::<TestSpec>::shared());
+        let tag = DynTag::new(0x81, DynValue::Master(Master::Full(vec![DynTag::new(0x4101, DynValue::Utf8(String::from("five")))])));
+        assert!(matches!(writer.write(&tag), Err(TagWriterError::TagDataMismatch { tag_id: 0x4101, expected: Some(TagDataType::UnsignedInt) })));
+        assert!(matches!(writer.write(&DynTag::new(0xf2, DynValue::UnsignedInt(1))), Err(TagWriterError::TagDataMismatch { tag_id: 0xf2, expected: None })));
+
+        writer.write(&DynTag::new(0xf2, DynValue::Raw(vec![0x01]))).unwrap();
+        assert_eq!(vec![0xf2, 0x81, 0x01], writer.into_inner().unwrap());
+    }
+
+    #[test]
+    pub fn select_spec_from_header() {
+        let document = |doc_type: &str| {
+            let mut writer = TagWriter::new(Vec::new());
+            writer.write_ebml_header(&EbmlHeader { doc_type: String::from(doc_type), ..EbmlHeader::default() }).unwrap();
+            writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(5)]))).unwrap();
+            writer.into_inner().unwrap()
+        };
+        let select = |header: &EbmlHeader| -> Option<Arc<dyn DynSpecification>> {
+            match header.doc_type.as_str() {
+                "test" => Some(StaticSpec::<TestSpec>::shared()),
+                "empty" => Some(Arc::from(Box::new(StaticSpec::<EmptySpec>::new()) as Box<dyn DynSpecification>)),
+                _ => None,
+            }
+        };
+
+        let mut iter = DynTagIterator::from_header(Cursor::new(document("test")), &[0x81], select).unwrap();
+        assert_eq!("test", iter.ebml_header().unwrap().doc_type);
+        let header_len = iter.ebml_header().unwrap().header_len;
+        let tag = iter.next().unwrap().unwrap();
+        assert_eq!(DynTag::new(0x81, DynValue::Master(Master::Full(vec![DynTag::new(0x4101, DynValue::UnsignedInt(5))]))), tag);
+        assert_eq!(header_len, iter.last_emitted_tag_offset());
+        assert_eq!(Some(TagDataType::Master), iter.spec().tag_data_type(0x81));
+
+        let mut iter = DynTagIterator::from_header(Cursor::new(document("empty")), &[], select).unwrap();
+        assert_eq!(Some(DynTag::new(0x81, DynValue::Binary(vec![0x41, 0x01, 0x81, 0x05]))), iter.next().transpose().unwrap());
+
+        assert!(matches!(DynTagIterator::from_header(Cursor::new(document("other")), &[], select), Err(HeaderError::UnsupportedDocType(doc_type)) if doc_type == "other"));
+        assert!(matches!(DynTagIterator::from_header(Cursor::new(vec![0x81, 0x80]), &[], select), Err(HeaderError::MissingHeader { found_tag_id: 0x81 })));
+        assert!(matches!(DynTagIterator::from_header(Cursor::new(Vec::new()), &[], select), Err(HeaderError::Read(TagIteratorError::UnexpectedEOF { .. }))));
+    }
+
+    #[test]
+    pub fn switch_spec_between_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(5)]))).unwrap();
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(6)]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter = DynTagIterator::new(Cursor::new(data), StaticSpec::<EmptySpec>::shared(), &[]);
+        assert_eq!(Some(DynTag::new(0x81, DynValue::Binary(vec![0x41, 0x01, 0x81, 0x05]))), iter.next().transpose().unwrap());
+        iter.set_spec(StaticSpec::<TestSpec>::shared());
+        assert_eq!(Some(DynTag::new(0x81, DynValue::Master(Master::Start))), iter.next().transpose().unwrap());
+        assert_eq!(Some(DynTag::new(0x4101, DynValue::UnsignedInt(6))), iter.next().transpose().unwrap());
+    }
+}
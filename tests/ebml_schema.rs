@@ -0,0 +1,147 @@
+#[cfg(feature = "ebml-schema")]
+pub mod ebml_schema {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use ebml_iterable::dynamic::{DynamicSpec, DynSpecification, DynTag, DynTagIterator, DynTagWriter, DynValue};
+    use ebml_iterable::error::SchemaError;
+    use ebml_iterable::specs::{Master, PathPart, TagDataType};
+    use ebml_iterable::validate::{Violation, ViolationKind};
+
+    const SCHEMA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<EBMLSchema xmlns="urn:ietf:rfc:8794" docType="matroska" version="4">
+  <!-- \Segment -->
+  <element name="Segment" path="\Segment" id="0x18538067" type="master" minOccurs="1" maxOccurs="1" unknownsizeallowed="1">
+    <documentation lang="en" purpose="definition">The Root Element that contains all other Top-Level Elements.</documentation>
+  </element>
+  <element name="Info" path="\Segment\Info" id="0x1549A966" type="master" minOccurs="1" maxOccurs="1"/>
+  <element name="TimestampScale" path="\Segment\Info\TimestampScale" id="0x2AD7B1" type="uinteger" range="not 0" default="1000000" minOccurs="1" maxOccurs="1"/>
+  <element name="Duration" path="\Segment\Info\Duration" id="0x4489" type="float" range="&gt; 0x0p+0" maxOccurs="1"/>
+  <element name="MuxingApp" path="\Segment\Info\MuxingApp" id="0x4D80" type="utf-8" minOccurs="1" maxOccurs="1"/>
+  <element name="SegmentUUID" path="\Segment\Info\SegmentUUID" id="0x73A4" type="binary" length="16" maxOccurs="1"/>
+  <element name="Chapters" path="\Segment\Chapters" id="0x1043A770" type="master" maxOccurs="1"/>
+  <element name="EditionEntry" path="\Segment\Chapters\EditionEntry" id="0x45B9" type="master" minOccurs="1"/>
+  <element name="ChapterAtom" path="\Segment\Chapters\EditionEntry\+ChapterAtom" id="0xB6" type="master" minOccurs="1" recursive="1"/>
+  <element name="ChapterUID" path="\Segment\Chapters\EditionEntry\+ChapterAtom\ChapterUID" id="0x73C4" type="uinteger" range="not 0" minOccurs="1" maxOccurs="1"/>
+  <element name="ChapLanguage" path="\Segment\Chapters\EditionEntry\+ChapterAtom\ChapLanguage" id="0x437C" type="string" minver="1"/>
+  <element name="Tag" path="\Segment\(1-\)Tag" id="0x7373" type="master"/>
+</EBMLSchema>"#;
+
+    fn write(spec: &Arc<dyn DynSpecification>, tags: &[DynTag]) -> Vec<u8> {
+        let mut writer = DynTagWriter::new(Vec::new(), Arc::clone(spec));
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn master(id: u64, children: Vec<DynTag>) -> DynTag {
+        DynTag::new(id, DynValue::Master(Master::Full(children)))
+    }
+
+    #[test]
+    pub fn load_schema() {
+        let spec = DynamicSpec::from_ebml_schema(SCHEMA).unwrap();
+        assert_eq!("matroska", spec.doc_type());
+        assert_eq!(Some(4), spec.version());
+        assert_eq!(Some(0x4d80), spec.id_by_name("MuxingApp"));
+
+        assert_eq!(Some(TagDataType::Float), spec.tag_data_type(0x4489));
+        assert_eq!(Some("ChapterUID"), spec.tag_name(0x73c4));
+        assert!(spec.is_ascii_string(0x437c));
+        assert!(!spec.is_ascii_string(0x4d80));
+        assert_eq!(Some(1), spec.min_version(0x437c));
+
+        // TimestampScale has a default, so it doesn't need to be present
+        assert_eq!(&[0x4d80], spec.mandatory_children(0x1549a966));
+        assert_eq!(&[0x1549a966], spec.mandatory_children(0x18538067));
+        assert_eq!(&[0x73c4], spec.mandatory_children(0xb6));
+
+        assert_eq!(&[PathPart::Id(0x18538067), PathPart::Id(0x1043a770), PathPart::Id(0x45b9), PathPart::Global((Some(0), None))], spec.path_by_id(0xb6));
+        assert_eq!(&[PathPart::Id(0x18538067), PathPart::Id(0x1043a770), PathPart::Id(0x45b9), PathPart::Global((Some(1), None))], spec.path_by_id(0x73c4));
+        assert_eq!(&[PathPart::Id(0x18538067), PathPart::Global((Some(1), None))], spec.path_by_id(0x7373));
+
+        // EBML header elements are always available
+        assert_eq!(Some(TagDataType::Master), spec.tag_data_type(0x1a45dfa3));
+        assert_eq!(Some("DocType"), spec.tag_name(0x4282));
+        assert!(spec.is_ascii_string(0x4282));
+        assert_eq!(None, spec.tag_data_type(0x4200));
+    }
+
+    #[test]
+    pub fn read_with_schema() {
+        let spec: Arc<dyn DynSpecification> = Arc::new(DynamicSpec::from_ebml_schema(SCHEMA).unwrap());
+        let tags = vec![
+            master(0x1a45dfa3, vec![DynTag::new(0x4282, DynValue::Utf8(String::from("matroska")))]),
+            master(0x18538067, vec![
+                master(0x1549a966, vec![DynTag::new(0x4d80, DynValue::Utf8(String::from("schema"))), DynTag::new(0x4489, DynValue::Float(2.5))]),
+                master(0x1043a770, vec![master(0x45b9, vec![
+                    master(0xb6, vec![DynTag::new(0x73c4, DynValue::UnsignedInt(1)), master(0xb6, vec![DynTag::new(0x73c4, DynValue::UnsignedInt(2))])]),
+                ])]),
+            ]),
+        ];
+        let data = write(&spec, &tags);
+
+        let iter = DynTagIterator::new(Cursor::new(&data), Arc::clone(&spec), &[0x1a45dfa3, 0x18538067]);
+        assert_eq!(tags, iter.collect::<Result<Vec<_>, _>>().unwrap());
+
+        let validator = DynamicSpec::from_ebml_schema(SCHEMA).unwrap().validator();
+        assert!(validator.validate_with_spec(Cursor::new(&data), &spec).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn validate_with_schema() {
+        let schema = DynamicSpec::from_ebml_schema(SCHEMA).unwrap();
+        let validator = schema.validator();
+        let spec: Arc<dyn DynSpecification> = Arc::new(schema);
+        let data = write(&spec, &[master(0x18538067, vec![
+            master(0x1549a966, vec![
+                DynTag::new(0x2ad7b1, DynValue::UnsignedInt(0)),
+                DynTag::new(0x4489, DynValue::Float(0.0)),
+                DynTag::new(0x73a4, DynValue::Binary(vec![0; 4])),
+            ]),
+            master(0x1549a966, vec![DynTag::new(0x4d80, DynValue::Utf8(String::from("second")))]),
+        ])]);
+
+        let violations = validator.validate_with_spec(Cursor::new(data), &spec).unwrap();
+        assert_eq!(vec![
+            Violation { offset: 10, tag_id: 0x2ad7b1, kind: ViolationKind::OutOfRange { value: String::from("0") } },
+            Violation { offset: 15, tag_id: 0x4489, kind: ViolationKind::OutOfRange { value: String::from("0") } },
+            Violation { offset: 26, tag_id: 0x73a4, kind: ViolationKind::OutOfRange { value: String::from("4 bytes") } },
+            Violation { offset: 5, tag_id: 0x1549a966, kind: ViolationKind::MissingChild { child_id: 0x4d80 } },
+            Violation { offset: 33, tag_id: 0x1549a966, kind: ViolationKind::TooManyOccurrences { max: 1 } },
+        ], violations);
+    }
+
+    #[test]
+    pub fn invalid_schemas() {
+        assert!(matches!(DynamicSpec::from_ebml_schema("<EBMLSchema"), Err(SchemaError::InvalidXml(_))));
+        assert_eq!(SchemaError::MissingSchema, DynamicSpec::from_ebml_schema("<schema/>").unwrap_err());
+
+        let schema = |element: &str| format!(r#"<EBMLSchema docType="test">{element}</EBMLSchema>"#);
+        assert_eq!(
+            SchemaError::MissingAttribute { element: String::from("Title"), attribute: "id" },
+            DynamicSpec::from_ebml_schema(&schema(r#"<element name="Title" path="\Title" type="utf-8"/>"#)).unwrap_err()
+        );
+        assert_eq!(
+            SchemaError::InvalidAttribute { element: String::from("Title"), attribute: "type", value: String::from("text") },
+            DynamicSpec::from_ebml_schema(&schema(r#"<element name="Title" path="\Title" id="0x4100" type="text"/>"#)).unwrap_err()
+        );
+        assert_eq!(
+            SchemaError::InvalidAttribute { element: String::from("Title"), attribute: "path", value: String::from("\\Name") },
+            DynamicSpec::from_ebml_schema(&schema(r#"<element name="Title" path="\Name" id="0x4100" type="utf-8"/>"#)).unwrap_err()
+        );
+        assert_eq!(
+            SchemaError::UnknownParent { element: String::from("Title"), parent: String::from("Info") },
+            DynamicSpec::from_ebml_schema(&schema(r#"<element name="Title" path="\Info\Title" id="0x4100" type="utf-8"/>"#)).unwrap_err()
+        );
+        assert_eq!(
+            SchemaError::DuplicateId(0x4100),
+            DynamicSpec::from_ebml_schema(&schema(r#"<element name="A" path="\A" id="0x4100" type="utf-8"/><element name="B" path="\B" id="0x4100" type="utf-8"/>"#)).unwrap_err()
+        );
+        assert_eq!(
+            SchemaError::DuplicateName(String::from("A")),
+            DynamicSpec::from_ebml_schema(&schema(r#"<element name="A" path="\A" id="0x4100" type="utf-8"/><element name="A" path="\A" id="0x4101" type="utf-8"/>"#)).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(feature = "derive-spec")]
+pub mod long_ids {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::writer::SizeWidth;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum LongIds {
+        #[id(0x1a45dfa3)]
+        #[data_type(TagDataType::Master)]
+        Ebml,
+
+        #[id(0x42f2)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Ebml)]
+        EbmlMaxIdLength,
+
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x0812345678)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment)]
+        FiveByteId,
+
+        #[id(0x0123456789abcdef)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Segment)]
+        EightByteId,
+    }
+
+    fn segment() -> LongIds {
+        LongIds::Segment(Master::Full(vec![
+            LongIds::FiveByteId(5),
+            LongIds::EightByteId(vec![0x08]),
+            LongIds::RawTag(0x0f00000001, vec![0x01]),
+        ]))
+    }
+
+    fn write(tags: &[LongIds]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn read_write_long_ids() {
+        let data = write(&[segment()]);
+        assert_eq!(&[0x08, 0x12, 0x34, 0x56, 0x78, 0x81, 0x05], &data[5..12]);
+        assert_eq!(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x81, 0x08], &data[12..22]);
+
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(data), &[LongIds::Segment(Master::Start)]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        assert_eq!(vec![segment()], iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn strict_mode_checks_max_id_length() {
+        let segment = LongIds::Segment(Master::Full(vec![LongIds::FiveByteId(5)]));
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(write(std::slice::from_ref(&segment))), &[]);
+        iter.set_strict_mode(true);
+        assert!(iter.any(|tag| matches!(tag, Err(TagIteratorError::CorruptedFileData(CorruptedFileError::IdTooLong { tag_id: 0x0812345678, length: 5, max_length: 4, .. })))));
+
+        let header = LongIds::Ebml(Master::Full(vec![LongIds::EbmlMaxIdLength(5)]));
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(write(&[header.clone(), segment.clone()])), &[LongIds::Ebml(Master::Start), LongIds::Segment(Master::Start)]);
+        iter.set_strict_mode(true);
+        assert_eq!(vec![header, segment], iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn declared_max_lengths() {
+        // Limits declared by the header apply outside of strict mode too
+        let header = LongIds::Ebml(Master::Full(vec![LongIds::EbmlMaxIdLength(4)]));
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(write(&[header, segment()])), &[]);
+        assert!(iter.any(|tag| matches!(tag, Err(TagIteratorError::CorruptedFileData(CorruptedFileError::IdTooLong { tag_id: 0x0812345678, .. })))));
+
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(write(&[segment()])), &[LongIds::Segment(Master::Start)]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        iter.set_max_lengths(Some(8), Some(1));
+        assert_eq!(vec![segment()], iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_size_width(SizeWidth::Fixed(2)).unwrap();
+        writer.write(&segment()).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        iter.set_max_lengths(Some(8), Some(1));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::SizeTooLong { position: 0, tag_id: 0x18538067, length: 2, max_length: 1 })))));
+
+        // Resetting restores the configured limits, not the ones read from the previous document
+        let header = LongIds::Ebml(Master::Full(vec![LongIds::EbmlMaxIdLength(4)]));
+        let mut iter: TagIterator<_, LongIds> = TagIterator::new(Cursor::new(write(std::slice::from_ref(&header))), &[]);
+        iter.set_max_lengths(Some(8), None);
+        assert!(iter.by_ref().all(|t| t.is_ok()));
+        iter.reset(Cursor::new(data));
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        assert!(iter.all(|t| t.is_ok()));
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(feature = "matroska-spec")]
+pub mod matroska_spec {
+    use std::io::Cursor;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use ebml_iterable::error::{ReadErrorKind, TagIteratorError, TagWriterError};
+    use ebml_iterable::ids;
+    use ebml_iterable::iterator::Utf8Handling;
+    use ebml_iterable::specs::matroska::MatroskaSpec;
+    use ebml_iterable::specs::{EbmlSpecification, Master, TagDataType};
+    use ebml_iterable::writer::FloatWidth;
+    use ebml_iterable::{tools, TagIterator, TagWriter};
+
+    #[test]
+    pub fn covers_all_ids() {
+        for (id, name) in ids::EBML_NAMES.iter().chain(ids::MATROSKA_NAMES) {
+            assert!(MatroskaSpec::get_tag_data_type(*id).is_some(), "Missing element {} (0x{:x})", name, id);
+        }
+        assert_eq!(Some(TagDataType::Master), MatroskaSpec::get_tag_data_type(ids::CLUSTER));
+        assert_eq!(Some(TagDataType::Float), MatroskaSpec::get_tag_data_type(ids::DURATION));
+        assert_eq!(Some(TagDataType::Integer), MatroskaSpec::get_tag_data_type(ids::REFERENCE_BLOCK));
+        assert_eq!(Some(TagDataType::Binary), MatroskaSpec::get_tag_data_type(ids::SIMPLE_BLOCK));
+    }
+
+    #[test]
+    pub fn date_round_trip() {
+        let date = tools::system_time_to_date(UNIX_EPOCH + Duration::from_secs(1_700_000_000)).unwrap();
+        let info = MatroskaSpec::Info(Master::Full(vec![MatroskaSpec::DateUtc(date)]));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&MatroskaSpec::Segment(Master::Full(vec![info.clone()]))).unwrap();
+        let data = writer.into_inner().unwrap();
+        // Dates are always stored as 8 bytes
+        assert_eq!([0x44, 0x61, 0x88], data[10..13]);
+        assert_eq!(date.to_be_bytes(), data[13..]);
+
+        let tags: Vec<MatroskaSpec> = TagIterator::new(Cursor::new(data), &[MatroskaSpec::Info(Master::Start)]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![MatroskaSpec::Segment(Master::Start), info, MatroskaSpec::Segment(Master::End)], tags);
+        assert_eq!(Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), tools::date_to_system_time(date));
+    }
+
+    #[test]
+    pub fn float_widths() {
+        let info = MatroskaSpec::Info(Master::Full(vec![MatroskaSpec::Duration(48000.0), MatroskaSpec::Duration(0.1)]));
+        for (width, expected_len, expected_second) in [(FloatWidth::Double, 32, 0.1), (FloatWidth::Lossless, 28, 0.1), (FloatWidth::Single, 24, f64::from(0.1f32))] {
+            let mut writer = TagWriter::new(Vec::new());
+            writer.set_float_width(width);
+            writer.write(&MatroskaSpec::Segment(Master::Full(vec![info.clone()]))).unwrap();
+            let data = writer.into_inner().unwrap();
+            assert_eq!(expected_len, data.len(), "{:?}", width);
+
+            let tags: Vec<MatroskaSpec> = TagIterator::new(Cursor::new(data), &[MatroskaSpec::Info(Master::Start)]).map(|tag| tag.unwrap()).collect();
+            assert_eq!(MatroskaSpec::Info(Master::Full(vec![MatroskaSpec::Duration(48000.0), MatroskaSpec::Duration(expected_second)])), tags[1]);
+        }
+    }
+
+    #[test]
+    pub fn nested_chapters_and_tags() {
+        let atom = |uid: u64, children: Vec<MatroskaSpec>| {
+            let mut atom = vec![MatroskaSpec::ChapterUid(uid), MatroskaSpec::ChapterTimeStart(0)];
+            atom.extend(children);
+            atom.push(MatroskaSpec::ChapterDisplay(Master::Full(vec![MatroskaSpec::ChapString(format!("Chapter {}", uid))])));
+            MatroskaSpec::ChapterAtom(Master::Full(atom))
+        };
+        let simple_tag = |name: &str, children: Vec<MatroskaSpec>| {
+            let mut tag = vec![MatroskaSpec::TagName(String::from(name))];
+            tag.extend(children);
+            MatroskaSpec::SimpleTag(Master::Full(tag))
+        };
+        let segment = MatroskaSpec::Segment(Master::Full(vec![
+            MatroskaSpec::Chapters(Master::Full(vec![
+                MatroskaSpec::EditionEntry(Master::Full(vec![atom(1, vec![atom(2, vec![atom(3, vec![])])])])),
+            ])),
+            MatroskaSpec::Tags(Master::Full(vec![
+                MatroskaSpec::Tag(Master::Full(vec![simple_tag("ARTIST", vec![simple_tag("SORT_WITH", vec![])])])),
+            ])),
+        ]));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&segment).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let tags: Vec<MatroskaSpec> = TagIterator::new(Cursor::new(data), &[MatroskaSpec::Segment(Master::Start)]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![segment], tags);
+    }
+
+    #[test]
+    pub fn ascii_strings() {
+        assert!(MatroskaSpec::is_ascii_string(ids::DOC_TYPE));
+        assert!(MatroskaSpec::is_ascii_string(ids::CODEC_ID));
+        assert!(!MatroskaSpec::is_ascii_string(ids::TITLE));
+        assert_eq!(Some(TagDataType::Utf8), MatroskaSpec::get_tag_data_type(ids::CODEC_ID));
+
+        let segment = |child: MatroskaSpec| MatroskaSpec::Segment(Master::Full(vec![child]));
+        let track = |codec: &str| segment(MatroskaSpec::Tracks(Master::Full(vec![MatroskaSpec::TrackEntry(Master::Full(vec![MatroskaSpec::CodecId(String::from(codec))]))])));
+        let mut writer = TagWriter::new(Vec::new());
+        assert!(matches!(writer.write(&track("V_caf\u{e9}")), Err(TagWriterError::InvalidAsciiString { tag_id: ids::CODEC_ID })));
+        assert!(matches!(writer.finish(), Err(TagWriterError::UnclosedTags { .. })));
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&segment(MatroskaSpec::Info(Master::Full(vec![MatroskaSpec::Title(String::from("caf\u{e9}"))])))).unwrap();
+
+        let mut writer = TagWriter::with_spec::<MatroskaSpec>(Vec::new());
+        assert!(matches!(writer.write_utf8(ids::DOC_TYPE, "web\u{e9}"), Err(TagWriterError::InvalidAsciiString { tag_id: ids::DOC_TYPE })));
+        writer.write_utf8(ids::DOC_TYPE, "webm").unwrap();
+
+        // Valid utf8 that isn't printable ascii is rejected when reading too
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&MatroskaSpec::Segment(Master::Start)).unwrap();
+        writer.write(&MatroskaSpec::Tracks(Master::Start)).unwrap();
+        writer.write(&MatroskaSpec::TrackEntry(Master::Start)).unwrap();
+        writer.write_raw(ids::CODEC_ID, "V_caf\u{e9}".as_bytes()).unwrap();
+        writer.write(&MatroskaSpec::TrackEntry(Master::End)).unwrap();
+        writer.write(&MatroskaSpec::Tracks(Master::End)).unwrap();
+        writer.write(&MatroskaSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let read = |handling: Utf8Handling| {
+            let mut iter: TagIterator<_, MatroskaSpec> = TagIterator::new(Cursor::new(&data), &[]);
+            iter.set_utf8_handling(handling);
+            iter.nth(3).unwrap()
+        };
+        let err = read(Utf8Handling::Strict).unwrap_err();
+        assert!(matches!(err, TagIteratorError::CorruptedTagData { tag_id: ids::CODEC_ID, .. }));
+        assert_eq!(ReadErrorKind::InvalidUtf8, err.kind());
+        assert_eq!(MatroskaSpec::CodecId(String::from("V_caf\u{fffd}\u{fffd}")), read(Utf8Handling::Lossy).unwrap());
+        assert_eq!(MatroskaSpec::RawTag(ids::CODEC_ID, "V_caf\u{e9}".as_bytes().to_vec()), read(Utf8Handling::Raw).unwrap());
+    }
+}
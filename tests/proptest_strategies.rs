@@ -0,0 +1,49 @@
+#![cfg(feature = "proptest")]
+mod test_spec;
+
+pub mod proptest_strategies {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::strategies::{boundary_sizes, tag_trees, vint_values, MAX_VINT_VALUE};
+    use ebml_iterable::tools::{read_vint, Vint};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use proptest::prelude::*;
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    const TEST_SPEC_IDS: [u64; 12] = [0x81, 0x4101, 0x4102, 0x4103, 0x210301, 0x18538067, 0x83, 0x1f43b675, 0x97, 0x4100, 0xa1, 0xa3];
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn generated_trees_round_trip(tree in tag_trees::<TestSpec>(&TEST_SPEC_IDS, 3, 4)) {
+            let mut writer = TagWriter::new(Vec::new());
+            writer.write(&tree).unwrap();
+            let data = writer.into_inner().unwrap();
+
+            let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Root(Master::Start), TestSpec::Segment(Master::Start)]);
+            let read: Vec<TestSpec> = iter.map(|tag| tag.unwrap()).collect();
+            prop_assert_eq!(vec![tree], read);
+        }
+
+        #[test]
+        fn vint_values_round_trip(value in vint_values()) {
+            prop_assert!(value <= MAX_VINT_VALUE);
+            let encoded = value.as_vint().unwrap();
+            prop_assert_eq!(Some((value, encoded.len())), read_vint(&encoded).unwrap());
+        }
+
+        #[test]
+        fn boundary_sized_binary_round_trip(size in boundary_sizes()) {
+            let cluster = TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x5a; size])]));
+            let mut writer = TagWriter::new(Vec::new());
+            writer.write(&TestSpec::Segment(Master::Full(vec![cluster.clone()]))).unwrap();
+            let data = writer.into_inner().unwrap();
+
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Cluster(Master::Start)]);
+            prop_assert_eq!(Some(TestSpec::Segment(Master::Start)), iter.next().transpose().unwrap());
+            prop_assert_eq!(Some(cluster), iter.next().transpose().unwrap());
+        }
+    }
+}
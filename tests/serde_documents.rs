@@ -0,0 +1,138 @@
+#[cfg(feature = "serde")]
+mod test_spec;
+
+#[cfg(feature = "serde")]
+pub mod serde_documents {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::SerdeError;
+    use ebml_iterable::serde_ebml::{from_iterator, to_writer};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+    use serde::{Deserialize, Serialize};
+
+    use super::test_spec::TestSpec;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum TrackKind {
+        None,
+        Video,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Document {
+        #[serde(rename = "0x18538067")]
+        segment: Segment,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Segment {
+        #[serde(rename = "0x83")]
+        kind: TrackKind,
+        #[serde(rename = "0x1f43b675", default)]
+        clusters: Vec<Cluster>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Cluster {
+        #[serde(rename = "0x4100")]
+        count: u32,
+        #[serde(rename = "0x97")]
+        cue: Option<u64>,
+        #[serde(rename = "0xa1")]
+        block: Vec<u8>,
+        #[serde(rename = "0xa3", default)]
+        simple_blocks: Vec<Vec<u8>>,
+    }
+
+    fn document() -> Document {
+        Document {
+            segment: Segment {
+                kind: TrackKind::Video,
+                clusters: vec![
+                    Cluster { count: 1, cue: Some(7), block: vec![1, 2], simple_blocks: vec![vec![3], vec![4, 5]] },
+                    Cluster { count: 2, cue: None, block: vec![6], simple_blocks: vec![] },
+                ],
+            },
+        }
+    }
+
+    fn tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::CueRefCluster(7), TestSpec::Block(vec![1, 2]), TestSpec::SimpleBlock(vec![3]), TestSpec::SimpleBlock(vec![4, 5])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![6])])),
+            TestSpec::Segment(Master::End),
+        ]
+    }
+
+    #[test]
+    pub fn serialize_document() {
+        let mut writer = TagWriter::new(Vec::new());
+        to_writer::<TestSpec, _, _>(&mut writer, &document()).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut expected = TagWriter::new(Vec::new());
+        for tag in tags() {
+            expected.write(&tag).unwrap();
+        }
+        assert_eq!(expected.into_inner().unwrap(), data);
+    }
+
+    #[test]
+    pub fn deserialize_document() {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags() {
+            writer.write(&tag).unwrap();
+        }
+        // Elements without a matching field are ignored
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(1)]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        let read: Document = from_iterator(iter).unwrap();
+        assert_eq!(document(), read);
+
+        // Fields can also be named after elements in the specification
+        #[derive(Deserialize)]
+        #[allow(non_snake_case)]
+        struct Named {
+            Segment: NamedSegment,
+        }
+        #[derive(Deserialize)]
+        #[allow(non_snake_case)]
+        struct NamedSegment {
+            TrackType: u64,
+            Cluster: Vec<std::collections::BTreeMap<String, serde::de::IgnoredAny>>,
+        }
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        let named: Named = from_iterator(iter).unwrap();
+        assert_eq!(1, named.Segment.TrackType);
+        assert_eq!(vec!["0x4100", "0x97", "0xa1", "0xa3"], named.Segment.Cluster[0].keys().map(String::as_str).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn invalid_documents() {
+        #[derive(Serialize)]
+        struct Named {
+            #[serde(rename = "TrackType")]
+            track_type: u64,
+        }
+        let mut writer = TagWriter::new(Vec::new());
+        assert!(matches!(to_writer::<TestSpec, _, _>(&mut writer, &Named { track_type: 1 }), Err(SerdeError::InvalidFieldName(name)) if name == "TrackType"));
+
+        #[derive(Serialize)]
+        struct Mismatched {
+            #[serde(rename = "0x83")]
+            track_type: String,
+        }
+        assert!(matches!(to_writer::<TestSpec, _, _>(&mut writer, &Mismatched { track_type: "video".to_string() }), Err(SerdeError::UnsupportedValue { tag_id: 0x83, .. })));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![]))).unwrap();
+        let data = writer.into_inner().unwrap();
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        assert!(matches!(from_iterator::<_, _, Document>(iter), Err(SerdeError::Message(_))));
+    }
+}
@@ -0,0 +1,97 @@
+mod test_spec;
+
+pub mod sidecar_index {
+    use std::io::{Cursor, Seek, SeekFrom};
+
+    use ebml_iterable::ids;
+    use ebml_iterable::index::{ElementIndex, IndexBuilder};
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn write_document(unknown_size: bool) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        for (timestamp, count) in [(0u8, 1), (40, 2), (80, 3)] {
+            if unknown_size {
+                writer.write_unknown_size(&TestSpec::Cluster(Master::Start)).unwrap();
+            } else {
+                writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+            }
+            writer.write_raw(ids::TIMESTAMP, &[timestamp]).unwrap();
+            writer.write(&TestSpec::Count(count)).unwrap();
+            writer.write(&TestSpec::Block(vec![0; 0x20])).unwrap();
+            writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn build_index(data: &[u8]) -> ElementIndex {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let mut builder = IndexBuilder::new(&[ids::CLUSTER]);
+        while let Some(tag) = iter.next() {
+            builder.observe(&tag.unwrap(), iter.last_emitted_tag_span());
+        }
+        builder.finish()
+    }
+
+    #[test]
+    pub fn index_clusters() {
+        for unknown_size in [false, true] {
+            let data = write_document(unknown_size);
+            let index = build_index(&data);
+
+            let clusters = index.entries();
+            assert_eq!(3, clusters.len());
+            assert_eq!(vec![Some(0), Some(40), Some(80)], clusters.iter().map(|entry| entry.timestamp).collect::<Vec<_>>());
+            for (cluster, next) in clusters.iter().zip(clusters.iter().skip(1)) {
+                assert_eq!(cluster.offset + cluster.size, next.offset);
+            }
+            assert_eq!(data.len() as u64, clusters[2].offset + clusters[2].size);
+
+            assert_eq!(Some(clusters[0]), index.cluster_at(0).copied());
+            assert_eq!(Some(clusters[0]), index.cluster_at(39).copied());
+            assert_eq!(Some(clusters[1]), index.cluster_at(40).copied());
+            assert_eq!(Some(clusters[2]), index.cluster_at(1000).copied());
+        }
+    }
+
+    #[test]
+    pub fn seek_using_stored_index() {
+        let data = write_document(false);
+        let mut stored = Vec::new();
+        build_index(&data).write_to(&mut stored).unwrap();
+
+        let index = ElementIndex::read_from(stored.as_slice()).unwrap();
+        let cluster = index.cluster_at(50).unwrap();
+        let mut source = Cursor::new(data);
+        source.seek(SeekFrom::Start(cluster.offset)).unwrap();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_parents(source, &[TestSpec::Segment(Master::Start)], &[TestSpec::Cluster(Master::Start)]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let tag = iter.next().unwrap().unwrap();
+        assert!(matches!(tag, TestSpec::Cluster(Master::Full(ref children)) if children.contains(&TestSpec::Count(2))));
+    }
+
+    #[test]
+    pub fn scan_document() {
+        let data = write_document(false);
+        let index = ElementIndex::scan::<TestSpec, _>(Cursor::new(&data), &[ids::SEGMENT]);
+        assert!(index.is_err());
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1), TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))]))).unwrap();
+        let data = writer.into_inner().unwrap();
+        let index = ElementIndex::scan::<TestSpec, _>(Cursor::new(&data), &[ids::SEGMENT, ids::CLUSTER]).unwrap();
+        assert_eq!(vec![ids::SEGMENT, ids::CLUSTER], index.entries().iter().map(|entry| entry.tag_id).collect::<Vec<_>>());
+        assert_eq!(0, index.entries()[0].offset);
+        assert_eq!(data.len() as u64, index.entries()[0].size);
+        assert_eq!(data.len() as u64, index.entries()[1].offset + index.entries()[1].size);
+        assert_eq!(None, index.entries()[1].timestamp);
+        assert_eq!(1, index.find(ids::CLUSTER).count());
+    }
+}
@@ -1,10 +1,15 @@
 mod test_spec;
 
 pub mod spec_write_read {
-    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::error::{CorruptedFileError, FeedError, TagIteratorError, TagWriterError};
     use ebml_iterable::specs::{Master, EbmlTag};
-    use ebml_iterable::{TagIterator, TagWriter};
-    use std::io::Cursor;
+    use ebml_iterable::iterator::{AllowableErrors, BorrowedTag, SkipReason, SkippedTag, TagSliceIterator, Utf8Handling};
+    use ebml_iterable::writer::{FlushPolicy, SizeWidth, SliceWriter, WriteOptions};
+    use ebml_iterable::tree::TreeValue;
+    use ebml_iterable::{copy_ebml, read_tree, Decision, EbmlPushParser, EbmlQuery, TagIterator, TagWriter};
+    use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+    use ebml_iterable_specification::TagDataType;
 
     use super::test_spec::TestSpec;
 
@@ -108,6 +113,203 @@ pub mod spec_write_read {
         assert_eq!(tags.len(), 6, "Reading every tag that was written");
     }
 
+    #[test]
+    pub fn write_unknown_size_start() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_unknown_size_start(0x18538067).unwrap();
+        writer.write_unknown_size_start(0x1f43b675).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        let streamed = writer.get_ref().len();
+        writer.write(&TestSpec::Block(vec![0; 8])).unwrap();
+        assert!(writer.get_ref().len() > streamed, "Children should be written without waiting for the end of the cluster");
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut expected = TagWriter::new(Vec::new());
+        expected.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        expected.write_unknown_size(&TestSpec::Cluster(Master::Start)).unwrap();
+        expected.write(&TestSpec::Count(1)).unwrap();
+        expected.write(&TestSpec::Block(vec![0; 8])).unwrap();
+        expected.write(&TestSpec::Cluster(Master::End)).unwrap();
+        expected.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert_eq!(expected.into_inner().unwrap(), data);
+    }
+
+    fn unknown_size_clusters() -> Vec<u8> {
+        vec![
+            0x18, 0x53, 0x80, 0x67, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x01,
+                // A sibling cluster ends the previous one
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x02,
+                    // Global elements don't end an unknown-size parent
+                    0xec, 0x80,
+                    0xa1, 0x82, 0x00, 0x00,
+                // An element from the parent level ends the cluster
+                0x83, 0x81, 0x01,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x03,
+            // The end of the data ends everything
+        ]
+    }
+
+    #[test]
+    pub fn read_unknown_size_clusters() {
+        let tags: Vec<TestSpec> = TagIterator::new(Cursor::new(unknown_size_clusters()), &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Void(vec![]),
+            TestSpec::Block(vec![0, 0]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(3),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn buffer_unknown_size_clusters() {
+        let tags: Vec<TestSpec> = TagIterator::new(Cursor::new(unknown_size_clusters()), &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Void(vec![]), TestSpec::Block(vec![0, 0])])),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)])),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn streaming_write() {
+        let tags = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Block(vec![0; 8]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ];
+
+        let mut writer = TagWriter::new_streaming(Vec::new());
+        let mut unknown_size = TagWriter::new(Vec::new());
+        for tag in tags.iter() {
+            let before = writer.get_ref().len();
+            writer.write(tag).unwrap();
+            if !matches!(tag, TestSpec::Segment(Master::End) | TestSpec::Cluster(Master::End)) {
+                assert!(writer.get_ref().len() > before, "{:?} should have been written immediately", tag);
+            }
+            match tag {
+                TestSpec::Segment(Master::Start) | TestSpec::Cluster(Master::Start) => unknown_size.write_unknown_size(tag).unwrap(),
+                _ => unknown_size.write(tag).unwrap(),
+            }
+        }
+        let data = writer.into_inner().unwrap();
+        assert_eq!(unknown_size.into_inner().unwrap(), data);
+
+        let read_tags: Vec<TestSpec> = TagIterator::new(Cursor::new(data), &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(tags, read_tags);
+    }
+
+    #[test]
+    pub fn backpatch_sizes() {
+        for width in [SizeWidth::Minimal, SizeWidth::Fixed(2)] {
+            let mut writer = TagWriter::new_streaming(Cursor::new(Vec::new()));
+            writer.set_size_width(width).unwrap();
+            writer.set_backpatch_sizes(true);
+            writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+            writer.write(&TestSpec::TrackType(1)).unwrap();
+            writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+            writer.write(&TestSpec::Count(1)).unwrap();
+            writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+            // The finished cluster can be patched while the segment is still open
+            writer.patch_sizes().unwrap();
+            writer.write(&TestSpec::Segment(Master::End)).unwrap();
+            writer.patch_sizes().unwrap();
+            let data = writer.into_inner().unwrap().into_inner();
+
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+            let mut sizes = Vec::new();
+            while let Some(tag) = iter.next() {
+                if matches!(tag.unwrap().as_master(), Some(Master::Start)) {
+                    sizes.push(iter.last_emitted_tag_span().data_len);
+                }
+            }
+            let (segment_header, count_len) = if width == SizeWidth::Minimal { (4 + 8, 4) } else { (4 + 2, 5) };
+            assert_eq!(vec![Some(data.len() - segment_header), Some(count_len)], sizes, "{width:?}");
+        }
+    }
+
+    #[test]
+    pub fn seekable_write() {
+        let tags = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Block(vec![0; 8]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ];
+
+        let mut writer = TagWriter::new_seekable(Cursor::new(Vec::new()));
+        for tag in tags.iter() {
+            let before = writer.get_ref().get_ref().len();
+            writer.write(tag).unwrap();
+            if !matches!(tag.as_master(), Some(Master::End)) {
+                assert!(writer.get_ref().get_ref().len() > before, "{:?} should have been written immediately", tag);
+            }
+        }
+        let data = writer.into_inner().unwrap().into_inner();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        let mut sizes = Vec::new();
+        let mut read_tags = Vec::new();
+        while let Some(tag) = iter.next() {
+            let tag = tag.unwrap();
+            if matches!(tag.as_master(), Some(Master::Start)) {
+                sizes.push(iter.last_emitted_tag_span().data_len);
+            }
+            read_tags.push(tag);
+        }
+        assert_eq!(tags, read_tags);
+        assert_eq!(vec![Some(data.len() - 12), Some(4 + 10), Some(4)], sizes);
+    }
+
+    #[test]
+    pub fn write_with_spec() {
+        let mut writer = TagWriter::with_spec::<TestSpec>(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+
+        // TrackType is an unsigned int, but EmptySpec treats every tag as binary
+        assert!(matches!(writer.write(&EmptySpec::with_data(0x83, b"one")), Err(TagWriterError::TypeMismatch { tag_id: 0x83, expected: Some(TagDataType::UnsignedInt), .. })));
+        assert!(matches!(writer.write_raw(0x83, &[0x01]), Err(TagWriterError::TypeMismatch { tag_id: 0x83, expected: Some(TagDataType::UnsignedInt), found: None })));
+        assert!(matches!(writer.write_unknown_size_start(0x83), Err(TagWriterError::TypeMismatch { tag_id: 0x83, expected: Some(TagDataType::UnsignedInt), found: Some(TagDataType::Master) })));
+
+        writer.write_unknown_size_start(0x1f43b675).unwrap();
+        writer.write_raw(0xa1, &[0x01]).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+
+        // Without a specification, anything goes
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_raw(0x83, b"one").unwrap();
+    }
+
     #[test]
     pub fn buffer_unknown_size() {
         let mut dest = Cursor::new(Vec::new());
@@ -155,7 +357,7 @@ pub mod spec_write_read {
         assert!(matches!(iter.next(), Some(Ok(TestSpec::Parent(Master::End)))));
         assert!(matches!(iter.next(), Some(Ok(TestSpec::Int(2)))));
         assert!(matches!(iter.next(), Some(Ok(TestSpec::Root(Master::End)))));
-        assert!(matches!(iter.next(), None));
+        assert!(iter.next().is_none());
     }
 
     #[test]
@@ -192,10 +394,7 @@ pub mod spec_write_read {
                 assert_eq!(tag_id, Some(TestSpec::Block(vec![]).get_id()));
                 assert_eq!(tag_size, Some(9));
             },
-            other => {
-                println!("{other:?}");
-                assert!(false);
-            }
+            other => panic!("Unexpected error: {:?}", other),
         }
     }
 
@@ -261,4 +460,1375 @@ pub mod spec_write_read {
             assert_eq!(tags[i], read_tags[i]);
         }       
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn write_deeply_nested_tags() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Root(Master::Start)).unwrap();
+        writer.write(&TestSpec::Parent(Master::Start)).unwrap();
+        writer.write(&TestSpec::Child(1)).unwrap();
+        writer.write(&TestSpec::Parent(Master::End)).unwrap();
+        writer.write(&TestSpec::Int(2)).unwrap();
+        writer.write(&TestSpec::Parent(Master::Full(vec![TestSpec::Child(3), TestSpec::Child(4)]))).unwrap();
+        writer.write(&TestSpec::Root(Master::End)).unwrap();
+
+        assert_eq!(&[
+            0x81, 0x99,
+                0x41, 0x03, 0x85,
+                    0x21, 0x03, 0x01, 0x81, 0x01,
+                0x41, 0x01, 0x81, 0x02,
+                0x41, 0x03, 0x8a,
+                    0x21, 0x03, 0x01, 0x81, 0x03,
+                    0x21, 0x03, 0x01, 0x81, 0x04,
+        ], dest.get_ref().as_slice());
+    }
+
+    #[test]
+    pub fn write_declared_size_streams_children() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.start_tag_with_size(&TestSpec::Segment(Master::Start), 12).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        writer.write(&TestSpec::TrackType(2)).unwrap();
+        assert_eq!(17, writer.get_ref().get_ref().len(), "Children should not be buffered");
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+
+        dest.set_position(0);
+        let read_tags: Vec<TestSpec> = TagIterator::new(dest, &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::TrackType(2),
+            TestSpec::Segment(Master::End),
+        ], read_tags);
+    }
+
+    #[test]
+    pub fn write_declared_size_inside_buffered_tag() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.start_tag_with_size(&TestSpec::Cluster(Master::Start), 4).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+
+        assert_eq!(&[0x18, 0x53, 0x80, 0x67, 0x89, 0x1f, 0x43, 0xb6, 0x75, 0x84, 0x41, 0x00, 0x81, 0x01], dest.get_ref().as_slice());
+    }
+
+    #[test]
+    pub fn write_declared_size_mismatch() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.start_tag_with_size(&TestSpec::Segment(Master::Start), 2).unwrap();
+        writer.write(&TestSpec::TrackType(2)).unwrap();
+        assert!(matches!(writer.write(&TestSpec::Segment(Master::End)), Err(TagWriterError::TagSizeError(_))));
+        assert!(matches!(writer.start_tag_with_size(&TestSpec::TrackType(2), 1), Err(TagWriterError::TagSizeError(_))));
+    }
+
+    fn borrowed_sample() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in [
+            TestSpec::Root(Master::Start),
+            TestSpec::String("first".to_string()),
+            TestSpec::Parent(Master::Full(vec![TestSpec::Child(1), TestSpec::Child(2)])),
+            TestSpec::String("second".to_string()),
+            TestSpec::Root(Master::End),
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Block(vec![1, 2, 3]),
+            TestSpec::Count(7),
+            TestSpec::SimpleBlock(vec![]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ].iter() {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn read_borrowed_tags() {
+        let data = borrowed_sample();
+        let expected: Vec<(TestSpec, usize)> = {
+            let mut iter = TagIterator::new(Cursor::new(&data), &[TestSpec::Parent(Master::Start)]);
+            let mut tags = Vec::new();
+            while let Some(tag) = iter.next() {
+                tags.push((tag.unwrap(), iter.last_emitted_tag_offset()));
+            }
+            tags
+        };
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(&data), &[TestSpec::Parent(Master::Start)], 4);
+        let mut read_tags = Vec::new();
+        let mut strings = Vec::new();
+        while let Some(tag) = iter.next_borrowed() {
+            let tag = tag.unwrap();
+            match &tag {
+                BorrowedTag::Utf8 { value, .. } => strings.push(value.to_string()),
+                BorrowedTag::Tag(TestSpec::Parent(Master::Full(children))) => assert_eq!(2, children.len()),
+                BorrowedTag::Tag(_) | BorrowedTag::Binary { .. } => {},
+                BorrowedTag::Raw { .. } | BorrowedTag::Skipped(_) => panic!("Unexpected tag {:?}", tag),
+            }
+            read_tags.push((tag.into_owned(), iter.last_emitted_tag_offset()));
+        }
+        assert_eq!(vec!["first", "second"], strings);
+        assert_eq!(expected, read_tags);
+    }
+
+    #[test]
+    pub fn mix_borrowed_and_owned_reads() {
+        let data = borrowed_sample();
+        let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[]).map(|t| t.unwrap()).collect();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        let mut read_tags = Vec::new();
+        for i in 0.. {
+            let tag = if i % 2 == 0 {
+                iter.next_borrowed().map(|t| t.unwrap().into_owned())
+            } else {
+                iter.next().map(|t| t.unwrap())
+            };
+            match tag {
+                Some(tag) => read_tags.push(tag),
+                None => break,
+            }
+        }
+        assert_eq!(expected, read_tags);
+    }
+
+    #[test]
+    pub fn read_slice_without_copying() {
+        let data = borrowed_sample();
+        let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Parent(Master::Start)]).map(|t| t.unwrap()).collect();
+
+        // Tags can be kept while reading on, since they borrow from the source
+        let mut iter: TagSliceIterator<TestSpec> = TagSliceIterator::new(&data, &[TestSpec::Parent(Master::Start)]);
+        iter.get_mut().set_max_depth(8);
+        let tags: Vec<BorrowedTag<'_, TestSpec>> = iter.map(|t| t.unwrap()).collect();
+        let source = data.as_ptr_range();
+        for tag in tags.iter() {
+            match tag {
+                BorrowedTag::Utf8 { value, .. } => assert!(source.contains(&value.as_ptr())),
+                BorrowedTag::Binary { value, .. } if !value.is_empty() => assert!(source.contains(&value.as_ptr())),
+                _ => {},
+            }
+        }
+        assert!(matches!(tags[1], BorrowedTag::Utf8 { id: 0x4102, value: "first" }));
+        assert_eq!(expected, tags.into_iter().map(BorrowedTag::into_owned).collect::<Vec<_>>());
+
+        let data = vec![0x81, 0x85, 0x41, 0x02, 0x82, 0xc3, 0x28];
+        let mut iter: TagSliceIterator<TestSpec> = TagSliceIterator::new(&data, &[]);
+        assert!(matches!(iter.next(), Some(Ok(BorrowedTag::Tag(TestSpec::Root(Master::Start))))));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedTagData { tag_id: 0x4102, .. }))));
+    }
+
+    #[test]
+    pub fn push_parser() {
+        let mut known_size = borrowed_sample();
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(4)]))]))).unwrap();
+        known_size.extend(writer.into_inner().unwrap());
+
+        for data in [known_size, unknown_size_clusters()] {
+            let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[]).map(|t| t.unwrap()).collect();
+            for chunk_size in [1, 3, data.len()] {
+                let mut parser: EbmlPushParser<TestSpec> = EbmlPushParser::new();
+                let mut tags = Vec::new();
+                for chunk in data.chunks(chunk_size) {
+                    parser.feed(chunk).unwrap();
+                    tags.extend(parser.by_ref().map(|t| t.unwrap()));
+                }
+                parser.finish();
+                tags.extend(parser.by_ref().map(|t| t.unwrap()));
+                assert_eq!(expected, tags);
+                assert_eq!(data.len(), parser.position());
+            }
+        }
+
+        // Data that stops part way through a tag is reported once the input is finished
+        let mut parser: EbmlPushParser<TestSpec> = EbmlPushParser::new();
+        parser.feed(&[0x18, 0x53, 0x80, 0x67, 0x89, 0x83, 0x81, 0x01, 0xa1, 0x84, 0x00]).unwrap();
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::TrackType(1)))));
+        assert!(parser.next().is_none());
+        assert_eq!(3, parser.buffered_len());
+        parser.finish();
+        assert!(matches!(parser.next(), Some(Err(TagIteratorError::UnexpectedEOF { tag_start: 8, tag_id: Some(0xa1), tag_size: Some(4), .. }))));
+        assert!(parser.next().is_none());
+
+        let mut parser: EbmlPushParser<TestSpec> = EbmlPushParser::new();
+        parser.feed(&[0x18, 0x53, 0x80, 0x67, 0x88, 0x83, 0x81, 0x01]).unwrap();
+        parser.finish();
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::TrackType(1)))));
+        assert!(matches!(parser.next(), Some(Err(TagIteratorError::UnexpectedEOF { tag_start: 0, tag_id: Some(0x18538067), tag_size: Some(8), .. }))));
+
+        // Feeding after the input is finished is an error rather than a panic
+        assert_eq!(Err(FeedError::Finished), parser.feed(&[0x01]));
+        assert_eq!(0, parser.buffered_len());
+    }
+
+    #[test]
+    pub fn push_parser_max_tag_size() {
+        let mut parser: EbmlPushParser<TestSpec> = EbmlPushParser::new();
+        parser.set_max_tag_size(Some(3));
+        parser.feed(&[0x18, 0x53, 0x80, 0x67, 0x89, 0x83, 0x81, 0x01, 0xa1, 0x83]).unwrap();
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::TrackType(1)))));
+        // A tag at the limit waits for its data as usual
+        assert!(parser.next().is_none());
+
+        // A larger tag is rejected as soon as its size is read, without waiting for its data
+        let mut parser: EbmlPushParser<TestSpec> = EbmlPushParser::new();
+        parser.set_max_tag_size(Some(3));
+        parser.feed(&[0x18, 0x53, 0x80, 0x67, 0x89, 0x83, 0x81, 0x01, 0xa1, 0x84]).unwrap();
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(parser.next(), Some(Ok(TestSpec::TrackType(1)))));
+        assert!(matches!(parser.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { position: 8, tag_id: 0xa1, size: 4, max_size: 3 })))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    pub fn read_borrowed_invalid_utf8() {
+        let data = vec![0x81, 0x85, 0x41, 0x02, 0x82, 0xc3, 0x28];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert!(matches!(iter.next_borrowed(), Some(Ok(BorrowedTag::Tag(TestSpec::Root(Master::Start))))));
+        assert!(matches!(iter.next_borrowed(), Some(Err(TagIteratorError::CorruptedTagData { tag_id: 0x4102, .. }))));
+    }
+
+    #[test]
+    pub fn lenient_utf8() {
+        let data = vec![0x81, 0x85, 0x41, 0x02, 0x82, 0xc3, 0x28];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Root(Master::Start)]);
+        iter.set_utf8_handling(Utf8Handling::Lossy);
+        assert_eq!(Some(TestSpec::Root(Master::Full(vec![TestSpec::String("\u{fffd}(".to_string())]))), iter.next().map(|t| t.unwrap()));
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.set_utf8_handling(Utf8Handling::Raw);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Root(Master::Start), TestSpec::RawTag(0x4102, vec![0xc3, 0x28]), TestSpec::Root(Master::End)], tags);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.set_utf8_handling(Utf8Handling::Lossy);
+        assert!(matches!(iter.next_borrowed(), Some(Ok(BorrowedTag::Tag(TestSpec::Root(Master::Start))))));
+        assert!(matches!(iter.next_borrowed(), Some(Ok(BorrowedTag::Tag(TestSpec::String(value)))) if value == "\u{fffd}("));
+
+        let mut iter: TagSliceIterator<TestSpec> = TagSliceIterator::new(&data, &[]);
+        iter.get_mut().set_utf8_handling(Utf8Handling::Raw);
+        assert!(matches!(iter.nth(1), Some(Ok(BorrowedTag::Raw { id: 0x4102, data: [0xc3, 0x28] }))));
+    }
+
+    #[derive(Default)]
+    struct FlushCounter {
+        data: Vec<u8>,
+        flushes: usize,
+    }
+
+    impl Write for FlushCounter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn write_full_tree_matches_streamed_tags() {
+        let block = vec![7u8; 5000];
+        let tree = TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Count(300),
+                TestSpec::Block(block.clone()),
+                TestSpec::SimpleBlock(vec![1, 2, 3]),
+            ])),
+            TestSpec::Cluster(Master::Full(vec![])),
+            TestSpec::TrackType(2),
+        ]));
+
+        let mut writer = TagWriter::new(FlushCounter::default());
+        writer.write(&tree).unwrap();
+        let tree_dest = writer.into_inner().unwrap();
+        // Once for the large block written straight from the tag, once after the tree, and once by `into_inner`
+        assert_eq!(3, tree_dest.flushes);
+
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in [
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(300),
+            TestSpec::Block(block),
+            TestSpec::SimpleBlock(vec![1, 2, 3]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Cluster(Master::End),
+            TestSpec::TrackType(2),
+            TestSpec::Segment(Master::End),
+        ].iter() {
+            writer.write(tag).unwrap();
+        }
+        assert_eq!(writer.into_inner().unwrap(), tree_dest.data);
+
+        let read_tags: Vec<TestSpec> = TagIterator::new(Cursor::new(tree_dest.data), &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![tree], read_tags);
+    }
+
+    #[test]
+    pub fn write_with_flush_policies() {
+        let segment = TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(2)]));
+        let segment_len = 8;
+        for (policy, expected_flushes, expected_len) in [
+            (FlushPolicy::EveryTopLevel, 10, 10 * segment_len),
+            (FlushPolicy::EveryNBytes(3 * segment_len), 3, 9 * segment_len),
+            (FlushPolicy::Never, 0, 10 * segment_len),
+            (FlushPolicy::Manual, 0, 0),
+        ] {
+            let mut writer = TagWriter::new(FlushCounter::default());
+            writer.set_flush_policy(policy);
+            for _ in 0..10 {
+                writer.write(&segment).unwrap();
+            }
+            assert_eq!(expected_flushes, writer.get_ref().flushes, "{policy:?}");
+            assert_eq!(expected_len, writer.get_ref().data.len(), "{policy:?}");
+
+            writer.flush().unwrap();
+            assert_eq!(expected_flushes + 1, writer.get_ref().flushes, "{policy:?}");
+            assert_eq!(10 * segment_len, writer.get_ref().data.len(), "{policy:?}");
+        }
+    }
+
+    #[test]
+    pub fn reset_with_open_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write_unknown_size(&TestSpec::Cluster(Master::Start)).unwrap();
+        assert!(matches!(writer.reset(Vec::new()), Err(TagWriterError::UnclosedTags { tag_ids }) if tag_ids == vec![0x18538067, 0x1f43b675]));
+
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let first = writer.reset(Vec::new()).unwrap();
+        let read_tags: Vec<TestSpec> = TagIterator::new(Cursor::new(first), &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(4, read_tags.len());
+    }
+
+    #[test]
+    pub fn finish_writer() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        assert!(matches!(writer.finish(), Err(TagWriterError::UnclosedTags { tag_ids }) if tag_ids == vec![0x18538067]));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert_eq!(vec![0x18, 0x53, 0x80, 0x67, 0x80], writer.finish().unwrap());
+    }
+
+    #[test]
+    pub fn close_nested_on_end() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        assert!(matches!(writer.write(&TestSpec::Segment(Master::End)), Err(TagWriterError::UnexpectedClosingTag { tag_id: 0x18538067, expected_id: Some(0x1f43b675) })));
+        assert!(matches!(writer.finish(), Err(TagWriterError::UnclosedTags { .. })));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_close_nested_on_end(true);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        assert!(matches!(writer.write(&TestSpec::Root(Master::End)), Err(TagWriterError::UnexpectedClosingTag { tag_id: 0x81, expected_id: Some(0x1f43b675) })));
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.finish().unwrap();
+
+        let read_tags: Vec<TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))]))], read_tags);
+    }
+
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.bytes_read += read;
+            Ok(read)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn write_clusters() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        for i in 0..3 {
+            writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i), TestSpec::Block(vec![0; 0x20000])]))).unwrap();
+            writer.write(&TestSpec::TrackType(i)).unwrap();
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn skip_tags() {
+        let expected = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0),
+            TestSpec::TrackType(1),
+            TestSpec::TrackType(2),
+            TestSpec::Segment(Master::End),
+        ];
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(write_clusters()), &[], 0x100);
+        iter.skip_tags(&[TestSpec::Cluster(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(expected, tags);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(CountingReader { inner: Cursor::new(write_clusters()), bytes_read: 0 }, &[TestSpec::Segment(Master::Start)], 0x100);
+        iter.skip_tags(&[TestSpec::Cluster(Master::Start)]);
+        iter.seek_when_skipping();
+        let tags: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Full(expected[1..4].to_vec()))], tags);
+        assert!(iter.get_ref().bytes_read < 0x1000, "Skipped data should not be read");
+    }
+
+    #[test]
+    pub fn skip_tags_eof() {
+        let mut data = write_clusters();
+        data.truncate(0x100);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        iter.skip_tags(&[TestSpec::Cluster(Master::Start)]);
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::UnexpectedEOF { tag_start: 7, .. }))));
+    }
+
+    #[test]
+    pub fn skipped_tag_callback() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0), TestSpec::Block(vec![0; 0x2000])])),
+            TestSpec::RawTag(0x4200, vec![1, 2]),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 0x10])])),
+            TestSpec::TrackType(1),
+        ]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let skipped = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = skipped.clone();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Segment(Master::Start)]);
+        iter.skip_unknown_tags(true);
+        iter.skip_oversized_tags(Some(0x1000));
+        iter.on_skipped_tag(move |tag| log.lock().unwrap().push(*tag));
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0)])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 0x10])])),
+            TestSpec::TrackType(1),
+        ]))], tags);
+
+        let skipped = skipped.lock().unwrap();
+        assert_eq!(vec![
+            SkippedTag { tag_id: 0xa1, position: 16, size: 0x2000, reason: SkipReason::Oversized },
+            SkippedTag { tag_id: 0x4200, position: 16 + 3 + 0x2000, size: 2, reason: SkipReason::Unknown },
+        ], *skipped);
+    }
+
+    #[test]
+    pub fn emit_skipped_tags() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(CountingReader { inner: Cursor::new(write_clusters()), bytes_read: 0 }, &[], 0x100);
+        iter.skip_tags(&[TestSpec::Block(Vec::new())]);
+        iter.seek_when_skipping();
+        iter.set_emit_skipped_tags(true);
+
+        let mut ids = Vec::new();
+        let mut skipped = Vec::new();
+        while let Some(tag) = iter.next_borrowed() {
+            let tag = tag.unwrap();
+            ids.push(tag.get_id());
+            if let BorrowedTag::Skipped(tag) = tag {
+                assert_eq!(tag.position, iter.last_emitted_tag_span().offset);
+                assert_eq!(Some(0x20000), iter.last_emitted_tag_span().data_len);
+                skipped.push(tag);
+            }
+        }
+        assert_eq!(vec![0x18538067, 0x1f43b675, 0x4100, 0xa1, 0x1f43b675, 0x83, 0x1f43b675, 0x4100, 0xa1, 0x1f43b675, 0x83, 0x1f43b675, 0x4100, 0xa1, 0x1f43b675, 0x83, 0x18538067], ids);
+        assert_eq!(3, skipped.len());
+        assert!(skipped.iter().all(|tag| tag.tag_id == 0xa1 && tag.size == 0x20000 && tag.reason == SkipReason::Filtered));
+        assert!(iter.get_ref().bytes_read < 0x1000, "Skipped data should not be read");
+
+        // Owned iteration does not report skipped tags
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(write_clusters()), &[]);
+        iter.skip_tags(&[TestSpec::Block(Vec::new())]);
+        iter.set_emit_skipped_tags(true);
+        assert!(iter.all(|tag| !matches!(tag, Ok(TestSpec::Block(_)) | Ok(TestSpec::RawTag(..)))));
+    }
+
+    #[test]
+    pub fn remux_preserves_unknown_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::RawTag(0x4200, vec![1, 2]),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0), TestSpec::RawTag(0x5300, vec![0x81, 0x00]), TestSpec::Block(vec![3])])),
+            TestSpec::TrackType(1),
+        ]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        for tags_to_buffer in [vec![], vec![TestSpec::Cluster(Master::Start)]] {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &tags_to_buffer);
+            iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+            let mut writer = TagWriter::new(Vec::new());
+            for tag in iter {
+                let tag = tag.unwrap();
+                writer.write(&tag).unwrap();
+            }
+            assert_eq!(data, writer.into_inner().unwrap());
+        }
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let raw: Vec<TestSpec> = iter.map(|t| t.unwrap()).filter(|t| matches!(t, TestSpec::RawTag(..))).collect();
+        assert_eq!(vec![TestSpec::RawTag(0x4200, vec![1, 2]), TestSpec::RawTag(0x5300, vec![0x81, 0x00])], raw);
+    }
+
+    #[test]
+    pub fn reset_iterator_keeps_config() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(CountingReader { inner: Cursor::new(write_clusters()), bytes_read: 0 }, &[], 0x100);
+        iter.skip_tags(&[TestSpec::Cluster(Master::Start)]);
+        iter.seek_when_skipping();
+        let skipped = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let count = skipped.clone();
+        iter.on_skipped_tag(move |tag| {
+            assert_eq!(SkipReason::Filtered, tag.reason);
+            count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::TrackType(0)))));
+
+        let previous = iter.reset(CountingReader { inner: Cursor::new(write_clusters()), bytes_read: 0 });
+        assert!(previous.bytes_read < 0x1000);
+        let tags: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0),
+            TestSpec::TrackType(1),
+            TestSpec::TrackType(2),
+            TestSpec::Segment(Master::End),
+        ], tags);
+        assert!(iter.get_ref().bytes_read < 0x1000, "Skipped data should not be read");
+        assert_eq!(0, iter.last_emitted_tag_offset());
+        assert_eq!(4, skipped.load(std::sync::atomic::Ordering::Relaxed));
+    }
+
+    #[derive(Clone)]
+    struct SharedCursor(std::rc::Rc<std::cell::RefCell<Cursor<Vec<u8>>>>);
+
+    impl Read for SharedCursor {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().read(buf)
+        }
+    }
+
+    impl Seek for SharedCursor {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.0.borrow_mut().seek(pos)
+        }
+    }
+
+    #[test]
+    pub fn fork_iterator() {
+        let expected: Vec<TestSpec> = TagIterator::<_, TestSpec>::new(Cursor::new(write_clusters()), &[]).map(|t| t.unwrap()).collect();
+
+        // Clones share one position, like `File::try_clone()`
+        let source = SharedCursor(std::rc::Rc::new(std::cell::RefCell::new(Cursor::new(write_clusters()))));
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(source, &[], 0x100);
+        iter.skip_tags(&[TestSpec::Block(vec![])]);
+        iter.seek_when_skipping();
+        assert_eq!(expected[0..3], iter.by_ref().take(3).map(|t| t.unwrap()).collect::<Vec<_>>()[..]);
+
+        let mut fork = iter.fork().unwrap();
+        let mut second_fork = fork.fork().unwrap();
+        let expected: Vec<TestSpec> = expected[3..].iter().filter(|t| !matches!(t, TestSpec::Block(_))).cloned().collect();
+        let mut forked_tags = Vec::new();
+        let mut tags = Vec::new();
+        loop {
+            match (fork.next(), iter.next()) {
+                (None, None) => break,
+                (forked, tag) => {
+                    forked_tags.extend(forked.map(|t| t.unwrap()));
+                    tags.extend(tag.map(|t| t.unwrap()));
+                }
+            }
+        }
+        assert_eq!(expected, tags);
+        assert_eq!(expected, forked_tags);
+        assert_eq!(expected, second_fork.by_ref().map(|t| t.unwrap()).collect::<Vec<_>>());
+        assert_eq!(iter.last_emitted_tag_span(), second_fork.last_emitted_tag_span());
+    }
+
+    #[test]
+    pub fn read_window() {
+        let mut data = vec![0xff; 10];
+        data.extend(write_clusters());
+        let window_len = data.len() as u64 - 10;
+        data.extend([0x1a, 0x45, 0xdf, 0xa3, 0xff]);
+
+        let expected: Vec<TestSpec> = TagIterator::<_, TestSpec>::new(Cursor::new(write_clusters()), &[]).map(|t| t.unwrap()).collect();
+        let iter: TagIterator<_, TestSpec> = TagIterator::with_window(Cursor::new(data.clone()), 10, window_len, &[]).unwrap();
+        assert_eq!(expected, iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+
+        // The window ends partway through the last cluster
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_window(Cursor::new(data.clone()), 10, window_len - 0x100, &[]).unwrap();
+        let last = iter.by_ref().find(|t| t.is_err());
+        assert!(matches!(last, Some(Err(TagIteratorError::UnexpectedEOF { .. }))));
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_window(Cursor::new(data), 10, window_len - 0x100, &[]).unwrap();
+        iter.skip_tags(&[TestSpec::Block(vec![])]);
+        iter.seek_when_skipping();
+        let last = iter.by_ref().find(|t| t.is_err());
+        assert!(matches!(last, Some(Err(TagIteratorError::UnexpectedEOF { tag_id: Some(0xa1), .. }))));
+    }
+
+    #[test]
+    pub fn read_with_positions() {
+        let data = write_clusters();
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)]);
+        let mut positions = iter.with_positions();
+        let clusters: Vec<_> = positions.by_ref()
+            .map(|tag| tag.unwrap())
+            .filter(|(tag, _)| matches!(tag, TestSpec::Cluster(_)))
+            .collect();
+        assert_eq!(3, clusters.len());
+        assert_eq!(positions.get_ref().last_emitted_tag_offset(), 0);
+
+        for (cluster, span) in clusters {
+            assert!(span.data_len.unwrap() > 0x20000);
+            let start = span.offset as u64;
+            let end = start + span.total_len().unwrap() as u64;
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::with_window(Cursor::new(data.clone()), start, end - start, &[TestSpec::Cluster(Master::Start)]).unwrap();
+            assert_eq!(Some(cluster), iter.next().map(|t| t.unwrap()));
+        }
+    }
+
+    #[test]
+    pub fn read_subtrees() {
+        let data = write_clusters();
+        let clusters: Vec<_> = TagIterator::<_, TestSpec>::new(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)])
+            .filter_map(|tag| tag.ok().filter(|tag| matches!(tag, TestSpec::Cluster(_))))
+            .collect();
+        assert_eq!(3, clusters.len());
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let mut subtrees = iter.into_subtree_iter(TestSpec::Cluster(Master::Start).get_id());
+        assert_eq!(clusters, subtrees.by_ref().map(|tag| tag.unwrap()).collect::<Vec<_>>());
+        assert!(subtrees.next().is_none());
+
+        // Errors end up in the subtree stream
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data[..(data.len() - 10)].to_vec()), &[]);
+        let read: Vec<_> = iter.into_subtree_iter(TestSpec::Cluster(Master::Start).get_id()).take(3).collect();
+        assert_eq!(clusters[..2], read[..2].iter().map(|tag| tag.as_ref().unwrap().clone()).collect::<Vec<_>>());
+        assert!(matches!(read[2], Err(TagIteratorError::UnexpectedEOF { .. })));
+    }
+
+    #[test]
+    pub fn report_progress() {
+        let data = write_clusters();
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = progress.clone();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)], 0x100);
+        iter.on_progress(0x10000, move |bytes_read| log.lock().unwrap().push(bytes_read));
+        assert_eq!(data.len() as u64, iter.total_len().unwrap());
+
+        assert!(iter.by_ref().all(|tag| tag.is_ok()));
+        assert_eq!(data.len(), iter.bytes_read());
+        assert_eq!(data.len() as u64, iter.total_len().unwrap());
+
+        // Buffered clusters are reported on while they are read
+        let progress = progress.lock().unwrap();
+        assert!(progress.len() > 3);
+        assert!(progress.windows(2).all(|pair| pair[1] >= pair[0] + 0x10000 || pair[1] == data.len()));
+        assert_eq!(Some(&data.len()), progress.last());
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_window(Cursor::new(data.clone()), 10, 100, &[]).unwrap();
+        iter.next();
+        assert_eq!(100, iter.total_len().unwrap());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    pub fn read_from_mmap() {
+        let data = write_clusters();
+        let path = std::env::temp_dir().join(format!("ebml-iterable-mmap-{}.ebml", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+
+        let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)]).map(|tag| tag.unwrap()).collect();
+        let mut iter: TagIterator<_, TestSpec> = unsafe { TagIterator::from_mmap(&path, &[TestSpec::Cluster(Master::Start)]) }.unwrap();
+        assert_eq!(data.len() as u64, iter.total_len().unwrap());
+        assert_eq!(expected, iter.by_ref().map(|tag| tag.unwrap()).collect::<Vec<_>>());
+
+        let slice_iter: TagSliceIterator<TestSpec> = TagSliceIterator::new(iter.get_ref().get_ref(), &[TestSpec::Cluster(Master::Start)]);
+        assert_eq!(expected, slice_iter.map(|tag| tag.unwrap().into_owned()).collect::<Vec<_>>());
+
+        drop(iter);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(unsafe { TagIterator::<_, TestSpec>::from_mmap(&path, &[]) }, Err(TagIteratorError::ReadError { .. })));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    pub fn scan_clusters_in_parallel() {
+        let cluster_id = TestSpec::Cluster(Master::Start).get_id();
+        for data in [write_clusters(), unknown_size_clusters()] {
+            let mut expected = Vec::new();
+            let mut subtrees = TagIterator::<_, TestSpec>::new(Cursor::new(data.clone()), &[]).into_subtree_iter(cluster_id);
+            while let Some(cluster) = subtrees.next() {
+                expected.push((cluster.unwrap(), subtrees.get_ref().last_emitted_tag_offset()));
+            }
+            assert_eq!(3, expected.len());
+
+            // Offsets are relative to where the source was positioned
+            let mut source = Cursor::new([vec![0xff; 5], data.clone()].concat());
+            source.set_position(5);
+            let scanned = ebml_iterable::scan_clusters_parallel_with(source, cluster_id, |cluster: TestSpec, offset| (cluster, offset)).unwrap();
+            assert_eq!(expected, scanned);
+
+            let path = std::env::temp_dir().join(format!("ebml-iterable-parallel-{}.ebml", std::process::id()));
+            std::fs::write(&path, &data).unwrap();
+            let counts = ebml_iterable::scan_clusters_parallel(&path, cluster_id, |cluster: TestSpec, _| cluster.as_master().unwrap().clone().get_children().len()).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(expected.iter().map(|(cluster, _)| cluster.as_master().unwrap().clone().get_children().len()).collect::<Vec<_>>(), counts);
+        }
+
+        let mut truncated = write_clusters();
+        truncated.truncate(truncated.len() - 10);
+        assert!(matches!(ebml_iterable::scan_clusters_parallel_with(Cursor::new(truncated), cluster_id, |_: TestSpec, offset| offset), Err(TagIteratorError::UnexpectedEOF { .. })));
+    }
+
+    #[test]
+    pub fn seek_to_offsets() {
+        let data = write_clusters();
+        let clusters: Vec<_> = TagIterator::<_, TestSpec>::new(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)])
+            .with_positions()
+            .map(|tag| tag.unwrap())
+            .filter(|(tag, _)| matches!(tag, TestSpec::Cluster(_)))
+            .collect();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)], 0x100);
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+        for index in [2, 0, 1] {
+            let (cluster, span) = &clusters[index];
+            iter.seek_to(span.offset).unwrap();
+            assert_eq!(Some(cluster), iter.next().map(|t| t.unwrap()).as_ref());
+            assert_eq!(*span, iter.last_emitted_tag_span());
+            assert_eq!(Some(TestSpec::TrackType(index as u64)), iter.next().map(|t| t.unwrap()));
+        }
+
+        // Offsets in a window are relative to the window
+        let mut windowed = vec![0xff; 10];
+        windowed.extend(data.iter());
+        let (cluster, span) = &clusters[1];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_window(Cursor::new(windowed), 10, data.len() as u64, &[TestSpec::Cluster(Master::Start)]).unwrap();
+        iter.seek_to(span.offset).unwrap();
+        assert_eq!(Some(cluster), iter.next().map(|t| t.unwrap()).as_ref());
+        let remaining: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::TrackType(1), clusters[2].0.clone(), TestSpec::TrackType(2), TestSpec::Segment(Master::End)], remaining);
+
+        // Forks seek back to their own position
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)]);
+        let mut fork = iter.fork_with(Cursor::new(data)).unwrap();
+        fork.seek_to(clusters[2].1.offset).unwrap();
+        assert_eq!(Some(&clusters[2].0), fork.next().map(|t| t.unwrap()).as_ref());
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Segment(Master::Start)))));
+    }
+
+    #[test]
+    pub fn query_paths() {
+        let data = write_clusters();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(CountingReader { inner: Cursor::new(data.clone()), bytes_read: 0 }, &[], 0x100);
+        iter.seek_when_skipping();
+        let mut query = EbmlQuery::new(iter, "Segment/TrackType");
+        let tags: Vec<TestSpec> = query.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::TrackType(0), TestSpec::TrackType(1), TestSpec::TrackType(2)], tags);
+        assert!(query.get_ref().get_ref().bytes_read < 0x1000, "Clusters should be skipped");
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let tags: Vec<TestSpec> = EbmlQuery::new(iter, "/Segment/0x1f43b675/Count").map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Count(0), TestSpec::Count(1), TestSpec::Count(2)], tags);
+
+        // Masters at the end of the path are emitted in full, whether or not the iterator buffers them
+        let expected: Vec<TestSpec> = (0..3).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i), TestSpec::Block(vec![0; 0x20000])]))).collect();
+        for tags_to_buffer in [vec![], vec![TestSpec::Segment(Master::Start)], vec![TestSpec::Cluster(Master::Start)]] {
+            let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &tags_to_buffer);
+            let tags: Vec<TestSpec> = EbmlQuery::new(iter, "Segment/Cluster").map(|t| t.unwrap()).collect();
+            assert_eq!(expected, tags);
+        }
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert_eq!(0, EbmlQuery::new(iter, "Cluster/Count").count());
+    }
+
+    #[test]
+    pub fn read_document_tree() {
+        let segment = TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![3; 4])])),
+        ]));
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&segment).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let tree = read_tree::<TestSpec, _>(Cursor::new(&data), 4, data.len()).unwrap();
+        assert_eq!(5, tree.len());
+        let root = tree.roots().next().unwrap();
+        assert_eq!(Some(TreeValue::UnsignedInt(2)), root.child(0x1f43b675).and_then(|cluster| cluster.child(0x4100)).map(|count| count.value()));
+        assert_eq!(segment, tree.to_tag(root.node_id()));
+
+        assert!(matches!(read_tree::<TestSpec, _>(Cursor::new(&data), 1, data.len()), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxDepthExceeded { tag_id: 0x1f43b675, max_depth: 1, .. }))));
+        assert!(matches!(read_tree::<TestSpec, _>(Cursor::new(&data), 4, data.len() - 1), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { position: 0, tag_id: 0x18538067, .. }))));
+
+        // Unknown sized masters are limited by their children
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 0x100])]))).unwrap();
+        let data = writer.into_inner().unwrap();
+        assert!(matches!(read_tree::<TestSpec, _>(Cursor::new(&data), 4, 0x80), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MaxTagSizeExceeded { tag_id: 0x1f43b675, .. }))));
+    }
+
+    #[test]
+    pub fn current_path() {
+        let tags = [
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ];
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags.iter() {
+            writer.write(tag).expect("Error writing tag");
+        }
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let mut paths: Vec<Vec<u64>> = Vec::new();
+        while let Some(tag) = iter.next() {
+            tag.unwrap();
+            paths.push(iter.current_path().to_vec());
+            if iter.current_path().len() == 2 {
+                assert_eq!(vec![Some("Segment"), Some("Cluster")], iter.current_path_names());
+            }
+        }
+        assert_eq!(vec![
+            vec![0x18538067],
+            vec![0x18538067],
+            vec![0x18538067, 0x1f43b675],
+            vec![0x18538067, 0x1f43b675],
+            vec![0x18538067],
+            vec![],
+        ], paths);
+
+        // Buffered masters are emitted whole and don't change the path
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[TestSpec::Cluster(Master::Start)]);
+        iter.nth(1).unwrap().unwrap();
+        assert!(matches!(iter.next(), Some(Ok(TestSpec::Cluster(Master::Full(_))))));
+        assert_eq!(&[0x18538067], iter.current_path());
+
+        // Starting partway through a document includes the implied parents
+        let count_start = data.windows(2).position(|w| w == [0x41, 0x00]).unwrap();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data[count_start..].to_vec()), &[]);
+        assert_eq!(Some(TestSpec::Count(1)), iter.next().transpose().unwrap());
+        assert_eq!(&[0x18538067, 0x1f43b675], iter.current_path());
+        assert_eq!(Some(TestSpec::Cluster(Master::End)), iter.next().transpose().unwrap());
+        assert_eq!(&[0x18538067], iter.current_path());
+        assert_eq!(Some(TestSpec::Segment(Master::End)), iter.next().transpose().unwrap());
+        assert!(iter.current_path().is_empty());
+    }
+
+    #[test]
+    pub fn read_with_parents() {
+        let tags = [
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Void(vec![0x00]),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ];
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags.iter() {
+            writer.write(tag).expect("Error writing tag");
+        }
+        let data = writer.into_inner().unwrap();
+        let void_start = data.iter().position(|b| *b == 0xec).unwrap();
+
+        let parents = [TestSpec::Segment(Master::Start), TestSpec::Cluster(Master::Start)];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_parents(Cursor::new(data[void_start..].to_vec()), &parents, &[]);
+        assert_eq!(&[0x18538067, 0x1f43b675], iter.current_path());
+        let read: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(tags[2..].to_vec(), read);
+        assert!(iter.current_path().is_empty());
+
+        // The first tag is checked against the given parents
+        let count_start = data.windows(2).position(|w| w == [0x41, 0x00]).unwrap();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_parents(Cursor::new(data[count_start..].to_vec()), &parents[..1], &[]);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::HierarchyError { found_tag_id: 0x4100, current_parent_id: Some(0x18538067), .. })))));
+    }
+
+    #[test]
+    pub fn write_at_path() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_at_path(&TestSpec::Count(1)).unwrap();
+        writer.write_at_path(&TestSpec::Block(vec![0x01])).unwrap();
+        writer.write_at_path(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write_at_path(&TestSpec::Void(vec![0x00])).unwrap();
+        writer.write_at_path(&TestSpec::Count(2)).unwrap();
+        writer.write_at_path(&TestSpec::TrackType(3)).unwrap();
+        writer.write_at_path(&TestSpec::Ebml(Master::Start)).unwrap();
+        writer.write_at_path(&TestSpec::Ebml(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let expected = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Block(vec![0x01]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Void(vec![0x00]),
+            TestSpec::Count(2),
+            TestSpec::Cluster(Master::End),
+            TestSpec::TrackType(3),
+            TestSpec::Segment(Master::End),
+            TestSpec::Ebml(Master::Start),
+            TestSpec::Ebml(Master::End),
+        ];
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert_eq!(expected, iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn write_master_builder() {
+        let tags = [
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(3),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Block(vec![0x01, 0x02]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])),
+            TestSpec::Segment(Master::End),
+        ];
+        for streaming in [false, true] {
+            let mut writer = TagWriter::new(Vec::new());
+            writer.set_streaming(streaming);
+            writer.master(0x18538067, |segment| {
+                segment.uint(0x83, 3)?;
+                segment.master(0x1f43b675, |cluster| {
+                    cluster.uint(0x4100, 1)?;
+                    cluster.binary(0xa1, &[0x01, 0x02])
+                })?;
+                segment.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])))
+            }).unwrap();
+
+            let mut expected = TagWriter::new(Vec::new());
+            expected.set_streaming(streaming);
+            for tag in tags.iter() {
+                expected.write(tag).unwrap();
+            }
+            assert_eq!(expected.into_inner().unwrap(), writer.into_inner().unwrap());
+        }
+
+        // Partial masters and mismatched types are rejected, but the master is still ended
+        let mut writer = TagWriter::with_spec::<TestSpec>(Vec::new());
+        assert!(matches!(writer.master(0x18538067, |w| w.write(&TestSpec::Cluster(Master::Start))), Err(TagWriterError::UnexpectedTag { tag_id: 0x1f43b675, .. })));
+        assert!(matches!(writer.master(0x18538067, |w| w.write(&TestSpec::Segment(Master::End))), Err(TagWriterError::UnexpectedClosingTag { tag_id: 0x18538067, expected_id: Some(0x18538067) })));
+        assert!(matches!(writer.master(0x18538067, |w| w.utf8(0x83, "video")), Err(TagWriterError::TypeMismatch { .. })));
+        assert!(matches!(writer.master(0x83, |_| Ok(())), Err(TagWriterError::TypeMismatch { .. })));
+        let data = writer.into_inner().unwrap();
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Segment(Master::Start)]);
+        assert_eq!(vec![TestSpec::Segment(Master::Full(vec![])); 3], iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn write_typed_values() {
+        let mut writer = TagWriter::with_spec::<TestSpec>(Vec::new());
+        writer.write(&TestSpec::Root(Master::Start)).unwrap();
+        writer.write_uint(0x4101, 300).unwrap();
+        writer.write_utf8(0x4102, "text").unwrap();
+        writer.write(&TestSpec::Root(Master::End)).unwrap();
+        writer.write_binary(0xec, &[0x00; 3]).unwrap();
+        assert!(matches!(writer.write_utf8(0x4101, "text"), Err(TagWriterError::TypeMismatch { tag_id: 0x4101, expected: Some(TagDataType::UnsignedInt), found: Some(TagDataType::Utf8) })));
+        assert!(matches!(writer.write_float(0xa1, 1.0), Err(TagWriterError::TypeMismatch { tag_id: 0xa1, .. })));
+
+        let mut expected = TagWriter::new(Vec::new());
+        for tag in [TestSpec::Root(Master::Full(vec![TestSpec::Int(300), TestSpec::String("text".to_string())])), TestSpec::Void(vec![0x00; 3])] {
+            expected.write(&tag).unwrap();
+        }
+        assert_eq!(expected.into_inner().unwrap(), writer.into_inner().unwrap());
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_int(0xfb, -2).unwrap();
+        writer.write_float(0x4489, 1.5).unwrap();
+        assert_eq!(&[0xfb, 0x81, 0xfe, 0x44, 0x89, 0x88, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], writer.into_inner().unwrap().as_slice());
+    }
+
+    #[test]
+    pub fn pipe_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0x02])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut source: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let mut dest = TagWriter::new(Vec::new());
+        ebml_iterable::pipe(&mut source, &mut dest, Some).unwrap();
+        let copied = dest.into_inner().unwrap();
+        let read = |data: Vec<u8>| TagIterator::<_, TestSpec>::new(Cursor::new(data), &[]).map(|t| t.unwrap()).collect::<Vec<_>>();
+        assert_eq!(read(data.clone()), read(copied.clone()));
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(copied), &[]);
+        iter.next().unwrap().unwrap();
+        assert_eq!(None, iter.last_emitted_tag_span().data_len);
+
+        // Drop the first cluster and change the track type
+        let mut source: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        let mut dest = TagWriter::new(Vec::new());
+        let mut clusters = 0;
+        ebml_iterable::pipe(&mut source, &mut dest, |tag| match tag {
+            TestSpec::Cluster(Master::Start) => {
+                clusters += 1;
+                if clusters == 1 { None } else { Some(tag) }
+            },
+            TestSpec::TrackType(_) => Some(TestSpec::TrackType(5)),
+            tag => Some(tag),
+        }).unwrap();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(5),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Block(vec![0x02]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read(dest.into_inner().unwrap()));
+    }
+
+    #[test]
+    pub fn copy_with_filter() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_size_width(SizeWidth::Fixed(4)).unwrap();
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::SimpleBlock(vec![0x02])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let copied = copy_ebml(Cursor::new(&data), Vec::new(), |tag| match tag {
+            TestSpec::Block(_) => Decision::Drop,
+            TestSpec::Count(count) => Decision::Replace(TestSpec::Count(count + 10)),
+            _ => Decision::Keep,
+        }).unwrap();
+
+        // Kept tags are copied with their original encoding
+        let track_type = [0x83, 0x10, 0x00, 0x00, 0x01, 0x01];
+        assert!(data.windows(track_type.len()).any(|window| window == track_type));
+        assert!(copied.windows(track_type.len()).any(|window| window == track_type));
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&copied), &[]);
+        iter.next().unwrap().unwrap();
+        assert_eq!(None, iter.last_emitted_tag_span().data_len);
+        assert_eq!(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(11),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(12),
+            TestSpec::SimpleBlock(vec![0x02]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], iter.map(|tag| tag.unwrap()).collect::<Vec<_>>());
+
+        // Dropping a master drops its children
+        let copied = copy_ebml(Cursor::new(&data), Vec::new(), |tag| match tag {
+            TestSpec::Cluster(_) => Decision::Drop,
+            _ => Decision::Keep,
+        }).unwrap();
+        let tags: Vec<TestSpec> = TagIterator::new(Cursor::new(copied), &[]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Segment(Master::End)], tags);
+    }
+
+    fn write_deterministic(tags: &[TestSpec], unknown_size: bool, flush_policy: FlushPolicy) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_deterministic(true);
+        writer.set_size_width(SizeWidth::Fixed(4)).unwrap();
+        writer.set_flush_policy(flush_policy);
+        for tag in tags {
+            if unknown_size && matches!(tag.as_master(), Some(Master::Start)) {
+                writer.write_unknown_size(tag).expect("Error writing tag");
+            } else {
+                writer.write(tag).expect("Error writing tag");
+            }
+        }
+        writer.into_inner().expect("Error finishing writer")
+    }
+
+    #[test]
+    pub fn deterministic_output() {
+        let full = vec![TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0x01; 200])])),
+        ]))];
+        let streamed = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Block(vec![0x01; 200]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ];
+
+        let expected = write_deterministic(&full, false, FlushPolicy::EveryTopLevel);
+        assert_eq!(expected, write_deterministic(&streamed, false, FlushPolicy::Never));
+        assert_eq!(expected, write_deterministic(&streamed, true, FlushPolicy::EveryNBytes(16)));
+
+        // Every size takes 4 bytes: segment header, track type, cluster header, ...
+        assert_eq!(&[0x18, 0x53, 0x80, 0x67, 0x10, 0x00, 0x00, 0xe2, 0x83, 0x10, 0x00, 0x00, 0x01, 0x01], &expected[..14]);
+
+        let read: Vec<TestSpec> = TagIterator::new(Cursor::new(expected), &[TestSpec::Segment(Master::Start)]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(full, read);
+    }
+
+    #[test]
+    pub fn fixed_size_width() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_size_width(SizeWidth::Fixed(1)).unwrap();
+        writer.write(&TestSpec::Void(vec![0x01; 126])).expect("Error writing tag");
+        assert!(matches!(writer.write(&TestSpec::Void(vec![0x01; 127])), Err(TagWriterError::TagSizeError(_))));
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        let data = writer.into_inner().expect("Error finishing writer");
+        assert_eq!(1 + 1 + 126 + 4 + 1, data.len());
+        assert_eq!(&[0x18, 0x53, 0x80, 0x67, 0xff], &data[128..]);
+    }
+
+    #[test]
+    pub fn fixed_int_width() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write_with_options(&TestSpec::TrackType(1), WriteOptions { int_width: Some(8), ..Default::default() }).expect("Error writing tag");
+        assert!(matches!(writer.write_with_options(&TestSpec::TrackType(0x1_0000), WriteOptions { int_width: Some(2), ..Default::default() }), Err(TagWriterError::TagSizeError(_))));
+        assert!(matches!(writer.write_with_options(&TestSpec::TrackType(1), WriteOptions { int_width: Some(9), ..Default::default() }), Err(TagWriterError::TagSizeError(_))));
+        assert!(matches!(writer.write_with_options(&TestSpec::TrackType(1), WriteOptions { size_width: Some(SizeWidth::Fixed(0)), ..Default::default() }), Err(TagWriterError::TagSizeError(_))));
+        writer.write_with_options(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x1234)])), WriteOptions { int_width: Some(3), ..Default::default() }).expect("Error writing tag");
+        writer.write(&TestSpec::TrackType(2)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        let data = writer.into_inner().expect("Error finishing writer");
+
+        assert_eq!(&[0x83, 0x88, 0, 0, 0, 0, 0, 0, 0, 0x01], &data[5..15]);
+        assert_eq!(&[0x1f, 0x43, 0xb6, 0x75, 0x86, 0x41, 0x00, 0x83, 0x00, 0x12, 0x34], &data[15..26]);
+        assert_eq!(&[0x83, 0x81, 0x02], &data[26..]);
+
+        let read: Vec<TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Cluster(Master::Start)]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x1234)])),
+            TestSpec::TrackType(2),
+            TestSpec::Segment(Master::End),
+        ], read);
+    }
+
+    #[test]
+    pub fn preserve_size_widths() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_with_options(&TestSpec::Segment(Master::Start), WriteOptions { size_width: Some(SizeWidth::Fixed(8)), ..Default::default() }).expect("Error writing tag");
+        writer.write(&TestSpec::TrackType(1)).expect("Error writing tag");
+        writer.write_with_options(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(5), TestSpec::Block(vec![1, 2, 3])])), WriteOptions { size_width: Some(SizeWidth::Fixed(3)), ..Default::default() }).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        let data = writer.into_inner().expect("Error finishing writer");
+        assert_eq!(&[0x18, 0x53, 0x80, 0x67, 0x01, 0, 0, 0, 0, 0, 0, 0x17], &data[..12]);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        let mut writer = TagWriter::new(Vec::new());
+        while let Some(tag) = iter.next() {
+            let tag = match tag.expect("Error reading tag") {
+                TestSpec::TrackType(_) => TestSpec::TrackType(2),
+                tag => tag,
+            };
+            writer.write_with_options(&tag, WriteOptions { size_width: Some(iter.last_emitted_tag_span().size_width()), ..Default::default() }).expect("Error writing tag");
+        }
+        let rewritten = writer.into_inner().expect("Error finishing writer");
+        assert_eq!(data.len(), rewritten.len());
+        assert_eq!(data[..13], rewritten[..13]);
+        assert_eq!(0x02, rewritten[14]);
+        assert_eq!(data[15..], rewritten[15..]);
+    }
+
+    #[test]
+    pub fn keep_encoding_of_borrowed_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Start)).expect("Error writing tag");
+        writer.write_with_options(&TestSpec::Count(1), WriteOptions { int_width: Some(2), ..Default::default() }).expect("Error writing tag");
+        writer.write_encoded(&TestSpec::Block(vec![0x01, 0x02]), &[0xa1, 0x40, 0x02, 0x01, 0x02]).expect("Error writing tag");
+        writer.write(&TestSpec::Block(vec![0x03])).expect("Error writing tag");
+        writer.write(&TestSpec::Count(2)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::End)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        let data = writer.into_inner().expect("Error finishing writer");
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.set_keep_encoding(true);
+        let mut encodings = Vec::new();
+        for borrowed in [false, false, false, true, true, false] {
+            if borrowed {
+                assert!(matches!(iter.next_borrowed(), Some(Ok(BorrowedTag::Binary { .. }))));
+            } else {
+                iter.next().expect("Tag should be present").expect("Error reading tag");
+            }
+            let span = iter.last_emitted_tag_span();
+            encodings.push((span.offset, iter.last_emitted_tag_encoding().map(<[u8]>::to_vec)));
+        }
+        assert_eq!(vec![
+            (0, None),
+            (5, None),
+            (10, Some(vec![0x41, 0x00, 0x82, 0x00, 0x01])),
+            (15, Some(vec![0xa1, 0x40, 0x02, 0x01, 0x02])),
+            (20, Some(vec![0xa1, 0x81, 0x03])),
+            (23, Some(vec![0x41, 0x00, 0x81, 0x02])),
+        ], encodings);
+    }
+
+    #[test]
+    pub fn copy_encoded_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write_with_options(&TestSpec::TrackType(1), WriteOptions { int_width: Some(4), ..Default::default() }).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Start)).expect("Error writing tag");
+        writer.write_encoded(&TestSpec::Block(vec![0x01, 0x02]), &[0xa1, 0x40, 0x02, 0x01, 0x02]).expect("Error writing tag");
+        writer.write(&TestSpec::Count(5)).expect("Error writing tag");
+        let mut encoded = vec![0xa3, 0x10, 0x00, 0x00, 0x40];
+        encoded.extend_from_slice(&[0x07; 0x40]);
+        writer.write_encoded(&TestSpec::SimpleBlock(vec![0x07; 0x40]), &encoded).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::End)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        let data = writer.into_inner().expect("Error finishing writer");
+
+        // A small buffer makes sure encodings survive the data being moved around in the buffer
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(Cursor::new(&data), &[], 16);
+        iter.set_keep_encoding(true);
+        let mut copy = TagWriter::new(Vec::new());
+        let mut reencoded = TagWriter::new(Vec::new());
+        while let Some(tag) = iter.next() {
+            let tag = tag.expect("Error reading tag");
+            match iter.last_emitted_tag_encoding() {
+                Some(encoding) => {
+                    assert!(tag.as_master().is_none());
+                    copy.write_encoded(&tag, encoding).expect("Error writing tag");
+                },
+                None => {
+                    assert!(tag.as_master().is_some());
+                    copy.write(&tag).expect("Error writing tag");
+                },
+            }
+            reencoded.write(&tag).expect("Error writing tag");
+        }
+        assert_eq!(data, copy.into_inner().expect("Error finishing writer"));
+        assert_ne!(data, reencoded.into_inner().expect("Error finishing writer"));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        assert!(matches!(writer.write_encoded(&TestSpec::TrackType(1), &[0x83, 0x82, 0x01]), Err(TagWriterError::TagSizeError(_))));
+        assert!(matches!(writer.write_encoded(&TestSpec::TrackType(1), &[0xa1, 0x81, 0x01]), Err(TagWriterError::TagSizeError(_))));
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+    }
+
+    #[test]
+    pub fn invalid_size_width() {
+        let mut writer = TagWriter::new(Vec::new());
+        assert!(matches!(writer.set_size_width(SizeWidth::Fixed(9)), Err(TagWriterError::TagSizeError(_))));
+        assert!(matches!(writer.set_size_width(SizeWidth::Fixed(0)), Err(TagWriterError::TagSizeError(_))));
+
+        // The previous width is kept
+        writer.write(&TestSpec::Root(Master::Full(Vec::new()))).unwrap();
+        assert_eq!(vec![0x81, 0x80], writer.into_inner().unwrap());
+    }
+
+    #[test]
+    pub fn slice_writer_matches_tag_writer() {
+        let tags = vec![
+            TestSpec::Ebml(Master::Full(vec![])),
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x1234),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x1_0000), TestSpec::Block(vec![0x01; 200])])),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::CueRefCluster(5),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+            TestSpec::RawTag(0x4111, vec![0x02, 0x03]),
+        ];
+        let mut writer = TagWriter::new(Vec::new());
+        let mut buffer = [0u8; 512];
+        let mut slice_writer = SliceWriter::new(&mut buffer);
+        for tag in tags.iter() {
+            writer.write(tag).expect("Error writing tag");
+            slice_writer.write(tag).expect("Error writing tag");
+        }
+        writer.write_unknown_size(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        slice_writer.write_unknown_size(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::TrackType(1)).expect("Error writing tag");
+        slice_writer.write(&TestSpec::TrackType(1)).expect("Error writing tag");
+
+        let expected = writer.into_inner().expect("Error finishing writer");
+        assert_eq!(expected.len(), slice_writer.len());
+        assert_eq!(&expected[..], slice_writer.finish().expect("Error finishing writer"));
+    }
+
+    #[test]
+    pub fn slice_writer_errors() {
+        let mut buffer = [0u8; 32];
+        let mut writer = SliceWriter::new(&mut buffer);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        let len = writer.len();
+
+        let too_big = TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 10])]));
+        assert!(matches!(writer.write(&too_big), Err(TagWriterError::BufferOverflow { needed: 12, remaining: 4 })));
+        assert_eq!(len, writer.len());
+        assert!(matches!(writer.write(&TestSpec::Count(1)), Err(TagWriterError::UnexpectedTag { .. })));
+        assert!(matches!(writer.write(&TestSpec::Cluster(Master::End)), Err(TagWriterError::UnexpectedClosingTag { tag_id: 0x1f43b675, expected_id: Some(0x18538067) })));
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).expect("Error writing tag");
+
+        let data = writer.finish().expect("Error finishing writer");
+        let tags: Vec<TestSpec> = TagIterator::new(&data[..], &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))]))], tags);
+    }
+}
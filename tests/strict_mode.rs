@@ -0,0 +1,140 @@
+#[cfg(feature = "derive-spec")]
+pub mod strict_mode {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Strict {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1f43b675)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment/Cluster)]
+        #[mandatory]
+        Timestamp,
+
+        #[id(0xa3)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Segment/Cluster)]
+        SimpleBlock,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Segment)]
+        #[mandatory]
+        Title,
+
+        #[id(0x4101)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Segment)]
+        #[ascii]
+        Language,
+    }
+
+    fn write(tags: &[Strict]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn incomplete_document() -> Vec<u8> {
+        write(&[Strict::Segment(Master::Full(vec![
+            Strict::Title(String::from("strict")),
+            Strict::Cluster(Master::Full(vec![Strict::SimpleBlock(vec![0x01])])),
+        ]))])
+    }
+
+    #[test]
+    pub fn derive_mandatory_children() {
+        assert_eq!(&[0xe7], Strict::get_mandatory_children(0x1f43b675));
+        assert_eq!(&[0x4100], Strict::get_mandatory_children(0x18538067));
+        assert!(Strict::get_mandatory_children(0xe7).is_empty());
+    }
+
+    #[test]
+    pub fn derive_ascii_strings() {
+        assert!(Strict::is_ascii_string(0x4101));
+        assert!(!Strict::is_ascii_string(0x4100));
+        assert!(!Strict::is_ascii_string(0xa3));
+    }
+
+    #[test]
+    pub fn lenient_by_default() {
+        let iter: TagIterator<_, Strict> = TagIterator::new(Cursor::new(incomplete_document()), &[]);
+        assert!(iter.into_iter().all(|tag| tag.is_ok()));
+    }
+
+    #[test]
+    pub fn missing_child_after_master_end() {
+        let mut iter: TagIterator<_, Strict> = TagIterator::new(Cursor::new(incomplete_document()), &[]);
+        iter.set_strict_mode(true);
+
+        assert_eq!(Strict::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(Strict::Title(String::from("strict")), iter.next().unwrap().unwrap());
+        assert_eq!(Strict::Cluster(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(Strict::SimpleBlock(vec![0x01]), iter.next().unwrap().unwrap());
+        assert_eq!(Strict::Cluster(Master::End), iter.next().unwrap().unwrap());
+        match iter.next().unwrap() {
+            Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MissingChildElement { position, tag_id, missing_tag_ids })) => {
+                assert_eq!(14, position);
+                assert_eq!(0x1f43b675, tag_id);
+                assert_eq!(vec![0xe7], missing_tag_ids);
+            },
+            other => panic!("expected a missing child error, got {:?}", other),
+        }
+        assert_eq!(Strict::Segment(Master::End), iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn missing_child_in_buffered_master() {
+        let mut iter: TagIterator<_, Strict> = TagIterator::new(Cursor::new(incomplete_document()), &[Strict::Segment(Master::Start)]);
+        iter.set_strict_mode(true);
+
+        assert!(matches!(iter.next().unwrap(), Ok(Strict::Segment(Master::Full(_)))));
+        assert!(matches!(iter.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::MissingChildElement { tag_id: 0x1f43b675, .. }))));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn complete_document_passes() {
+        let data = write(&[Strict::Segment(Master::Full(vec![
+            Strict::Cluster(Master::Full(vec![Strict::Timestamp(0), Strict::SimpleBlock(vec![0x01])])),
+            Strict::Title(String::from("strict")),
+        ]))]);
+        let mut iter: TagIterator<_, Strict> = TagIterator::new(Cursor::new(data), &[]);
+        iter.set_strict_mode(true);
+        assert!(iter.all(|tag| tag.is_ok()));
+    }
+
+    #[test]
+    pub fn strict_mode_ignores_allowed_errors() {
+        let data = write(&[Strict::Segment(Master::Full(vec![
+            Strict::Title(String::from("strict")),
+            Strict::RawTag(0x4200, vec![0x01]),
+        ]))]);
+
+        let mut iter: TagIterator<_, Strict> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        assert!(iter.into_iter().all(|tag| tag.is_ok()));
+
+        let mut iter: TagIterator<_, Strict> = TagIterator::new(Cursor::new(data), &[]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        iter.set_strict_mode(true);
+        assert!(iter.any(|tag| matches!(tag, Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagId { tag_id: 0x4200, .. })))));
+    }
+}
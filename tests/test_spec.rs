@@ -18,6 +18,8 @@
 //     }
 // )
 
+#![allow(clippy::match_single_binding)]
+
 use ebml_iterable::specs::TagDataType;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -95,6 +97,26 @@ impl ebml_iterable::specs::EbmlSpecification<TestSpec> for TestSpec {
             _ => &[],
         }
     }
+    fn get_tag_name(id: u64) -> Option<&'static str> {
+        match id {
+            129u64 => Some("Root"),
+            16641u64 => Some("Int"),
+            16642u64 => Some("String"),
+            16643u64 => Some("Parent"),
+            2163457u64 => Some("Child"),
+            440786851u64 => Some("Ebml"),
+            408125543u64 => Some("Segment"),
+            131u64 => Some("TrackType"),
+            524531317u64 => Some("Cluster"),
+            151u64 => Some("CueRefCluster"),
+            16640u64 => Some("Count"),
+            161u64 => Some("Block"),
+            163u64 => Some("SimpleBlock"),
+            191u64 => Some("Crc32"),
+            236u64 => Some("Void"),
+            _ => None,
+        }
+    }
     fn get_unsigned_int_tag(id: u64, data: u64) -> Option<TestSpec> {
         match id {
             16641u64 => Some(TestSpec::Int(data)),
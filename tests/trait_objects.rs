@@ -0,0 +1,68 @@
+mod test_spec;
+
+pub mod trait_objects {
+    use std::io::{Cursor, Read, Write};
+
+    use ebml_iterable::iterator::ReadSeek;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::stats::analyze;
+    use ebml_iterable::writer::WriteSeek;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 0x100])])),
+            TestSpec::TrackType(2),
+            TestSpec::Segment(Master::End),
+        ]
+    }
+
+    fn write(writer: &mut TagWriter<Box<dyn Write + '_>>) {
+        for tag in tags() {
+            writer.write(&tag).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    #[test]
+    pub fn boxed_writer_and_iterator() {
+        let mut data = Vec::new();
+        let mut writer = TagWriter::boxed(&mut data);
+        write(&mut writer);
+        drop(writer);
+
+        let sources: Vec<Box<dyn Read>> = vec![Box::new(Cursor::new(data.clone())), Box::new(&data[..])];
+        for source in sources {
+            let iter: TagIterator<Box<dyn Read>, TestSpec> = TagIterator::new(source, &[TestSpec::Cluster(Master::Start)]);
+            assert_eq!(tags(), iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+        }
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::boxed(&data[..], &[TestSpec::Cluster(Master::Start)]);
+        assert_eq!(tags(), iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+
+        let source: &mut dyn Read = &mut &data[..];
+        assert_eq!(data.len() as u64, analyze::<TestSpec, _>(source, 0).unwrap().total_bytes);
+    }
+
+    #[test]
+    pub fn seekable_trait_objects() {
+        let mut dest = Cursor::new(Vec::new());
+        let boxed: Box<dyn WriteSeek + '_> = Box::new(&mut dest);
+        let mut writer = TagWriter::new(boxed);
+        for tag in tags() {
+            writer.write(&tag).unwrap();
+        }
+        writer.patch_doc_type_versions().unwrap();
+        drop(writer);
+
+        let source: Box<dyn ReadSeek> = Box::new(Cursor::new(dest.into_inner()));
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(source, &[]);
+        iter.skip_tags(&[TestSpec::Cluster(Master::Start)]);
+        iter.seek_when_skipping();
+        let expected = vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(2), TestSpec::Segment(Master::End)];
+        assert_eq!(expected, iter.map(|t| t.unwrap()).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(feature = "derive-spec")]
+pub mod validator {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::validate::{Validator, ValueRange, Violation, ViolationKind};
+    use ebml_iterable::TagWriter;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Checked {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1f43b675)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment/Cluster)]
+        #[mandatory]
+        Timestamp,
+
+        #[id(0xa3)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Segment/Cluster)]
+        SimpleBlock,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Segment)]
+        Title,
+
+        #[id(0x4489)]
+        #[data_type(TagDataType::Float)]
+        #[doc_path(Segment)]
+        Duration,
+    }
+
+    fn write(tags: &[Checked]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn conforming_document() {
+        let data = write(&[Checked::Segment(Master::Full(vec![
+            Checked::Title(String::from("valid")),
+            Checked::Cluster(Master::Full(vec![Checked::Timestamp(0), Checked::SimpleBlock(vec![0x01])])),
+        ]))]);
+        assert!(Validator::<Checked>::new().validate(Cursor::new(data)).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn reports_every_violation() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&Checked::Segment(Master::Start)).unwrap();
+        // A cluster holding a SimpleBlock and a Title, which the writer wouldn't allow
+        writer.write_raw(0x1f43b675, &[&[0xa3, 0x81, 0x01, 0x41, 0x00, 0x89][..], b"misplaced"].concat()).unwrap();
+        writer.write(&Checked::RawTag(0x4200, vec![0x01])).unwrap();
+        writer.write(&Checked::Cluster(Master::Full(vec![Checked::Timestamp(0)]))).unwrap();
+        writer.write(&Checked::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let violations = Validator::<Checked>::new().validate(Cursor::new(data)).unwrap();
+        assert_eq!(vec![
+            Violation { offset: 13, tag_id: 0x4100, kind: ViolationKind::WrongParent { parent: Some(0x1f43b675) } },
+            Violation { offset: 5, tag_id: 0x1f43b675, kind: ViolationKind::MissingChild { child_id: 0xe7 } },
+            Violation { offset: 25, tag_id: 0x4200, kind: ViolationKind::UnknownId },
+        ], violations);
+        assert_eq!("Tag [0x1f43b675] at position 5 is missing mandatory child [0xe7]", violations[1].to_string());
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_raw(0x1f43b675, &[0xe7, 0x81, 0x00]).unwrap();
+        let data = writer.into_inner().unwrap();
+        let violations = Validator::<Checked>::new().validate(Cursor::new(data)).unwrap();
+        assert_eq!(vec![Violation { offset: 0, tag_id: 0x1f43b675, kind: ViolationKind::WrongParent { parent: None } }], violations);
+    }
+
+    #[test]
+    pub fn custom_rules() {
+        let data = write(&[Checked::Segment(Master::Full(vec![
+            Checked::Title(String::from("first")),
+            Checked::Title(String::from("second")),
+            Checked::Duration(-1.0),
+            Checked::Cluster(Master::Full(vec![Checked::Timestamp(5), Checked::SimpleBlock(vec![])])),
+        ]))]);
+
+        let mut validator = Validator::<Checked>::new();
+        validator.set_max_occurs(0x4100, 1);
+        validator.set_range(0x4489, ValueRange::Float(0.0..=f64::MAX));
+        validator.set_range(0xe7, ValueRange::Unsigned(0..=4));
+        validator.set_range(0xa3, ValueRange::Length(1..=usize::MAX));
+        // Ranges for a different data type are ignored
+        validator.set_range(0x4100, ValueRange::Signed(0..=0));
+
+        let violations = validator.validate(Cursor::new(data)).unwrap();
+        let kinds: Vec<(u64, ViolationKind)> = violations.into_iter().map(|violation| (violation.tag_id, violation.kind)).collect();
+        assert_eq!(vec![
+            (0x4100, ViolationKind::TooManyOccurrences { max: 1 }),
+            (0x4489, ViolationKind::OutOfRange { value: String::from("-1") }),
+            (0xe7, ViolationKind::OutOfRange { value: String::from("5") }),
+            (0xa3, ViolationKind::OutOfRange { value: String::from("0 bytes") }),
+        ], kinds);
+    }
+
+    #[test]
+    pub fn unreadable_document() {
+        let mut data = write(&[Checked::Segment(Master::Full(vec![Checked::Title(String::from("truncated"))]))]);
+        data.truncate(data.len() - 2);
+        assert!(matches!(Validator::<Checked>::new().validate(Cursor::new(data)), Err(TagIteratorError::UnexpectedEOF { .. })));
+    }
+}